@@ -3,23 +3,40 @@
 //! Administrative command-line interface for managing Aspens Market Stacks  configuration.
 //! Requires authentication via EIP-712 signature to perform admin operations.
 
-use aspens::commands::admin::{self, Chain, CreateInstanceParams, SetMarketParams, Token};
+mod session;
+
+use aspens::chain::tx::TxType;
+use aspens::commands::admin::{
+    self, Chain, CreateInstanceParams, DeployJournal, PendingDeploy, SetMarketParams, Token,
+    TokenRef,
+};
 use aspens::commands::auth;
 use aspens::commands::config;
 use aspens::commands::trading::balance;
-use aspens::{AspensClient, AsyncExecutor, DirectExecutor};
-use aspens_cliutil::BinaryContext;
+#[cfg(feature = "keychain")]
+use aspens::keychain;
+use aspens::profiles::{Profile, SignerSource};
+use aspens::{AspensClient, AsyncExecutor, ConfirmationPolicy, DirectExecutor};
+use aspens_cliutil::{
+    BinaryContext, ErrorFormat, parse_address_arg, parse_bps_arg, parse_duration_arg,
+};
 use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
 use eyre::Result;
+use session::{AdminKey, Session};
 use std::collections::HashMap;
 use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::info;
 use tracing_subscriber::FmtSubscriber;
 use tracing_subscriber::filter::LevelFilter;
 use url::Url;
 
+/// How long before expiry `deploy-contract` warns that the admin JWT may
+/// run out mid-deploy.
+const DEPLOY_JWT_MARGIN: Duration = Duration::from_secs(10 * 60);
+
 /// Format a Unix timestamp as a human-readable datetime string
 fn format_expiry(timestamp: u64) -> String {
     DateTime::<Utc>::from_timestamp(timestamp as i64, 0)
@@ -27,9 +44,49 @@ fn format_expiry(timestamp: u64) -> String {
         .unwrap_or_else(|| format!("{} (invalid timestamp)", timestamp))
 }
 
-/// Local thin wrapper over [`aspens_cliutil::format_error`].
-fn format_error(err: &eyre::Report, context: &str) -> String {
-    aspens_cliutil::format_error(err, context, &BinaryContext::ADMIN)
+/// Clap value parser for `--window`.
+fn parse_trading_window(s: &str) -> Result<config::config_pb::TradingWindow, String> {
+    s.parse().map_err(|e: eyre::Report| e.to_string())
+}
+
+/// Clap value parser for `--block`.
+fn parse_market_block(s: &str) -> Result<config::config_pb::MarketBlock, String> {
+    s.parse().map_err(|e: eyre::Report| e.to_string())
+}
+
+/// Local thin wrapper over [`aspens_cliutil::user_error`].
+fn user_error(err: impl Into<eyre::Report>, context: &str) -> eyre::Report {
+    aspens_cliutil::user_error(err, context, &BinaryContext::ADMIN)
+}
+
+/// The admin key as hex: the one unlocked from `--keystore` or
+/// `ADMIN_KEYSTORE` if given, otherwise `ADMIN_PRIVKEY`.
+fn admin_privkey(client: &AspensClient) -> Result<Option<String>> {
+    let unlocked;
+    let wallet = match client.keystore_wallet() {
+        Some(wallet) => wallet,
+        None if client
+            .get_env("ADMIN_KEYSTORE")
+            .is_some_and(|path| !path.is_empty()) =>
+        {
+            unlocked = aspens::load_admin_wallet(aspens::CurveType::Secp256k1)?;
+            &unlocked
+        }
+        None => return Ok(client.get_env("ADMIN_PRIVKEY").cloned()),
+    };
+    Ok(wallet.as_evm().map(|signer| hex::encode(signer.to_bytes())))
+}
+
+/// Clap value parser for `--hd-path`.
+#[cfg(feature = "ledger")]
+fn parse_hd_path_arg(s: &str) -> Result<aspens::ledger::HDPath, String> {
+    aspens::ledger::parse_hd_path(s).map_err(|e| e.to_string())
+}
+
+/// [`parse_bps_arg`] narrowed to the contract's uint16 fee field.
+fn parse_fee_bps(s: &str) -> Result<u16, String> {
+    let bps = parse_bps_arg(s)?;
+    u16::try_from(bps).map_err(|_| format!("fee of {bps} bps exceeds the uint16 maximum (65535)"))
 }
 
 #[derive(Debug, Parser)]
@@ -45,10 +102,70 @@ struct Cli {
     #[arg(short = 'e', long = "env-file", global = true)]
     env_file: Option<String>,
 
+    /// Profile from the config file (~/.config/aspens/config.toml) whose
+    /// stack URL, env file and signer to default to; defaults to
+    /// ASPENS_PROFILE, then the config file's default
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
     /// JWT token for authentication (can also be set via ASPENS_JWT in .env file)
     #[arg(long, global = true)]
     jwt: Option<String>,
 
+    /// How to report a failure on stderr: "text" (default) or "json" (one
+    /// object with kind, exit_code and message). The exit code names the
+    /// kind either way: 2 connection, 3 auth, 4 not found, 5 insufficient
+    /// balance, 6 invalid input, 7 protocol, 8 chain, 9 risk limit, 1 other
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        env = "ASPENS_ERROR_FORMAT",
+        default_value = "text"
+    )]
+    error_format: ErrorFormat,
+
+    /// Encrypted keystore file (Web3 Secret Storage JSON) holding the admin
+    /// key, used instead of ADMIN_PRIVKEY. The passphrase is prompted for
+    /// unless ASPENS_KEYSTORE_PASSPHRASE is set
+    #[arg(long, global = true, value_name = "PATH", env = "ADMIN_KEYSTORE")]
+    keystore: Option<std::path::PathBuf>,
+
+    /// Sign admin logins on a Ledger device instead of with ADMIN_PRIVKEY,
+    /// confirming each on the device. Deploying still needs a local key
+    #[cfg(feature = "ledger")]
+    #[arg(long, global = true)]
+    ledger: bool,
+
+    /// Ledger derivation path: live:N (Ledger Live account N), legacy:N or
+    /// a full m/44'/60'/... path
+    #[cfg(feature = "ledger")]
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        default_value = "live:0",
+        value_parser = parse_hd_path_arg
+    )]
+    hd_path: aspens::ledger::HDPath,
+
+    /// AWS KMS key (ID, alias or ARN) holding the admin key, used instead
+    /// of ADMIN_PRIVKEY for logins and deployments. Credentials come from
+    /// the standard AWS provider chain
+    #[cfg(feature = "kms")]
+    #[arg(long, global = true, value_name = "KEY", env = "ADMIN_KMS_KEY_ID")]
+    kms_key_id: Option<String>,
+
+    /// Blocks deploy-contract waits for once createInstance is mined,
+    /// counting its own block (default 1)
+    #[arg(long, global = true, value_name = "N", env = "ASPENS_CONFIRMATIONS")]
+    confirmations: Option<u64>,
+
+    /// How long deploy-contract waits for those confirmations, e.g. `10m`
+    /// (default 2m; 0 waits indefinitely)
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration_arg)]
+    confirmation_timeout: Option<Duration>,
+
     #[command(flatten)]
     verbose: clap_verbosity::Verbosity,
 
@@ -64,7 +181,7 @@ enum Commands {
     /// Initialize the first admin (only works on fresh stack)
     InitAdmin {
         /// Ethereum address to set as initial admin
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         address: String,
     },
 
@@ -73,14 +190,26 @@ enum Commands {
         /// Chain ID for EIP-712 domain (default: 1)
         #[arg(long, default_value = "1")]
         chain_id: u64,
+
+        /// Save the token in the OS keychain for this stack, so later
+        /// commands use it without ASPENS_JWT until it expires
+        #[cfg(feature = "keychain")]
+        #[arg(long)]
+        save: bool,
     },
 
+    /// Remove the JWT saved by 'login --save' for this stack from the OS
+    /// keychain
+    #[cfg(feature = "keychain")]
+    Logout,
+
     // ========================================================================
     // Admin Management Commands
     // ========================================================================
     /// Update the admin address
     UpdateAdmin {
         /// New admin Ethereum address
+        #[arg(value_parser = parse_address_arg)]
         address: String,
     },
 
@@ -110,7 +239,7 @@ enum Commands {
         rpc_url: String,
 
         /// Factory contract address
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         factory_address: String,
 
         /// Optional block explorer URL
@@ -127,7 +256,7 @@ enum Commands {
         /// SDK fails with "invalid instance_signer_address". Supply it
         /// explicitly to break that asymmetry; query via
         /// `aspens-cli signer-public-key --chain-network <network>`.
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         instance_signer_address: Option<String>,
     },
 
@@ -155,7 +284,7 @@ enum Commands {
         symbol: String,
 
         /// Token contract address
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         address: String,
 
         /// Token decimals
@@ -200,11 +329,11 @@ enum Commands {
         quote_symbol: String,
 
         /// Base token address
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         base_address: String,
 
         /// Quote token address
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         quote_address: String,
 
         /// Base token decimals
@@ -218,6 +347,33 @@ enum Commands {
         /// Pair decimals for trading
         #[arg(long)]
         pair_decimals: i32,
+
+        /// Allow editing the decimals or token addresses of an existing
+        /// market, which can invalidate its resting orders
+        #[arg(long)]
+        confirm_changes: bool,
+    },
+
+    /// Create a market between two tokens already registered on the stack
+    ///
+    /// Token addresses and decimals are resolved from the stack
+    /// configuration, so only the two tokens need to be named.
+    CreateMarket {
+        /// Base token as SYMBOL@network (e.g., "USDC@base-sepolia")
+        #[arg(long)]
+        base: TokenRef,
+
+        /// Quote token as SYMBOL@network (e.g., "USDC@op-sepolia")
+        #[arg(long)]
+        quote: TokenRef,
+
+        /// Pair decimals for trading (defaults to the smaller of the two token decimals)
+        #[arg(long)]
+        pair_decimals: Option<i32>,
+
+        /// Print the resolved market without submitting it
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Delete a market
@@ -226,6 +382,23 @@ enum Commands {
         market_id: String,
     },
 
+    /// Set a market's trading hours. With no --window or --block the
+    /// schedule is cleared and the market is always open.
+    SetMarketSchedule {
+        /// Market ID to update
+        market_id: String,
+
+        /// Recurring UTC window the market is open in, e.g.
+        /// `mon-fri 13:30-20:00` (repeatable)
+        #[arg(long = "window", value_parser = parse_trading_window)]
+        windows: Vec<config::config_pb::TradingWindow>,
+
+        /// One-off closure as `START-END[:REASON]` in unix seconds
+        /// (repeatable)
+        #[arg(long = "block", value_parser = parse_market_block)]
+        blocks: Vec<config::config_pb::MarketBlock>,
+    },
+
     // ========================================================================
     // Contract Commands
     // ========================================================================
@@ -234,16 +407,49 @@ enum Commands {
         /// Network to deploy on (e.g., "base-sepolia")
         network: String,
 
-        /// Fee percentage for the trading instance (uint16: 0-65535)
-        /// Represents the fee in basis points (e.g., 100 = 1%)
-        #[arg(long, default_value = "0")]
+        /// Fee for the trading instance in basis points (uint16: 0-65535),
+        /// e.g. `100`, `100bps` or `1%`
+        #[arg(long, default_value = "0", value_parser = parse_fee_bps)]
         fees: u16,
+
+        /// Skip to confirmation and registration of an already-broadcast
+        /// createInstance transaction (EVM only)
+        #[arg(long, value_name = "TX_HASH")]
+        resume: Option<String>,
+
+        /// Top up the admin account's gas from this key (hex) before
+        /// broadcasting; also read from PAYER_PRIVKEY in .env
+        #[arg(long, value_name = "HEX", conflicts_with = "offline_sign")]
+        payer_privkey: Option<String>,
+
+        /// Sign the createInstance transaction without broadcasting it, for
+        /// an ops relay to send; requires --out
+        #[arg(long, requires = "out", conflicts_with = "resume")]
+        offline_sign: bool,
+
+        /// File --offline-sign writes the signed transaction to, as hex
+        #[arg(long, value_name = "FILE", requires = "offline_sign")]
+        out: Option<std::path::PathBuf>,
+
+        /// Write the createInstance transaction unsigned to this file, to sign
+        /// on an offline machine with `aspens-cli sign --key-env ADMIN_PRIVKEY`
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["offline_sign", "resume", "payer_privkey"]
+        )]
+        unsigned_out: Option<std::path::PathBuf>,
+
+        /// Admin address the --unsigned-out transaction is from; defaults to
+        /// the ADMIN_PRIVKEY address
+        #[arg(long, value_parser = parse_address_arg, requires = "unsigned_out")]
+        from: Option<String>,
     },
 
     /// Set a trade contract on a chain
     SetTradeContract {
         /// Contract address
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         address: String,
 
         /// Chain network to associate with (e.g., "base-sepolia")
@@ -259,11 +465,11 @@ enum Commands {
         chain_network: String,
 
         /// Operator-fee recipient address (0x-hex EVM / base58 Solana)
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         recipient: String,
 
-        /// Operator fee in basis points
-        #[arg(long)]
+        /// Operator fee in basis points, e.g. `25`, `25bps` or `0.25%`
+        #[arg(long, value_parser = parse_bps_arg)]
         bps: u32,
     },
 
@@ -275,7 +481,7 @@ enum Commands {
         chain_network: String,
 
         /// The new operator_admin address (0x-hex EVM / base58 Solana)
-        #[arg(long)]
+        #[arg(long, value_parser = parse_address_arg)]
         new_admin: String,
     },
 
@@ -285,6 +491,27 @@ enum Commands {
         chain_network: String,
     },
 
+    // ========================================================================
+    // Maintenance Commands
+    // ========================================================================
+    /// Publish an operator message / maintenance window that clients show
+    /// as a banner
+    SetMaintenance {
+        /// Message to show traders (e.g., "Upgrading settlement contracts")
+        message: String,
+
+        /// When the maintenance starts, from now (e.g. `2h`, `30m`)
+        #[arg(long, value_parser = parse_duration_arg)]
+        starts_in: Option<Duration>,
+
+        /// How long the maintenance lasts (e.g. `45m`)
+        #[arg(long, value_parser = parse_duration_arg)]
+        lasts: Option<Duration>,
+    },
+
+    /// Remove the operator message / maintenance window
+    ClearMaintenance,
+
     // ========================================================================
     // Info Commands
     // ========================================================================
@@ -299,21 +526,89 @@ enum Commands {
 
     /// Show balances for owner, signers, and contracts across all chains
     Balances,
+
+    /// Cross-check the configuration against every chain (contract code,
+    /// factory, token decimals/symbols, signer address and gas) and suggest
+    /// fixes. Exits non-zero if any check fails.
+    Reconcile,
+}
+
+/// Fill the flags left unset from the active profile. A `mnemonic:`
+/// signer picks a trader account and doesn't apply here.
+fn apply_profile(cli: &mut Cli, matches: &clap::ArgMatches, profile: &Profile) -> Result<()> {
+    if cli.env_file.is_none() {
+        cli.env_file = profile
+            .env_file_path()
+            .map(|path| path.to_string_lossy().into_owned());
+    }
+    if cli.stack_url.is_none()
+        && let Some(ref url) = profile.stack_url
+    {
+        cli.stack_url = Some(
+            url.parse()
+                .map_err(|e| eyre::eyre!("invalid stack URL '{}' in profile: {}", url, e))?,
+        );
+    }
+    match profile.signer {
+        Some(SignerSource::Keystore(_)) if cli.keystore.is_none() => {
+            cli.keystore = profile.keystore();
+        }
+        #[cfg(feature = "ledger")]
+        Some(SignerSource::Ledger(ref path)) => {
+            cli.ledger = true;
+            if let Some(path) = path
+                && matches.value_source("hd_path") != Some(clap::parser::ValueSource::CommandLine)
+            {
+                cli.hd_path = aspens::ledger::parse_hd_path(path)?;
+            }
+        }
+        #[cfg(not(feature = "ledger"))]
+        Some(SignerSource::Ledger(_)) => {
+            eyre::bail!(
+                "the profile signs with a Ledger, but this build lacks the `ledger` feature"
+            )
+        }
+        #[cfg(feature = "kms")]
+        Some(SignerSource::Kms(ref key_id)) if cli.kms_key_id.is_none() => {
+            cli.kms_key_id = Some(key_id.clone());
+        }
+        #[cfg(not(feature = "kms"))]
+        Some(SignerSource::Kms(_)) => {
+            eyre::bail!("the profile signs with a KMS key, but this build lacks the `kms` feature")
+        }
+        _ => {}
+    }
+    let _ = matches;
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> ExitCode {
-    match run().await {
+    let profile = match aspens_cliutil::activate_profile() {
+        Ok(profile) => profile.map(|(_, profile)| profile).unwrap_or_default(),
+        Err(e) => {
+            let format = aspens_cliutil::error_format_arg(std::env::args_os());
+            return aspens_cliutil::report_error(&e, format);
+        }
+    };
+    let matches = aspens_cliutil::get_matches(Cli::command());
+    match run(&matches, &profile).await {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            eprintln!("{}", e);
-            ExitCode::FAILURE
+            let format = matches
+                .get_one::<ErrorFormat>("error_format")
+                .copied()
+                .unwrap_or_default();
+            aspens_cliutil::report_error(&e, format)
         }
     }
 }
 
-async fn run() -> Result<()> {
-    let cli = Cli::parse();
+async fn run(matches: &clap::ArgMatches, profile: &Profile) -> Result<()> {
+    let mut cli = Cli::from_arg_matches(matches)?;
+    apply_profile(&mut cli, matches, profile)?;
+    // Prompt for a keystore once, however often the command loads it.
+    aspens::wallet::remember_keystores();
 
     // Configure log level - convert from clap-verbosity's log::LevelFilter to tracing's LevelFilter
     let log_level = if cli.verbose.is_silent() {
@@ -347,25 +642,57 @@ async fn run() -> Result<()> {
         builder = builder.with_url(url.to_string())?;
     }
 
+    if let Some(ref path) = cli.keystore {
+        builder = builder.with_keystore(path);
+    }
+
+    if cli.confirmations.is_some() || cli.confirmation_timeout.is_some() {
+        let default = ConfirmationPolicy::default();
+        builder = builder.with_confirmation_policy(ConfirmationPolicy {
+            confirmations: cli.confirmations.unwrap_or(default.confirmations),
+            timeout: cli.confirmation_timeout.unwrap_or(default.timeout),
+        });
+    }
+
     let client = builder.build()?;
     let executor = DirectExecutor;
     let stack_url = client.stack_url().to_string();
+    let admin_privkey = admin_privkey(&client)?;
+    #[allow(unused_mut)]
+    let mut admin_key = admin_privkey.clone().map(AdminKey::Privkey);
+    #[cfg(feature = "ledger")]
+    if cli.ledger {
+        let ledger = executor
+            .execute(aspens::ledger::LedgerWallet::connect(cli.hd_path.clone()))?
+            .with_prompt(|what| eprintln!("Confirm signing {} on your Ledger...", what));
+        info!(
+            "Signing logins with Ledger account {} ({:?})",
+            ledger.address(),
+            ledger.path()
+        );
+        admin_key = Some(AdminKey::Ledger(std::sync::Arc::new(ledger)));
+    }
+    #[cfg(feature = "kms")]
+    if let Some(ref key_id) = cli.kms_key_id {
+        let signer = executor.execute(aspens::kms::AwsKmsSigner::connect(key_id.clone()))?;
+        info!(
+            "Signing as admin with KMS key {} ({})",
+            signer.key_id(),
+            aspens::remote_signer::RemoteSigner::address(&signer)
+        );
+        admin_key = Some(AdminKey::Remote(std::sync::Arc::new(signer)));
+    }
 
-    // Helper to get JWT (from CLI arg, env var, or .env file)
-    let get_jwt = || -> Result<String> {
+    // JWT from CLI arg, env var, .env file, or the one 'login --save' kept
+    // in the OS keychain; renewed with the admin key when it expires.
+    let mut session = Session::new(
+        stack_url.clone(),
         cli.jwt
             .clone()
             .or_else(|| client.get_env("ASPENS_JWT").cloned())
-            .ok_or_else(|| {
-                eyre::eyre!(
-                    "JWT token required\n\n\
-                     Hints:\n\
-                     - Run 'aspens-admin login' to authenticate and get a JWT token\n\
-                     - Set ASPENS_JWT in your .env file\n\
-                     - Use the --jwt flag to provide a token directly"
-                )
-            })
-    };
+            .or_else(|| client.get_jwt_token()),
+        admin_key.clone(),
+    );
 
     match cli.command {
         // ====================================================================
@@ -375,7 +702,7 @@ async fn run() -> Result<()> {
             info!("Initializing admin with address: {}", address);
             let result = executor
                 .execute(auth::initialize_admin(stack_url, address))
-                .map_err(|e| eyre::eyre!(format_error(&e, "initialize admin")))?;
+                .map_err(|e| user_error(e, "initialize admin"))?;
             println!("Admin initialized successfully!");
             println!("JWT Token: {}", result.jwt_token);
             println!("Expires at: {}", format_expiry(result.expires_at));
@@ -383,39 +710,29 @@ async fn run() -> Result<()> {
             println!("\nTo use this token, set ASPENS_JWT environment variable or use --jwt flag");
         }
 
-        Commands::Login { chain_id } => {
-            use alloy::signers::local::PrivateKeySigner;
-
-            let privkey = client.get_env("ADMIN_PRIVKEY").ok_or_else(|| {
-                eyre::eyre!(
-                    "ADMIN_PRIVKEY not found\n\n\
-                     Hints:\n\
-                     - Set ADMIN_PRIVKEY in your .env file\n\
-                     - The private key should be a 64-character hex string (without 0x prefix)\n\
-                     - This should be the private key for the admin wallet"
-                )
-            })?;
-
-            // Parse the private key to show the derived address
-            let signer: PrivateKeySigner = privkey.parse().map_err(|e| {
-                eyre::eyre!(
-                    "Invalid ADMIN_PRIVKEY format\n\n\
-                     Error: {}\n\n\
-                     Hints:\n\
-                     - The private key should be a 64-character hex string\n\
-                     - Do not include the '0x' prefix\n\
-                     - Check for extra whitespace or newlines",
-                    e
-                )
-            })?;
-            let address = signer.address();
+        Commands::Login {
+            chain_id,
+            #[cfg(feature = "keychain")]
+            save,
+        } => {
+            let wallet = admin_key
+                .as_ref()
+                .ok_or_else(|| {
+                    eyre::eyre!(
+                        "ADMIN_PRIVKEY not found\n\n\
+                         Hints:\n\
+                         - Set ADMIN_PRIVKEY in your .env file\n\
+                         - Or pass --keystore with an encrypted keystore file\n\
+                         - The private key should be a 64-character hex string (without 0x prefix)\n\
+                         - This should be the private key for the admin wallet"
+                    )
+                })?
+                .wallet()?;
+            let address = wallet.address();
 
             info!("Authenticating with EIP-712 signature...");
             info!("  Wallet address: {}", address);
 
-            // Re-build the admin wallet from the same privkey — the
-            // address parse above was just for the user-facing message.
-            let wallet = aspens::Wallet::from_evm_hex(privkey)?;
             let url = stack_url.clone();
             let result = executor
                 .execute(async move {
@@ -423,16 +740,16 @@ async fn run() -> Result<()> {
                 })
                 .map_err(|e| {
                     // Include the address in the error context for better debugging
-                    let err_msg = format_error(&e, "authenticate");
+                    let err = user_error(e, "authenticate");
+                    let err_msg = err.to_string();
                     if err_msg.contains("not authorized as admin") {
-                        eyre::eyre!(
+                        err.wrap_err(format!(
                             "{}\n\n\
                              Your wallet address: {}",
-                            err_msg,
-                            address
-                        )
+                            err_msg, address
+                        ))
                     } else {
-                        eyre::eyre!(err_msg)
+                        err
                     }
                 })?;
 
@@ -440,19 +757,38 @@ async fn run() -> Result<()> {
             println!("JWT Token: {}", result.jwt_token);
             println!("Expires at: {}", format_expiry(result.expires_at));
             println!("Address: {}", result.address);
+            #[cfg(feature = "keychain")]
+            if save {
+                keychain::save_jwt(&keychain::StoredJwt::new(&stack_url, &result))?;
+                println!("\nSaved to the OS keychain; later commands against this stack use it");
+                println!("until it expires. Run 'aspens-admin logout' to remove it.");
+                return Ok(());
+            }
             println!("\nTo use this token:");
             println!("  export ASPENS_JWT=\"{}\"", result.jwt_token);
+            #[cfg(feature = "keychain")]
+            println!("or log in with --save to keep it in the OS keychain.");
+        }
+
+        #[cfg(feature = "keychain")]
+        Commands::Logout => {
+            if keychain::delete_jwt(&stack_url)? {
+                println!("Removed the saved JWT for {}", stack_url);
+            } else {
+                println!("No JWT saved for {}", stack_url);
+            }
         }
 
         // ====================================================================
         // Admin Management Commands
         // ====================================================================
         Commands::UpdateAdmin { address } => {
-            let jwt = get_jwt()?;
             info!("Updating admin to: {}", address);
-            let result = executor
-                .execute(admin::update_admin(stack_url.clone(), jwt, address))
-                .map_err(|e| eyre::eyre!(format_error(&e, "update admin")))?;
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::update_admin(stack_url.clone(), jwt, address.clone())
+                })
+                .map_err(|e| user_error(e, "update admin"))?;
             println!("Admin updated successfully to: {}", result.admin_address);
         }
 
@@ -469,7 +805,6 @@ async fn run() -> Result<()> {
             explorer_url,
             instance_signer_address,
         } => {
-            let jwt = get_jwt()?;
             info!("Setting chain: {} ({})", canonical_name, network);
 
             let chain = Chain {
@@ -485,9 +820,11 @@ async fn run() -> Result<()> {
                 tokens: HashMap::new(),
             };
 
-            let result = executor
-                .execute(admin::set_chain(stack_url.clone(), jwt, chain))
-                .map_err(|e| eyre::eyre!(format_error(&e, &format!("set chain '{}'", network))))?;
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::set_chain(stack_url.clone(), jwt, chain.clone())
+                })
+                .map_err(|e| user_error(e, &format!("set chain '{}'", network)))?;
             if result.success {
                 println!("Chain '{}' set successfully!", network);
             } else {
@@ -505,13 +842,12 @@ async fn run() -> Result<()> {
         }
 
         Commands::DeleteChain { network } => {
-            let jwt = get_jwt()?;
             info!("Deleting chain: {}", network);
-            let result = executor
-                .execute(admin::delete_chain(stack_url.clone(), jwt, network.clone()))
-                .map_err(|e| {
-                    eyre::eyre!(format_error(&e, &format!("delete chain '{}'", network)))
-                })?;
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::delete_chain(stack_url.clone(), jwt, network.clone())
+                })
+                .map_err(|e| user_error(e, &format!("delete chain '{}'", network)))?;
             if result.success {
                 println!("Chain '{}' deleted successfully!", network);
             } else {
@@ -536,7 +872,6 @@ async fn run() -> Result<()> {
             decimals,
             token_id,
         } => {
-            let jwt = get_jwt()?;
             info!("Setting token {} ({}) on {}", name, symbol, network);
 
             let token = Token {
@@ -547,19 +882,11 @@ async fn run() -> Result<()> {
                 decimals,
             };
 
-            let result = executor
-                .execute(admin::set_token(
-                    stack_url.clone(),
-                    jwt,
-                    network.clone(),
-                    token,
-                ))
-                .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("set token '{}' on '{}'", symbol, network)
-                    ))
-                })?;
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::set_token(stack_url.clone(), jwt, network.clone(), token.clone())
+                })
+                .map_err(|e| user_error(e, &format!("set token '{}' on '{}'", symbol, network)))?;
             if result.success {
                 println!("Token '{}' set on '{}' successfully!", symbol, network);
             } else {
@@ -577,20 +904,13 @@ async fn run() -> Result<()> {
         }
 
         Commands::DeleteToken { network, symbol } => {
-            let jwt = get_jwt()?;
             info!("Deleting token {} from {}", symbol, network);
-            let result = executor
-                .execute(admin::delete_token(
-                    stack_url.clone(),
-                    jwt,
-                    network.clone(),
-                    symbol.clone(),
-                ))
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::delete_token(stack_url.clone(), jwt, network.clone(), symbol.clone())
+                })
                 .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("delete token '{}' from '{}'", symbol, network)
-                    ))
+                    user_error(e, &format!("delete token '{}' from '{}'", symbol, network))
                 })?;
             if result.success {
                 println!(
@@ -623,8 +943,8 @@ async fn run() -> Result<()> {
             base_decimals,
             quote_decimals,
             pair_decimals,
+            confirm_changes,
         } => {
-            let jwt = get_jwt()?;
             let market_name = format!("{}/{}", base_symbol, quote_symbol);
             info!(
                 "Setting market: {} ({}/{})",
@@ -643,11 +963,28 @@ async fn run() -> Result<()> {
                 pair_decimals,
             };
 
-            let result = executor
-                .execute(admin::set_market(stack_url.clone(), jwt, params))
-                .map_err(|e| {
-                    eyre::eyre!(format_error(&e, &format!("set market '{}'", market_name)))
-                })?;
+            let config = executor
+                .execute(config::get_config(stack_url.clone()))
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            if let Some(diff) = admin::diff_market(&config, &params) {
+                println!("{}", diff);
+                if diff.is_breaking() && !confirm_changes {
+                    return Err(eyre::eyre!(
+                        "Refusing to edit market '{}': the changes above can invalidate its \
+                         resting orders\n\n\
+                         Hints:\n\
+                         - Re-run with --confirm-changes once resting orders are cancelled\n\
+                         - Or create a new market instead of editing this one",
+                        market_name
+                    ));
+                }
+            }
+
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::set_market(stack_url.clone(), jwt, params.clone())
+                })
+                .map_err(|e| user_error(e, &format!("set market '{}'", market_name)))?;
             if result.success {
                 println!("Market '{}' set successfully!", market_name);
             } else {
@@ -666,18 +1003,94 @@ async fn run() -> Result<()> {
             }
         }
 
+        Commands::CreateMarket {
+            base,
+            quote,
+            pair_decimals,
+            dry_run,
+        } => {
+            let config = executor
+                .execute(config::get_config(stack_url.clone()))
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            let params = admin::market_params_from_config(&config, &base, &quote, pair_decimals)?;
+            let market_name = format!("{}/{}", base.symbol, quote.symbol);
+
+            let mut table = Table::new();
+            table.load_preset(UTF8_BORDERS_ONLY);
+            table.set_header(vec!["", "Base", "Quote"]);
+            table.add_row(vec![
+                "Network",
+                &params.base_chain_network,
+                &params.quote_chain_network,
+            ]);
+            table.add_row(vec![
+                "Symbol",
+                &params.base_chain_token_symbol,
+                &params.quote_chain_token_symbol,
+            ]);
+            table.add_row(vec![
+                "Address",
+                &params.base_chain_token_address,
+                &params.quote_chain_token_address,
+            ]);
+            table.add_row(vec![
+                "Decimals".to_string(),
+                params.base_chain_token_decimals.to_string(),
+                params.quote_chain_token_decimals.to_string(),
+            ]);
+            println!("Market '{}'", market_name);
+            println!("{}", table);
+            println!(
+                "Pair decimals: {}{}",
+                params.pair_decimals,
+                if pair_decimals.is_none() {
+                    " (suggested)"
+                } else {
+                    ""
+                }
+            );
+            if let Some(existing) = config.get_market_by_tokens(
+                &base.network,
+                &base.symbol,
+                &quote.network,
+                &quote.symbol,
+            ) {
+                println!(
+                    "Note: market already exists (id: {}, pair decimals: {}) and will be updated",
+                    existing.market_id, existing.pair_decimals
+                );
+            }
+
+            if dry_run {
+                println!("Dry run: market not submitted");
+                return Ok(());
+            }
+
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::set_market(stack_url.clone(), jwt, params.clone())
+                })
+                .map_err(|e| user_error(e, &format!("create market '{}'", market_name)))?;
+            if result.success {
+                println!("Market '{}' created successfully!", market_name);
+            } else {
+                return Err(eyre::eyre!(
+                    "Failed to create market '{}'\n\n\
+                     Hints:\n\
+                     - Check the server logs for more details\n\
+                     - Re-run with --dry-run to inspect the resolved parameters",
+                    market_name
+                ));
+            }
+        }
+
         Commands::DeleteMarket { market_id } => {
-            let jwt = get_jwt()?;
             info!("Deleting market: {}", market_id);
-            let result = executor
-                .execute(admin::delete_market(
-                    stack_url.clone(),
-                    jwt,
-                    market_id.clone(),
-                ))
-                .map_err(|e| {
-                    eyre::eyre!(format_error(&e, &format!("delete market '{}'", market_id)))
-                })?;
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::delete_market(stack_url.clone(), jwt, market_id.clone())
+                })
+                .map_err(|e| user_error(e, &format!("delete market '{}'", market_id)))?;
             if result.success {
                 println!("Market '{}' deleted successfully!", market_id);
             } else {
@@ -692,11 +1105,66 @@ async fn run() -> Result<()> {
             }
         }
 
+        Commands::SetMarketSchedule {
+            market_id,
+            windows,
+            blocks,
+        } => {
+            let schedule = (!windows.is_empty() || !blocks.is_empty())
+                .then_some(config::config_pb::MarketSchedule { windows, blocks });
+            info!("Setting trading hours for market: {}", market_id);
+            session
+                .call(&executor, |jwt| {
+                    admin::set_market_schedule(
+                        stack_url.clone(),
+                        jwt,
+                        market_id.clone(),
+                        schedule.clone(),
+                    )
+                })
+                .map_err(|e| {
+                    user_error(e, &format!("set trading hours for market '{}'", market_id))
+                })?;
+            match schedule {
+                None => println!("Market '{}' is now always open", market_id),
+                Some(schedule) => {
+                    println!("Trading hours set for market '{}':", market_id);
+                    for window in &schedule.windows {
+                        println!("  Open:   {} UTC", window);
+                    }
+                    for block in &schedule.blocks {
+                        println!(
+                            "  Closed: {} - {}{}",
+                            format_expiry(block.starts_at),
+                            format_expiry(block.ends_at),
+                            if block.reason.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" ({})", block.reason)
+                            }
+                        );
+                    }
+                }
+            }
+        }
+
         // ====================================================================
         // Contract Commands
         // ====================================================================
-        Commands::DeployContract { network, fees } => {
-            let jwt = get_jwt()?;
+        Commands::DeployContract {
+            network,
+            fees,
+            resume,
+            payer_privkey,
+            offline_sign,
+            out,
+            unsigned_out,
+            from,
+        } => {
+            // Fail fast without a token. Signing, broadcasting and waiting on
+            // the chain can outlast a nearly-expired one.
+            session.jwt()?;
+            session.warn_if_expiring(DEPLOY_JWT_MARGIN);
 
             // Resolve chain architecture upfront: EVM admins sign+broadcast
             // create_instance locally and then ask arborter to confirm; Solana
@@ -705,12 +1173,7 @@ async fn run() -> Result<()> {
             // constraint. The two flows share nothing past this point.
             let config = executor
                 .execute(aspens::commands::config::get_config(stack_url.clone()))
-                .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("fetch configuration for '{}'", network)
-                    ))
-                })?;
+                .map_err(|e| user_error(e, &format!("fetch configuration for '{}'", network)))?;
             let chain = config.get_chain(&network).ok_or_else(|| {
                 let available_chains = config
                     .config
@@ -735,35 +1198,157 @@ async fn run() -> Result<()> {
 
             let is_solana = chain.architecture.eq_ignore_ascii_case("solana");
 
-            let tx_hash = if is_solana {
+            // Broadcast-but-unregistered deployments, so an interrupted
+            // deploy can be resumed instead of deploying a second instance.
+            let mut journal = DeployJournal::open_default()
+                .inspect_err(|e| eprintln!("Warning: can't open the deploy journal: {}", e))
+                .ok();
+
+            let tx_hash = if let Some(tx_hash) = resume {
+                if is_solana {
+                    return Err(eyre::eyre!(
+                        "--resume applies to EVM deployments only\n\n\
+                         Solana deployments are signed and submitted by the stack in one call;\n\
+                         re-run 'aspens-admin deploy-contract {}' instead",
+                        network
+                    ));
+                }
+                if let Some(pending) = journal.as_ref().and_then(|j| j.get(&tx_hash))
+                    && pending.network != network
+                {
+                    return Err(eyre::eyre!(
+                        "transaction {} deployed on '{}', not '{}'",
+                        tx_hash,
+                        pending.network,
+                        network
+                    ));
+                }
+                info!("Resuming deployment from transaction {}", tx_hash);
+                tx_hash
+            } else if is_solana {
+                if offline_sign || payer_privkey.is_some() || unsigned_out.is_some() {
+                    return Err(eyre::eyre!(
+                        "--offline-sign, --unsigned-out and --payer-privkey apply to EVM \
+                         deployments only\n\n\
+                         On Solana the stack signs and pays for the deployment itself"
+                    ));
+                }
                 // Solana: server signs + submits, no admin private key needed.
                 String::new()
             } else {
-                // EVM: admin must sign + broadcast createInstance locally first.
-                let privkey = client.get_env("ADMIN_PRIVKEY").ok_or_else(|| {
-                    eyre::eyre!(
-                        "ADMIN_PRIVKEY not found\n\n\
-                         This command requires ADMIN_PRIVKEY to sign the deployment transaction.\n\n\
+                if let Some(pending) = journal.as_ref().and_then(|j| {
+                    j.pending()
+                        .find(|p| p.network == network && p.stack_url == stack_url)
+                }) {
+                    return Err(eyre::eyre!(
+                        "a deployment on '{}' (transaction {}, broadcast {}) was never registered\n\n\
                          Hints:\n\
-                         - Set ADMIN_PRIVKEY in your .env file\n\
-                         - The private key should be a 64-character hex string (without 0x prefix)\n\
-                         - This wallet will pay the gas fees for the deployment"
-                    )
-                })?;
+                         - Finish it with 'aspens-admin deploy-contract {} --resume {}'\n\
+                         - Or remove the entry from {} to deploy a new instance",
+                        network,
+                        pending.tx_hash,
+                        format_expiry(pending.broadcast_at),
+                        network,
+                        pending.tx_hash,
+                        journal
+                            .as_ref()
+                            .map(|j| j.path().display().to_string())
+                            .unwrap_or_default()
+                    ));
+                }
+
+                if let Some(unsigned_out) = unsigned_out {
+                    let from = match from {
+                        Some(from) => from,
+                        None => {
+                            let privkey = admin_privkey.as_ref().ok_or_else(|| {
+                                eyre::eyre!(
+                                    "--unsigned-out needs the admin address\n\n\
+                                     Hints:\n\
+                                     - Pass --from with the admin address"
+                                )
+                            })?;
+                            aspens::Wallet::from_evm_hex(privkey)?.address()
+                        }
+                    };
+                    let calldata_response = session
+                        .call(&executor, |jwt| {
+                            admin::get_deploy_calldata(
+                                stack_url.clone(),
+                                jwt,
+                                network.clone(),
+                                fees as u32,
+                            )
+                        })
+                        .map_err(|e| {
+                            user_error(e, &format!("fetch deploy calldata for '{}'", network))
+                        })?;
+                    let tx = executor
+                        .execute(admin::unsigned_create_instance(
+                            chain.clone(),
+                            calldata_response.factory_address,
+                            calldata_response.calldata,
+                            from,
+                        ))
+                        .map_err(|e| {
+                            user_error(
+                                e,
+                                &format!("build createInstance transaction for '{}'", network),
+                            )
+                        })?;
+                    tx.write(&unsigned_out)?;
+                    println!(
+                        "Unsigned createInstance transaction written to {}",
+                        unsigned_out.display()
+                    );
+                    println!("On the offline machine, sign it with:");
+                    println!(
+                        "  aspens-cli sign -f {} --key-env ADMIN_PRIVKEY -o signed.hex",
+                        unsigned_out.display()
+                    );
+                    println!("Then broadcast it and finish the deployment with:");
+                    println!("  aspens-cli broadcast -f signed.hex --network {}", network);
+                    println!(
+                        "  aspens-admin deploy-contract {} --resume <TX_HASH>",
+                        network
+                    );
+                    return Ok(());
+                }
+
+                // EVM: admin must sign + broadcast createInstance locally first.
+                let deployer = match admin_key.as_ref().filter(|key| key.signs_transactions()) {
+                    Some(key) => key.wallet()?,
+                    None => {
+                        let privkey = admin_privkey.as_ref().ok_or_else(|| {
+                            eyre::eyre!(
+                                "ADMIN_PRIVKEY not found\n\n\
+                                 This command requires ADMIN_PRIVKEY to sign the deployment transaction.\n\n\
+                                 Hints:\n\
+                                 - Set ADMIN_PRIVKEY in your .env file\n\
+                                 - Or pass --keystore with an encrypted keystore file\n\
+                                 - The private key should be a 64-character hex string (without 0x prefix)\n\
+                                 - This wallet pays the gas fees, unless --payer-privkey is set"
+                            )
+                        })?;
+                        AdminKey::Privkey(privkey.clone()).wallet()?
+                    }
+                };
+                // Shared with each command future, which must own what it
+                // borrows.
+                let deployer = std::sync::Arc::new(deployer);
 
                 info!("Fetching deploy calldata from server for: {}", network);
-                let calldata_response = executor
-                    .execute(admin::get_deploy_calldata(
-                        stack_url.clone(),
-                        jwt.clone(),
-                        network.clone(),
-                        fees as u32,
-                    ))
+                let calldata_response = session
+                    .call(&executor, |jwt| {
+                        admin::get_deploy_calldata(
+                            stack_url.clone(),
+                            jwt,
+                            network.clone(),
+                            fees as u32,
+                        )
+                    })
                     .map_err(|e| {
-                        eyre::eyre!(format_error(
-                            &e,
-                            &format!("fetch deploy calldata for '{}'", network)
-                        ))
+                        user_error(e, &format!("fetch deploy calldata for '{}'", network))
                     })?;
 
                 info!(
@@ -782,55 +1367,179 @@ async fn run() -> Result<()> {
                     calldata: calldata_response.calldata.clone(),
                     rpc_url: chain.rpc_url.clone(),
                     chain_id: calldata_response.chain_id as u64,
-                    privkey: privkey.clone(),
+                    privkey: admin_privkey.clone().unwrap_or_default(),
+                    tx_type: TxType::for_network(&network),
                 };
 
+                let signer = deployer.clone();
                 let signed_tx = executor
-                    .execute(admin::build_create_instance_tx(params))
+                    .execute(async move {
+                        admin::build_create_instance_tx_with_wallet(params, signer.as_ref()).await
+                    })
                     .map_err(|e| {
-                        eyre::eyre!(format_error(
-                            &e,
-                            &format!("build createInstance transaction for '{}'", network)
-                        ))
+                        user_error(
+                            e,
+                            &format!("build createInstance transaction for '{}'", network),
+                        )
                     })?;
 
-                info!(
-                    "Transaction signed ({} bytes), broadcasting to chain...",
-                    signed_tx.len()
+                let tx_hash = if offline_sign {
+                    let out = out
+                        .as_ref()
+                        .ok_or_else(|| eyre::eyre!("--offline-sign needs --out"))?;
+                    std::fs::write(out, format!("0x{}\n", hex::encode(&signed_tx)))
+                        .map_err(|e| eyre::eyre!("Failed to write {}: {}", out.display(), e))?;
+                    admin::signed_tx_hash(&signed_tx)
+                } else {
+                    let payer = payer_privkey
+                        .clone()
+                        .or_else(|| client.get_env("PAYER_PRIVKEY").cloned());
+                    if let Some(payer) = payer {
+                        let payer = aspens::Wallet::from_evm_hex(&payer)
+                            .map_err(|e| eyre::eyre!("Invalid payer private key: {}", e))?;
+                        let (rpc_url, network_name, tx) =
+                            (chain.rpc_url.clone(), network.clone(), signed_tx.clone());
+                        let policy = client.confirmation_policy();
+                        let topup = executor
+                            .execute(async move {
+                                admin::sponsor_gas(rpc_url, network_name, tx, &payer, policy).await
+                            })
+                            .map_err(|e| {
+                                user_error(e, &format!("fund deployment gas on '{}'", network))
+                            })?;
+                        match topup {
+                            Some(hash) => println!("Gas funded by payer: tx {}", hash),
+                            None => info!("Admin account already holds enough gas"),
+                        }
+                    }
+
+                    info!(
+                        "Transaction signed ({} bytes), broadcasting to chain...",
+                        signed_tx.len()
+                    );
+                    let tx_hash = executor
+                        .execute(admin::broadcast_transaction(
+                            chain.rpc_url.clone(),
+                            signed_tx,
+                        ))
+                        .map_err(|e| {
+                            user_error(e, &format!("broadcast transaction to '{}'", network))
+                        })?;
+                    info!("Transaction broadcast with hash: {}", tx_hash);
+                    tx_hash
+                };
+
+                // Journaled before an offline-signed transaction is sent too,
+                // so a second deploy doesn't start while the relay holds it.
+                let pending = PendingDeploy {
+                    stack_url: stack_url.clone(),
+                    network: network.clone(),
+                    fee_bps: fees as u32,
+                    tx_hash: tx_hash.clone(),
+                    broadcast_at: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0),
+                };
+                if let Some(journal) = journal.as_mut()
+                    && let Err(e) = journal.record(pending.clone())
+                {
+                    eprintln!("Warning: can't record the deployment: {}", e);
+                }
+
+                if offline_sign {
+                    println!(
+                        "Signed createInstance transaction written to {}",
+                        out.as_ref()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_default()
+                    );
+                    println!("Transaction hash: {}", tx_hash);
+                    println!("Once it is broadcast, finish with:");
+                    println!(
+                        "  aspens-admin deploy-contract {} --resume {}",
+                        network, tx_hash
+                    );
+                    return Ok(());
+                }
+
+                // A stuck createInstance is sent again with higher fees; the
+                // stack has to be told the hash of the version that landed.
+                let (rpc_url, chain_id, hash) = (
+                    chain.rpc_url.clone(),
+                    calldata_response.chain_id as u64,
+                    tx_hash.clone(),
                 );
-                let tx_hash = executor
-                    .execute(admin::broadcast_transaction(
-                        chain.rpc_url.clone(),
-                        signed_tx,
-                    ))
+                let (signer, policy) = (deployer.clone(), client.confirmation_policy());
+                let mined = executor
+                    .execute(async move {
+                        admin::wait_for_broadcast(rpc_url, chain_id, hash, signer.as_ref(), policy)
+                            .await
+                    })
                     .map_err(|e| {
-                        eyre::eyre!(format_error(
-                            &e,
-                            &format!("broadcast transaction to '{}'", network)
+                        let context = format!("wait for createInstance on '{}'", network);
+                        let err = user_error(e, &context);
+                        let msg = err.to_string();
+                        err.wrap_err(format!(
+                            "{}\n\n\
+                             The createInstance transaction was broadcast; once it is mined, finish with:\n  \
+                             aspens-admin deploy-contract {} --resume {}",
+                            msg, network, tx_hash
                         ))
                     })?;
-
-                info!("Transaction broadcast with hash: {}", tx_hash);
-                tx_hash
+                if mined != tx_hash {
+                    println!("createInstance replaced with higher fees: {}", mined);
+                    if let Some(journal) = journal.as_mut() {
+                        let replaced = journal.complete(&tx_hash).and_then(|_| {
+                            journal.record(PendingDeploy {
+                                tx_hash: mined.clone(),
+                                ..pending
+                            })
+                        });
+                        if let Err(e) = replaced {
+                            eprintln!("Warning: can't update the deploy journal: {}", e);
+                        }
+                    }
+                }
+                mined
             };
 
             // Server-side handler: EVM waits on tx_hash; Solana signs + submits
-            // and returns the new instance PDA + a signature receipt.
-            let result = executor
-                .execute(admin::deploy_contract(
-                    stack_url.clone(),
-                    jwt,
-                    network.clone(),
-                    tx_hash,
-                    /* force */ false,
-                    /* fee_bps */ fees as u32,
-                ))
+            // and returns the new instance PDA + a signature receipt. Safe to
+            // repeat for the same tx_hash, which is what --resume does.
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::register_deployment(
+                        stack_url.clone(),
+                        jwt,
+                        network.clone(),
+                        tx_hash.clone(),
+                        fees as u32,
+                    )
+                })
                 .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("wait for contract deployment on '{}'", network)
-                    ))
+                    let context = format!("wait for contract deployment on '{}'", network);
+                    let err = user_error(e, &context);
+                    if tx_hash.is_empty() {
+                        err
+                    } else {
+                        let msg = err.to_string();
+                        err.wrap_err(format!(
+                            "{}\n\n\
+                             The createInstance transaction was broadcast; once the issue is fixed, finish with:\n  \
+                             aspens-admin deploy-contract {} --resume {}",
+                            msg,
+                            network,
+                            tx_hash
+                        ))
+                    }
                 })?;
+            if !tx_hash.is_empty()
+                && let Some(journal) = journal.as_mut()
+                && let Err(e) = journal.complete(&tx_hash)
+            {
+                eprintln!("Warning: can't update the deploy journal: {}", e);
+            }
             println!("Trade contract deployed at: {}", result.contract_address);
             if !result.tx_signature.is_empty() {
                 println!("Transaction: {}", result.tx_signature);
@@ -841,23 +1550,21 @@ async fn run() -> Result<()> {
             address,
             chain_network,
         } => {
-            let jwt = get_jwt()?;
             info!(
                 "Setting trade contract {} on chain {}",
                 address, chain_network
             );
-            let result = executor
-                .execute(admin::set_trade_contract(
-                    stack_url.clone(),
-                    jwt,
-                    address.clone(),
-                    chain_network.clone(),
-                ))
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::set_trade_contract(
+                        stack_url.clone(),
+                        jwt,
+                        address.clone(),
+                        chain_network.clone(),
+                    )
+                })
                 .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("set trade contract on chain {}", chain_network)
-                    ))
+                    user_error(e, &format!("set trade contract on chain {}", chain_network))
                 })?;
             if let Some(tc) = result.trade_contract {
                 println!("Trade contract set: {}", tc.address);
@@ -871,24 +1578,22 @@ async fn run() -> Result<()> {
             recipient,
             bps,
         } => {
-            let jwt = get_jwt()?;
             info!(
                 "Setting operator fee {} bps -> {} on chain {}",
                 bps, recipient, chain_network
             );
-            let result = executor
-                .execute(admin::set_operator_fee(
-                    stack_url.clone(),
-                    jwt,
-                    chain_network.clone(),
-                    recipient.clone(),
-                    bps,
-                ))
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::set_operator_fee(
+                        stack_url.clone(),
+                        jwt,
+                        chain_network.clone(),
+                        recipient.clone(),
+                        bps,
+                    )
+                })
                 .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("set operator fee on chain {}", chain_network)
-                    ))
+                    user_error(e, &format!("set operator fee on chain {}", chain_network))
                 })?;
             if result.tx_signature.is_empty() {
                 println!("Operator fee set (no on-chain tx returned)");
@@ -901,23 +1606,24 @@ async fn run() -> Result<()> {
             chain_network,
             new_admin,
         } => {
-            let jwt = get_jwt()?;
             info!(
                 "Rotating operator admin -> {} on chain {}",
                 new_admin, chain_network
             );
-            let result = executor
-                .execute(admin::set_operator_admin(
-                    stack_url.clone(),
-                    jwt,
-                    chain_network.clone(),
-                    new_admin.clone(),
-                ))
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::set_operator_admin(
+                        stack_url.clone(),
+                        jwt,
+                        chain_network.clone(),
+                        new_admin.clone(),
+                    )
+                })
                 .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("rotate operator admin on chain {}", chain_network)
-                    ))
+                    user_error(
+                        e,
+                        &format!("rotate operator admin on chain {}", chain_network),
+                    )
                 })?;
             if result.tx_signature.is_empty() {
                 println!("Operator admin rotated (no on-chain tx returned)");
@@ -927,19 +1633,16 @@ async fn run() -> Result<()> {
         }
 
         Commands::DeleteTradeContract { chain_network } => {
-            let jwt = get_jwt()?;
             info!("Deleting trade contract from chain {}", chain_network);
-            let result = executor
-                .execute(admin::delete_trade_contract(
-                    stack_url.clone(),
-                    jwt,
-                    chain_network.clone(),
-                ))
+            let result = session
+                .call(&executor, |jwt| {
+                    admin::delete_trade_contract(stack_url.clone(), jwt, chain_network.clone())
+                })
                 .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("delete trade contract from chain {}", chain_network)
-                    ))
+                    user_error(
+                        e,
+                        &format!("delete trade contract from chain {}", chain_network),
+                    )
                 })?;
             if result.success {
                 println!(
@@ -958,13 +1661,54 @@ async fn run() -> Result<()> {
             }
         }
 
+        Commands::SetMaintenance {
+            message,
+            starts_in,
+            lasts,
+        } => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let starts_at = starts_in.map(|d| now + d.as_secs());
+            let ends_at = lasts.map(|d| starts_at.unwrap_or(now) + d.as_secs());
+            info!("Setting maintenance notice");
+            let notice = config::config_pb::MaintenanceNotice {
+                message,
+                starts_at,
+                ends_at,
+            };
+            session
+                .call(&executor, |jwt| {
+                    admin::set_maintenance(stack_url.clone(), jwt, notice.clone())
+                })
+                .map_err(|e| user_error(e, "set maintenance notice"))?;
+            println!("Maintenance notice set");
+            if let Some(start) = starts_at {
+                println!("  Starts: {}", format_expiry(start));
+            }
+            if let Some(end) = ends_at {
+                println!("  Ends:   {}", format_expiry(end));
+            }
+        }
+
+        Commands::ClearMaintenance => {
+            info!("Clearing maintenance notice");
+            session
+                .call(&executor, |jwt| {
+                    admin::clear_maintenance(stack_url.clone(), jwt)
+                })
+                .map_err(|e| user_error(e, "clear maintenance notice"))?;
+            println!("Maintenance notice cleared");
+        }
+
         // ====================================================================
         // Info Commands
         // ====================================================================
         Commands::Version => {
             let version = executor
                 .execute(admin::get_version(stack_url.clone()))
-                .map_err(|e| eyre::eyre!(format_error(&e, "get server version")))?;
+                .map_err(|e| user_error(e, "get server version"))?;
             println!("Server Version Information:");
             println!("  Version: {}", version.version);
             println!("  Git Commit: {}", version.git_commit_hash);
@@ -1045,11 +1789,12 @@ async fn run() -> Result<()> {
         Commands::AdminPublicKey => {
             use alloy::signers::local::PrivateKeySigner;
 
-            let privkey = client.get_env("ADMIN_PRIVKEY").ok_or_else(|| {
+            let privkey = admin_privkey.as_ref().ok_or_else(|| {
                 eyre::eyre!(
                     "ADMIN_PRIVKEY not found\n\n\
                      Hints:\n\
                      - Set ADMIN_PRIVKEY in your .env file\n\
+                     - Or pass --keystore with an encrypted keystore file\n\
                      - The private key should be a 64-character hex string (without 0x prefix)\n\
                      - This should be the private key for the admin wallet"
                 )
@@ -1085,7 +1830,7 @@ async fn run() -> Result<()> {
 
             let config_response = executor
                 .execute(config::get_config(stack_url.clone()))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+                .map_err(|e| user_error(e, "fetch configuration"))?;
 
             let configuration = config_response.config.as_ref().ok_or_else(|| {
                 eyre::eyre!(
@@ -1097,11 +1842,11 @@ async fn run() -> Result<()> {
 
             let signer_response = executor
                 .execute(config::get_signer_public_key(stack_url.clone(), None))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch signer public keys")))?;
+                .map_err(|e| user_error(e, "fetch signer public keys"))?;
 
-            // Get owner address from ADMIN_PRIVKEY if available
-            let owner_address: Option<Address> = client
-                .get_env("ADMIN_PRIVKEY")
+            // Get owner address from the admin key if available
+            let owner_address: Option<Address> = admin_privkey
+                .as_ref()
                 .and_then(|pk| {
                     use alloy::signers::local::PrivateKeySigner;
                     pk.parse::<PrivateKeySigner>().ok()
@@ -1224,6 +1969,22 @@ async fn run() -> Result<()> {
                 );
             }
         }
+
+        Commands::Reconcile => {
+            info!("Fetching configuration and checking every chain...");
+
+            let report = executor
+                .execute(admin::reconcile(stack_url.clone()))
+                .map_err(|e| user_error(e, "reconcile"))?;
+
+            println!("{}", report);
+            if !report.is_healthy() {
+                return Err(eyre::eyre!(
+                    "{} check(s) failed; see the remediation steps above",
+                    report.count(admin::CheckStatus::Failed)
+                ));
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,222 @@
+//! The admin JWT for one invocation: expiry checks and automatic re-login.
+//!
+//! A token passed with `--jwt` or `ASPENS_JWT`, or saved in the OS keychain
+//! by `aspens-admin login --save`, can expire partway through a long
+//! command (`deploy-contract` signs, broadcasts and then waits on the
+//! chain). [`Session`] reads the token's `exp` claim to warn before such
+//! commands, and when the admin key is available (`ADMIN_PRIVKEY`,
+//! `--keystore`, `--ledger` or `--kms-key-id`) it logs in again, both up
+//! front for an already-expired token and once after a call the stack
+//! rejects as unauthenticated.
+
+use std::future::Future;
+#[cfg(any(feature = "ledger", feature = "kms"))]
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use aspens::Wallet;
+use aspens::commands::auth;
+#[cfg(feature = "ledger")]
+use aspens::ledger::LedgerWallet;
+#[cfg(feature = "kms")]
+use aspens::remote_signer::RemoteSigner;
+use aspens::{AsyncExecutor, DirectExecutor};
+use aspens_cliutil::is_auth_error;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use eyre::Result;
+
+use crate::format_expiry;
+
+/// Chain ID for the EIP-712 domain of an automatic re-login; the same
+/// default as `aspens-admin login`.
+const RELOGIN_CHAIN_ID: u64 = 1;
+
+/// Unix expiry (`exp`, in seconds) of a JWT, or `None` if the token isn't
+/// a JWT or carries no expiry. The signature is not checked; the stack
+/// does that.
+pub fn jwt_expiry(jwt: &str) -> Option<u64> {
+    let payload = jwt.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    claims.get("exp")?.as_u64()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The key admin logins sign with.
+#[derive(Clone)]
+pub enum AdminKey {
+    /// Hex private key: `ADMIN_PRIVKEY`, or the one unlocked from
+    /// `--keystore`.
+    Privkey(String),
+    /// The Ledger connected with `--ledger`.
+    #[cfg(feature = "ledger")]
+    Ledger(Arc<LedgerWallet>),
+    /// The KMS key named with `--kms-key-id`.
+    #[cfg(feature = "kms")]
+    Remote(Arc<dyn RemoteSigner>),
+}
+
+impl AdminKey {
+    /// The wallet to log in with.
+    pub fn wallet(&self) -> Result<Wallet> {
+        match self {
+            AdminKey::Privkey(privkey) => Wallet::from_evm_hex(privkey).map_err(|e| {
+                eyre::eyre!(
+                    "Invalid ADMIN_PRIVKEY format\n\n\
+                     Error: {}\n\n\
+                     Hints:\n\
+                     - The private key should be a 64-character hex string\n\
+                     - Do not include the '0x' prefix\n\
+                     - Check for extra whitespace or newlines",
+                    e
+                )
+            }),
+            #[cfg(feature = "ledger")]
+            AdminKey::Ledger(ledger) => Ok(Wallet::Ledger(ledger.clone())),
+            #[cfg(feature = "kms")]
+            AdminKey::Remote(remote) => Ok(Wallet::Remote(remote.clone())),
+        }
+    }
+
+    /// Whether the key also signs transactions, such as deployments. A
+    /// Ledger only signs logins here.
+    pub fn signs_transactions(&self) -> bool {
+        #[cfg(feature = "ledger")]
+        if let AdminKey::Ledger(_) = self {
+            return false;
+        }
+        true
+    }
+}
+
+/// The JWT admin calls run with, and the key to renew it with.
+pub struct Session {
+    stack_url: String,
+    jwt: Option<String>,
+    key: Option<AdminKey>,
+}
+
+impl Session {
+    /// A session starting from `jwt` (from `--jwt`, `ASPENS_JWT` or the
+    /// OS keychain), able to log in again when `key` is set.
+    pub fn new(stack_url: String, jwt: Option<String>, key: Option<AdminKey>) -> Self {
+        Self {
+            stack_url,
+            jwt,
+            key,
+        }
+    }
+
+    /// Expiry of the current token, if it has one.
+    fn expires_at(&self) -> Option<u64> {
+        self.jwt.as_deref().and_then(jwt_expiry)
+    }
+
+    /// The token to call with. A missing or expired token is replaced by a
+    /// fresh login when the admin key is set.
+    pub fn jwt(&mut self) -> Result<String> {
+        let expires_at = self.expires_at();
+        let expired = expires_at.is_some_and(|exp| exp <= now_secs());
+        if let Some(jwt) = &self.jwt
+            && !expired
+        {
+            return Ok(jwt.clone());
+        }
+        if self.key.is_some() {
+            if expired {
+                eprintln!("Warning: the admin JWT has expired; logging in again");
+            }
+            return self.relogin();
+        }
+        match expires_at {
+            Some(exp) => Err(eyre::eyre!(
+                "JWT token expired at {}\n\n\
+                 Hints:\n\
+                 - Run 'aspens-admin login' to get a fresh JWT token\n\
+                 - Set ADMIN_PRIVKEY in your .env file to log in again automatically",
+                format_expiry(exp)
+            )),
+            None => Err(eyre::eyre!(
+                "JWT token required\n\n\
+                 Hints:\n\
+                 - Run 'aspens-admin login --save' to authenticate and keep the token\n\
+                 - Set ASPENS_JWT in your .env file\n\
+                 - Use the --jwt flag to provide a token directly"
+            )),
+        }
+    }
+
+    /// Warn if the token expires within `duration`, before a command that
+    /// can run that long.
+    pub fn warn_if_expiring(&self, duration: Duration) {
+        let Some(exp) = self.expires_at() else {
+            return;
+        };
+        let now = now_secs();
+        if exp <= now || exp > now + duration.as_secs() {
+            return;
+        }
+        eprintln!(
+            "Warning: the admin JWT expires at {} ({}s from now); this command may outlast it",
+            format_expiry(exp),
+            exp - now
+        );
+        if self.key.is_none() {
+            eprintln!(
+                "  Run 'aspens-admin login' first, or set ADMIN_PRIVKEY to log in again automatically"
+            );
+        }
+    }
+
+    /// Run an authenticated call. If the stack rejects the token and
+    /// the admin key is set, log in again and retry the call once.
+    pub fn call<T, E, F, Fut>(&mut self, executor: &DirectExecutor, mut call: F) -> Result<T>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: Send + 'static,
+        E: Into<eyre::Report> + Send + 'static,
+    {
+        let mut run = |jwt| {
+            let future = call(jwt);
+            executor.execute(async move { future.await.map_err(Into::into) })
+        };
+        let jwt = self.jwt()?;
+        match run(jwt) {
+            Err(e) if self.key.is_some() && is_auth_error(&e) => {
+                eprintln!("Warning: the stack rejected the admin JWT; logging in again");
+                let jwt = self.relogin()?;
+                run(jwt)
+            }
+            result => result,
+        }
+    }
+
+    /// Log in with the admin key and keep the new token.
+    fn relogin(&mut self) -> Result<String> {
+        let wallet = self
+            .key
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("ADMIN_PRIVKEY is required to log in again"))?
+            .wallet()?;
+        let url = self.stack_url.clone();
+        let token = DirectExecutor
+            .execute(async move {
+                auth::authenticate_with_wallet(url, &wallet, Some(RELOGIN_CHAIN_ID)).await
+            })
+            .map_err(|e| crate::user_error(e, "log in again"))?;
+        eprintln!(
+            "Logged in again; new JWT expires at {}",
+            format_expiry(token.expires_at)
+        );
+        self.jwt = Some(token.jwt_token.clone());
+        Ok(token.jwt_token)
+    }
+}
@@ -0,0 +1,164 @@
+//! `aspens-cli debug-bundle`: one redacted JSON file to attach to a bug
+//! report.
+//!
+//! The bundle holds version and platform info, the env file and `ASPENS_*`
+//! variables, a connectivity check and the stack config, the state files in
+//! the profile directory (last entries only), and the tail of any log files
+//! passed in. The whole document goes through
+//! [`redact_text`](aspens_cliutil::redact_text) before it is written, on top
+//! of dropping secret-named variables, so keys, JWTs and RPC API keys don't
+//! leave the machine.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use aspens::{AspensClient, AsyncExecutor, DirectExecutor};
+use aspens_cliutil::{redact_env, redact_text};
+use eyre::Result;
+use serde_json::{Value, json};
+
+/// What to collect beyond the fixed sections.
+pub struct BundleOptions {
+    /// Log files to include the tail of.
+    pub logs: Vec<PathBuf>,
+    /// Lines kept from the end of each log file.
+    pub lines: usize,
+    /// Entries kept from each list or map in the profile state files.
+    pub entries: usize,
+}
+
+/// Collect the bundle as redacted, pretty-printed JSON.
+pub fn collect(
+    executor: &DirectExecutor,
+    client: &AspensClient,
+    opts: &BundleOptions,
+) -> Result<String> {
+    let bundle = json!({
+        "generated_at": aspens::util::unix_now(),
+        "version": {
+            "aspens_cli": env!("CARGO_PKG_VERSION"),
+            "sdk": aspens::telemetry::SDK_VERSION,
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+        },
+        "environment": environment(client),
+        "checks": checks(executor, client),
+        "profile": profile(opts.entries),
+        "logs": logs(&opts.logs, opts.lines),
+    });
+    Ok(redact_text(&serde_json::to_string_pretty(&bundle)?))
+}
+
+/// Env-file variables and `ASPENS_*` process variables, secrets removed.
+fn environment(client: &AspensClient) -> Value {
+    let env_file: BTreeMap<_, _> = client
+        .env_vars()
+        .map(|(k, v)| (k.to_string(), redact_env(k, v)))
+        .collect();
+    let process: BTreeMap<_, _> = std::env::vars()
+        .filter(|(k, _)| k.starts_with("ASPENS_") || k == "RUST_LOG")
+        .map(|(k, v)| {
+            let v = redact_env(&k, &v);
+            (k, v)
+        })
+        .collect();
+    json!({ "env_file": env_file, "process": process })
+}
+
+/// The `status` checks, plus the stack config when it can be fetched.
+fn checks(executor: &DirectExecutor, client: &AspensClient) -> Value {
+    let stack_url = client.stack_url().to_string();
+    let ping = executor.execute(aspens::health::ping_grpc_server(stack_url.clone()));
+    let config = executor
+        .execute_with_timeout(
+            aspens::commands::config::get_config(stack_url.clone()),
+            client.command_timeout(),
+        )
+        .map_err(|e| e.to_string())
+        .and_then(|config| serde_json::to_value(&config).map_err(|e| e.to_string()));
+    json!({
+        "stack_url": stack_url,
+        "ping": {
+            "success": ping.success,
+            "latency_ms": ping.latency_ms,
+            "error": ping.error,
+        },
+        "config": match config {
+            Ok(config) => config,
+            Err(e) => json!({ "error": e }),
+        },
+    })
+}
+
+/// The JSON state files in the profile directory, each cut to its last
+/// `entries` entries.
+fn profile(entries: usize) -> Value {
+    let dir = match aspens::util::profile_dir() {
+        Ok(dir) => dir,
+        Err(e) => return json!({ "error": e.to_string() }),
+    };
+    let mut files = BTreeMap::new();
+    if let Ok(read_dir) = std::fs::read_dir(&dir) {
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let value = match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| serde_json::from_str::<Value>(&s).map_err(|e| e.to_string()))
+            {
+                Ok(value) => last_entries(value, entries, true),
+                Err(e) => json!({ "error": e }),
+            };
+            files.insert(name, value);
+        }
+    }
+    json!({ "dir": dir.display().to_string(), "files": files })
+}
+
+/// `value` with every array, and a `top`-level object (a store keyed by
+/// name or hash), cut to its last `n` entries.
+fn last_entries(value: Value, n: usize, top: bool) -> Value {
+    match value {
+        Value::Array(items) => {
+            let skip = items.len().saturating_sub(n);
+            Value::Array(
+                items
+                    .into_iter()
+                    .skip(skip)
+                    .map(|v| last_entries(v, n, false))
+                    .collect(),
+            )
+        }
+        Value::Object(map) => {
+            let skip = if top { map.len().saturating_sub(n) } else { 0 };
+            Value::Object(
+                map.into_iter()
+                    .skip(skip)
+                    .map(|(k, v)| (k, last_entries(v, n, false)))
+                    .collect(),
+            )
+        }
+        other => other,
+    }
+}
+
+/// The last `lines` lines of each log file.
+fn logs(paths: &[PathBuf], lines: usize) -> Value {
+    let logs: BTreeMap<_, _> = paths
+        .iter()
+        .map(|path| {
+            let tail = match std::fs::read_to_string(path) {
+                Ok(text) => {
+                    let all: Vec<&str> = text.lines().collect();
+                    json!(all[all.len().saturating_sub(lines)..])
+                }
+                Err(e) => json!({ "error": e.to_string() }),
+            };
+            (path.display().to_string(), tail)
+        })
+        .collect();
+    json!(logs)
+}
@@ -0,0 +1,136 @@
+//! `aspens-cli completions <shell>` and the completers behind it.
+//!
+//! The script `completions` prints calls back into `aspens-cli` on each
+//! TAB (clap_complete's `COMPLETE=<shell>` protocol, handled first thing
+//! in `main`), so subcommands and flags always match the installed binary.
+//! Market names, networks and token symbols come from the config the last
+//! command fetched, cached by [`aspens::config_cache`]; until one has run,
+//! only the static completions are offered.
+
+use aspens::commands::config::config_pb::GetConfigResponse;
+use aspens::config_cache;
+use aspens::profiles::ProfilesConfig;
+use clap_complete::dynamic::CompletionCandidate;
+use clap_complete::dynamic::env::Shells;
+use eyre::{Result, eyre};
+
+/// Environment variable the completion script sets when it calls back.
+pub const COMPLETE_ENV: &str = "COMPLETE";
+
+/// Shells `completions` writes a script for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CompletionShell {
+    /// Bash 4+
+    Bash,
+    /// Zsh
+    Zsh,
+    /// Fish
+    Fish,
+    /// Elvish
+    Elvish,
+    /// PowerShell
+    Powershell,
+}
+
+impl CompletionShell {
+    fn name(self) -> &'static str {
+        match self {
+            CompletionShell::Bash => "bash",
+            CompletionShell::Zsh => "zsh",
+            CompletionShell::Fish => "fish",
+            CompletionShell::Elvish => "elvish",
+            CompletionShell::Powershell => "powershell",
+        }
+    }
+}
+
+/// Write the script that registers `aspens-cli` completion in `shell`.
+pub fn write_script(shell: CompletionShell, out: &mut dyn std::io::Write) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(shell.name())
+        .ok_or_else(|| eyre!("no completion support for {}", shell.name()))?;
+    // Call back into this binary by absolute path, so completion keeps
+    // working when another `aspens-cli` is earlier on PATH.
+    let exe = std::env::current_exe()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "aspens-cli".to_string());
+    completer.write_registration(COMPLETE_ENV, "aspens-cli", "aspens-cli", &exe, out)?;
+    Ok(())
+}
+
+/// The cached config of the stack in use: the active profile's, else
+/// `ASPENS_MARKET_STACK_URL`'s, else whichever was fetched last. The env
+/// file isn't read while completing, so the fallback covers a stack URL
+/// kept only there.
+fn cached_config() -> Option<GetConfigResponse> {
+    let profile_stack = ProfilesConfig::load()
+        .ok()
+        .and_then(|config| config.resolve(None).ok().flatten())
+        .and_then(|(_, profile)| profile.stack_url);
+    let stack_url = profile_stack.or_else(|| std::env::var("ASPENS_MARKET_STACK_URL").ok());
+    let cached = match stack_url {
+        Some(url) => config_cache::load(&url).ok().flatten(),
+        None => None,
+    };
+    cached
+        .or_else(|| config_cache::load_latest().ok().flatten())
+        .map(|cached| cached.config)
+}
+
+/// Candidates from `values`, sorted and without duplicates. clap_complete
+/// keeps the ones starting with what has been typed so far.
+fn candidates(
+    values: impl IntoIterator<Item = (String, Option<String>)>,
+) -> Vec<CompletionCandidate> {
+    let mut values: Vec<_> = values.into_iter().collect();
+    values.sort();
+    values.dedup_by(|a, b| a.0 == b.0);
+    values
+        .into_iter()
+        .map(|(value, help)| CompletionCandidate::new(value).help(help.map(Into::into)))
+        .collect()
+}
+
+/// Market names, with the market ID as help.
+pub fn complete_market() -> Vec<CompletionCandidate> {
+    let Some(config) = cached_config().and_then(|c| c.config) else {
+        return Vec::new();
+    };
+    candidates(
+        config
+            .markets
+            .into_iter()
+            .map(|market| (market.name, Some(market.market_id))),
+    )
+}
+
+/// Chain network names.
+pub fn complete_network() -> Vec<CompletionCandidate> {
+    let Some(config) = cached_config().and_then(|c| c.config) else {
+        return Vec::new();
+    };
+    candidates(config.chains.into_iter().map(|chain| (chain.network, None)))
+}
+
+/// Token symbols on any chain, with the networks that list each as help.
+#[cfg(feature = "trading")]
+pub fn complete_token() -> Vec<CompletionCandidate> {
+    let Some(config) = cached_config().and_then(|c| c.config) else {
+        return Vec::new();
+    };
+    let mut tokens: std::collections::BTreeMap<String, Vec<String>> = Default::default();
+    for chain in config.chains {
+        for symbol in chain.tokens.into_keys() {
+            tokens
+                .entry(symbol)
+                .or_default()
+                .push(chain.network.clone());
+        }
+    }
+    candidates(
+        tokens
+            .into_iter()
+            .map(|(symbol, networks)| (symbol, Some(networks.join(", ")))),
+    )
+}
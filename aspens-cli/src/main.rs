@@ -1,38 +1,328 @@
+mod bundle;
+mod completions;
+mod output;
+
+#[cfg(feature = "trading")]
+use aspens::chain::tx::{self, StuckPolicy};
 use aspens::commands::config::config_pb::GetConfigResponse;
+use aspens::commands::trading::{activity, auction, rebates, send_order};
+use aspens::decimals::PrecisionMode;
+use aspens::profiles::{Profile, ProfilesConfig, SignerSource};
+use aspens::storage::{EncryptedStorage, FsStorage, KeySource, PASSPHRASE_ENV, Storage};
+use aspens::telemetry::Telemetry;
+use aspens::{AspensClient, AsyncExecutor, ConfirmationPolicy, DirectExecutor};
+use aspens_cliutil::{BinaryContext, ErrorFormat, parse_duration_arg, parse_since_arg};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use clap_complete::dynamic::ArgValueCompleter;
+use eyre::Result;
+use output::{OutputFormat, StatusReport, emit};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tracing::{Level, info};
+use tracing_subscriber::FmtSubscriber;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use url::Url;
+
+#[cfg(feature = "trading")]
+use alloy::primitives::U256;
+#[cfg(feature = "trading")]
+use aspens::commands::config::FeeRole;
+#[cfg(feature = "trading")]
+use aspens::commands::trading::allowance::Spender;
+#[cfg(feature = "trading")]
 use aspens::commands::trading::send_order::{
+    ExecutionType, OrderOptions, OrderTags,
     arborter_pb::{SendOrderResponse, Side},
     origin_network_for_side, parse_side,
 };
+#[cfg(any(feature = "trading", feature = "streaming"))]
+use aspens::commands::trading::stream_orderbook;
+#[cfg(feature = "trading")]
 use aspens::commands::trading::{
-    balance, cancel_order, deposit, send_order, stream_orderbook, stream_trades, withdraw,
+    allowance, balance, cancel_order, conflate, deposit, depth, estimate, fees, hedera, history,
+    offline, open_orders, orderbook, portfolio, replace_order, rfq, withdraw,
 };
-use aspens::tdx_verify::reportdata::CurveTag;
-use aspens::{
-    AspensClient, AsyncExecutor, CurveType, DirectExecutor, Wallet, load_trader_wallet,
-    load_trader_wallet_for_network,
+#[cfg(feature = "trading")]
+use aspens::presets::{OrderPreset, PresetStore};
+#[cfg(feature = "trading")]
+use aspens::scheduler::{Schedule, Scheduler};
+#[cfg(feature = "trading")]
+use aspens::util::MAX_AMOUNT;
+#[cfg(feature = "trading")]
+use aspens::{AspensSigner, CurveType, Wallet, load_trader_wallet, load_trader_wallet_for_network};
+#[cfg(any(feature = "trading", feature = "streaming"))]
+use aspens_cliutil::parse_address_arg;
+#[cfg(feature = "trading")]
+use aspens_cliutil::{
+    parse_amount_arg, parse_amount_or_max_arg, parse_bps_arg, parse_signed_bps_arg,
 };
-use aspens_cliutil::BinaryContext;
-use clap::Parser;
-use eyre::Result;
-use std::path::PathBuf;
-use std::process::ExitCode;
-use tracing::{Level, info};
-use tracing_subscriber::FmtSubscriber;
-use url::Url;
+#[cfg(feature = "trading")]
+use output::{AllowanceReport, DepositBatchReport, OpenOrders, TraderKey};
+
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::candles::{self, CandleInterval};
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::idle::IdleOptions;
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::live_book::OrderBook;
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::reconnect::{DEFAULT_MAX_ATTEMPTS, ReconnectOptions};
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::replay::{HistoricalReplay, ReplaySpeed, pace};
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::stream_trades;
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::ticker;
+#[cfg(feature = "streaming")]
+use aspens::notify::{LogNotifier, WebhookNotifier};
+#[cfg(feature = "streaming")]
+use aspens::surveillance::{
+    PriceSpikeDetector, QuoteStuffingDetector, Surveillance, WashTradeDetector,
+};
+#[cfg(feature = "streaming")]
+use futures::StreamExt;
+
+#[cfg(feature = "attestation")]
+use aspens::tdx_verify::reportdata::CurveTag;
+
+#[cfg(feature = "journal")]
+use aspens::journal::{EntryKind, Journal, JournalQuery, display_journal};
+
+#[cfg(feature = "ledger")]
+use aspens::ledger::{HDPath, LedgerWallet};
+
+/// The Ledger `--ledger` connected to, shared by every wallet lookup of
+/// the run.
+#[cfg(feature = "ledger")]
+static LEDGER: std::sync::OnceLock<Arc<LedgerWallet>> = std::sync::OnceLock::new();
+
+/// The KMS key `--kms-key-id` named, shared by every wallet lookup of the
+/// run.
+#[cfg(feature = "kms")]
+static REMOTE_SIGNER: std::sync::OnceLock<Arc<dyn aspens::remote_signer::RemoteSigner>> =
+    std::sync::OnceLock::new();
+
+/// The `--kms-key-id` wallet, for an EVM `curve`.
+#[cfg(feature = "trading")]
+fn remote_wallet(curve: CurveType) -> Option<Wallet> {
+    #[cfg(feature = "kms")]
+    if curve == CurveType::Secp256k1
+        && let Some(remote) = REMOTE_SIGNER.get()
+    {
+        return Some(Wallet::Remote(remote.clone()));
+    }
+    let _ = curve;
+    None
+}
+
+/// [`load_trader_wallet`], except that with `--kms-key-id` or `--ledger`
+/// the EVM wallet is the KMS key or the Ledger.
+#[cfg(feature = "trading")]
+fn trader_wallet(curve: CurveType) -> Result<Wallet> {
+    if let Some(remote) = remote_wallet(curve) {
+        return Ok(remote);
+    }
+    #[cfg(feature = "ledger")]
+    if curve == CurveType::Secp256k1
+        && let Some(ledger) = LEDGER.get()
+    {
+        return Ok(Wallet::Ledger(ledger.clone()));
+    }
+    load_trader_wallet(curve)
+}
+
+/// [`load_trader_wallet_for_network`], honouring `--ledger` like
+/// [`trader_wallet`].
+#[cfg(feature = "trading")]
+fn trader_wallet_for_network(config: &GetConfigResponse, network: &str) -> Result<Wallet> {
+    let chain = config
+        .get_chain(network)
+        .ok_or_else(|| eyre::eyre!("Chain '{}' not found in server configuration", network))?;
+    trader_wallet(aspens::chain_curve(chain))
+}
+
+/// The wallet for deposits, withdrawals and token association on
+/// `network`: [`load_trader_wallet_for_network`], or the KMS key with
+/// `--kms-key-id`. These send transactions, which a Ledger doesn't sign
+/// here, so `--ledger` leaves them on the local key.
+#[cfg(feature = "trading")]
+fn transacting_wallet_for_network(config: &GetConfigResponse, network: &str) -> Result<Wallet> {
+    if let Some(chain) = config.get_chain(network)
+        && let Some(remote) = remote_wallet(aspens::chain_curve(chain))
+    {
+        return Ok(remote);
+    }
+    load_trader_wallet_for_network(config, network)
+}
+
+/// Clap value parser for `--hd-path`.
+#[cfg(feature = "ledger")]
+fn parse_hd_path_arg(s: &str) -> std::result::Result<HDPath, String> {
+    aspens::ledger::parse_hd_path(s).map_err(|e| e.to_string())
+}
+
+/// Fill the flags left unset from the active profile. Its market and output
+/// format already reached clap through the environment (see
+/// [`aspens_cliutil::activate_profile`]).
+fn apply_profile(cli: &mut Cli, matches: &clap::ArgMatches, profile: &Profile) -> Result<()> {
+    if cli.env_file.is_none() {
+        cli.env_file = profile
+            .env_file_path()
+            .map(|path| path.to_string_lossy().into_owned());
+    }
+    if cli.stack_url.is_none()
+        && let Some(ref url) = profile.stack_url
+    {
+        cli.stack_url = Some(
+            url.parse()
+                .map_err(|e| eyre::eyre!("invalid stack URL '{}' in profile: {}", url, e))?,
+        );
+    }
+    match profile.signer {
+        Some(SignerSource::Keystore(_)) if cli.keystore.is_none() => {
+            cli.keystore = profile.keystore();
+        }
+        Some(SignerSource::Mnemonic(index)) if cli.account_index.is_none() => {
+            cli.account_index = Some(index);
+        }
+        #[cfg(feature = "ledger")]
+        Some(SignerSource::Ledger(ref path)) => {
+            cli.ledger = true;
+            if let Some(path) = path
+                && matches.value_source("hd_path") != Some(clap::parser::ValueSource::CommandLine)
+            {
+                cli.hd_path = aspens::ledger::parse_hd_path(path)?;
+            }
+        }
+        #[cfg(not(feature = "ledger"))]
+        Some(SignerSource::Ledger(_)) => {
+            eyre::bail!(
+                "the profile signs with a Ledger, but this build lacks the `ledger` feature"
+            )
+        }
+        #[cfg(feature = "kms")]
+        Some(SignerSource::Kms(ref key_id)) if cli.kms_key_id.is_none() => {
+            cli.kms_key_id = Some(key_id.clone());
+        }
+        #[cfg(not(feature = "kms"))]
+        Some(SignerSource::Kms(_)) => {
+            eyre::bail!("the profile signs with a KMS key, but this build lacks the `kms` feature")
+        }
+        _ => {}
+    }
+    let _ = matches;
+    Ok(())
+}
+
+/// `aspens-cli profile`. Runs before the client is built, so it works
+/// with no stack configured.
+fn run_profile_command(
+    action: &ProfileAction,
+    cli: &Cli,
+    matches: &clap::ArgMatches,
+) -> Result<()> {
+    let path = aspens::profiles::config_file()?;
+    let mut config = ProfilesConfig::load_from(&path)?;
+    match action {
+        ProfileAction::Add {
+            name,
+            market,
+            signer,
+            default,
+        } => {
+            let output = (matches.value_source("output")
+                == Some(clap::parser::ValueSource::CommandLine))
+            .then(|| cli.output.name().to_string());
+            config.add(
+                name,
+                Profile {
+                    stack_url: cli.stack_url.as_ref().map(|url| url.to_string()),
+                    env_file: cli.env_file.as_ref().map(PathBuf::from),
+                    market: market.clone(),
+                    output,
+                    signer: signer.clone(),
+                },
+            )?;
+            if *default {
+                config.set_default(name)?;
+            }
+            config.save_to(&path)?;
+            println!("Profile '{}' saved to {}", name, path.display());
+        }
+        ProfileAction::List => {
+            println!("Config file: {}", path.display());
+            if config.profiles.is_empty() {
+                println!("No profiles; add one with `aspens-cli profile add`");
+            }
+            for (name, profile) in &config.profiles {
+                let marker = if config.default.as_deref() == Some(name.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{} {}", marker, name);
+                let fields = [
+                    ("stack", profile.stack_url.clone()),
+                    (
+                        "env file",
+                        profile.env_file.as_ref().map(|p| p.display().to_string()),
+                    ),
+                    ("market", profile.market.clone()),
+                    ("output", profile.output.clone()),
+                    ("signer", profile.signer.as_ref().map(|s| s.to_string())),
+                ];
+                for (label, value) in fields {
+                    if let Some(value) = value {
+                        println!("    {}: {}", label, value);
+                    }
+                }
+            }
+        }
+        ProfileAction::Use { name } => {
+            config.set_default(name)?;
+            config.save_to(&path)?;
+            println!("Default profile is now '{}'", name);
+        }
+    }
+    Ok(())
+}
+
+/// [`get_config`](aspens::commands::config::get_config), also saving the
+/// response to the config cache shell completion reads. Saving is
+/// best-effort.
+async fn fetch_config(stack_url: String) -> aspens::error::Result<GetConfigResponse> {
+    let config = aspens::commands::config::get_config(stack_url.clone()).await?;
+    if let Err(e) = aspens::config_cache::save(&stack_url, &config) {
+        tracing::debug!("Could not cache the config: {}", e);
+    }
+    Ok(config)
+}
+
+/// Print the operator's maintenance notice, if the config carries a
+/// current one, to stderr so it stands out from command output.
+fn print_maintenance_banner(config: &GetConfigResponse) {
+    let now = aspens::util::unix_now();
+    if let Some(notice) = config.maintenance().filter(|n| !n.is_over(now)) {
+        eprintln!("*** {} ***", notice.banner(now));
+    }
+}
 
-/// Local thin wrapper over [`aspens_cliutil::format_error`] so existing
+/// Local thin wrapper over [`aspens_cliutil::user_error`] so existing
 /// call sites don't have to pass [`BinaryContext::TRADER_CLI`] explicitly.
-fn format_error(err: &eyre::Report, context: &str) -> String {
-    aspens_cliutil::format_error(err, context, &BinaryContext::TRADER_CLI)
+fn user_error(err: impl Into<eyre::Report>, context: &str) -> eyre::Report {
+    aspens_cliutil::user_error(err, context, &BinaryContext::TRADER_CLI)
 }
 
 /// Decode a hex string (with or without `0x`) for `--{label}`.
+#[cfg(any(feature = "trading", feature = "attestation"))]
 fn parse_hex(label: &str, s: &str) -> Result<Vec<u8>> {
     let s = s.strip_prefix("0x").unwrap_or(s);
     hex::decode(s).map_err(|e| eyre::eyre!("invalid hex for --{label}: {e}"))
 }
 
 /// Decode an optional fixed-width hex value (`N` bytes) for `--{label}`.
+#[cfg(feature = "attestation")]
 fn parse_fixed<const N: usize>(label: &str, s: &Option<String>) -> Result<Option<[u8; N]>> {
     match s {
         None => Ok(None),
@@ -52,6 +342,7 @@ fn parse_fixed<const N: usize>(label: &str, s: &Option<String>) -> Result<Option
 
 /// Parse an `--expected-pubkey <curve>:<hex>` argument into a curve tag + raw
 /// pubkey bytes. Accepts `secp256k1`/`evm`/`k1` and `ed25519`/`solana`/`sol`.
+#[cfg(feature = "attestation")]
 fn parse_expected_pubkey(s: &str) -> Result<(CurveTag, Vec<u8>)> {
     let (curve, hex_str) = s.split_once(':').ok_or_else(|| {
         eyre::eyre!("--expected-pubkey must be `<curve>:<hex>` (e.g. secp256k1:04ab…), got `{s}`")
@@ -74,6 +365,7 @@ fn parse_expected_pubkey(s: &str) -> Result<(CurveTag, Vec<u8>)> {
 
 /// Read a raw TD quote from a file: hex text if the whole (trimmed) file decodes
 /// as hex, otherwise the raw bytes verbatim.
+#[cfg(feature = "attestation")]
 fn read_quote_file(path: &std::path::Path) -> Result<Vec<u8>> {
     let raw = std::fs::read(path)
         .map_err(|e| eyre::eyre!("reading quote file {}: {e}", path.display()))?;
@@ -91,25 +383,38 @@ fn read_quote_file(path: &std::path::Path) -> Result<Vec<u8>> {
     Ok(raw)
 }
 
-/// Shared shape for buy-market / buy-limit / sell-market / sell-limit:
-/// fetch config → load wallets for both chains in the market → submit via
-/// `send_order_with_wallets`. Cross-chain markets that span EVM + Solana
-/// need *both* a Secp256k1 wallet (for the EVM leg's address) and an
-/// Ed25519 wallet (for the Solana leg). The CLI loads each opportunistically
-/// and the lib selects the right one per chain.
 /// Order-flag pair threaded from the command arms into the library call.
 /// Named-field construction at each arm keeps the two same-typed bools
 /// from ever being transposed positionally, and a future flag is one new
 /// field here instead of another positional bool at every call site.
-#[derive(Clone, Copy)]
+#[cfg(feature = "trading")]
+#[derive(Clone)]
 struct OrderFlags {
     /// Reject the order if it would cross at submission (limit only).
     post_only: bool,
     /// Invisible order: excluded from public book exposure; fills print
     /// with this side redacted.
     hidden: bool,
+    /// Queue for the market's next batch auction (limit only).
+    auction: bool,
+    /// Time in force.
+    execution: ExecutionType,
+    /// Good-till-time: cancel whatever still rests this long after sending
+    /// (limit only).
+    expires_in: Option<std::time::Duration>,
+    /// What to do with an amount finer than the market's pair decimals.
+    precision: PrecisionMode,
+    /// Strategy ID, tags and referral code.
+    tags: OrderTags,
 }
 
+/// Shared shape for buy-market / buy-limit / sell-market / sell-limit:
+/// fetch config → load wallets for both chains in the market → submit via
+/// `send_order_with_options`. Cross-chain markets that span EVM + Solana
+/// need *both* a Secp256k1 wallet (for the EVM leg's address) and an
+/// Ed25519 wallet (for the Solana leg). The CLI loads each opportunistically
+/// and the lib selects the right one per chain.
+#[cfg(feature = "trading")]
 fn dispatch_send_order(
     executor: &DirectExecutor,
     client: &AspensClient,
@@ -121,18 +426,26 @@ fn dispatch_send_order(
 ) -> Result<SendOrderResponse> {
     let stack_url = client.stack_url().to_string();
     let config = executor
-        .execute(aspens::commands::config::get_config(stack_url.clone()))
-        .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+        .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+        .map_err(|e| user_error(e, "fetch configuration"))?;
+    print_maintenance_banner(&config);
     // Load both wallets if available. The lib picks whichever one matches
     // each chain's architecture (and errors if neither matches).
-    let evm = load_trader_wallet(CurveType::Secp256k1).ok();
-    let solana = load_trader_wallet(CurveType::Ed25519).ok();
+    let evm = trader_wallet(CurveType::Secp256k1).ok();
+    let solana = trader_wallet(CurveType::Ed25519).ok();
     if evm.is_none() && solana.is_none() {
         return Err(eyre::eyre!(
             "No trader wallet configured. Set TRADER_PRIVKEY (EVM) and/or \
              TRADER_PRIVKEY_SOLANA (Solana) in your .env file."
         ));
     }
+    if let Some(p) = &price {
+        log_fee_estimate(executor, client, &config, &market, &amount, p);
+    }
+    let mut tags = flags.tags;
+    if tags.referral.is_none() {
+        tags.referral = client.referral_code().map(str::to_string);
+    }
     let context = match (side, &price) {
         (Side::Bid, Some(p)) => {
             format!("send limit buy order for {} at {} on {}", amount, p, market)
@@ -147,26 +460,541 @@ fn dispatch_send_order(
         (Side::Ask, None) => format!("send market sell order for {} on {}", amount, market),
         (Side::Unspecified, _) => format!("send order on {}", market),
     };
-    executor
-        .execute(async move {
-            let wallets: Vec<&Wallet> = [evm.as_ref(), solana.as_ref()]
-                .into_iter()
-                .flatten()
-                .collect();
-            send_order::send_order_with_wallets(
-                stack_url,
+    // Checked against the client's risk limits before signing, as
+    // `AspensApi::send_order` does.
+    let risk_limits = client.risk_limits().clone();
+    #[cfg(feature = "journal")]
+    let journaled = (
+        send_order::lookup_market(&config, &market).ok().cloned(),
+        aspens::OrderRequest {
+            market_id: market.clone(),
+            side: side as i32,
+            quantity: amount.clone(),
+            price: price.clone(),
+            post_only: flags.post_only,
+            hidden: flags.hidden,
+            auction: flags.auction,
+            precision: flags.precision,
+            ..Default::default()
+        },
+    );
+    let response = executor
+        .execute_with_timeout(
+            async move {
+                let wallets: Vec<&dyn AspensSigner> = [evm.as_ref(), solana.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .map(|w| w as &dyn AspensSigner)
+                    .collect();
+                send_order::send_order_with_options(
+                    stack_url,
+                    market,
+                    side as i32,
+                    amount,
+                    price,
+                    &wallets,
+                    config,
+                    OrderOptions {
+                        post_only: flags.post_only,
+                        hidden: flags.hidden,
+                        auction: flags.auction,
+                        execution: flags.execution,
+                        expires_at: flags
+                            .expires_in
+                            .map(|d| aspens::util::unix_now().saturating_add(d.as_secs())),
+                        precision: flags.precision,
+                        client_order_id: None,
+                        tags,
+                        risk: risk_limits,
+                    },
+                )
+                .await
+            },
+            client.command_timeout(),
+        )
+        .map_err(|e| user_error(e, &context))?;
+    #[cfg(feature = "journal")]
+    if let (Some(market), order) = &journaled {
+        record_journal(|journal| journal.record_order(order, market, &response));
+    }
+    Ok(response)
+}
+
+/// Record a completed action in the local journal. Best-effort: a journal
+/// that can't be opened or written only logs a warning.
+#[cfg(feature = "journal")]
+fn record_journal(record: impl FnOnce(&Journal) -> Result<()>) {
+    if let Err(e) = Journal::open_default().and_then(|journal| record(&journal)) {
+        tracing::warn!("Could not write to the journal: {e}");
+    }
+}
+
+/// Order preview: log the maker and taker fee a limit order would pay if
+/// it filled in full. Best-effort — a market without published fees falls
+/// back to on-chain reads, and any failure only logs a warning.
+#[cfg(feature = "trading")]
+fn log_fee_estimate(
+    executor: &DirectExecutor,
+    client: &AspensClient,
+    config: &GetConfigResponse,
+    market: &str,
+    amount: &str,
+    price: &str,
+) {
+    let Ok(m) = send_order::lookup_market(config, market) else {
+        return;
+    };
+    let pair_decimals = m.pair_decimals as u32;
+    let (Ok(quantity), Ok(price)) = (
+        aspens::decimals::parse_decimal_amount(amount, pair_decimals),
+        aspens::decimals::parse_decimal_amount(price, pair_decimals),
+    ) else {
+        return;
+    };
+    let (config, m) = (config.clone(), m.clone());
+    let symbol = m.quote_chain_token_symbol.clone();
+    let fees = match executor.execute_with_timeout(
+        async move { fees::market_fees(&config, &m).await },
+        client.command_timeout(),
+    ) {
+        Ok(fees) => fees,
+        Err(e) => {
+            tracing::warn!("Could not estimate fees: {e}");
+            return;
+        }
+    };
+    let [maker, taker] = [FeeRole::Maker, FeeRole::Taker]
+        .map(|role| fees.estimate(role, quantity, price, pair_decimals));
+    info!(
+        "Estimated fee if filled: {} {symbol} as maker ({} bps), {} {symbol} as taker ({} bps)",
+        aspens::decimals::format_decimal_amount(maker.fee, pair_decimals),
+        maker.bps,
+        aspens::decimals::format_decimal_amount(taker.fee, pair_decimals),
+        taker.bps
+    );
+}
+
+/// Run the interactive `aspens-cli rfq` flow: collect dealer quotes until
+/// the request's TTL lapses, list them, and accept the one the user picks.
+#[cfg(feature = "trading")]
+fn run_rfq(
+    executor: &DirectExecutor,
+    client: &AspensClient,
+    market: String,
+    side: Side,
+    size: String,
+    ttl: std::time::Duration,
+) -> Result<()> {
+    let stack_url = client.stack_url().to_string();
+    let config = executor
+        .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+        .map_err(|e| user_error(e, "fetch configuration"))?;
+    print_maintenance_banner(&config);
+    let load_wallets = || {
+        let wallets = [
+            trader_wallet(CurveType::Secp256k1).ok(),
+            trader_wallet(CurveType::Ed25519).ok(),
+        ];
+        if wallets.iter().all(Option::is_none) {
+            return Err(eyre::eyre!(
+                "No trader wallet configured. Set TRADER_PRIVKEY (EVM) and/or \
+                 TRADER_PRIVKEY_SOLANA (Solana) in your .env file."
+            ));
+        }
+        Ok(wallets)
+    };
+    let pair_decimals = send_order::lookup_market(&config, &market)?.pair_decimals as u32;
+
+    info!(
+        "Requesting quotes to {} {size} on {market} (waiting {}s for dealers)",
+        if side == Side::Bid { "buy" } else { "sell" },
+        ttl.as_secs()
+    );
+    let wallets = load_wallets()?;
+    let request_config = config.clone();
+    let context = format!("request quotes for {} on {}", size, market);
+    let quotes = executor
+        .execute_with_timeout(
+            async move {
+                let wallets: Vec<&dyn AspensSigner> = wallets
+                    .iter()
+                    .flatten()
+                    .map(|w| w as &dyn AspensSigner)
+                    .collect();
+                let (mut rx, task) = rfq::request_quotes(
+                    stack_url,
+                    &market,
+                    side as i32,
+                    &size,
+                    &wallets,
+                    &request_config,
+                    ttl,
+                )
+                .await?;
+                let mut quotes = Vec::new();
+                while let Some(quote) = rx.recv().await {
+                    println!(
+                        "  {}. {}",
+                        quotes.len() + 1,
+                        quote.format_for_cli(pair_decimals)
+                    );
+                    quotes.push(quote);
+                }
+                task.await??;
+                Ok::<_, eyre::Report>(quotes)
+            },
+            ttl + client.command_timeout(),
+        )
+        .map_err(|e| user_error(e, &context))?;
+
+    let Some(best) = rfq::best_quote(&quotes, aspens::util::unix_now()) else {
+        info!("No live quotes received");
+        return Ok(());
+    };
+    let best_index = quotes.iter().position(|q| q == best).unwrap_or(0) + 1;
+    print!(
+        "Accept quote [1-{}, enter to decline; best is {}]: ",
+        quotes.len(),
+        best_index
+    );
+    std::io::Write::flush(&mut std::io::stdout())?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    if answer.is_empty() {
+        info!("Declined all quotes");
+        return Ok(());
+    }
+    let quote = answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| quotes.get(i))
+        .cloned()
+        .ok_or_else(|| eyre::eyre!("'{}' is not a quote number", answer))?;
+
+    let wallets = load_wallets()?;
+    let stack_url = client.stack_url().to_string();
+    let context = format!("accept quote {}", quote.quote_id);
+    let result = executor
+        .execute_with_timeout(
+            async move {
+                let wallets: Vec<&dyn AspensSigner> = wallets
+                    .iter()
+                    .flatten()
+                    .map(|w| w as &dyn AspensSigner)
+                    .collect();
+                rfq::accept_quote(stack_url, &quote, &wallets, &config).await
+            },
+            client.command_timeout(),
+        )
+        .map_err(|e| user_error(e, &context))?;
+    info!("Quote accepted (order_id: {})", result.order_id);
+    log_tx_hashes(&result.get_formatted_transaction_hashes());
+    Ok(())
+}
+
+/// Run an `aspens-cli schedule ...` subcommand against the job file in the
+/// profile directory. `schedule run` blocks, submitting due jobs through
+/// the client until interrupted.
+#[cfg(feature = "trading")]
+fn run_schedule_command(
+    executor: &DirectExecutor,
+    client: &AspensClient,
+    action: ScheduleAction,
+    tags: &OrderTags,
+    precision: PrecisionMode,
+) -> Result<()> {
+    let mut jobs = Scheduler::open_default().map_err(|e| user_error(e, "open the schedule"))?;
+    let (side, market, amount, price, timing, flags) = match action {
+        ScheduleAction::BuyLimit {
+            market,
+            amount,
+            price,
+            timing,
+            post_only,
+            hidden,
+            auction,
+        } => (
+            Side::Bid,
+            market,
+            amount,
+            price,
+            timing,
+            OrderFlags {
+                post_only,
+                hidden,
+                auction,
+                execution: ExecutionType::GoodTillCancel,
+                expires_in: None,
+                precision,
+                tags: tags.clone(),
+            },
+        ),
+        ScheduleAction::SellLimit {
+            market,
+            amount,
+            price,
+            timing,
+            post_only,
+            hidden,
+            auction,
+        } => (
+            Side::Ask,
+            market,
+            amount,
+            price,
+            timing,
+            OrderFlags {
+                post_only,
+                hidden,
+                auction,
+                execution: ExecutionType::GoodTillCancel,
+                expires_in: None,
+                precision,
+                tags: tags.clone(),
+            },
+        ),
+        ScheduleAction::List => {
+            if jobs.jobs().is_empty() {
+                info!("No scheduled jobs in {}", jobs.path().display());
+            }
+            for job in jobs.jobs() {
+                info!(
+                    "#{} {} {} @ {} on {} ({}, next run in {}s, {} runs so far)",
+                    job.id,
+                    if job.side == Side::Bid as i32 {
+                        "BUY"
+                    } else {
+                        "SELL"
+                    },
+                    job.quantity,
+                    job.price.as_deref().unwrap_or("market"),
+                    job.market_id,
+                    job.schedule,
+                    job.next_run.saturating_sub(aspens::util::unix_now()),
+                    job.runs
+                );
+            }
+            return Ok(());
+        }
+        ScheduleAction::Remove { id } => {
+            if !jobs
+                .remove(id)
+                .map_err(|e| user_error(e, "update the schedule"))?
+            {
+                return Err(eyre::eyre!("no scheduled job with id {}", id));
+            }
+            info!("Removed scheduled job {id}");
+            return Ok(());
+        }
+        ScheduleAction::Run => {
+            let evm = trader_wallet(CurveType::Secp256k1).ok();
+            let solana = trader_wallet(CurveType::Ed25519).ok();
+            if evm.is_none() && solana.is_none() {
+                return Err(eyre::eyre!(
+                    "No trader wallet configured. Set TRADER_PRIVKEY (EVM) and/or \
+                     TRADER_PRIVKEY_SOLANA (Solana) in your .env file."
+                ));
+            }
+            info!(
+                "Running {} scheduled job(s) from {} (Ctrl-C to stop)",
+                jobs.jobs().len(),
+                jobs.path().display()
+            );
+            let client = client.clone();
+            return executor
+                .execute(async move {
+                    let wallets: Vec<&Wallet> = [evm.as_ref(), solana.as_ref()]
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                    jobs.run(&client, &wallets).await
+                })
+                .map_err(|e| user_error(e, "run scheduled jobs"));
+        }
+    };
+
+    let schedule = match (timing.cron, timing.every) {
+        (Some(expr), _) => Schedule::cron(expr),
+        (None, Some(every)) => Schedule::every(every),
+        (None, None) => unreachable!("clap requires --cron or --every"),
+    };
+    let order = aspens::api::OrderRequest {
+        market_id: market,
+        side: side as i32,
+        quantity: amount,
+        price: Some(price),
+        post_only: flags.post_only,
+        hidden: flags.hidden,
+        auction: flags.auction,
+        execution: flags.execution,
+        expires_at: None,
+        precision: flags.precision,
+        client_order_id: None,
+        tags: flags.tags,
+    };
+    let description = schedule.to_string();
+    let now = aspens::util::unix_now();
+    let id = jobs
+        .add(schedule, order, now)
+        .map_err(|e| user_error(e, "schedule the order"))?;
+    let next_in = jobs
+        .jobs()
+        .iter()
+        .find(|j| j.id == id)
+        .map_or(0, |j| j.next_run.saturating_sub(now));
+    info!(
+        "Scheduled job {id} ({description}); next run in {next_in}s. \
+         Jobs only fire while `aspens-cli schedule run` is running."
+    );
+    Ok(())
+}
+
+/// Parse a `--override key=value` pair for `preset run`.
+#[cfg(feature = "trading")]
+fn parse_override(s: &str) -> std::result::Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .filter(|(k, _)| !k.is_empty())
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{s}'"))
+}
+
+/// Run an `aspens-cli preset ...` subcommand against the preset file in the
+/// profile directory.
+#[cfg(feature = "trading")]
+fn run_preset_command(
+    executor: &DirectExecutor,
+    client: &AspensClient,
+    action: PresetAction,
+    tags: &OrderTags,
+    precision: PrecisionMode,
+) -> Result<()> {
+    let mut store =
+        PresetStore::open_default().map_err(|e| user_error(e, "open the preset file"))?;
+    match action {
+        PresetAction::Save {
+            name,
+            market,
+            side,
+            amount,
+            price,
+            price_offset,
+            post_only,
+            hidden,
+            auction,
+            tif,
+        } => {
+            let preset = OrderPreset {
                 market,
-                side as i32,
+                side,
                 amount,
                 price,
-                &wallets,
-                config,
-                flags.post_only,
-                flags.hidden,
-            )
-            .await
-        })
-        .map_err(|e| eyre::eyre!(format_error(&e, &context)))
+                price_offset_bps: price_offset,
+                post_only,
+                hidden,
+                auction,
+                execution: tif,
+                strategy: tags.strategy.clone(),
+                tags: tags.tags.clone(),
+            };
+            store
+                .save(&name, preset)
+                .map_err(|e| user_error(e, &format!("save preset {name}")))?;
+            info!("Saved preset '{name}' to {}", store.path().display());
+        }
+        PresetAction::List => {
+            if store.presets().is_empty() {
+                info!("No presets in {}", store.path().display());
+            }
+            for (name, preset) in store.presets() {
+                let price = match (&preset.price, preset.price_offset_bps) {
+                    (Some(price), _) => price.clone(),
+                    (None, Some(bps)) => format!("mid {bps:+} bps"),
+                    (None, None) => "market".to_string(),
+                };
+                info!(
+                    "{name}: {} {} @ {} on {}{}{}{}",
+                    preset.side,
+                    preset.amount,
+                    price,
+                    preset.market,
+                    if preset.post_only { " post-only" } else { "" },
+                    if preset.hidden { " hidden" } else { "" },
+                    if preset.auction { " auction" } else { "" }
+                );
+            }
+        }
+        PresetAction::Remove { name } => {
+            if !store
+                .remove(&name)
+                .map_err(|e| user_error(e, "update the preset file"))?
+            {
+                return Err(eyre::eyre!("no preset named '{}'", name));
+            }
+            info!("Removed preset '{name}'");
+        }
+        PresetAction::Run { name, overrides } => {
+            let context = format!("run preset {name}");
+            let mut preset = store
+                .get(&name)
+                .map_err(|e| user_error(e, &context))?
+                .clone();
+            for (key, value) in &overrides {
+                preset
+                    .set(key, value)
+                    .map_err(|e| user_error(e, &context))?;
+            }
+            // --strategy / --tag on the command line replace the saved ones.
+            if !tags.is_empty() {
+                preset.strategy = tags.strategy.clone();
+                preset.tags = tags.tags.clone();
+            }
+            let side = preset.side().map_err(|e| user_error(e, &context))?;
+
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let order = executor
+                .execute_with_timeout(
+                    async move { preset.resolve(stack_url, &config).await },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            info!(
+                "Preset '{name}': {} {} at {} on {}",
+                if side == Side::Bid { "BUY" } else { "SELL" },
+                order.quantity,
+                order.price.as_deref().unwrap_or("market"),
+                order.market_id
+            );
+            let result = dispatch_send_order(
+                executor,
+                client,
+                order.market_id,
+                side,
+                order.quantity,
+                order.price,
+                OrderFlags {
+                    post_only: order.post_only,
+                    hidden: order.hidden,
+                    auction: order.auction,
+                    execution: order.execution,
+                    expires_in: None,
+                    precision,
+                    tags: order.tags,
+                },
+            )?;
+            info!(
+                "Preset order sent successfully (order_id: {})",
+                result.order_id
+            );
+            log_tx_hashes(&result.get_formatted_transaction_hashes());
+        }
+    }
+    Ok(())
 }
 
 /// Resolve a slippage-capped limit price for the `buy-marketable` /
@@ -189,6 +1017,115 @@ fn dispatch_send_order(
 /// ceiling / floor that the contract can verify, and the slippage
 /// cap is how the user controls "how aggressively will I cross the
 /// spread".
+/// Handle `aspens-cli build-tx`: resolve the transaction against the stack
+/// and chain, and write it unsigned for `sign`.
+#[cfg(feature = "trading")]
+fn run_build_tx(
+    executor: &DirectExecutor,
+    client: &AspensClient,
+    action: BuildTxAction,
+) -> Result<()> {
+    let (kind, args) = match &action {
+        BuildTxAction::Deposit(args) => ("deposit", args),
+        BuildTxAction::Approve(args) => ("approve", args),
+        BuildTxAction::Withdraw { args, .. } => ("withdraw", args),
+    };
+    let BuildTxArgs {
+        network,
+        token,
+        amount,
+        from,
+        out,
+    } = args.clone();
+    let context = format!("build {} {} {} on {}", kind, amount, token, network);
+
+    let from = match from {
+        Some(from) => from,
+        None => {
+            let wallet = trader_wallet(CurveType::Secp256k1).map_err(|e| {
+                eyre::eyre!(
+                    "{}\n\n\
+                     Hints:\n\
+                     - Pass --from with the signing account's address",
+                    e
+                )
+            })?;
+            wallet.address()
+        }
+    };
+    let from = aspens::util::parse_address(&from)?;
+
+    let stack_url = client.stack_url().to_string();
+    let config = executor
+        .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+        .map_err(|e| user_error(e, "fetch configuration"))?;
+    print_maintenance_banner(&config);
+    let amount_base = resolve_token_amount(&config, &network, &token, &amount)
+        .map_err(|e| user_error(e, &context))?;
+
+    let tx = match action {
+        BuildTxAction::Deposit(_) => {
+            executor.execute_with_timeout(
+                async move {
+                    offline::unsigned_deposit(network, token, amount_base, from, &config).await
+                },
+                client.command_timeout(),
+            )
+        }
+        BuildTxAction::Approve(_) => {
+            executor.execute_with_timeout(
+                async move {
+                    offline::unsigned_approve(network, token, amount_base, from, &config).await
+                },
+                client.command_timeout(),
+            )
+        }
+        BuildTxAction::Withdraw {
+            request_signature, ..
+        } => {
+            let Some(signature) = request_signature else {
+                let message =
+                    offline::withdraw_request_message(&network, &token, amount_base, from, &config)
+                        .map_err(|e| user_error(e, &context))?;
+                println!("A withdrawal voucher must be requested with the account's signature.");
+                println!("On the signing machine, run:");
+                println!("  aspens-cli sign --withdraw-request '{}'", message);
+                println!("then re-run this command with --request-signature <HEX>.");
+                return Ok(());
+            };
+            let signature = parse_hex("request-signature", &signature)?;
+            executor.execute_with_timeout(
+                async move {
+                    offline::unsigned_withdraw(
+                        stack_url,
+                        network,
+                        token,
+                        amount_base,
+                        from,
+                        signature,
+                        &config,
+                    )
+                    .await
+                },
+                client.command_timeout(),
+            )
+        }
+    }
+    .map_err(|e| user_error(e, &context))?;
+
+    tx.write(&out)?;
+    println!("Unsigned transaction written to {}", out.display());
+    println!("  {}", tx.description);
+    println!(
+        "Sign it offline with 'aspens-cli sign -f {} -o signed.hex', then send it with \
+         'aspens-cli broadcast -f signed.hex --network {}'",
+        out.display(),
+        tx.network
+    );
+    Ok(())
+}
+
+#[cfg(feature = "trading")]
 fn resolve_marketable_price(
     executor: &DirectExecutor,
     client: &AspensClient,
@@ -205,20 +1142,24 @@ fn resolve_marketable_price(
     // surface consistent with what users see from the buy-limit /
     // sell-limit commands.
     let config = executor
-        .execute(aspens::commands::config::get_config(stack_url.clone()))
-        .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+        .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+        .map_err(|e| user_error(e, "fetch configuration"))?;
+    print_maintenance_banner(&config);
     let market = send_order::lookup_market(&config, market_id)
-        .map_err(|e| eyre::eyre!(format_error(&e, &format!("look up market {market_id}"))))?;
+        .map_err(|e| user_error(e, &format!("look up market {market_id}")))?;
     let pair_decimals = market.pair_decimals as u32;
 
     let collection_window = std::time::Duration::from_millis(1_500);
     let top = executor
-        .execute(stream_orderbook::fetch_top_of_book(
-            stack_url,
-            market.market_id.clone(),
-            collection_window,
-        ))
-        .map_err(|e| eyre::eyre!(format_error(&e, "fetch top-of-book")))?;
+        .execute_with_timeout(
+            stream_orderbook::fetch_top_of_book(
+                stack_url,
+                market.market_id.clone(),
+                collection_window,
+            ),
+            client.command_timeout(),
+        )
+        .map_err(|e| user_error(e, "fetch top-of-book"))?;
 
     let (is_buy, reference, label) = match side {
         Side::Bid => (
@@ -258,6 +1199,7 @@ fn resolve_marketable_price(
 
 /// Local thin wrapper over [`aspens_cliutil::resolve_token_amount`].
 /// Kept so existing call sites don't have to change.
+#[cfg(feature = "trading")]
 fn resolve_token_amount(
     config: &GetConfigResponse,
     network: &str,
@@ -267,7 +1209,101 @@ fn resolve_token_amount(
     aspens_cliutil::resolve_token_amount(config, network, token_symbol, amount)
 }
 
+/// Base units for a deposit or withdraw AMOUNT, and the amount in token
+/// units to report: [`MAX_AMOUNT`] is resolved by the `max` query, anything
+/// else is scaled by the token's decimals.
+#[cfg(feature = "trading")]
+fn resolve_transfer_amount<F>(
+    executor: &DirectExecutor,
+    client: &AspensClient,
+    config: &GetConfigResponse,
+    (network, token, amount): (&str, &str, &str),
+    max: F,
+) -> Result<(u128, String)>
+where
+    F: Future<Output = std::result::Result<u128, aspens::AspensError>> + Send + 'static,
+{
+    if amount != MAX_AMOUNT {
+        let base = resolve_token_amount(config, network, token, amount)?;
+        return Ok((base, amount.to_string()));
+    }
+    let base = executor.execute_with_timeout(max, client.command_timeout())?;
+    let decimals = config.get_token(network, token).map_or(0, |t| t.decimals);
+    let amount = aspens::decimals::format_decimal_amount(base, decimals);
+    info!("Maximum is {} {}", amount, token);
+    Ok((base, amount))
+}
+
+/// Parse a side argument into the orderbook stream's `Side`, which the
+/// book queries take. Each trading module carries its own copy of the
+/// proto enums, so [`parse_side`]'s order `Side` doesn't fit them.
+#[cfg(feature = "trading")]
+fn parse_book_side(s: &str) -> Result<stream_orderbook::arborter_pb::Side> {
+    Ok(match parse_side(s)? {
+        Side::Bid => stream_orderbook::arborter_pb::Side::Bid,
+        Side::Ask => stream_orderbook::arborter_pb::Side::Ask,
+        Side::Unspecified => stream_orderbook::arborter_pb::Side::Unspecified,
+    })
+}
+
+/// Print one `stream-orderbook` event: a table line, or under `--output
+/// json` a JSON Lines record. With a local book the event is folded in
+/// first and the book's indicators printed with it.
+#[cfg(feature = "streaming")]
+fn print_orderbook_event(
+    entry: &stream_orderbook::arborter_pb::OrderbookEntry,
+    book: Option<&mut (OrderBook, usize)>,
+    output: OutputFormat,
+    simulated_ms: Option<u64>,
+) {
+    let indicators = book.map(|(book, depth)| {
+        book.apply(entry);
+        book.indicators(*depth)
+    });
+    if output == OutputFormat::Json {
+        // Serialized straight to a string: depths are u128, which a
+        // `serde_json::Value` can't hold.
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Record<'a> {
+            event: &'a stream_orderbook::arborter_pb::OrderbookEntry,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            indicators: Option<stream_orderbook::BookIndicators>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            simulated_ms: Option<u64>,
+        }
+        let record = Record {
+            event: entry,
+            indicators,
+            simulated_ms,
+        };
+        match serde_json::to_string(&record) {
+            Ok(line) => println!("{line}"),
+            Err(e) => tracing::warn!("Skipping an orderbook event that failed to serialize: {e}"),
+        }
+        return;
+    }
+    let line = stream_orderbook::format_orderbook_entry(entry);
+    match simulated_ms {
+        Some(ms) => println!("[sim {ms}] {line}"),
+        None => println!("{line}"),
+    }
+    if let Some(ind) = indicators {
+        let price = |p: Option<f64>| p.map_or_else(|| "-".to_string(), |p| format!("{p:.2}"));
+        println!(
+            "    depth {} / {} | imbalance {} | mid {} | microprice {}",
+            ind.bid_depth,
+            ind.ask_depth,
+            ind.imbalance
+                .map_or_else(|| "-".to_string(), |i| format!("{i:+.3}")),
+            price(ind.mid),
+            price(ind.microprice),
+        );
+    }
+}
+
 /// Print the transaction-hash footer that all order/cancel commands share.
+#[cfg(feature = "trading")]
 fn log_tx_hashes(formatted: &[String]) {
     if formatted.is_empty() {
         return;
@@ -291,6 +1327,108 @@ struct Cli {
     #[arg(short = 'e', long = "env-file", global = true)]
     env_file: Option<String>,
 
+    /// Profile from the config file (~/.config/aspens/config.toml) whose
+    /// stack URL, env file, market, output format and signer to default to;
+    /// defaults to ASPENS_PROFILE, then the config file's default
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// How to print results: "table" (default), "json", "plain"
+    /// (tab-separated) or "csv". With all but table, logs go to stderr
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        env = "ASPENS_OUTPUT",
+        default_value = "table"
+    )]
+    output: OutputFormat,
+
+    /// How to report a failure on stderr: "text" (default) or "json" (one
+    /// object with kind, exit_code and message). The exit code names the
+    /// kind either way: 2 connection, 3 auth, 4 not found, 5 insufficient
+    /// balance, 6 invalid input, 7 protocol, 8 chain, 9 risk limit, 1 other
+    #[arg(
+        long,
+        global = true,
+        value_name = "FORMAT",
+        env = "ASPENS_ERROR_FORMAT",
+        default_value = "text"
+    )]
+    error_format: ErrorFormat,
+
+    /// Per-command timeout for non-streaming commands, e.g. `30s` or `5m`;
+    /// a bare number is seconds and 0 disables it
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration_arg)]
+    timeout: Option<std::time::Duration>,
+
+    /// Blocks a deposit or withdraw transaction waits for once mined,
+    /// counting its own block (default 1)
+    #[arg(long, global = true, value_name = "N", env = "ASPENS_CONFIRMATIONS")]
+    confirmations: Option<u64>,
+
+    /// How long a deposit or withdraw waits for those confirmations, e.g.
+    /// `10m` (default 2m; 0 waits indefinitely). Stretches --timeout for
+    /// those commands when longer
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration_arg)]
+    confirmation_timeout: Option<std::time::Duration>,
+
+    /// Strategy ID to attribute submitted orders to
+    #[arg(long, global = true, value_name = "ID")]
+    strategy: Option<String>,
+
+    /// Free-form tag for submitted orders; repeat for several
+    #[arg(long = "tag", global = true, value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Referral code to attribute submitted orders to (overrides
+    /// ASPENS_REFERRAL_CODE from .env)
+    #[arg(long, global = true, value_name = "CODE")]
+    referral: Option<String>,
+
+    /// What to do with an order quantity or price that has more decimals
+    /// than the market's pair decimals: "error" (default), "round" (half to
+    /// even) or "truncate"
+    #[arg(long, global = true, value_name = "MODE", default_value = "error")]
+    precision: PrecisionMode,
+
+    /// Encrypted keystore file (Web3 Secret Storage JSON) holding the EVM
+    /// trader key, used instead of TRADER_PRIVKEY. The passphrase is
+    /// prompted for unless ASPENS_KEYSTORE_PASSPHRASE is set
+    #[arg(long, global = true, value_name = "PATH", env = "TRADER_KEYSTORE")]
+    keystore: Option<std::path::PathBuf>,
+
+    /// Account to derive from TRADER_MNEMONIC (BIP-44 path
+    /// m/44'/60'/0'/0/N), for running many trading accounts off one phrase
+    #[arg(long, global = true, value_name = "N", env = "TRADER_ACCOUNT_INDEX")]
+    account_index: Option<u32>,
+
+    /// Sign orders and cancels on a Ledger device instead of with
+    /// TRADER_PRIVKEY, confirming each on the device. Deposits and
+    /// withdrawals still need a local key
+    #[cfg(feature = "ledger")]
+    #[arg(long, global = true)]
+    ledger: bool,
+
+    /// Ledger derivation path: live:N (Ledger Live account N), legacy:N or
+    /// a full m/44'/60'/... path
+    #[cfg(feature = "ledger")]
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        default_value = "live:0",
+        value_parser = parse_hd_path_arg
+    )]
+    hd_path: HDPath,
+
+    /// AWS KMS key (ID, alias or ARN) holding the EVM trader key, used
+    /// instead of TRADER_PRIVKEY for orders, deposits and withdrawals.
+    /// Credentials come from the standard AWS provider chain
+    #[cfg(feature = "kms")]
+    #[arg(long, global = true, value_name = "KEY", env = "TRADER_KMS_KEY_ID")]
+    kms_key_id: Option<String>,
+
     #[command(flatten)]
     verbose: clap_verbosity::Verbosity<clap_verbosity::InfoLevel>,
 
@@ -305,41 +1443,177 @@ struct Cli {
 #[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Fetch and display the configuration from the server
+    #[cfg(feature = "config-export")]
     Config {
         /// Optional path to save the configuration file (supports .json or .toml)
         #[arg(short, long)]
         output_file: Option<String>,
     },
     /// Deposit tokens to make them available for trading (requires NETWORK TOKEN AMOUNT)
+    #[cfg(feature = "trading")]
     Deposit {
         /// The network name to deposit to (e.g., anvil-1, base-sepolia)
+        #[arg(add = ArgValueCompleter::new(completions::complete_network))]
         network: String,
         /// Token symbol to deposit (e.g., USDC, WETH, WBTC)
+        #[arg(add = ArgValueCompleter::new(completions::complete_token))]
         token: String,
-        /// Amount in human-readable units (e.g., "10", "10.5"). Scaled
-        /// by the token's `decimals` from the chain config.
+        /// Amount in human-readable units (e.g., "10", "10.5"), scaled
+        /// by the token's `decimals` from the chain config, or `max` for
+        /// the whole wallet balance, less gas
+        #[arg(value_parser = parse_amount_or_max_arg)]
         amount: String,
+        /// Hedera only: associate the wallet with the token first, if it
+        /// isn't already
+        #[arg(long, default_value_t = false)]
+        associate: bool,
+    },
+    /// Run the deposits listed in a TOML file (`[[deposit]]` tables with
+    /// network, token and amount), different networks concurrently, and
+    /// report each one's outcome
+    #[cfg(feature = "trading")]
+    DepositBatch {
+        /// The deposits file
+        #[arg(long, short)]
+        file: PathBuf,
     },
     /// Withdraw tokens to a local wallet (requires NETWORK TOKEN AMOUNT)
+    #[cfg(feature = "trading")]
     Withdraw {
         /// The network name to withdraw from (e.g., anvil-1, base-sepolia)
+        #[arg(add = ArgValueCompleter::new(completions::complete_network))]
         network: String,
         /// Token symbol to withdraw (e.g., USDC, WETH, WBTC)
+        #[arg(add = ArgValueCompleter::new(completions::complete_token))]
         token: String,
-        /// Amount in human-readable units (e.g., "10", "10.5"). Scaled
-        /// by the token's `decimals` from the chain config.
+        /// Amount in human-readable units (e.g., "10", "10.5"), scaled
+        /// by the token's `decimals` from the chain config, or `max` for
+        /// the whole available trade balance
+        #[arg(value_parser = parse_amount_or_max_arg)]
         amount: String,
         /// Solana WSOL (native SOL) only: keep the withdrawn funds as WSOL
         /// instead of unwrapping. By default the WSOL ATA is closed after the
         /// withdraw, converting its ENTIRE wrapped balance + rent back to SOL.
         #[arg(long, default_value_t = false)]
         no_unwrap: bool,
+        /// EVM only: send the withdrawn funds on to this address (e.g. a
+        /// cold wallet) in a second transaction, instead of keeping them in
+        /// the trader wallet
+        #[arg(long, value_name = "ADDRESS", value_parser = parse_address_arg)]
+        to: Option<String>,
+    },
+    /// Show how much of an ERC-20 token the trade contract (or Permit2) may
+    /// take from the trader wallet
+    #[cfg(feature = "trading")]
+    Allowance {
+        /// The network name (e.g., anvil-1, base-sepolia)
+        #[arg(add = ArgValueCompleter::new(completions::complete_network))]
+        network: String,
+        /// Token symbol (e.g., USDC, WETH, WBTC)
+        #[arg(add = ArgValueCompleter::new(completions::complete_token))]
+        token: String,
+        /// Who may spend: trade-contract or permit2
+        #[arg(long, default_value_t = Spender::TradeContract)]
+        spender: Spender,
+        /// Wallet whose allowance to read (default: the trader wallet)
+        #[arg(long, value_parser = parse_address_arg)]
+        owner: Option<String>,
+    },
+    /// Let the trade contract (or Permit2) take up to AMOUNT of an ERC-20
+    /// token from the trader wallet, replacing the current allowance
+    #[cfg(feature = "trading")]
+    Approve {
+        /// The network name (e.g., anvil-1, base-sepolia)
+        #[arg(add = ArgValueCompleter::new(completions::complete_network))]
+        network: String,
+        /// Token symbol (e.g., USDC, WETH, WBTC)
+        #[arg(add = ArgValueCompleter::new(completions::complete_token))]
+        token: String,
+        /// Amount in human-readable units (e.g., "100"), `max` for an
+        /// unlimited approval, or 0 to revoke
+        #[arg(value_parser = parse_amount_or_max_arg)]
+        amount: String,
+        /// Who may spend: trade-contract or permit2
+        #[arg(long, default_value_t = Spender::TradeContract)]
+        spender: Spender,
+    },
+    /// Associate the trader wallet with a Hedera token, which Hedera requires
+    /// before the wallet can hold, deposit or withdraw it
+    #[cfg(feature = "trading")]
+    AssociateToken {
+        /// The Hedera network name (e.g., hedera-testnet)
+        #[arg(add = ArgValueCompleter::new(completions::complete_network))]
+        network: String,
+        /// Token symbol, or a Hedera token ID such as 0.0.12345
+        #[arg(add = ArgValueCompleter::new(completions::complete_token))]
+        token: String,
+    },
+    /// Build an unsigned deposit, approve or withdraw transaction file, to
+    /// sign on an offline machine with `sign`
+    #[cfg(feature = "trading")]
+    BuildTx {
+        #[command(subcommand)]
+        action: BuildTxAction,
+    },
+    /// Sign a transaction file from `build-tx` (or `aspens-admin
+    /// deploy-contract --unsigned-out`) without network access
+    #[cfg(feature = "trading")]
+    Sign {
+        /// Unsigned transaction file to sign
+        #[arg(short, long, required_unless_present = "withdraw_request")]
+        file: Option<PathBuf>,
+        /// Sign this withdrawal voucher request, printed by `build-tx
+        /// withdraw`, instead of a transaction
+        #[arg(long, value_name = "MESSAGE", conflicts_with = "file")]
+        withdraw_request: Option<String>,
+        /// Write the signature as hex to this file instead of printing it
+        #[arg(short, long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        /// Environment variable holding the signing key (hex)
+        #[arg(long, value_name = "VAR", default_value = "TRADER_PRIVKEY")]
+        key_env: String,
+    },
+    /// Broadcast a transaction signed with `sign`
+    #[cfg(feature = "trading")]
+    Broadcast {
+        /// File holding the signed transaction as hex
+        #[arg(short, long)]
+        file: PathBuf,
+        /// The network to broadcast on (e.g., base-sepolia)
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_network))]
+        network: String,
+    },
+    /// Show whether an EVM transaction is pending, mined or unknown to the
+    /// node, with its nonce, fees and confirmations
+    #[cfg(feature = "trading")]
+    TxStatus {
+        /// The transaction hash
+        hash: String,
+        /// The network it was sent on (e.g., base-sepolia)
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_network))]
+        network: String,
+    },
+    /// Send a stuck EVM transaction from the trader wallet again with the
+    /// same nonce and higher fees, replacing it
+    #[cfg(feature = "trading")]
+    TxBump {
+        /// The pending transaction's hash
+        hash: String,
+        /// The network it was sent on (e.g., base-sepolia)
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_network))]
+        network: String,
+        /// How much to raise the fees, in percent (at least 10)
+        #[arg(long, default_value_t = StuckPolicy::default().fee_bump_percent)]
+        percent: u64,
     },
     /// Send a market BUY order (executes at best available price)
+    #[cfg(feature = "trading")]
     BuyMarket {
         /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Amount to buy
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Invisible order: your fills print in the public trade stream
         /// with your side's identity redacted. A market order never
@@ -347,14 +1621,22 @@ enum Commands {
         /// effect here is anonymous taking.
         #[arg(long, default_value_t = false)]
         hidden: bool,
+        /// Time in force: `gtc` (default), `ioc` (cancel whatever
+        /// doesn't fill on arrival) or `fok` (fill in full or not at all)
+        #[arg(long, value_name = "TIF", default_value_t = ExecutionType::GoodTillCancel)]
+        tif: ExecutionType,
     },
     /// Send a limit BUY order (executes at specified price or better)
+    #[cfg(feature = "trading")]
     BuyLimit {
         /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Amount to buy
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Limit price for the order
+        #[arg(value_parser = parse_amount_arg)]
         price: String,
         /// Post-only: reject the order if it would cross at submission.
         /// Guarantees you pay the maker side of the fee schedule and
@@ -367,24 +1649,49 @@ enum Commands {
         /// returned order id.
         #[arg(long, default_value_t = false)]
         hidden: bool,
+        /// Batch-auction order: queue for the market's next periodic
+        /// auction and fill at its uniform clearing price instead of
+        /// matching on arrival. See `auction status`.
+        #[arg(long, default_value_t = false)]
+        auction: bool,
+        /// Time in force: `gtc` (default, rest until filled or
+        /// canceled), `ioc` (cancel whatever doesn't fill on arrival),
+        /// `fok` (fill in full on arrival or not at all) or `post-only`
+        /// (same as --post-only)
+        #[arg(long, value_name = "TIF", default_value_t = ExecutionType::GoodTillCancel)]
+        tif: ExecutionType,
+        /// Good-till-time: the stack cancels whatever is still resting this
+        /// long after sending, e.g. `30s`, `5m` or `2h`
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+        expires_in: Option<std::time::Duration>,
     },
     /// Send a market SELL order (executes at best available price)
+    #[cfg(feature = "trading")]
     SellMarket {
         /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Amount to sell
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Invisible order: see `buy-market --hidden`.
         #[arg(long, default_value_t = false)]
         hidden: bool,
+        /// Time in force: see `buy-market --tif`.
+        #[arg(long, value_name = "TIF", default_value_t = ExecutionType::GoodTillCancel)]
+        tif: ExecutionType,
     },
     /// Send a limit SELL order (executes at specified price or better)
+    #[cfg(feature = "trading")]
     SellLimit {
         /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Amount to sell
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Limit price for the order
+        #[arg(value_parser = parse_amount_arg)]
         price: String,
         /// Post-only: see `buy-limit --post-only`.
         #[arg(long)]
@@ -392,20 +1699,32 @@ enum Commands {
         /// Invisible order: see `buy-limit --hidden`.
         #[arg(long, default_value_t = false)]
         hidden: bool,
+        /// Batch-auction order: see `buy-limit --auction`.
+        #[arg(long, default_value_t = false)]
+        auction: bool,
+        /// Time in force: see `buy-limit --tif`.
+        #[arg(long, value_name = "TIF", default_value_t = ExecutionType::GoodTillCancel)]
+        tif: ExecutionType,
+        /// Good-till-time: see `buy-limit --expires-in`.
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+        expires_in: Option<std::time::Duration>,
     },
     /// Marketable BUY: snapshot the resting book, cap slippage off the
     /// best ask, submit as a buy-limit. The gasless cross-chain
     /// protocol rejects true market orders (no honest amount to sign at
     /// price-unknown time) — this helper turns "take the top of book
     /// with a 0.5% slippage cap" into the equivalent priced order.
+    #[cfg(feature = "trading")]
     BuyMarketable {
         /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Amount to buy (human-readable)
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
-        /// Maximum slippage above best ask, in basis points
-        /// (10_000 = 100%). Default 50 = 0.5%.
-        #[arg(long, default_value_t = 50)]
+        /// Maximum slippage above best ask, as basis points (`50`,
+        /// `50bps`; 10_000 = 100%) or a percentage (`0.5%`). Default 50 = 0.5%.
+        #[arg(long, alias = "max-slippage", default_value_t = 50, value_parser = parse_bps_arg)]
         slippage_bps: u32,
         /// Invisible order: the synthesized limit order is hidden — fills
         /// print with your side redacted, and any unfilled remainder
@@ -416,75 +1735,364 @@ enum Commands {
     /// Marketable SELL: snapshot the resting book, cap slippage off
     /// the best bid, submit as a sell-limit. See `buy-marketable` for
     /// the rationale.
+    #[cfg(feature = "trading")]
     SellMarketable {
         /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Amount to sell (human-readable)
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
-        /// Maximum slippage below best bid, in basis points
-        /// (10_000 = 100%). Default 50 = 0.5%.
-        #[arg(long, default_value_t = 50)]
+        /// Maximum slippage below best bid, as basis points (`50`,
+        /// `50bps`; 10_000 = 100%) or a percentage (`0.5%`). Default 50 = 0.5%.
+        #[arg(long, alias = "max-slippage", default_value_t = 50, value_parser = parse_bps_arg)]
         slippage_bps: u32,
         /// Invisible order: see `buy-marketable --hidden`.
         #[arg(long, default_value_t = false)]
         hidden: bool,
     },
     /// Cancel an existing order by its ID
+    #[cfg(feature = "trading")]
     CancelOrder {
         /// Market ID the order is on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Order side: "buy" or "sell"
         side: String,
         /// The internal order ID to cancel
         order_id: u64,
     },
+    /// Move a resting limit order to a new price or amount: cancel it,
+    /// then send the replacement once the cancel is confirmed. Nothing is
+    /// sent if the original already filled.
+    #[cfg(feature = "trading")]
+    Replace {
+        /// Market ID the order is on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Side of the order: "buy" or "sell"
+        side: String,
+        /// The internal order ID to replace
+        order_id: u64,
+        /// New amount
+        #[arg(value_parser = parse_amount_arg)]
+        amount: String,
+        /// New limit price
+        #[arg(value_parser = parse_amount_arg)]
+        price: String,
+        /// Post-only: see `buy-limit --post-only`.
+        #[arg(long)]
+        post_only: bool,
+        /// Invisible order: see `buy-limit --hidden`.
+        #[arg(long, default_value_t = false)]
+        hidden: bool,
+    },
+    /// Cancel all your resting orders on a market
+    #[cfg(feature = "trading")]
+    CancelAll {
+        /// Market ID to cancel orders on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+    },
+    /// List your resting orders on a market
+    #[cfg(feature = "trading")]
+    Orders {
+        /// Market ID to list orders on
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Trader address to list (defaults to the trader wallet on the
+        /// market's base chain)
+        #[arg(long, short = 't', value_parser = parse_address_arg)]
+        trader: Option<String>,
+    },
+    /// Show how much you can buy or sell on a market before crossing a
+    /// price, from the current book
+    #[cfg(feature = "trading")]
+    Depth {
+        /// Market ID to size against
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Limit price to size up to
+        #[arg(long, value_parser = parse_amount_arg)]
+        price: String,
+        /// Only answer for "buy" or "sell" (defaults to both)
+        #[arg(long)]
+        side: Option<String>,
+    },
+    /// Preview a market order: the average and worst price it would fill
+    /// at on the current book, and what the book can't fill
+    #[cfg(feature = "trading")]
+    Quote {
+        /// Market ID to quote on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// "buy" or "sell"
+        side: String,
+        /// Quantity to fill
+        #[arg(value_parser = parse_amount_arg)]
+        quantity: String,
+    },
+    /// Show a market's resting book as a price ladder, asks above bids
+    #[cfg(feature = "trading")]
+    Orderbook {
+        /// Market ID to show
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Price levels to show on each side
+        #[arg(long, default_value_t = 20)]
+        depth: usize,
+    },
+    /// Show positions, average entry and realized / unrealized PnL per
+    /// market, marked at the middle of the top of book
+    #[cfg(feature = "trading")]
+    Pnl {
+        /// Market ID (defaults to every market)
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
+        market: Option<String>,
+        /// Count fills since this time: a duration ago such as `30d`, or a
+        /// unix timestamp in seconds
+        #[arg(long, value_name = "TIME", value_parser = parse_since_arg, default_value = "30d")]
+        since: u64,
+        /// Read fills from the local journal instead of replaying the
+        /// trades stream
+        #[cfg(feature = "journal")]
+        #[arg(long)]
+        journal: bool,
+    },
     /// Fetch the current balances for all supported tokens across all chains
-    Balance,
+    #[cfg(feature = "trading")]
+    Balance {
+        /// Only this chain (e.g., base-sepolia)
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_network))]
+        network: Option<String>,
+        /// Only this token symbol (e.g., USDC); leaves out the gas row
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_token))]
+        token: Option<String>,
+    },
+    /// Show deposit/withdraw history for the trader wallet on an EVM chain,
+    /// read from the trade contract's event logs
+    #[cfg(feature = "trading")]
+    History {
+        /// The network name to scan (e.g., anvil-1, base-sepolia)
+        #[arg(add = ArgValueCompleter::new(completions::complete_network))]
+        network: String,
+        /// Only show transfers of this token symbol
+        #[arg(add = ArgValueCompleter::new(completions::complete_token))]
+        token: Option<String>,
+        /// Address to look up (defaults to the trader wallet)
+        #[arg(long, value_parser = parse_address_arg)]
+        address: Option<String>,
+        /// First block to scan (defaults to 10,000 blocks before --to-block)
+        #[arg(long)]
+        from_block: Option<u64>,
+        /// Last block to scan (defaults to the chain head)
+        #[arg(long)]
+        to_block: Option<u64>,
+    },
+    /// Export a market's closed trades over a time range to CSV or JSON
+    #[cfg(feature = "trading")]
+    ExportTrades {
+        /// Market ID to export
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Start of the range: a duration ago such as `1d`, or a unix
+        /// timestamp in seconds
+        #[arg(long, value_name = "TIME", value_parser = parse_since_arg)]
+        from: u64,
+        /// End of the range, in the same forms (defaults to now)
+        #[arg(long, value_name = "TIME", value_parser = parse_since_arg)]
+        to: Option<u64>,
+        /// Output format: "csv" (default) or "json"
+        #[arg(long, default_value = "csv")]
+        format: history::ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o', value_name = "PATH")]
+        output_file: Option<std::path::PathBuf>,
+    },
+    /// List entries of the local trade journal, newest first
+    #[cfg(feature = "journal")]
+    Journal {
+        /// Only this kind: order, cancel, fill, deposit or withdraw
+        #[arg(long)]
+        kind: Option<EntryKind>,
+        /// Only entries on this market ID
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_market))]
+        market: Option<String>,
+        /// Only entries since this time: a duration ago such as `1d`, or a
+        /// unix timestamp in seconds
+        #[arg(long, value_name = "TIME", value_parser = parse_since_arg)]
+        since: Option<u64>,
+        /// Show at most this many entries
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+    },
+    /// Schedule recurring limit orders (DCA, periodic rebalancing), list or
+    /// remove them, or run the scheduler
+    #[cfg(feature = "trading")]
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Save, list, remove or run named order presets
+    #[cfg(feature = "trading")]
+    Preset {
+        #[command(subcommand)]
+        action: PresetAction,
+    },
+    /// Request dealer quotes for a size, then pick one to accept
+    #[cfg(feature = "trading")]
+    Rfq {
+        /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Order side: "buy" or "sell"
+        side: String,
+        /// Size to quote (human-readable)
+        #[arg(value_parser = parse_amount_arg)]
+        size: String,
+        /// How long dealers get to respond, e.g. `10s` or `1m`
+        #[arg(long, default_value = "10s", value_parser = parse_duration_arg)]
+        ttl: std::time::Duration,
+    },
+    /// Inspect a market's periodic batch auctions
+    Auction {
+        #[command(subcommand)]
+        action: AuctionAction,
+    },
+    /// Operator reports on how markets are used
+    Report {
+        #[command(subcommand)]
+        action: ReportAction,
+    },
+    /// Watch a market for wash trades, price spikes and quote stuffing
+    #[cfg(feature = "streaming")]
+    Surveil {
+        /// Market to watch (ID or name)
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Flag trade price moves beyond this many standard deviations
+        #[arg(long, default_value_t = 4.0)]
+        sigma: f64,
+        /// Flag an address sending more orderbook updates than this per second
+        #[arg(long, default_value_t = 50)]
+        stuffing_rate: usize,
+        /// Flag two addresses trading with each other this many times in
+        /// ten minutes
+        #[arg(long, default_value_t = 5)]
+        wash_pairs: usize,
+        /// Also POST each finding as JSON to this URL
+        #[arg(long)]
+        webhook: Option<String>,
+    },
+    /// Show rebates accrued by a referral code
+    Rebates {
+        /// Referral code to report on (defaults to --referral /
+        /// ASPENS_REFERRAL_CODE)
+        #[arg(long)]
+        code: Option<String>,
+    },
     /// Show current configuration and connection status
     Status,
     /// Get the public key and address for the trader wallet
+    #[cfg(feature = "trading")]
     TraderPublicKey,
     /// Get the signer public key(s) for the trading instance
     SignerPublicKey {
         /// Optional chain network to filter by (e.g., "base-sepolia"). If not provided, returns all chains.
-        #[arg(long)]
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_network))]
         chain_network: Option<String>,
     },
     /// Stream orderbook entries in real-time
+    #[cfg(feature = "streaming")]
     StreamOrderbook {
         /// Market ID to stream orders for
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Include historical open orders when stream starts
         #[arg(long, short = 'H')]
         historical: bool,
         /// Filter by a specific trader address
-        #[arg(long, short = 't')]
+        #[arg(long, short = 't', value_parser = parse_address_arg)]
         trader: Option<String>,
+        #[command(flatten)]
+        replay: ReplayArgs,
+        /// Probe the stack after this long without events, and resubscribe
+        /// if it doesn't answer (e.g. `2m`)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+        idle_timeout: Option<std::time::Duration>,
+        /// Reconnect attempts in a row before giving up when the stream
+        /// drops; reconnects resume from the last event shown. 0 exits on
+        /// the first drop
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_ATTEMPTS)]
+        max_reconnects: u32,
+        /// Keep a local book and show depth, imbalance, mid and microprice
+        /// over its top DEPTH levels after each event. Pair with
+        /// `--historical` so the book starts from the resting orders
+        #[arg(long, value_name = "DEPTH")]
+        indicators: Option<usize>,
     },
     /// Stream executed trades in real-time
+    #[cfg(feature = "streaming")]
     StreamTrades {
         /// Market ID to stream trades for
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
         market: String,
         /// Include historical closed trades when stream starts
         #[arg(long, short = 'H')]
         historical: bool,
         /// Filter by a specific trader address
-        #[arg(long, short = 't')]
+        #[arg(long, short = 't', value_parser = parse_address_arg)]
         trader: Option<String>,
+        #[command(flatten)]
+        replay: ReplayArgs,
+        /// Probe the stack after this long without events, and resubscribe
+        /// if it doesn't answer (e.g. `2m`)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+        idle_timeout: Option<std::time::Duration>,
+        /// Reconnect attempts in a row before giving up when the stream
+        /// drops; reconnects resume from the last event shown. 0 exits on
+        /// the first drop
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_ATTEMPTS)]
+        max_reconnects: u32,
+    },
+    /// Aggregate a market's trades into OHLCV candles as they complete
+    #[cfg(feature = "streaming")]
+    Candles {
+        /// Market ID to build candles for
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Candle length: `1s`, `1m`, `5m`, `1h` or any whole number of
+        /// seconds
+        #[arg(long, default_value = "1m")]
+        interval: CandleInterval,
+        /// Also build candles for trades since a time: a duration ago such
+        /// as `1h`, or a unix timestamp in seconds
+        #[arg(long, value_name = "TIME", value_parser = parse_since_arg)]
+        since: Option<u64>,
+        /// Write candles to this CSV file instead of printing them
+        #[arg(long, value_name = "PATH")]
+        csv: Option<std::path::PathBuf>,
+    },
+    /// Show a market's best bid, best ask, spread and last trade price on
+    /// one line, refreshed in place
+    #[cfg(feature = "streaming")]
+    Ticker {
+        /// Market ID to follow
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
     },
     /// Get TEE attestation report from the signer
+    #[cfg(feature = "attestation")]
     GetAttestation {
         /// Optional hex-encoded data to bind to the attestation report (max 64 bytes)
         #[arg(long)]
         report_data: Option<String>,
-        /// Output format: "text" (default) or "json"
-        #[arg(long, short = 'o', default_value = "text")]
-        output: String,
     },
     /// Verify a signer's TDX attestation, fail-closed: DCAP quote/TCB check, then
     /// pinned measurements, then the REPORTDATA binding (tx pubkeys + images +
     /// nonce). The quote is fetched from the stack (or read with --quote); its DCAP
     /// collateral is fetched from a PCCS (or read with --collateral).
+    #[cfg(feature = "attestation")]
     VerifyAttestation {
         /// Expected tx pubkey the quote must bind, as `<curve>:<hex>` where curve is
         /// `secp256k1`/`evm` or `ed25519`/`solana`. Repeatable (one per chain key).
@@ -543,25 +2151,414 @@ enum Commands {
         /// Revoked must never be allow-listed.
         #[arg(long = "accept-tcb", value_name = "STATUS")]
         accept_tcb: Vec<String>,
-        /// Output format: "text" (default) or "json"
-        #[arg(long, short = 'o', default_value = "text")]
-        output: String,
     },
+    /// Write a redacted debug bundle (versions, environment, connectivity,
+    /// stack config, profile state, log tails) to attach to a bug report
+    DebugBundle {
+        /// File to write; defaults to aspens-debug-<unix time>.json
+        #[arg(short, long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        /// Log file to include the tail of, e.g. one captured with
+        /// `aspens-cli -vv … 2> aspens.log` (repeatable)
+        #[arg(long = "log", value_name = "FILE")]
+        logs: Vec<PathBuf>,
+        /// Lines kept from the end of each log file
+        #[arg(long, value_name = "N", default_value_t = 500)]
+        lines: usize,
+        /// Entries kept from each list in the profile state files
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        entries: usize,
+    },
+    /// Print a shell completion script. Markets, networks and tokens
+    /// complete from the config the last command fetched. E.g. add
+    /// `source <(aspens-cli completions bash)` to ~/.bashrc
+    Completions {
+        /// Shell to write the script for
+        shell: completions::CompletionShell,
+    },
+    /// Add, list or switch between the named profiles in the config file
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Show where profile state is kept, or encrypt it at rest
+    Storage {
+        #[command(subcommand)]
+        action: StorageAction,
+    },
+    /// Turn anonymous usage statistics on or off, or show what is queued
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryAction,
+    },
+    /// Print the installed version
+    Version {
+        /// Also check the release feed for a newer version, and for the
+        /// minimum version the connected stack supports
+        #[arg(long)]
+        check: bool,
+    },
+    /// Check a minisign signature on a downloaded release artifact (e.g.
+    /// SHA256SUMS) or a config file
+    VerifySignature {
+        /// File to check
+        file: PathBuf,
+        /// Signature file; defaults to <FILE>.minisig
+        #[arg(long, value_name = "FILE")]
+        sig: Option<PathBuf>,
+        /// Minisign public key, or a path to a .pub file (defaults to
+        /// ASPENS_RELEASE_PUBKEY)
+        #[arg(long, value_name = "KEY")]
+        pubkey: Option<String>,
+    },
+}
+
+/// `aspens-cli auction` subcommands.
+#[derive(Debug, clap::Subcommand)]
+enum AuctionAction {
+    /// Show the auction cadence, next clear and recent clearing prices
+    Status {
+        /// Market ID to inspect
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+    },
+}
+
+/// `aspens-cli report` subcommands.
+#[derive(Debug, clap::Subcommand)]
+enum ReportAction {
+    /// Trade volume by weekday and hour (as a heatmap) and by day, the most
+    /// active traders, and the current spread
+    Activity {
+        /// Market ID to report on
+        #[arg(env = "ASPENS_MARKET", add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Start of the period: a duration ago such as `7d`, or a unix
+        /// timestamp in seconds
+        #[arg(long, value_name = "TIME", default_value = "7d", value_parser = parse_since_arg)]
+        since: u64,
+        /// How many of the most active traders to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+        /// Also write the hourly volumes to this CSV file
+        #[arg(long, value_name = "PATH")]
+        csv: Option<std::path::PathBuf>,
+    },
+}
+
+/// `aspens-cli telemetry` subcommands.
+#[derive(Debug, clap::Subcommand)]
+enum TelemetryAction {
+    /// Record command name, duration, success and SDK version for each run
+    On,
+    /// Stop recording and delete the queued events
+    Off,
+    /// Show the setting and the queued events
+    Status,
+}
+
+/// `aspens-cli profile` subcommands.
+#[derive(Debug, clap::Subcommand)]
+enum ProfileAction {
+    /// Add a profile, or replace one of the same name. Its stack URL, env
+    /// file and output format are the global --stack, --env-file and
+    /// --output
+    Add {
+        /// Profile name: letters, digits, '-' and '_'
+        name: String,
+        /// Market ID for commands that take one, when none is given
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_market))]
+        market: Option<String>,
+        /// Signer: env (TRADER_PRIVKEY or TRADER_MNEMONIC), keystore:PATH,
+        /// mnemonic:INDEX, ledger[:PATH] or kms:KEY
+        #[arg(long, value_name = "SOURCE")]
+        signer: Option<SignerSource>,
+        /// Also make it the default profile
+        #[arg(long)]
+        default: bool,
+    },
+    /// List the profiles; the default is marked with '*'
+    List,
+    /// Make a profile the default
+    Use {
+        /// Profile name
+        name: String,
+    },
+}
+
+/// `aspens-cli storage` subcommands.
+#[derive(Debug, clap::Subcommand)]
+enum StorageAction {
+    /// Show the profile directory and whether it is encrypted
+    Status,
+    /// Encrypt the profile's state files from now on, with a key derived
+    /// from ASPENS_STORAGE_PASSPHRASE or kept in the OS keychain
+    Encrypt {
+        /// Keep a random key in the OS keychain instead of using a passphrase
+        /// (builds with the `keychain` feature)
+        #[arg(long)]
+        keychain: bool,
+    },
+}
+
+/// `aspens-cli build-tx` subcommands.
+#[cfg(feature = "trading")]
+#[derive(Debug, clap::Subcommand)]
+enum BuildTxAction {
+    /// Deposit into the trade contract. An ERC-20 deposit needs an `approve`
+    /// broadcast and mined first.
+    Deposit(BuildTxArgs),
+    /// Let the trade contract take an amount of an ERC-20 token
+    Approve(BuildTxArgs),
+    /// Withdraw from the trade contract. Without --request-signature, prints
+    /// the voucher request to sign with `sign --withdraw-request`.
+    Withdraw {
+        #[command(flatten)]
+        args: BuildTxArgs,
+        /// Signature over the voucher request (hex), from `sign
+        /// --withdraw-request`
+        #[arg(long, value_name = "HEX")]
+        request_signature: Option<String>,
+    },
+}
+
+/// Arguments shared by the `build-tx` subcommands.
+#[cfg(feature = "trading")]
+#[derive(Debug, Clone, clap::Args)]
+struct BuildTxArgs {
+    /// The network name (e.g., anvil-1, base-sepolia)
+    #[arg(add = ArgValueCompleter::new(completions::complete_network))]
+    network: String,
+    /// Token symbol (e.g., USDC, WETH, WBTC)
+    #[arg(add = ArgValueCompleter::new(completions::complete_token))]
+    token: String,
+    /// Amount in human-readable units (e.g., "10", "10.5"). Scaled
+    /// by the token's `decimals` from the chain config.
+    #[arg(value_parser = parse_amount_arg)]
+    amount: String,
+    /// Account that will sign; defaults to the TRADER_PRIVKEY address
+    #[arg(long, value_parser = parse_address_arg)]
+    from: Option<String>,
+    /// File to write the unsigned transaction to
+    #[arg(short, long, value_name = "FILE")]
+    out: PathBuf,
+}
+
+/// `aspens-cli preset` subcommands.
+#[cfg(feature = "trading")]
+#[derive(Debug, clap::Subcommand)]
+enum PresetAction {
+    /// Save an order as a named preset (replacing any preset of that name).
+    /// `--strategy` / `--tag` are saved with it.
+    Save {
+        /// Preset name (letters, digits, '-' and '_')
+        name: String,
+        /// Market ID to trade on
+        #[arg(long, add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Order side: "buy" or "sell"
+        #[arg(long)]
+        side: String,
+        /// Amount to trade
+        #[arg(long, value_parser = parse_amount_arg)]
+        amount: String,
+        /// Fixed limit price
+        #[arg(long, value_parser = parse_amount_arg, conflicts_with = "price_offset")]
+        price: Option<String>,
+        /// Limit price as an offset from the mid at run time, e.g. `-0.1%`
+        /// or `+25bps`
+        #[arg(long, allow_hyphen_values = true, value_parser = parse_signed_bps_arg)]
+        price_offset: Option<i32>,
+        /// Post-only: see `buy-limit --post-only`.
+        #[arg(long)]
+        post_only: bool,
+        /// Invisible order: see `buy-limit --hidden`.
+        #[arg(long, default_value_t = false)]
+        hidden: bool,
+        /// Batch-auction order: see `buy-limit --auction`.
+        #[arg(long, default_value_t = false)]
+        auction: bool,
+        /// Time in force: see `buy-limit --tif`.
+        #[arg(long, value_name = "TIF", default_value_t = ExecutionType::GoodTillCancel)]
+        tif: ExecutionType,
+    },
+    /// Submit the order a preset describes
+    Run {
+        /// Preset name
+        name: String,
+        /// Replace a saved field for this run, e.g. `price=101` or
+        /// `price-offset=-0.2%`; repeatable
+        #[arg(long = "override", value_name = "KEY=VALUE", value_parser = parse_override)]
+        overrides: Vec<(String, String)>,
+    },
+    /// List saved presets
+    List,
+    /// Delete a preset
+    Remove {
+        /// Preset name
+        name: String,
+    },
+}
+
+/// `aspens-cli schedule` subcommands.
+#[cfg(feature = "trading")]
+#[derive(Debug, clap::Subcommand)]
+enum ScheduleAction {
+    /// Schedule a recurring limit BUY
+    BuyLimit {
+        /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Amount to buy on each run
+        #[arg(value_parser = parse_amount_arg)]
+        amount: String,
+        /// Limit price for each order
+        #[arg(value_parser = parse_amount_arg)]
+        price: String,
+        #[command(flatten)]
+        timing: ScheduleTiming,
+        /// Post-only: see `buy-limit --post-only`.
+        #[arg(long)]
+        post_only: bool,
+        /// Invisible order: see `buy-limit --hidden`.
+        #[arg(long, default_value_t = false)]
+        hidden: bool,
+        /// Batch-auction order: see `buy-limit --auction`.
+        #[arg(long, default_value_t = false)]
+        auction: bool,
+    },
+    /// Schedule a recurring limit SELL
+    SellLimit {
+        /// Market ID to trade on
+        #[arg(add = ArgValueCompleter::new(completions::complete_market))]
+        market: String,
+        /// Amount to sell on each run
+        #[arg(value_parser = parse_amount_arg)]
+        amount: String,
+        /// Limit price for each order
+        #[arg(value_parser = parse_amount_arg)]
+        price: String,
+        #[command(flatten)]
+        timing: ScheduleTiming,
+        /// Post-only: see `buy-limit --post-only`.
+        #[arg(long)]
+        post_only: bool,
+        /// Invisible order: see `buy-limit --hidden`.
+        #[arg(long, default_value_t = false)]
+        hidden: bool,
+        /// Batch-auction order: see `buy-limit --auction`.
+        #[arg(long, default_value_t = false)]
+        auction: bool,
+    },
+    /// List scheduled jobs
+    List,
+    /// Remove a scheduled job by its ID
+    Remove {
+        /// Job ID as shown by `schedule list`
+        id: u64,
+    },
+    /// Submit jobs as they fall due, until interrupted
+    Run,
+}
+
+/// When a scheduled order repeats; exactly one of the two is required.
+#[cfg(feature = "trading")]
+#[derive(Debug, clap::Args)]
+#[group(required = true, multiple = false)]
+struct ScheduleTiming {
+    /// Cron expression in UTC (minute hour day-of-month month day-of-week),
+    /// e.g. "0 9 * * MON-FRI"
+    #[arg(long)]
+    cron: Option<String>,
+    /// Fixed interval, e.g. `1h` or `30m`
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+    every: Option<std::time::Duration>,
+}
+
+/// Bounds on a stream's historical replay (`--historical`).
+#[cfg(feature = "streaming")]
+#[derive(Debug, clap::Args)]
+struct ReplayArgs {
+    /// Only replay history since a time: a duration ago such as `1h`, or a
+    /// unix timestamp in seconds
+    #[arg(long, value_name = "TIME", value_parser = parse_since_arg, requires = "historical")]
+    since: Option<u64>,
+    /// Only replay the most recent N historical events
+    #[arg(long, value_name = "N", requires = "historical")]
+    last: Option<u32>,
+    /// Page size the server replays history in
+    #[arg(long, value_name = "N", requires = "historical")]
+    batch_size: Option<u32>,
+    /// Pace the replay: `realtime`, a multiple such as `10x`, or `max`;
+    /// prints each event's simulated time
+    #[arg(long, value_name = "SPEED", requires = "historical")]
+    speed: Option<ReplaySpeed>,
+}
+
+#[cfg(feature = "streaming")]
+impl ReplayArgs {
+    fn replay(&self) -> HistoricalReplay {
+        HistoricalReplay {
+            since_ms: self.since,
+            limit: self.last,
+            batch_size: self.batch_size,
+        }
+    }
+}
+
+/// Queue an anonymous usage event if the user opted in. Best effort: a
+/// telemetry failure never fails the command.
+fn record_usage(command: &str, duration: std::time::Duration, success: bool) {
+    if let Ok(mut telemetry) = Telemetry::open_default() {
+        let _ = telemetry.record(command, duration, success);
+    }
 }
 
 #[tokio::main]
 async fn main() -> ExitCode {
-    match run().await {
+    // A TAB in a shell with `completions` installed: print candidates and
+    // exit.
+    clap_complete::dynamic::CompleteEnv::with_factory(Cli::command)
+        .var(completions::COMPLETE_ENV)
+        .complete();
+
+    // Before clap, which reads the profile's market and output defaults
+    // from the environment.
+    let profile = match aspens_cliutil::activate_profile() {
+        Ok(profile) => profile.map(|(_, profile)| profile).unwrap_or_default(),
+        Err(e) => {
+            let format = aspens_cliutil::error_format_arg(std::env::args_os());
+            return aspens_cliutil::report_error(&e, format);
+        }
+    };
+    let matches = aspens_cliutil::get_matches(Cli::command());
+    // Only the subcommand's name is recorded, never its arguments.
+    let command = matches.subcommand_name().unwrap_or_default().to_string();
+    let started = std::time::Instant::now();
+    let result = run(&matches, &profile).await;
+    if command != "telemetry" {
+        record_usage(&command, started.elapsed(), result.is_ok());
+    }
+    match result {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            eprintln!("{}", e);
-            ExitCode::FAILURE
+            let format = matches
+                .get_one::<ErrorFormat>("error_format")
+                .copied()
+                .unwrap_or_default();
+            aspens_cliutil::report_error(&e, format)
         }
     }
 }
 
-async fn run() -> Result<()> {
-    let cli = Cli::parse();
+async fn run(matches: &clap::ArgMatches, profile: &Profile) -> Result<()> {
+    let mut cli = Cli::from_arg_matches(matches)?;
+    // `profile add` stores the flags as given.
+    if !matches!(cli.command, Commands::Profile { .. }) {
+        apply_profile(&mut cli, matches, profile)?;
+    }
+    // A command loads its wallet once per leg; prompt for a keystore once.
+    aspens::wallet::remember_keystores();
 
     // Configure log level based on verbosity flag
     let log_level = if cli.verbose.is_silent() {
@@ -577,74 +2574,265 @@ async fn run() -> Result<()> {
         }
     };
 
-    let subscriber = FmtSubscriber::builder().with_max_level(log_level).finish();
+    // Keep stdout for the result when another program reads it.
+    let subscriber = FmtSubscriber::builder()
+        .with_max_level(log_level)
+        .with_writer(if cli.output.is_machine() {
+            BoxMakeWriter::new(std::io::stderr)
+        } else {
+            BoxMakeWriter::new(std::io::stdout)
+        })
+        .finish();
     // Best-effort: failing here only means logs aren't captured (e.g. a
     // subscriber is already set in-process) — don't abort the command over it.
     let _ = tracing::subscriber::set_global_default(subscriber);
 
+    // These don't talk to a stack, so they work before one is configured.
+    match &cli.command {
+        Commands::Completions { shell } => {
+            return completions::write_script(*shell, &mut std::io::stdout().lock());
+        }
+        Commands::Profile { action } => return run_profile_command(action, &cli, matches),
+        _ => {}
+    }
+
     // Build the client
     let mut builder = AspensClient::builder();
 
-    if let Some(ref env_file) = cli.env_file {
-        builder = builder.with_env_file(env_file);
+    if let Some(ref env_file) = cli.env_file {
+        builder = builder.with_env_file(env_file);
+    }
+
+    if let Some(ref url) = cli.stack_url {
+        builder = builder.with_url(url.to_string())?;
+    }
+
+    if let Some(timeout) = cli.timeout {
+        builder = builder.with_command_timeout(timeout);
+    }
+
+    if cli.confirmations.is_some() || cli.confirmation_timeout.is_some() {
+        let default = ConfirmationPolicy::default();
+        builder = builder.with_confirmation_policy(ConfirmationPolicy {
+            confirmations: cli.confirmations.unwrap_or(default.confirmations),
+            timeout: cli.confirmation_timeout.unwrap_or(default.timeout),
+        });
+    }
+
+    if let Some(code) = cli.referral {
+        builder = builder.with_referral_code(code);
     }
 
-    if let Some(ref url) = cli.stack_url {
-        builder = builder.with_url(url.to_string())?;
+    if let Some(ref path) = cli.keystore {
+        // The wallet loaders read TRADER_KEYSTORE; the builder unlocks the
+        // file up front so they find it already unlocked.
+        unsafe {
+            std::env::set_var("TRADER_KEYSTORE", path);
+        }
+        builder = builder.with_keystore(path);
     }
 
     let client = builder.build()?;
+    if let Some(index) = cli.account_index {
+        // After the build, which loads .env into the process env, so the
+        // flag beats a TRADER_ACCOUNT_INDEX set there.
+        unsafe {
+            std::env::set_var(aspens::TRADER_ACCOUNT_INDEX_ENV, index.to_string());
+        }
+    }
     let executor = DirectExecutor;
+    #[cfg(feature = "ledger")]
+    if cli.ledger {
+        let ledger = executor
+            .execute(LedgerWallet::connect(cli.hd_path.clone()))?
+            .with_prompt(|what| eprintln!("Confirm signing {} on your Ledger...", what));
+        info!(
+            "Signing with Ledger account {} ({:?})",
+            ledger.address(),
+            ledger.path()
+        );
+        let _ = LEDGER.set(Arc::new(ledger));
+    }
+    #[cfg(feature = "kms")]
+    if let Some(ref key_id) = cli.kms_key_id {
+        let signer = executor.execute(aspens::kms::AwsKmsSigner::connect(key_id.clone()))?;
+        info!(
+            "Signing with KMS key {} ({})",
+            signer.key_id(),
+            aspens::remote_signer::RemoteSigner::address(&signer)
+        );
+        let _ = REMOTE_SIGNER.set(Arc::new(signer));
+    }
+    // The referral code is filled in from the client at submission, so
+    // presets and scheduled jobs pick it up too.
+    #[cfg(feature = "trading")]
+    let order_tags = OrderTags {
+        strategy: cli.strategy,
+        tags: cli.tags,
+        referral: None,
+    };
+    #[cfg(feature = "trading")]
+    let precision = cli.precision;
+
+    let output = cli.output;
 
     match cli.command {
+        #[cfg(feature = "trading")]
         Commands::Deposit {
             network,
             token,
             amount,
+            associate,
         } => {
             info!("Depositing {amount} {token} on {network}");
 
             let stack_url = client.stack_url().to_string();
             let config = executor
-                .execute(aspens::commands::config::get_config(stack_url))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
             let context = format!("deposit {} {} on {}", amount, token, network);
-            let amount_base = resolve_token_amount(&config, &network, &token, &amount)
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
-            let wallet = load_trader_wallet_for_network(&config, &network)
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
+            let wallet = transacting_wallet_for_network(&config, &network)
+                .map_err(|e| user_error(e, &context))?;
+            let (amount_base, amount) = resolve_transfer_amount(
+                &executor,
+                &client,
+                &config,
+                (&network, &token, &amount),
+                deposit::max_deposit_amount(
+                    network.clone(),
+                    token.clone(),
+                    wallet.address(),
+                    config.clone(),
+                ),
+            )
+            .map_err(|e| user_error(e, &context))?;
+            #[cfg(feature = "journal")]
+            let (journal_network, journal_token) = (network.clone(), token.clone());
+            let confirmations = client.confirmation_policy();
+            // No command deadline once transactions go out: dropping the
+            // future after the broadcast would abandon a deposit that may
+            // still land. The confirmation policy bounds the wait instead.
             executor
                 .execute(async move {
-                    deposit::call_deposit_from_config_with_wallet(
+                    if associate
+                        && let Some(tx_hash) = hedera::associate_token(
+                            network.clone(),
+                            token.clone(),
+                            &wallet,
+                            config.clone(),
+                        )
+                        .await?
+                    {
+                        info!("Associated with {} in {}", token, tx_hash);
+                    }
+                    deposit::call_deposit_from_config_with_wallet_opts(
                         network,
                         token,
                         amount_base,
                         &wallet,
                         config,
+                        deposit::DepositOpts { confirmations },
                     )
                     .await
                 })
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
+                .map_err(|e| user_error(e, &context))?;
+
+            info!("Deposit of {} was successful", amount);
+            #[cfg(feature = "journal")]
+            record_journal(|journal| {
+                journal.record_transfer(
+                    EntryKind::Deposit,
+                    &journal_network,
+                    &journal_token,
+                    &amount,
+                )
+            });
+        }
+        #[cfg(feature = "trading")]
+        Commands::DepositBatch { file } => {
+            let batch = deposit::DepositBatch::load(&file)
+                .map_err(|e| user_error(e, &format!("read {}", file.display())))?;
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+
+            // One wallet per network in the file; a network whose wallet
+            // can't be loaded fails its own deposits, not the batch.
+            let mut wallets = Vec::new();
+            for item in &batch.deposits {
+                if let Ok(wallet) = transacting_wallet_for_network(&config, &item.network)
+                    && !wallets.iter().any(|w: &Wallet| w.curve() == wallet.curve())
+                {
+                    wallets.push(wallet);
+                }
+            }
+            info!("Running {} deposits", batch.deposits.len());
+            let opts = deposit::DepositOpts {
+                confirmations: client.confirmation_policy(),
+            };
+            let deposits = executor.execute(async move {
+                let signers: Vec<&dyn AspensSigner> =
+                    wallets.iter().map(|w| w as &dyn AspensSigner).collect();
+                deposit::deposit_many(batch.deposits, &signers, config, opts).await
+            });
 
-            info!("Deposit was successful");
+            #[cfg(feature = "journal")]
+            for status in &deposits {
+                if let Some(deposited) = &status.deposited {
+                    record_journal(|journal| {
+                        journal.record_transfer(
+                            EntryKind::Deposit,
+                            &status.network,
+                            &status.token,
+                            deposited,
+                        )
+                    });
+                }
+            }
+            let failed = deposits.iter().filter(|s| !s.is_ok()).count();
+            let total = deposits.len();
+            emit(output, &DepositBatchReport { deposits })?;
+            if failed > 0 {
+                return Err(eyre::eyre!("{} of {} deposits failed", failed, total));
+            }
         }
+        #[cfg(feature = "trading")]
         Commands::Withdraw {
             network,
             token,
             amount,
             no_unwrap,
+            to,
         } => {
             info!("Withdrawing {amount} {token} from {network}");
 
             let stack_url = client.stack_url().to_string();
             let config = executor
-                .execute(aspens::commands::config::get_config(stack_url.clone()))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
             let context = format!("withdraw {} {} from {}", amount, token, network);
-            let amount_base = resolve_token_amount(&config, &network, &token, &amount)
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
-            let wallet = load_trader_wallet_for_network(&config, &network)
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
+            let wallet = transacting_wallet_for_network(&config, &network)
+                .map_err(|e| user_error(e, &context))?;
+            let (amount_base, amount) = resolve_transfer_amount(
+                &executor,
+                &client,
+                &config,
+                (&network, &token, &amount),
+                withdraw::max_withdraw_amount(
+                    network.clone(),
+                    token.clone(),
+                    wallet.address(),
+                    config.clone(),
+                ),
+            )
+            .map_err(|e| user_error(e, &context))?;
+            #[cfg(feature = "journal")]
+            let (journal_network, journal_token) = (network.clone(), token.clone());
+            let confirmations = client.confirmation_policy();
             executor
                 .execute(async move {
                     withdraw::call_withdraw_from_config_with_wallet_opts(
@@ -656,20 +2844,261 @@ async fn run() -> Result<()> {
                         config,
                         withdraw::WithdrawOpts {
                             unwrap_native: !no_unwrap,
+                            to,
+                            confirmations,
                         },
                     )
                     .await
                 })
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
+                .map_err(|e| user_error(e, &context))?;
 
-            info!("Withdraw was successful");
+            info!("Withdrawal of {} was successful", amount);
+            #[cfg(feature = "journal")]
+            record_journal(|journal| {
+                journal.record_transfer(
+                    EntryKind::Withdraw,
+                    &journal_network,
+                    &journal_token,
+                    &amount,
+                )
+            });
+        }
+        #[cfg(feature = "trading")]
+        Commands::Allowance {
+            network,
+            token,
+            spender,
+            owner,
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            let context = format!("read the {} allowance on {}", token, network);
+            let owner = match owner {
+                Some(owner) => aspens::util::parse_address(&owner)?,
+                None => {
+                    let wallet = transacting_wallet_for_network(&config, &network)
+                        .map_err(|e| user_error(e, &context))?;
+                    aspens::util::parse_address(&wallet.address())?
+                }
+            };
+            let decimals = config.get_token(&network, &token).map_or(0, |t| t.decimals);
+            let amount = executor
+                .execute_with_timeout(
+                    allowance::get_allowance(
+                        network.clone(),
+                        token.clone(),
+                        owner,
+                        spender,
+                        config,
+                    ),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            emit(
+                output,
+                &AllowanceReport {
+                    network,
+                    token,
+                    owner: owner.to_checksum(None),
+                    spender: spender.to_string(),
+                    allowance: allowance::format_allowance(amount, decimals),
+                    base_units: amount.to_string(),
+                },
+            )?;
+        }
+        #[cfg(feature = "trading")]
+        Commands::Approve {
+            network,
+            token,
+            amount,
+            spender,
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            let context = format!("approve {} {} on {}", amount, token, network);
+            let amount_base = if amount == MAX_AMOUNT {
+                U256::MAX
+            } else {
+                U256::from(
+                    resolve_token_amount(&config, &network, &token, &amount)
+                        .map_err(|e| user_error(e, &context))?,
+                )
+            };
+            let wallet = transacting_wallet_for_network(&config, &network)
+                .map_err(|e| user_error(e, &context))?;
+            let tx_hash = executor
+                .execute_with_timeout(
+                    {
+                        let token = token.clone();
+                        async move {
+                            allowance::approve(
+                                network,
+                                token,
+                                amount_base,
+                                spender,
+                                &wallet,
+                                config,
+                            )
+                            .await
+                        }
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            println!(
+                "Approved {} {} for {}: tx {}",
+                amount, token, spender, tx_hash
+            );
+        }
+        #[cfg(feature = "trading")]
+        Commands::AssociateToken { network, token } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            let context = format!("associate with {} on {}", token, network);
+            let wallet = transacting_wallet_for_network(&config, &network)
+                .map_err(|e| user_error(e, &context))?;
+            let associated = executor
+                .execute_with_timeout(
+                    {
+                        let token = token.clone();
+                        async move { hedera::associate_token(network, token, &wallet, config).await }
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            match associated {
+                Some(tx_hash) => println!("Associated with {}: tx {}", token, tx_hash),
+                None => println!("Already associated with {}", token),
+            }
+        }
+        #[cfg(feature = "trading")]
+        Commands::BuildTx { action } => {
+            run_build_tx(&executor, &client, action)?;
+        }
+        #[cfg(feature = "trading")]
+        Commands::Sign {
+            file,
+            withdraw_request,
+            out,
+            key_env,
+        } => {
+            let key = client.get_env(&key_env).cloned().ok_or_else(|| {
+                eyre::eyre!(
+                    "{} not found\n\n\
+                     Hints:\n\
+                     - Set {} in the .env file on the signing machine\n\
+                     - Use --key-env to sign with a different variable",
+                    key_env,
+                    key_env
+                )
+            })?;
+            let signer = key
+                .parse::<alloy::signers::local::PrivateKeySigner>()
+                .map_err(|e| eyre::eyre!("Invalid {} format: {}", key_env, e))?;
+            let signed = match (withdraw_request, file) {
+                (Some(message), _) => {
+                    println!("Signing withdrawal request: {}", message);
+                    offline::sign_withdraw_request(&message, &signer).await?
+                }
+                (None, Some(file)) => {
+                    let tx = offline::UnsignedTx::read(&file)?;
+                    println!("Signing: {}", tx.description);
+                    println!(
+                        "  {} -> {} on {} (chain ID {}, nonce {})",
+                        tx.from, tx.to, tx.network, tx.chain_id, tx.nonce
+                    );
+                    tx.sign(&signer).await?
+                }
+                (None, None) => {
+                    return Err(eyre::eyre!("--file or --withdraw-request is required"));
+                }
+            };
+            let signed_hex = format!("0x{}", hex::encode(&signed));
+            match out {
+                Some(out) => {
+                    std::fs::write(&out, format!("{}\n", signed_hex))
+                        .map_err(|e| eyre::eyre!("Failed to write {}: {}", out.display(), e))?;
+                    println!("Signature written to {}", out.display());
+                }
+                None => println!("{}", signed_hex),
+            }
+        }
+        #[cfg(feature = "trading")]
+        Commands::Broadcast { file, network } => {
+            let signed = offline::read_signed(&file)?;
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            let rpc_url = config
+                .get_chain(&network)
+                .ok_or_else(|| eyre::eyre!("Chain '{}' not found in configuration", network))?
+                .rpc_url
+                .clone();
+            let tx_hash = executor
+                .execute_with_timeout(
+                    offline::broadcast(rpc_url, signed),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &format!("broadcast to {}", network)))?;
+            println!("Transaction broadcast: {}", tx_hash);
+        }
+        #[cfg(feature = "trading")]
+        Commands::TxStatus { hash, network } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            let status = executor
+                .execute_with_timeout(
+                    tx::transaction_status(network.clone(), hash.clone(), config),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &format!("look up {} on {}", hash, network)))?;
+            emit(output, &status)?;
+        }
+        #[cfg(feature = "trading")]
+        Commands::TxBump {
+            hash,
+            network,
+            percent,
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            let context = format!("bump {} on {}", hash, network);
+            let wallet = transacting_wallet_for_network(&config, &network)
+                .map_err(|e| user_error(e, &context))?;
+            let replacement = executor
+                .execute_with_timeout(
+                    {
+                        let hash = hash.clone();
+                        async move {
+                            tx::bump_transaction(network, hash, percent, &wallet, config).await
+                        }
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            println!("Replaced {} with {}", hash, replacement);
         }
+        #[cfg(feature = "trading")]
         Commands::BuyMarket {
             market,
             amount,
             hidden,
+            tif,
         } => {
-            info!("Sending market BUY order for {amount} on market {market} (hidden={hidden})");
+            info!(
+                "Sending market BUY order for {amount} on market {market} (hidden={hidden}, tif={tif})"
+            );
             let result = dispatch_send_order(
                 &executor,
                 &client,
@@ -680,6 +3109,11 @@ async fn run() -> Result<()> {
                 OrderFlags {
                     post_only: false, // meaningless for market orders
                     hidden,
+                    auction: false,
+                    execution: tif,
+                    expires_in: None,
+                    precision,
+                    tags: order_tags.clone(),
                 },
             )?;
             info!(
@@ -688,16 +3122,21 @@ async fn run() -> Result<()> {
             );
             log_tx_hashes(&result.get_formatted_transaction_hashes());
         }
+        #[cfg(feature = "trading")]
         Commands::BuyLimit {
             market,
             amount,
             price,
             post_only,
             hidden,
+            auction,
+            tif,
+            expires_in,
         } => {
             info!(
                 "Sending limit BUY order for {amount} at price {price} on market {market} \
-                 (post_only={post_only}, hidden={hidden})"
+                 (post_only={post_only}, hidden={hidden}, auction={auction}, tif={tif}, \
+                 expires_in={expires_in:?})"
             );
             let result = dispatch_send_order(
                 &executor,
@@ -706,7 +3145,15 @@ async fn run() -> Result<()> {
                 Side::Bid,
                 amount,
                 Some(price),
-                OrderFlags { post_only, hidden },
+                OrderFlags {
+                    post_only,
+                    hidden,
+                    auction,
+                    execution: tif,
+                    expires_in,
+                    precision,
+                    tags: order_tags.clone(),
+                },
             )?;
             info!(
                 "Limit buy order sent successfully (order_id: {})",
@@ -714,12 +3161,16 @@ async fn run() -> Result<()> {
             );
             log_tx_hashes(&result.get_formatted_transaction_hashes());
         }
+        #[cfg(feature = "trading")]
         Commands::SellMarket {
             market,
             amount,
             hidden,
+            tif,
         } => {
-            info!("Sending market SELL order for {amount} on market {market} (hidden={hidden})");
+            info!(
+                "Sending market SELL order for {amount} on market {market} (hidden={hidden}, tif={tif})"
+            );
             let result = dispatch_send_order(
                 &executor,
                 &client,
@@ -730,6 +3181,11 @@ async fn run() -> Result<()> {
                 OrderFlags {
                     post_only: false, // meaningless for market orders
                     hidden,
+                    auction: false,
+                    execution: tif,
+                    expires_in: None,
+                    precision,
+                    tags: order_tags.clone(),
                 },
             )?;
             info!(
@@ -738,16 +3194,21 @@ async fn run() -> Result<()> {
             );
             log_tx_hashes(&result.get_formatted_transaction_hashes());
         }
+        #[cfg(feature = "trading")]
         Commands::SellLimit {
             market,
             amount,
             price,
             post_only,
             hidden,
+            auction,
+            tif,
+            expires_in,
         } => {
             info!(
                 "Sending limit SELL order for {amount} at price {price} on market {market} \
-                 (post_only={post_only}, hidden={hidden})"
+                 (post_only={post_only}, hidden={hidden}, auction={auction}, tif={tif}, \
+                 expires_in={expires_in:?})"
             );
             let result = dispatch_send_order(
                 &executor,
@@ -756,7 +3217,15 @@ async fn run() -> Result<()> {
                 Side::Ask,
                 amount,
                 Some(price),
-                OrderFlags { post_only, hidden },
+                OrderFlags {
+                    post_only,
+                    hidden,
+                    auction,
+                    execution: tif,
+                    expires_in,
+                    precision,
+                    tags: order_tags.clone(),
+                },
             )?;
             info!(
                 "Limit sell order sent successfully (order_id: {})",
@@ -764,6 +3233,7 @@ async fn run() -> Result<()> {
             );
             log_tx_hashes(&result.get_formatted_transaction_hashes());
         }
+        #[cfg(feature = "trading")]
         Commands::BuyMarketable {
             market,
             amount,
@@ -788,233 +3258,925 @@ async fn run() -> Result<()> {
                 OrderFlags {
                     post_only: false,
                     hidden,
+                    auction: false,
+                    execution: ExecutionType::GoodTillCancel,
+                    expires_in: None,
+                    precision,
+                    tags: order_tags.clone(),
+                },
+            )?;
+            info!(
+                "Marketable buy order sent successfully (order_id: {})",
+                result.order_id
+            );
+            log_tx_hashes(&result.get_formatted_transaction_hashes());
+        }
+        #[cfg(feature = "trading")]
+        Commands::SellMarketable {
+            market,
+            amount,
+            slippage_bps,
+            hidden,
+        } => {
+            let price =
+                resolve_marketable_price(&executor, &client, &market, Side::Ask, slippage_bps)?;
+            info!(
+                "Sending marketable SELL for {amount} on {market} (slippage cap {} bps -> price {}, hidden={})",
+                slippage_bps, price, hidden
+            );
+            let result = dispatch_send_order(
+                &executor,
+                &client,
+                market,
+                Side::Ask,
+                amount,
+                Some(price),
+                OrderFlags {
+                    post_only: false,
+                    hidden,
+                    auction: false,
+                    execution: ExecutionType::GoodTillCancel,
+                    expires_in: None,
+                    precision,
+                    tags: order_tags.clone(),
+                },
+            )?;
+            info!(
+                "Marketable sell order sent successfully (order_id: {})",
+                result.order_id
+            );
+            log_tx_hashes(&result.get_formatted_transaction_hashes());
+        }
+        #[cfg(feature = "trading")]
+        Commands::CancelOrder {
+            market,
+            side,
+            order_id,
+        } => {
+            info!("Canceling order {order_id} ({side}) on market {market}");
+
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("cancel order {} on {}", order_id, market);
+            let origin = origin_network_for_side(&config, &market, parse_side(&side)?)
+                .map_err(|e| user_error(e, &context))?;
+            let wallet =
+                trader_wallet_for_network(&config, origin).map_err(|e| user_error(e, &context))?;
+            #[cfg(feature = "journal")]
+            let (journal_market, journal_side) = (
+                send_order::lookup_market(&config, &market)
+                    .map_or_else(|_| market.clone(), |m| m.market_id.clone()),
+                side.clone(),
+            );
+            let result = executor
+                .execute_with_timeout(
+                    async move {
+                        cancel_order::call_cancel_order_from_config_with_wallet(
+                            stack_url, market, side, order_id, &wallet, config,
+                        )
+                        .await
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+
+            #[cfg(feature = "journal")]
+            record_journal(|journal| {
+                journal.record_cancel(&journal_market, &journal_side, order_id, &result)
+            });
+            if result.order_canceled {
+                info!("Order {} canceled successfully", order_id);
+            } else {
+                info!("Order {} was not found or already canceled", order_id);
+            }
+
+            // Log transaction hashes if available
+            if !result.transaction_hashes.is_empty() {
+                info!("Transaction hashes:");
+                for formatted_hash in result.get_formatted_transaction_hashes() {
+                    info!("  {}", formatted_hash);
+                }
+                info!(
+                    "Paste these hashes into your chain's block explorer (e.g., Etherscan, Basescan)"
+                );
+            }
+        }
+        #[cfg(feature = "trading")]
+        Commands::History {
+            network,
+            token,
+            address,
+            from_block,
+            to_block,
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("fetch transfer history on {}", network);
+            let address = match address {
+                Some(a) => a,
+                None => trader_wallet_for_network(&config, &network)
+                    .map_err(|e| user_error(e, &context))?
+                    .address(),
+            };
+            info!("Scanning {} transfers of {}", network, address);
+
+            let range = history::BlockRange {
+                from: from_block,
+                to: to_block,
+            };
+            let net = network.clone();
+            let (records, config) = executor
+                .execute_with_timeout(
+                    async move {
+                        let records = history::transfer_history(
+                            &net,
+                            token.as_deref(),
+                            &address,
+                            range,
+                            &config,
+                        )
+                        .await?;
+                        Ok::<_, eyre::Report>((records, config))
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+
+            if records.is_empty() {
+                info!("No deposits or withdrawals found in the scanned range");
+            } else {
+                info!(
+                    "\n{}",
+                    history::display_transfer_history(&network, &records, &config)
+                );
+            }
+        }
+        #[cfg(feature = "trading")]
+        Commands::ExportTrades {
+            market,
+            from,
+            to,
+            format,
+            output_file,
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("export trades for market {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let market = resolved.clone();
+
+            // A long range replays for longer than a command timeout.
+            let records = executor
+                .execute(async move {
+                    let records =
+                        history::trade_history(stack_url, &market, from, to, None).await?;
+                    Ok::<_, eyre::Report>(records)
+                })
+                .map_err(|e| user_error(e, &context))?;
+
+            match &output_file {
+                Some(path) => {
+                    history::write_trades(&records, format, std::fs::File::create(path)?)?;
+                    info!("Wrote {} trades to {}", records.len(), path.display());
+                }
+                None => history::write_trades(&records, format, std::io::stdout().lock())?,
+            }
+        }
+        #[cfg(feature = "journal")]
+        Commands::Journal {
+            kind,
+            market,
+            since,
+            limit,
+        } => {
+            let entries = Journal::open_default()?.query(&JournalQuery {
+                kind,
+                market_id: market,
+                since_ms: since,
+                until_ms: None,
+                limit: Some(limit),
+            })?;
+            if entries.is_empty() {
+                info!("No journal entries found");
+            } else {
+                info!("\n{}", display_journal(&entries));
+            }
+        }
+        #[cfg(feature = "trading")]
+        Commands::Replace {
+            market,
+            side,
+            order_id,
+            amount,
+            price,
+            post_only,
+            hidden,
+        } => {
+            info!(
+                "Replacing order {order_id} ({side}) on market {market} with {amount} at {price}"
+            );
+            let side = parse_side(&side)?;
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let evm = trader_wallet(CurveType::Secp256k1).ok();
+            let solana = trader_wallet(CurveType::Ed25519).ok();
+            if evm.is_none() && solana.is_none() {
+                return Err(eyre::eyre!(
+                    "No trader wallet configured. Set TRADER_PRIVKEY (EVM) and/or \
+                     TRADER_PRIVKEY_SOLANA (Solana) in your .env file."
+                ));
+            }
+            let mut tags = order_tags.clone();
+            if tags.referral.is_none() {
+                tags.referral = client.referral_code().map(str::to_string);
+            }
+            let context = format!("replace order {} on {}", order_id, market);
+            let risk_limits = client.risk_limits().clone();
+            #[cfg(feature = "journal")]
+            let journaled = (
+                send_order::lookup_market(&config, &market).ok().cloned(),
+                aspens::OrderRequest {
+                    market_id: market.clone(),
+                    side: side as i32,
+                    quantity: amount.clone(),
+                    price: Some(price.clone()),
+                    post_only,
+                    hidden,
+                    ..Default::default()
+                },
+            );
+            let result = executor
+                .execute_with_timeout(
+                    async move {
+                        let wallets: Vec<&dyn AspensSigner> = [evm.as_ref(), solana.as_ref()]
+                            .into_iter()
+                            .flatten()
+                            .map(|w| w as &dyn AspensSigner)
+                            .collect();
+                        replace_order::replace_order(
+                            stack_url,
+                            market,
+                            order_id,
+                            side as i32,
+                            amount,
+                            price,
+                            &wallets,
+                            config,
+                            OrderOptions {
+                                post_only,
+                                hidden,
+                                precision,
+                                tags,
+                                risk: risk_limits,
+                                ..Default::default()
+                            },
+                        )
+                        .await
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            info!("Order {}", result);
+            log_tx_hashes(&result.placed.get_formatted_transaction_hashes());
+            #[cfg(feature = "journal")]
+            if let (Some(market), order) = &journaled {
+                let side = if side == Side::Ask { "sell" } else { "buy" };
+                record_journal(|journal| {
+                    journal.record_cancel(&market.market_id, side, order_id, &result.canceled)?;
+                    journal.record_order(order, market, &result.placed)
+                });
+            }
+        }
+        #[cfg(feature = "trading")]
+        Commands::CancelAll { market } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("cancel all orders on {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let wallet = trader_wallet_for_network(&config, &resolved.base_chain_network)
+                .map_err(|e| user_error(e, &context))?;
+            let mkt = market.clone();
+            let summary = executor
+                .execute_with_timeout(
+                    async move {
+                        cancel_order::cancel_all_orders(stack_url, mkt, &wallet, config).await
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+
+            info!("{}", summary);
+            if !summary.is_success() {
+                return Err(eyre::eyre!(
+                    "{} of the cancels on {} failed",
+                    summary.failed.len(),
+                    market
+                ));
+            }
+        }
+        #[cfg(feature = "trading")]
+        Commands::Orders { market, trader } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("list orders on {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let trader = match trader {
+                Some(t) => t,
+                None => trader_wallet_for_network(&config, &resolved.base_chain_network)
+                    .map_err(|e| user_error(e, &context))?
+                    .address(),
+            };
+            let market_id = resolved.market_id.clone();
+            let pair_decimals = resolved.pair_decimals as u32;
+            let orders = executor
+                .execute_with_timeout(
+                    open_orders::get_orders(stack_url, market_id, trader.clone()),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+
+            emit(
+                output,
+                &OpenOrders {
+                    market,
+                    trader,
+                    orders: orders
+                        .iter()
+                        .map(|order| open_orders::OrderSummary::new(order, pair_decimals))
+                        .collect(),
                 },
             )?;
-            info!(
-                "Marketable buy order sent successfully (order_id: {})",
-                result.order_id
-            );
-            log_tx_hashes(&result.get_formatted_transaction_hashes());
         }
-        Commands::SellMarketable {
+        #[cfg(feature = "trading")]
+        Commands::Depth {
             market,
-            amount,
-            slippage_bps,
-            hidden,
+            price,
+            side,
         } => {
-            let price =
-                resolve_marketable_price(&executor, &client, &market, Side::Ask, slippage_bps)?;
-            info!(
-                "Sending marketable SELL for {amount} on {market} (slippage cap {} bps -> price {}, hidden={})",
-                slippage_bps, price, hidden
-            );
-            let result = dispatch_send_order(
-                &executor,
-                &client,
-                market,
-                Side::Ask,
-                amount,
-                Some(price),
-                OrderFlags {
-                    post_only: false,
-                    hidden,
-                },
-            )?;
-            info!(
-                "Marketable sell order sent successfully (order_id: {})",
-                result.order_id
-            );
-            log_tx_hashes(&result.get_formatted_transaction_hashes());
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("size depth on {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let pair_decimals = resolved.pair_decimals as u32;
+            let limit_price = aspens::decimals::parse_decimal_amount(&price, pair_decimals)?;
+            let sides = match side {
+                Some(side) => vec![parse_book_side(&side)?],
+                None => vec![
+                    stream_orderbook::arborter_pb::Side::Bid,
+                    stream_orderbook::arborter_pb::Side::Ask,
+                ],
+            };
+            let book = executor
+                .execute_with_timeout(
+                    conflate::fetch_book_state(
+                        stack_url,
+                        resolved.market_id.clone(),
+                        std::time::Duration::from_millis(1_500),
+                    ),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            let depths: Vec<_> = sides
+                .into_iter()
+                .map(|side| depth::depth_at_price(&book, side, limit_price, pair_decimals))
+                .collect();
+            info!("\n{}", depth::display_depth(&depths, pair_decimals));
         }
-        Commands::CancelOrder {
+        #[cfg(feature = "trading")]
+        Commands::Quote {
             market,
             side,
-            order_id,
+            quantity,
         } => {
-            info!("Canceling order {order_id} ({side}) on market {market}");
-
             let stack_url = client.stack_url().to_string();
             let config = executor
-                .execute(aspens::commands::config::get_config(stack_url.clone()))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
-            let context = format!("cancel order {} on {}", order_id, market);
-            let origin = origin_network_for_side(&config, &market, parse_side(&side)?)
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
-            let wallet = load_trader_wallet_for_network(&config, origin)
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
-            let result = executor
-                .execute(async move {
-                    cancel_order::call_cancel_order_from_config_with_wallet(
-                        stack_url, market, side, order_id, &wallet, config,
-                    )
-                    .await
-                })
-                .map_err(|e| eyre::eyre!(format_error(&e, &context)))?;
-
-            if result.order_canceled {
-                info!("Order {} canceled successfully", order_id);
-            } else {
-                info!("Order {} was not found or already canceled", order_id);
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("quote {} {} on {}", side, quantity, market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let pair_decimals = resolved.pair_decimals as u32;
+            let side = parse_book_side(&side)?;
+            let quantity = aspens::decimals::parse_decimal_amount(&quantity, pair_decimals)?;
+            let market = resolved.clone();
+            let estimate = executor
+                .execute_with_timeout(
+                    async move { estimate::estimate_fill(stack_url, &market, side, quantity).await },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            info!("\n{}", estimate::display_estimate(&estimate, pair_decimals));
+            if !estimate.is_complete() {
+                tracing::warn!(
+                    "The book only fills {} of {}; a market order would leave {} unfilled",
+                    aspens::decimals::format_decimal_amount(estimate.filled, pair_decimals),
+                    aspens::decimals::format_decimal_amount(estimate.quantity, pair_decimals),
+                    aspens::decimals::format_decimal_amount(estimate.unfilled, pair_decimals)
+                );
             }
+        }
+        #[cfg(feature = "trading")]
+        Commands::Orderbook { market, depth } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("fetch the orderbook for {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let book = executor
+                .execute_with_timeout(
+                    orderbook::get_orderbook(stack_url, resolved.market_id.clone(), depth),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+            info!("\n{}", book.ladder(resolved.pair_decimals as u32));
+        }
+        #[cfg(feature = "trading")]
+        Commands::Pnl {
+            market,
+            since,
+            #[cfg(feature = "journal")]
+            journal,
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = match &market {
+                Some(market) => format!("compute PnL for market {}", market),
+                None => "compute PnL".to_string(),
+            };
+            let markets: Vec<_> = match &market {
+                Some(market) => vec![
+                    send_order::lookup_market(&config, market)
+                        .map_err(|e| user_error(e, &context))?
+                        .clone(),
+                ],
+                None => config
+                    .config
+                    .as_ref()
+                    .map(|c| c.markets.clone())
+                    .unwrap_or_default(),
+            };
 
-            // Log transaction hashes if available
-            if !result.transaction_hashes.is_empty() {
-                info!("Transaction hashes:");
-                for formatted_hash in result.get_formatted_transaction_hashes() {
-                    info!("  {}", formatted_hash);
+            let run = async move {
+                #[cfg(feature = "journal")]
+                if journal {
+                    let entries = Journal::open_default()?.query(&JournalQuery {
+                        kind: Some(EntryKind::Fill),
+                        since_ms: Some(since),
+                        ..JournalQuery::default()
+                    })?;
+                    let mut book = portfolio::Portfolio::new();
+                    // The journal lists newest first.
+                    for entry in entries.iter().rev() {
+                        let Some(m) = markets
+                            .iter()
+                            .find(|m| entry.market_id.as_deref() == Some(m.market_id.as_str()))
+                        else {
+                            continue;
+                        };
+                        let pair_decimals = m.pair_decimals as u32;
+                        if let Some(fill) = portfolio::Fill::from_journal(entry, pair_decimals) {
+                            book.apply(&fill, pair_decimals);
+                        }
+                    }
+                    portfolio::mark_to_book(stack_url, &mut book).await?;
+                    return Ok::<_, eyre::Report>(book);
                 }
+                let addresses: Vec<String> = [CurveType::Secp256k1, CurveType::Ed25519]
+                    .into_iter()
+                    .filter_map(|curve| trader_wallet(curve).ok())
+                    .map(|wallet| wallet.address())
+                    .collect();
+                if addresses.is_empty() {
+                    return Err(eyre::eyre!(
+                        "No trader wallet configured. Set TRADER_PRIVKEY (EVM) and/or \
+                         TRADER_PRIVKEY_SOLANA (Solana) in your .env file."
+                    ));
+                }
+                let markets: Vec<_> = markets.iter().collect();
+                Ok(portfolio::portfolio(stack_url, &markets, &addresses, since).await?)
+            };
+            // Every market is replayed in full, which outlasts a command
+            // timeout.
+            let book = executor.execute(run).map_err(|e| user_error(e, &context))?;
+
+            if book.is_empty() {
+                info!("No fills found in the range");
+            } else {
                 info!(
-                    "Paste these hashes into your chain's block explorer (e.g., Etherscan, Basescan)"
+                    "\n{}",
+                    portfolio::display_portfolio(&book, |market_id| {
+                        config
+                            .get_market_by_id(market_id)
+                            .map_or_else(|| market_id.to_string(), |m| m.name.clone())
+                    })
                 );
             }
         }
-        Commands::Balance => {
-            use aspens::commands::config;
-
+        #[cfg(feature = "trading")]
+        Commands::Balance { network, token } => {
             info!("Fetching balances for all tokens across all chains");
             let stack_url = client.stack_url().to_string();
             let config = executor
-                .execute(config::get_config(stack_url))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+                .execute_with_timeout(fetch_config(stack_url), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
 
             // Chains whose architecture has no matching wallet are rendered
             // with the lib's `error` placeholder; we only require at least one.
-            let evm = load_trader_wallet(CurveType::Secp256k1).ok();
-            let solana = load_trader_wallet(CurveType::Ed25519).ok();
+            let evm = trader_wallet(CurveType::Secp256k1).ok();
+            let solana = trader_wallet(CurveType::Ed25519).ok();
             if evm.is_none() && solana.is_none() {
                 return Err(eyre::eyre!(
                     "No trader wallet configured. Set TRADER_PRIVKEY (EVM) and/or \
                      TRADER_PRIVKEY_SOLANA (Solana) in your .env file."
                 ));
             }
+            let filter = balance::BalanceFilter {
+                network: network.clone(),
+                token: token.clone(),
+            };
+            let report = executor
+                .execute_with_timeout(
+                    async move {
+                        let wallets: Vec<&dyn AspensSigner> = [evm.as_ref(), solana.as_ref()]
+                            .into_iter()
+                            .flatten()
+                            .map(|w| w as &dyn AspensSigner)
+                            .collect();
+                        balance::fetch_balance_report(config, &wallets, &filter).await
+                    },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, "fetch balances"))?;
+            emit(output, &report)?;
+        }
+        #[cfg(feature = "trading")]
+        Commands::Schedule { action } => {
+            run_schedule_command(&executor, &client, action, &order_tags, precision)?;
+        }
+        #[cfg(feature = "trading")]
+        Commands::Preset { action } => {
+            run_preset_command(&executor, &client, action, &order_tags, precision)?;
+        }
+        #[cfg(feature = "trading")]
+        Commands::Rfq {
+            market,
+            side,
+            size,
+            ttl,
+        } => {
+            let side = parse_side(&side)?;
+            run_rfq(&executor, &client, market, side, size, ttl)?;
+        }
+        Commands::Auction {
+            action: AuctionAction::Status { market },
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let status = executor
+                .execute_with_timeout(
+                    async move { auction::auction_status(stack_url, &market, &config).await },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, "fetch auction status"))?;
+            println!("{status}");
+        }
+        Commands::Report {
+            action:
+                ReportAction::Activity {
+                    market,
+                    since,
+                    top,
+                    csv,
+                },
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("report activity on {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let market_id = resolved.market_id.clone();
+            let pair_decimals = resolved.pair_decimals as u32;
+            let report = executor
+                .execute_with_timeout(
+                    activity::activity_report(stack_url, market_id, since),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, &context))?;
+
+            println!("{}", report.display(pair_decimals, top));
+            if let Some(path) = csv {
+                std::fs::write(&path, report.to_csv(pair_decimals))?;
+                info!("Hourly volumes written to {}", path.display());
+            }
+        }
+        #[cfg(feature = "streaming")]
+        Commands::Surveil {
+            market,
+            sigma,
+            stuffing_rate,
+            wash_pairs,
+            webhook,
+        } => {
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("surveil market {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+
+            let mut watch = Surveillance::new(resolved.market_id.clone())
+                .with_detector(WashTradeDetector::new(
+                    wash_pairs,
+                    std::time::Duration::from_secs(600),
+                ))
+                .with_detector(PriceSpikeDetector::new(sigma, 100))
+                .with_detector(QuoteStuffingDetector::new(
+                    stuffing_rate,
+                    std::time::Duration::from_secs(1),
+                ))
+                .with_notifier(Arc::new(LogNotifier));
+            if let Some(url) = webhook {
+                watch = watch.with_notifier(Arc::new(WebhookNotifier::new(url)?));
+            }
+
+            println!("Watching market {} for suspicious activity", resolved.name);
+            println!("Press Ctrl+C to stop");
             executor
-                .execute(async move {
-                    let wallets: Vec<&Wallet> = [evm.as_ref(), solana.as_ref()]
-                        .into_iter()
-                        .flatten()
-                        .collect();
-                    balance::balance_from_config_with_wallets(config, &wallets).await
-                })
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch balances")))?;
+                .execute(watch.run(stack_url))
+                .map_err(|e| user_error(e, &context))?;
         }
-        Commands::Status => {
-            println!("Configuration Status:");
-            println!("  Stack URL: {}", client.stack_url());
+        Commands::Rebates { code } => {
+            let code = code
+                .or_else(|| client.referral_code().map(str::to_string))
+                .ok_or_else(|| {
+                    eyre::eyre!(
+                        "No referral code given. Pass --code, --referral, or set \
+                         ASPENS_REFERRAL_CODE in your .env file."
+                    )
+                })?;
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let report = executor
+                .execute_with_timeout(
+                    async move { rebates::rebate_report(stack_url, &code, &config).await },
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, "fetch rebates"))?;
+            println!("{report}");
+        }
+        Commands::DebugBundle {
+            out,
+            logs,
+            lines,
+            entries,
+        } => {
+            let bundle = bundle::collect(
+                &executor,
+                &client,
+                &bundle::BundleOptions {
+                    logs,
+                    lines,
+                    entries,
+                },
+            )?;
+            let out = out.unwrap_or_else(|| {
+                PathBuf::from(format!("aspens-debug-{}.json", aspens::util::unix_now()))
+            });
+            std::fs::write(&out, bundle)
+                .map_err(|e| eyre::eyre!("Failed to write {}: {}", out.display(), e))?;
+            println!("Debug bundle written to {}", out.display());
+            println!(
+                "Secrets are redacted, but review the file before attaching it to a bug report."
+            );
+        }
+        Commands::Completions { .. } | Commands::Profile { .. } => {
+            unreachable!("handled before the client is built")
+        }
+        Commands::Storage { action } => {
+            let profile = FsStorage::open_default()?;
+            let root = profile.root().to_path_buf();
+            let storage: Arc<dyn Storage> = Arc::new(profile);
+            match action {
+                StorageAction::Status => {
+                    println!("Profile directory: {}", root.display());
+                    println!(
+                        "Encrypted: {}",
+                        if EncryptedStorage::is_enabled(storage.as_ref())? {
+                            "yes"
+                        } else {
+                            "no"
+                        }
+                    );
+                }
+                StorageAction::Encrypt { keychain } => {
+                    let source = if keychain {
+                        KeySource::Keychain
+                    } else {
+                        KeySource::Passphrase(std::env::var(PASSPHRASE_ENV).map_err(|_| {
+                            eyre::eyre!(
+                                "No passphrase given\n\n\
+                                 Hints:\n\
+                                 - Set {} to the passphrase\n\
+                                 - Pass --keychain to keep the key in the OS keychain",
+                                PASSPHRASE_ENV
+                            )
+                        })?)
+                    };
+                    EncryptedStorage::enable(storage, source)?;
+                    println!("Encryption on for {}; its files are sealed", root.display());
+                }
+            }
+        }
+        Commands::Telemetry { action } => {
+            let mut telemetry = Telemetry::open_default()?;
+            match action {
+                TelemetryAction::On => {
+                    telemetry.set_enabled(true)?;
+                    println!(
+                        "Telemetry on: each command's name, duration, success and SDK version \
+                         are queued in {}",
+                        telemetry.path().display()
+                    );
+                    println!("Keys, addresses, amounts and arguments are never recorded.");
+                }
+                TelemetryAction::Off => {
+                    telemetry.set_enabled(false)?;
+                    println!("Telemetry off; queued events deleted");
+                }
+                TelemetryAction::Status => {
+                    println!(
+                        "Telemetry: {}{}",
+                        if telemetry.enabled() { "on" } else { "off" },
+                        if Telemetry::disabled_by_env() {
+                            " (disabled by DO_NOT_TRACK / ASPENS_TELEMETRY)"
+                        } else {
+                            ""
+                        }
+                    );
+                    println!("Queued events: {}", telemetry.queued().count());
+                    for event in telemetry.queued() {
+                        println!(
+                            "  {} {} {}ms {} (v{})",
+                            event.at,
+                            event.command,
+                            event.duration_ms,
+                            if event.success { "ok" } else { "failed" },
+                            event.sdk_version
+                        );
+                    }
+                }
+            }
+        }
+        Commands::Version { check } => {
+            println!("aspens-cli {}", env!("CARGO_PKG_VERSION"));
+            if check {
+                let status = executor
+                    .execute_with_timeout(
+                        aspens::update::check(Some(client.stack_url().to_string())),
+                        client.command_timeout(),
+                    )
+                    .map_err(|e| user_error(e, "check for updates"))?;
+                println!("{}", status.message());
+                if status.unsupported() {
+                    return Err(eyre::eyre!(
+                        "aspens-cli {} is no longer supported by the connected stack",
+                        env!("CARGO_PKG_VERSION")
+                    ));
+                }
+            }
+        }
+        Commands::VerifySignature { file, sig, pubkey } => {
+            use aspens::verify::{PublicKey, RELEASE_PUBKEY_ENV, verify_file};
 
+            let key = match pubkey {
+                Some(value) => PublicKey::from_arg(&value)?,
+                None => PublicKey::from_env(RELEASE_PUBKEY_ENV)?.ok_or_else(|| {
+                    eyre::eyre!("no public key: pass --pubkey or set {}", RELEASE_PUBKEY_ENV)
+                })?,
+            };
+            let comment = verify_file(&file, sig.as_deref(), &key)?;
+            println!(
+                "Good signature on {} from key {}",
+                file.display(),
+                key.key_id()
+            );
+            println!("Trusted comment: {}", comment);
+        }
+        Commands::Status => {
             // Ping the gRPC server
             let ping_result = executor.execute(aspens::health::ping_grpc_server(
                 client.stack_url().to_string(),
             ));
-            if ping_result.success {
-                println!(
-                    "  Connection: OK ({}ms)",
-                    ping_result.latency_ms.unwrap_or(0)
-                );
-            } else {
-                let error_msg = ping_result
-                    .error
-                    .unwrap_or_else(|| "Unknown error".to_string());
-
-                println!("  Connection: FAILED");
-                println!();
-
-                if error_msg.contains("Connection refused") {
-                    println!("Could not connect to the server.");
-                    println!();
-                    println!("Possible causes:");
-                    println!("  - The Aspens server is not running");
-                    println!("  - The server URL is incorrect");
-                    println!("  - A firewall is blocking the connection");
-                } else if error_msg.contains("dns") || error_msg.contains("resolve") {
-                    println!("Could not resolve the server hostname.");
-                    println!();
-                    println!("Possible causes:");
-                    println!("  - The hostname is incorrect");
-                    println!("  - DNS is not configured properly");
-                    println!("  - No internet connection");
-                } else if error_msg.contains("tls")
-                    || error_msg.contains("ssl")
-                    || error_msg.contains("certificate")
-                {
-                    println!("TLS/SSL error: {}", error_msg);
-                    println!();
-                    println!("Possible causes:");
-                    println!("  - Using wrong protocol (http vs https)");
-                    println!("  - Server certificate is invalid");
-                } else if error_msg.contains("timeout") {
-                    println!("Connection timed out.");
-                    println!();
-                    println!("Possible causes:");
-                    println!("  - Server is overloaded or unresponsive");
-                    println!("  - Network latency is too high");
-                } else {
-                    println!("Error: {}", error_msg);
-                }
-
-                println!();
-                println!("Hints:");
-                println!("  - Verify ASPENS_MARKET_STACK_URL in your .env file");
-                println!("  - Use --stack flag to specify a different URL");
-                println!("  - For local: http://localhost:50051");
-                println!("  - For remote: https://your-server:50051");
-            }
+            emit(
+                output,
+                &StatusReport {
+                    stack_url: client.stack_url().to_string(),
+                    connected: ping_result.success,
+                    latency_ms: ping_result.latency_ms,
+                    error: (!ping_result.success).then(|| {
+                        ping_result
+                            .error
+                            .unwrap_or_else(|| "Unknown error".to_string())
+                    }),
+                },
+            )?;
         }
+        #[cfg(feature = "trading")]
         Commands::TraderPublicKey => {
-            use alloy::signers::local::PrivateKeySigner;
-
-            let privkey = client.get_env("TRADER_PRIVKEY").cloned().ok_or_else(|| {
+            let wallet = load_trader_wallet(CurveType::Secp256k1).map_err(|e| {
                 eyre::eyre!(
-                    "TRADER_PRIVKEY not found\n\n\
+                    "Cannot load the trader wallet: {}\n\n\
                      Hints:\n\
                      - Set TRADER_PRIVKEY in your .env file\n\
-                     - The private key should be a 64-character hex string (without 0x prefix)"
-                )
-            })?;
-            let signer = privkey.parse::<PrivateKeySigner>().map_err(|e| {
-                eyre::eyre!(
-                    "Invalid TRADER_PRIVKEY format\n\n\
-                     Error: {}\n\n\
-                     Hints:\n\
-                     - The private key should be a 64-character hex string\n\
-                     - Do not include the '0x' prefix\n\
+                     - Or pass --keystore with an encrypted keystore file\n\
+                     - Or set TRADER_MNEMONIC (and --account-index)\n\
+                     - The private key should be a 64-character hex string (without 0x prefix)\n\
                      - Check for any extra whitespace or newlines",
                     e
                 )
             })?;
-            let address = signer.address();
+            let signer = wallet
+                .as_evm()
+                .ok_or_else(|| eyre::eyre!("the trader wallet is not an EVM wallet"))?;
             let pubkey = signer.credential().verifying_key();
-
-            println!("Trader Wallet:");
-            println!("  Address:    {}", address);
-            println!(
-                "  Public Key: 0x{}",
-                hex::encode(pubkey.to_encoded_point(false).as_bytes())
-            );
+            emit(
+                output,
+                &TraderKey {
+                    address: signer.address().to_checksum(None),
+                    public_key: format!(
+                        "0x{}",
+                        hex::encode(pubkey.to_encoded_point(false).as_bytes())
+                    ),
+                },
+            )?;
         }
+        #[cfg(feature = "config-export")]
         Commands::Config { output_file } => {
             use aspens::commands::config;
 
             let stack_url = client.stack_url().to_string();
             info!("Fetching configuration from {stack_url}");
             let config = executor
-                .execute(config::get_config(stack_url.clone()))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
 
             // If output_file is provided, save to file
             if let Some(ref path) = output_file {
                 executor
-                    .execute(config::download_config(stack_url.clone(), path.clone()))
-                    .map_err(|e| {
-                        eyre::eyre!(format_error(
-                            &e,
-                            &format!("save configuration to '{}'", path)
-                        ))
-                    })?;
+                    .execute_with_timeout(
+                        config::download_config(stack_url.clone(), path.clone()),
+                        client.command_timeout(),
+                    )
+                    .map_err(|e| user_error(e, &format!("save configuration to '{}'", path)))?;
                 info!("Configuration saved to: {}", path);
             } else {
                 // Display config as JSON
-                let json = serde_json::to_string_pretty(&config)
-                    .map_err(|e| eyre::eyre!("failed to format configuration as JSON: {e}"))?;
+                let json = aspens::json::to_json(&config)?;
                 println!("{}", json);
             }
         }
@@ -1024,11 +4186,11 @@ async fn run() -> Result<()> {
             let stack_url = client.stack_url().to_string();
             info!("Fetching signer public key(s) and gas balances from {stack_url}");
             let signer_infos = executor
-                .execute(config::get_signer_public_key_with_balances(
-                    stack_url,
-                    chain_network,
-                ))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch signer public key(s)")))?;
+                .execute_with_timeout(
+                    config::get_signer_public_key_with_balances(stack_url, chain_network),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, "fetch signer public key(s)"))?;
 
             println!("Signer Public Keys:");
             for info in &signer_infos {
@@ -1037,78 +4199,113 @@ async fn run() -> Result<()> {
                 println!("    Gas Balance: {} (native)", info.formatted_gas_balance());
             }
         }
+        #[cfg(feature = "streaming")]
         Commands::StreamOrderbook {
             market,
             historical,
             trader,
+            replay,
+            idle_timeout,
+            max_reconnects,
+            indicators,
         } => {
             info!("Streaming orderbook for market {market}");
             if historical {
                 info!("Including historical open orders");
             }
+            let speed = replay.speed;
+            let replay = replay.replay();
             if let Some(ref t) = trader {
                 info!("Filtering by trader: {}", t);
             }
 
             let stack_url = client.stack_url().to_string();
             let config = executor
-                .execute(aspens::commands::config::get_config(stack_url.clone()))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
             let resolved_market = send_order::lookup_market(&config, &market)
-                .map_err(|e| eyre::eyre!(format_error(&e, "look up market")))?;
+                .map_err(|e| user_error(e, "look up market"))?;
             let resolved_market_id = resolved_market.market_id.clone();
 
             let options = stream_orderbook::StreamOrderbookOptions {
                 market_id: resolved_market_id,
                 historical_open_orders: historical,
                 filter_by_trader: trader,
+                grpc: None,
+                replay,
+                idle: idle_timeout.map(IdleOptions::after),
+                reconnect: (max_reconnects > 0)
+                    .then(|| ReconnectOptions::default().with_max_attempts(max_reconnects)),
             };
 
-            println!("Streaming orderbook for market: {}", market);
-            println!("Press Ctrl+C to stop");
-            println!();
-            println!("{}", "-".repeat(120));
+            // JSON output is one record per line, so stdout carries nothing
+            // else.
+            if output != OutputFormat::Json {
+                println!("Streaming orderbook for market: {}", market);
+                println!("Press Ctrl+C to stop");
+                println!();
+                println!("{}", "-".repeat(120));
+            }
 
+            let mut book =
+                indicators.map(|depth| (OrderBook::new(options.market_id.clone()), depth));
+            let stream = async move {
+                let Some(speed) = speed else {
+                    return stream_orderbook::stream_orderbook(stack_url, options, |entry| {
+                        print_orderbook_event(&entry, book.as_mut(), output, None);
+                    })
+                    .await;
+                };
+                let (rx, handle) =
+                    stream_orderbook::stream_orderbook_channel(stack_url, options).await?;
+                let (mut paced, _pacer) = pace(rx, speed);
+                while let Some(p) = paced.recv().await {
+                    print_orderbook_event(&p.event, book.as_mut(), output, Some(p.simulated_ms));
+                }
+                handle.await?
+            };
             executor
-                .execute(stream_orderbook::stream_orderbook(
-                    stack_url,
-                    options,
-                    |entry| {
-                        println!("{}", stream_orderbook::format_orderbook_entry(&entry));
-                    },
-                ))
-                .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("stream orderbook for market {}", market)
-                    ))
-                })?;
+                .execute(stream)
+                .map_err(|e| user_error(e, &format!("stream orderbook for market {}", market)))?;
         }
+        #[cfg(feature = "streaming")]
         Commands::StreamTrades {
             market,
             historical,
             trader,
+            replay,
+            idle_timeout,
+            max_reconnects,
         } => {
             info!("Streaming trades for market {market}");
             if historical {
                 info!("Including historical closed trades");
             }
+            let speed = replay.speed;
+            let replay = replay.replay();
             if let Some(ref t) = trader {
                 info!("Filtering by trader: {}", t);
             }
 
             let stack_url = client.stack_url().to_string();
             let config = executor
-                .execute(aspens::commands::config::get_config(stack_url.clone()))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch configuration")))?;
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
             let resolved_market = send_order::lookup_market(&config, &market)
-                .map_err(|e| eyre::eyre!(format_error(&e, "look up market")))?;
+                .map_err(|e| user_error(e, "look up market"))?;
             let resolved_market_id = resolved_market.market_id.clone();
 
             let options = stream_trades::StreamTradesOptions {
                 market_id: resolved_market_id,
                 historical_closed_trades: historical,
                 filter_by_trader: trader,
+                grpc: None,
+                replay,
+                idle: idle_timeout.map(IdleOptions::after),
+                reconnect: (max_reconnects > 0)
+                    .then(|| ReconnectOptions::default().with_max_attempts(max_reconnects)),
             };
 
             println!("Streaming trades for market: {}", market);
@@ -1116,21 +4313,114 @@ async fn run() -> Result<()> {
             println!();
             println!("{}", "-".repeat(140));
 
+            let stream = async move {
+                let Some(speed) = speed else {
+                    return stream_trades::stream_trades(stack_url, options, |trade| {
+                        println!("{}", stream_trades::format_trade(&trade));
+                    })
+                    .await;
+                };
+                let (rx, handle) = stream_trades::stream_trades_channel(stack_url, options).await?;
+                let (mut paced, _pacer) = pace(rx, speed);
+                while let Some(p) = paced.recv().await {
+                    println!(
+                        "[sim {}] {}",
+                        p.simulated_ms,
+                        stream_trades::format_trade(&p.event)
+                    );
+                }
+                handle.await?
+            };
             executor
-                .execute(stream_trades::stream_trades(stack_url, options, |trade| {
-                    println!("{}", stream_trades::format_trade(&trade));
-                }))
-                .map_err(|e| {
-                    eyre::eyre!(format_error(
-                        &e,
-                        &format!("stream trades for market {}", market)
-                    ))
-                })?;
+                .execute(stream)
+                .map_err(|e| user_error(e, &format!("stream trades for market {}", market)))?;
         }
-        Commands::GetAttestation {
-            report_data,
-            output,
+        #[cfg(feature = "streaming")]
+        Commands::Candles {
+            market,
+            interval,
+            since,
+            csv,
         } => {
+            use std::io::Write;
+
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("build candles for market {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let pair_decimals = resolved.pair_decimals as u32;
+            let options = stream_trades::StreamTradesOptions {
+                market_id: resolved.market_id.clone(),
+                historical_closed_trades: since.is_some(),
+                replay: since.map(HistoricalReplay::since).unwrap_or_default(),
+                reconnect: Some(ReconnectOptions::default()),
+                ..Default::default()
+            };
+
+            let mut out = match &csv {
+                Some(path) => {
+                    let mut file = std::fs::File::create(path)?;
+                    writeln!(file, "{}", candles::CSV_HEADER)?;
+                    Some(file)
+                }
+                None => None,
+            };
+            println!("Building {} candles for market: {}", interval, market);
+            println!("Press Ctrl+C to stop");
+            println!();
+
+            let run = async move {
+                let mut stream =
+                    std::pin::pin!(candles::stream_candles(stack_url, options, interval));
+                while let Some(candle) = stream.next().await {
+                    let candle = candle?;
+                    match &mut out {
+                        Some(file) => {
+                            writeln!(file, "{}", candle.to_csv_row(pair_decimals))?;
+                            file.flush()?;
+                        }
+                        None => println!("{}", candle.display(pair_decimals)),
+                    }
+                }
+                Ok::<_, eyre::Report>(())
+            };
+            executor.execute(run).map_err(|e| user_error(e, &context))?;
+        }
+        #[cfg(feature = "streaming")]
+        Commands::Ticker { market } => {
+            use std::io::Write;
+
+            let stack_url = client.stack_url().to_string();
+            let config = executor
+                .execute_with_timeout(fetch_config(stack_url.clone()), client.command_timeout())
+                .map_err(|e| user_error(e, "fetch configuration"))?;
+            print_maintenance_banner(&config);
+            let context = format!("follow the ticker for market {}", market);
+            let resolved =
+                send_order::lookup_market(&config, &market).map_err(|e| user_error(e, &context))?;
+            let pair_decimals = resolved.pair_decimals as u32;
+            let market_id = resolved.market_id.clone();
+
+            println!("Press Ctrl+C to stop");
+            let run = async move {
+                let mut stream = std::pin::pin!(ticker::ticker(stack_url, market_id));
+                let mut stdout = std::io::stdout();
+                while let Some(update) = stream.next().await {
+                    // Carriage return and erase-line: rewrite the same line.
+                    write!(stdout, "\r\x1b[2K{}", update?.display(pair_decimals))?;
+                    stdout.flush()?;
+                }
+                writeln!(stdout)?;
+                Ok::<_, eyre::Report>(())
+            };
+            executor.execute(run).map_err(|e| user_error(e, &context))?;
+        }
+        #[cfg(feature = "attestation")]
+        Commands::GetAttestation { report_data } => {
             use aspens::commands::config;
 
             info!("Fetching TEE attestation from signer");
@@ -1169,11 +4459,14 @@ async fn run() -> Result<()> {
             }
 
             let response = executor
-                .execute(config::get_attestation(stack_url, report_data_bytes))
-                .map_err(|e| eyre::eyre!(format_error(&e, "fetch TEE attestation")))?;
+                .execute_with_timeout(
+                    config::get_attestation(stack_url, report_data_bytes),
+                    client.command_timeout(),
+                )
+                .map_err(|e| user_error(e, "fetch TEE attestation"))?;
 
-            match output.as_str() {
-                "json" => {
+            match output {
+                OutputFormat::Json => {
                     // Output as JSON
                     if let Some(report) = &response.report {
                         let json = serde_json::json!({
@@ -1213,6 +4506,7 @@ async fn run() -> Result<()> {
                 }
             }
         }
+        #[cfg(feature = "attestation")]
         Commands::VerifyAttestation {
             expected_pubkey,
             mr_td,
@@ -1230,7 +4524,6 @@ async fn run() -> Result<()> {
             collateral,
             pccs_url,
             accept_tcb,
-            output,
         } => {
             use aspens::commands::config;
             use aspens::tdx_verify::collateral::{collateral_from_json, fetch_collateral};
@@ -1322,47 +4615,49 @@ async fn run() -> Result<()> {
             // The verifier-chosen nonce is bound on the stack side, so fetch with a
             // clone and keep the original as the expected REPORTDATA input.
             let nonce_for_request = nonce_bytes.clone();
-            let result = executor.execute(async move {
-                // 1. Raw quote: from the stack unless --quote supplied one.
-                let raw_quote = match quote_from_file {
-                    Some(q) => q,
-                    None => {
-                        let resp =
-                            config::get_attestation(stack_url, Some(nonce_for_request)).await?;
-                        resp.report
-                            .ok_or_else(|| eyre::eyre!("stack returned no attestation report"))?
-                            .raw_quote
+            let result = executor.execute_with_timeout(
+                async move {
+                    // 1. Raw quote: from the stack unless --quote supplied one.
+                    let raw_quote = match quote_from_file {
+                        Some(q) => q,
+                        None => {
+                            let resp =
+                                config::get_attestation(stack_url, Some(nonce_for_request)).await?;
+                            resp.report
+                                .ok_or_else(|| eyre::eyre!("stack returned no attestation report"))?
+                                .raw_quote
+                        }
+                    };
+                    if raw_quote.is_empty() {
+                        return Err(eyre::eyre!(
+                            "quote is empty — the signer produced no TD Quote (is TDX active?)"
+                        ));
                     }
-                };
-                if raw_quote.is_empty() {
-                    return Err(eyre::eyre!(
-                        "quote is empty — the signer produced no TD Quote (is TDX active?)"
-                    ));
-                }
 
-                // 2. Collateral: from --collateral file, else fetched from the PCCS.
-                let collateral = match collateral_json {
-                    Some(j) => collateral_from_json(&j)?,
-                    None => fetch_collateral(&pccs_url, &raw_quote).await?,
-                };
+                    // 2. Collateral: from --collateral file, else fetched from the PCCS.
+                    let collateral = match collateral_json {
+                        Some(j) => collateral_from_json(&j)?,
+                        None => fetch_collateral(&pccs_url, &raw_quote).await?,
+                    };
 
-                // 3. Verify fail-closed: DCAP+TCB -> measurements -> REPORTDATA.
-                let verifier =
-                    DcapQuoteVerifier::new(collateral, now_secs).accept_tcb_statuses(accepted_tcb);
-                let expected = ExpectedReportData {
-                    pubkeys,
-                    image_digests,
-                    report_data: nonce_bytes,
-                };
-                let verified = verify_attestation(&raw_quote, &verifier, &policy, &expected)?;
-                Ok::<_, eyre::Report>(verified)
-            });
+                    // 3. Verify fail-closed: DCAP+TCB -> measurements -> REPORTDATA.
+                    let verifier = DcapQuoteVerifier::new(collateral, now_secs)
+                        .accept_tcb_statuses(accepted_tcb);
+                    let expected = ExpectedReportData {
+                        pubkeys,
+                        image_digests,
+                        report_data: nonce_bytes,
+                    };
+                    let verified = verify_attestation(&raw_quote, &verifier, &policy, &expected)?;
+                    Ok::<_, eyre::Report>(verified)
+                },
+                client.command_timeout(),
+            );
 
-            let verified =
-                result.map_err(|e| eyre::eyre!(format_error(&e, "verify attestation")))?;
+            let verified = result.map_err(|e| user_error(e, "verify attestation"))?;
 
-            match output.as_str() {
-                "json" => {
+            match output {
+                OutputFormat::Json => {
                     let json = serde_json::json!({
                         "verified": true,
                         "mr_td": hex::encode(verified.mr_td),
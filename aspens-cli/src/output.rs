@@ -0,0 +1,473 @@
+//! The `--output` layer.
+//!
+//! Handlers that report data build a serializable result value and pass
+//! it to [`emit`], which prints it in the chosen [`OutputFormat`]: the
+//! human-readable table, one pretty-printed JSON document, plain
+//! tab-separated lines for `cut` and `awk`, or CSV with a header row. In
+//! every format but the table, logs go to stderr (see `main`), so stdout
+//! carries only the result.
+
+use std::fmt::Write as _;
+
+use aspens::util::csv_row;
+use eyre::Result;
+use serde::Serialize;
+
+#[cfg(feature = "trading")]
+use aspens::chain::tx::TxStatus;
+#[cfg(feature = "trading")]
+use aspens::commands::trading::balance::{self, BalanceReport};
+#[cfg(feature = "trading")]
+use aspens::commands::trading::deposit::DepositStatus;
+#[cfg(feature = "trading")]
+use aspens::commands::trading::open_orders::{self, OrderSummary};
+
+/// How command results are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables and summaries
+    #[default]
+    #[value(alias = "text")]
+    Table,
+    /// One JSON document
+    Json,
+    /// Tab-separated lines, one record per line, no headers
+    Plain,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+impl OutputFormat {
+    /// True for the formats meant for other programs, which keep logs off
+    /// stdout.
+    pub fn is_machine(self) -> bool {
+        self != OutputFormat::Table
+    }
+
+    /// The name `--output` takes.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Plain => "plain",
+            OutputFormat::Csv => "csv",
+        }
+    }
+}
+
+/// A command result that can be printed in every [`OutputFormat`]; JSON
+/// comes from its `Serialize` impl.
+pub trait Render: Serialize {
+    /// Names of the fields in each [`plain`](Self::plain) record, the CSV
+    /// header.
+    const COLUMNS: &'static [&'static str];
+
+    /// The human-readable form.
+    fn table(&self) -> String;
+
+    /// Tab-separated records, one per line.
+    fn plain(&self) -> String;
+
+    /// The [`plain`](Self::plain) records as CSV, under a header of
+    /// [`COLUMNS`](Self::COLUMNS).
+    fn csv(&self) -> String {
+        let mut out = Self::COLUMNS.join(",");
+        out.push('\n');
+        for line in self.plain().lines() {
+            out.push_str(&csv_row(line.split('\t')));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Print `value` to stdout in `format`.
+pub fn emit<T: Render>(format: OutputFormat, value: &T) -> Result<()> {
+    let out = match format {
+        OutputFormat::Table => value.table(),
+        OutputFormat::Json => aspens::json::to_json(value)?,
+        OutputFormat::Plain => value.plain(),
+        OutputFormat::Csv => value.csv(),
+    };
+    let out = out.trim_end_matches('\n');
+    if !out.is_empty() {
+        println!("{}", out);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "trading")]
+impl Render for BalanceReport {
+    const COLUMNS: &'static [&'static str] = &[
+        "network",
+        "token",
+        "decimals",
+        "wallet",
+        "deposited",
+        "locked",
+        "low_gas",
+    ];
+
+    fn table(&self) -> String {
+        if self.balances.is_empty() {
+            return "No tokens found in configuration".to_string();
+        }
+        balance::display_balance_report(self)
+    }
+
+    /// Gas rows leave `deposited` and `locked` empty.
+    fn plain(&self) -> String {
+        let mut out = String::new();
+        for row in &self.balances {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                row.network,
+                row.token,
+                row.decimals,
+                row.wallet,
+                row.deposited.as_deref().unwrap_or_default(),
+                row.locked.as_deref().unwrap_or_default(),
+                row.low_gas
+            );
+        }
+        out
+    }
+
+    fn csv(&self) -> String {
+        self.to_csv()
+    }
+}
+
+/// `orders`: a trader's resting orders on a market.
+#[cfg(feature = "trading")]
+#[derive(Debug, Serialize)]
+pub struct OpenOrders {
+    /// The market as given on the command line.
+    pub market: String,
+    /// The trader whose orders these are.
+    pub trader: String,
+    /// Oldest first.
+    pub orders: Vec<OrderSummary>,
+}
+
+#[cfg(feature = "trading")]
+impl Render for OpenOrders {
+    const COLUMNS: &'static [&'static str] = &[
+        "order_id",
+        "side",
+        "price",
+        "remaining",
+        "state",
+        "placed_at",
+    ];
+
+    fn table(&self) -> String {
+        if self.orders.is_empty() {
+            return format!("No open orders for {} on {}", self.trader, self.market);
+        }
+        open_orders::display_order_summaries(&self.orders)
+    }
+
+    /// `order_id  side  price  remaining  state  placed_at`.
+    fn plain(&self) -> String {
+        let mut out = String::new();
+        for order in &self.orders {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                order.order_id,
+                order.side,
+                order.price,
+                order.remaining,
+                order.state,
+                order.placed_at
+            );
+        }
+        out
+    }
+}
+
+/// `deposit-batch`: each deposit's outcome.
+#[cfg(feature = "trading")]
+#[derive(Debug, Serialize)]
+pub struct DepositBatchReport {
+    /// In file order.
+    pub deposits: Vec<DepositStatus>,
+}
+
+#[cfg(feature = "trading")]
+impl Render for DepositBatchReport {
+    const COLUMNS: &'static [&'static str] =
+        &["network", "token", "amount", "status", "deposited", "error"];
+
+    fn table(&self) -> String {
+        if self.deposits.is_empty() {
+            return "No deposits in the file".to_string();
+        }
+        let mut out = String::new();
+        let ok = self.deposits.iter().filter(|d| d.is_ok()).count();
+        let _ = writeln!(out, "Deposits: {} of {} succeeded", ok, self.deposits.len());
+        for d in &self.deposits {
+            match (&d.deposited, &d.error) {
+                (Some(deposited), _) => {
+                    let _ = writeln!(out, "  ok      {} {} on {}", deposited, d.token, d.network);
+                }
+                (None, error) => {
+                    let _ = writeln!(
+                        out,
+                        "  FAILED  {} {} on {}: {}",
+                        d.amount,
+                        d.token,
+                        d.network,
+                        error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+            }
+        }
+        out
+    }
+
+    /// `network  token  amount  ok|failed  deposited  error`.
+    fn plain(&self) -> String {
+        let mut out = String::new();
+        for d in &self.deposits {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                d.network,
+                d.token,
+                d.amount,
+                if d.is_ok() { "ok" } else { "failed" },
+                d.deposited.as_deref().unwrap_or_default(),
+                d.error
+                    .as_deref()
+                    .unwrap_or_default()
+                    .replace(['\t', '\n'], " ")
+            );
+        }
+        out
+    }
+}
+
+/// `allowance`: what a spender may take from a wallet.
+#[cfg(feature = "trading")]
+#[derive(Debug, Serialize)]
+pub struct AllowanceReport {
+    /// The network as given on the command line.
+    pub network: String,
+    /// The token symbol as given on the command line.
+    pub token: String,
+    /// Checksummed address of the wallet.
+    pub owner: String,
+    /// `trade-contract` or `permit2`.
+    pub spender: String,
+    /// In token units, or `unlimited`.
+    pub allowance: String,
+    /// In base units.
+    pub base_units: String,
+}
+
+#[cfg(feature = "trading")]
+impl Render for AllowanceReport {
+    const COLUMNS: &'static [&'static str] = &[
+        "network",
+        "token",
+        "owner",
+        "spender",
+        "allowance",
+        "base_units",
+    ];
+
+    fn table(&self) -> String {
+        format!(
+            "Allowance of {} on {}:\n  Owner:     {}\n  Spender:   {}\n  Allowance: {}",
+            self.token, self.network, self.owner, self.spender, self.allowance
+        )
+    }
+
+    /// `network  token  owner  spender  allowance  base_units`.
+    fn plain(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.network, self.token, self.owner, self.spender, self.allowance, self.base_units
+        )
+    }
+}
+
+#[cfg(feature = "trading")]
+impl Render for TxStatus {
+    const COLUMNS: &'static [&'static str] = &[
+        "network",
+        "hash",
+        "state",
+        "from",
+        "nonce",
+        "block",
+        "confirmations",
+        "max_fee_per_gas",
+        "max_priority_fee_per_gas",
+    ];
+
+    fn table(&self) -> String {
+        let mut out = format!(
+            "Transaction {} on {}: {}",
+            self.hash,
+            self.network,
+            self.state.as_str()
+        );
+        if let Some(from) = &self.from {
+            let _ = write!(out, "\n  From:         {}", from);
+        }
+        if let Some(nonce) = self.nonce {
+            let _ = write!(out, "\n  Nonce:        {}", nonce);
+        }
+        if let (Some(block), Some(confirmations)) = (self.block, self.confirmations) {
+            let _ = write!(
+                out,
+                "\n  Block:        {} ({} confirmations)",
+                block, confirmations
+            );
+        }
+        if let Some(fee) = self.max_fee_per_gas {
+            let _ = write!(out, "\n  Max fee:      {} wei/gas", fee);
+        }
+        if let Some(fee) = self.max_priority_fee_per_gas {
+            let _ = write!(out, "\n  Priority fee: {} wei/gas", fee);
+        }
+        out
+    }
+
+    /// Unknown fields are empty.
+    fn plain(&self) -> String {
+        let field = |value: Option<String>| value.unwrap_or_default();
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.network,
+            self.hash,
+            self.state.as_str(),
+            field(self.from.clone()),
+            field(self.nonce.map(|n| n.to_string())),
+            field(self.block.map(|n| n.to_string())),
+            field(self.confirmations.map(|n| n.to_string())),
+            field(self.max_fee_per_gas.map(|n| n.to_string())),
+            field(self.max_priority_fee_per_gas.map(|n| n.to_string()))
+        )
+    }
+}
+
+/// `trader-public-key`: the EVM trader wallet.
+#[cfg(feature = "trading")]
+#[derive(Debug, Serialize)]
+pub struct TraderKey {
+    /// Checksummed address.
+    pub address: String,
+    /// Uncompressed SEC1 public key, 0x-prefixed hex.
+    pub public_key: String,
+}
+
+#[cfg(feature = "trading")]
+impl Render for TraderKey {
+    const COLUMNS: &'static [&'static str] = &["address", "public_key"];
+
+    fn table(&self) -> String {
+        format!(
+            "Trader Wallet:\n  Address:    {}\n  Public Key: {}",
+            self.address, self.public_key
+        )
+    }
+
+    /// `address  public_key`.
+    fn plain(&self) -> String {
+        format!("{}\t{}", self.address, self.public_key)
+    }
+}
+
+/// `status`: the configured stack and whether it answers.
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    /// The stack URL in use.
+    pub stack_url: String,
+    /// Whether the gRPC ping succeeded.
+    pub connected: bool,
+    /// Round trip of the ping, when it succeeded.
+    pub latency_ms: Option<u64>,
+    /// Why the ping failed.
+    pub error: Option<String>,
+}
+
+impl Render for StatusReport {
+    const COLUMNS: &'static [&'static str] = &["stack_url", "status", "detail"];
+
+    /// The summary, with likely causes and hints when the stack is
+    /// unreachable.
+    fn table(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Configuration Status:");
+        let _ = writeln!(out, "  Stack URL: {}", self.stack_url);
+        if self.connected {
+            let _ = writeln!(out, "  Connection: OK ({}ms)", self.latency_ms.unwrap_or(0));
+            return out;
+        }
+
+        let error_msg = self.error.as_deref().unwrap_or("Unknown error");
+        let _ = writeln!(out, "  Connection: FAILED\n");
+        if error_msg.contains("Connection refused") {
+            out.push_str(
+                "Could not connect to the server.\n\n\
+                 Possible causes:\n  \
+                 - The Aspens server is not running\n  \
+                 - The server URL is incorrect\n  \
+                 - A firewall is blocking the connection\n",
+            );
+        } else if error_msg.contains("dns") || error_msg.contains("resolve") {
+            out.push_str(
+                "Could not resolve the server hostname.\n\n\
+                 Possible causes:\n  \
+                 - The hostname is incorrect\n  \
+                 - DNS is not configured properly\n  \
+                 - No internet connection\n",
+            );
+        } else if error_msg.contains("tls")
+            || error_msg.contains("ssl")
+            || error_msg.contains("certificate")
+        {
+            let _ = writeln!(out, "TLS/SSL error: {}\n", error_msg);
+            out.push_str(
+                "Possible causes:\n  \
+                 - Using wrong protocol (http vs https)\n  \
+                 - Server certificate is invalid\n",
+            );
+        } else if error_msg.contains("timeout") {
+            out.push_str(
+                "Connection timed out.\n\n\
+                 Possible causes:\n  \
+                 - Server is overloaded or unresponsive\n  \
+                 - Network latency is too high\n",
+            );
+        } else {
+            let _ = writeln!(out, "Error: {}", error_msg);
+        }
+        out.push_str(
+            "\nHints:\n  \
+             - Verify ASPENS_MARKET_STACK_URL in your .env file\n  \
+             - Use --stack flag to specify a different URL\n  \
+             - For local: http://localhost:50051\n  \
+             - For remote: https://your-server:50051\n",
+        );
+        out
+    }
+
+    /// `stack_url  ok|failed  latency_ms|error`.
+    fn plain(&self) -> String {
+        if self.connected {
+            format!("{}\tok\t{}", self.stack_url, self.latency_ms.unwrap_or(0))
+        } else {
+            format!(
+                "{}\tfailed\t{}",
+                self.stack_url,
+                self.error.as_deref().unwrap_or("Unknown error")
+            )
+        }
+    }
+}
@@ -1,12 +1,12 @@
 //! Token-amount resolution shared by aspens-cli and aspens-repl.
 
 use aspens::commands::config::config_pb::GetConfigResponse;
-use aspens::decimals::parse_decimal_amount;
+use aspens::util::parse_amount;
 use eyre::Result;
 
 /// Look up `token_symbol` on `network` in the server config and parse
-/// `amount` (a human-readable decimal string like `"1.5"`) against the
-/// token's `decimals`. Returns the value in base units.
+/// `amount` (a human-readable decimal string like `"1.5"` or `"1_000"`)
+/// against the token's `decimals`. Returns the value in base units.
 ///
 /// Used by `deposit` / `withdraw` flows in both `aspens-cli` and
 /// `aspens-repl`. The error hint mentions the generic `config`
@@ -29,7 +29,7 @@ pub fn resolve_token_amount(
     // amount, lifting the old ~18-token cap on 18-decimal tokens (DEC-1). The
     // Solana path downcasts to u64 at the SPL boundary (its native width), with
     // a checked error, in `call_{deposit,withdraw}_from_config_with_wallet`.
-    parse_decimal_amount(amount, token.decimals)
+    parse_amount(amount, token.decimals)
         .map_err(|e| eyre::eyre!("Invalid amount '{}' for {}: {}", amount, token_symbol, e))
 }
 
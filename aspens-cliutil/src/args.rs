@@ -0,0 +1,85 @@
+//! Clap `value_parser`s shared by the CLI binaries.
+//!
+//! Each one is a thin wrapper over the matching [`aspens::util`] helper, so
+//! `--timeout 5m`, `--slippage-bps 0.5%` or an amount of `1_000.5` are read
+//! the same way by aspens-cli, aspens-repl and aspens-admin.
+
+use std::time::Duration;
+
+/// Address arguments: `0x` addresses are EIP-55 validated and returned
+/// checksummed, base58 Solana addresses pass through unchanged (see
+/// [`aspens::util::normalize_address`]).
+pub fn parse_address_arg(s: &str) -> Result<String, String> {
+    aspens::util::normalize_address(s).map_err(|e| e.to_string())
+}
+
+/// Duration arguments such as `30s`, `5m`, `24h` or `1h30m`; a bare integer
+/// is seconds (see [`aspens::util::parse_duration`]).
+pub fn parse_duration_arg(s: &str) -> Result<Duration, String> {
+    aspens::util::parse_duration(s).map_err(|e| e.to_string())
+}
+
+/// Points in time such as `1h` (an hour ago) or a unix timestamp in
+/// seconds, as unix milliseconds (see [`aspens::util::parse_since`]).
+pub fn parse_since_arg(s: &str) -> Result<u64, String> {
+    let now_ms = aspens::util::unix_now().saturating_mul(1_000);
+    aspens::util::parse_since(s, now_ms).map_err(|e| e.to_string())
+}
+
+/// Amount arguments, with `_` digit separators allowed. Returns the plain
+/// decimal string the library's amount parsing expects (see
+/// [`aspens::util::normalize_amount`]).
+pub fn parse_amount_arg(s: &str) -> Result<String, String> {
+    aspens::util::normalize_amount(s).map_err(|e| e.to_string())
+}
+
+/// Amount arguments that may also be `max` (any case), returned as
+/// [`aspens::util::MAX_AMOUNT`]; other amounts as [`parse_amount_arg`].
+pub fn parse_amount_or_max_arg(s: &str) -> Result<String, String> {
+    if s.eq_ignore_ascii_case(aspens::util::MAX_AMOUNT) {
+        return Ok(aspens::util::MAX_AMOUNT.to_string());
+    }
+    parse_amount_arg(s)
+}
+
+/// Basis-point arguments: `50`, `50bps` or `0.5%` (see
+/// [`aspens::util::parse_bps`]).
+pub fn parse_bps_arg(s: &str) -> Result<u32, String> {
+    aspens::util::parse_bps(s).map_err(|e| e.to_string())
+}
+
+/// Signed basis-point arguments such as `-0.1%` or `+25bps` (see
+/// [`aspens::util::parse_signed_bps`]).
+pub fn parse_signed_bps_arg(s: &str) -> Result<i32, String> {
+    aspens::util::parse_signed_bps(s).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_evm_and_rejects_garbage() {
+        assert_eq!(
+            parse_address_arg("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap(),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+        );
+        assert!(parse_address_arg("0x1234").is_err());
+    }
+
+    #[test]
+    fn wrappers_surface_library_errors_as_strings() {
+        assert_eq!(parse_duration_arg("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_amount_arg("1_000.5").unwrap(), "1000.5");
+        assert_eq!(parse_amount_or_max_arg("MAX").unwrap(), "max");
+        assert_eq!(parse_amount_or_max_arg("2_000").unwrap(), "2000");
+        assert!(parse_amount_or_max_arg("maximum").is_err());
+        assert_eq!(parse_bps_arg("0.5%").unwrap(), 50);
+        assert_eq!(parse_signed_bps_arg("-0.1%").unwrap(), -10);
+        assert!(
+            parse_bps_arg("0.005%")
+                .unwrap_err()
+                .contains("finer than 1 bp")
+        );
+    }
+}
@@ -1,341 +1,48 @@
 //! Shared user-facing error formatter for the Aspens CLI binaries.
 //!
-//! Inspects an `eyre::Report` and matches the lowercased message text
-//! against a battery of known failure modes (transport, auth, config
-//! lookups, gas, balance, signing). Each branch returns a multi-line
-//! message that explains what went wrong and what the user can try
-//! next, with the original error appended as the "Underlying error"
-//! footer. If no branch matches, a generic fallback is returned.
-//!
-//! The branches and their order are the union of what `aspens-cli`,
-//! `aspens-repl`, and `aspens-admin` historically each carried. The
-//! cli/repl branches dominate for trading-path errors; the admin
-//! branches dominate for JWT / auth / config-mutation errors. Most
-//! branches are inert for binaries they don't apply to (e.g. cli never
-//! sees `"invalid token"`), so leaving them in the shared helper is
-//! cheap and avoids drift.
+//! The explanations and hints live in [`aspens::explain`], which picks a
+//! [`UserMessage`] from the error's message text and kind; this module
+//! renders it for one binary, naming that binary's commands and key
+//! variable, and appends the original error as the "Underlying error"
+//! footer.
+
+use aspens::AspensError;
+use aspens::explain::UserMessage;
 
 use crate::BinaryContext;
 
+/// True if `err` reads as the stack rejecting the caller's JWT.
+///
+/// This is the match behind [`format_error`]'s authentication branch, so a
+/// binary that reacts to auth failures (by logging in again, say) agrees
+/// with the message the user would otherwise see.
+pub fn is_auth_error(err: &eyre::Report) -> bool {
+    aspens::explain::is_auth_error(err.as_ref())
+}
+
 /// Analyze an error and return a user-friendly message with hints.
 ///
 /// `context` is a short verb-phrase describing the operation that
 /// failed (e.g. `"send buy order"`, `"fetch balance"`). It's
 /// interpolated into the first line as `"Failed to {context}: ..."`.
 pub fn format_error(err: &eyre::Report, context: &str, ctx: &BinaryContext) -> String {
-    let err_string = err.to_string().to_lowercase();
-    let root_cause = err.root_cause().to_string().to_lowercase();
-    let name = ctx.name;
-
-    let with_underlying = |msg: String| -> String { format!("{msg}\n\nUnderlying error: {err}") };
-
-    // -- Transport / network ---------------------------------------------
-
-    if err_string.contains("failed to connect")
-        || err_string.contains("connection refused")
-        || root_cause.contains("connection refused")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Could not connect to the server\n\n\
-             Possible causes:\n\
-             - The Aspens server is not running\n\
-             - The server URL is incorrect\n\
-             - A firewall is blocking the connection\n\n\
-             Hints:\n\
-             - Check that the server is running\n\
-             - Verify the stack URL with '{name} status'\n\
-             - Check ASPENS_MARKET_STACK_URL in your .env file"
-        ));
-    }
-
-    if err_string.contains("dns error")
-        || err_string.contains("no such host")
-        || err_string.contains("name or service not known")
-        || root_cause.contains("dns")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Could not resolve server hostname\n\n\
-             Possible causes:\n\
-             - The server hostname is incorrect\n\
-             - DNS is not configured properly\n\
-             - No internet connection\n\n\
-             Hints:\n\
-             - Verify the stack URL is correct\n\
-             - Check your internet connection\n\
-             - Try using an IP address instead of hostname"
-        ));
-    }
-
-    if err_string.contains("tls")
-        || err_string.contains("ssl")
-        || err_string.contains("certificate")
-        || root_cause.contains("certificate")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: TLS/SSL error\n\n\
-             Possible causes:\n\
-             - The server's SSL certificate is invalid or expired\n\
-             - Certificate chain is incomplete\n\
-             - Using HTTP URL for HTTPS server or vice versa\n\n\
-             Hints:\n\
-             - Verify you're using the correct protocol (http:// vs https://)\n\
-             - For local development, use http://localhost:50051\n\
-             - For remote servers, use https://"
-        ));
-    }
-
-    if err_string.contains("compression flag")
-        || err_string.contains("protocol error")
-        || err_string.contains("invalid compression")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Protocol mismatch\n\n\
-             Possible causes:\n\
-             - Using HTTP to connect to an HTTPS server\n\
-             - Using HTTPS to connect to an HTTP server\n\
-             - The server is not a gRPC endpoint\n\n\
-             Hints:\n\
-             - For remote servers, use https://\n\
-             - For local development, use http://\n\
-             - Verify ASPENS_MARKET_STACK_URL in your .env file"
-        ));
-    }
-
-    if err_string.contains("timeout") || err_string.contains("timed out") {
-        return with_underlying(format!(
-            "Failed to {context}: Request timed out\n\n\
-             Possible causes:\n\
-             - The server is overloaded or unresponsive\n\
-             - Network latency is too high\n\
-             - The operation is taking longer than expected\n\n\
-             Hints:\n\
-             - Try again in a few moments\n\
-             - Check server status with '{name} status'\n\
-             - Verify network connectivity"
-        ));
-    }
-
-    // -- Auth / admin (only fires for admin flows) -----------------------
-
-    if err_string.contains("unauthenticated")
-        || err_string.contains("unauthorized")
-        || err_string.contains("401")
-        || err_string.contains("invalid token")
-        || err_string.contains("token expired")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Authentication failed\n\n\
-             Possible causes:\n\
-             - JWT token is missing, invalid, or expired\n\
-             - You don't have admin privileges\n\n\
-             Hints:\n\
-             - Run '{name} login' to get a fresh JWT token\n\
-             - Set ASPENS_JWT in your .env file or use --jwt flag\n\
-             - Verify {privkey} is set correctly",
-            privkey = ctx.privkey_env_var,
-        ));
-    }
-
-    if err_string.contains("not authorized as an admin")
-        || err_string.contains("address is not authorized")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Address is not authorized as admin\n\n\
-             The wallet address derived from {privkey} is not registered as an admin\n\
-             on this Aspens server.\n\n\
-             Possible causes:\n\
-             - Using the wrong private key (not the admin wallet)\n\
-             - The admin address was changed on the server\n\
-             - This is a fresh server and admin hasn't been initialized\n\n\
-             Hints:\n\
-             - Run '{name} admin-public-key' to see your wallet address\n\
-             - Compare with the registered admin address on the server\n\
-             - If this is a new server, use '{name} init-admin --address <your-address>'\n\
-             - Check that {privkey} in .env matches the expected admin wallet",
-            privkey = ctx.privkey_env_var,
-        ));
-    }
-
-    if err_string.contains("permission denied")
-        || err_string.contains("forbidden")
-        || err_string.contains("403")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Permission denied\n\n\
-             Possible causes:\n\
-             - Your account doesn't have admin privileges\n\
-             - The operation requires a different permission level\n\n\
-             Hints:\n\
-             - Verify you are using the correct admin wallet\n\
-             - Contact the system administrator"
-        ));
-    }
-
-    if err_string.contains("admin already") || err_string.contains("already initialized") {
-        return with_underlying(format!(
-            "Failed to {context}: Admin has already been initialized\n\n\
-             Hints:\n\
-             - Use '{name} login' to authenticate with the existing admin\n\
-             - Use '{name} update-admin' to change the admin address (requires auth)"
-        ));
-    }
-
-    // -- Config / lookup errors ------------------------------------------
-
-    // Specific resource not-found branches first (chain/token/market),
-    // then a generic 404 fallback for admin-side mutations.
-    if err_string.contains("chain not found")
-        || err_string.contains("network not found")
-        || (err_string.contains("not found") && err_string.contains("chain"))
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Chain/network not found\n\n\
-             Hints:\n\
-             - Check available chains with '{name} config'\n\
-             - Verify the network name is spelled correctly\n\
-             - The chain may not be configured on this server"
-        ));
-    }
-
-    if err_string.contains("token not found")
-        || (err_string.contains("not found") && err_string.contains("token"))
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Token not found\n\n\
-             Hints:\n\
-             - Check available tokens with '{name} config'\n\
-             - Verify the token symbol is spelled correctly (case-sensitive)\n\
-             - The token may not be configured on this chain"
-        ));
-    }
-
-    if err_string.contains("market not found")
-        || (err_string.contains("not found") && err_string.contains("market"))
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Market not found\n\n\
-             Hints:\n\
-             - Check available markets with '{name} config'\n\
-             - Verify the market ID is correct\n\
-             - Markets are identified by their full ID (e.g., chain_id::token::chain_id::token)"
-        ));
-    }
-
-    if err_string.contains("already exists") || err_string.contains("duplicate") {
-        return with_underlying(format!(
-            "Failed to {context}: Resource already exists\n\n\
-             Hints:\n\
-             - Use the appropriate delete command first if you want to replace it\n\
-             - Check existing configuration with '{name} config'"
-        ));
-    }
-
-    if err_string.contains("not found") || err_string.contains("404") {
-        return with_underlying(format!(
-            "Failed to {context}: Resource not found\n\n\
-             Hints:\n\
-             - Verify the resource name/ID is correct\n\
-             - Check existing configuration with '{name} config'\n\
-             - The resource may have been deleted"
-        ));
-    }
-
-    // -- Trading / on-chain errors ---------------------------------------
-
-    if err_string.contains("insufficient gas") || err_string.contains("insufficient funds for gas")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Insufficient gas for transaction fees\n\n\
-             Your wallet needs native tokens (ETH, FLR, etc.) to pay for gas.\n\n\
-             Hints:\n\
-             - Fund your wallet with native tokens on the target chain\n\
-             - For testnets, use a faucet to get free test tokens:\n\
-               - Base Sepolia: https://www.alchemy.com/faucets/base-sepolia\n\
-               - Flare Coston2: https://faucet.flare.network"
-        ));
-    }
-
-    if err_string.contains("insufficient")
-        || err_string.contains("not enough")
-        || err_string.contains("balance too low")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Insufficient balance\n\n\
-             Hints:\n\
-             - Check your balances with '{name} balance'\n\
-             - For trading: ensure you have deposited tokens first\n\
-             - For deposits: ensure your wallet has enough tokens"
-        ));
-    }
-
-    if err_string.contains("invalid string length") {
-        return with_underlying(format!(
-            "Failed to {context}: Invalid amount format\n\n\
-             The server rejected the order due to an invalid amount format.\n\n\
-             Possible causes:\n\
-             - Amount or price is too small or has too few digits\n\
-             - Values need to be in the correct decimal format\n\n\
-             Hints:\n\
-             - Use decimal notation for amounts (e.g., '1.5' instead of '1')\n\
-             - Check '{name} config' to see the market's pairDecimals setting\n\
-             - For market with pairDecimals=4: '1' becomes '10000', '0.5' becomes '5000'"
-        ));
-    }
-
-    if err_string.contains("transaction")
-        || err_string.contains("revert")
-        || err_string.contains("execution reverted")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Transaction failed\n\n\
-             Possible causes:\n\
-             - Insufficient token balance or allowance\n\
-             - Contract execution reverted\n\
-             - Gas estimation failed\n\n\
-             Hints:\n\
-             - Check your wallet balance\n\
-             - Verify you have approved the contract to spend tokens\n\
-             - Try with a smaller amount"
-        ));
-    }
-
-    // -- Signing key / address format ------------------------------------
-
-    if err_string.contains("invalid address") || err_string.contains("invalid checksum") {
-        return with_underlying(format!(
-            "Failed to {context}: Invalid Ethereum address format\n\n\
-             Hints:\n\
-             - Ensure the address starts with '0x'\n\
-             - Verify the address is 42 characters long (including '0x')\n\
-             - Use a checksummed address format"
-        ));
-    }
-
-    if err_string.contains("invalid private key")
-        || err_string.contains("privkey")
-        || err_string.contains("secret key")
-        || err_string.contains("hex decode")
-    {
-        return with_underlying(format!(
-            "Failed to {context}: Invalid private key\n\n\
-             Hints:\n\
-             - Ensure {privkey} is set correctly in your .env file\n\
-             - The private key should be a 64-character hex string\n\
-             - Do not include the '0x' prefix",
-            privkey = ctx.privkey_env_var,
-        ));
-    }
-
-    // -- Generic fallback ------------------------------------------------
+    let message = UserMessage::of(err.as_ref()).render(context, ctx.name, ctx.privkey_env_var);
+    format!("{message}\n\nUnderlying error: {err}")
+}
 
-    format!(
-        "Failed to {context}\n\n\
-         Hints:\n\
-         - Check server status with '{name} status'\n\
-         - Verify your configuration in .env file\n\
-         - Use -v flag for more detailed output\n\n\
-         Underlying error: {err}"
-    )
+/// [`format_error`] as a report that keeps `err`'s [`AspensError`] kind.
+///
+/// The report displays as the formatted message; the typed error stays in
+/// its chain (see [`AspensError::from`] for how an untyped report is
+/// classified), so [`exit_code`](crate::exit_code) still sees what failed.
+pub fn user_error(
+    err: impl Into<eyre::Report>,
+    context: &str,
+    ctx: &BinaryContext,
+) -> eyre::Report {
+    let err = err.into();
+    let message = format_error(&err, context, ctx);
+    eyre::Report::new(AspensError::from(err)).wrap_err(message)
 }
 
 #[cfg(test)]
@@ -378,6 +85,16 @@ mod tests {
         assert!(out.contains("'aspens-admin login'"));
     }
 
+    #[test]
+    fn auth_errors_are_recognized() {
+        assert!(is_auth_error(&report(
+            "status: Unauthenticated, message: \"token expired\""
+        )));
+        assert!(!is_auth_error(&report(
+            "transport error: Connection refused"
+        )));
+    }
+
     #[test]
     fn trader_privkey_branch_mentions_trader_env() {
         let e = report("Invalid private key: hex decode failed");
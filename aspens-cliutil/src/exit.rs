@@ -0,0 +1,213 @@
+//! Exit codes and `--error-format` for the CLI binaries.
+//!
+//! A failed command exits with a code chosen by the [`AspensError`] kind
+//! in its error chain, so scripts can tell a stack that is down from a
+//! rejected JWT without parsing messages. The codes are stable:
+//!
+//! | code | kind                   |
+//! |------|------------------------|
+//! | 1    | anything else          |
+//! | 2    | `connection`           |
+//! | 3    | `auth`                 |
+//! | 4    | `not_found`            |
+//! | 5    | `insufficient_balance` |
+//! | 6    | `invalid_input`        |
+//! | 7    | `protocol`             |
+//! | 8    | `chain`                |
+//! | 9    | `risk_limit`           |
+//!
+//! Command-line usage errors exit with 6 rather than clap's usual 2, which
+//! would read as a connection failure.
+
+use std::process::ExitCode;
+
+use aspens::AspensError;
+use serde::Serialize;
+
+use crate::error::is_auth_error;
+
+/// How a failed command reports its error on stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// The message with hints, as text
+    #[default]
+    Text,
+    /// One JSON object on a single line
+    Json,
+}
+
+/// The error object `--error-format json` prints.
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    /// `connection`, `auth`, `not_found`, `insufficient_balance`,
+    /// `invalid_input`, `protocol`, `chain`, `risk_limit` or `other`.
+    pub kind: &'static str,
+    /// The process exit code, as in the table above.
+    pub exit_code: u8,
+    /// The underlying error, without hints.
+    pub message: String,
+    /// The gRPC status code name, when the stack answered with one.
+    pub grpc_code: Option<String>,
+    /// The message with hints, as `--error-format text` prints it.
+    pub details: String,
+}
+
+impl ErrorReport {
+    /// Describe `err`.
+    pub fn new(err: &eyre::Report) -> Self {
+        let typed = aspens_error(err);
+        let (kind, exit_code) = match typed {
+            Some(e) => kind_of(e),
+            None if is_auth_error(err) => ("auth", 3),
+            None => ("other", 1),
+        };
+        Self {
+            kind,
+            exit_code,
+            message: typed.map_or_else(|| err.to_string(), |e| e.to_string()),
+            grpc_code: typed
+                .and_then(AspensError::grpc_status)
+                .map(|status| format!("{:?}", status.code())),
+            details: err.to_string(),
+        }
+    }
+
+    /// The error object for a command line clap rejected.
+    pub fn usage(err: &clap::Error) -> Self {
+        let details = err.render().to_string();
+        Self {
+            kind: "invalid_input",
+            exit_code: USAGE_EXIT_CODE,
+            message: details.lines().next().unwrap_or_default().to_string(),
+            grpc_code: None,
+            details,
+        }
+    }
+
+    /// Print to stderr in `format` and return the exit code.
+    pub fn print(&self, format: ErrorFormat) -> ExitCode {
+        match format {
+            ErrorFormat::Text => eprintln!("{}", self.details.trim_end()),
+            ErrorFormat::Json => match aspens::json::to_json_line(self) {
+                Ok(json) => eprintln!("{}", json),
+                Err(_) => eprintln!("{}", self.details.trim_end()),
+            },
+        }
+        ExitCode::from(self.exit_code)
+    }
+}
+
+/// Exit code for usage errors: the `invalid_input` code.
+pub const USAGE_EXIT_CODE: u8 = 6;
+
+/// The exit code for `err`.
+pub fn exit_code(err: &eyre::Report) -> u8 {
+    ErrorReport::new(err).exit_code
+}
+
+/// Print `err` to stderr in `format` and return the exit code to leave with.
+pub fn report_error(err: &eyre::Report, format: ErrorFormat) -> ExitCode {
+    ErrorReport::new(err).print(format)
+}
+
+/// Parse the command line with `command`, printing usage errors in
+/// `--error-format` (read from the raw arguments, since clap failed) and
+/// exiting with [`USAGE_EXIT_CODE`]. `--help` and `--version` print and
+/// exit 0 as usual.
+pub fn get_matches(command: clap::Command) -> clap::ArgMatches {
+    match command.try_get_matches() {
+        Ok(matches) => matches,
+        Err(e) if e.use_stderr() => {
+            let format = error_format_arg(std::env::args_os());
+            ErrorReport::usage(&e).print(format);
+            std::process::exit(USAGE_EXIT_CODE.into());
+        }
+        Err(e) => e.exit(),
+    }
+}
+
+/// `--error-format` from `args` (which start with the program name), else
+/// `ASPENS_ERROR_FORMAT`, else text.
+pub fn error_format_arg<I: IntoIterator<Item = std::ffi::OsString>>(args: I) -> ErrorFormat {
+    let value = crate::profile::flag_value(args, "--error-format")
+        .or_else(|| std::env::var("ASPENS_ERROR_FORMAT").ok());
+    match value.as_deref() {
+        Some("json") => ErrorFormat::Json,
+        _ => ErrorFormat::Text,
+    }
+}
+
+/// The first [`AspensError`] in `err`'s chain.
+fn aspens_error(err: &eyre::Report) -> Option<&AspensError> {
+    err.chain().find_map(|e| e.downcast_ref::<AspensError>())
+}
+
+/// The kind name and exit code of `err`.
+fn kind_of(err: &AspensError) -> (&'static str, u8) {
+    match err {
+        AspensError::Connection(_) => ("connection", 2),
+        AspensError::Auth(_) => ("auth", 3),
+        AspensError::NotFound(_) => ("not_found", 4),
+        AspensError::InsufficientBalance(_) => ("insufficient_balance", 5),
+        AspensError::InvalidInput(_) => ("invalid_input", 6),
+        AspensError::Protocol(_) => ("protocol", 7),
+        AspensError::Chain(_) => ("chain", 8),
+        AspensError::RiskLimit(_) => ("risk_limit", 9),
+        _ => ("other", 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinaryContext, user_error};
+
+    #[test]
+    fn exit_codes_follow_the_error_kind_through_formatting() {
+        let err = user_error(
+            AspensError::from(tonic::Status::unauthenticated("token expired")),
+            "fetch balance",
+            &BinaryContext::TRADER_CLI,
+        );
+        let report = ErrorReport::new(&err);
+        assert_eq!((report.kind, report.exit_code), ("auth", 3));
+        assert_eq!(report.grpc_code.as_deref(), Some("Unauthenticated"));
+        assert!(report.details.contains("Failed to fetch balance"));
+
+        let err = user_error(
+            eyre::Report::new(tonic::Status::unavailable("down")),
+            "ping",
+            &BinaryContext::TRADER_CLI,
+        );
+        assert_eq!(exit_code(&err), 2);
+
+        let err: eyre::Report = AspensError::insufficient_balance("need 5 USDC").into();
+        assert_eq!(exit_code(&err.wrap_err("withdraw")), 5);
+
+        assert_eq!(exit_code(&eyre::eyre!("something else")), 1);
+        assert_eq!(exit_code(&eyre::eyre!("401 unauthorized")), 3);
+    }
+
+    #[test]
+    fn json_reports_are_one_line() {
+        let err: eyre::Report = AspensError::not_found("Chain 'x' not found").into();
+        let json = aspens::json::to_json_line(&ErrorReport::new(&err)).unwrap();
+        assert!(!json.contains('\n'));
+        assert!(json.contains(r#""kind":"not_found""#));
+        assert!(json.contains(r#""exit_code":4"#));
+        assert!(json.contains(r#""message":"Chain 'x' not found""#));
+    }
+
+    #[test]
+    fn error_format_is_read_before_clap() {
+        let args = |list: &[&str]| list.iter().map(Into::into).collect::<Vec<_>>();
+        assert_eq!(
+            error_format_arg(args(&["aspens-cli", "--error-format", "json", "x"])),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            error_format_arg(args(&["aspens-cli", "--error-format=json"])),
+            ErrorFormat::Json
+        );
+    }
+}
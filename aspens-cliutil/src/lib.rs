@@ -10,10 +10,24 @@
 //! interpolate those into hint messages.
 
 mod amount;
+mod args;
 mod error;
+mod exit;
+mod profile;
+mod redact;
 
 pub use amount::resolve_token_amount;
-pub use error::format_error;
+pub use args::{
+    parse_address_arg, parse_amount_arg, parse_amount_or_max_arg, parse_bps_arg,
+    parse_duration_arg, parse_signed_bps_arg, parse_since_arg,
+};
+pub use error::{format_error, is_auth_error, user_error};
+pub use exit::{
+    ErrorFormat, ErrorReport, USAGE_EXIT_CODE, error_format_arg, exit_code, get_matches,
+    report_error,
+};
+pub use profile::{activate_profile, profile_arg};
+pub use redact::{REDACTED, is_secret_name, redact_env, redact_text};
 
 /// Per-binary parameters used to customize hint messages from the
 /// shared CLI helpers.
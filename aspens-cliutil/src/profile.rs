@@ -0,0 +1,77 @@
+//! `--profile` handling shared by the CLI binaries.
+//!
+//! The profile has to be known before clap parses the command line: its
+//! market and output format reach arguments as environment defaults
+//! (`ASPENS_MARKET`, `ASPENS_OUTPUT`). [`activate_profile`] therefore reads
+//! `--profile` from the raw arguments, then loads and activates the
+//! profile; the binaries fill their remaining unset flags from the
+//! [`Profile`] it returns.
+
+use std::ffi::OsString;
+
+use aspens::profiles::{self, Profile, ProfilesConfig};
+
+/// The value of `--profile NAME` or `--profile=NAME` in `args` (which
+/// start with the program name), if given.
+pub fn profile_arg<I: IntoIterator<Item = OsString>>(args: I) -> Option<String> {
+    flag_value(args, "--profile")
+}
+
+/// The value of `flag NAME` or `flag=NAME` in `args` (which start with the
+/// program name), if given.
+pub(crate) fn flag_value<I: IntoIterator<Item = OsString>>(args: I, flag: &str) -> Option<String> {
+    let mut args = args
+        .into_iter()
+        .skip(1)
+        .map(|a| a.to_string_lossy().into_owned());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|v| v.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Resolve the profile named on this process's command line (else
+/// `ASPENS_PROFILE`, else the config file's default) and activate it. Call
+/// first thing in `main`, before clap parses the arguments.
+pub fn activate_profile() -> eyre::Result<Option<(String, Profile)>> {
+    let name = profile_arg(std::env::args_os());
+    let resolved = ProfilesConfig::load()?.resolve(name.as_deref())?;
+    if let Some((name, profile)) = &resolved {
+        profiles::activate(name, profile);
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn finds_the_profile_flag_before_clap_runs() {
+        assert_eq!(
+            profile_arg(args(&["aspens-cli", "--profile", "testnet", "balance"])),
+            Some("testnet".into())
+        );
+        assert_eq!(
+            profile_arg(args(&["aspens-cli", "balance", "--profile=mainnet"])),
+            Some("mainnet".into())
+        );
+        assert_eq!(profile_arg(args(&["aspens-cli", "balance"])), None);
+        assert_eq!(
+            profile_arg(args(&["aspens-cli", "--", "--profile", "x"])),
+            None
+        );
+    }
+}
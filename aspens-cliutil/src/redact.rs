@@ -0,0 +1,159 @@
+//! Secret redaction for text users share, such as `aspens-cli debug-bundle`.
+//!
+//! Redaction errs on the side of removing too much. Secret-named variables
+//! lose their value outright, and free text is scanned word by word:
+//!
+//! - JWTs and 32-byte hex values (private keys, but also hashes) are removed;
+//! - longer hex blobs (signatures, signed transactions) are removed;
+//! - 0x addresses keep their first and last four digits;
+//! - base58 strings longer than a public key (Solana secret keys) are removed;
+//! - URLs keep only their scheme and host, since RPC providers put API keys
+//!   in the path or query.
+
+/// Placeholder for a removed value.
+pub const REDACTED: &str = "[redacted]";
+
+/// Name fragments that mark an environment variable as secret.
+const SECRET_NAME_PARTS: &[&str] = &[
+    "KEY", "SECRET", "JWT", "TOKEN", "PASS", "MNEMONIC", "SEED", "PRIV", "AUTH",
+];
+
+/// True if an environment variable called `name` likely holds a secret.
+pub fn is_secret_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    SECRET_NAME_PARTS.iter().any(|part| upper.contains(part))
+}
+
+/// The value of environment variable `name`, safe to share: [`REDACTED`]
+/// for a secret-named variable, otherwise [`redact_text`] of the value.
+pub fn redact_env(name: &str, value: &str) -> String {
+    if is_secret_name(name) {
+        REDACTED.to_string()
+    } else {
+        redact_text(value)
+    }
+}
+
+/// `text` with every word that looks like a secret, address or URL path
+/// redacted; see the module docs.
+pub fn redact_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_word_char(c) {
+            word.push(c);
+        } else {
+            out.push_str(&redact_word(&word));
+            word.clear();
+            out.push(c);
+        }
+    }
+    out.push_str(&redact_word(&word));
+    out
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-.+/=:?&%@~".contains(c)
+}
+
+fn redact_word(word: &str) -> String {
+    // Sentence punctuation isn't part of the value.
+    let core = word.trim_end_matches(['.', ',', ':', ';']);
+    let tail = &word[core.len()..];
+    if core.is_empty() {
+        return word.to_string();
+    }
+
+    let redacted = if let Some((name, value)) = core.split_once('=')
+        && !name.contains("://")
+    {
+        if is_secret_name(name) {
+            format!("{}={}", name, REDACTED)
+        } else {
+            format!("{}={}", name, redact_word(value))
+        }
+    } else if let Some((scheme, rest)) = core.split_once("://") {
+        let host = rest
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .rsplit('@')
+            .next()
+            .unwrap_or_default();
+        if host.len() + 3 + scheme.len() < core.len() {
+            format!("{}://{}/{}", scheme, host, REDACTED)
+        } else {
+            format!("{}://{}", scheme, host)
+        }
+    } else if is_jwt(core) {
+        REDACTED.to_string()
+    } else if let Some(hex) = hex_digits(core) {
+        match hex.len() {
+            40 => format!("0x{}…{}", &hex[..4], &hex[36..]),
+            n if n >= 64 => REDACTED.to_string(),
+            _ => core.to_string(),
+        }
+    } else if core.len() > 44 && core.chars().all(is_base58_char) {
+        REDACTED.to_string()
+    } else {
+        core.to_string()
+    };
+    redacted + tail
+}
+
+fn is_jwt(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('.').collect();
+    parts.len() == 3
+        && parts[0].starts_with("eyJ")
+        && parts.iter().all(|p| {
+            !p.is_empty()
+                && p.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+/// The hex digits of `s`, with or without `0x`, if it is all hex.
+fn hex_digits(s: &str) -> Option<&str> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit())).then_some(digits)
+}
+
+fn is_base58_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_secrets_and_trims_identifiers() {
+        let key = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+        assert_eq!(redact_env("TRADER_PRIVKEY", key), REDACTED);
+        assert_eq!(redact_env("ASPENS_JWT", "anything"), REDACTED);
+        assert_eq!(
+            redact_env("ASPENS_MARKET_STACK_URL", "http://localhost:50051"),
+            "http://localhost:50051"
+        );
+
+        let text = format!(
+            "signing with 0x{key} for 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266. \
+             rpc=https://base.g.alchemy.com/v2/SECRETAPIKEY jwt eyJhbGciOi.eyJzdWIi.c2ln \
+             TRADER_PRIVKEY={key} order 42"
+        );
+        let out = redact_text(&text);
+        assert!(!out.contains(key), "{out}");
+        assert!(!out.contains("SECRETAPIKEY"), "{out}");
+        assert!(!out.contains("eyJzdWIi"), "{out}");
+        assert!(out.contains("0xf39F…2266."), "{out}");
+        assert!(
+            out.contains("rpc=https://base.g.alchemy.com/[redacted]"),
+            "{out}"
+        );
+        assert!(out.contains("TRADER_PRIVKEY=[redacted]"), "{out}");
+        assert!(out.ends_with("order 42"), "{out}");
+    }
+}
@@ -1,38 +1,97 @@
-use aspens::commands::config::config_pb::GetConfigResponse;
-use aspens::commands::trading::{
-    balance, cancel_order, deposit, send_order, stream_orderbook, stream_trades, withdraw,
-};
-use aspens::{AspensClient, AsyncExecutor, BlockingExecutor, Wallet};
+use aspens::profiles::{Profile, SignerSource};
+use aspens::{AspensClient, AsyncExecutor, BlockingExecutor};
 use aspens_cliutil::BinaryContext;
 use clap::Parser;
 use clap_repl::ClapEditor;
 use clap_repl::reedline::{DefaultPrompt, DefaultPromptSegment, FileBackedHistory};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tracing::{Level, info};
 use tracing_subscriber::FmtSubscriber;
 
+#[cfg(feature = "trading")]
+use aspens::commands::config::config_pb::GetConfigResponse;
+#[cfg(feature = "trading")]
+use aspens::commands::trading::{
+    balance, cancel_order, deposit, open_orders, replace_order, send_order, withdraw,
+};
+#[cfg(feature = "streaming")]
+use aspens::commands::trading::{stream_orderbook, stream_trades};
+#[cfg(feature = "trading")]
+use aspens::decimals::PrecisionMode;
+#[cfg(feature = "trading")]
+use aspens::util::MAX_AMOUNT;
+#[cfg(feature = "trading")]
+use aspens::{AspensSigner, Wallet};
+#[cfg(any(feature = "trading", feature = "streaming"))]
+use aspens_cliutil::parse_address_arg;
+#[cfg(feature = "trading")]
+use aspens_cliutil::{parse_amount_arg, parse_amount_or_max_arg};
+
 /// Local thin wrapper over [`aspens_cliutil::format_error`].
 fn format_error(err: &eyre::Report, context: &str) -> String {
     aspens_cliutil::format_error(err, context, &BinaryContext::TRADER_REPL)
 }
 
 /// Print a friendly error message for missing TRADER_PRIVKEY
+#[cfg(feature = "trading")]
 fn print_missing_privkey_error() {
     println!();
     println!("TRADER_PRIVKEY not found");
     println!();
     println!("Hints:");
     println!("  - Set TRADER_PRIVKEY in your .env file");
+    println!("  - Or start the REPL with --keystore <PATH>");
+    println!("  - Or set TRADER_MNEMONIC (and --account-index)");
     println!("  - The private key should be a 64-character hex string");
     println!("  - Do not include the '0x' prefix");
     println!();
 }
 
-/// Pull `TRADER_PRIVKEY` from the REPL's session env (not process env, so
-/// `.env` changes during the session are honoured) and build an EVM
-/// [`Wallet`]. Returns `None` after printing a user-friendly error if the
-/// key is missing or malformed — call sites just `return` in that case.
+/// The wallet unlocked from `--keystore`, or else the `TRADER_KEYSTORE`,
+/// `TRADER_MNEMONIC` (account `--account-index` or `TRADER_ACCOUNT_INDEX`)
+/// or `TRADER_PRIVKEY` key from the REPL's session env (not process env,
+/// so `.env` changes during the session are honoured), as an EVM
+/// [`Wallet`].
+/// Returns `None` after printing a user-friendly error if the key is
+/// missing or malformed — call sites just `return` in that case.
+#[cfg(feature = "trading")]
 fn load_trader_wallet_or_complain(app_state: &AppState) -> Option<Wallet> {
+    if let Some(signer) = app_state.client.keystore_wallet().and_then(Wallet::as_evm) {
+        return Some(Wallet::Evm(signer.clone()));
+    }
+    if let Some(path) = app_state
+        .get_env("TRADER_KEYSTORE")
+        .filter(|path| !path.is_empty())
+    {
+        return match Wallet::unlock_keystore(&path) {
+            Ok(w) => Some(w),
+            Err(e) => {
+                print_error(&format_error(&e, "unlock TRADER_KEYSTORE"));
+                None
+            }
+        };
+    }
+    if let Some(phrase) = app_state
+        .get_env("TRADER_MNEMONIC")
+        .filter(|phrase| !phrase.trim().is_empty())
+    {
+        let index = match app_state.account_index {
+            Some(index) => Ok(index),
+            None => app_state
+                .get_env("TRADER_ACCOUNT_INDEX")
+                .map_or(Ok(0), |index| index.trim().parse::<u32>()),
+        };
+        return match index
+            .map_err(|_| eyre::eyre!("TRADER_ACCOUNT_INDEX must be a non-negative integer"))
+            .and_then(|index| Wallet::from_mnemonic(&phrase, index))
+        {
+            Ok(w) => Some(w),
+            Err(e) => {
+                print_error(&format_error(&e, "derive the TRADER_MNEMONIC account"));
+                None
+            }
+        };
+    }
     let key = match app_state.get_env("TRADER_PRIVKEY") {
         Some(k) => k,
         None => {
@@ -59,6 +118,7 @@ fn print_error(message: &str) {
 }
 
 /// Local thin wrapper over [`aspens_cliutil::resolve_token_amount`].
+#[cfg(feature = "trading")]
 fn resolve_token_amount(
     config: &GetConfigResponse,
     network: &str,
@@ -68,6 +128,43 @@ fn resolve_token_amount(
     aspens_cliutil::resolve_token_amount(config, network, token_symbol, amount)
 }
 
+/// [`send_order::send_order_with_wallet`], except that an amount with
+/// more decimals than the market's pair decimals is refused instead of
+/// truncated, and the order is checked against `risk`.
+// Mirrors `send_order_with_wallet`'s argument list so call sites read the same.
+#[cfg(feature = "trading")]
+#[allow(clippy::too_many_arguments)]
+async fn send_order_strict(
+    url: String,
+    market_id: String,
+    side: i32,
+    quantity: String,
+    price: Option<String>,
+    wallet: &Wallet,
+    config: GetConfigResponse,
+    post_only: bool,
+    hidden: bool,
+    risk: &aspens::RiskConfig,
+) -> aspens::error::Result<send_order::arborter_pb::SendOrderResponse> {
+    send_order::send_order_with_options(
+        url,
+        market_id,
+        side,
+        quantity,
+        price,
+        &[wallet],
+        config,
+        send_order::OrderOptions {
+            post_only,
+            hidden,
+            precision: PrecisionMode::Error,
+            risk: risk.clone(),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
 fn print_status_error(error_msg: &str) {
     println!("  Connection: FAILED");
     println!();
@@ -112,38 +209,52 @@ fn print_status_error(error_msg: &str) {
     println!("  - For remote: https://your-server:50051");
 }
 
+// `AspensClient` is `Clone + Send + Sync` with lock-free shared state, so
+// the REPL holds it directly rather than behind a `Mutex`.
 struct AppState {
-    client: Arc<Mutex<AspensClient>>,
+    client: AspensClient,
+    /// `--account-index`, which beats TRADER_ACCOUNT_INDEX.
+    #[cfg_attr(not(feature = "trading"), allow(dead_code))]
+    account_index: Option<u32>,
 }
 
 impl AppState {
-    fn new(client: AspensClient) -> Self {
+    fn new(client: AspensClient, account_index: Option<u32>) -> Self {
         Self {
-            client: Arc::new(Mutex::new(client)),
+            client,
+            account_index,
         }
     }
 
     fn stack_url(&self) -> String {
-        let guard = self.client.lock().unwrap_or_else(|p| p.into_inner());
-        guard.stack_url().to_string()
+        self.client.stack_url().to_string()
     }
 
+    #[cfg(feature = "trading")]
     fn get_env(&self, key: &str) -> Option<String> {
-        let guard = self.client.lock().unwrap_or_else(|p| p.into_inner());
-        guard.get_env(key).cloned()
+        self.client.get_env(key).cloned()
+    }
+
+    fn command_timeout(&self) -> std::time::Duration {
+        self.client.command_timeout()
     }
 
+    #[cfg(feature = "trading")]
     fn get_config_sync(
         &self,
     ) -> eyre::Result<aspens::commands::config::config_pb::GetConfigResponse> {
-        let guard = self.client.lock().unwrap_or_else(|p| p.into_inner());
-        let url = guard.stack_url().to_string();
-        drop(guard); // Release lock before async call
+        let url = self.stack_url();
 
         // Block on the async fetch via a tokio runtime.
-        tokio::runtime::Runtime::new()
+        let config = tokio::runtime::Runtime::new()
             .map_err(|e| eyre::eyre!("could not start the async runtime: {e}"))?
-            .block_on(async { aspens::commands::config::get_config(url).await })
+            .block_on(async { aspens::commands::config::get_config(url).await })?;
+
+        let now = aspens::util::unix_now();
+        if let Some(notice) = config.maintenance().filter(|n| !n.is_over(now)) {
+            println!("*** {} ***", notice.banner(now));
+        }
+        Ok(config)
     }
 }
 
@@ -158,42 +269,72 @@ struct ReplCli {
     /// Path to environment file (defaults to .env in current directory)
     #[arg(short = 'e', long = "env-file")]
     env_file: Option<String>,
+
+    /// Profile from the config file (~/.config/aspens/config.toml) whose
+    /// stack URL, env file, market, output format and signer to default to;
+    /// defaults to ASPENS_PROFILE, then the config file's default
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Skip the once-a-day check for a newer release
+    #[arg(long)]
+    no_update_check: bool,
+
+    /// Encrypted keystore file (Web3 Secret Storage JSON) holding the EVM
+    /// trader key, used instead of TRADER_PRIVKEY. The passphrase is
+    /// prompted for unless ASPENS_KEYSTORE_PASSPHRASE is set
+    #[arg(long, value_name = "PATH", env = "TRADER_KEYSTORE")]
+    keystore: Option<std::path::PathBuf>,
+
+    /// Account to derive from TRADER_MNEMONIC (BIP-44 path
+    /// m/44'/60'/0'/0/N); defaults to TRADER_ACCOUNT_INDEX, else 0
+    #[arg(long, value_name = "N")]
+    account_index: Option<u32>,
 }
 
 #[derive(Debug, Parser)]
 #[command(name = "", author, version, about, long_about = None)]
 enum ReplCommand {
     /// Fetch and display the configuration from the server
+    #[cfg(feature = "config-export")]
     Config {
         /// Optional path to save the configuration file (supports .json or .toml)
         #[arg(short, long)]
         output_file: Option<String>,
     },
     /// Deposit tokens to make them available for trading (requires network, token, amount)
+    #[cfg(feature = "trading")]
     Deposit {
         /// The network name to deposit to (e.g., anvil-1, base-sepolia)
         network: String,
         /// Token symbol to deposit (e.g., USDC, WETH, WBTC)
         token: String,
-        /// Amount in human-readable units (e.g., "10", "10.5"). Scaled
-        /// by the token's `decimals` from the chain config.
+        /// Amount in human-readable units (e.g., "10", "10.5"), scaled
+        /// by the token's `decimals` from the chain config, or `max` for
+        /// the whole wallet balance, less gas
+        #[arg(value_parser = parse_amount_or_max_arg)]
         amount: String,
     },
     /// Withdraw tokens to a local wallet (requires network, token, amount)
+    #[cfg(feature = "trading")]
     Withdraw {
         /// The network name to withdraw from (e.g., anvil-1, base-sepolia)
         network: String,
         /// Token symbol to withdraw (e.g., USDC, WETH, WBTC)
         token: String,
-        /// Amount in human-readable units (e.g., "10", "10.5"). Scaled
-        /// by the token's `decimals` from the chain config.
+        /// Amount in human-readable units (e.g., "10", "10.5"), scaled
+        /// by the token's `decimals` from the chain config, or `max` for
+        /// the whole available trade balance
+        #[arg(value_parser = parse_amount_or_max_arg)]
         amount: String,
     },
     /// Send a market BUY order (executes at best available price)
+    #[cfg(feature = "trading")]
     BuyMarket {
         /// Market ID to trade on
         market: String,
         /// Amount to buy
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Invisible order: your fills print in the public trade stream
         /// with your side's identity redacted. A market order never
@@ -203,12 +344,15 @@ enum ReplCommand {
         hidden: bool,
     },
     /// Send a limit BUY order (executes at specified price or better)
+    #[cfg(feature = "trading")]
     BuyLimit {
         /// Market ID to trade on
         market: String,
         /// Amount to buy
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Limit price for the order
+        #[arg(value_parser = parse_amount_arg)]
         price: String,
         /// Post-only: reject the order if it would cross at submission.
         /// Use this to guarantee maker-side execution; arborter returns
@@ -222,22 +366,27 @@ enum ReplCommand {
         hidden: bool,
     },
     /// Send a market SELL order (executes at best available price)
+    #[cfg(feature = "trading")]
     SellMarket {
         /// Market ID to trade on
         market: String,
         /// Amount to sell
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Invisible order: see `buy-market --hidden`.
         #[arg(long, default_value_t = false)]
         hidden: bool,
     },
     /// Send a limit SELL order (executes at specified price or better)
+    #[cfg(feature = "trading")]
     SellLimit {
         /// Market ID to trade on
         market: String,
         /// Amount to sell
+        #[arg(value_parser = parse_amount_arg)]
         amount: String,
         /// Limit price for the order
+        #[arg(value_parser = parse_amount_arg)]
         price: String,
         /// Post-only: see `buy-limit --post-only`.
         #[arg(long)]
@@ -247,6 +396,7 @@ enum ReplCommand {
         hidden: bool,
     },
     /// Cancel an existing order by its ID
+    #[cfg(feature = "trading")]
     CancelOrder {
         /// Market ID the order is on
         market: String,
@@ -255,11 +405,45 @@ enum ReplCommand {
         /// The internal order ID to cancel
         order_id: u64,
     },
+    /// Move a resting limit order to a new price or amount (cancel, then resend)
+    #[cfg(feature = "trading")]
+    Replace {
+        /// Market ID the order is on
+        market: String,
+        /// Side of the order: "buy" or "sell"
+        side: String,
+        /// The internal order ID to replace
+        order_id: u64,
+        /// New amount
+        #[arg(value_parser = parse_amount_arg)]
+        amount: String,
+        /// New limit price
+        #[arg(value_parser = parse_amount_arg)]
+        price: String,
+        /// Post-only: see `buy-limit --post-only`.
+        #[arg(long)]
+        post_only: bool,
+        /// Invisible order: see `buy-limit --hidden`.
+        #[arg(long, default_value_t = false)]
+        hidden: bool,
+    },
+    /// List your resting orders on a market
+    #[cfg(feature = "trading")]
+    Orders {
+        /// Market ID to list orders on
+        #[arg(env = "ASPENS_MARKET")]
+        market: String,
+        /// Trader address to list (defaults to the trader wallet)
+        #[arg(long, short = 't', value_parser = parse_address_arg)]
+        trader: Option<String>,
+    },
     /// Fetch the current balances for all supported tokens across all chains
+    #[cfg(feature = "trading")]
     Balance,
     /// Show current configuration and connection status
     Status,
     /// Get the public key and address for the trader wallet
+    #[cfg(feature = "trading")]
     TraderPublicKey,
     /// Get the signer public key(s) for the trading instance
     SignerPublicKey {
@@ -268,42 +452,122 @@ enum ReplCommand {
         chain_network: Option<String>,
     },
     /// Stream orderbook entries in real-time (press Ctrl+C to stop)
+    #[cfg(feature = "streaming")]
     StreamOrderbook {
         /// Market ID to stream orders for
+        #[arg(env = "ASPENS_MARKET")]
         market: String,
         /// Include historical open orders when stream starts
         #[arg(long, short = 'H')]
         historical: bool,
         /// Filter by a specific trader address
-        #[arg(long, short = 't')]
+        #[arg(long, short = 't', value_parser = parse_address_arg)]
         trader: Option<String>,
     },
     /// Stream executed trades in real-time (press Ctrl+C to stop)
+    #[cfg(feature = "streaming")]
     StreamTrades {
         /// Market ID to stream trades for
+        #[arg(env = "ASPENS_MARKET")]
         market: String,
         /// Include historical closed trades when stream starts
         #[arg(long, short = 'H')]
         historical: bool,
         /// Filter by a specific trader address
-        #[arg(long, short = 't')]
+        #[arg(long, short = 't', value_parser = parse_address_arg)]
         trader: Option<String>,
     },
     /// Get TEE attestation report from the signer
+    #[cfg(feature = "attestation")]
     GetAttestation {
         /// Optional hex-encoded data to bind to the attestation report (max 64 bytes)
         #[arg(long)]
         report_data: Option<String>,
         /// Output format: "text" (default) or "json"
-        #[arg(long, short = 'o', default_value = "text")]
+        #[arg(long, short = 'o', env = "ASPENS_OUTPUT", default_value = "text")]
         output: String,
     },
     /// Quit the REPL
     Quit,
 }
 
+/// Fill the flags left unset from the active profile. The REPL signs with
+/// a local key, so a Ledger or KMS signer is an error.
+fn apply_profile(cli: &mut ReplCli, profile: &Profile) -> eyre::Result<()> {
+    if cli.env_file.is_none() {
+        cli.env_file = profile
+            .env_file_path()
+            .map(|path| path.to_string_lossy().into_owned());
+    }
+    if cli.stack_url.is_none()
+        && let Some(ref url) = profile.stack_url
+    {
+        cli.stack_url = Some(
+            url.parse()
+                .map_err(|e| eyre::eyre!("invalid stack URL '{}' in profile: {}", url, e))?,
+        );
+    }
+    match profile.signer {
+        Some(SignerSource::Keystore(_)) if cli.keystore.is_none() => {
+            cli.keystore = profile.keystore();
+        }
+        Some(SignerSource::Mnemonic(index)) if cli.account_index.is_none() => {
+            cli.account_index = Some(index);
+        }
+        Some(ref signer @ (SignerSource::Ledger(_) | SignerSource::Kms(_))) => {
+            eyre::bail!(
+                "the REPL signs with a local key; use aspens-cli for the profile's {} signer",
+                signer
+            )
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Check the release feed in the background, at most once a day, and print
+/// upgrade instructions if a newer release exists. Best effort: a failed
+/// check is only logged at debug level and doesn't count towards the day.
+fn spawn_update_check(stack_url: String) {
+    use aspens::update::{CheckState, check};
+
+    let Ok(mut state) = CheckState::open_default() else {
+        return;
+    };
+    if !state.check_due() {
+        return;
+    }
+    std::thread::spawn(move || {
+        let executor = BlockingExecutor::new();
+        match executor.execute(check(Some(stack_url))) {
+            Ok(status) => {
+                let _ = state.record_check();
+                if status.update_available() {
+                    eprintln!("\n{}\n", status.message());
+                }
+            }
+            Err(e) => tracing::debug!("update check failed: {}", e),
+        }
+    });
+}
+
 fn main() {
-    let cli = ReplCli::parse();
+    // Before clap, which reads the profile's market and output defaults
+    // from the environment.
+    let profile = match aspens_cliutil::activate_profile() {
+        Ok(profile) => profile.map(|(_, profile)| profile).unwrap_or_default(),
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let mut cli = ReplCli::parse();
+    if let Err(e) = apply_profile(&mut cli, &profile) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+    // Prompt for TRADER_KEYSTORE once per session, not once per command.
+    aspens::wallet::remember_keystores();
 
     let subscriber = FmtSubscriber::builder()
         .with_max_level(Level::INFO)
@@ -326,6 +590,9 @@ fn main() {
             }
         };
     }
+    if let Some(ref path) = cli.keystore {
+        builder = builder.with_keystore(path);
+    }
     // A missing/invalid stack URL is a normal misconfiguration, not a bug —
     // print the (actionable) error and exit non-zero instead of panicking with
     // a backtrace. (aspens-cli does this via run() -> Result + ExitCode.)
@@ -333,14 +600,20 @@ fn main() {
         Ok(c) => c,
         Err(e) => {
             eprintln!("error: {e}");
-            eprintln!(
-                "hint: pass --stack <URL>, or set ASPENS_MARKET_STACK_URL (e.g. in a .env file)."
-            );
+            if e.to_string().contains("stack URL") {
+                eprintln!(
+                    "hint: pass --stack <URL>, or set ASPENS_MARKET_STACK_URL (e.g. in a .env file)."
+                );
+            }
             std::process::exit(1);
         }
     };
 
-    let app_state = AppState::new(client);
+    if !cli.no_update_check {
+        spawn_update_check(client.stack_url().to_string());
+    }
+
+    let app_state = AppState::new(client, cli.account_index);
     let executor = BlockingExecutor::new();
 
     let prompt = DefaultPrompt {
@@ -371,18 +644,23 @@ fn main() {
         .build();
 
     rl.repl(|command| match command {
+        #[cfg(feature = "config-export")]
         ReplCommand::Config { output_file } => {
             use aspens::commands::config;
 
             let stack_url = app_state.stack_url();
             info!("Fetching configuration from {}", stack_url);
-            match executor.execute(config::get_config(stack_url.clone())) {
+            match executor.execute_with_timeout(
+                config::get_config(stack_url.clone()),
+                app_state.command_timeout(),
+            ) {
                 Ok(config) => {
                     // If output_file is provided, save to file
                     if let Some(ref path) = output_file {
-                        match executor
-                            .execute(config::download_config(stack_url.clone(), path.clone()))
-                        {
+                        match executor.execute_with_timeout(
+                            config::download_config(stack_url.clone(), path.clone()),
+                            app_state.command_timeout(),
+                        ) {
                             Ok(_) => info!("Configuration saved to: {}", path),
                             Err(e) => print_error(&format_error(
                                 &e,
@@ -400,6 +678,7 @@ fn main() {
                 Err(e) => print_error(&format_error(&e, "fetch configuration")),
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::Deposit {
             network,
             token,
@@ -416,14 +695,19 @@ fn main() {
                 }
             };
 
-            let amount_base = match resolve_token_amount(&config, &network, &token, &amount) {
-                Ok(v) => v,
-                Err(e) => {
-                    print_error(&format_error(
-                        &e,
-                        &format!("deposit {} {} on {}", amount, token, network),
-                    ));
-                    return;
+            // `max` is resolved in the future below, by a balance query.
+            let amount_base = if amount == MAX_AMOUNT {
+                None
+            } else {
+                match resolve_token_amount(&config, &network, &token, &amount) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        print_error(&format_error(
+                            &e,
+                            &format!("deposit {} {} on {}", amount, token, network),
+                        ));
+                        return;
+                    }
                 }
             };
 
@@ -439,6 +723,18 @@ fn main() {
             let net = network.clone();
             let tok = token.clone();
             let res = executor.execute(async move {
+                let amount_base = match amount_base {
+                    Some(v) => v,
+                    None => {
+                        deposit::max_deposit_amount(
+                            net.clone(),
+                            tok.clone(),
+                            wallet.address(),
+                            config.clone(),
+                        )
+                        .await?
+                    }
+                };
                 deposit::call_deposit_from_config_with_wallet(
                     net,
                     tok,
@@ -451,11 +747,12 @@ fn main() {
             match res {
                 Ok(_) => info!("Deposit successful"),
                 Err(e) => print_error(&format_error(
-                    &e,
+                    &e.into(),
                     &format!("deposit {} {} on {}", amount, token, network),
                 )),
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::Withdraw {
             network,
             token,
@@ -472,14 +769,19 @@ fn main() {
                 }
             };
 
-            let amount_base = match resolve_token_amount(&config, &network, &token, &amount) {
-                Ok(v) => v,
-                Err(e) => {
-                    print_error(&format_error(
-                        &e,
-                        &format!("withdraw {} {} from {}", amount, token, network),
-                    ));
-                    return;
+            // `max` is resolved in the future below, by a balance query.
+            let amount_base = if amount == MAX_AMOUNT {
+                None
+            } else {
+                match resolve_token_amount(&config, &network, &token, &amount) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        print_error(&format_error(
+                            &e,
+                            &format!("withdraw {} {} from {}", amount, token, network),
+                        ));
+                        return;
+                    }
                 }
             };
 
@@ -492,6 +794,18 @@ fn main() {
             let net = network.clone();
             let tok = token.clone();
             let res = executor.execute(async move {
+                let amount_base = match amount_base {
+                    Some(v) => v,
+                    None => {
+                        withdraw::max_withdraw_amount(
+                            net.clone(),
+                            tok.clone(),
+                            wallet.address(),
+                            config.clone(),
+                        )
+                        .await?
+                    }
+                };
                 withdraw::call_withdraw_from_config_with_wallet(
                     stack_url,
                     net,
@@ -505,11 +819,12 @@ fn main() {
             match res {
                 Ok(_) => info!("Withdraw successful"),
                 Err(e) => print_error(&format_error(
-                    &e,
+                    &e.into(),
                     &format!("withdraw {} {} from {}", amount, token, network),
                 )),
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::BuyMarket {
             market,
             amount,
@@ -534,15 +849,19 @@ fn main() {
             let url = app_state.stack_url();
             let mkt = market.clone();
             let amt = amount.clone();
-            let res = executor.execute(async move {
-                send_order::send_order_with_wallet(
-                    url, mkt, 1, // Buy side
-                    amt, None, // No limit price (market order)
-                    &wallet, config, false, // post_only meaningless for market orders
-                    hidden,
-                )
-                .await
-            });
+            let risk = app_state.client.risk_limits().clone();
+            let res = executor.execute_with_timeout(
+                async move {
+                    send_order_strict(
+                        url, mkt, 1, // Buy side
+                        amt, None, // No limit price (market order)
+                        &wallet, config, false, // post_only meaningless for market orders
+                        hidden, &risk,
+                    )
+                    .await
+                },
+                app_state.command_timeout(),
+            );
             match res {
                 Ok(result) => {
                     info!(
@@ -563,6 +882,7 @@ fn main() {
                 )),
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::BuyLimit {
             market,
             amount,
@@ -593,20 +913,25 @@ fn main() {
             let mkt = market.clone();
             let amt = amount.clone();
             let prc = price.clone();
-            let res = executor.execute(async move {
-                send_order::send_order_with_wallet(
-                    url,
-                    mkt,
-                    1, // Buy side
-                    amt,
-                    Some(prc),
-                    &wallet,
-                    config,
-                    post_only,
-                    hidden,
-                )
-                .await
-            });
+            let risk = app_state.client.risk_limits().clone();
+            let res = executor.execute_with_timeout(
+                async move {
+                    send_order_strict(
+                        url,
+                        mkt,
+                        1, // Buy side
+                        amt,
+                        Some(prc),
+                        &wallet,
+                        config,
+                        post_only,
+                        hidden,
+                        &risk,
+                    )
+                    .await
+                },
+                app_state.command_timeout(),
+            );
             match res {
                 Ok(result) => {
                     info!(
@@ -630,6 +955,7 @@ fn main() {
                 )),
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::SellMarket {
             market,
             amount,
@@ -654,15 +980,19 @@ fn main() {
             let url = app_state.stack_url();
             let mkt = market.clone();
             let amt = amount.clone();
-            let res = executor.execute(async move {
-                send_order::send_order_with_wallet(
-                    url, mkt, 2, // Sell side
-                    amt, None, // No limit price (market order)
-                    &wallet, config, false, // post_only meaningless for market orders
-                    hidden,
-                )
-                .await
-            });
+            let risk = app_state.client.risk_limits().clone();
+            let res = executor.execute_with_timeout(
+                async move {
+                    send_order_strict(
+                        url, mkt, 2, // Sell side
+                        amt, None, // No limit price (market order)
+                        &wallet, config, false, // post_only meaningless for market orders
+                        hidden, &risk,
+                    )
+                    .await
+                },
+                app_state.command_timeout(),
+            );
             match res {
                 Ok(result) => {
                     info!(
@@ -683,6 +1013,7 @@ fn main() {
                 )),
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::SellLimit {
             market,
             amount,
@@ -713,20 +1044,25 @@ fn main() {
             let mkt = market.clone();
             let amt = amount.clone();
             let prc = price.clone();
-            let res = executor.execute(async move {
-                send_order::send_order_with_wallet(
-                    url,
-                    mkt,
-                    2, // Sell side
-                    amt,
-                    Some(prc),
-                    &wallet,
-                    config,
-                    post_only,
-                    hidden,
-                )
-                .await
-            });
+            let risk = app_state.client.risk_limits().clone();
+            let res = executor.execute_with_timeout(
+                async move {
+                    send_order_strict(
+                        url,
+                        mkt,
+                        2, // Sell side
+                        amt,
+                        Some(prc),
+                        &wallet,
+                        config,
+                        post_only,
+                        hidden,
+                        &risk,
+                    )
+                    .await
+                },
+                app_state.command_timeout(),
+            );
             match res {
                 Ok(result) => {
                     info!(
@@ -750,6 +1086,7 @@ fn main() {
                 )),
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::CancelOrder {
             market,
             side,
@@ -777,12 +1114,15 @@ fn main() {
             let url = app_state.stack_url();
             let mkt = market.clone();
             let sd = side.clone();
-            let res = executor.execute(async move {
-                cancel_order::call_cancel_order_from_config_with_wallet(
-                    url, mkt, sd, order_id, &wallet, config,
-                )
-                .await
-            });
+            let res = executor.execute_with_timeout(
+                async move {
+                    cancel_order::call_cancel_order_from_config_with_wallet(
+                        url, mkt, sd, order_id, &wallet, config,
+                    )
+                    .await
+                },
+                app_state.command_timeout(),
+            );
             match res {
                 Ok(result) => {
                     if result.order_canceled {
@@ -804,21 +1144,149 @@ fn main() {
                 )),
             }
         }
+        #[cfg(feature = "trading")]
+        ReplCommand::Replace {
+            market,
+            side,
+            order_id,
+            amount,
+            price,
+            post_only,
+            hidden,
+        } => {
+            info!(
+                "Replacing order {} ({}) on market {} with {} at {}",
+                order_id, side, market, amount, price
+            );
+            let context = format!("replace order {} on {}", order_id, market);
+            let side = match send_order::parse_side(&side) {
+                Ok(s) => s,
+                Err(e) => {
+                    print_error(&format_error(&eyre::Report::from(e), &context));
+                    return;
+                }
+            };
+
+            // Fetch configuration from server
+            let config = match app_state.get_config_sync() {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    print_error(&format_error(&e, "fetch configuration"));
+                    return;
+                }
+            };
+
+            let wallet = match load_trader_wallet_or_complain(&app_state) {
+                Some(w) => w,
+                None => return,
+            };
+
+            let url = app_state.stack_url();
+            let risk = app_state.client.risk_limits().clone();
+            let res = executor.execute_with_timeout(
+                async move {
+                    replace_order::replace_order(
+                        url,
+                        market,
+                        order_id,
+                        side as i32,
+                        amount,
+                        price,
+                        &[&wallet],
+                        config,
+                        send_order::OrderOptions {
+                            post_only,
+                            hidden,
+                            precision: PrecisionMode::Error,
+                            risk,
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                },
+                app_state.command_timeout(),
+            );
+            match res {
+                Ok(result) => {
+                    info!("Order {}", result);
+                    let hashes = result.placed.get_formatted_transaction_hashes();
+                    if !hashes.is_empty() {
+                        info!("Transaction hashes:");
+                        for formatted_hash in hashes {
+                            info!("  {}", formatted_hash);
+                        }
+                        info!("Paste these hashes into your chain's block explorer");
+                    }
+                }
+                Err(e) => print_error(&format_error(&e, &context)),
+            }
+        }
+        #[cfg(feature = "trading")]
+        ReplCommand::Orders { market, trader } => {
+            let config = match app_state.get_config_sync() {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    print_error(&format_error(&e, "fetch configuration"));
+                    return;
+                }
+            };
+            let context = format!("list orders on {}", market);
+            let resolved = match send_order::lookup_market(&config, &market) {
+                Ok(m) => m,
+                Err(e) => {
+                    print_error(&format_error(&eyre::Report::from(e), &context));
+                    return;
+                }
+            };
+            let trader = match trader {
+                Some(t) => t,
+                None => match load_trader_wallet_or_complain(&app_state) {
+                    Some(w) => w.address(),
+                    None => return,
+                },
+            };
+
+            let res = executor.execute_with_timeout(
+                open_orders::get_orders(
+                    app_state.stack_url(),
+                    resolved.market_id.clone(),
+                    trader.clone(),
+                ),
+                app_state.command_timeout(),
+            );
+            match res {
+                Ok(orders) if orders.is_empty() => {
+                    info!("No open orders for {} on {}", trader, market)
+                }
+                Ok(orders) => info!(
+                    "\n{}",
+                    open_orders::display_orders(&orders, resolved.pair_decimals as u32)
+                ),
+                Err(e) => print_error(&format_error(&e, &context)),
+            }
+        }
+        #[cfg(feature = "trading")]
         ReplCommand::Balance => {
             use aspens::commands::config;
 
             info!("Fetching balances for all tokens across all chains");
             let stack_url = app_state.stack_url();
-            match executor.execute(config::get_config(stack_url.clone())) {
+            match executor.execute_with_timeout(
+                config::get_config(stack_url.clone()),
+                app_state.command_timeout(),
+            ) {
                 Ok(config) => {
                     let wallet = match load_trader_wallet_or_complain(&app_state) {
                         Some(w) => w,
                         None => return,
                     };
-                    let res = executor.execute(async move {
-                        let wallets: [&Wallet; 1] = [&wallet];
-                        balance::balance_from_config_with_wallets(config, &wallets).await
-                    });
+                    let res = executor.execute_with_timeout(
+                        async move {
+                            let wallets: [&dyn AspensSigner; 1] = [&wallet];
+                            balance::balance_from_config_with_wallets(config, &wallets).await
+                        },
+                        app_state.command_timeout(),
+                    );
                     if let Err(e) = res {
                         print_error(&format_error(&e, "fetch balances"));
                     }
@@ -845,36 +1313,20 @@ fn main() {
                 print_status_error(&error_msg);
             }
         }
+        #[cfg(feature = "trading")]
         ReplCommand::TraderPublicKey => {
-            use alloy::signers::local::PrivateKeySigner;
-
-            match app_state.get_env("TRADER_PRIVKEY") {
-                Some(privkey) => match privkey.parse::<PrivateKeySigner>() {
-                    Ok(signer) => {
-                        let address = signer.address();
-                        let pubkey = signer.credential().verifying_key();
-
-                        println!("Trader Wallet:");
-                        println!("  Address:    {}", address);
-                        println!(
-                            "  Public Key: 0x{}",
-                            hex::encode(pubkey.to_encoded_point(false).as_bytes())
-                        );
-                    }
-                    Err(e) => {
-                        println!();
-                        println!("Invalid TRADER_PRIVKEY format");
-                        println!();
-                        println!("Error: {}", e);
-                        println!();
-                        println!("Hints:");
-                        println!("  - The private key should be a 64-character hex string");
-                        println!("  - Do not include the '0x' prefix");
-                        println!("  - Check for extra whitespace or newlines");
-                        println!();
-                    }
-                },
-                None => print_missing_privkey_error(),
+            if let Some(wallet) = load_trader_wallet_or_complain(&app_state)
+                && let Some(signer) = wallet.as_evm()
+            {
+                let address = signer.address();
+                let pubkey = signer.credential().verifying_key();
+
+                println!("Trader Wallet:");
+                println!("  Address:    {}", address);
+                println!(
+                    "  Public Key: 0x{}",
+                    hex::encode(pubkey.to_encoded_point(false).as_bytes())
+                );
             }
         }
         ReplCommand::SignerPublicKey { chain_network } => {
@@ -885,10 +1337,10 @@ fn main() {
                 "Fetching signer public key(s) and gas balances from {}",
                 stack_url
             );
-            match executor.execute(config::get_signer_public_key_with_balances(
-                stack_url,
-                chain_network,
-            )) {
+            match executor.execute_with_timeout(
+                config::get_signer_public_key_with_balances(stack_url, chain_network),
+                app_state.command_timeout(),
+            ) {
                 Ok(signer_infos) => {
                     println!("Signer Public Keys:");
                     for info in &signer_infos {
@@ -900,6 +1352,7 @@ fn main() {
                 Err(e) => print_error(&format_error(&e, "fetch signer public key(s)")),
             }
         }
+        #[cfg(feature = "streaming")]
         ReplCommand::StreamOrderbook {
             market,
             historical,
@@ -918,6 +1371,10 @@ fn main() {
                 market_id: market.clone(),
                 historical_open_orders: historical,
                 filter_by_trader: trader,
+                grpc: None,
+                replay: Default::default(),
+                idle: None,
+                reconnect: None,
             };
 
             println!("Streaming orderbook for market: {}", market);
@@ -934,11 +1391,12 @@ fn main() {
             )) {
                 Ok(_) => info!("Stream ended"),
                 Err(e) => print_error(&format_error(
-                    &e,
+                    &eyre::Report::from(e),
                     &format!("stream orderbook for market {}", market),
                 )),
             }
         }
+        #[cfg(feature = "streaming")]
         ReplCommand::StreamTrades {
             market,
             historical,
@@ -957,6 +1415,10 @@ fn main() {
                 market_id: market.clone(),
                 historical_closed_trades: historical,
                 filter_by_trader: trader,
+                grpc: None,
+                replay: Default::default(),
+                idle: None,
+                reconnect: None,
             };
 
             println!("Streaming trades for market: {}", market);
@@ -969,11 +1431,12 @@ fn main() {
             })) {
                 Ok(_) => info!("Stream ended"),
                 Err(e) => print_error(&format_error(
-                    &e,
+                    &eyre::Report::from(e),
                     &format!("stream trades for market {}", market),
                 )),
             }
         }
+        #[cfg(feature = "attestation")]
         ReplCommand::GetAttestation {
             report_data,
             output,
@@ -1013,7 +1476,10 @@ fn main() {
                 None
             };
 
-            match executor.execute(config::get_attestation(stack_url, report_data_bytes)) {
+            match executor.execute_with_timeout(
+                config::get_attestation(stack_url, report_data_bytes),
+                app_state.command_timeout(),
+            ) {
                 Ok(response) => match output.as_str() {
                     "json" => {
                         if let Some(report) = &response.report {
@@ -1,5 +1,18 @@
+use std::path::Path;
+
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// Directory holding the committed, shipped bindings.
+const GENERATED_DIR: &str = "proto/generated";
+
+/// Every binding `build_protos` emits, relative to its output directory.
+const GENERATED: [&str; 4] = [
+    "xyz.aspens.arborter.v1.rs",
+    "xyz.aspens.arborter_auth.v1.rs",
+    "xyz.aspens.arborter_config.v1.rs",
+    "xyz.aspens.attestation.v1.rs",
+];
+
 // Shared pure transform — also unit-tested under
 // `aspens/tests/build_attestation_paths.rs`.
 mod build_attestation_paths;
@@ -12,6 +25,7 @@ fn main() -> Result<()> {
     println!("cargo:rerun-if-changed=proto/attestation.proto");
     println!("cargo:rerun-if-env-changed=DOCS_RS");
     println!("cargo:rerun-if-env-changed=ASPENS_REGEN_PROTOS");
+    println!("cargo:rerun-if-env-changed=ASPENS_CHECK_PROTOS");
 
     // The generated bindings under proto/generated/ are committed and ship with
     // the crate, so by default we DO NOT regenerate them. tonic_prost_build's
@@ -23,12 +37,20 @@ fn main() -> Result<()> {
     //
     // After editing a *.proto, regenerate explicitly and commit the result:
     //   ASPENS_REGEN_PROTOS=1 cargo build -p aspens
+    //
+    // To verify the committed bindings still match the *.proto sources
+    // (CI does this), regenerate into OUT_DIR and diff instead:
+    //   ASPENS_CHECK_PROTOS=1 cargo build -p aspens
+    if std::env::var_os("ASPENS_CHECK_PROTOS").is_some() {
+        return check_protos();
+    }
+
     let force = std::env::var_os("ASPENS_REGEN_PROTOS").is_some();
     if (!force && generated_present()) || std::env::var_os("DOCS_RS").is_some() {
         return Ok(());
     }
 
-    build_protos()?;
+    build_protos(Path::new(GENERATED_DIR))?;
     Ok(())
 }
 
@@ -36,25 +58,56 @@ fn main() -> Result<()> {
 /// `proto/generated/` (the committed, shipped set). Codegen is skipped in that
 /// case unless `ASPENS_REGEN_PROTOS` forces it.
 fn generated_present() -> bool {
-    use std::path::Path;
-    const GENERATED: [&str; 4] = [
-        "xyz.aspens.arborter.v1.rs",
-        "xyz.aspens.arborter_auth.v1.rs",
-        "xyz.aspens.arborter_config.v1.rs",
-        "xyz.aspens.attestation.v1.rs",
-    ];
     GENERATED
         .iter()
-        .all(|f| Path::new("proto/generated").join(f).exists())
+        .all(|f| Path::new(GENERATED_DIR).join(f).exists())
 }
 
-fn build_protos() -> Result<()> {
+/// Regenerate every binding into `OUT_DIR` and fail if any differs from the
+/// committed copy under `proto/generated/`. Never writes to the source tree.
+fn check_protos() -> Result<()> {
+    use std::fs;
+
+    let out_dir = Path::new(&std::env::var("OUT_DIR")?).join("proto-check");
+    fs::create_dir_all(&out_dir)?;
+    build_protos(&out_dir)?;
+
+    let stale: Vec<&str> = GENERATED
+        .iter()
+        .copied()
+        .filter(|f| {
+            let fresh = fs::read(out_dir.join(f)).ok();
+            let committed = fs::read(Path::new(GENERATED_DIR).join(f)).ok();
+            fresh.is_none() || fresh != committed
+        })
+        .collect();
+
+    if !stale.is_empty() {
+        return Err(format!(
+            "committed proto bindings are out of date with proto/*.proto: {}\n\
+             regenerate with `ASPENS_REGEN_PROTOS=1 cargo build -p aspens` and commit the result",
+            stale.join(", ")
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Serde derive applied to every generated message and enum, so any request,
+/// response or stream event can round-trip through JSON (see `aspens::json`).
+const SERDE_DERIVE: &str = "#[derive(serde::Serialize, serde::Deserialize)]";
+const SERDE_CAMEL_CASE: &str =
+    "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]";
+
+fn build_protos(out_dir: &Path) -> Result<()> {
     // build arborter API
     tonic_prost_build::configure()
         .protoc_arg("--experimental_allow_proto3_optional")
         .build_server(false)
         .build_client(true)
-        .out_dir("proto/generated")
+        .out_dir(out_dir)
+        .message_attribute(".", SERDE_CAMEL_CASE)
+        .enum_attribute(".", SERDE_DERIVE)
         .compile_protos(&["proto/arborter.proto"], &["proto"])?;
 
     // build arborter auth API
@@ -62,23 +115,10 @@ fn build_protos() -> Result<()> {
         .protoc_arg("--experimental_allow_proto3_optional")
         .build_server(false)
         .build_client(true)
-        .out_dir("proto/generated")
-        .type_attribute(
-            "xyz.aspens.arborter_auth.v1.AuthRequest",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_auth.v1.AuthResponse",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_auth.v1.InitializeAdminRequest",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_auth.v1.InitializeAdminResponse",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
+        .out_dir(out_dir)
+        // Auth messages predate the blanket derive and keep their snake_case
+        // JSON keys so existing serialized payloads stay readable.
+        .message_attribute(".", SERDE_DERIVE)
         .compile_protos(&["proto/arborter_auth.proto"], &["proto"])?;
 
     // build arborter_config API
@@ -86,51 +126,30 @@ fn build_protos() -> Result<()> {
         .protoc_arg("--experimental_allow_proto3_optional")
         .build_server(false)
         .build_client(true)
-        .out_dir("proto/generated")
-        .type_attribute(
-            "xyz.aspens.arborter_config.v1.Configuration",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_config.v1.Chain",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_config.v1.Market",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_config.v1.Token",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_config.v1.TradeContract",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_config.v1.GetConfigResponse",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]",
-        )
-        .type_attribute(
-            "xyz.aspens.arborter_config.v1.GetConfigRequest",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"camelCase\")]",
+        .out_dir(out_dir)
+        .message_attribute(".", SERDE_CAMEL_CASE)
+        .enum_attribute(".", SERDE_DERIVE)
+        // Config files saved before signing-version negotiation lack the key.
+        .field_attribute(
+            ".xyz.aspens.arborter_config.v1.Configuration.signing_versions",
+            "#[serde(default)]",
         )
         .compile_protos(&["proto/arborter_config.proto"], &["proto"])?;
 
     // Post-process the generated arborter_config file to fix attestation type references.
     // The generated code uses relative `super::super::super::attestation::v1::` paths,
     // but we need absolute `crate::attestation::v1::` paths for proper module resolution.
-    fix_attestation_paths()?;
+    fix_attestation_paths(out_dir)?;
 
     Ok(())
 }
 
-fn fix_attestation_paths() -> Result<()> {
+fn fix_attestation_paths(out_dir: &Path) -> Result<()> {
     use std::fs;
 
-    let config_file = "proto/generated/xyz.aspens.arborter_config.v1.rs";
-    let content = fs::read_to_string(config_file)?;
+    let config_file = out_dir.join("xyz.aspens.arborter_config.v1.rs");
+    let content = fs::read_to_string(&config_file)?;
     let fixed_content = build_attestation_paths::rewrite_attestation_paths(&content);
-    fs::write(config_file, fixed_content)?;
+    fs::write(&config_file, fixed_content)?;
     Ok(())
 }
@@ -4,8 +4,8 @@
 //!
 //! tonic-prost-build emits cross-package type references relative to
 //! the generated module's own location. The arborter_config module
-//! lives at `crate::proto::config::xyz::aspens::arborter_config::v1`
-//! (four levels deep — see `aspens/src/lib.rs`), and from there it
+//! lives at `crate::commands::config::config_pb` (re-exported as
+//! `crate::proto::v1::config` — see `aspens/src/lib.rs`), and from there it
 //! has to walk back up to `crate::attestation::v1::*`. With the
 //! current module layout that walk-up path is
 //! `super::super::super::attestation::v1::...`.
@@ -0,0 +1,212 @@
+//! Market data snapshot server: the orderbook and recent trades over REST.
+//!
+//! A reference integration for frontends that can't speak gRPC. At startup
+//! the server subscribes to every market in the stack config: a conflated
+//! orderbook stream keeps the latest `BookSnapshot` per market, and a trades
+//! stream fills a bounded cache of recent trades. Requests are answered from
+//! those in-memory copies, never by opening a stream per request.
+//!
+//!   GET /markets          every market with its best bid and ask
+//!   GET /book/{market}    aggregated price levels (`?depth=N`, default 20)
+//!   GET /trades/{market}  the most recent trades, newest first
+//!
+//! `{market}` is a market ID or the `base_network/SYMBOL::quote_network/SYMBOL`
+//! shorthand. Prices and quantities are decimal strings.
+//!
+//! Prerequisites:
+//!   1. A running Aspens Market Stack (e.g. http://localhost:50051)
+//!   2. A `.env` file with ASPENS_MARKET_STACK_URL=http://localhost:50051
+//!
+//! Run:
+//!   cargo run -p aspens --example market_data_server --features axum
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use aspens::commands::config::config_pb::Market;
+use aspens::commands::trading::conflate::{
+    DEFAULT_CONFLATION_INTERVAL, stream_orderbook_conflated,
+};
+use aspens::commands::trading::send_order::lookup_market;
+use aspens::commands::trading::stream_trades::stream_trades_channel;
+use aspens::decimals::format_decimal_amount;
+use aspens::service::{Aspens, AspensService};
+use aspens::{
+    AspensClient, AspensError, BookSnapshot, StreamOrderbookOptions, StreamTradesOptions, Trade,
+};
+use axum::extract::{Path, Query};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::watch;
+
+/// Trades kept per market.
+const TRADE_CACHE_SIZE: usize = 200;
+
+/// Levels per side `/book` returns unless `?depth=` says otherwise.
+const DEFAULT_DEPTH: usize = 20;
+
+/// The in-memory copies the handlers answer from.
+#[derive(Default)]
+struct MarketData {
+    books: HashMap<String, watch::Receiver<BookSnapshot>>,
+    trades: HashMap<String, Arc<RwLock<VecDeque<Trade>>>>,
+}
+
+impl MarketData {
+    /// Subscribe to the book and trades of every market in `markets`.
+    async fn subscribe(stack_url: &str, markets: &[Market]) -> Result<Self, AspensError> {
+        let mut data = Self::default();
+        for market in markets {
+            let id = market.market_id.clone();
+            let (book, _) = stream_orderbook_conflated(
+                stack_url.to_string(),
+                StreamOrderbookOptions {
+                    market_id: id.clone(),
+                    historical_open_orders: true,
+                    ..Default::default()
+                },
+                DEFAULT_CONFLATION_INTERVAL,
+            )
+            .await?;
+            data.books.insert(id.clone(), book);
+
+            let (mut rx, _) = stream_trades_channel(
+                stack_url.to_string(),
+                StreamTradesOptions {
+                    market_id: id.clone(),
+                    historical_closed_trades: true,
+                    ..Default::default()
+                },
+            )
+            .await?;
+            let cache = Arc::new(RwLock::new(VecDeque::with_capacity(TRADE_CACHE_SIZE)));
+            data.trades.insert(id, cache.clone());
+            tokio::spawn(async move {
+                while let Some(trade) = rx.recv().await {
+                    let mut cache = cache.write().unwrap();
+                    if cache.len() == TRADE_CACHE_SIZE {
+                        cache.pop_back();
+                    }
+                    cache.push_front(trade);
+                }
+            });
+        }
+        Ok(data)
+    }
+}
+
+/// `[price, quantity]` pairs as decimal strings.
+fn levels(levels: &[(u128, u128)], depth: usize, decimals: u32) -> Vec<[String; 2]> {
+    levels
+        .iter()
+        .take(depth)
+        .map(|&(price, qty)| {
+            [
+                format_decimal_amount(price, decimals),
+                format_decimal_amount(qty, decimals),
+            ]
+        })
+        .collect()
+}
+
+/// The market `id` names, from the client's cached config.
+async fn market(aspens: &AspensClient, id: &str) -> Result<Market, AspensError> {
+    let config = aspens.shared_config().await?;
+    Ok(lookup_market(&config, id)?.clone())
+}
+
+/// `GET /markets`
+async fn list_markets(
+    aspens: Aspens,
+    Extension(data): Extension<Arc<MarketData>>,
+) -> Result<Json<Value>, AspensError> {
+    let config = aspens.shared_config().await?;
+    let markets = config.config.as_ref().map_or(&[][..], |c| c.markets.as_slice());
+    let out: Vec<Value> = markets
+        .iter()
+        .map(|m| {
+            let decimals = m.pair_decimals as u32;
+            let book = data.books.get(&m.market_id).map(|rx| rx.borrow().clone());
+            let price = |p: Option<u128>| p.map(|p| format_decimal_amount(p, decimals));
+            json!({
+                "market_id": m.market_id,
+                "name": m.name,
+                "base": format!("{}/{}", m.base_chain_network, m.base_chain_token_symbol),
+                "quote": format!("{}/{}", m.quote_chain_network, m.quote_chain_token_symbol),
+                "best_bid": price(book.as_ref().and_then(BookSnapshot::best_bid)),
+                "best_ask": price(book.as_ref().and_then(BookSnapshot::best_ask)),
+            })
+        })
+        .collect();
+    Ok(Json(json!(out)))
+}
+
+#[derive(Deserialize)]
+struct BookQuery {
+    depth: Option<usize>,
+}
+
+/// `GET /book/{market}`
+async fn book(
+    aspens: Aspens,
+    Extension(data): Extension<Arc<MarketData>>,
+    Path(id): Path<String>,
+    Query(query): Query<BookQuery>,
+) -> Result<Json<Value>, AspensError> {
+    let market = market(&aspens, &id).await?;
+    let snapshot = data
+        .books
+        .get(&market.market_id)
+        .map(|rx| rx.borrow().clone())
+        .ok_or_else(|| AspensError::not_found(format!("no book for market {}", id)))?;
+    let depth = query.depth.unwrap_or(DEFAULT_DEPTH);
+    let decimals = market.pair_decimals as u32;
+    Ok(Json(json!({
+        "market_id": market.market_id,
+        "bids": levels(&snapshot.bids, depth, decimals),
+        "asks": levels(&snapshot.asks, depth, decimals),
+        "orders": snapshot.orders,
+    })))
+}
+
+/// `GET /trades/{market}`
+async fn trades(
+    aspens: Aspens,
+    Extension(data): Extension<Arc<MarketData>>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, AspensError> {
+    let market = market(&aspens, &id).await?;
+    let cache = data
+        .trades
+        .get(&market.market_id)
+        .ok_or_else(|| AspensError::not_found(format!("no trades for market {}", id)))?;
+    let trades: Vec<Trade> = cache.read().unwrap().iter().cloned().collect();
+    Ok(Json(
+        json!({ "market_id": market.market_id, "trades": trades }),
+    ))
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let service = AspensService::new(AspensClient::builder().build()?);
+    service.warm().await?;
+    let client = service.client();
+    let config = client.shared_config().await?;
+    let markets = config.config.as_ref().map_or(&[][..], |c| c.markets.as_slice());
+    let data = MarketData::subscribe(client.stack_url().as_str(), markets).await?;
+    println!("Subscribed to {} market(s)", markets.len());
+
+    let app = Router::new()
+        .route("/markets", get(list_markets))
+        .route("/book/{*market}", get(book))
+        .route("/trades/{*market}", get(trades))
+        .layer(Extension(Arc::new(data)))
+        .layer(service.layer());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
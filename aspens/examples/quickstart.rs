@@ -11,7 +11,7 @@
 
 use aspens::commands::config;
 use aspens::commands::trading::{balance, deposit, send_order, withdraw};
-use aspens::{AspensClient, AsyncExecutor, BlockingExecutor, Wallet};
+use aspens::{AspensClient, AspensSigner, AsyncExecutor, BlockingExecutor, Wallet};
 use eyre::Result;
 
 fn main() -> Result<()> {
@@ -38,7 +38,7 @@ fn main() -> Result<()> {
     let pk = privkey.clone();
     executor.execute(async move {
         let wallet = Wallet::from_evm_hex(&pk)?;
-        let wallets: [&Wallet; 1] = [&wallet];
+        let wallets: [&dyn AspensSigner; 1] = [&wallet];
         balance::balance_from_config_with_wallets(cfg_clone, &wallets).await
     })?;
 
@@ -25,6 +25,7 @@ fn main() {
         ],
         current_orderbook: vec![],
         order_id: 0,
+        client_order_id: String::new(),
     };
 
     println!("=== CLI Output Example ===");
@@ -0,0 +1,101 @@
+//! Web service: expose quotes and balances over REST with axum.
+//!
+//! One `AspensService` is built at startup and shared through its tower
+//! layer; each handler takes the `Aspens` extractor, a handle on that shared
+//! client, and returns `AspensError` directly (mapped to an HTTP status).
+//!
+//! Prerequisites:
+//!   1. A running Aspens Market Stack (e.g. http://localhost:50051)
+//!   2. A `.env` file with at least:
+//!      ASPENS_MARKET_STACK_URL=http://localhost:50051
+//!      TRADER_PRIVKEY=<your-64-char-hex-private-key>
+//!
+//! Run:
+//!   cargo run -p aspens --example web_service --features axum
+//!
+//! Then:
+//!   curl localhost:3000/balances
+//!   curl localhost:3000/quote/<MARKET_ID>
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use aspens::commands::trading::{send_order, stream_orderbook};
+use aspens::decimals::format_decimal_amount;
+use aspens::service::{Aspens, AspensService};
+use aspens::{AspensApi, AspensClient, AspensError, Wallet};
+use axum::extract::Path;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde_json::{Value, json};
+
+/// How long to listen to the orderbook stream for the best bid and ask.
+const QUOTE_WINDOW: Duration = Duration::from_millis(1_500);
+
+/// `GET /quote/{market}`: best bid and ask, as decimal strings.
+async fn quote(aspens: Aspens, Path(market): Path<String>) -> Result<Json<Value>, AspensError> {
+    let config = aspens.shared_config().await?;
+    let market = send_order::lookup_market(&config, &market)?;
+    let decimals = market.pair_decimals as u32;
+    let top = stream_orderbook::fetch_top_of_book(
+        aspens.stack_url().to_string(),
+        market.market_id.clone(),
+        QUOTE_WINDOW,
+    )
+    .await?;
+    let price = |p: Option<u128>| p.map(|p| format_decimal_amount(p, decimals));
+    Ok(Json(json!({
+        "market_id": market.market_id,
+        "best_bid": price(top.best_bid),
+        "best_ask": price(top.best_ask),
+    })))
+}
+
+/// `GET /balances`: wallet, deposited and locked balances per token and chain.
+async fn balances(
+    aspens: Aspens,
+    Extension(wallet): Extension<Arc<Wallet>>,
+) -> Result<Json<Value>, AspensError> {
+    let snapshot = aspens.balances(&[wallet.as_ref()]).await?;
+    let tokens: Vec<Value> = snapshot
+        .tokens
+        .iter()
+        .map(|token| {
+            json!({
+                "symbol": token.token_info.symbol,
+                "decimals": token.token_info.decimals,
+                "chains": token.chain_balances.iter().map(|cb| json!({
+                    "network": cb.chain_network,
+                    "wallet": cb.wallet_balance,
+                    "available": cb.available_balance,
+                    "locked": cb.locked_balance,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    Ok(Json(json!({ "tokens": tokens })))
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    // Built once; every request shares its cached config and connections.
+    let service = AspensService::new(AspensClient::builder().build()?);
+    service.warm().await?;
+    let wallet = Wallet::from_evm_hex(
+        service
+            .client()
+            .get_env("TRADER_PRIVKEY")
+            .expect("TRADER_PRIVKEY must be set in .env"),
+    )?;
+
+    let app = Router::new()
+        .route("/quote/{*market}", get(quote))
+        .route("/balances", get(balances))
+        .layer(Extension(Arc::new(wallet)))
+        .layer(service.layer());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
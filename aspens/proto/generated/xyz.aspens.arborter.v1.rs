@@ -1,7 +1,11 @@
 // This file is @generated by prost-build.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Empty {}
 /// rpc: Withdraw
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct WithdrawRequest {
     /// The chain/network the funds sit on (network key, e.g. "flare-coston2").
@@ -22,6 +26,8 @@ pub struct WithdrawRequest {
     #[prost(bytes = "vec", tag = "5")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct WithdrawResponse {
     /// The voucher fields the holder submits to MidribV2.withdraw(...).
@@ -39,13 +45,20 @@ pub struct WithdrawResponse {
     #[prost(bytes = "vec", tag = "6")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct CancelOrderRequest {
     #[prost(message, optional, tag = "1")]
     pub order: ::core::option::Option<OrderToCancel>,
     #[prost(bytes = "vec", tag = "2")]
     pub signature_hash: ::prost::alloc::vec::Vec<u8>,
+    /// Encoding of the signed OrderToCancel bytes; see SendOrderRequest.
+    #[prost(uint32, tag = "3")]
+    pub signing_version: u32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CancelOrderResponse {
     /// Whether the order was found and canceled
@@ -58,6 +71,8 @@ pub struct CancelOrderResponse {
     #[prost(message, repeated, tag = "3")]
     pub current_orderbook: ::prost::alloc::vec::Vec<OrderbookEntry>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct OrderbookRequest {
     /// Used to keep the client alive
@@ -72,7 +87,18 @@ pub struct OrderbookRequest {
     /// If this is sent too, then we filter by a specific trader
     #[prost(string, optional, tag = "4")]
     pub filter_by_trader: ::core::option::Option<::prost::alloc::string::String>,
+    /// Only replay orders placed at or after this unix timestamp (milliseconds)
+    #[prost(uint64, optional, tag = "5")]
+    pub historical_since: ::core::option::Option<u64>,
+    /// Only replay the most recent N historical orders
+    #[prost(uint32, optional, tag = "6")]
+    pub historical_limit: ::core::option::Option<u32>,
+    /// Page size the server reads history in
+    #[prost(uint32, optional, tag = "7")]
+    pub historical_batch_size: ::core::option::Option<u32>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct TradeRequest {
     /// Used to keep the client alive
@@ -87,7 +113,18 @@ pub struct TradeRequest {
     /// If this is sent too, then we filter by a specific trader
     #[prost(string, optional, tag = "4")]
     pub filter_by_trader: ::core::option::Option<::prost::alloc::string::String>,
+    /// Only replay trades executed at or after this unix timestamp (milliseconds)
+    #[prost(uint64, optional, tag = "5")]
+    pub historical_since: ::core::option::Option<u64>,
+    /// Only replay the most recent N historical trades
+    #[prost(uint32, optional, tag = "6")]
+    pub historical_limit: ::core::option::Option<u32>,
+    /// Page size the server reads history in
+    #[prost(uint32, optional, tag = "7")]
+    pub historical_batch_size: ::core::option::Option<u32>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SendOrderRequest {
     /// The order to send
@@ -103,6 +140,11 @@ pub struct SendOrderRequest {
     /// the request boundary.
     #[prost(message, optional, tag = "3")]
     pub authorization: ::core::option::Option<OrderAuthorization>,
+    /// Encoding of the signed Order bytes, picked from
+    /// Configuration.signing_versions. 0 (unset) means v1: fields in tag
+    /// order, proto3 defaults omitted, byte-identical to the legacy encoding.
+    #[prost(uint32, tag = "4")]
+    pub signing_version: u32,
 }
 /// SDK-derived order authorization. Under the optimistic shadow ledger, order
 /// entry never touches the chain — the arborter authenticates the order via the
@@ -111,6 +153,8 @@ pub struct SendOrderRequest {
 /// (user_signature / deadline / nonce / open_deadline / amount_out) were removed
 /// with the on-chain order machinery; the message and its `SendOrderRequest`
 /// field were renamed from `GaslessAuthorization` / `gasless` to match.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct OrderAuthorization {
     /// The canonical on-chain order id (32-byte hex, 0x-prefixed), derived by the
@@ -126,6 +170,8 @@ pub struct OrderAuthorization {
     #[prost(string, tag = "2")]
     pub amount_in: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Order {
     /// 'BID' or 'ASK'
@@ -146,7 +192,8 @@ pub struct Order {
     /// User's pubkey (address) on the Quote chain
     #[prost(string, tag = "6")]
     pub quote_account_address: ::prost::alloc::string::String,
-    /// 'DIRECT' (default) or 'DISCRETIONARY'
+    /// 'DIRECT' (default, good-till-cancel), 'DISCRETIONARY',
+    /// 'IMMEDIATE_OR_CANCEL' or 'FILL_OR_KILL'
     #[prost(enumeration = "ExecutionType", tag = "7")]
     pub execution_type: i32,
     /// When execution_type == 'discretionary', include order_ids to match with.
@@ -172,7 +219,31 @@ pub struct Order {
     /// are byte-identical.
     #[prost(bool, tag = "10")]
     pub hidden: bool,
+    /// Auction order: queued for the market's next periodic batch auction
+    /// instead of matching continuously on arrival. All auction orders queued
+    /// for a clear fill at the single clearing price. Rejected on markets
+    /// without batch auctions. Defaults to false (wire-skipped), so
+    /// pre-feature signed envelopes are byte-identical.
+    #[prost(bool, tag = "11")]
+    pub auction: bool,
+    /// Good-till-time expiry: unix seconds after which the stack cancels the
+    /// order if any of it is still resting. 0 (default, wire-skipped) never
+    /// expires, so pre-feature signed envelopes are byte-identical. Limit
+    /// orders only; incompatible with IMMEDIATE_OR_CANCEL and FILL_OR_KILL,
+    /// which never rest.
+    #[prost(uint64, tag = "12")]
+    pub expires_at: u64,
+    /// Optional caller-chosen ID, at most 64 bytes of printable ASCII without
+    /// spaces, unique among the account's orders. The stack rejects a second
+    /// order with the same ID instead of placing it, and echoes the ID in
+    /// SendOrderResponse.
+    /// Empty (default, wire-skipped) keeps pre-feature signed envelopes
+    /// byte-identical.
+    #[prost(string, tag = "13")]
+    pub client_order_id: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Trade {
     /// When the engine registers this executed trade
@@ -213,6 +284,8 @@ pub struct Trade {
     pub order_hit: u64,
 }
 /// Transaction hash information
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct TransactionHash {
     /// Type of transaction hash
@@ -223,6 +296,8 @@ pub struct TransactionHash {
     pub hash_value: ::prost::alloc::string::String,
 }
 /// rpc: SendOrder
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SendOrderResponse {
     /// Whether the order has unmatched volume and has been added to the book.
@@ -243,8 +318,13 @@ pub struct SendOrderResponse {
     /// The unique identifier for this order
     #[prost(uint64, tag = "6")]
     pub order_id: u64,
+    /// The order's client_order_id, echoed; empty if it had none
+    #[prost(string, tag = "7")]
+    pub client_order_id: ::prost::alloc::string::String,
 }
 /// rpc: CancelOrder
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct OrderToCancel {
     /// Identity the market: concat(base_chain_id "::" token_address "::" quote_chain_id "::" token_address)
@@ -261,6 +341,8 @@ pub struct OrderToCancel {
     pub order_id: u64,
 }
 /// rpc: Orderbook
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct OrderbookEntry {
     /// when the order first landed in The Orderbook
@@ -295,6 +377,214 @@ pub struct OrderbookEntry {
     #[prost(bool, tag = "10")]
     pub post_only: bool,
 }
+/// rpc: GetAuctionSchedule
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AuctionScheduleRequest {
+    #[prost(string, tag = "1")]
+    pub market_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AuctionSchedule {
+    #[prost(string, tag = "1")]
+    pub market_id: ::prost::alloc::string::String,
+    /// False when the market matches continuously.
+    #[prost(bool, tag = "2")]
+    pub enabled: bool,
+    /// Seconds between clears.
+    #[prost(uint64, tag = "3")]
+    pub interval_secs: u64,
+    /// Unix seconds of the next clear; 0 when disabled.
+    #[prost(uint64, tag = "4")]
+    pub next_auction_at: u64,
+    /// Auction orders queued for the next clear.
+    #[prost(uint32, tag = "5")]
+    pub pending_orders: u32,
+}
+/// rpc: GetAuctionResults
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AuctionResultsRequest {
+    #[prost(string, tag = "1")]
+    pub market_id: ::prost::alloc::string::String,
+    /// Maximum results to return; 0 lets the server pick.
+    #[prost(uint32, tag = "2")]
+    pub limit: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AuctionResult {
+    #[prost(uint64, tag = "1")]
+    pub auction_id: u64,
+    /// Unix seconds the auction cleared.
+    #[prost(uint64, tag = "2")]
+    pub cleared_at: u64,
+    /// Uniform clearing price in pair decimals; empty if nothing crossed.
+    #[prost(string, tag = "3")]
+    pub clearing_price: ::prost::alloc::string::String,
+    /// Total quantity matched, in pair decimals.
+    #[prost(string, tag = "4")]
+    pub matched_quantity: ::prost::alloc::string::String,
+    /// Orders that (partially) filled in this clear.
+    #[prost(uint32, tag = "5")]
+    pub orders_filled: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AuctionResultsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<AuctionResult>,
+}
+/// rpc: RequestQuotes
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct QuoteRequest {
+    #[prost(string, tag = "1")]
+    pub market_id: ::prost::alloc::string::String,
+    /// The requester's side: BID to buy base, ASK to sell it.
+    #[prost(enumeration = "Side", tag = "2")]
+    pub side: i32,
+    /// Size wanted, in pair decimals.
+    #[prost(string, tag = "3")]
+    pub quantity: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub base_account_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub quote_account_address: ::prost::alloc::string::String,
+    /// Seconds dealers have to respond; 0 lets the server pick.
+    #[prost(uint32, tag = "6")]
+    pub ttl_secs: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Quote {
+    /// Identifies the quote request; shared by every quote it receives.
+    #[prost(string, tag = "1")]
+    pub rfq_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub quote_id: ::prost::alloc::string::String,
+    /// Dealer identity as published by the stack.
+    #[prost(string, tag = "3")]
+    pub dealer: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub market_id: ::prost::alloc::string::String,
+    /// The requester's side, echoed from the request.
+    #[prost(enumeration = "Side", tag = "5")]
+    pub side: i32,
+    /// Price in pair decimals.
+    #[prost(string, tag = "6")]
+    pub price: ::prost::alloc::string::String,
+    /// Size the dealer will fill, in pair decimals; may be below the request.
+    #[prost(string, tag = "7")]
+    pub quantity: ::prost::alloc::string::String,
+    /// Unix seconds after which the quote can no longer be accepted.
+    #[prost(uint64, tag = "8")]
+    pub expires_at: u64,
+}
+/// rpc: AcceptQuote
+/// The signed part of a quote acceptance.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct QuoteAcceptance {
+    #[prost(string, tag = "1")]
+    pub rfq_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub quote_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub market_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "Side", tag = "4")]
+    pub side: i32,
+    #[prost(string, tag = "5")]
+    pub price: ::prost::alloc::string::String,
+    #[prost(string, tag = "6")]
+    pub quantity: ::prost::alloc::string::String,
+    #[prost(string, tag = "7")]
+    pub base_account_address: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub quote_account_address: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AcceptQuoteRequest {
+    #[prost(message, optional, tag = "1")]
+    pub acceptance: ::core::option::Option<QuoteAcceptance>,
+    /// Signature over the encoded acceptance by the wallet that locks funds.
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature_hash: ::prost::alloc::vec::Vec<u8>,
+    /// As SendOrderRequest.authorization, derived for the quoted price and
+    /// quantity.
+    #[prost(message, optional, tag = "3")]
+    pub authorization: ::core::option::Option<OrderAuthorization>,
+    /// Encoding of the signed QuoteAcceptance bytes; see SendOrderRequest.
+    #[prost(uint32, tag = "4")]
+    pub signing_version: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AcceptQuoteResponse {
+    /// False when the quote expired or the dealer withdrew it.
+    #[prost(bool, tag = "1")]
+    pub accepted: bool,
+    /// Order ID the execution settles under.
+    #[prost(uint64, tag = "2")]
+    pub order_id: u64,
+    #[prost(message, repeated, tag = "3")]
+    pub transaction_hashes: ::prost::alloc::vec::Vec<TransactionHash>,
+    /// Why the quote wasn't accepted; empty when accepted.
+    #[prost(string, tag = "4")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// rpc: GetRebates
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RebatesRequest {
+    #[prost(string, tag = "1")]
+    pub referral_code: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Rebate {
+    #[prost(string, tag = "1")]
+    pub network: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub token_symbol: ::prost::alloc::string::String,
+    /// Earned but not yet paid out, in the token's decimals.
+    #[prost(string, tag = "3")]
+    pub accrued: ::prost::alloc::string::String,
+    /// Paid out to date, in the token's decimals.
+    #[prost(string, tag = "4")]
+    pub paid: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RebatesResponse {
+    #[prost(string, tag = "1")]
+    pub referral_code: ::prost::alloc::string::String,
+    /// Share of the fees on referred orders paid back, in basis points.
+    #[prost(uint32, tag = "2")]
+    pub rebate_bps: u32,
+    /// Orders attributed to the code.
+    #[prost(uint64, tag = "3")]
+    pub referred_orders: u64,
+    /// One entry per network and token rebates were earned in.
+    #[prost(message, repeated, tag = "4")]
+    pub rebates: ::prost::alloc::vec::Vec<Rebate>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum Side {
@@ -324,6 +614,7 @@ impl Side {
         }
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum ExecutionType {
@@ -331,6 +622,12 @@ pub enum ExecutionType {
     Unspecified = 0,
     /// For dealroom use.
     Discretionary = 1,
+    /// Match what crosses on arrival, then cancel the remainder instead of
+    /// resting it.
+    ImmediateOrCancel = 2,
+    /// Fill the whole quantity on arrival or reject the order without
+    /// matching any of it.
+    FillOrKill = 3,
 }
 impl ExecutionType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -341,6 +638,8 @@ impl ExecutionType {
         match self {
             Self::Unspecified => "EXECUTION_TYPE_UNSPECIFIED",
             Self::Discretionary => "EXECUTION_TYPE_DISCRETIONARY",
+            Self::ImmediateOrCancel => "EXECUTION_TYPE_IMMEDIATE_OR_CANCEL",
+            Self::FillOrKill => "EXECUTION_TYPE_FILL_OR_KILL",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -348,11 +647,14 @@ impl ExecutionType {
         match value {
             "EXECUTION_TYPE_UNSPECIFIED" => Some(Self::Unspecified),
             "EXECUTION_TYPE_DISCRETIONARY" => Some(Self::Discretionary),
+            "EXECUTION_TYPE_IMMEDIATE_OR_CANCEL" => Some(Self::ImmediateOrCancel),
+            "EXECUTION_TYPE_FILL_OR_KILL" => Some(Self::FillOrKill),
             _ => None,
         }
     }
 }
 /// The actual state of an order in the matching engine
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum OrderState {
@@ -396,6 +698,7 @@ impl OrderState {
         }
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum TradeRole {
@@ -659,5 +962,159 @@ pub mod arborter_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Batch-auction cadence for a market. Markets that match continuously
+        /// report enabled = false.
+        pub async fn get_auction_schedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AuctionScheduleRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AuctionSchedule>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/xyz.aspens.arborter.v1.ArborterService/GetAuctionSchedule",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "xyz.aspens.arborter.v1.ArborterService",
+                        "GetAuctionSchedule",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Most recent batch-auction clears for a market, newest first.
+        pub async fn get_auction_results(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AuctionResultsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AuctionResultsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/xyz.aspens.arborter.v1.ArborterService/GetAuctionResults",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "xyz.aspens.arborter.v1.ArborterService",
+                        "GetAuctionResults",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Request-for-quote: broadcast a size to dealers and stream back their
+        /// quotes until the request's TTL lapses.
+        pub async fn request_quotes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QuoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::Quote>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/xyz.aspens.arborter.v1.ArborterService/RequestQuotes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "xyz.aspens.arborter.v1.ArborterService",
+                        "RequestQuotes",
+                    ),
+                );
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Accept one dealer quote with a signed execution. It settles at the
+        /// quoted price and quantity like a matched order.
+        pub async fn accept_quote(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AcceptQuoteRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AcceptQuoteResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/xyz.aspens.arborter.v1.ArborterService/AcceptQuote",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "xyz.aspens.arborter.v1.ArborterService",
+                        "AcceptQuote",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Rebates accrued by a referral code. Orders are attributed to a code by
+        /// the x-aspens-referral request header on SendOrder.
+        pub async fn get_rebates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RebatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RebatesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/xyz.aspens.arborter.v1.ArborterService/GetRebates",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "xyz.aspens.arborter.v1.ArborterService",
+                        "GetRebates",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
@@ -1,16 +1,22 @@
 // This file is @generated by prost-build.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct UpdateAdminRequest {
     /// The address to set for admin management operations
     #[prost(string, tag = "1")]
     pub admin_address: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct UpdateAdminResponse {
     /// The address that has been set for admin management operations
     #[prost(string, tag = "1")]
     pub admin_address: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct GetDeployCalldataRequest {
     /// The name of the chain to deploy the instance on. e.g. base-sepolia
@@ -20,6 +26,8 @@ pub struct GetDeployCalldataRequest {
     #[prost(uint32, tag = "2")]
     pub fee_bps: u32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct GetDeployCalldataResponse {
     /// The factory contract address to call
@@ -36,6 +44,8 @@ pub struct GetDeployCalldataResponse {
     #[prost(uint32, tag = "4")]
     pub chain_id: u32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct DeployContractRequest {
     /// The name of the chain to deploy the instance on. e.g. base-sepolia
@@ -61,6 +71,8 @@ pub struct DeployContractRequest {
     #[prost(uint32, tag = "4")]
     pub fee_bps: u32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct DeployContractResponse {
     /// The address that the instance is deployed to on the specified chain.
@@ -73,12 +85,16 @@ pub struct DeployContractResponse {
     #[prost(string, tag = "2")]
     pub tx_signature: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SetChainRequest {
     /// The chain to set
     #[prost(message, optional, tag = "1")]
     pub chain: ::core::option::Option<Chain>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SetChainResponse {
     /// Indicates if the chain was set successfully
@@ -88,6 +104,8 @@ pub struct SetChainResponse {
     #[prost(message, optional, tag = "2")]
     pub config: ::core::option::Option<Configuration>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetTokenRequest {
     /// The name of the chain to set the token on. e.g. base-sepolia
@@ -97,6 +115,8 @@ pub struct SetTokenRequest {
     #[prost(message, optional, tag = "2")]
     pub token: ::core::option::Option<Token>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SetTokenResponse {
     /// Indicates if the token was set successfully
@@ -106,6 +126,8 @@ pub struct SetTokenResponse {
     #[prost(message, optional, tag = "2")]
     pub config: ::core::option::Option<Configuration>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetMarketRequest {
     /// The base chain network
@@ -136,6 +158,8 @@ pub struct SetMarketRequest {
     #[prost(int32, tag = "9")]
     pub pair_decimals: i32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SetMarketResponse {
     /// Indicates if the market was set successfully
@@ -145,6 +169,8 @@ pub struct SetMarketResponse {
     #[prost(message, optional, tag = "2")]
     pub config: ::core::option::Option<Configuration>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetTradeContractRequest {
     /// The chain address of the trading instance
@@ -154,12 +180,16 @@ pub struct SetTradeContractRequest {
     #[prost(string, tag = "2")]
     pub chain_network: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetTradeContractResponse {
     /// The created trading instance
     #[prost(message, optional, tag = "1")]
     pub trade_contract: ::core::option::Option<TradeContract>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetOperatorFeeRequest {
     /// The chain network whose instance to update. e.g. base-sepolia
@@ -173,6 +203,8 @@ pub struct SetOperatorFeeRequest {
     #[prost(uint32, tag = "3")]
     pub bps: u32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetOperatorFeeResponse {
     /// On-chain tx hash / signature for the setOperatorFee call (0x-hex EVM,
@@ -180,6 +212,8 @@ pub struct SetOperatorFeeResponse {
     #[prost(string, tag = "1")]
     pub tx_signature: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetOperatorAdminRequest {
     /// The chain network whose instance to update.
@@ -189,6 +223,8 @@ pub struct SetOperatorAdminRequest {
     #[prost(string, tag = "2")]
     pub new_admin: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct SetOperatorAdminResponse {
     /// On-chain tx hash / signature for the setOperatorAdmin call.
@@ -221,6 +257,29 @@ pub struct Configuration {
     /// List of supported markets
     #[prost(message, repeated, tag = "2")]
     pub markets: ::prost::alloc::vec::Vec<Market>,
+    /// Operator message / scheduled maintenance, if the admin set one
+    #[prost(message, optional, tag = "3")]
+    pub maintenance: ::core::option::Option<MaintenanceNotice>,
+    /// Signed-payload encodings the stack verifies (see
+    /// SendOrderRequest.signing_version). Empty means v1 only.
+    #[prost(uint32, repeated, tag = "4")]
+    #[serde(default)]
+    pub signing_versions: ::prost::alloc::vec::Vec<u32>,
+}
+/// An operator message, optionally tied to a maintenance window
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct MaintenanceNotice {
+    /// Message to show traders, e.g. 'Upgrading settlement contracts'
+    #[prost(string, tag = "1")]
+    pub message: ::prost::alloc::string::String,
+    /// Optional: unix seconds the maintenance window starts
+    #[prost(uint64, optional, tag = "2")]
+    pub starts_at: ::core::option::Option<u64>,
+    /// Optional: unix seconds the maintenance window ends
+    #[prost(uint64, optional, tag = "3")]
+    pub ends_at: ::core::option::Option<u64>,
 }
 /// Represents the trading instance details
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -302,6 +361,84 @@ pub struct Market {
     /// Identity the market: concat(base_network "::" token_address "::" quote_network "::" token_address)
     #[prost(string, tag = "9")]
     pub market_id: ::prost::alloc::string::String,
+    /// Optional: trading hours; unset means the market is always open
+    #[prost(message, optional, tag = "10")]
+    pub schedule: ::core::option::Option<MarketSchedule>,
+    /// Optional: fee rates; unset means the stack doesn't publish them
+    #[prost(message, optional, tag = "11")]
+    pub fees: ::core::option::Option<MarketFees>,
+    /// Optional: price and size increments; unset means any amount the pair decimals can express
+    #[prost(message, optional, tag = "12")]
+    pub limits: ::core::option::Option<MarketLimits>,
+}
+/// A market's order price and size rules, as decimal strings in the market's pair units (e.g. "0.01"); empty means no rule
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct MarketLimits {
+    /// Limit prices must be a multiple of this
+    #[prost(string, tag = "1")]
+    pub tick_size: ::prost::alloc::string::String,
+    /// Quantities must be a multiple of this
+    #[prost(string, tag = "2")]
+    pub lot_size: ::prost::alloc::string::String,
+    /// Smallest price x quantity an order may have
+    #[prost(string, tag = "3")]
+    pub min_notional: ::prost::alloc::string::String,
+}
+/// A market's trading fees, in basis points of settled notional
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct MarketFees {
+    /// Charged on fills of resting (maker) orders
+    #[prost(uint32, tag = "1")]
+    pub maker_bps: u32,
+    /// Charged on fills of incoming (taker) orders
+    #[prost(uint32, tag = "2")]
+    pub taker_bps: u32,
+}
+/// A market's trading hours
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct MarketSchedule {
+    /// Recurring windows the market is open in; empty means always open
+    #[prost(message, repeated, tag = "1")]
+    pub windows: ::prost::alloc::vec::Vec<TradingWindow>,
+    /// One-off blocks the market is closed for, overriding the windows
+    #[prost(message, repeated, tag = "2")]
+    pub blocks: ::prost::alloc::vec::Vec<MarketBlock>,
+}
+/// A recurring daily open/close window, in UTC
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct TradingWindow {
+    /// Days of the week the window opens on (0 = Sunday .. 6 = Saturday); empty means every day
+    #[prost(uint32, repeated, tag = "1")]
+    pub days: ::prost::alloc::vec::Vec<u32>,
+    /// Minutes after 00:00 UTC the window opens
+    #[prost(uint32, tag = "2")]
+    pub open_minute: u32,
+    /// Minutes after 00:00 UTC the window closes (exclusive); below open_minute wraps past midnight
+    #[prost(uint32, tag = "3")]
+    pub close_minute: u32,
+}
+/// A one-off closure, e.g. a maintenance block
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct MarketBlock {
+    /// Unix seconds the block starts
+    #[prost(uint64, tag = "1")]
+    pub starts_at: u64,
+    /// Unix seconds the block ends (exclusive)
+    #[prost(uint64, tag = "2")]
+    pub ends_at: u64,
+    /// Optional: why the market is closed
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
 }
 /// Represents a single token
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -325,6 +462,8 @@ pub struct Token {
     pub decimals: u32,
 }
 /// Request to get signer public key(s)
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct GetSignerPublicKeyRequest {
     /// Optional: Request specific chain by network name
@@ -333,6 +472,8 @@ pub struct GetSignerPublicKeyRequest {
     pub chain_network: ::core::option::Option<::prost::alloc::string::String>,
 }
 /// Single chain's public key information
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct ChainPublicKey {
     /// The chain ID
@@ -346,6 +487,8 @@ pub struct ChainPublicKey {
     pub public_key: ::prost::alloc::string::String,
 }
 /// Response with public keys per chain
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetSignerPublicKeyResponse {
     /// Map of chain network name to public key info
@@ -356,6 +499,8 @@ pub struct GetSignerPublicKeyResponse {
     >,
 }
 /// Delete market request
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct DeleteMarketRequest {
     /// The market ID to delete
@@ -363,6 +508,8 @@ pub struct DeleteMarketRequest {
     pub market_id: ::prost::alloc::string::String,
 }
 /// Delete market response
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteMarketResponse {
     /// Indicates if the market was deleted successfully
@@ -373,6 +520,8 @@ pub struct DeleteMarketResponse {
     pub config: ::core::option::Option<Configuration>,
 }
 /// Delete token request
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct DeleteTokenRequest {
     /// The chain network where the token exists
@@ -383,6 +532,8 @@ pub struct DeleteTokenRequest {
     pub token_symbol: ::prost::alloc::string::String,
 }
 /// Delete token response
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteTokenResponse {
     /// Indicates if the token was deleted successfully
@@ -393,6 +544,8 @@ pub struct DeleteTokenResponse {
     pub config: ::core::option::Option<Configuration>,
 }
 /// Delete chain request
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct DeleteChainRequest {
     /// The chain network to delete
@@ -400,6 +553,8 @@ pub struct DeleteChainRequest {
     pub chain_network: ::prost::alloc::string::String,
 }
 /// Delete chain response
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteChainResponse {
     /// Indicates if the chain was deleted successfully
@@ -410,6 +565,8 @@ pub struct DeleteChainResponse {
     pub config: ::core::option::Option<Configuration>,
 }
 /// Delete trading instance request
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct DeleteTradeContractRequest {
     /// The chain network to delete the trading instance from
@@ -417,6 +574,8 @@ pub struct DeleteTradeContractRequest {
     pub chain_network: ::prost::alloc::string::String,
 }
 /// Delete trading instance response
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteTradeContractResponse {
     /// Indicates if the trading instance was deleted successfully
@@ -426,9 +585,48 @@ pub struct DeleteTradeContractResponse {
     #[prost(message, optional, tag = "2")]
     pub config: ::core::option::Option<Configuration>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct SetMaintenanceRequest {
+    /// The notice to serve; unset clears the current one
+    #[prost(message, optional, tag = "1")]
+    pub notice: ::core::option::Option<MaintenanceNotice>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetMaintenanceResponse {
+    /// The configuration object after the update
+    #[prost(message, optional, tag = "1")]
+    pub config: ::core::option::Option<Configuration>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct SetMarketScheduleRequest {
+    /// The market to update
+    #[prost(string, tag = "1")]
+    pub market_id: ::prost::alloc::string::String,
+    /// The schedule to apply; unset clears it
+    #[prost(message, optional, tag = "2")]
+    pub schedule: ::core::option::Option<MarketSchedule>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetMarketScheduleResponse {
+    /// The configuration object after the update
+    #[prost(message, optional, tag = "1")]
+    pub config: ::core::option::Option<Configuration>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Empty {}
 /// Version information in vergen style
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct VersionInfo {
     /// The version of the application (e.g., "1.0.0")
@@ -1062,5 +1260,66 @@ pub mod config_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// rpc service to set (or clear) the operator message / maintenance window
+        /// served in the configuration
+        pub async fn set_maintenance(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetMaintenanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetMaintenanceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/xyz.aspens.arborter_config.v1.ConfigService/SetMaintenance",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "xyz.aspens.arborter_config.v1.ConfigService",
+                        "SetMaintenance",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// rpc service to set (or clear) a market's trading hours
+        pub async fn set_market_schedule(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetMarketScheduleRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SetMarketScheduleResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/xyz.aspens.arborter_config.v1.ConfigService/SetMarketSchedule",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "xyz.aspens.arborter_config.v1.ConfigService",
+                        "SetMarketSchedule",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
@@ -1,4 +1,6 @@
 // This file is @generated by prost-build.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct GetAttestationRequest {
     /// Caller-supplied freshness nonce (anti-replay). At most 64 bytes.
@@ -14,11 +16,15 @@ pub struct GetAttestationRequest {
     #[prost(bytes = "vec", optional, tag = "1")]
     pub report_data: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct GetAttestationResponse {
     #[prost(message, optional, tag = "1")]
     pub report: ::core::option::Option<AttestationReport>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct AttestationReport {
     /// TEE TCB Security Version Number
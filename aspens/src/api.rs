@@ -0,0 +1,532 @@
+//! Façade trait over the high-level trading operations.
+//!
+//! [`AspensApi`] captures what a trading application usually needs from the
+//! SDK — config, order entry, open orders, cancels, balances and the two
+//! streams — so application code and strategy frameworks can be written
+//! against the trait and unit-tested with [`mock::MockAspensApi`] instead of
+//! a live stack. [`AspensClient`] implements it by delegating to the
+//! `commands::trading` functions.
+
+use std::future::Future;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::client::AspensClient;
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::commands::trading::balance::{self, BalanceSnapshot};
+use crate::commands::trading::cancel_order::{self, arborter_pb::CancelOrderResponse};
+use crate::commands::trading::open_orders;
+use crate::commands::trading::send_order::{
+    self, ExecutionType, OrderOptions, OrderTags, arborter_pb::SendOrderResponse,
+};
+use crate::commands::trading::stream_orderbook::{
+    self, StreamOrderbookOptions, arborter_pb::OrderbookEntry,
+};
+use crate::commands::trading::stream_trades::{self, StreamTradesOptions, arborter_pb::Trade};
+use crate::decimals::PrecisionMode;
+use crate::error::Result;
+use crate::signer::as_signers;
+use crate::wallet::Wallet;
+
+/// A running stream: the receiver plus the background task feeding it.
+///
+/// Abort the handle to stop the stream; dropping the receiver alone leaves
+/// the task connected until the server closes the stream.
+pub type StreamHandle<T> = (mpsc::Receiver<T>, JoinHandle<Result<()>>);
+
+/// An order as submitted through [`AspensApi::send_order`].
+///
+/// Mirrors the arguments of
+/// [`send_order_with_wallets`](crate::commands::trading::send_order::send_order_with_wallets):
+/// `quantity` and `price` are human-readable decimal strings, `side` is the
+/// arborter `Side` as `i32` (1 = bid, 2 = ask).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderRequest {
+    /// Market ID or `base_network/SYMBOL::quote_network/SYMBOL` shorthand.
+    pub market_id: String,
+    /// Order side (1 = bid, 2 = ask).
+    pub side: i32,
+    /// Quantity as a decimal string.
+    pub quantity: String,
+    /// Limit price as a decimal string; `None` for a market order.
+    pub price: Option<String>,
+    /// Reject if the order would cross at submission (limit orders only).
+    pub post_only: bool,
+    /// Match normally but keep the order out of every orderbook stream.
+    pub hidden: bool,
+    /// Queue for the market's next batch auction (limit orders only).
+    pub auction: bool,
+    /// Time in force; good-till-cancel by default.
+    pub execution: ExecutionType,
+    /// Good-till-time expiry in unix seconds; `None` rests until filled or
+    /// canceled (limit orders only).
+    pub expires_at: Option<u64>,
+    /// What to do with a quantity or price finer than the market's pair
+    /// decimals; truncated by default.
+    pub precision: PrecisionMode,
+    /// Caller-chosen ID signed into the order and echoed in the response.
+    /// Resubmitting an order under the same ID can't place it twice; see
+    /// [`IdempotencyCache`](crate::commands::trading::send_order::IdempotencyCache).
+    pub client_order_id: Option<String>,
+    /// Strategy ID, tags and referral code sent alongside the order. An
+    /// unset referral falls back to the client's
+    /// [`referral_code`](AspensClient::referral_code).
+    pub tags: OrderTags,
+}
+
+/// High-level operations against an Aspens Market Stack.
+pub trait AspensApi: Send + Sync {
+    /// Fetch (or return the cached) stack configuration.
+    fn get_config(&self) -> impl Future<Output = Result<GetConfigResponse>> + Send;
+
+    /// Sign and submit an order with the wallets covering both market legs.
+    ///
+    /// [`AspensClient`] first checks it against its
+    /// [`risk_limits`](AspensClient::risk_limits).
+    fn send_order(
+        &self,
+        order: OrderRequest,
+        wallets: &[&Wallet],
+    ) -> impl Future<Output = Result<SendOrderResponse>> + Send;
+
+    /// The orders `wallet` has resting on `market_id`, oldest first.
+    fn open_orders(
+        &self,
+        market_id: &str,
+        wallet: &Wallet,
+    ) -> impl Future<Output = Result<Vec<OrderbookEntry>>> + Send;
+
+    /// Cancel a resting order. `side` accepts `buy`/`bid`/`sell`/`ask`.
+    fn cancel_order(
+        &self,
+        market_id: &str,
+        side: &str,
+        order_id: u64,
+        wallet: &Wallet,
+    ) -> impl Future<Output = Result<CancelOrderResponse>> + Send;
+
+    /// Wallet, deposited and gas balances across every configured chain.
+    fn balances(&self, wallets: &[&Wallet])
+    -> impl Future<Output = Result<BalanceSnapshot>> + Send;
+
+    /// Subscribe to the orderbook stream for a market.
+    fn stream_orderbook(
+        &self,
+        options: StreamOrderbookOptions,
+    ) -> impl Future<Output = Result<StreamHandle<OrderbookEntry>>> + Send;
+
+    /// Subscribe to the trades stream for a market.
+    fn stream_trades(
+        &self,
+        options: StreamTradesOptions,
+    ) -> impl Future<Output = Result<StreamHandle<Trade>>> + Send;
+}
+
+impl AspensApi for AspensClient {
+    async fn get_config(&self) -> Result<GetConfigResponse> {
+        Ok(AspensClient::get_config(self).await?)
+    }
+
+    async fn send_order(
+        &self,
+        order: OrderRequest,
+        wallets: &[&Wallet],
+    ) -> Result<SendOrderResponse> {
+        let config = AspensClient::get_config(self).await?;
+        let wallets = as_signers(wallets);
+        let mut tags = order.tags;
+        if tags.referral.is_none() {
+            tags.referral = self.referral_code().map(str::to_string);
+        }
+        send_order::send_order_with_options(
+            self.stack_url.to_string(),
+            order.market_id,
+            order.side,
+            order.quantity,
+            order.price,
+            &wallets,
+            config,
+            OrderOptions {
+                post_only: order.post_only,
+                hidden: order.hidden,
+                auction: order.auction,
+                execution: order.execution,
+                expires_at: order.expires_at,
+                precision: order.precision,
+                client_order_id: order.client_order_id,
+                tags,
+                risk: self.risk_limits().clone(),
+            },
+        )
+        .await
+    }
+
+    async fn open_orders(&self, market_id: &str, wallet: &Wallet) -> Result<Vec<OrderbookEntry>> {
+        let config = AspensClient::get_config(self).await?;
+        let market = send_order::lookup_market(&config, market_id)?;
+        open_orders::get_orders(
+            self.stack_url.to_string(),
+            market.market_id.clone(),
+            wallet.address(),
+        )
+        .await
+    }
+
+    async fn cancel_order(
+        &self,
+        market_id: &str,
+        side: &str,
+        order_id: u64,
+        wallet: &Wallet,
+    ) -> Result<CancelOrderResponse> {
+        let config = AspensClient::get_config(self).await?;
+        cancel_order::call_cancel_order_from_config_with_wallet(
+            self.stack_url.to_string(),
+            market_id.to_string(),
+            side.to_string(),
+            order_id,
+            wallet,
+            config,
+        )
+        .await
+    }
+
+    async fn balances(&self, wallets: &[&Wallet]) -> Result<BalanceSnapshot> {
+        let config = AspensClient::get_config(self).await?;
+        balance::fetch_balances_with_wallets(config, &as_signers(wallets)).await
+    }
+
+    async fn stream_orderbook(
+        &self,
+        mut options: StreamOrderbookOptions,
+    ) -> Result<StreamHandle<OrderbookEntry>> {
+        options.grpc = options.grpc.or(self.grpc_options);
+        stream_orderbook::stream_orderbook_channel(self.stack_url.to_string(), options).await
+    }
+
+    async fn stream_trades(&self, mut options: StreamTradesOptions) -> Result<StreamHandle<Trade>> {
+        options.grpc = options.grpc.or(self.grpc_options);
+        stream_trades::stream_trades_channel(self.stack_url.to_string(), options).await
+    }
+}
+
+/// In-memory [`AspensApi`] for tests.
+pub mod mock {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+    use crate::error::AspensError;
+
+    /// A cancel recorded by [`MockAspensApi`].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct CancelRecord {
+        /// Market ID as passed to `cancel_order`.
+        pub market_id: String,
+        /// Side as passed to `cancel_order`.
+        pub side: String,
+        /// Order ID as passed to `cancel_order`.
+        pub order_id: u64,
+    }
+
+    /// Canned-response [`AspensApi`] that records every order and cancel.
+    ///
+    /// Orders are validated against `config` (unknown markets, closed
+    /// markets and bad sides error like the real client) and acknowledged
+    /// with increasing order IDs starting at 1; an order repeating an
+    /// earlier client order ID gets the earlier order's ID and isn't
+    /// recorded again. Limit orders rest until cancelled: `open_orders`
+    /// lists them with the quantity and price as sent. Streams replay
+    /// `orderbook` / `trades` and then close.
+    #[derive(Debug, Default)]
+    pub struct MockAspensApi {
+        /// Returned by `get_config` and used to validate orders.
+        pub config: GetConfigResponse,
+        /// Returned by `balances`.
+        pub balances: BalanceSnapshot,
+        /// Replayed by `stream_orderbook`.
+        pub orderbook: Vec<OrderbookEntry>,
+        /// Replayed by `stream_trades`.
+        pub trades: Vec<Trade>,
+        sent_orders: Mutex<Vec<OrderRequest>>,
+        cancels: Mutex<Vec<CancelRecord>>,
+        last_order_id: AtomicU64,
+    }
+
+    impl MockAspensApi {
+        /// Create a mock serving `config`.
+        pub fn new(config: GetConfigResponse) -> Self {
+            Self {
+                config,
+                ..Default::default()
+            }
+        }
+
+        /// Orders submitted so far, in order.
+        pub fn sent_orders(&self) -> Vec<OrderRequest> {
+            self.sent_orders
+                .lock()
+                .expect("MockAspensApi lock poisoned")
+                .clone()
+        }
+
+        /// Orders submitted so far whose strategy or tags include `tag`.
+        pub fn sent_orders_tagged(&self, tag: &str) -> Vec<OrderRequest> {
+            self.sent_orders()
+                .into_iter()
+                .filter(|o| o.tags.matches(tag))
+                .collect()
+        }
+
+        /// Cancels submitted so far, in order.
+        pub fn cancels(&self) -> Vec<CancelRecord> {
+            self.cancels
+                .lock()
+                .expect("MockAspensApi lock poisoned")
+                .clone()
+        }
+
+        fn replay<T: Send + 'static>(items: Vec<T>) -> StreamHandle<T> {
+            let (tx, rx) = mpsc::channel(items.len().max(1));
+            for item in items {
+                // Capacity covers every item, so this cannot fail.
+                let _ = tx.try_send(item);
+            }
+            (rx, tokio::spawn(std::future::ready(Ok(()))))
+        }
+    }
+
+    impl AspensApi for MockAspensApi {
+        async fn get_config(&self) -> Result<GetConfigResponse> {
+            Ok(self.config.clone())
+        }
+
+        async fn send_order(
+            &self,
+            order: OrderRequest,
+            _wallets: &[&Wallet],
+        ) -> Result<SendOrderResponse> {
+            send_order::lookup_market(&self.config, &order.market_id)?
+                .ensure_open(crate::util::unix_now())?;
+            if !matches!(order.side, 1 | 2) {
+                return Err(AspensError::invalid_input(format!(
+                    "invalid side {}",
+                    order.side
+                )));
+            }
+            order.tags.validate()?;
+            if let Some(id) = &order.client_order_id {
+                send_order::validate_client_order_id(id)?;
+            }
+            let client_order_id = order.client_order_id.clone().unwrap_or_default();
+            let order_in_book = order.price.is_some();
+            let mut sent_orders = self
+                .sent_orders
+                .lock()
+                .expect("MockAspensApi lock poisoned");
+            // A repeated client order ID is acknowledged as the original
+            // order, as the real client's idempotency cache does.
+            if let Some(index) = order.client_order_id.as_ref().and_then(|id| {
+                sent_orders
+                    .iter()
+                    .position(|o| o.client_order_id.as_ref() == Some(id))
+            }) {
+                return Ok(SendOrderResponse {
+                    order_in_book: sent_orders[index].price.is_some(),
+                    order_id: index as u64 + 1,
+                    client_order_id,
+                    ..Default::default()
+                });
+            }
+            sent_orders.push(order);
+            Ok(SendOrderResponse {
+                order_in_book,
+                order_id: self.last_order_id.fetch_add(1, Ordering::SeqCst) + 1,
+                client_order_id,
+                ..Default::default()
+            })
+        }
+
+        async fn open_orders(
+            &self,
+            market_id: &str,
+            _wallet: &Wallet,
+        ) -> Result<Vec<OrderbookEntry>> {
+            let market = send_order::lookup_market(&self.config, market_id)?;
+            let cancelled: Vec<u64> = self.cancels().iter().map(|c| c.order_id).collect();
+            Ok(self
+                .sent_orders()
+                .into_iter()
+                .enumerate()
+                .map(|(index, order)| (index as u64 + 1, order))
+                .filter(|(order_id, order)| {
+                    order.price.is_some()
+                        && !cancelled.contains(order_id)
+                        && send_order::lookup_market(&self.config, &order.market_id)
+                            .is_ok_and(|m| m.market_id == market.market_id)
+                })
+                .map(|(order_id, order)| OrderbookEntry {
+                    order_id,
+                    side: order.side,
+                    quantity: order.quantity,
+                    price: order.price.unwrap_or_default(),
+                    market_id: market.market_id.clone(),
+                    ..Default::default()
+                })
+                .collect())
+        }
+
+        async fn cancel_order(
+            &self,
+            market_id: &str,
+            side: &str,
+            order_id: u64,
+            _wallet: &Wallet,
+        ) -> Result<CancelOrderResponse> {
+            send_order::lookup_market(&self.config, market_id)?;
+            send_order::parse_side(side)?;
+            self.cancels
+                .lock()
+                .expect("MockAspensApi lock poisoned")
+                .push(CancelRecord {
+                    market_id: market_id.to_string(),
+                    side: side.to_string(),
+                    order_id,
+                });
+            Ok(CancelOrderResponse {
+                order_canceled: true,
+                ..Default::default()
+            })
+        }
+
+        async fn balances(&self, _wallets: &[&Wallet]) -> Result<BalanceSnapshot> {
+            Ok(self.balances.clone())
+        }
+
+        async fn stream_orderbook(
+            &self,
+            _options: StreamOrderbookOptions,
+        ) -> Result<StreamHandle<OrderbookEntry>> {
+            Ok(Self::replay(self.orderbook.clone()))
+        }
+
+        async fn stream_trades(
+            &self,
+            _options: StreamTradesOptions,
+        ) -> Result<StreamHandle<Trade>> {
+            Ok(Self::replay(self.trades.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockAspensApi;
+    use super::*;
+    use crate::fixtures::{ConfigFixture, OrderFixture};
+
+    const MARKET_ID: &str = ConfigFixture::TWO_CHAIN_MARKET_ID;
+
+    fn config() -> GetConfigResponse {
+        ConfigFixture::two_chain_usdc().build()
+    }
+
+    fn wallet() -> Wallet {
+        Wallet::from_evm_hex("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+            .unwrap()
+    }
+
+    // Application code written against the trait, as a strategy would be.
+    async fn place_two_sided<A: AspensApi>(api: &A, wallet: &Wallet) -> Result<(u64, u64)> {
+        let bid = OrderFixture::limit_buy().price("0.99").build();
+        let ask = OrderFixture::limit_sell().price("1.01").build();
+        let b = api.send_order(bid, &[wallet]).await?;
+        let a = api.send_order(ask, &[wallet]).await?;
+        Ok((b.order_id, a.order_id))
+    }
+
+    #[tokio::test]
+    async fn mock_records_orders_and_assigns_ids() {
+        let api = MockAspensApi::new(config());
+        let w = wallet();
+        assert_eq!(place_two_sided(&api, &w).await.unwrap(), (1, 2));
+        let sent = api.sent_orders();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].price.as_deref(), Some("1.01"));
+    }
+
+    #[tokio::test]
+    async fn mock_acknowledges_repeated_client_order_ids_once() {
+        let api = MockAspensApi::new(config());
+        let w = wallet();
+        let order = OrderFixture::limit_buy().client_order_id("bot-1").build();
+        let first = api.send_order(order.clone(), &[&w]).await.unwrap();
+        place_two_sided(&api, &w).await.unwrap();
+        let retry = api.send_order(order, &[&w]).await.unwrap();
+        assert_eq!((first.order_id, retry.order_id), (1, 1));
+        assert_eq!(retry.client_order_id, "bot-1");
+        assert_eq!(api.sent_orders().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn mock_filters_sent_orders_by_tag() {
+        let api = MockAspensApi::new(config());
+        let w = wallet();
+        let tagged = OrderRequest {
+            tags: OrderTags::strategy("mm").with_tag("night"),
+            ..OrderFixture::market_buy().build()
+        };
+        api.send_order(tagged, &[&w]).await.unwrap();
+        place_two_sided(&api, &w).await.unwrap();
+        assert_eq!(api.sent_orders_tagged("mm").len(), 1);
+        assert_eq!(api.sent_orders_tagged("night").len(), 1);
+        assert!(api.sent_orders_tagged("arb").is_empty());
+    }
+
+    #[tokio::test]
+    async fn mock_rejects_unknown_market() {
+        let api = MockAspensApi::new(config());
+        let order = OrderFixture::limit_buy().market("nope").build();
+        assert!(api.send_order(order, &[&wallet()]).await.is_err());
+        assert!(api.sent_orders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn mock_records_cancels() {
+        let api = MockAspensApi::new(config());
+        let resp = api
+            .cancel_order(MARKET_ID, "buy", 7, &wallet())
+            .await
+            .unwrap();
+        assert!(resp.order_canceled);
+        assert_eq!(api.cancels()[0].order_id, 7);
+        assert!(
+            api.cancel_order(MARKET_ID, "sideways", 7, &wallet())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_stream_replays_then_closes() {
+        let mut api = MockAspensApi::new(config());
+        api.orderbook = vec![
+            OrderbookEntry {
+                order_id: 1,
+                ..Default::default()
+            },
+            OrderbookEntry {
+                order_id: 2,
+                ..Default::default()
+            },
+        ];
+        let (mut rx, _handle) = api
+            .stream_orderbook(StreamOrderbookOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().order_id, 1);
+        assert_eq!(rx.recv().await.unwrap().order_id, 2);
+        assert!(rx.recv().await.is_none());
+    }
+}
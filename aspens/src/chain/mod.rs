@@ -0,0 +1,3 @@
+//! On-chain transaction plumbing shared by the trading and admin commands.
+
+pub mod tx;
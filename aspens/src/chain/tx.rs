@@ -0,0 +1,926 @@
+//! Nonces for transactions sent from this process.
+//!
+//! Each deposit, withdraw or deployment builds its own provider, and a
+//! provider picks a nonce by asking the node for the account's pending
+//! transaction count. Two tasks sending from the same account on the same
+//! chain can both ask before either has broadcast, get the same answer, and
+//! one transaction replaces or blocks the other.
+//!
+//! [`reserve_nonce`] hands out nonces from one table per (chain ID,
+//! address), shared by the whole process, so concurrent senders get
+//! consecutive nonces. The node's pending count is still read on every
+//! reservation, so transactions sent from elsewhere (another process, a
+//! wallet app) move the table forward instead of colliding with it.
+//!
+//! A reserved nonce whose transaction never reaches the node would leave a
+//! gap that every later transaction waits behind. A [`NonceReservation`]
+//! that is dropped without [`commit`](NonceReservation::commit) (a failed
+//! send, a `?` on the way out) gives its nonce back: the next reservation
+//! reuses it, so the gap closes with the next transaction.
+//!
+//! Once broadcast, [`wait_for_inclusion`] follows a transaction until it is
+//! mined. One still pending after [`StuckPolicy::stuck_after`] is sent again
+//! with the same nonce and higher fees ([`bump_fees`]), which replaces it in
+//! the mempool; whichever version lands first is the result.
+//! [`wait_for_confirmations`] goes on to wait for the blocks a
+//! [`ConfirmationPolicy`] asks for.
+//!
+//! Some chains, and EVM relays such as Hedera's, don't take EIP-1559
+//! transactions. [`legacy_gas_price`] tells senders when to set a single
+//! gas price instead, going by [`TxType`]: set per network with
+//! [`tx_type_env_key`], or found out from the node's fee history.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use alloy::consensus::Transaction as _;
+use alloy::eips::BlockNumberOrTag;
+use alloy::eips::eip1559::Eip1559Estimation;
+use alloy::network::EthereumWallet;
+use alloy::primitives::{Address, TxHash};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionReceipt;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::chain_client::ARCH_SOLANA;
+use crate::commands::config::config_pb::{Chain, GetConfigResponse};
+use crate::error::{AspensError, Result};
+use crate::signer::AspensSigner;
+
+/// Nonce state of one account on one chain.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct AccountNonces {
+    /// One past the highest nonce handed out, once one has been.
+    next: Option<u64>,
+    /// Nonces handed out and given back below `next`, reused lowest first.
+    gaps: BTreeSet<u64>,
+}
+
+impl AccountNonces {
+    /// The nonce to use, given the node's pending transaction count.
+    fn take(&mut self, pending: u64) -> u64 {
+        // Anything below the pending count has been used by now.
+        self.gaps = self.gaps.split_off(&pending);
+        if let Some(gap) = self.gaps.pop_first() {
+            return gap;
+        }
+        let nonce = self.next.map_or(pending, |next| next.max(pending));
+        self.next = Some(nonce + 1);
+        nonce
+    }
+
+    /// `nonce` was handed out but its transaction never reached the node.
+    fn give_back(&mut self, nonce: u64) {
+        let Some(next) = self.next else {
+            return;
+        };
+        if nonce >= next {
+            return;
+        }
+        self.gaps.insert(nonce);
+        // Gaps at the top just lower `next`.
+        let mut next = next;
+        while next > 0 && self.gaps.remove(&(next - 1)) {
+            next -= 1;
+        }
+        self.next = Some(next);
+    }
+}
+
+type NonceKey = (u64, Address);
+
+/// The nonce table shared by every [`reserve_nonce`].
+static NONCES: OnceLock<Mutex<HashMap<NonceKey, AccountNonces>>> = OnceLock::new();
+
+fn nonces() -> MutexGuard<'static, HashMap<NonceKey, AccountNonces>> {
+    NONCES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// A nonce held for one transaction. Set it on the transaction, then
+/// [`commit`](Self::commit) once the node has accepted it; dropping the
+/// reservation instead gives the nonce back.
+#[derive(Debug)]
+#[must_use = "dropping a reservation gives its nonce back"]
+pub struct NonceReservation {
+    chain_id: u64,
+    address: Address,
+    nonce: u64,
+    committed: bool,
+}
+
+impl NonceReservation {
+    /// The reserved nonce.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Keep the nonce: its transaction was broadcast (or signed and handed
+    /// off to be).
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for NonceReservation {
+    fn drop(&mut self) {
+        if !self.committed {
+            tracing::debug!(
+                "Giving back nonce {} of {} on chain {}",
+                self.nonce,
+                self.address,
+                self.chain_id
+            );
+            if let Some(account) = nonces().get_mut(&(self.chain_id, self.address)) {
+                account.give_back(self.nonce);
+            }
+        }
+    }
+}
+
+/// Reserve the next nonce for `address` on chain `chain_id`, which
+/// `provider` is connected to.
+pub async fn reserve_nonce(
+    provider: &impl Provider,
+    chain_id: u64,
+    address: Address,
+) -> Result<NonceReservation> {
+    let pending = provider.get_transaction_count(address).pending().await?;
+    let nonce = nonces()
+        .entry((chain_id, address))
+        .or_default()
+        .take(pending);
+    tracing::debug!(
+        "Reserved nonce {} of {} on chain {} (pending count {})",
+        nonce,
+        address,
+        chain_id,
+        pending
+    );
+    Ok(NonceReservation {
+        chain_id,
+        address,
+        nonce,
+        committed: false,
+    })
+}
+
+/// Drop what this process knows about `address`'s nonces on `chain_id`, so
+/// the next [`reserve_nonce`] starts again from the node's pending count.
+/// For when a transaction was replaced or dropped from the mempool outside
+/// this process.
+pub fn forget_nonces(chain_id: u64, address: Address) {
+    nonces().remove(&(chain_id, address));
+}
+
+/// Prefix of the per-network environment variables that pick a
+/// transaction type; see [`tx_type_env_key`].
+pub const TX_TYPE_ENV_PREFIX: &str = "ASPENS_TX_TYPE_";
+
+/// Which kind of transaction to send on a chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxType {
+    /// EIP-1559 if the chain's node reports a base fee, else legacy; asked
+    /// once per chain ID and remembered.
+    #[default]
+    Auto,
+    /// Type 0, priced with a single gas price. For chains and relays
+    /// (Hedera's among them) that don't take EIP-1559 transactions.
+    Legacy,
+    /// Type 2, with a max fee and a priority fee.
+    Eip1559,
+}
+
+impl fmt::Display for TxType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TxType::Auto => "auto",
+            TxType::Legacy => "legacy",
+            TxType::Eip1559 => "eip1559",
+        })
+    }
+}
+
+impl FromStr for TxType {
+    type Err = AspensError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s
+            .trim()
+            .to_ascii_lowercase()
+            .replace(['-', '_'], "")
+            .as_str()
+        {
+            "auto" => Ok(TxType::Auto),
+            "legacy" | "0" => Ok(TxType::Legacy),
+            "eip1559" | "1559" | "2" => Ok(TxType::Eip1559),
+            _ => Err(AspensError::invalid_input(format!(
+                "unknown transaction type '{}': expected auto, legacy or eip1559",
+                s
+            ))),
+        }
+    }
+}
+
+/// The env-var key that picks the transaction type for `network`:
+/// `ASPENS_TX_TYPE_<NETWORK>`, with `network` upper-cased and every
+/// non-alphanumeric byte replaced by `_`, as for
+/// [`rpc_override_env_key`](crate::chain_client::rpc_override_env_key).
+pub fn tx_type_env_key(network: &str) -> String {
+    let suffix: String = network
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{TX_TYPE_ENV_PREFIX}{suffix}")
+}
+
+impl TxType {
+    /// The type set for `network` with [`tx_type_env_key`], else
+    /// [`TxType::Auto`]. An unparseable value is warned about and ignored.
+    pub fn for_network(network: &str) -> Self {
+        let key = tx_type_env_key(network);
+        match std::env::var(&key) {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                tracing::warn!("Ignoring {}={}: {}", key, value, e);
+                TxType::Auto
+            }),
+            Err(_) => TxType::Auto,
+        }
+    }
+
+    /// This type with [`TxType::Auto`] settled by asking `provider`, which
+    /// is connected to chain `chain_id`, for its fee history: a node that
+    /// doesn't serve `eth_feeHistory`, or reports no base fee, gets legacy
+    /// transactions.
+    pub async fn resolve(self, provider: &impl Provider, chain_id: u64) -> Result<Self> {
+        if self != TxType::Auto {
+            return Ok(self);
+        }
+        if let Some(known) = fee_markets().get(&chain_id) {
+            return Ok(*known);
+        }
+        let resolved = match provider
+            .get_fee_history(1, BlockNumberOrTag::Latest, &[])
+            .await
+        {
+            Ok(history) if history.latest_block_base_fee().is_some_and(|fee| fee > 0) => {
+                TxType::Eip1559
+            }
+            Ok(_) => TxType::Legacy,
+            Err(e) if e.is_transport_error() => return Err(e.into()),
+            Err(e) => {
+                tracing::debug!("eth_feeHistory failed on chain {}: {}", chain_id, e);
+                TxType::Legacy
+            }
+        };
+        if resolved == TxType::Legacy {
+            tracing::info!(
+                "Chain {} has no EIP-1559 fee market; sending legacy transactions",
+                chain_id
+            );
+        }
+        fee_markets().insert(chain_id, resolved);
+        Ok(resolved)
+    }
+}
+
+/// What [`TxType::resolve`] found for each chain ID.
+static FEE_MARKETS: OnceLock<Mutex<HashMap<u64, TxType>>> = OnceLock::new();
+
+fn fee_markets() -> MutexGuard<'static, HashMap<u64, TxType>> {
+    FEE_MARKETS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// The gas price to send a transaction on `network` (chain `chain_id`,
+/// which `provider` is connected to) with, if it must be a legacy one;
+/// `None` leaves EIP-1559 fees to the provider.
+pub async fn legacy_gas_price(
+    provider: &impl Provider,
+    network: &str,
+    chain_id: u64,
+) -> Result<Option<u128>> {
+    match TxType::for_network(network)
+        .resolve(provider, chain_id)
+        .await?
+    {
+        TxType::Legacy => Ok(Some(provider.get_gas_price().await?)),
+        _ => Ok(None),
+    }
+}
+
+/// Environment variable holding how long, in seconds, a broadcast
+/// transaction may stay pending before it counts as stuck and is sent again
+/// with higher fees. `0` turns fee bumping off.
+pub const STUCK_AFTER_ENV: &str = "ASPENS_TX_STUCK_AFTER";
+
+/// The smallest fee increase nodes accept for a replacement transaction,
+/// in percent.
+pub const MIN_FEE_BUMP_PERCENT: u64 = 10;
+
+/// How often [`wait_for_inclusion`] asks for a receipt.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When [`wait_for_inclusion`] gives a pending transaction higher fees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StuckPolicy {
+    /// How long a transaction may stay pending before it is bumped; zero
+    /// never bumps.
+    pub stuck_after: Duration,
+    /// How much each bump raises the fees, in percent; at least
+    /// [`MIN_FEE_BUMP_PERCENT`] is used.
+    pub fee_bump_percent: u64,
+    /// Bumps at most, after which the last version is waited on as is.
+    pub max_bumps: u32,
+}
+
+impl Default for StuckPolicy {
+    fn default() -> Self {
+        Self {
+            stuck_after: Duration::from_secs(90),
+            fee_bump_percent: 20,
+            max_bumps: 3,
+        }
+    }
+}
+
+impl StuckPolicy {
+    /// The default policy, with `stuck_after` from [`STUCK_AFTER_ENV`] when
+    /// set. An unparseable value is warned about and ignored.
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(value) = std::env::var(STUCK_AFTER_ENV) {
+            match value.trim().parse::<u64>() {
+                Ok(secs) => policy.stuck_after = Duration::from_secs(secs),
+                Err(e) => tracing::warn!("Ignoring {}={}: {}", STUCK_AFTER_ENV, value, e),
+            }
+        }
+        policy
+    }
+}
+
+/// Fees for a replacement of a transaction that paid `old`: each raised by
+/// `percent` (at least [`MIN_FEE_BUMP_PERCENT`]), and no lower than what
+/// the chain asks for `now`.
+fn bumped_fees(old: Eip1559Estimation, now: Eip1559Estimation, percent: u64) -> Eip1559Estimation {
+    let percent = u128::from(percent.max(MIN_FEE_BUMP_PERCENT));
+    let bump = |fee: u128| fee.saturating_mul(100 + percent).div_ceil(100);
+    let max_priority_fee_per_gas =
+        bump(old.max_priority_fee_per_gas).max(now.max_priority_fee_per_gas);
+    Eip1559Estimation {
+        max_fee_per_gas: bump(old.max_fee_per_gas)
+            .max(now.max_fee_per_gas)
+            .max(max_priority_fee_per_gas),
+        max_priority_fee_per_gas,
+    }
+}
+
+/// Send the pending transaction `hash` again with the same nonce and fees
+/// raised by `percent`, replacing it in the mempool. `provider` must sign
+/// for the transaction's sender. Legacy transactions get a higher gas
+/// price instead.
+///
+/// Returns the replacement's hash.
+pub async fn bump_fees(provider: &impl Provider, hash: TxHash, percent: u64) -> Result<TxHash> {
+    let tx = provider
+        .get_transaction_by_hash(hash)
+        .await?
+        .ok_or_else(|| {
+            AspensError::not_found(format!(
+                "transaction {} is unknown to the node; it may have been dropped",
+                hash
+            ))
+        })?;
+    if let Some(block) = tx.block_number {
+        return Err(AspensError::invalid_input(format!(
+            "transaction {} was already mined in block {}",
+            hash, block
+        )));
+    }
+    let from = tx.inner.signer();
+    let old_priority_fee = tx.max_priority_fee_per_gas();
+    let old_max_fee = tx.max_fee_per_gas();
+    let nonce = tx.nonce();
+
+    let mut request = tx.into_request().from(from);
+    match old_priority_fee {
+        Some(max_priority_fee_per_gas) => {
+            let fees = bumped_fees(
+                Eip1559Estimation {
+                    max_fee_per_gas: old_max_fee,
+                    max_priority_fee_per_gas,
+                },
+                provider.estimate_eip1559_fees().await?,
+                percent,
+            );
+            tracing::info!(
+                "Bumping {} (nonce {}): max fee {} -> {}, priority fee {} -> {}",
+                hash,
+                nonce,
+                old_max_fee,
+                fees.max_fee_per_gas,
+                max_priority_fee_per_gas,
+                fees.max_priority_fee_per_gas
+            );
+            request.max_fee_per_gas = Some(fees.max_fee_per_gas);
+            request.max_priority_fee_per_gas = Some(fees.max_priority_fee_per_gas);
+        }
+        None => {
+            let now = provider.get_gas_price().await?;
+            let gas_price = bumped_fees(
+                Eip1559Estimation {
+                    max_fee_per_gas: old_max_fee,
+                    max_priority_fee_per_gas: 0,
+                },
+                Eip1559Estimation {
+                    max_fee_per_gas: now,
+                    max_priority_fee_per_gas: 0,
+                },
+                percent,
+            )
+            .max_fee_per_gas;
+            tracing::info!(
+                "Bumping {} (nonce {}): gas price {} -> {}",
+                hash,
+                nonce,
+                old_max_fee,
+                gas_price
+            );
+            request.gas_price = Some(gas_price);
+        }
+    }
+    let pending = provider.send_transaction(request).await.map_err(|e| {
+        AspensError::chain(format!(
+            "Failed to send the replacement for {}: {}",
+            hash, e
+        ))
+    })?;
+    Ok(*pending.tx_hash())
+}
+
+/// How many blocks to wait for after a transaction is mined, and for how
+/// long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmationPolicy {
+    /// Blocks counting the one the transaction was mined in; `1` returns as
+    /// soon as it is mined, and `0` counts as `1`.
+    pub confirmations: u64,
+    /// How long to wait for them, from when waiting starts; zero waits
+    /// indefinitely.
+    pub timeout: Duration,
+}
+
+impl Default for ConfirmationPolicy {
+    /// Mined once, within two minutes.
+    fn default() -> Self {
+        Self {
+            confirmations: 1,
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+/// A transaction didn't reach the confirmations a [`ConfirmationPolicy`]
+/// asked for in time. Carried by an [`AspensError::Chain`]; the
+/// transaction may still land, so look it up before sending it again.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "transaction {hash} has {confirmations} of {required} confirmations after {}s",
+    timeout.as_secs()
+)]
+pub struct ConfirmationTimeout {
+    /// The version that was mined, else the latest one sent.
+    pub hash: TxHash,
+    /// Confirmations it had when time ran out; `0` if it wasn't mined.
+    pub confirmations: u64,
+    /// Confirmations the policy asked for.
+    pub required: u64,
+    /// The policy's timeout.
+    pub timeout: Duration,
+}
+
+impl ConfirmationTimeout {
+    /// The timeout behind `err`, if it is one.
+    pub fn of(err: &AspensError) -> Option<&ConfirmationTimeout> {
+        match err {
+            AspensError::Chain(e) => e.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Wait for the broadcast transaction `hash` to be mined, bumping its fees
+/// by `stuck` while it is stuck. `provider` must sign for the sender, so it
+/// can send the replacements.
+///
+/// Returns the receipt of whichever version was mined, which may not be
+/// `hash`.
+pub async fn wait_for_inclusion(
+    provider: &impl Provider,
+    hash: TxHash,
+    stuck: StuckPolicy,
+) -> Result<TransactionReceipt> {
+    let policy = ConfirmationPolicy {
+        confirmations: 1,
+        timeout: Duration::ZERO,
+    };
+    wait_for_confirmations(provider, hash, stuck, policy).await
+}
+
+/// [`wait_for_inclusion`], then wait for the block count `policy` asks
+/// for, logging each new confirmation. Running out of time is an
+/// [`AspensError::Chain`] holding a [`ConfirmationTimeout`]; a bump that
+/// fails is logged and waiting goes on.
+pub async fn wait_for_confirmations(
+    provider: &impl Provider,
+    hash: TxHash,
+    stuck: StuckPolicy,
+    policy: ConfirmationPolicy,
+) -> Result<TransactionReceipt> {
+    let started = Instant::now();
+    let required = policy.confirmations.max(1);
+    // Every version sent, newest last: any of them may be the one mined.
+    let mut sent = vec![hash];
+    let mut since = Instant::now();
+    let mut confirmed = (hash, 0);
+    loop {
+        let mut mined = None;
+        for hash in sent.iter().rev() {
+            if let Some(receipt) = provider.get_transaction_receipt(*hash).await? {
+                mined = Some(receipt);
+                break;
+            }
+        }
+        match mined {
+            Some(receipt) => {
+                let block = receipt.block_number.unwrap_or_default();
+                let head = provider.get_block_number().await?;
+                let confirmations = head.saturating_sub(block) + 1;
+                if confirmations >= required {
+                    return Ok(receipt);
+                }
+                if confirmed != (receipt.transaction_hash, confirmations) {
+                    tracing::info!(
+                        "Transaction {}: {} of {} confirmations",
+                        receipt.transaction_hash,
+                        confirmations,
+                        required
+                    );
+                    confirmed = (receipt.transaction_hash, confirmations);
+                }
+            }
+            None => {
+                // Not mined, or mined and then reorged out.
+                let latest = sent[sent.len() - 1];
+                confirmed = (latest, 0);
+                let bumps = sent.len() - 1;
+                if !stuck.stuck_after.is_zero()
+                    && since.elapsed() >= stuck.stuck_after
+                    && bumps < stuck.max_bumps as usize
+                {
+                    tracing::warn!(
+                        "Transaction {} not mined after {}s; rebroadcasting with higher fees",
+                        latest,
+                        since.elapsed().as_secs()
+                    );
+                    match bump_fees(provider, latest, stuck.fee_bump_percent).await {
+                        Ok(replacement) => {
+                            tracing::info!("Replacement transaction sent: {}", replacement);
+                            sent.push(replacement);
+                        }
+                        // Usually one of the versions was mined between the
+                        // receipt check and the bump ("already mined", "nonce
+                        // too low"), which the next check picks up. Anything
+                        // else is retried once it is stuck again; only the
+                        // timeout gives up.
+                        Err(e) => tracing::warn!("Could not bump {}: {}", latest, e),
+                    }
+                    since = Instant::now();
+                }
+            }
+        }
+        if !policy.timeout.is_zero() && started.elapsed() >= policy.timeout {
+            let (hash, confirmations) = confirmed;
+            return Err(AspensError::Chain(Box::new(ConfirmationTimeout {
+                hash,
+                confirmations,
+                required,
+                timeout: policy.timeout,
+            })));
+        }
+        tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+    }
+}
+
+/// Where a transaction is, as [`transaction_status`] reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxState {
+    /// In the mempool, not yet mined.
+    Pending,
+    /// Mined and succeeded.
+    Mined,
+    /// Mined and reverted.
+    Reverted,
+    /// Unknown to the node: never sent, dropped, or replaced.
+    Unknown,
+}
+
+impl TxState {
+    /// The state's name, as serialized.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TxState::Pending => "pending",
+            TxState::Mined => "mined",
+            TxState::Reverted => "reverted",
+            TxState::Unknown => "unknown",
+        }
+    }
+}
+
+/// A transaction's state on a chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TxStatus {
+    /// The network as given.
+    pub network: String,
+    /// The transaction hash, 0x-prefixed.
+    pub hash: String,
+    /// Where the transaction is.
+    pub state: TxState,
+    /// Checksummed sender, when the node knows the transaction.
+    pub from: Option<String>,
+    /// Sender nonce, when the node knows the transaction.
+    pub nonce: Option<u64>,
+    /// Block it was mined in.
+    pub block: Option<u64>,
+    /// Blocks on top of and including `block`.
+    pub confirmations: Option<u64>,
+    /// Max fee per gas in wei (the gas price, for legacy transactions).
+    pub max_fee_per_gas: Option<u128>,
+    /// Max priority fee per gas in wei; `None` for legacy transactions.
+    pub max_priority_fee_per_gas: Option<u128>,
+}
+
+/// The state of transaction `hash` on `network`.
+pub async fn transaction_status(
+    network: String,
+    hash: String,
+    config: GetConfigResponse,
+) -> Result<TxStatus> {
+    let chain = evm_chain(&config, &network)?;
+    let tx_hash = parse_tx_hash(&hash)?;
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+
+    let mut status = TxStatus {
+        network,
+        hash: format!("{:?}", tx_hash),
+        state: TxState::Unknown,
+        from: None,
+        nonce: None,
+        block: None,
+        confirmations: None,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+    };
+    if let Some(tx) = provider.get_transaction_by_hash(tx_hash).await? {
+        status.state = TxState::Pending;
+        status.from = Some(tx.inner.signer().to_checksum(None));
+        status.nonce = Some(tx.nonce());
+        status.max_fee_per_gas = Some(tx.max_fee_per_gas());
+        status.max_priority_fee_per_gas = tx.max_priority_fee_per_gas();
+    }
+    if let Some(receipt) = provider.get_transaction_receipt(tx_hash).await? {
+        status.state = if receipt.status() {
+            TxState::Mined
+        } else {
+            TxState::Reverted
+        };
+        if let Some(block) = receipt.block_number {
+            let head = provider.get_block_number().await?;
+            status.block = Some(block);
+            status.confirmations = Some(head.saturating_sub(block) + 1);
+        }
+    }
+    Ok(status)
+}
+
+/// Send pending transaction `hash` on `network` again with fees raised by
+/// `percent`, signed by `wallet`, which must be the transaction's sender.
+///
+/// Returns the replacement's hash.
+pub async fn bump_transaction(
+    network: String,
+    hash: String,
+    percent: u64,
+    wallet: &dyn AspensSigner,
+    config: GetConfigResponse,
+) -> Result<String> {
+    let chain = evm_chain(&config, &network)?;
+    let tx_hash = parse_tx_hash(&hash)?;
+    let signer = wallet.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(format!(
+            "bumping on chain '{}' needs a secp256k1 wallet that signs transactions",
+            network
+        ))
+    })?;
+    let from = signer.address();
+    let provider = ProviderBuilder::new()
+        .with_chain_id(chain.chain_id as u64)
+        .wallet(EthereumWallet::new(signer))
+        .connect_http(Url::parse(&chain.rpc_url)?);
+    if let Some(tx) = provider.get_transaction_by_hash(tx_hash).await?
+        && tx.inner.signer() != from
+    {
+        return Err(AspensError::invalid_input(format!(
+            "transaction {} was sent by {}, not this wallet ({})",
+            hash,
+            tx.inner.signer(),
+            from
+        )));
+    }
+    let replacement = bump_fees(&provider, tx_hash, percent).await?;
+    Ok(format!("{:?}", replacement))
+}
+
+/// The EVM chain `network` names in `config`.
+fn evm_chain<'a>(config: &'a GetConfigResponse, network: &str) -> Result<&'a Chain> {
+    let chain = config.get_chain(network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) {
+        return Err(AspensError::invalid_input(format!(
+            "transaction tracking is EVM-only; '{}' is a Solana chain",
+            network
+        )));
+    }
+    Ok(chain)
+}
+
+fn parse_tx_hash(hash: &str) -> Result<TxHash> {
+    hash.trim().parse().map_err(|e| {
+        AspensError::invalid_input(format!("invalid transaction hash '{}': {}", hash, e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_reservations_get_consecutive_nonces() {
+        let mut account = AccountNonces::default();
+        // Two senders both read a pending count of 5 before either sent.
+        assert_eq!(account.take(5), 5);
+        assert_eq!(account.take(5), 6);
+        // A transaction sent from elsewhere moved the count past ours.
+        assert_eq!(account.take(9), 9);
+        assert_eq!(account.next, Some(10));
+    }
+
+    #[test]
+    fn given_back_nonces_fill_the_gap_first() {
+        let mut account = AccountNonces::default();
+        let nonces: Vec<_> = (0..4).map(|_| account.take(3)).collect();
+        assert_eq!(nonces, [3, 4, 5, 6]);
+
+        // 4's send failed while 5 and 6 went out: 4 is reused next.
+        account.give_back(4);
+        assert_eq!(account.take(3), 4);
+        assert_eq!(account.take(3), 7);
+
+        // Failures at the top just step back.
+        account.give_back(7);
+        account.give_back(6);
+        assert_eq!(account.next, Some(6));
+        assert!(account.gaps.is_empty());
+
+        // A gap the chain has moved past is dropped, not reused.
+        account.give_back(3);
+        assert_eq!(account.take(6), 6);
+        assert!(account.gaps.is_empty());
+    }
+
+    #[test]
+    fn bumps_raise_every_fee_past_the_replacement_minimum() {
+        let fees = |max_fee_per_gas, max_priority_fee_per_gas| Eip1559Estimation {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        };
+        // 20% over the old fees, which beat the chain's current ones.
+        assert_eq!(bumped_fees(fees(100, 10), fees(50, 5), 20), fees(120, 12));
+        // Too small a bump is raised to the minimum, rounding up.
+        assert_eq!(bumped_fees(fees(101, 3), fees(0, 0), 1), fees(112, 4));
+        // The chain's fees went up past the bump: follow them.
+        assert_eq!(bumped_fees(fees(100, 10), fees(300, 40), 20), fees(300, 40));
+        // The max fee never falls below the priority fee.
+        assert_eq!(bumped_fees(fees(10, 10), fees(0, 50), 10), fees(50, 50));
+    }
+
+    #[test]
+    fn tx_types_parse_by_name_or_number() {
+        assert_eq!("legacy".parse::<TxType>().unwrap(), TxType::Legacy);
+        assert_eq!("0".parse::<TxType>().unwrap(), TxType::Legacy);
+        assert_eq!("EIP-1559".parse::<TxType>().unwrap(), TxType::Eip1559);
+        assert_eq!("2".parse::<TxType>().unwrap(), TxType::Eip1559);
+        assert_eq!(" Auto ".parse::<TxType>().unwrap(), TxType::Auto);
+        assert!("eip2930".parse::<TxType>().is_err());
+        assert_eq!(
+            tx_type_env_key("hedera-testnet"),
+            "ASPENS_TX_TYPE_HEDERA_TESTNET"
+        );
+    }
+
+    #[test]
+    fn confirmation_timeouts_are_recoverable_from_the_error() {
+        let timeout = ConfirmationTimeout {
+            hash: TxHash::repeat_byte(0xab),
+            confirmations: 2,
+            required: 6,
+            timeout: Duration::from_secs(60),
+        };
+        let err = AspensError::Chain(Box::new(timeout.clone()));
+        assert_eq!(ConfirmationTimeout::of(&err), Some(&timeout));
+        assert!(
+            err.to_string()
+                .ends_with("has 2 of 6 confirmations after 60s")
+        );
+        assert_eq!(
+            ConfirmationTimeout::of(&AspensError::chain("reverted")),
+            None
+        );
+    }
+
+    #[test]
+    fn dropped_reservations_give_their_nonce_back() {
+        let address = Address::repeat_byte(0x11);
+        let chain_id = 31_337_001;
+        let key = (chain_id, address);
+        let take = || nonces().entry(key).or_default().take(0);
+        let reservation = |nonce| NonceReservation {
+            chain_id,
+            address,
+            nonce,
+            committed: false,
+        };
+
+        reservation(take()).commit();
+        drop(reservation(take()));
+        assert_eq!(take(), 1);
+        forget_nonces(chain_id, address);
+        assert!(!nonces().contains_key(&key));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_failed_bump_keeps_waiting_for_the_receipt() {
+        use alloy::transports::mock::Asserter;
+
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let hash = TxHash::repeat_byte(0xab);
+        let stuck = StuckPolicy {
+            stuck_after: Duration::from_nanos(1),
+            ..StuckPolicy::default()
+        };
+
+        // Not mined yet, so it is bumped; the node refuses because it was
+        // mined in the meantime.
+        asserter.push_success(&Option::<TransactionReceipt>::None);
+        asserter.push_failure_msg("nonce too low");
+        // The next poll finds it.
+        asserter.push_success(&serde_json::json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0x5208",
+            "logs": [],
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "transactionHash": hash,
+            "transactionIndex": "0x0",
+            "blockHash": TxHash::repeat_byte(0x01),
+            "blockNumber": "0x10",
+            "gasUsed": "0x5208",
+            "effectiveGasPrice": "0x1",
+            "from": Address::repeat_byte(0x11),
+            "to": Address::repeat_byte(0x22),
+            "contractAddress": null,
+        }));
+        asserter.push_success(&"0x10");
+
+        let receipt = wait_for_inclusion(&provider, hash, stuck).await.unwrap();
+        assert_eq!(receipt.transaction_hash, hash);
+    }
+}
@@ -1,7 +1,7 @@
 //! Chain-aware RPC client that dispatches between Alloy (EVM) and
 //! solana-client (Solana) based on the chain's `architecture` field.
 
-use alloy::primitives::{Address, Uint};
+use alloy::primitives::Uint;
 use alloy::providers::{Provider, ProviderBuilder};
 use alloy_chains::NamedChain;
 use eyre::Result;
@@ -22,6 +22,9 @@ use crate::commands::config::config_pb::{Chain, Token};
 pub const ARCH_SOLANA: &str = "Solana";
 /// Architecture string used in chain config for EVM chains.
 pub const ARCH_EVM: &str = "EVM";
+/// Architecture string used in chain config for Hedera chains, which are
+/// reached through their EVM JSON-RPC relay.
+pub const ARCH_HEDERA: &str = "Hedera";
 
 /// A curve-aware RPC client.
 pub enum ChainClient {
@@ -75,7 +78,7 @@ impl ChainClient {
             ChainClient::Evm { rpc_url, .. } => {
                 let url = Url::parse(rpc_url)?;
                 let provider = ProviderBuilder::new().connect_http(url);
-                let addr: Address = address.parse()?;
+                let addr = crate::util::parse_address(address)?;
                 let balance: Uint<256, 4> = provider.get_balance(addr).await?;
                 Ok(balance.try_into().unwrap_or(u128::MAX))
             }
@@ -104,8 +107,8 @@ impl ChainClient {
                 let provider = ProviderBuilder::new()
                     .with_chain(named_chain)
                     .connect_http(url);
-                let token_addr: Address = token.address.parse()?;
-                let owner_addr: Address = owner.parse()?;
+                let token_addr = crate::util::parse_address(&token.address)?;
+                let owner_addr = crate::util::parse_address(owner)?;
                 let contract = IERC20::new(token_addr, &provider);
                 let result: Uint<256, 4> = contract.balanceOf(owner_addr).call().await?;
                 Ok(result.try_into().unwrap_or(u128::MAX))
@@ -1,9 +1,19 @@
+use arc_swap::ArcSwapOption;
 use eyre::{Context, Result};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use std::time::Duration;
 use url::Url;
 
-use crate::commands::config::config_pb::{Chain, GetConfigResponse, Token};
+use crate::chain::tx::ConfirmationPolicy;
+use crate::commands::config::config_pb::{Chain, GetConfigResponse, MaintenanceNotice, Token};
+#[cfg(any(feature = "trader", feature = "admin"))]
+use crate::commands::trading::risk::RiskConfig;
+#[cfg(feature = "admin")]
+use crate::error::AspensError;
+use crate::grpc::GrpcOptions;
+#[cfg(any(feature = "admin", feature = "keystore"))]
+use crate::wallet::Wallet;
 
 /// JWT token information for authenticated admin operations
 #[derive(Debug, Clone)]
@@ -14,72 +24,198 @@ pub struct JwtToken {
     pub expires_at: u64,
 }
 
+/// Callback invoked with the operator's maintenance notice whenever
+/// [`AspensClient::fetch_config`] receives one.
+pub type MaintenanceCallback = Arc<dyn Fn(&MaintenanceNotice) + Send + Sync>;
+
 /// Main client for interacting with Aspens trading platform
+///
+/// # Concurrency
+///
+/// `AspensClient` is `Send + Sync` and cheap to [`Clone`]: every field is
+/// behind an `Arc`, so clones share the same cached config and JWT. Hand a
+/// clone (or an `Arc<AspensClient>`) to each task rather than wrapping the
+/// client in a `Mutex`.
+///
+/// - Cached config and JWT reads are lock-free snapshots; they never block
+///   and never observe a half-written value.
+/// - [`fetch_config`](Self::fetch_config) and
+///   [`set_jwt_token`](Self::set_jwt_token) replace the shared value
+///   atomically; tasks holding an earlier snapshot keep using it.
+/// - Tasks that call [`get_config`](Self::get_config) before anything is
+///   cached may each fetch; the last response to arrive wins. Call
+///   `fetch_config` once at startup to avoid the stampede.
+#[derive(Clone)]
 pub struct AspensClient {
     /// URL of the Aspens Market Stack
-    pub(crate) stack_url: Url,
+    pub(crate) stack_url: Arc<Url>,
     /// Environment variables loaded from .env file
-    pub(crate) env_vars: HashMap<String, String>,
+    pub(crate) env_vars: Arc<HashMap<String, String>>,
     /// Cached configuration from the server
-    pub(crate) config: Arc<RwLock<Option<GetConfigResponse>>>,
+    pub(crate) config: Arc<ArcSwapOption<GetConfigResponse>>,
     /// JWT token for admin operations (when authenticated)
-    pub(crate) jwt_token: Arc<RwLock<Option<JwtToken>>>,
+    pub(crate) jwt_token: Arc<ArcSwapOption<JwtToken>>,
+    /// Deadline for a single non-streaming command
+    pub(crate) command_timeout: Duration,
+    /// Confirmations deposits, withdrawals and deploys wait for
+    pub(crate) confirmation_policy: ConfirmationPolicy,
+    /// Called when a fetched config carries a maintenance notice
+    pub(crate) maintenance_callback: Option<MaintenanceCallback>,
+    /// Referral code attached to orders that don't carry their own
+    pub(crate) referral_code: Option<Arc<str>>,
+    /// Message size / compression for streams that don't set their own
+    pub(crate) grpc_options: Option<GrpcOptions>,
+    /// Per-market streams shared by every clone of this client
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    pub(crate) subscriptions: crate::subscriptions::SubscriptionHub,
+    /// Limits every order sent through the client is checked against
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    pub(crate) risk_limits: Arc<RiskConfig>,
+    /// Wallet unlocked from the builder's keystore file
+    #[cfg(feature = "keystore")]
+    pub(crate) keystore_wallet: Option<Arc<Wallet>>,
+    /// Signer the [`JwtManager`] logs in again with
+    #[cfg(feature = "admin")]
+    pub(crate) jwt_signer: Option<Arc<Wallet>>,
+    /// Chain ID for the EIP-712 domain of those logins
+    #[cfg(feature = "admin")]
+    pub(crate) jwt_chain_id: Option<u64>,
+    /// Serializes logins so concurrent refreshes make only one
+    #[cfg(feature = "admin")]
+    pub(crate) jwt_refresh: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl AspensClient {
+    /// Default [`command_timeout`](Self::command_timeout): short enough that
+    /// a half-open connection fails instead of hanging.
+    pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
     /// Create a new builder for AspensClient
     pub fn builder() -> AspensClientBuilder {
         AspensClientBuilder::default()
     }
 
+    /// An anonymous, rate-limited client for the public endpoints of the
+    /// stack at `url`: config, streams and book snapshots only.
+    ///
+    /// Unlike [`builder`](Self::builder), nothing is read from an env file
+    /// and no wallet or JWT is ever involved; see
+    /// [`PublicClient`](crate::public::PublicClient).
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    pub fn public(url: impl AsRef<str>) -> Result<crate::public::PublicClient> {
+        crate::public::PublicClient::new(url)
+    }
+
     /// Get the Aspens Market Stack URL
     pub fn stack_url(&self) -> &Url {
         &self.stack_url
     }
 
+    /// Deadline for a single non-streaming command, for use with
+    /// [`AsyncExecutor::execute_with_timeout`](crate::AsyncExecutor::execute_with_timeout).
+    /// Zero means no deadline.
+    ///
+    /// Commands that send transactions shouldn't be held to it: dropping one
+    /// after the broadcast abandons a transaction that may still land, and
+    /// the [`confirmation_policy`](Self::confirmation_policy) already bounds
+    /// its wait with an error naming the hash.
+    pub fn command_timeout(&self) -> Duration {
+        self.command_timeout
+    }
+
+    /// How many confirmations deposits, withdrawals and deploys wait for,
+    /// and for how long: pass it in their options, e.g.
+    /// [`DepositOpts`](crate::commands::trading::deposit::DepositOpts).
+    pub fn confirmation_policy(&self) -> ConfirmationPolicy {
+        self.confirmation_policy
+    }
+
+    /// Referral code attached to orders sent through this client, unless
+    /// the order's [`OrderTags`](crate::commands::trading::send_order::OrderTags)
+    /// name their own.
+    pub fn referral_code(&self) -> Option<&str> {
+        self.referral_code.as_deref()
+    }
+
+    /// Message size / compression settings for the orderbook and trades
+    /// streams opened through [`AspensApi`](crate::api::AspensApi), unless
+    /// the stream options set their own. `None` picks per stream: see
+    /// [`GrpcOptions::historical`].
+    pub fn grpc_options(&self) -> Option<GrpcOptions> {
+        self.grpc_options
+    }
+
+    /// Reference-counted per-market orderbook and trades streams, shared
+    /// by every clone of this client: components subscribing to the same
+    /// market share one upstream stream.
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    pub fn subscriptions(&self) -> &crate::subscriptions::SubscriptionHub {
+        &self.subscriptions
+    }
+
+    /// Client-side limits orders sent through
+    /// [`AspensApi::send_order`](crate::api::AspensApi::send_order) are
+    /// checked against before they're signed.
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    pub fn risk_limits(&self) -> &RiskConfig {
+        &self.risk_limits
+    }
+
+    /// The wallet unlocked from
+    /// [`AspensClientBuilder::with_keystore`]'s file, if one was given.
+    #[cfg(feature = "keystore")]
+    pub fn keystore_wallet(&self) -> Option<&Wallet> {
+        self.keystore_wallet.as_deref()
+    }
+
     /// Get an environment variable value
     pub fn get_env(&self, key: &str) -> Option<&String> {
         self.env_vars.get(key)
     }
 
+    /// All variables loaded from the env file, in no particular order.
+    pub fn env_vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.env_vars.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
     /// Fetch configuration from the server and cache it
+    ///
+    /// If the config carries a maintenance notice, the
+    /// [maintenance callback](AspensClientBuilder::with_maintenance_callback)
+    /// is called with it.
     pub async fn fetch_config(&self) -> Result<()> {
         let config = crate::commands::config::get_config(self.stack_url.to_string()).await?;
-        let mut guard = self
-            .config
-            .write()
-            .expect("AspensClient config lock poisoned");
-        *guard = Some(config);
+        if let (Some(callback), Some(notice)) = (&self.maintenance_callback, config.maintenance()) {
+            callback(notice);
+        }
+        self.config.store(Some(Arc::new(config)));
         Ok(())
     }
 
     /// Get the cached configuration, fetching it if necessary
     pub async fn get_config(&self) -> Result<GetConfigResponse> {
-        // Check if we have a cached config
-        {
-            let guard = self
-                .config
-                .read()
-                .expect("AspensClient config lock poisoned");
-            if let Some(config) = guard.as_ref() {
-                return Ok(config.clone());
-            }
+        self.shared_config().await.map(|config| (*config).clone())
+    }
+
+    /// Like [`get_config`](Self::get_config), but returns the shared snapshot
+    /// without cloning it. Prefer this on hot paths in many-task bots.
+    pub async fn shared_config(&self) -> Result<Arc<GetConfigResponse>> {
+        if let Some(config) = self.cached_config() {
+            return Ok(config);
         }
 
         // No cached config, fetch it
         self.fetch_config().await?;
 
-        // Return the newly fetched config
-        let guard = self
-            .config
-            .read()
-            .expect("AspensClient config lock poisoned");
-        guard
-            .as_ref()
-            .cloned()
+        self.cached_config()
             .ok_or_else(|| eyre::eyre!("Failed to fetch configuration"))
     }
 
+    /// The cached configuration, if one has been fetched. Never blocks.
+    pub fn cached_config(&self) -> Option<Arc<GetConfigResponse>> {
+        self.config.load_full()
+    }
+
     /// Get chain information by network name
     pub async fn get_chain_info(&self, network: &str) -> Result<Chain> {
         let config = self.get_config().await?;
@@ -143,35 +279,29 @@ impl AspensClient {
 
     /// Set the JWT token for admin operations
     pub fn set_jwt_token(&self, token: String, expires_at: u64) {
-        let mut guard = self
-            .jwt_token
-            .write()
-            .expect("AspensClient JWT lock poisoned");
-        *guard = Some(JwtToken { token, expires_at });
+        self.jwt_token
+            .store(Some(Arc::new(JwtToken { token, expires_at })));
     }
 
-    /// Get the current JWT token if valid
+    /// Get the current JWT token if valid. With the `keychain` feature, a
+    /// token saved for this stack (see [`crate::keychain`]) is loaded when
+    /// none is cached.
     pub fn get_jwt_token(&self) -> Option<String> {
-        let guard = self
-            .jwt_token
-            .read()
-            .expect("AspensClient JWT lock poisoned");
-        guard.as_ref().and_then(|jwt| {
-            if self.is_jwt_valid_internal(jwt) {
-                Some(jwt.token.clone())
-            } else {
-                None
-            }
-        })
+        #[cfg(feature = "keychain")]
+        if !self.is_jwt_valid() {
+            load_keychain_jwt(&self.stack_url, &self.jwt_token);
+        }
+        self.jwt_token
+            .load()
+            .as_ref()
+            .filter(|jwt| self.is_jwt_valid_internal(jwt))
+            .map(|jwt| jwt.token.clone())
     }
 
     /// Check if the current JWT token is valid
     pub fn is_jwt_valid(&self) -> bool {
-        let guard = self
-            .jwt_token
-            .read()
-            .expect("AspensClient JWT lock poisoned");
-        guard
+        self.jwt_token
+            .load()
             .as_ref()
             .map(|jwt| self.is_jwt_valid_internal(jwt))
             .unwrap_or(false)
@@ -190,20 +320,154 @@ impl AspensClient {
 
     /// Clear the JWT token
     pub fn clear_jwt_token(&self) {
-        let mut guard = self
-            .jwt_token
-            .write()
-            .expect("AspensClient JWT lock poisoned");
-        *guard = None;
+        self.jwt_token.store(None);
     }
 
     /// Get JWT expiry time (if set)
     pub fn get_jwt_expiry(&self) -> Option<u64> {
-        let guard = self
-            .jwt_token
-            .read()
-            .expect("AspensClient JWT lock poisoned");
-        guard.as_ref().map(|jwt| jwt.expires_at)
+        self.jwt_token.load().as_ref().map(|jwt| jwt.expires_at)
+    }
+
+    /// A [`JwtManager`] sharing this client's JWT, for admin calls that
+    /// should survive the token expiring.
+    #[cfg(feature = "admin")]
+    pub fn jwt_manager(&self) -> JwtManager {
+        JwtManager {
+            stack_url: self.stack_url.clone(),
+            token: self.jwt_token.clone(),
+            signer: self.jwt_signer.clone(),
+            chain_id: self.jwt_chain_id,
+            refresh: self.jwt_refresh.clone(),
+        }
+    }
+}
+
+/// Cache the valid token saved in the OS keychain for `stack_url`, if any.
+#[cfg(feature = "keychain")]
+fn load_keychain_jwt(stack_url: &Url, slot: &ArcSwapOption<JwtToken>) {
+    match crate::keychain::load_jwt(stack_url.as_str()) {
+        Ok(Some(saved)) => slot.store(Some(Arc::new(JwtToken {
+            token: saved.jwt_token,
+            expires_at: saved.expires_at,
+        }))),
+        Ok(None) => {}
+        Err(e) => tracing::debug!("No admin JWT from the OS keychain: {}", e),
+    }
+}
+
+/// Keeps the admin JWT of an [`AspensClient`] current.
+///
+/// [`token`](Self::token) returns the cached token while it is valid (see
+/// [`is_token_valid`](crate::commands::auth::is_token_valid)) and otherwise
+/// logs in again with the client's signer, set with
+/// [`AspensClientBuilder::with_jwt_signer`] or `ADMIN_PRIVKEY` in the env
+/// file. [`call`](Self::call) also logs in again, and retries once, when the
+/// stack rejects the token. Clones share the token with the client, and
+/// concurrent refreshes are coalesced into a single login.
+#[cfg(feature = "admin")]
+#[derive(Clone)]
+pub struct JwtManager {
+    stack_url: Arc<Url>,
+    token: Arc<ArcSwapOption<JwtToken>>,
+    signer: Option<Arc<Wallet>>,
+    chain_id: Option<u64>,
+    refresh: Arc<tokio::sync::Mutex<()>>,
+}
+
+#[cfg(feature = "admin")]
+impl JwtManager {
+    /// True if a signer is configured, so expired tokens are renewed.
+    pub fn can_refresh(&self) -> bool {
+        self.signer.is_some()
+    }
+
+    /// The current token if it is still valid, or else the one saved in the
+    /// OS keychain for this stack (with the `keychain` feature).
+    pub fn valid_token(&self) -> Option<String> {
+        #[cfg(feature = "keychain")]
+        if !self
+            .token
+            .load()
+            .as_ref()
+            .is_some_and(|jwt| crate::commands::auth::is_token_valid(jwt.expires_at))
+        {
+            load_keychain_jwt(&self.stack_url, &self.token);
+        }
+        self.token
+            .load()
+            .as_ref()
+            .filter(|jwt| crate::commands::auth::is_token_valid(jwt.expires_at))
+            .map(|jwt| jwt.token.clone())
+    }
+
+    /// A valid token, logging in again first if the current one is missing
+    /// or about to expire.
+    pub async fn token(&self) -> crate::error::Result<String> {
+        match self.valid_token() {
+            Some(jwt) => Ok(jwt),
+            None => self.renew(None).await,
+        }
+    }
+
+    /// Log in again now, replacing the current token even if it hasn't
+    /// expired.
+    pub async fn refresh(&self) -> crate::error::Result<String> {
+        let current = self.token.load_full().map(|jwt| jwt.token.clone());
+        self.renew(current.as_deref()).await
+    }
+
+    /// Run `call` with a valid token. If the stack answers with an
+    /// [`Auth`](AspensError::Auth) error and a signer is configured, log in
+    /// again and run `call` once more with the new token.
+    pub async fn call<T, F, Fut>(&self, mut call: F) -> crate::error::Result<T>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = crate::error::Result<T>>,
+    {
+        let jwt = self.token().await?;
+        match call(jwt.clone()).await {
+            Err(AspensError::Auth(e)) if self.can_refresh() => {
+                tracing::warn!("Admin JWT rejected ({}); logging in again", e);
+                let jwt = self.renew(Some(&jwt)).await?;
+                call(jwt).await
+            }
+            result => result,
+        }
+    }
+
+    /// Log in with the signer unless another task already replaced
+    /// `rejected` (or a missing/expired token, for `None`) with a valid one.
+    async fn renew(&self, rejected: Option<&str>) -> crate::error::Result<String> {
+        let _guard = self.refresh.lock().await;
+        if let Some(jwt) = self.valid_token()
+            && rejected != Some(jwt.as_str())
+        {
+            return Ok(jwt);
+        }
+        let signer = self.signer.as_ref().ok_or_else(|| {
+            AspensError::auth(
+                "admin JWT is missing or expired and no signer is configured to log in again\n\n\
+                 Hints:\n\
+                 - Set ADMIN_PRIVKEY in your .env file\n\
+                 - Or use AspensClientBuilder::with_jwt_signer",
+            )
+        })?;
+        let auth = crate::commands::auth::authenticate_with_wallet(
+            self.stack_url.to_string(),
+            signer.as_ref(),
+            self.chain_id,
+        )
+        .await?;
+        tracing::info!(
+            "Logged in again as {}; JWT expires at {}",
+            auth.address,
+            auth.expires_at
+        );
+        self.token.store(Some(Arc::new(JwtToken {
+            token: auth.jwt_token.clone(),
+            expires_at: auth.expires_at,
+        })));
+        Ok(auth.jwt_token)
     }
 }
 
@@ -212,6 +476,17 @@ impl AspensClient {
 pub struct AspensClientBuilder {
     stack_url: Option<Url>,
     env_file_path: Option<String>,
+    command_timeout: Option<Duration>,
+    confirmation_policy: Option<ConfirmationPolicy>,
+    maintenance_callback: Option<MaintenanceCallback>,
+    referral_code: Option<String>,
+    grpc_options: Option<GrpcOptions>,
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    risk_limits: Option<RiskConfig>,
+    #[cfg(feature = "keystore")]
+    keystore_path: Option<std::path::PathBuf>,
+    #[cfg(feature = "admin")]
+    jwt_signer: Option<(Wallet, Option<u64>)>,
 }
 
 impl AspensClientBuilder {
@@ -228,6 +503,79 @@ impl AspensClientBuilder {
         self
     }
 
+    /// Set the per-command timeout (defaults to
+    /// [`AspensClient::DEFAULT_COMMAND_TIMEOUT`]; `Duration::ZERO` disables it)
+    pub fn with_command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how many confirmations on-chain operations wait for, and for how
+    /// long (defaults to [`ConfirmationPolicy::default`]: mined once,
+    /// within two minutes).
+    pub fn with_confirmation_policy(mut self, policy: ConfirmationPolicy) -> Self {
+        self.confirmation_policy = Some(policy);
+        self
+    }
+
+    /// Call `callback` with the operator's maintenance notice each time a
+    /// fetched config carries one, so a bot can warn or pause ahead of
+    /// announced downtime.
+    pub fn with_maintenance_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&MaintenanceNotice) + Send + Sync + 'static,
+    {
+        self.maintenance_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Attribute every order sent through the client to a referral code
+    /// (defaults to `ASPENS_REFERRAL_CODE` from the env file, if set).
+    pub fn with_referral_code(mut self, code: impl Into<String>) -> Self {
+        self.referral_code = Some(code.into());
+        self
+    }
+
+    /// Set the message size limits and compression used for streams (by
+    /// default, raised limits and gzip only when replaying history).
+    pub fn with_grpc_options(mut self, options: GrpcOptions) -> Self {
+        self.grpc_options = Some(options);
+        self
+    }
+
+    /// Check every order sent through the client against `limits` before
+    /// signing it. The env file's `ASPENS_RISK_*` variables override
+    /// individual limits (see
+    /// [`RiskConfig::with_env_overrides`]).
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    pub fn with_risk_limits(mut self, limits: RiskConfig) -> Self {
+        self.risk_limits = Some(limits);
+        self
+    }
+
+    /// Unlock the EVM key in a Web3 Secret Storage (geth keystore JSON)
+    /// file at build, prompting for its passphrase unless
+    /// [`KEYSTORE_PASSPHRASE_ENV`](crate::wallet::KEYSTORE_PASSPHRASE_ENV)
+    /// is set. The wallet is then available from
+    /// [`AspensClient::keystore_wallet`], and is the default JWT signer
+    /// under the `admin` feature.
+    #[cfg(feature = "keystore")]
+    pub fn with_keystore(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.keystore_path = Some(path.into());
+        self
+    }
+
+    /// Log in with `wallet` whenever the [`JwtManager`] finds the admin JWT
+    /// expired or rejected (defaults to the
+    /// [`with_keystore`](Self::with_keystore) wallet, or else `ADMIN_PRIVKEY`
+    /// from the env file, if set). `chain_id` is the EIP-712 domain's chain ID, as in
+    /// [`authenticate_with_wallet`](crate::commands::auth::authenticate_with_wallet).
+    #[cfg(feature = "admin")]
+    pub fn with_jwt_signer(mut self, wallet: Wallet, chain_id: Option<u64>) -> Self {
+        self.jwt_signer = Some((wallet, chain_id));
+        self
+    }
+
     /// Build the AspensClient
     pub fn build(self) -> Result<AspensClient> {
         // Load environment file (defaults to .env)
@@ -250,11 +598,69 @@ impl AspensClientBuilder {
                 )
             })?;
 
+        let referral_code = self
+            .referral_code
+            .or_else(|| env_vars.get("ASPENS_REFERRAL_CODE").cloned())
+            .filter(|code| !code.is_empty())
+            .map(Arc::from);
+
+        #[cfg(feature = "keystore")]
+        let keystore_wallet = self
+            .keystore_path
+            .map(|path| Wallet::unlock_keystore(path).map(Arc::new))
+            .transpose()?;
+
+        #[cfg(feature = "admin")]
+        let (jwt_signer, jwt_chain_id) = match self.jwt_signer {
+            Some((wallet, chain_id)) => (Some(Arc::new(wallet)), chain_id),
+            #[cfg(feature = "keystore")]
+            None if keystore_wallet.is_some() => (keystore_wallet.clone(), None),
+            None => match env_vars.get("ADMIN_PRIVKEY").filter(|k| !k.is_empty()) {
+                Some(key) => (
+                    Some(Arc::new(
+                        Wallet::from_evm_hex(key).context("Invalid ADMIN_PRIVKEY")?,
+                    )),
+                    None,
+                ),
+                None => (None, None),
+            },
+        };
+
+        #[cfg(any(feature = "trader", feature = "admin"))]
+        let risk_limits = self
+            .risk_limits
+            .unwrap_or_default()
+            .with_env_overrides(|key| env_vars.get(key).cloned())
+            .with_context(|| format!("Invalid risk limit in {}", env_file))?;
+
+        #[cfg(any(feature = "trader", feature = "admin"))]
+        let subscriptions =
+            crate::subscriptions::SubscriptionHub::new(stack_url.as_str(), self.grpc_options);
+
         Ok(AspensClient {
-            stack_url,
-            env_vars,
-            config: Arc::new(RwLock::new(None)),
-            jwt_token: Arc::new(RwLock::new(None)),
+            stack_url: Arc::new(stack_url),
+            env_vars: Arc::new(env_vars),
+            config: Arc::new(ArcSwapOption::empty()),
+            jwt_token: Arc::new(ArcSwapOption::empty()),
+            command_timeout: self
+                .command_timeout
+                .unwrap_or(AspensClient::DEFAULT_COMMAND_TIMEOUT),
+            confirmation_policy: self.confirmation_policy.unwrap_or_default(),
+            maintenance_callback: self.maintenance_callback,
+            referral_code,
+            grpc_options: self.grpc_options,
+            #[cfg(any(feature = "trader", feature = "admin"))]
+            subscriptions,
+            #[cfg(any(feature = "trader", feature = "admin"))]
+            risk_limits: Arc::new(risk_limits),
+            #[cfg(feature = "keystore")]
+            keystore_wallet,
+            #[cfg(feature = "admin")]
+            jwt_signer,
+            #[cfg(feature = "admin")]
+            jwt_chain_id,
+            #[cfg(feature = "admin")]
+            jwt_refresh: Arc::new(tokio::sync::Mutex::new(())),
         })
     }
 }
@@ -318,6 +724,54 @@ mod tests {
         assert_eq!(client.stack_url().as_str(), "http://example.com:8080/");
     }
 
+    #[test]
+    fn test_builder_command_timeout() {
+        let builder = || {
+            AspensClient::builder()
+                .with_url("http://example.com")
+                .unwrap()
+        };
+        assert_eq!(
+            builder().build().unwrap().command_timeout(),
+            AspensClient::DEFAULT_COMMAND_TIMEOUT
+        );
+        let client = builder()
+            .with_command_timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        assert_eq!(client.command_timeout(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_builder_confirmation_policy() {
+        let builder = || {
+            AspensClient::builder()
+                .with_url("http://example.com")
+                .unwrap()
+        };
+        assert_eq!(
+            builder().build().unwrap().confirmation_policy(),
+            ConfirmationPolicy::default()
+        );
+        let policy = ConfirmationPolicy {
+            confirmations: 6,
+            timeout: Duration::from_secs(900),
+        };
+        let client = builder().with_confirmation_policy(policy).build().unwrap();
+        assert_eq!(client.confirmation_policy(), policy);
+    }
+
+    #[test]
+    fn test_builder_referral_code() {
+        let client = AspensClient::builder()
+            .with_url("http://example.com")
+            .unwrap()
+            .with_referral_code("desk-42")
+            .build()
+            .unwrap();
+        assert_eq!(client.referral_code(), Some("desk-42"));
+    }
+
     #[test]
     fn test_builder_requires_stack_url() {
         let file = NamedTempFile::new().unwrap();
@@ -334,6 +788,80 @@ mod tests {
         );
     }
 
+    #[cfg(any(feature = "trader", feature = "admin"))]
+    #[test]
+    fn test_public_client_ignores_env_file() {
+        let client = AspensClient::public("http://example.com:8080").unwrap();
+        assert_eq!(client.stack_url().as_str(), "http://example.com:8080/");
+        assert!(AspensClient::public("not a url").is_err());
+    }
+
+    #[test]
+    fn test_client_is_shareable_across_tasks() {
+        fn assert_shareable<T: Send + Sync + Clone + 'static>() {}
+        assert_shareable::<AspensClient>();
+    }
+
+    #[test]
+    fn test_clones_share_cached_state() {
+        let client = AspensClient::builder()
+            .with_url("http://example.com:8080")
+            .unwrap()
+            .build()
+            .unwrap();
+        let clone = client.clone();
+        assert!(clone.cached_config().is_none());
+
+        client
+            .config
+            .store(Some(Arc::new(GetConfigResponse::default())));
+        client.set_jwt_token("token".into(), u64::MAX);
+
+        assert!(clone.cached_config().is_some());
+        assert_eq!(clone.get_jwt_token().as_deref(), Some("token"));
+        clone.clear_jwt_token();
+        assert!(!client.is_jwt_valid());
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_jwt_manager_reuses_valid_token() {
+        let file = NamedTempFile::new().unwrap();
+        let client = AspensClient::builder()
+            .with_url("http://example.com:8080")
+            .unwrap()
+            .with_env_file(file.path().to_str().unwrap())
+            .build()
+            .unwrap();
+        let jwt = client.jwt_manager();
+        assert!(!jwt.can_refresh());
+
+        // No token and no signer: an auth error instead of a call.
+        let err = jwt
+            .call(|_| async { Ok::<_, AspensError>(()) })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AspensError::Auth(_)));
+
+        client.set_jwt_token("fresh".into(), u64::MAX);
+        assert_eq!(jwt.token().await.unwrap(), "fresh");
+
+        // A token another task already renewed is used without logging in.
+        assert_eq!(jwt.renew(Some("stale")).await.unwrap(), "fresh");
+
+        // Without a signer, a rejected token is reported, not retried.
+        let mut calls = 0;
+        let err = jwt
+            .call(|token| {
+                calls += 1;
+                async move { Err::<(), _>(AspensError::auth(format!("{token} expired"))) }
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(calls, 1);
+        assert_eq!(err.to_string(), "fresh expired");
+    }
+
     #[test]
     fn test_env_file_quote_stripping() {
         // Create a temporary .env file with quoted values
@@ -0,0 +1,270 @@
+//! Resumable trade-contract deployment.
+//!
+//! Deploying an EVM trade contract takes five steps: fetch the calldata,
+//! sign, broadcast, wait for confirmation, and register the instance with
+//! the stack. Interrupted after the broadcast, the instance exists on chain
+//! but the stack doesn't know about it. [`DeployJournal`] records each
+//! broadcast deployment under the profile directory (see
+//! [`crate::storage`]) until it is registered, and
+//! [`register_deployment`] can be re-run for the same transaction without
+//! registering twice.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use alloy::primitives::B256;
+use alloy::providers::{Provider, ProviderBuilder};
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::config_pb::DeployContractResponse;
+use super::{MidribFactory, deploy_contract};
+use crate::commands::config::get_config;
+use crate::error::{self, AspensError};
+use crate::schema::{Schema, wrap_in};
+use crate::storage::{Storage, default_storage, storage_for_path};
+
+/// File name of the deploy journal inside the profile directory.
+pub const DEPLOY_JOURNAL_FILE: &str = "pending_deploys.json";
+
+/// v1 was the bare tx-hash-to-deploy map; v2 moves it under `pending`.
+static DEPLOY_JOURNAL_SCHEMA: Schema = Schema::new(
+    "deploy journal",
+    &[|doc| {
+        wrap_in(doc, "pending");
+        Ok(())
+    }],
+);
+
+#[derive(Deserialize)]
+struct JournalFile {
+    #[serde(default)]
+    pending: BTreeMap<String, PendingDeploy>,
+}
+
+/// A deployment that was broadcast but not yet registered with the stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDeploy {
+    /// Stack the instance is to be registered with.
+    pub stack_url: String,
+    /// Network the instance was deployed on.
+    pub network: String,
+    /// Trading fee, in basis points.
+    pub fee_bps: u32,
+    /// Hash of the `createInstance` transaction (0x-prefixed hex).
+    pub tx_hash: String,
+    /// Unix seconds when the transaction was broadcast, or signed for a
+    /// relay to broadcast.
+    pub broadcast_at: u64,
+}
+
+/// Broadcast deployments awaiting registration, keyed by transaction hash.
+#[derive(Debug)]
+pub struct DeployJournal {
+    storage: Arc<dyn Storage>,
+    name: String,
+    pending: BTreeMap<String, PendingDeploy>,
+}
+
+impl DeployJournal {
+    /// Open the journal at `path`; a missing file is an empty journal.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let (storage, name) = storage_for_path(&path.into())?;
+        Self::load(storage, name)
+    }
+
+    /// Open [`DEPLOY_JOURNAL_FILE`] in `storage`.
+    pub fn open_in(storage: Arc<dyn Storage>) -> Result<Self> {
+        Self::load(storage, DEPLOY_JOURNAL_FILE.to_string())
+    }
+
+    /// Open [`DEPLOY_JOURNAL_FILE`] in the default storage (see [`crate::storage`]).
+    pub fn open_default() -> Result<Self> {
+        Self::open_in(default_storage()?)
+    }
+
+    fn load(storage: Arc<dyn Storage>, name: String) -> Result<Self> {
+        let pending = match storage.read(&name)? {
+            Some(json) => {
+                DEPLOY_JOURNAL_SCHEMA
+                    .load::<JournalFile>(&json)
+                    .map_err(|e| {
+                        eyre!(
+                            "invalid deploy journal {}: {}",
+                            storage.path(&name).display(),
+                            e
+                        )
+                    })?
+                    .pending
+            }
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            storage,
+            name,
+            pending,
+        })
+    }
+
+    /// Path of the journal file.
+    pub fn path(&self) -> PathBuf {
+        self.storage.path(&self.name)
+    }
+
+    /// All pending deployments, sorted by transaction hash.
+    pub fn pending(&self) -> impl Iterator<Item = &PendingDeploy> {
+        self.pending.values()
+    }
+
+    /// The pending deployment broadcast in `tx_hash`, if recorded.
+    pub fn get(&self, tx_hash: &str) -> Option<&PendingDeploy> {
+        self.pending.get(&tx_hash.to_ascii_lowercase())
+    }
+
+    /// Record a broadcast deployment, replacing any entry for the same
+    /// transaction.
+    pub fn record(&mut self, deploy: PendingDeploy) -> Result<()> {
+        self.pending
+            .insert(deploy.tx_hash.to_ascii_lowercase(), deploy);
+        self.write()
+    }
+
+    /// Drop the entry for `tx_hash` once it is registered. Returns false if
+    /// there was no such entry.
+    pub fn complete(&mut self, tx_hash: &str) -> Result<bool> {
+        if self.pending.remove(&tx_hash.to_ascii_lowercase()).is_none() {
+            return Ok(false);
+        }
+        self.write()?;
+        Ok(true)
+    }
+
+    fn write(&self) -> Result<()> {
+        let _lock = self.storage.lock(&self.name)?;
+        self.storage.write(
+            &self.name,
+            &DEPLOY_JOURNAL_SCHEMA.to_string(&serde_json::json!({ "pending": self.pending }))?,
+        )
+    }
+}
+
+/// Address of the trading instance the factory created in `tx_hash`, from
+/// the `TradingInstanceCreated` event in its receipt. `None` while the
+/// transaction is unmined.
+pub async fn deployed_instance(rpc_url: String, tx_hash: String) -> error::Result<Option<String>> {
+    let hash: B256 = tx_hash.parse().map_err(|e| {
+        AspensError::invalid_input(format!("invalid transaction hash '{}': {}", tx_hash, e))
+    })?;
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&rpc_url)?);
+    let Some(receipt) = provider.get_transaction_receipt(hash).await.map_err(|e| {
+        AspensError::chain(format!("Failed to fetch receipt for {}: {}", tx_hash, e))
+    })?
+    else {
+        return Ok(None);
+    };
+    if !receipt.status() {
+        return Err(AspensError::chain(format!(
+            "createInstance transaction {} reverted",
+            tx_hash
+        )));
+    }
+    let created = receipt
+        .inner
+        .logs()
+        .iter()
+        .find_map(|log| {
+            log.log_decode::<MidribFactory::TradingInstanceCreated>()
+                .ok()
+        })
+        .ok_or_else(|| {
+            AspensError::invalid_input(format!(
+                "transaction {} created no trading instance; is it a createInstance call?",
+                tx_hash
+            ))
+        })?;
+    Ok(Some(created.inner.data.tradingInstanceAddress.to_string()))
+}
+
+/// Register the instance deployed in `tx_hash` with the stack, idempotently.
+///
+/// If the chain already has a trade contract and it is the instance
+/// `tx_hash` created, the earlier registration stands and is returned as is;
+/// a different contract is an error rather than an overwrite. Otherwise,
+/// and for Solana (empty `tx_hash`), this is [`deploy_contract`] without
+/// `force`.
+pub async fn register_deployment(
+    url: String,
+    jwt: String,
+    chain_network: String,
+    tx_hash: String,
+    fee_bps: u32,
+) -> error::Result<DeployContractResponse> {
+    let config = get_config(url.clone()).await?;
+    let chain = config.get_chain(&chain_network).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "Chain '{}' not found in configuration",
+            chain_network
+        ))
+    })?;
+
+    if !tx_hash.is_empty()
+        && let Some(existing) = chain
+            .trade_contract
+            .as_ref()
+            .filter(|tc| !tc.address.is_empty())
+    {
+        let deployed = deployed_instance(chain.rpc_url.clone(), tx_hash.clone()).await?;
+        if deployed.is_some_and(|addr| addr.eq_ignore_ascii_case(&existing.address)) {
+            tracing::info!(
+                "{} is already registered on '{}'",
+                existing.address,
+                chain_network
+            );
+            return Ok(DeployContractResponse {
+                contract_address: existing.address.clone(),
+                tx_signature: String::new(),
+            });
+        }
+        return Err(AspensError::invalid_input(format!(
+            "chain '{}' already has trade contract {}, not the instance created in {}\n\n\
+             Hints:\n\
+             - Remove it with 'aspens-admin delete-trade-contract {}' first, if it is stale",
+            chain_network, existing.address, tx_hash, chain_network
+        )));
+    }
+
+    deploy_contract(url, jwt, chain_network, tx_hash, false, fee_bps).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(tx_hash: &str) -> PendingDeploy {
+        PendingDeploy {
+            stack_url: "http://localhost:50051".into(),
+            network: "base-sepolia".into(),
+            fee_bps: 100,
+            tx_hash: tx_hash.into(),
+            broadcast_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn journal_round_trips_and_completes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DEPLOY_JOURNAL_FILE);
+
+        let mut journal = DeployJournal::open(&path).unwrap();
+        assert_eq!(journal.pending().count(), 0);
+        journal.record(pending("0xABCD")).unwrap();
+
+        let mut reopened = DeployJournal::open(&path).unwrap();
+        assert_eq!(reopened.get("0xabcd"), Some(&pending("0xABCD")));
+        assert!(reopened.complete("0xAbCd").unwrap());
+        assert!(!reopened.complete("0xabcd").unwrap());
+        assert_eq!(DeployJournal::open(&path).unwrap().pending().count(), 0);
+    }
+}
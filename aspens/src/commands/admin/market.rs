@@ -0,0 +1,425 @@
+//! Derive market parameters from the chain configuration already on the stack.
+//!
+//! `set_market` needs nine fields, but seven of them (token addresses and
+//! decimals, plus the network/symbol pairs) are already known to the server
+//! once both tokens are registered with `set_token`. The helpers here look
+//! those up from a [`GetConfigResponse`] so callers only have to name the two
+//! tokens as `SYMBOL@network`.
+//!
+//! [`diff_market`] compares `set_market` parameters against the market
+//! already on the stack, so an edit can be reviewed before it is sent.
+
+use std::fmt;
+use std::str::FromStr;
+
+use eyre::{Result, eyre};
+
+use super::SetMarketParams;
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::error::{self, AspensError};
+
+/// A token on a specific chain, written as `SYMBOL@network`
+/// (e.g. `USDC@base-sepolia`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenRef {
+    /// Token symbol as registered on the chain (e.g. `"USDC"`).
+    pub symbol: String,
+    /// Network name of the chain holding the token (e.g. `"base-sepolia"`).
+    pub network: String,
+}
+
+impl FromStr for TokenRef {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (symbol, network) = s.trim().split_once('@').ok_or_else(|| {
+            eyre!(
+                "invalid token reference '{}': expected SYMBOL@network (e.g. USDC@base-sepolia)",
+                s
+            )
+        })?;
+        let (symbol, network) = (symbol.trim(), network.trim());
+        if symbol.is_empty() || network.is_empty() || network.contains('@') {
+            return Err(eyre!(
+                "invalid token reference '{}': expected SYMBOL@network (e.g. USDC@base-sepolia)",
+                s
+            ));
+        }
+        Ok(Self {
+            symbol: symbol.to_string(),
+            network: network.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for TokenRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.symbol, self.network)
+    }
+}
+
+/// Suggest a `pair_decimals` value for a market between two tokens.
+///
+/// Uses the smaller of the two token decimals: anything larger is dropped
+/// when the arborter normalises order amounts down to the settling token's
+/// decimals, so it only adds digits that can never settle.
+pub fn suggest_pair_decimals(base_decimals: i32, quote_decimals: i32) -> i32 {
+    base_decimals.min(quote_decimals)
+}
+
+/// Build [`SetMarketParams`] for `base`/`quote` from the tokens registered in
+/// `config`.
+///
+/// `pair_decimals` defaults to [`suggest_pair_decimals`] when `None`.
+/// Fails if either chain or token is missing from the configuration.
+pub fn market_params_from_config(
+    config: &GetConfigResponse,
+    base: &TokenRef,
+    quote: &TokenRef,
+    pair_decimals: Option<i32>,
+) -> error::Result<SetMarketParams> {
+    let lookup = |token: &TokenRef, role: &str| {
+        if config.get_chain(&token.network).is_none() {
+            return Err(AspensError::not_found(format!(
+                "{} chain '{}' is not configured on the stack\n\n\
+                 Hints:\n\
+                 - Register it first with `aspens-admin set-chain`\n\
+                 - Check the network name with `aspens-cli config`",
+                role, token.network
+            )));
+        }
+        config
+            .get_token(&token.network, &token.symbol)
+            .ok_or_else(|| {
+                AspensError::not_found(format!(
+                    "{} token '{}' is not configured on chain '{}'\n\n\
+                     Hints:\n\
+                     - Register it first with `aspens-admin set-token`\n\
+                     - Token symbols are case-sensitive",
+                    role, token.symbol, token.network
+                ))
+            })
+    };
+
+    let base_token = lookup(base, "base")?;
+    let quote_token = lookup(quote, "quote")?;
+
+    let base_decimals = i32::try_from(base_token.decimals).map_err(|_| {
+        AspensError::invalid_input(format!(
+            "base token decimals {} out of range",
+            base_token.decimals
+        ))
+    })?;
+    let quote_decimals = i32::try_from(quote_token.decimals).map_err(|_| {
+        AspensError::invalid_input(format!(
+            "quote token decimals {} out of range",
+            quote_token.decimals
+        ))
+    })?;
+
+    let pair_decimals =
+        pair_decimals.unwrap_or_else(|| suggest_pair_decimals(base_decimals, quote_decimals));
+    if pair_decimals < 0 {
+        return Err(AspensError::invalid_input(format!(
+            "pair decimals must be non-negative, got {}",
+            pair_decimals
+        )));
+    }
+
+    Ok(SetMarketParams {
+        base_chain_network: base.network.clone(),
+        quote_chain_network: quote.network.clone(),
+        base_chain_token_symbol: base.symbol.clone(),
+        quote_chain_token_symbol: quote.symbol.clone(),
+        base_chain_token_address: base_token.address.clone(),
+        quote_chain_token_address: quote_token.address.clone(),
+        base_chain_token_decimals: base_decimals,
+        quote_chain_token_decimals: quote_decimals,
+        pair_decimals,
+    })
+}
+
+/// One field a market edit changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Human-readable field name (e.g. `"base token decimals"`).
+    pub field: &'static str,
+    /// Value on the stack now.
+    pub current: String,
+    /// Value the edit sets.
+    pub new: String,
+    /// True for decimals and token addresses: changing them reinterprets
+    /// the amounts of orders already resting on the market.
+    pub breaking: bool,
+}
+
+/// How [`SetMarketParams`] would change a market that already exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketDiff {
+    /// ID of the market being edited.
+    pub market_id: String,
+    /// Display name of the market being edited.
+    pub name: String,
+    /// Fields that differ, in parameter order. Empty if the edit is a no-op.
+    pub changes: Vec<FieldChange>,
+}
+
+impl MarketDiff {
+    /// True if the edit changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// True if any change can invalidate resting orders.
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|c| c.breaking)
+    }
+}
+
+impl fmt::Display for MarketDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return write!(f, "Market '{}' ({}): no changes", self.name, self.market_id);
+        }
+        write!(f, "Market '{}' ({}):", self.name, self.market_id)?;
+        for change in &self.changes {
+            write!(
+                f,
+                "\n  {}: {} -> {}{}",
+                change.field,
+                change.current,
+                change.new,
+                if change.breaking {
+                    "  (invalidates resting orders)"
+                } else {
+                    ""
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare `params` against the market with the same base and quote tokens
+/// in `config`.
+///
+/// Returns `None` if no such market exists, i.e. `set_market` would create
+/// one. Token addresses are compared against the tokens registered on each
+/// chain, since markets don't carry them.
+pub fn diff_market(config: &GetConfigResponse, params: &SetMarketParams) -> Option<MarketDiff> {
+    let market = config.get_market_by_tokens(
+        &params.base_chain_network,
+        &params.base_chain_token_symbol,
+        &params.quote_chain_network,
+        &params.quote_chain_token_symbol,
+    )?;
+    let address = |network: &str, symbol: &str| {
+        config
+            .get_token(network, symbol)
+            .map(|t| t.address.clone())
+            .unwrap_or_default()
+    };
+
+    let mut changes = Vec::new();
+    let mut compare = |field, current: String, new: &str, breaking| {
+        if !same_value(&current, new) {
+            changes.push(FieldChange {
+                field,
+                current,
+                new: new.to_string(),
+                breaking,
+            });
+        }
+    };
+    compare(
+        "base token address",
+        address(&market.base_chain_network, &market.base_chain_token_symbol),
+        &params.base_chain_token_address,
+        true,
+    );
+    compare(
+        "quote token address",
+        address(
+            &market.quote_chain_network,
+            &market.quote_chain_token_symbol,
+        ),
+        &params.quote_chain_token_address,
+        true,
+    );
+    compare(
+        "base token decimals",
+        market.base_chain_token_decimals.to_string(),
+        &params.base_chain_token_decimals.to_string(),
+        true,
+    );
+    compare(
+        "quote token decimals",
+        market.quote_chain_token_decimals.to_string(),
+        &params.quote_chain_token_decimals.to_string(),
+        true,
+    );
+    compare(
+        "pair decimals",
+        market.pair_decimals.to_string(),
+        &params.pair_decimals.to_string(),
+        true,
+    );
+
+    Some(MarketDiff {
+        market_id: market.market_id.clone(),
+        name: market.name.clone(),
+        changes,
+    })
+}
+
+/// Field equality, ignoring case for hex (EVM) addresses.
+fn same_value(current: &str, new: &str) -> bool {
+    if current.starts_with("0x") && new.starts_with("0x") {
+        current.eq_ignore_ascii_case(new)
+    } else {
+        current == new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::config_pb::{Chain, Configuration, Market, Token};
+    use std::collections::HashMap;
+
+    fn chain(network: &str, symbol: &str, address: &str, decimals: u32) -> Chain {
+        let mut tokens = HashMap::new();
+        tokens.insert(
+            symbol.to_string(),
+            Token {
+                name: symbol.into(),
+                symbol: symbol.into(),
+                address: address.into(),
+                token_id: None,
+                decimals,
+            },
+        );
+        Chain {
+            architecture: "evm".into(),
+            canonical_name: network.into(),
+            network: network.into(),
+            chain_id: 1,
+            instance_signer_address: String::new(),
+            explorer_url: None,
+            rpc_url: "http://localhost".into(),
+            factory_address: String::new(),
+            trade_contract: None,
+            tokens,
+        }
+    }
+
+    fn config() -> GetConfigResponse {
+        GetConfigResponse {
+            config: Some(Configuration {
+                chains: vec![
+                    chain("base-sepolia", "USDC", "0xbase", 6),
+                    chain("flare-coston2", "WFLR", "0xflare", 18),
+                ],
+                markets: vec![],
+                maintenance: None,
+                signing_versions: vec![],
+            }),
+        }
+    }
+
+    #[test]
+    fn token_ref_parses_symbol_and_network() {
+        let t: TokenRef = "USDC@base-sepolia".parse().unwrap();
+        assert_eq!(t.symbol, "USDC");
+        assert_eq!(t.network, "base-sepolia");
+        assert_eq!(t.to_string(), "USDC@base-sepolia");
+    }
+
+    #[test]
+    fn token_ref_rejects_malformed_input() {
+        for bad in ["USDC", "@base-sepolia", "USDC@", "USDC@a@b", ""] {
+            assert!(bad.parse::<TokenRef>().is_err(), "accepted {:?}", bad);
+        }
+    }
+
+    #[test]
+    fn params_resolve_addresses_and_decimals_from_config() {
+        let base = "WFLR@flare-coston2".parse().unwrap();
+        let quote = "USDC@base-sepolia".parse().unwrap();
+        let p = market_params_from_config(&config(), &base, &quote, None).unwrap();
+        assert_eq!(p.base_chain_token_address, "0xflare");
+        assert_eq!(p.quote_chain_token_address, "0xbase");
+        assert_eq!(p.base_chain_token_decimals, 18);
+        assert_eq!(p.quote_chain_token_decimals, 6);
+        assert_eq!(p.pair_decimals, 6);
+    }
+
+    #[test]
+    fn explicit_pair_decimals_override_suggestion() {
+        let base = "WFLR@flare-coston2".parse().unwrap();
+        let quote = "USDC@base-sepolia".parse().unwrap();
+        let p = market_params_from_config(&config(), &base, &quote, Some(8)).unwrap();
+        assert_eq!(p.pair_decimals, 8);
+    }
+
+    #[test]
+    fn unknown_chain_or_token_is_an_error() {
+        let quote = "USDC@base-sepolia".parse().unwrap();
+        let missing_chain = "USDC@nowhere".parse().unwrap();
+        let missing_token = "DAI@base-sepolia".parse().unwrap();
+        let err = market_params_from_config(&config(), &missing_chain, &quote, None).unwrap_err();
+        assert!(err.to_string().contains("chain 'nowhere'"));
+        let err = market_params_from_config(&config(), &missing_token, &quote, None).unwrap_err();
+        assert!(err.to_string().contains("token 'DAI'"));
+    }
+
+    fn config_with_market() -> GetConfigResponse {
+        let mut config = config();
+        config.config.as_mut().unwrap().markets.push(Market {
+            name: "WFLR/USDC".into(),
+            market_id: "m1".into(),
+            base_chain_network: "flare-coston2".into(),
+            quote_chain_network: "base-sepolia".into(),
+            base_chain_token_symbol: "WFLR".into(),
+            quote_chain_token_symbol: "USDC".into(),
+            base_chain_token_decimals: 18,
+            quote_chain_token_decimals: 6,
+            pair_decimals: 6,
+            ..Default::default()
+        });
+        config
+    }
+
+    #[test]
+    fn diff_is_none_for_a_new_market() {
+        let base = "WFLR@flare-coston2".parse().unwrap();
+        let quote = "USDC@base-sepolia".parse().unwrap();
+        let p = market_params_from_config(&config(), &base, &quote, None).unwrap();
+        assert_eq!(diff_market(&config(), &p), None);
+    }
+
+    #[test]
+    fn diff_flags_decimal_and_address_changes() {
+        let config = config_with_market();
+        let base = "WFLR@flare-coston2".parse().unwrap();
+        let quote = "USDC@base-sepolia".parse().unwrap();
+        let mut p = market_params_from_config(&config, &base, &quote, None).unwrap();
+
+        let diff = diff_market(&config, &p).unwrap();
+        assert!(diff.is_empty());
+        assert!(!diff.is_breaking());
+
+        // Address case doesn't count as a change.
+        p.base_chain_token_address = "0xFLARE".into();
+        assert!(diff_market(&config, &p).unwrap().is_empty());
+
+        p.pair_decimals = 4;
+        p.quote_chain_token_address = "0xother".into();
+        let diff = diff_market(&config, &p).unwrap();
+        assert_eq!(diff.market_id, "m1");
+        let fields: Vec<_> = diff.changes.iter().map(|c| c.field).collect();
+        assert_eq!(fields, ["quote token address", "pair decimals"]);
+        assert!(diff.is_breaking());
+        assert!(diff.to_string().contains("pair decimals: 6 -> 4"));
+    }
+}
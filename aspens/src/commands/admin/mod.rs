@@ -25,18 +25,31 @@ use config_pb::{
     DeleteChainRequest, DeleteChainResponse, DeleteMarketRequest, DeleteMarketResponse,
     DeleteTokenRequest, DeleteTokenResponse, DeleteTradeContractRequest,
     DeleteTradeContractResponse, DeployContractRequest, DeployContractResponse, Empty,
-    GetDeployCalldataRequest, GetDeployCalldataResponse, SetChainRequest, SetChainResponse,
-    SetMarketRequest, SetMarketResponse, SetOperatorAdminRequest, SetOperatorAdminResponse,
-    SetOperatorFeeRequest, SetOperatorFeeResponse, SetTokenRequest, SetTokenResponse,
-    SetTradeContractRequest, SetTradeContractResponse, UpdateAdminRequest, UpdateAdminResponse,
-    VersionInfo,
+    GetDeployCalldataRequest, GetDeployCalldataResponse, MaintenanceNotice, MarketSchedule,
+    SetChainRequest, SetChainResponse, SetMaintenanceRequest, SetMaintenanceResponse,
+    SetMarketRequest, SetMarketResponse, SetMarketScheduleRequest, SetMarketScheduleResponse,
+    SetOperatorAdminRequest, SetOperatorAdminResponse, SetOperatorFeeRequest,
+    SetOperatorFeeResponse, SetTokenRequest, SetTokenResponse, SetTradeContractRequest,
+    SetTradeContractResponse, UpdateAdminRequest, UpdateAdminResponse, VersionInfo,
 };
-use eyre::Result;
 use tonic::Request;
 use tonic::metadata::MetadataValue;
 
+use crate::error::{AspensError, Result};
 use crate::grpc::create_channel;
 
+pub mod deploy;
+pub mod market;
+#[cfg(feature = "evm")]
+pub mod reconcile;
+pub use deploy::{DeployJournal, PendingDeploy, register_deployment};
+pub use market::{
+    FieldChange, MarketDiff, TokenRef, diff_market, market_params_from_config,
+    suggest_pair_decimals,
+};
+#[cfg(feature = "evm")]
+pub use reconcile::{CheckStatus, ReconcileReport, reconcile};
+
 /// Create an authenticated gRPC request with JWT bearer token
 fn authenticated_request<T>(jwt: &str, payload: T) -> Request<T> {
     let mut request = Request::new(payload);
@@ -161,6 +174,10 @@ pub struct CreateInstanceParams {
     pub chain_id: u64,
     /// The private key for signing (hex string without 0x prefix)
     pub privkey: String,
+    /// Whether to sign a legacy or an EIP-1559 transaction; `Auto` asks the
+    /// chain. Use [`TxType::for_network`](crate::chain::tx::TxType::for_network)
+    /// to honour the network's `ASPENS_TX_TYPE_<NETWORK>` setting.
+    pub tx_type: crate::chain::tx::TxType,
 }
 
 /// Build and sign a createInstance transaction for deploying a trading instance
@@ -168,37 +185,69 @@ pub struct CreateInstanceParams {
 /// This creates a signed transaction using pre-encoded calldata from the server.
 /// The calldata is obtained from the GetDeployCalldata RPC call.
 ///
+/// Nothing is broadcast. Send the bytes with [`broadcast_transaction`], or
+/// hand them to a relay ([`signed_tx_hash`] gives the hash to resume the
+/// deploy with). When the admin key isn't funded, [`sponsor_gas`] tops it up
+/// from a payer key first.
+///
+/// The transaction is EIP-1559 unless `params.tx_type` resolves to legacy,
+/// for chains without a base fee.
+///
 /// # Arguments
 /// * `params` - Parameters for building the transaction (includes server-provided calldata)
 ///
 /// # Returns
 /// The RLP-encoded signed transaction bytes
 pub async fn build_create_instance_tx(params: CreateInstanceParams) -> Result<Vec<u8>> {
-    use alloy::consensus::{SignableTransaction, TxEip1559, TxEnvelope};
-    use alloy::network::{EthereumWallet, TransactionBuilder, TxSigner};
-    use alloy::primitives::{Address, Bytes, TxKind, U256};
+    use alloy::signers::local::PrivateKeySigner;
+
+    let signer: PrivateKeySigner = params
+        .privkey
+        .parse()
+        .map_err(|e| AspensError::InvalidInput(Box::new(e)))?;
+    sign_create_instance_tx(&params, &signer).await
+}
+
+/// [`build_create_instance_tx`] signed by `signer` (a local key or a remote
+/// signer such as AWS KMS) instead of `params.privkey`, which is ignored.
+pub async fn build_create_instance_tx_with_wallet(
+    params: CreateInstanceParams,
+    signer: &dyn crate::AspensSigner,
+) -> Result<Vec<u8>> {
+    let signer = signer.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(
+            "deploying needs a wallet that signs transactions (a local key or a remote signer)",
+        )
+    })?;
+    sign_create_instance_tx(&params, signer.as_ref()).await
+}
+
+async fn sign_create_instance_tx(
+    params: &CreateInstanceParams,
+    signer: &(dyn alloy::network::TxSigner<alloy::primitives::Signature> + Send + Sync),
+) -> Result<Vec<u8>> {
+    use alloy::consensus::{SignableTransaction, TxEip1559, TxEnvelope, TxLegacy};
+    use alloy::network::TransactionBuilder;
+    use alloy::primitives::{Bytes, TxKind, U256};
     use alloy::providers::{Provider, ProviderBuilder};
     use alloy::rpc::types::TransactionRequest;
-    use alloy::signers::local::PrivateKeySigner;
     use url::Url;
 
-    // Parse addresses
-    let factory_addr: Address = params.factory_address.parse()?;
+    use crate::chain::tx::TxType;
 
-    // Set up the signer
-    let signer: PrivateKeySigner = params.privkey.parse()?;
-    let wallet = EthereumWallet::new(signer.clone());
+    // Parse addresses
+    let factory_addr = crate::util::parse_address(&params.factory_address)?;
     let from_address = signer.address();
 
-    // Set up the provider
+    // Set up the provider; it only reads, the transaction is signed below
     let rpc_url = Url::parse(&params.rpc_url)?;
     let provider = ProviderBuilder::new()
         .with_chain_id(params.chain_id)
-        .wallet(wallet)
         .connect_http(rpc_url);
 
-    // Get the nonce for the signing address
-    let nonce = provider.get_transaction_count(from_address).await?;
+    // Reserve the nonce for the signing address. It is kept once the
+    // transaction is signed, since the caller broadcasts it.
+    let nonce = crate::chain::tx::reserve_nonce(&provider, params.chain_id, from_address).await?;
 
     // The calldata is ABI-encoded createInstance(address, uint16)
     let calldata_bytes = Bytes::from(params.calldata.clone());
@@ -211,26 +260,41 @@ pub async fn build_create_instance_tx(params: CreateInstanceParams) -> Result<Ve
 
     // Estimate gas
     let gas_estimate = provider.estimate_gas(tx_request).await?;
-
-    // Get current gas prices
-    let fee_estimate = provider.estimate_eip1559_fees().await?;
-
-    // Build the EIP-1559 transaction
-    let mut tx = TxEip1559 {
-        chain_id: params.chain_id,
-        nonce,
-        gas_limit: gas_estimate + (gas_estimate / 10), // Add 10% buffer
-        max_fee_per_gas: fee_estimate.max_fee_per_gas,
-        max_priority_fee_per_gas: fee_estimate.max_priority_fee_per_gas,
-        to: TxKind::Call(factory_addr),
-        value: U256::ZERO,
-        access_list: Default::default(),
-        input: calldata_bytes,
+    let gas_limit = gas_estimate + (gas_estimate / 10); // Add 10% buffer
+
+    // Build and sign the transaction; chains without EIP-1559 get a legacy one
+    let signed_tx = match params.tx_type.resolve(&provider, params.chain_id).await? {
+        TxType::Legacy => {
+            let mut tx = TxLegacy {
+                chain_id: Some(params.chain_id),
+                nonce: nonce.nonce(),
+                gas_price: provider.get_gas_price().await?,
+                gas_limit,
+                to: TxKind::Call(factory_addr),
+                value: U256::ZERO,
+                input: calldata_bytes,
+            };
+            let signature = signer.sign_transaction(&mut tx).await?;
+            TxEnvelope::Legacy(tx.into_signed(signature))
+        }
+        _ => {
+            let fee_estimate = provider.estimate_eip1559_fees().await?;
+            let mut tx = TxEip1559 {
+                chain_id: params.chain_id,
+                nonce: nonce.nonce(),
+                gas_limit,
+                max_fee_per_gas: fee_estimate.max_fee_per_gas,
+                max_priority_fee_per_gas: fee_estimate.max_priority_fee_per_gas,
+                to: TxKind::Call(factory_addr),
+                value: U256::ZERO,
+                access_list: Default::default(),
+                input: calldata_bytes,
+            };
+            let signature = signer.sign_transaction(&mut tx).await?;
+            TxEnvelope::Eip1559(tx.into_signed(signature))
+        }
     };
-
-    // Sign the transaction
-    let signature = signer.sign_transaction(&mut tx).await?;
-    let signed_tx = TxEnvelope::Eip1559(tx.into_signed(signature));
+    nonce.commit();
 
     // Encode the signed transaction to RLP bytes
     use alloy::eips::eip2718::Encodable2718;
@@ -240,6 +304,31 @@ pub async fn build_create_instance_tx(params: CreateInstanceParams) -> Result<Ve
     Ok(encoded)
 }
 
+/// Build the createInstance transaction from `from` without signing it, for
+/// an admin key kept offline. Sign the result with
+/// [`UnsignedTx::sign`](crate::commands::trading::offline::UnsignedTx::sign).
+pub async fn unsigned_create_instance(
+    chain: Chain,
+    factory_address: String,
+    calldata: Vec<u8>,
+    from: String,
+) -> Result<crate::commands::trading::offline::UnsignedTx> {
+    use alloy::primitives::U256;
+
+    crate::commands::trading::offline::unsigned_call(
+        &chain,
+        crate::util::parse_address(&from)?,
+        crate::util::parse_address(&factory_address)?,
+        calldata.into(),
+        U256::ZERO,
+        format!(
+            "createInstance on {} via factory {}",
+            chain.network, factory_address
+        ),
+    )
+    .await
+}
+
 /// Broadcast a signed transaction and return the transaction hash
 ///
 /// # Arguments
@@ -259,7 +348,7 @@ pub async fn broadcast_transaction(rpc_url: String, signed_tx: Vec<u8>) -> Resul
     let pending_tx = provider
         .send_raw_transaction(&signed_tx)
         .await
-        .map_err(|e| eyre::eyre!("Failed to broadcast transaction: {}", e))?;
+        .map_err(|e| AspensError::chain(format!("Failed to broadcast transaction: {}", e)))?;
 
     let tx_hash = pending_tx.tx_hash();
 
@@ -267,6 +356,138 @@ pub async fn broadcast_transaction(rpc_url: String, signed_tx: Vec<u8>) -> Resul
     Ok(format!("{:?}", tx_hash))
 }
 
+/// Wait for a transaction from [`broadcast_transaction`] to be mined and
+/// reach the confirmations `policy` asks for, sending it again with higher
+/// fees while it is stuck (see
+/// [`crate::chain::tx::wait_for_confirmations`]). `signer` must be the
+/// transaction's sender, to sign the replacements.
+///
+/// # Returns
+/// The hash of the version that was mined, which differs from `tx_hash`
+/// when a replacement landed.
+pub async fn wait_for_broadcast(
+    rpc_url: String,
+    chain_id: u64,
+    tx_hash: String,
+    signer: &dyn crate::AspensSigner,
+    policy: crate::chain::tx::ConfirmationPolicy,
+) -> Result<String> {
+    use alloy::network::EthereumWallet;
+    use alloy::providers::ProviderBuilder;
+    use url::Url;
+
+    use crate::chain::tx::{StuckPolicy, wait_for_confirmations};
+
+    let hash = tx_hash.parse().map_err(|e| {
+        AspensError::invalid_input(format!("invalid transaction hash '{}': {}", tx_hash, e))
+    })?;
+    let signer = signer.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(
+            "replacing a stuck transaction needs a wallet that signs transactions",
+        )
+    })?;
+    let provider = ProviderBuilder::new()
+        .with_chain_id(chain_id)
+        .wallet(EthereumWallet::new(signer))
+        .connect_http(Url::parse(&rpc_url)?);
+    let receipt = wait_for_confirmations(&provider, hash, StuckPolicy::from_env(), policy).await?;
+    Ok(format!("{:?}", receipt.transaction_hash))
+}
+
+/// Hash of an RLP-encoded signed transaction: the hash
+/// [`broadcast_transaction`] reports once it is sent.
+pub fn signed_tx_hash(signed_tx: &[u8]) -> String {
+    format!("{:?}", alloy::primitives::keccak256(signed_tx))
+}
+
+/// Fund the gas of a signed transaction on `network` from a separate payer
+/// wallet.
+///
+/// EVM charges gas to the account that signs a transaction, and
+/// `createInstance` has to come from the admin, so the payer can't pay
+/// directly. Instead it transfers the shortfall between the signer's balance
+/// and the transaction's worst-case cost (`gas_limit * max_fee_per_gas +
+/// value`), and waits for the transfer to be confirmed as `policy` asks, so
+/// the admin key never has to hold more than one deploy's worth.
+///
+/// # Returns
+/// The transfer's hash, or `None` if the signer already had enough.
+pub async fn sponsor_gas(
+    rpc_url: String,
+    network: String,
+    signed_tx: Vec<u8>,
+    payer: &dyn crate::AspensSigner,
+    policy: crate::chain::tx::ConfirmationPolicy,
+) -> Result<Option<String>> {
+    use alloy::consensus::{Transaction, TxEnvelope};
+    use alloy::eips::eip2718::Decodable2718;
+    use alloy::network::{EthereumWallet, TransactionBuilder, TxSigner};
+    use alloy::primitives::U256;
+    use alloy::providers::{Provider, ProviderBuilder};
+    use alloy::rpc::types::TransactionRequest;
+    use url::Url;
+
+    use crate::chain::tx::{StuckPolicy, legacy_gas_price, reserve_nonce, wait_for_confirmations};
+
+    let tx = TxEnvelope::decode_2718(&mut signed_tx.as_slice())
+        .map_err(|e| AspensError::invalid_input(format!("invalid signed transaction: {}", e)))?;
+    let signer = tx
+        .signature()
+        .recover_address_from_prehash(&tx.signature_hash())
+        .map_err(|e| {
+            AspensError::invalid_input(format!("can't recover the transaction's signer: {}", e))
+        })?;
+    let max_cost = U256::from(tx.gas_limit()) * U256::from(tx.max_fee_per_gas()) + tx.value();
+
+    let payer = payer.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input("the gas payer needs a wallet that signs transactions")
+    })?;
+    let payer_address = payer.address();
+    let provider = ProviderBuilder::new()
+        .wallet(EthereumWallet::new(payer))
+        .connect_http(Url::parse(&rpc_url)?);
+
+    let balance = provider.get_balance(signer).await?;
+    if balance >= max_cost {
+        return Ok(None);
+    }
+    let shortfall = max_cost - balance;
+    tracing::info!("Funding {} with {} wei for gas", signer, shortfall);
+    let chain_id = match tx.chain_id() {
+        Some(chain_id) => chain_id,
+        None => provider.get_chain_id().await?,
+    };
+    let gas_price = legacy_gas_price(&provider, &network, chain_id).await?;
+    let nonce = reserve_nonce(&provider, chain_id, payer_address).await?;
+    let mut topup = TransactionRequest::default()
+        .with_to(signer)
+        .with_value(shortfall)
+        .with_chain_id(chain_id)
+        .with_nonce(nonce.nonce());
+    if let Some(price) = gas_price {
+        topup.set_gas_price(price);
+    }
+    let pending = provider
+        .send_transaction(topup)
+        .await
+        .map_err(|e| AspensError::chain(format!("Failed to send gas top-up: {}", e)))?;
+    nonce.commit();
+    let receipt = wait_for_confirmations(
+        &provider,
+        *pending.tx_hash(),
+        StuckPolicy::from_env(),
+        policy,
+    )
+    .await?;
+    if !receipt.status() {
+        return Err(AspensError::chain(format!(
+            "gas top-up {:?} reverted",
+            receipt.transaction_hash
+        )));
+    }
+    Ok(Some(format!("{:?}", receipt.transaction_hash)))
+}
+
 /// Set a trade contract on a chain (requires auth)
 ///
 /// # Arguments
@@ -382,6 +603,67 @@ pub async fn delete_trade_contract(
     Ok(response.into_inner())
 }
 
+// ============================================================================
+// Maintenance Operations
+// ============================================================================
+
+/// Set the operator message / maintenance window served in the config (requires auth)
+///
+/// Clients that read the config surface it: the trader CLI prints it as a
+/// banner and [`AspensClient`](crate::AspensClient) passes it to its
+/// maintenance callback.
+///
+/// # Arguments
+/// * `url` - The Aspens stack gRPC URL
+/// * `jwt` - Valid JWT token
+/// * `notice` - Message and optional window (unix seconds)
+pub async fn set_maintenance(
+    url: String,
+    jwt: String,
+    notice: MaintenanceNotice,
+) -> Result<SetMaintenanceResponse> {
+    if notice.message.trim().is_empty() {
+        return Err(AspensError::invalid_input(
+            "maintenance message must not be empty",
+        ));
+    }
+    if let (Some(start), Some(end)) = (notice.starts_at, notice.ends_at)
+        && end <= start
+    {
+        return Err(AspensError::invalid_input(format!(
+            "maintenance window must end after it starts ({} <= {})",
+            end, start
+        )));
+    }
+    let channel = create_channel(&url).await?;
+    let mut client = ConfigServiceClient::new(channel);
+
+    let request = authenticated_request(
+        &jwt,
+        SetMaintenanceRequest {
+            notice: Some(notice),
+        },
+    );
+    let response = client.set_maintenance(request).await?;
+
+    Ok(response.into_inner())
+}
+
+/// Clear the operator message / maintenance window (requires auth)
+///
+/// # Arguments
+/// * `url` - The Aspens stack gRPC URL
+/// * `jwt` - Valid JWT token
+pub async fn clear_maintenance(url: String, jwt: String) -> Result<SetMaintenanceResponse> {
+    let channel = create_channel(&url).await?;
+    let mut client = ConfigServiceClient::new(channel);
+
+    let request = authenticated_request(&jwt, SetMaintenanceRequest { notice: None });
+    let response = client.set_maintenance(request).await?;
+
+    Ok(response.into_inner())
+}
+
 // ============================================================================
 // Chain Operations
 // ============================================================================
@@ -562,6 +844,40 @@ pub async fn delete_market(
     Ok(response.into_inner())
 }
 
+/// Set or clear a market's trading hours (requires auth)
+///
+/// Clients refuse to submit orders while the market is closed; see
+/// [`Market::is_open`](config_pb::Market::is_open).
+///
+/// # Arguments
+/// * `url` - The Aspens stack gRPC URL
+/// * `jwt` - Valid JWT token
+/// * `market_id` - Market ID to update
+/// * `schedule` - Trading windows and blocks; `None` makes the market always open
+pub async fn set_market_schedule(
+    url: String,
+    jwt: String,
+    market_id: String,
+    schedule: Option<MarketSchedule>,
+) -> Result<SetMarketScheduleResponse> {
+    if let Some(schedule) = &schedule {
+        schedule.validate()?;
+    }
+    let channel = create_channel(&url).await?;
+    let mut client = ConfigServiceClient::new(channel);
+
+    let request = authenticated_request(
+        &jwt,
+        SetMarketScheduleRequest {
+            market_id,
+            schedule,
+        },
+    );
+    let response = client.set_market_schedule(request).await?;
+
+    Ok(response.into_inner())
+}
+
 // ============================================================================
 // Read-Only Operations (no auth required)
 // ============================================================================
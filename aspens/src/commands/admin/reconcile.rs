@@ -0,0 +1,752 @@
+//! Config-vs-chain drift detection.
+//!
+//! The stack's configuration records, per chain, where the trade contract,
+//! factory and tokens live and which address signs for the instance.
+//! Nothing keeps that in step with the chains themselves: a redeployed
+//! contract, a token registered with the wrong decimals or a signer that
+//! ran out of gas all look fine in `aspens-admin status`. [`reconcile`]
+//! checks every configured chain against what its RPC endpoint reports and
+//! collects the results into one [`ReconcileReport`], each problem paired
+//! with the admin command that fixes it.
+//!
+//! All checks are read-only. The server masks `rpc_url`, so each chain is
+//! reached through [`resolve_rpc_url`]; a chain with no usable endpoint is
+//! reported as skipped rather than failed.
+
+use std::fmt;
+
+use alloy::primitives::Address;
+use alloy::providers::{Provider, ProviderBuilder};
+use serde::Serialize;
+use url::Url;
+
+use super::MidribFactory;
+use super::config_pb::{Chain, GetConfigResponse};
+use crate::chain_client::{ARCH_SOLANA, resolve_rpc_url, rpc_override_env_key};
+use crate::commands::config::{get_config, get_signer_public_key};
+use crate::error::{AspensError, Result};
+use crate::evm::is_native_token;
+use crate::evm::rpc::IERC20;
+
+/// Signer gas balance (wei) below which an EVM chain is flagged: 0.001.
+pub const MIN_EVM_SIGNER_BALANCE: u128 = 1_000_000_000_000_000;
+/// Signer balance (lamports) below which a Solana chain is flagged: 0.01 SOL.
+pub const MIN_SOLANA_SIGNER_BALANCE: u128 = 10_000_000;
+
+/// Outcome of one check, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Config and chain agree.
+    Ok,
+    /// Not checked (no RPC endpoint, or nothing to compare).
+    Skipped,
+    /// Works today but worth fixing.
+    Warning,
+    /// Config and chain disagree.
+    Failed,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ok => "ok",
+            Self::Skipped => "skipped",
+            Self::Warning => "warning",
+            Self::Failed => "FAILED",
+        })
+    }
+}
+
+/// One config-vs-chain comparison.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    /// What was checked, e.g. `trade contract` or `token USDC`.
+    pub name: String,
+    /// How it came out.
+    pub status: CheckStatus,
+    /// What was found.
+    pub detail: String,
+    /// How to fix it, for warnings and failures.
+    pub remediation: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Ok, detail, None)
+    }
+
+    fn skipped(name: impl Into<String>, detail: impl Into<String>, fix: Option<String>) -> Self {
+        Self::new(name, CheckStatus::Skipped, detail, fix)
+    }
+
+    fn warning(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warning, detail, Some(fix.into()))
+    }
+
+    fn failed(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Failed, detail, Some(fix.into()))
+    }
+
+    fn new(
+        name: impl Into<String>,
+        status: CheckStatus,
+        detail: impl Into<String>,
+        remediation: Option<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            remediation,
+        }
+    }
+
+    /// A failed check for an RPC call that errored.
+    fn rpc_error(name: impl Into<String>, network: &str, e: impl fmt::Display) -> Self {
+        Self::failed(
+            name,
+            format!("RPC error: {e}"),
+            format!(
+                "check that the RPC endpoint for '{network}' is reachable (override it with {})",
+                rpc_override_env_key(network)
+            ),
+        )
+    }
+}
+
+/// Every check run against one chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainReport {
+    /// Network name from the config, e.g. `base-sepolia`.
+    pub network: String,
+    /// Chain architecture from the config.
+    pub architecture: String,
+    /// The checks, in the order they ran.
+    pub checks: Vec<Check>,
+}
+
+impl ChainReport {
+    /// The worst status among this chain's checks.
+    pub fn status(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Ok)
+    }
+}
+
+/// The result of [`reconcile`]: one [`ChainReport`] per configured chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReconcileReport {
+    /// One report per chain, in config order.
+    pub chains: Vec<ChainReport>,
+}
+
+impl ReconcileReport {
+    /// How many checks across all chains came out as `status`.
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.chains
+            .iter()
+            .flat_map(|c| &c.checks)
+            .filter(|c| c.status == status)
+            .count()
+    }
+
+    /// `true` if no check failed. Warnings and skips don't count.
+    pub fn is_healthy(&self) -> bool {
+        self.count(CheckStatus::Failed) == 0
+    }
+}
+
+impl fmt::Display for ReconcileReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut fixes = Vec::new();
+        for chain in &self.chains {
+            writeln!(
+                f,
+                "── {} ({}) — {}",
+                chain.network,
+                chain.architecture,
+                chain.status()
+            )?;
+            for check in &chain.checks {
+                writeln!(
+                    f,
+                    "  {:<8} {}: {}",
+                    check.status.to_string(),
+                    check.name,
+                    check.detail
+                )?;
+                if check.status >= CheckStatus::Warning
+                    && let Some(fix) = &check.remediation
+                {
+                    fixes.push(format!("{} / {}: {}", chain.network, check.name, fix));
+                }
+            }
+            writeln!(f)?;
+        }
+
+        if !fixes.is_empty() {
+            writeln!(f, "Remediation:")?;
+            for fix in &fixes {
+                writeln!(f, "  - {fix}")?;
+            }
+            writeln!(f)?;
+        }
+
+        write!(
+            f,
+            "{} chain(s) checked: {} failed, {} warning(s), {} skipped",
+            self.chains.len(),
+            self.count(CheckStatus::Failed),
+            self.count(CheckStatus::Warning),
+            self.count(CheckStatus::Skipped)
+        )
+    }
+}
+
+/// Cross-check the configuration of the stack at `url` against every
+/// configured chain.
+///
+/// Only fails if the configuration itself can't be fetched; everything
+/// found on the chains ends up in the report.
+pub async fn reconcile(url: String) -> Result<ReconcileReport> {
+    let config = get_config(url.clone()).await?;
+    // Without the signer service the funding check falls back to the
+    // configured instance signer address.
+    let signer_keys = match get_signer_public_key(url, None).await {
+        Ok(response) => Some(response.chain_keys),
+        Err(e) => {
+            tracing::warn!("could not fetch signer public keys: {}", e);
+            None
+        }
+    };
+    reconcile_config(&config, |network| {
+        signer_keys
+            .as_ref()
+            .and_then(|keys| keys.get(network))
+            .map(|k| k.public_key.clone())
+    })
+    .await
+}
+
+/// [`reconcile`] over an already-fetched configuration. `signer_key` maps a
+/// network to the signer service's current address for it, if known.
+pub async fn reconcile_config(
+    config: &GetConfigResponse,
+    signer_key: impl Fn(&str) -> Option<String>,
+) -> Result<ReconcileReport> {
+    let configuration = config.config.as_ref().ok_or_else(|| {
+        AspensError::not_found(
+            "the server returned no configuration; register a chain with `aspens-admin set-chain`",
+        )
+    })?;
+
+    let mut report = ReconcileReport::default();
+    for chain in &configuration.chains {
+        let signer = signer_key(&chain.network);
+        report.chains.push(reconcile_chain(chain, signer).await);
+    }
+    Ok(report)
+}
+
+/// Run every check against one chain.
+async fn reconcile_chain(chain: &Chain, signer_key: Option<String>) -> ChainReport {
+    let mut checks = vec![check_signer_address(chain, signer_key.as_deref())];
+    // Fund whichever key actually signs; the configured one if unknown.
+    let signer = signer_key.unwrap_or_else(|| chain.instance_signer_address.clone());
+
+    match resolve_rpc_url(&chain.network, &chain.rpc_url) {
+        Err(e) => checks.push(Check::skipped(
+            "rpc endpoint",
+            e.to_string(),
+            Some(format!(
+                "set {} to check this chain",
+                rpc_override_env_key(&chain.network)
+            )),
+        )),
+        Ok(rpc_url) if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) => {
+            checks.extend(solana::checks(chain, &rpc_url, &signer).await);
+        }
+        Ok(rpc_url) => checks.extend(evm_checks(chain, &rpc_url, &signer).await),
+    }
+
+    ChainReport {
+        network: chain.network.clone(),
+        architecture: chain.architecture.clone(),
+        checks,
+    }
+}
+
+/// The configured instance signer against the signer service's key.
+fn check_signer_address(chain: &Chain, signer_key: Option<&str>) -> Check {
+    const NAME: &str = "signer address";
+    let configured = chain.instance_signer_address.trim();
+    match signer_key {
+        _ if configured.is_empty() => Check::failed(
+            NAME,
+            "no instance signer address configured",
+            "re-run `aspens-admin set-chain` with --instance-signer-address",
+        ),
+        None => Check::skipped(
+            NAME,
+            format!("{configured} (signer service key unavailable)"),
+            None,
+        ),
+        Some(key) if key.eq_ignore_ascii_case(configured) => Check::ok(NAME, configured),
+        Some(key) => Check::failed(
+            NAME,
+            format!("config has {configured}, signer service signs with {key}"),
+            format!("re-run `aspens-admin set-chain` with --instance-signer-address {key}"),
+        ),
+    }
+}
+
+/// Verdict on a signer balance against `min`, in the chain's base unit.
+fn signer_funding(balance: u128, min: u128, signer: &str, unit: &str) -> Check {
+    const NAME: &str = "signer funded";
+    let detail = format!("{signer} holds {balance} {unit}");
+    let fix = format!("send gas to {signer} (at least {min} {unit})");
+    if balance == 0 {
+        Check::failed(NAME, detail, fix)
+    } else if balance < min {
+        Check::warning(NAME, detail, fix)
+    } else {
+        Check::ok(NAME, detail)
+    }
+}
+
+/// Checks for EVM and Hedera chains.
+async fn evm_checks(chain: &Chain, rpc_url: &str, signer: &str) -> Vec<Check> {
+    let network = chain.network.as_str();
+    let url = match Url::parse(rpc_url) {
+        Ok(url) => url,
+        Err(e) => return vec![Check::rpc_error("rpc endpoint", network, e)],
+    };
+    let provider = ProviderBuilder::new().connect_http(url);
+    let mut checks = Vec::new();
+
+    match provider.get_chain_id().await {
+        Ok(id) if id == u64::from(chain.chain_id) => {
+            checks.push(Check::ok("chain id", id.to_string()))
+        }
+        Ok(id) => {
+            // Every other check would be against the wrong chain.
+            checks.push(Check::failed(
+                "chain id",
+                format!("config says {}, RPC reports {id}", chain.chain_id),
+                format!(
+                    "point {} at the right network, or fix the chain id with `aspens-admin set-chain`",
+                    rpc_override_env_key(network)
+                ),
+            ));
+            return checks;
+        }
+        Err(e) => {
+            checks.push(Check::rpc_error("chain id", network, e));
+            return checks;
+        }
+    }
+
+    let has_code = async |address: Address| {
+        provider
+            .get_code_at(address)
+            .await
+            .map(|code| !code.is_empty())
+    };
+
+    // Trade contract.
+    let instance = chain
+        .trade_contract
+        .as_ref()
+        .map(|tc| tc.address.trim())
+        .filter(|a| !a.is_empty());
+    let instance = match instance.map(|a| (a, a.parse::<Address>())) {
+        None => {
+            checks.push(Check::failed(
+                "trade contract",
+                "none configured",
+                "deploy one with `aspens-admin deploy-contract`",
+            ));
+            None
+        }
+        Some((raw, Err(e))) => {
+            checks.push(Check::failed(
+                "trade contract",
+                format!("'{raw}' is not an address: {e}"),
+                "re-register it with `aspens-admin set-trade-contract`",
+            ));
+            None
+        }
+        Some((_, Ok(address))) => match has_code(address).await {
+            Ok(true) => {
+                checks.push(Check::ok("trade contract", format!("{address} has code")));
+                Some(address)
+            }
+            Ok(false) => {
+                checks.push(Check::failed(
+                    "trade contract",
+                    format!("no code at {address}"),
+                    "redeploy with `aspens-admin deploy-contract`, or register the live \
+                     instance with `aspens-admin set-trade-contract`",
+                ));
+                None
+            }
+            Err(e) => {
+                checks.push(Check::rpc_error("trade contract", network, e));
+                None
+            }
+        },
+    };
+
+    // Factory, and that it deployed the instance for the configured signer.
+    let factory = chain.factory_address.trim();
+    match factory.parse::<Address>() {
+        _ if factory.is_empty() => checks.push(Check::warning(
+            "factory",
+            "none configured",
+            "set the factory address with `aspens-admin set-chain --factory-address`",
+        )),
+        Err(e) => checks.push(Check::failed(
+            "factory",
+            format!("'{factory}' is not an address: {e}"),
+            "fix it with `aspens-admin set-chain --factory-address`",
+        )),
+        Ok(address) => match has_code(address).await {
+            Ok(false) => checks.push(Check::failed(
+                "factory",
+                format!("no code at {address}"),
+                "fix it with `aspens-admin set-chain --factory-address`",
+            )),
+            Err(e) => checks.push(Check::rpc_error("factory", network, e)),
+            Ok(true) => match instance {
+                None => checks.push(Check::ok("factory", format!("{address} has code"))),
+                Some(instance) => {
+                    let factory_contract = MidribFactory::new(address, &provider);
+                    match factory_contract
+                        .getTradingInstanceSigner(instance)
+                        .call()
+                        .await
+                    {
+                        Ok(owner) if owner == Address::ZERO => checks.push(Check::failed(
+                            "factory",
+                            format!("{address} did not deploy the trade contract {instance}"),
+                            "register the instance deployed by this factory with \
+                             `aspens-admin set-trade-contract`, or fix the factory with \
+                             `aspens-admin set-chain --factory-address`",
+                        )),
+                        Ok(owner)
+                            if owner
+                                .to_string()
+                                .eq_ignore_ascii_case(chain.instance_signer_address.trim()) =>
+                        {
+                            checks.push(Check::ok(
+                                "factory",
+                                format!("{address} deployed {instance} for {owner}"),
+                            ))
+                        }
+                        Ok(owner) => checks.push(Check::failed(
+                            "factory",
+                            format!(
+                                "{instance} was deployed for signer {owner}, config has {}",
+                                chain.instance_signer_address
+                            ),
+                            "deploy an instance for the configured signer with \
+                             `aspens-admin deploy-contract`",
+                        )),
+                        Err(e) => checks.push(Check::rpc_error("factory", network, e)),
+                    }
+                }
+            },
+        },
+    }
+
+    // Tokens, in a stable order.
+    let mut tokens: Vec<_> = chain.tokens.iter().collect();
+    tokens.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, token) in tokens {
+        let name = format!("token {key}");
+        let fix = format!(
+            "re-register it with `aspens-admin set-token --network {network} --symbol {key}`"
+        );
+        if is_native_token(&token.address) {
+            checks.push(Check::ok(name, "native asset"));
+            continue;
+        }
+        let address = match token.address.trim().parse::<Address>() {
+            Ok(address) => address,
+            Err(e) => {
+                checks.push(Check::failed(
+                    name,
+                    format!("'{}' is not an address: {e}", token.address),
+                    fix,
+                ));
+                continue;
+            }
+        };
+        match has_code(address).await {
+            Ok(true) => {}
+            Ok(false) => {
+                checks.push(Check::failed(name, format!("no code at {address}"), fix));
+                continue;
+            }
+            Err(e) => {
+                checks.push(Check::rpc_error(name, network, e));
+                continue;
+            }
+        }
+
+        let erc20 = IERC20::new(address, &provider);
+        let (symbol, decimals) = (erc20.symbol(), erc20.decimals());
+        let (symbol, decimals) = tokio::join!(symbol.call(), decimals.call());
+        let mut problems = Vec::new();
+        match decimals {
+            Ok(d) if u32::from(d) == token.decimals => {}
+            Ok(d) => problems.push(format!(
+                "config has {} decimals, chain has {d}",
+                token.decimals
+            )),
+            Err(e) => problems.push(format!("decimals() failed: {e}")),
+        }
+        match symbol {
+            Ok(s) if s == token.symbol => {}
+            // Some tokens don't implement the optional symbol(); not fatal.
+            Ok(s) => problems.push(format!("config has symbol {}, chain has {s}", token.symbol)),
+            Err(_) => {}
+        }
+        let check = if problems.is_empty() {
+            Check::ok(name, format!("{address}: {} decimals", token.decimals))
+        } else {
+            Check::failed(name, format!("{address}: {}", problems.join("; ")), fix)
+        };
+        checks.push(check);
+    }
+
+    // Signer gas.
+    match signer.parse::<Address>() {
+        Ok(address) => match provider.get_balance(address).await {
+            Ok(balance) => checks.push(signer_funding(
+                balance.try_into().unwrap_or(u128::MAX),
+                MIN_EVM_SIGNER_BALANCE,
+                signer,
+                "wei",
+            )),
+            Err(e) => checks.push(Check::rpc_error("signer funded", network, e)),
+        },
+        Err(_) => checks.push(Check::skipped(
+            "signer funded",
+            "no valid signer address to check",
+            None,
+        )),
+    }
+
+    checks
+}
+
+#[cfg(feature = "solana")]
+mod solana {
+    use std::str::FromStr;
+
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+    use crate::chain_client::ChainClient;
+
+    /// Checks for Solana chains. Solana has no factory contract and SPL
+    /// mints carry no symbol, so this covers the program, mint decimals
+    /// and the signer's lamports.
+    pub(super) async fn checks(chain: &Chain, rpc_url: &str, signer: &str) -> Vec<Check> {
+        let network = chain.network.as_str();
+        let client = RpcClient::new(rpc_url.to_string());
+        let mut checks = Vec::new();
+
+        let program = chain
+            .trade_contract
+            .as_ref()
+            .map(|tc| tc.address.trim())
+            .filter(|a| !a.is_empty());
+        match program.map(|p| (p, Pubkey::from_str(p))) {
+            None => checks.push(Check::failed(
+                "trade contract",
+                "no program configured",
+                "register the program with `aspens-admin set-trade-contract`",
+            )),
+            Some((raw, Err(e))) => checks.push(Check::failed(
+                "trade contract",
+                format!("'{raw}' is not a pubkey: {e}"),
+                "re-register it with `aspens-admin set-trade-contract`",
+            )),
+            Some((_, Ok(pubkey))) => match client.get_account(&pubkey).await {
+                Ok(account) if account.executable => checks.push(Check::ok(
+                    "trade contract",
+                    format!("{pubkey} is a program"),
+                )),
+                Ok(_) => checks.push(Check::failed(
+                    "trade contract",
+                    format!("{pubkey} exists but is not executable"),
+                    "register the program ID with `aspens-admin set-trade-contract`",
+                )),
+                Err(e) => checks.push(Check::failed(
+                    "trade contract",
+                    format!("no program at {pubkey}: {e}"),
+                    "deploy the program, then register it with `aspens-admin set-trade-contract`",
+                )),
+            },
+        }
+
+        let mut tokens: Vec<_> = chain.tokens.iter().collect();
+        tokens.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, token) in tokens {
+            let name = format!("token {key}");
+            let fix = format!(
+                "re-register it with `aspens-admin set-token --network {network} --symbol {key}`"
+            );
+            let mint = match Pubkey::from_str(token.address.trim()) {
+                Ok(mint) => mint,
+                Err(e) => {
+                    checks.push(Check::failed(
+                        name,
+                        format!("'{}' is not a pubkey: {e}", token.address),
+                        fix,
+                    ));
+                    continue;
+                }
+            };
+            match client.get_token_supply(&mint).await {
+                Ok(supply) if u32::from(supply.decimals) == token.decimals => checks.push(
+                    Check::ok(name, format!("{mint}: {} decimals", token.decimals)),
+                ),
+                Ok(supply) => checks.push(Check::failed(
+                    name,
+                    format!(
+                        "{mint}: config has {} decimals, chain has {}",
+                        token.decimals, supply.decimals
+                    ),
+                    fix,
+                )),
+                Err(e) => checks.push(Check::failed(
+                    name,
+                    format!("{mint} is not a token mint: {e}"),
+                    fix,
+                )),
+            }
+        }
+
+        let chain_client = ChainClient::Solana { client };
+        match chain_client.native_balance(signer).await {
+            Ok(lamports) => checks.push(signer_funding(
+                lamports,
+                MIN_SOLANA_SIGNER_BALANCE,
+                signer,
+                "lamports",
+            )),
+            Err(e) => checks.push(Check::rpc_error("signer funded", network, e)),
+        }
+
+        checks
+    }
+}
+
+#[cfg(not(feature = "solana"))]
+mod solana {
+    use super::*;
+
+    pub(super) async fn checks(chain: &Chain, _rpc_url: &str, _signer: &str) -> Vec<Check> {
+        vec![Check::skipped(
+            "chain",
+            format!(
+                "'{}' is Solana but the `solana` feature is disabled",
+                chain.network
+            ),
+            None,
+        )]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(signer: &str) -> Chain {
+        Chain {
+            network: "anvil-1".into(),
+            architecture: "EVM".into(),
+            instance_signer_address: signer.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn signer_address_drift() {
+        let a = "0x1111111111111111111111111111111111111111";
+        let b = "0x2222222222222222222222222222222222222222";
+        assert_eq!(
+            check_signer_address(&chain(a), Some(&a.to_uppercase())).status,
+            CheckStatus::Ok
+        );
+        let drift = check_signer_address(&chain(a), Some(b));
+        assert_eq!(drift.status, CheckStatus::Failed);
+        assert!(drift.remediation.unwrap().contains(b));
+        assert_eq!(
+            check_signer_address(&chain(a), None).status,
+            CheckStatus::Skipped
+        );
+        assert_eq!(
+            check_signer_address(&chain(""), Some(b)).status,
+            CheckStatus::Failed
+        );
+    }
+
+    #[test]
+    fn signer_funding_thresholds() {
+        assert_eq!(
+            signer_funding(0, 10, "s", "wei").status,
+            CheckStatus::Failed
+        );
+        assert_eq!(
+            signer_funding(5, 10, "s", "wei").status,
+            CheckStatus::Warning
+        );
+        assert_eq!(signer_funding(10, 10, "s", "wei").status, CheckStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn unreachable_chain_is_skipped_not_failed() {
+        // A masked rpc_url and no override: nothing to check against.
+        let mut chain = chain("0x1111111111111111111111111111111111111111");
+        chain.network = "reconcile-test-no-rpc".into();
+        chain.rpc_url = "***".into();
+        let report = ReconcileReport {
+            chains: vec![reconcile_chain(&chain, None).await],
+        };
+        assert!(report.is_healthy());
+        assert_eq!(report.count(CheckStatus::Skipped), 2);
+        assert_eq!(report.chains[0].status(), CheckStatus::Skipped);
+
+        let text = report.to_string();
+        assert!(text.contains("ASPENS_RPC_URL_RECONCILE_TEST_NO_RPC"));
+        assert!(text.ends_with("1 chain(s) checked: 0 failed, 0 warning(s), 2 skipped"));
+    }
+
+    #[test]
+    fn report_lists_remediations_for_problems_only() {
+        let report = ReconcileReport {
+            chains: vec![ChainReport {
+                network: "base-sepolia".into(),
+                architecture: "EVM".into(),
+                checks: vec![
+                    Check::ok("chain id", "84532"),
+                    Check::failed("token USDC", "decimals differ", "fix the decimals"),
+                ],
+            }],
+        };
+        assert!(!report.is_healthy());
+        assert_eq!(report.chains[0].status(), CheckStatus::Failed);
+        let text = report.to_string();
+        assert!(text.contains("Remediation:\n  - base-sepolia / token USDC: fix the decimals"));
+        assert!(!text.contains("chain id: 84532\n  -"));
+    }
+}
@@ -10,14 +10,14 @@ pub mod auth_pb {
 }
 
 use alloy::primitives::{Address, B256, U256, keccak256};
-use alloy::signers::{Signer, local::PrivateKeySigner};
 use auth_pb::auth_service_client::AuthServiceClient;
 use auth_pb::{AuthRequest, AuthResponse, InitializeAdminRequest, InitializeAdminResponse};
-use eyre::{Result, eyre};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::error::{AspensError, Result};
 use crate::grpc::create_channel;
-use crate::wallet::{CurveType, Wallet};
+use crate::signer::AspensSigner;
+use crate::wallet::CurveType;
 
 /// EIP-712 domain separator for Arborter authentication
 const EIP712_DOMAIN_NAME: &str = "Arborter";
@@ -87,23 +87,23 @@ pub async fn initialize_admin(url: String, address: String) -> Result<AuthToken>
 /// * `chain_id` - The chain ID for EIP-712 domain (EVM only, defaults to 1)
 pub async fn authenticate_with_wallet(
     url: String,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     chain_id: Option<u64>,
 ) -> Result<AuthToken> {
     let address_str = wallet.address();
 
     // Generate timestamp and nonce
-    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| AspensError::other(format!("system clock is before the Unix epoch: {}", e)))?
+        .as_secs();
     let nonce = generate_nonce();
 
     let signature = match wallet.curve() {
         CurveType::Secp256k1 => {
             // EIP-712 path
-            let evm_signer = wallet
-                .as_evm()
-                .ok_or_else(|| eyre!("expected EVM wallet"))?;
-            let address: Address = address_str.parse()?;
-            sign_auth_message(evm_signer, address, timestamp, &nonce, chain_id).await?
+            let address = crate::util::parse_address(&address_str)?;
+            sign_auth_message(wallet, address, timestamp, &nonce, chain_id).await?
         }
         CurveType::Ed25519 => {
             // Solana path: sign canonical message bytes
@@ -151,34 +151,74 @@ fn generate_nonce() -> String {
 /// The typed data structure matches the server's expected format:
 /// - Domain: { name: "Aspens", version: "1", chainId: <chain_id> }
 /// - Message: { address, timestamp, nonce }
+///
+/// A Ledger signs the typed data it can display; other signers sign the
+/// digest.
 async fn sign_auth_message(
-    signer: &PrivateKeySigner,
+    signer: &dyn AspensSigner,
     address: Address,
     timestamp: u64,
     nonce: &str,
     chain_id: Option<u64>,
 ) -> Result<String> {
-    let chain_id = chain_id.unwrap_or(1);
+    #[cfg(feature = "ledger")]
+    if let Some(ledger) = signer.as_ledger() {
+        let request = typed::AuthRequest {
+            address,
+            timestamp,
+            nonce: nonce.to_string(),
+        };
+        let domain = auth_domain(chain_id.unwrap_or(1));
+        let signature = ledger.sign_typed_data(&request, &domain).await?;
+        return Ok(format!("0x{}", hex::encode(signature.as_bytes())));
+    }
 
-    // Compute domain separator
-    let domain_separator = compute_domain_separator(chain_id);
+    let digest = auth_signing_digest(address, timestamp, nonce, chain_id.unwrap_or(1));
+
+    // Sign the digest
+    let signature = signer.sign_eip712_digest(digest).await?;
+
+    // Return as hex string with 0x prefix
+    Ok(format!("0x{}", hex::encode(signature)))
+}
 
-    // Compute struct hash for the authentication message
+/// The EIP-712 digest signed for an authentication request:
+/// `keccak256("\x19\x01" || domainSeparator || structHash)`.
+fn auth_signing_digest(address: Address, timestamp: u64, nonce: &str, chain_id: u64) -> B256 {
+    let domain_separator = compute_domain_separator(chain_id);
     let struct_hash = compute_auth_struct_hash(address, timestamp, nonce);
 
-    // Compute final EIP-712 hash: keccak256("\x19\x01" || domainSeparator || structHash)
     let mut digest_input = Vec::with_capacity(66);
     digest_input.extend_from_slice(&[0x19, 0x01]);
     digest_input.extend_from_slice(domain_separator.as_slice());
     digest_input.extend_from_slice(struct_hash.as_slice());
 
-    let digest = keccak256(&digest_input);
+    keccak256(&digest_input)
+}
 
-    // Sign the digest
-    let signature = signer.sign_hash(&digest).await?;
+/// The authentication message as a typed struct, for signers that sign
+/// typed data rather than a digest. Hashes to [`auth_signing_digest`].
+#[cfg(any(feature = "ledger", test))]
+mod typed {
+    alloy::sol! {
+        struct AuthRequest {
+            address address;
+            uint64 timestamp;
+            string nonce;
+        }
+    }
+}
 
-    // Return as hex string with 0x prefix
-    Ok(format!("0x{}", hex::encode(signature.as_bytes())))
+/// The EIP-712 domain [`compute_domain_separator`] hashes.
+#[cfg(any(feature = "ledger", test))]
+fn auth_domain(chain_id: u64) -> alloy::sol_types::Eip712Domain {
+    alloy::sol_types::Eip712Domain::new(
+        Some(EIP712_DOMAIN_NAME.into()),
+        Some(EIP712_DOMAIN_VERSION.into()),
+        Some(U256::from(chain_id)),
+        None,
+        None,
+    )
 }
 
 /// Compute EIP-712 domain separator
@@ -275,4 +315,96 @@ mod tests {
         // Token expiring in 10 seconds should be invalid (30 second buffer)
         assert!(!is_token_valid(now + 10));
     }
+
+    mod eip712 {
+        //! Cross-checks the hand-rolled EIP-712 encoding against alloy's:
+        //! a drift here makes every admin login fail signature recovery.
+
+        use super::*;
+        use alloy::signers::{Signer, local::PrivateKeySigner};
+        use alloy_sol_types::{SolStruct, SolValue};
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn domain_separator_matches_alloy(chain_id: u64) {
+                prop_assert_eq!(
+                    compute_domain_separator(chain_id),
+                    auth_domain(chain_id).separator()
+                );
+            }
+
+            #[test]
+            fn struct_hash_matches_abi_encoding(
+                address: [u8; 20],
+                timestamp: u64,
+                nonce in "\\PC{0,40}",
+            ) {
+                let address = Address::from(address);
+                let type_hash =
+                    keccak256(b"AuthRequest(address address,uint64 timestamp,string nonce)");
+                let encoded =
+                    (type_hash, address, timestamp, keccak256(nonce.as_bytes())).abi_encode();
+                prop_assert_eq!(
+                    compute_auth_struct_hash(address, timestamp, &nonce),
+                    keccak256(encoded)
+                );
+            }
+
+            #[test]
+            fn signed_digest_recovers_the_signer(
+                timestamp: u64,
+                nonce in "[0-9a-f]{1,32}",
+                chain_id in 1..=u64::from(u32::MAX),
+            ) {
+                // Anvil test key #0.
+                let signer: PrivateKeySigner =
+                    "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+                        .parse()
+                        .unwrap();
+                let address = Signer::address(&signer);
+                let signature = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap()
+                    .block_on(sign_auth_message(&signer, address, timestamp, &nonce, Some(chain_id)))
+                    .unwrap();
+                let signature =
+                    alloy::primitives::Signature::try_from(
+                        hex::decode(signature.trim_start_matches("0x")).unwrap().as_slice(),
+                    )
+                    .unwrap();
+                // The digest alloy's domain separator composes to.
+                let digest = keccak256(
+                    [
+                        &[0x19, 0x01][..],
+                        auth_domain(chain_id).separator().as_slice(),
+                        compute_auth_struct_hash(address, timestamp, &nonce).as_slice(),
+                    ]
+                    .concat(),
+                );
+                prop_assert_eq!(auth_signing_digest(address, timestamp, &nonce, chain_id), digest);
+                prop_assert_eq!(signature.recover_address_from_prehash(&digest).unwrap(), address);
+            }
+
+            #[test]
+            fn typed_request_hashes_to_the_signed_digest(
+                address: [u8; 20],
+                timestamp: u64,
+                nonce in "[0-9a-f]{1,32}",
+                chain_id: u64,
+            ) {
+                // What a Ledger signs must recover like the digest path.
+                let address = Address::from(address);
+                let request = typed::AuthRequest {
+                    address,
+                    timestamp,
+                    nonce: nonce.clone(),
+                };
+                prop_assert_eq!(
+                    request.eip712_signing_hash(&auth_domain(chain_id)),
+                    auth_signing_digest(address, timestamp, &nonce, chain_id)
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,349 @@
+//! Market trading hours.
+//!
+//! A [`Market`] may carry a [`MarketSchedule`]: recurring daily
+//! [`TradingWindow`]s (UTC) the market is open in, and one-off
+//! [`MarketBlock`]s it is closed for. Stacks that run scheduled batch
+//! auctions use it to publish their sessions; a market without a schedule
+//! is always open. [`Market::is_open`] answers for a given instant, and
+//! order submission refuses orders while the market is closed.
+
+use std::fmt;
+use std::str::FromStr;
+
+use eyre::{Result, eyre};
+
+use super::config_pb::{Market, MarketBlock, MarketSchedule, TradingWindow};
+use crate::error::{self, AspensError};
+use crate::util::format_utc;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Why a market is closed at a given instant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketClosed {
+    /// Inside a one-off block.
+    Blocked {
+        /// Unix seconds the block ends.
+        until: u64,
+        /// The block's reason, possibly empty.
+        reason: String,
+    },
+    /// Outside every trading window.
+    OutsideHours,
+}
+
+impl fmt::Display for MarketClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketClosed::Blocked { until, reason } if reason.is_empty() => {
+                write!(f, "closed until {} UTC", format_utc(*until))
+            }
+            MarketClosed::Blocked { until, reason } => {
+                write!(f, "closed until {} UTC ({})", format_utc(*until), reason)
+            }
+            MarketClosed::OutsideHours => write!(f, "outside trading hours"),
+        }
+    }
+}
+
+impl Market {
+    /// True when the market accepts orders at `now` (unix seconds).
+    pub fn is_open(&self, now: u64) -> bool {
+        self.closed_at(now).is_none()
+    }
+
+    /// Why the market is closed at `now`, or `None` if it is open.
+    pub fn closed_at(&self, now: u64) -> Option<MarketClosed> {
+        self.schedule.as_ref()?.closed_at(now)
+    }
+
+    /// Error if the market is closed at `now`. Order submission calls this
+    /// before signing.
+    pub fn ensure_open(&self, now: u64) -> error::Result<()> {
+        match self.closed_at(now) {
+            Some(closed) => Err(AspensError::invalid_input(format!(
+                "market '{}' is {}",
+                self.name, closed
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl MarketSchedule {
+    /// Why the schedule is closed at `now`, or `None` if it is open.
+    pub fn closed_at(&self, now: u64) -> Option<MarketClosed> {
+        if let Some(block) = self
+            .blocks
+            .iter()
+            .find(|b| b.starts_at <= now && now < b.ends_at)
+        {
+            return Some(MarketClosed::Blocked {
+                until: block.ends_at,
+                reason: block.reason.clone(),
+            });
+        }
+        if self.windows.is_empty() || self.windows.iter().any(|w| w.contains(now)) {
+            None
+        } else {
+            Some(MarketClosed::OutsideHours)
+        }
+    }
+
+    /// Check every window and block is well-formed.
+    pub fn validate(&self) -> error::Result<()> {
+        for window in &self.windows {
+            window.validate()?;
+        }
+        for block in &self.blocks {
+            if block.ends_at <= block.starts_at {
+                return Err(AspensError::invalid_input(format!(
+                    "market block must end after it starts ({} <= {})",
+                    block.ends_at, block.starts_at
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TradingWindow {
+    /// True when `now` (unix seconds) falls inside the window.
+    pub fn contains(&self, now: u64) -> bool {
+        let days = now / 86_400;
+        let minute = ((now % 86_400) / 60) as u32;
+        // 1970-01-01 was a Thursday.
+        let weekday = ((days + 4) % 7) as u32;
+        let yesterday = (weekday + 6) % 7;
+        let opens_on = |day: u32| self.days.is_empty() || self.days.contains(&day);
+
+        if self.open_minute < self.close_minute {
+            opens_on(weekday) && (self.open_minute..self.close_minute).contains(&minute)
+        } else {
+            // Wraps past midnight: the tail belongs to the previous day's
+            // window.
+            (opens_on(weekday) && minute >= self.open_minute)
+                || (opens_on(yesterday) && minute < self.close_minute)
+        }
+    }
+
+    /// Check days are 0-6 and the times are within a day and distinct.
+    pub fn validate(&self) -> error::Result<()> {
+        if let Some(day) = self.days.iter().find(|d| **d > 6) {
+            return Err(AspensError::invalid_input(format!(
+                "invalid weekday {} (expected 0 = Sunday .. 6)",
+                day
+            )));
+        }
+        if self.open_minute >= MINUTES_PER_DAY || self.close_minute > MINUTES_PER_DAY {
+            return Err(AspensError::invalid_input(
+                "trading window times must be within one day",
+            ));
+        }
+        if self.open_minute == self.close_minute {
+            return Err(AspensError::invalid_input(
+                "trading window opens and closes at the same time",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parses `[DAYS ]HH:MM-HH:MM`, where `DAYS` is `*`, a day (`mon`), a range
+/// (`mon-fri`) or a comma-separated list of either (`sat,sun`). Times are
+/// UTC; `24:00` closes at midnight and a close before the open wraps past
+/// midnight.
+impl FromStr for TradingWindow {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (days, times) = match s.split_once(char::is_whitespace) {
+            Some((days, times)) => (parse_days(days)?, times.trim()),
+            None => (Vec::new(), s),
+        };
+        let (open, close) = times
+            .split_once('-')
+            .ok_or_else(|| eyre!("invalid trading window '{}': expected HH:MM-HH:MM", s))?;
+        let window = TradingWindow {
+            days,
+            open_minute: parse_time(open)?,
+            close_minute: parse_time(close)?,
+        };
+        window.validate()?;
+        Ok(window)
+    }
+}
+
+impl fmt::Display for TradingWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.days.is_empty() {
+            let days: Vec<&str> = self
+                .days
+                .iter()
+                .filter_map(|d| DAY_NAMES.get(*d as usize).copied())
+                .collect();
+            write!(f, "{} ", days.join(","))?;
+        }
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            self.open_minute / 60,
+            self.open_minute % 60,
+            self.close_minute / 60,
+            self.close_minute % 60
+        )
+    }
+}
+
+/// Parses `START-END[:REASON]` with unix-second bounds, e.g.
+/// `1735689600-1735696800:contract upgrade`.
+impl FromStr for MarketBlock {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (range, reason) = s.split_once(':').unwrap_or((s, ""));
+        let (start, end) = range
+            .split_once('-')
+            .ok_or_else(|| eyre!("invalid market block '{}': expected START-END", s))?;
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<u64>()
+                .map_err(|_| eyre!("invalid market block time '{}': expected unix seconds", v))
+        };
+        let block = MarketBlock {
+            starts_at: parse(start)?,
+            ends_at: parse(end)?,
+            reason: reason.trim().to_string(),
+        };
+        if block.ends_at <= block.starts_at {
+            return Err(eyre!("market block '{}' must end after it starts", s));
+        }
+        Ok(block)
+    }
+}
+
+fn parse_days(s: &str) -> Result<Vec<u32>> {
+    if s == "*" {
+        return Ok(Vec::new());
+    }
+    let day = |name: &str| {
+        DAY_NAMES
+            .iter()
+            .position(|d| d.eq_ignore_ascii_case(name))
+            .map(|d| d as u32)
+            .ok_or_else(|| eyre!("invalid weekday '{}' (expected sun..sat)", name))
+    };
+    let mut days = Vec::new();
+    for part in s.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (day(from)?, day(to)?);
+                let mut d = from;
+                loop {
+                    days.push(d);
+                    if d == to {
+                        break;
+                    }
+                    d = (d + 1) % 7;
+                }
+            }
+            None => days.push(day(part)?),
+        }
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+fn parse_time(s: &str) -> Result<u32> {
+    let (h, m) = s
+        .trim()
+        .split_once(':')
+        .ok_or_else(|| eyre!("invalid time '{}': expected HH:MM", s))?;
+    let h: u32 = h.parse().map_err(|_| eyre!("invalid hour in '{}'", s))?;
+    let m: u32 = m.parse().map_err(|_| eyre!("invalid minute in '{}'", s))?;
+    if m >= 60 || h * 60 + m > MINUTES_PER_DAY {
+        return Err(eyre!("invalid time '{}'", s));
+    }
+    Ok(h * 60 + m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2023-11-13 00:00:00 UTC, a Monday.
+    const MONDAY: u64 = 1_699_833_600;
+    const HOUR: u64 = 3_600;
+
+    fn market(schedule: MarketSchedule) -> Market {
+        Market {
+            name: "m".into(),
+            schedule: Some(schedule),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_and_formats_windows() {
+        let window: TradingWindow = "mon-fri 13:30-20:00".parse().unwrap();
+        assert_eq!(window.days, vec![1, 2, 3, 4, 5]);
+        assert_eq!(window.open_minute, 13 * 60 + 30);
+        assert_eq!(window.to_string(), "mon,tue,wed,thu,fri 13:30-20:00");
+        assert_eq!(
+            "fri-mon 22:00-02:00".parse::<TradingWindow>().unwrap().days,
+            vec![0, 1, 5, 6]
+        );
+        assert!("00:00-24:00".parse::<TradingWindow>().is_ok());
+        assert!("mon 10:00-10:00".parse::<TradingWindow>().is_err());
+        assert!("funday 10:00-11:00".parse::<TradingWindow>().is_err());
+        assert!("10:00-24:30".parse::<TradingWindow>().is_err());
+    }
+
+    #[test]
+    fn weekday_windows_open_and_close() {
+        let m = market(MarketSchedule {
+            windows: vec!["mon-fri 13:30-20:00".parse().unwrap()],
+            blocks: vec![],
+        });
+        assert!(!m.is_open(MONDAY + 13 * HOUR));
+        assert!(m.is_open(MONDAY + 14 * HOUR));
+        assert!(!m.is_open(MONDAY + 20 * HOUR));
+        // Saturday.
+        assert!(!m.is_open(MONDAY + 5 * 24 * HOUR + 14 * HOUR));
+        assert_eq!(m.closed_at(MONDAY).unwrap(), MarketClosed::OutsideHours);
+    }
+
+    #[test]
+    fn overnight_window_wraps_into_the_next_day() {
+        let m = market(MarketSchedule {
+            windows: vec!["mon 22:00-02:00".parse().unwrap()],
+            blocks: vec![],
+        });
+        assert!(m.is_open(MONDAY + 23 * HOUR));
+        // Tuesday 01:00 is the tail of Monday's session.
+        assert!(m.is_open(MONDAY + 25 * HOUR));
+        // Monday 01:00 would be the tail of Sunday's, which doesn't exist.
+        assert!(!m.is_open(MONDAY + HOUR));
+    }
+
+    #[test]
+    fn blocks_override_windows() {
+        let block: MarketBlock = format!("{}-{}:upgrade", MONDAY, MONDAY + HOUR)
+            .parse()
+            .unwrap();
+        let m = market(MarketSchedule {
+            windows: vec![],
+            blocks: vec![block],
+        });
+        let err = m.ensure_open(MONDAY + 60).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "market 'm' is closed until 2023-11-13 01:00:00 UTC (upgrade)"
+        );
+        assert!(m.is_open(MONDAY + HOUR));
+        assert!(Market::default().is_open(MONDAY));
+    }
+}
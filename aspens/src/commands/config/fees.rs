@@ -0,0 +1,153 @@
+//! Maker/taker trading fees.
+//!
+//! A [`Market`] may publish its [`MarketFees`]: maker and taker rates in
+//! basis points of settled notional. [`MarketFees::estimate`] prices the fee
+//! on a fill so strategies can reason about net edges up front rather than
+//! discovering fees after settlement. Stacks that don't publish fees leave
+//! [`Market::fees`] unset; `trading::fees::market_fees` then falls back to
+//! the instance contracts' on-chain rates.
+
+use std::fmt;
+
+use super::config_pb::{Market, MarketFees};
+use crate::error::{AspensError, Result};
+
+/// Basis points in 100%.
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Which side of a fill an order was on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeeRole {
+    /// The order was resting on the book.
+    Maker,
+    /// The order crossed on arrival.
+    Taker,
+}
+
+impl fmt::Display for FeeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeRole::Maker => write!(f, "maker"),
+            FeeRole::Taker => write!(f, "taker"),
+        }
+    }
+}
+
+/// The fee on one fill. Amounts are raw pair-decimal quote units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// The role the rate was taken for.
+    pub role: FeeRole,
+    /// The rate applied.
+    pub bps: u32,
+    /// Quantity × price.
+    pub notional: u128,
+    /// The fee, rounded up.
+    pub fee: u128,
+}
+
+impl FeeEstimate {
+    /// Notional after the fee.
+    pub fn net_notional(&self) -> u128 {
+        self.notional.saturating_sub(self.fee)
+    }
+}
+
+impl Market {
+    /// The fee rates the stack publishes for this market, if any.
+    pub fn fees(&self) -> Option<&MarketFees> {
+        self.fees.as_ref()
+    }
+}
+
+impl MarketFees {
+    /// The same rate for makers and takers.
+    pub fn flat(bps: u32) -> Self {
+        Self {
+            maker_bps: bps,
+            taker_bps: bps,
+        }
+    }
+
+    /// The rate for `role`.
+    pub fn bps(&self, role: FeeRole) -> u32 {
+        match role {
+            FeeRole::Maker => self.maker_bps,
+            FeeRole::Taker => self.taker_bps,
+        }
+    }
+
+    /// Check neither rate exceeds 100%.
+    pub fn validate(&self) -> Result<()> {
+        if self.maker_bps > BPS_DENOMINATOR || self.taker_bps > BPS_DENOMINATOR {
+            return Err(AspensError::invalid_input(format!(
+                "fee rates must be at most {} bps (maker {}, taker {})",
+                BPS_DENOMINATOR, self.maker_bps, self.taker_bps
+            )));
+        }
+        Ok(())
+    }
+
+    /// Estimate the fee on a fill of `quantity` at `price`, both raw pair
+    /// decimals.
+    pub fn estimate(
+        &self,
+        role: FeeRole,
+        quantity: u128,
+        price: u128,
+        pair_decimals: u32,
+    ) -> FeeEstimate {
+        let notional = quantity.saturating_mul(price) / 10u128.pow(pair_decimals);
+        let bps = self.bps(role);
+        FeeEstimate {
+            role,
+            bps,
+            notional,
+            fee: fee_on(notional, bps),
+        }
+    }
+
+    /// A gross edge in basis points less the fee for `role`.
+    pub fn net_edge_bps(&self, gross_edge_bps: i64, role: FeeRole) -> i64 {
+        gross_edge_bps - i64::from(self.bps(role))
+    }
+}
+
+impl fmt::Display for MarketFees {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "maker {} bps / taker {} bps",
+            self.maker_bps, self.taker_bps
+        )
+    }
+}
+
+/// `bps` of `amount`, rounded up so estimates never undershoot.
+pub fn fee_on(amount: u128, bps: u32) -> u128 {
+    amount
+        .saturating_mul(u128::from(bps))
+        .div_ceil(u128::from(BPS_DENOMINATOR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_round_up_and_net_out() {
+        let fees = MarketFees {
+            maker_bps: 2,
+            taker_bps: 10,
+        };
+        // 1.50 at 2000.00 with two pair decimals: notional 3000.00.
+        let taker = fees.estimate(FeeRole::Taker, 150, 200_000, 2);
+        assert_eq!(taker.notional, 300_000);
+        assert_eq!(taker.fee, 300);
+        assert_eq!(taker.net_notional(), 299_700);
+        assert_eq!(fee_on(1, 2), 1);
+        assert_eq!(fees.net_edge_bps(5, FeeRole::Maker), 3);
+        assert_eq!(fees.to_string(), "maker 2 bps / taker 10 bps");
+        assert!(MarketFees::flat(10_001).validate().is_err());
+    }
+}
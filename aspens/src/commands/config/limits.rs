@@ -0,0 +1,215 @@
+//! Order price and size rules.
+//!
+//! A [`Market`] may publish [`MarketLimits`]: the tick size limit prices
+//! must be a multiple of, the lot size quantities must be a multiple of,
+//! and the smallest notional an order may have. Each rule is a decimal
+//! string in the market's pair units; an empty rule is no rule. Order
+//! submission calls [`Market::check_order_limits`] before signing, so an
+//! off-tick price fails locally with the nearest valid prices instead of
+//! as a server rejection.
+
+use super::config_pb::{Market, MarketLimits};
+use crate::decimals::{PrecisionMode, format_decimal_amount, parse_decimal_amount_with};
+use crate::error::{self, AspensError};
+
+impl Market {
+    /// The price and size rules the stack publishes for this market, if
+    /// any.
+    pub fn limits(&self) -> Option<&MarketLimits> {
+        self.limits.as_ref()
+    }
+
+    /// Error if an order of `quantity` at `price` (raw pair decimals;
+    /// `None` for a market order) breaks the market's tick size, lot size
+    /// or minimum notional. Order submission calls this before signing.
+    pub fn check_order_limits(&self, quantity: u128, price: Option<u128>) -> error::Result<()> {
+        let Some(limits) = self.limits() else {
+            return Ok(());
+        };
+        let decimals = self.pair_decimals as u32;
+        let amount = |raw: u128| trimmed(raw, decimals);
+
+        if let (Some(price), Some(tick)) = (price, limits.tick(decimals)?) {
+            let below = price - price % tick;
+            if below != price {
+                return Err(AspensError::invalid_input(format!(
+                    "price {} must be a multiple of {} (market '{}' tick size); \
+                     nearest valid prices are {} and {}",
+                    amount(price),
+                    amount(tick),
+                    self.name,
+                    amount(below),
+                    amount(below + tick)
+                )));
+            }
+        }
+        let lot = limits.lot(decimals)?;
+        if let Some(lot) = lot {
+            let below = quantity - quantity % lot;
+            if below != quantity {
+                let nearest = if below == 0 {
+                    format!("the smallest valid quantity is {}", amount(lot))
+                } else {
+                    format!(
+                        "nearest valid quantities are {} and {}",
+                        amount(below),
+                        amount(below + lot)
+                    )
+                };
+                return Err(AspensError::invalid_input(format!(
+                    "quantity {} must be a multiple of {} (market '{}' lot size); {}",
+                    amount(quantity),
+                    amount(lot),
+                    self.name,
+                    nearest
+                )));
+            }
+        }
+        // A market order's notional isn't known until it fills.
+        if let (Some(price), Some(min)) = (price, limits.min_notional(decimals)?) {
+            let scale = 10u128.pow(decimals);
+            let notional = quantity.saturating_mul(price) / scale;
+            if notional < min && price > 0 {
+                let mut needed = min.saturating_mul(scale).div_ceil(price);
+                if let Some(lot) = lot {
+                    needed = needed.div_ceil(lot).saturating_mul(lot);
+                }
+                return Err(AspensError::invalid_input(format!(
+                    "order notional {} is below market '{}' minimum of {}; \
+                     at price {} the quantity must be at least {}",
+                    amount(notional),
+                    self.name,
+                    amount(min),
+                    amount(price),
+                    amount(needed)
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MarketLimits {
+    /// The tick size in raw pair decimals; `None` if unset.
+    pub fn tick(&self, pair_decimals: u32) -> error::Result<Option<u128>> {
+        rule("tick size", &self.tick_size, pair_decimals)
+    }
+
+    /// The lot size in raw pair decimals; `None` if unset.
+    pub fn lot(&self, pair_decimals: u32) -> error::Result<Option<u128>> {
+        rule("lot size", &self.lot_size, pair_decimals)
+    }
+
+    /// The minimum notional in raw pair decimals; `None` if unset.
+    pub fn min_notional(&self, pair_decimals: u32) -> error::Result<Option<u128>> {
+        rule("minimum notional", &self.min_notional, pair_decimals)
+    }
+}
+
+/// Parse one rule. Empty and zero rules are no rule; a rule the pair
+/// decimals can't express is a config error rather than something to
+/// round.
+fn rule(name: &str, value: &str, pair_decimals: u32) -> error::Result<Option<u128>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let raw =
+        parse_decimal_amount_with(value, pair_decimals, PrecisionMode::Error).map_err(|e| {
+            AspensError::invalid_input(format!("market {} '{}' is invalid: {}", name, value, e))
+        })?;
+    Ok((raw > 0).then_some(raw))
+}
+
+/// `raw` as a decimal without trailing zeros, e.g. `0.01` rather than
+/// `0.010000`.
+fn trimmed(raw: u128, decimals: u32) -> String {
+    let formatted = format_decimal_amount(raw, decimals);
+    if formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(tick: &str, lot: &str, min_notional: &str) -> Market {
+        Market {
+            name: "ETH/USDC".into(),
+            pair_decimals: 2,
+            limits: Some(MarketLimits {
+                tick_size: tick.into(),
+                lot_size: lot.into(),
+                min_notional: min_notional.into(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn off_tick_price_names_the_nearest_valid_prices() {
+        let market = market("0.05", "", "");
+        assert!(market.check_order_limits(100, Some(1_005)).is_ok());
+        let err = market
+            .check_order_limits(100, Some(1_007))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("price 10.07 must be a multiple of 0.05"),
+            "{err}"
+        );
+        assert!(err.contains("10.05 and 10.1"), "{err}");
+        // Market orders have no price to check.
+        assert!(market.check_order_limits(100, None).is_ok());
+    }
+
+    #[test]
+    fn quantity_must_be_a_whole_number_of_lots() {
+        let market = market("", "0.5", "");
+        assert!(market.check_order_limits(150, None).is_ok());
+        let err = market
+            .check_order_limits(120, None)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("nearest valid quantities are 1 and 1.5"),
+            "{err}"
+        );
+        let err = market.check_order_limits(20, None).unwrap_err().to_string();
+        assert!(err.contains("the smallest valid quantity is 0.5"), "{err}");
+    }
+
+    #[test]
+    fn small_orders_get_the_quantity_that_would_pass() {
+        let market = market("", "0.5", "10");
+        // 1.00 at 5.00 is 5.00 notional; 2.00 reaches 10.
+        let err = market
+            .check_order_limits(100, Some(500))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("below market 'ETH/USDC' minimum of 10"),
+            "{err}"
+        );
+        assert!(err.contains("at least 2"), "{err}");
+        assert!(market.check_order_limits(200, Some(500)).is_ok());
+        assert!(market.check_order_limits(100, None).is_ok());
+    }
+
+    #[test]
+    fn empty_and_zero_rules_are_no_rule() {
+        assert!(market("", "0", "").check_order_limits(1, Some(1)).is_ok());
+        assert!(Market::default().check_order_limits(1, Some(1)).is_ok());
+        assert!(
+            market("0.001", "", "")
+                .check_order_limits(1, Some(1))
+                .is_err()
+        );
+    }
+}
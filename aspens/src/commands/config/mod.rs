@@ -11,13 +11,25 @@ pub mod config_pb {
     include!("../../../proto/generated/xyz.aspens.arborter_config.v1.rs");
 }
 
-use config_pb::{Chain, GetConfigRequest, GetConfigResponse, Market, Token};
-use eyre::{Result, bail};
+mod calendar;
+mod fees;
+mod limits;
+pub use calendar::MarketClosed;
+pub use fees::{BPS_DENOMINATOR, FeeEstimate, FeeRole, fee_on};
+
+use crate::error::{AspensError, Result};
+use config_pb::{Chain, GetConfigRequest, GetConfigResponse, MaintenanceNotice, Market, Token};
 use std::fs;
 use std::path::Path;
 use tracing::info;
 
 use crate::grpc::create_channel;
+use crate::schema::Schema;
+use crate::util::format_utc;
+
+/// Saved config files. Version 1 is the bare `GetConfigResponse` written
+/// before files were versioned; it needs no migration.
+static CONFIG_SCHEMA: Schema = Schema::new("config file", &[]);
 
 /// Raw config fetch from the trading server — NO local RPC overrides applied.
 /// Used by the `download_*` helpers, which should snapshot exactly what the
@@ -40,6 +52,7 @@ async fn fetch_config(url: String) -> Result<GetConfigResponse> {
 pub async fn get_config(url: String) -> Result<GetConfigResponse> {
     let mut config = fetch_config(url).await?;
     config.apply_rpc_overrides();
+    config.normalize_addresses();
     Ok(config)
 }
 
@@ -51,10 +64,19 @@ pub async fn download_config(url: String, path: String) -> Result<()> {
 
     // Determine format based on file extension
     let contents = match Path::new(&path).extension().and_then(|ext| ext.to_str()) {
-        Some("json") => serde_json::to_string_pretty(&config)?,
-        Some("toml") => toml::to_string_pretty(&config)?,
-        Some(ext) => bail!("Unsupported file extension: {}. Use .json or .toml", ext),
-        None => bail!("No file extension found. Use .json or .toml"),
+        Some("json") => serde_json::to_string_pretty(&CONFIG_SCHEMA.versioned(&config))?,
+        Some("toml") => toml::to_string_pretty(&CONFIG_SCHEMA.versioned(&config))?,
+        Some(ext) => {
+            return Err(AspensError::invalid_input(format!(
+                "Unsupported file extension: {}. Use .json or .toml",
+                ext
+            )));
+        }
+        None => {
+            return Err(AspensError::invalid_input(
+                "No file extension found. Use .json or .toml",
+            ));
+        }
     };
 
     fs::write(&path, contents)?;
@@ -65,24 +87,83 @@ pub async fn download_config(url: String, path: String) -> Result<()> {
 
 impl GetConfigResponse {
     /// Load a `GetConfigResponse` from a `.json` or `.toml` file on disk.
+    ///
+    /// When `ASPENS_CONFIG_PUBKEY` holds a minisign public key, the file must
+    /// have a valid signature by it at `<path>.minisig`; see
+    /// [`crate::verify`].
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
         let contents = fs::read_to_string(path)?;
 
-        // Determine file type based on extension
-        let mut config: Self = match path.extension().and_then(|ext| ext.to_str()) {
+        if let Some(key) = crate::verify::PublicKey::from_env(crate::verify::CONFIG_PUBKEY_ENV)? {
+            let signature = crate::verify::signature_path(path);
+            crate::verify::verify_bytes(contents.as_bytes(), &signature, &key).map_err(|e| {
+                AspensError::invalid_input(format!(
+                    "refusing config {}: {}\n\nHints:\n- {} is set, so config files must be \
+                     signed with `minisign -Sm {}`",
+                    path.display(),
+                    e,
+                    crate::verify::CONFIG_PUBKEY_ENV,
+                    path.display()
+                ))
+            })?;
+        }
+
+        // Determine file type based on extension, then upgrade older formats.
+        let doc: serde_json::Value = match path.extension().and_then(|ext| ext.to_str()) {
             Some("json") => serde_json::from_str(&contents)?,
-            Some("toml") => toml::from_str(&contents)?,
-            Some(ext) => bail!("Unsupported file extension: {}", ext),
-            None => bail!("No file extension found"),
+            Some("toml") => serde_json::to_value(toml::from_str::<toml::Value>(&contents)?)?,
+            Some(ext) => {
+                return Err(AspensError::invalid_input(format!(
+                    "Unsupported file extension: {}",
+                    ext
+                )));
+            }
+            None => return Err(AspensError::invalid_input("No file extension found")),
         };
+        let mut config: Self = CONFIG_SCHEMA.from_value(doc).map_err(|e| {
+            AspensError::invalid_input(format!("invalid config {}: {}", path.display(), e))
+        })?;
 
         // A file may carry a masked rpc_url (e.g. a download snapshot); apply
         // the same local override resolution used for a live fetch.
         config.apply_rpc_overrides();
+        config.normalize_addresses();
         Ok(config)
     }
 
+    /// Rewrite every EVM address in the config (tokens, factory, trade
+    /// contract, instance signer) to its EIP-55 form via
+    /// [`crate::util::parse_address`], so later comparisons and display don't
+    /// depend on how the server cased them. Solana chains are left untouched;
+    /// an address that fails validation is kept as-is and logged at WARN.
+    fn normalize_addresses(&mut self) {
+        let Some(config) = self.config.as_mut() else {
+            return;
+        };
+        for chain in &mut config.chains {
+            if chain
+                .architecture
+                .eq_ignore_ascii_case(crate::chain_client::ARCH_SOLANA)
+            {
+                continue;
+            }
+            let network = chain.network.clone();
+            let mut fields: Vec<&mut String> = vec![
+                &mut chain.factory_address,
+                &mut chain.instance_signer_address,
+            ];
+            fields.extend(chain.trade_contract.as_mut().map(|tc| &mut tc.address));
+            fields.extend(chain.tokens.values_mut().map(|t| &mut t.address));
+            for field in fields.into_iter().filter(|f| !f.is_empty()) {
+                match crate::util::parse_address(field) {
+                    Ok(address) => *field = address.to_checksum(None),
+                    Err(e) => tracing::warn!(network = %network, "{}", e),
+                }
+            }
+        }
+    }
+
     /// Rewrite each chain's `rpc_url` to the client's local override
     /// (`ASPENS_RPC_URL_<NETWORK>`) when set; otherwise keep the server value
     /// (an unmasked URL stays usable). The arborter masks `rpc_url` in its
@@ -164,6 +245,46 @@ impl GetConfigResponse {
             .iter()
             .find(|market| market.market_id == market_id)
     }
+
+    /// The operator message / maintenance window the admin set, if any.
+    pub fn maintenance(&self) -> Option<&MaintenanceNotice> {
+        self.config.as_ref()?.maintenance.as_ref()
+    }
+}
+
+impl MaintenanceNotice {
+    /// True while `now` (unix seconds) is inside the maintenance window. A
+    /// notice without a start is active until it ends; one without either
+    /// bound is a standing operator message and always active.
+    pub fn is_active(&self, now: u64) -> bool {
+        self.starts_at.is_none_or(|start| now >= start) && !self.is_over(now)
+    }
+
+    /// True once the window's end has passed.
+    pub fn is_over(&self, now: u64) -> bool {
+        self.ends_at.is_some_and(|end| now >= end)
+    }
+
+    /// One-line banner for CLIs and logs, e.g.
+    /// `MAINTENANCE SCHEDULED from 2025-01-01 00:00:00 UTC: Upgrading contracts`.
+    pub fn banner(&self, now: u64) -> String {
+        let label = if self.starts_at.is_some_and(|start| now < start) {
+            "MAINTENANCE SCHEDULED"
+        } else if self.starts_at.is_some() || self.ends_at.is_some() {
+            "MAINTENANCE"
+        } else {
+            "NOTICE"
+        };
+        let window = match (self.starts_at, self.ends_at) {
+            (Some(start), Some(end)) => {
+                format!(" {} - {} UTC", format_utc(start), format_utc(end))
+            }
+            (Some(start), None) => format!(" from {} UTC", format_utc(start)),
+            (None, Some(end)) => format!(" until {} UTC", format_utc(end)),
+            (None, None) => String::new(),
+        };
+        format!("{}{}: {}", label, window, self.message)
+    }
 }
 
 /// Download the stack configuration from `url` and write it to `path` as JSON.
@@ -183,7 +304,7 @@ pub async fn download_config_to_file<P: AsRef<Path>>(url: String, path: P) -> Re
     }
 
     // Write config to file
-    let json = serde_json::to_string_pretty(&config)?;
+    let json = serde_json::to_string_pretty(&CONFIG_SCHEMA.versioned(&config))?;
     fs::write(path, json)?;
 
     info!("Configuration downloaded successfully");
@@ -242,14 +363,13 @@ impl SignerInfo {
 
 /// Get native token balance for an address on a chain via RPC
 async fn get_native_balance(rpc_url: &str, address: &str) -> Result<u128> {
-    use alloy::primitives::Address;
     use alloy::providers::{Provider, ProviderBuilder};
     use url::Url;
 
     let rpc_url = Url::parse(rpc_url)?;
     let provider = ProviderBuilder::new().connect_http(rpc_url);
 
-    let address: Address = address.parse()?;
+    let address = crate::util::parse_address(address)?;
     let balance = provider.get_balance(address).await?;
 
     Ok(balance.to::<u128>())
@@ -274,7 +394,7 @@ pub async fn get_signer_public_key_with_balances(
     let config_response = get_config(url).await?;
     let config = config_response
         .config
-        .ok_or_else(|| eyre::eyre!("No configuration found"))?;
+        .ok_or_else(|| AspensError::not_found("No configuration found"))?;
 
     // Build a map of chain_network -> rpc_url
     let chain_rpc_map: std::collections::HashMap<String, String> = config
@@ -402,6 +522,88 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn normalize_addresses_checksums_evm_chains_only() {
+        let evm_token = Token {
+            address: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".into(),
+            ..Default::default()
+        };
+        let mint = "So11111111111111111111111111111111111111112";
+        let sol_token = Token {
+            address: mint.into(),
+            ..Default::default()
+        };
+        let mut config = GetConfigResponse {
+            config: Some(config_pb::Configuration {
+                chains: vec![
+                    Chain {
+                        architecture: "EVM".into(),
+                        network: "anvil-1".into(),
+                        factory_address: "not-an-address".into(),
+                        tokens: [("USDC".to_string(), evm_token)].into(),
+                        ..Default::default()
+                    },
+                    Chain {
+                        architecture: crate::chain_client::ARCH_SOLANA.into(),
+                        network: "solana-devnet".into(),
+                        tokens: [("SOL".to_string(), sol_token)].into(),
+                        ..Default::default()
+                    },
+                ],
+                markets: vec![],
+                maintenance: None,
+                signing_versions: vec![],
+            }),
+        };
+        config.normalize_addresses();
+
+        assert_eq!(
+            config.get_token("anvil-1", "USDC").unwrap().address,
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+        );
+        // Invalid values are kept for the caller to surface.
+        assert_eq!(
+            config.get_chain("anvil-1").unwrap().factory_address,
+            "not-an-address"
+        );
+        assert_eq!(
+            config.get_token("solana-devnet", "SOL").unwrap().address,
+            mint
+        );
+    }
+
+    #[test]
+    fn maintenance_notice_window_and_banner() {
+        let notice = MaintenanceNotice {
+            message: "Upgrading contracts".into(),
+            starts_at: Some(1_700_000_000),
+            ends_at: Some(1_700_003_600),
+        };
+        assert!(!notice.is_active(1_699_999_999));
+        assert!(notice.is_active(1_700_000_000));
+        assert!(notice.is_over(1_700_003_600));
+        assert_eq!(
+            notice.banner(0),
+            "MAINTENANCE SCHEDULED 2023-11-14 22:13:20 - 2023-11-14 23:13:20 UTC: \
+             Upgrading contracts"
+        );
+
+        let standing = MaintenanceNotice {
+            message: "Read-only until further notice".into(),
+            ..Default::default()
+        };
+        assert!(standing.is_active(0));
+        assert_eq!(standing.banner(0), "NOTICE: Read-only until further notice");
+
+        let config = GetConfigResponse {
+            config: Some(config_pb::Configuration {
+                maintenance: Some(standing),
+                ..Default::default()
+            }),
+        };
+        assert!(config.maintenance().is_some());
+    }
+
     fn verify_config(config: &GetConfigResponse) {
         // Test chain retrieval
         let anvil1 = config.get_chain("anvil-1").unwrap();
@@ -0,0 +1,353 @@
+//! When a market is actually used: trade volume by hour and day, the most
+//! active traders, and the spread.
+//!
+//! [`activity_report`] replays the market's closed trades since a point in
+//! time and folds them into an [`ActivityReport`]. The stack keeps no
+//! spread history, so the report's spread comes from top-of-book snapshots:
+//! [`activity_report`] takes one of the live book, and callers that poll
+//! the book themselves can add more with [`ActivityReport::add_spread`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
+
+use super::replay::HistoricalReplay;
+use super::stream_orderbook::{TopOfBook, fetch_top_of_book};
+use super::stream_trades::{StreamTradesOptions, arborter_pb::Trade, stream_trades_channel};
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+use crate::util::{civil_from_days, csv_row, format_utc};
+
+/// How long [`activity_report`] waits for another trade before treating
+/// the replay as complete.
+pub const DEFAULT_REPLAY_QUIET: Duration = Duration::from_secs(2);
+
+/// How long [`activity_report`] listens to the orderbook for its spread
+/// snapshot.
+pub const DEFAULT_BOOK_WINDOW: Duration = Duration::from_millis(1_500);
+
+/// Weekday labels for [`ActivityReport::heatmap`] rows, Monday first.
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Shades for heatmap cells, from no volume to the busiest hour.
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// Trades and volume in one bucket of an [`ActivityReport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Activity {
+    /// Number of trades.
+    pub trades: u64,
+    /// Traded quantity, raw in the market's pair decimals.
+    pub volume: u128,
+}
+
+impl Activity {
+    fn add(&mut self, volume: u128) {
+        self.trades += 1;
+        self.volume += volume;
+    }
+}
+
+/// Trade activity on one market since a point in time.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityReport {
+    /// Start of the reported period, unix milliseconds.
+    pub since_ms: u64,
+    /// Activity per UTC hour, keyed by the hour's start in unix seconds.
+    pub hourly: BTreeMap<u64, Activity>,
+    /// Activity per trader base-chain address, as maker or taker.
+    pub traders: HashMap<String, Activity>,
+    /// Spread samples in basis points of the mid.
+    pub spreads_bps: Vec<f64>,
+}
+
+impl ActivityReport {
+    /// An empty report covering trades from `since_ms` on.
+    pub fn new(since_ms: u64) -> Self {
+        Self {
+            since_ms,
+            ..Self::default()
+        }
+    }
+
+    /// Count `trade`. Trades before the report's start or with an
+    /// unparseable quantity are skipped.
+    pub fn add_trade(&mut self, trade: &Trade) {
+        if trade.timestamp < self.since_ms {
+            return;
+        }
+        let Ok(volume) = trade.qty.parse::<u128>() else {
+            return;
+        };
+        let hour = trade.timestamp / 1_000 / 3_600 * 3_600;
+        self.hourly.entry(hour).or_default().add(volume);
+        for address in [&trade.maker_base_address, &trade.taker_base_address] {
+            if !address.is_empty() {
+                self.traders.entry(address.clone()).or_default().add(volume);
+            }
+        }
+    }
+
+    /// Record a spread snapshot. A book missing either side is skipped.
+    pub fn add_spread(&mut self, top: &TopOfBook) {
+        if let (Some(bid), Some(ask)) = (top.best_bid, top.best_ask) {
+            let mid = (bid as f64 + ask as f64) / 2.0;
+            if mid > 0.0 {
+                self.spreads_bps
+                    .push((ask as f64 - bid as f64) / mid * 10_000.0);
+            }
+        }
+    }
+
+    /// All trades in the report.
+    pub fn total(&self) -> Activity {
+        self.hourly
+            .values()
+            .fold(Activity::default(), |acc, a| Activity {
+                trades: acc.trades + a.trades,
+                volume: acc.volume + a.volume,
+            })
+    }
+
+    /// Activity per UTC day, keyed by `YYYY-MM-DD`.
+    pub fn daily(&self) -> BTreeMap<String, Activity> {
+        let mut days: BTreeMap<String, Activity> = BTreeMap::new();
+        for (&hour, a) in &self.hourly {
+            let (year, month, day) = civil_from_days((hour / 86_400) as i64);
+            let day = days
+                .entry(format!("{:04}-{:02}-{:02}", year, month, day))
+                .or_default();
+            day.trades += a.trades;
+            day.volume += a.volume;
+        }
+        days
+    }
+
+    /// Volume by weekday (Monday first, as in [`WEEKDAYS`]) and UTC hour.
+    pub fn heatmap(&self) -> [[u128; 24]; 7] {
+        let mut grid = [[0u128; 24]; 7];
+        for (&hour, a) in &self.hourly {
+            grid[weekday(hour)][(hour % 86_400 / 3_600) as usize] += a.volume;
+        }
+        grid
+    }
+
+    /// The `n` traders with the most volume, busiest first.
+    pub fn top_traders(&self, n: usize) -> Vec<(&str, Activity)> {
+        let mut traders: Vec<(&str, Activity)> = self
+            .traders
+            .iter()
+            .map(|(address, a)| (address.as_str(), *a))
+            .collect();
+        traders.sort_by(|a, b| b.1.volume.cmp(&a.1.volume).then(a.0.cmp(b.0)));
+        traders.truncate(n);
+        traders
+    }
+
+    /// Mean of the spread samples, in basis points.
+    pub fn average_spread_bps(&self) -> Option<f64> {
+        (!self.spreads_bps.is_empty())
+            .then(|| self.spreads_bps.iter().sum::<f64>() / self.spreads_bps.len() as f64)
+    }
+
+    /// Render the report for a terminal: totals, the weekday-by-hour
+    /// heatmap, daily volumes and the `top` busiest traders, with volumes
+    /// scaled by `pair_decimals`.
+    pub fn display(&self, pair_decimals: u32, top: usize) -> String {
+        let total = self.total();
+        let mut out = format!(
+            "Since {} UTC: {} trades, volume {}",
+            format_utc(self.since_ms / 1_000),
+            total.trades,
+            format_decimal_amount(total.volume, pair_decimals)
+        );
+        match self.average_spread_bps() {
+            Some(bps) => out.push_str(&format!(
+                "\nAverage spread: {:.1} bps ({} snapshot(s))",
+                bps,
+                self.spreads_bps.len()
+            )),
+            None => out.push_str("\nAverage spread: n/a (no two-sided book)"),
+        }
+        if total.trades == 0 {
+            return out;
+        }
+
+        out.push_str("\n\nVolume by weekday and hour (UTC)\n");
+        out.push_str(&self.render_heatmap());
+
+        let mut daily = Table::new();
+        daily.load_preset(UTF8_BORDERS_ONLY);
+        daily.set_header(vec!["Day (UTC)", "Trades", "Volume"]);
+        for (day, a) in self.daily() {
+            daily.add_row(vec![
+                day,
+                a.trades.to_string(),
+                format_decimal_amount(a.volume, pair_decimals),
+            ]);
+        }
+        out.push_str(&format!("\n\n{}", daily));
+
+        let mut traders = Table::new();
+        traders.load_preset(UTF8_BORDERS_ONLY);
+        traders.set_header(vec!["Trader", "Trades", "Volume"]);
+        for (address, a) in self.top_traders(top) {
+            traders.add_row(vec![
+                address.to_string(),
+                a.trades.to_string(),
+                format_decimal_amount(a.volume, pair_decimals),
+            ]);
+        }
+        out.push_str(&format!("\n\n{}", traders));
+        out
+    }
+
+    fn render_heatmap(&self) -> String {
+        let grid = self.heatmap();
+        let max = grid.iter().flatten().copied().max().unwrap_or(0);
+        let mut out = String::from("    ");
+        for hour in 0..24 {
+            out.push_str(&format!("{:02} ", hour));
+        }
+        for (day, row) in grid.iter().enumerate() {
+            out.push_str(&format!("\n{} ", WEEKDAYS[day]));
+            for &volume in row {
+                let shade = shade(volume, max);
+                out.push_str(&format!("{}{} ", shade, shade));
+            }
+        }
+        out.push_str(&format!(
+            "\n    {} none  {} low  {} busiest",
+            SHADES[0].to_string().repeat(2),
+            SHADES[1].to_string().repeat(2),
+            SHADES[4].to_string().repeat(2)
+        ));
+        out
+    }
+
+    /// The hourly buckets as CSV: `hour_utc,weekday,trades,volume`, one row
+    /// per hour with trades, volumes scaled by `pair_decimals`.
+    pub fn to_csv(&self, pair_decimals: u32) -> String {
+        let mut out = String::from("hour_utc,weekday,trades,volume\n");
+        for (&hour, a) in &self.hourly {
+            out.push_str(&csv_row([
+                format_utc(hour),
+                WEEKDAYS[weekday(hour)].to_string(),
+                a.trades.to_string(),
+                format_decimal_amount(a.volume, pair_decimals),
+            ]));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Row of [`WEEKDAYS`] for unix seconds `secs`. 1970-01-01 was a Thursday.
+fn weekday(secs: u64) -> usize {
+    ((secs / 86_400 + 3) % 7) as usize
+}
+
+fn shade(volume: u128, max: u128) -> char {
+    if volume == 0 || max == 0 {
+        return SHADES[0];
+    }
+    // Any volume at all gets at least the lightest shade.
+    let level = (volume * (SHADES.len() as u128 - 1)).div_ceil(max);
+    SHADES[level as usize]
+}
+
+/// Build an [`ActivityReport`] for `market_id` from its closed trades since
+/// `since_ms`, plus one spread snapshot of the live book.
+///
+/// The trades stream has no end-of-history marker, so the replay is
+/// treated as complete once no trade has arrived for
+/// [`DEFAULT_REPLAY_QUIET`].
+pub async fn activity_report(
+    url: String,
+    market_id: String,
+    since_ms: u64,
+) -> Result<ActivityReport> {
+    let (mut rx, handle) = stream_trades_channel(
+        url.clone(),
+        StreamTradesOptions {
+            market_id: market_id.clone(),
+            historical_closed_trades: true,
+            filter_by_trader: None,
+            grpc: None,
+            replay: HistoricalReplay::since(since_ms),
+            idle: None,
+            reconnect: None,
+        },
+    )
+    .await?;
+
+    let mut report = ActivityReport::new(since_ms);
+    while let Ok(Some(trade)) = tokio::time::timeout(DEFAULT_REPLAY_QUIET, rx.recv()).await {
+        report.add_trade(&trade);
+    }
+    handle.abort();
+
+    let top = fetch_top_of_book(url, market_id, DEFAULT_BOOK_WINDOW).await?;
+    report.add_spread(&top);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(timestamp_ms: u64, qty: &str, maker: &str, taker: &str) -> Trade {
+        Trade {
+            timestamp: timestamp_ms,
+            qty: qty.to_string(),
+            maker_base_address: maker.to_string(),
+            taker_base_address: taker.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn buckets_trades_by_hour_day_and_trader() {
+        // 2023-11-14 22:13:20 UTC, a Tuesday.
+        let t = 1_700_000_000_000;
+        let mut report = ActivityReport::new(t - 1_000);
+        report.add_trade(&trade(t, "1000000", "0xa", "0xb"));
+        report.add_trade(&trade(t + 60_000, "500000", "0xa", "0xc"));
+        report.add_trade(&trade(t + 7_200_000, "250000", "0xc", "0xb"));
+        // Before the window.
+        report.add_trade(&trade(t - 5_000, "9", "0xa", "0xb"));
+
+        assert_eq!(
+            report.total(),
+            Activity {
+                trades: 3,
+                volume: 1_750_000
+            }
+        );
+        let daily = report.daily();
+        assert_eq!(daily["2023-11-14"].trades, 2);
+        assert_eq!(daily["2023-11-15"].trades, 1);
+
+        let grid = report.heatmap();
+        assert_eq!(grid[1][22], 1_500_000);
+        assert_eq!(grid[2][0], 250_000);
+
+        let top = report.top_traders(2);
+        assert_eq!(top[0].0, "0xa");
+        assert_eq!(top[0].1.volume, 1_500_000);
+        assert_eq!(top.len(), 2);
+
+        report.add_spread(&TopOfBook {
+            best_bid: Some(99),
+            best_ask: Some(101),
+        });
+        assert_eq!(report.average_spread_bps(), Some(200.0));
+
+        let csv = report.to_csv(6);
+        assert!(csv.contains("2023-11-14 22:00:00,Tue,2,1.5"), "{csv}");
+        let text = report.display(6, 10);
+        assert!(text.contains("3 trades"), "{text}");
+        assert!(text.contains("Tue"), "{text}");
+    }
+}
@@ -0,0 +1,271 @@
+//! ERC-20 allowances: how much of a token a spender may take from a wallet.
+//!
+//! An ERC-20 deposit pulls the tokens with `transferFrom`, so the trade
+//! contract needs an allowance first. [`call_deposit_from_config_with_wallet`]
+//! approves just enough on its own; [`get_allowance`] and [`approve`] are for
+//! managing approvals directly: approving once for many deposits, or
+//! revoking with an amount of zero.
+//!
+//! Deposits only go through the trade contract today. [`Spender::Permit2`]
+//! is the canonical Permit2 contract, which earlier trade contracts pulled
+//! tokens through, so old approvals to it can be inspected and revoked.
+//!
+//! [`call_deposit_from_config_with_wallet`]: super::deposit::call_deposit_from_config_with_wallet
+
+use std::fmt;
+use std::str::FromStr;
+
+use alloy::network::EthereumWallet;
+use alloy::primitives::{Address, U256, address};
+use alloy::providers::ProviderBuilder;
+use url::Url;
+
+use crate::chain::tx::{StuckPolicy, legacy_gas_price, reserve_nonce, wait_for_inclusion};
+use crate::chain_client::ARCH_SOLANA;
+use crate::commands::config::config_pb::{Chain, GetConfigResponse, Token};
+use crate::error::{AspensError, Result};
+use crate::evm::rpc::IERC20;
+use crate::signer::AspensSigner;
+use crate::util::parse_address;
+
+/// The canonical Permit2 deployment, at the same address on every EVM chain.
+pub const PERMIT2_ADDRESS: Address = address!("0x000000000022D473030F116dDEE9F6B43aC78BA3");
+
+/// Who an allowance is granted to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Spender {
+    /// The chain's trade contract, which takes deposits.
+    #[default]
+    TradeContract,
+    /// The canonical Permit2 contract ([`PERMIT2_ADDRESS`]).
+    Permit2,
+}
+
+impl Spender {
+    /// The spender's address on `chain`.
+    pub fn address(self, chain: &Chain) -> Result<Address> {
+        match self {
+            Spender::TradeContract => {
+                let contract = chain
+                    .trade_contract
+                    .as_ref()
+                    .filter(|tc| !tc.address.is_empty())
+                    .ok_or_else(|| {
+                        AspensError::not_found(format!(
+                            "Trade contract not found for chain '{}'",
+                            chain.network
+                        ))
+                    })?;
+                Ok(parse_address(&contract.address)?)
+            }
+            Spender::Permit2 => Ok(PERMIT2_ADDRESS),
+        }
+    }
+}
+
+impl fmt::Display for Spender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Spender::TradeContract => "trade-contract",
+            Spender::Permit2 => "permit2",
+        })
+    }
+}
+
+impl FromStr for Spender {
+    type Err = AspensError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "trade-contract" | "trade" | "contract" => Ok(Spender::TradeContract),
+            "permit2" => Ok(Spender::Permit2),
+            _ => Err(AspensError::invalid_input(format!(
+                "unknown spender '{}': expected trade-contract or permit2",
+                s
+            ))),
+        }
+    }
+}
+
+/// `amount` base units of a token with `decimals` places, as a decimal
+/// string; [`U256::MAX`], the usual "unlimited" approval, reads
+/// `unlimited`.
+pub fn format_allowance(amount: U256, decimals: u32) -> String {
+    if amount == U256::MAX {
+        return "unlimited".to_string();
+    }
+    match u128::try_from(amount) {
+        Ok(raw) => crate::decimals::format_decimal_amount(raw, decimals),
+        Err(_) => format!("{} base units", amount),
+    }
+}
+
+/// How much of `token_symbol` on `network` `spender` may take from `owner`,
+/// in base units.
+pub async fn get_allowance(
+    network: String,
+    token_symbol: String,
+    owner: Address,
+    spender: Spender,
+    config: GetConfigResponse,
+) -> Result<U256> {
+    let (chain, token) = erc20_target(&config, &network, &token_symbol)?;
+    let token_addr = super::hedera::token_address(token)?;
+    let spender_addr = spender.address(chain)?;
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+    IERC20::new(token_addr, &provider)
+        .allowance(owner, spender_addr)
+        .call()
+        .await
+        .map_err(|e| {
+            AspensError::chain(format!(
+                "Failed to read the {} allowance of {} for {}: {}",
+                token_symbol, owner, spender, e
+            ))
+        })
+}
+
+/// Let `spender` take `amount` base units of `token_symbol` on `network`
+/// from the wallet, replacing any earlier allowance. Pass [`U256::MAX`] for
+/// an unlimited approval, or zero to revoke.
+///
+/// Returns the transaction hash once the approval is mined.
+pub async fn approve(
+    network: String,
+    token_symbol: String,
+    amount: U256,
+    spender: Spender,
+    wallet: &dyn AspensSigner,
+    config: GetConfigResponse,
+) -> Result<String> {
+    let (chain, token) = erc20_target(&config, &network, &token_symbol)?;
+    let token_addr = super::hedera::token_address(token)?;
+    let spender_addr = spender.address(chain)?;
+    let signer = wallet.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(format!(
+            "approving on chain '{}' needs a secp256k1 wallet that signs transactions",
+            network
+        ))
+    })?;
+    super::hedera::ensure_associated(chain, token, signer.address()).await?;
+
+    tracing::info!(
+        "Approving {} for {} base units of {} on {}",
+        spender,
+        amount,
+        token_symbol,
+        network
+    );
+    let from = signer.address();
+    let provider = ProviderBuilder::new()
+        .with_chain_id(chain.chain_id as u64)
+        .wallet(EthereumWallet::new(signer))
+        .connect_http(Url::parse(&chain.rpc_url)?);
+    let gas_price = legacy_gas_price(&provider, &network, chain.chain_id.into()).await?;
+    let nonce = reserve_nonce(&provider, chain.chain_id.into(), from).await?;
+    let contract = IERC20::new(token_addr, &provider);
+    let call = contract.approve(spender_addr, amount).nonce(nonce.nonce());
+    let call = match gas_price {
+        Some(price) => call.gas_price(price),
+        None => call,
+    };
+    let pending = call.send().await.map_err(|e| {
+        AspensError::chain(format!(
+            "Failed to send approve() for {}: {}",
+            token_symbol, e
+        ))
+    })?;
+    nonce.commit();
+    let receipt =
+        wait_for_inclusion(&provider, *pending.tx_hash(), StuckPolicy::from_env()).await?;
+    if !receipt.status() {
+        return Err(AspensError::chain(format!(
+            "approve() for {} reverted in {}",
+            token_symbol, receipt.transaction_hash
+        )));
+    }
+    Ok(format!("{:?}", receipt.transaction_hash))
+}
+
+/// The EVM chain and ERC-20 token an allowance is about. Native assets
+/// have no allowance, and Solana has delegates instead.
+fn erc20_target<'a>(
+    config: &'a GetConfigResponse,
+    network: &str,
+    token_symbol: &str,
+) -> Result<(&'a Chain, &'a Token)> {
+    let chain = config.get_chain(network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) {
+        return Err(AspensError::invalid_input(format!(
+            "allowances are EVM-only; '{}' is a Solana chain",
+            network
+        )));
+    }
+    let token = config.get_token(network, token_symbol).ok_or_else(|| {
+        let available_tokens = chain
+            .tokens
+            .keys()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        AspensError::not_found(format!(
+            "Token '{}' not found on chain '{}'. Available tokens: {}",
+            token_symbol, network, available_tokens
+        ))
+    })?;
+    if crate::evm::is_native_token(&token.address) {
+        return Err(AspensError::invalid_input(format!(
+            "{} is the native asset on '{}' and needs no approval",
+            token_symbol, network
+        )));
+    }
+    Ok((chain, token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::config_pb::TradeContract;
+
+    #[test]
+    fn spenders_resolve_per_chain() {
+        let mut chain = Chain {
+            network: "anvil-1".into(),
+            ..Default::default()
+        };
+        assert_eq!(Spender::Permit2.address(&chain).unwrap(), PERMIT2_ADDRESS);
+        assert!(matches!(
+            Spender::TradeContract.address(&chain),
+            Err(AspensError::NotFound(_))
+        ));
+
+        chain.trade_contract = Some(TradeContract {
+            contract_id: None,
+            address: "0x5FbDB2315678afecb367f032d93F642f64180aa3".into(),
+        });
+        assert_eq!(
+            Spender::TradeContract.address(&chain).unwrap(),
+            address!("0x5FbDB2315678afecb367f032d93F642f64180aa3")
+        );
+
+        assert_eq!("permit2".parse::<Spender>().unwrap(), Spender::Permit2);
+        assert_eq!(
+            "Trade_Contract".parse::<Spender>().unwrap(),
+            Spender::TradeContract
+        );
+        assert!("router".parse::<Spender>().is_err());
+    }
+
+    #[test]
+    fn allowances_format_in_token_units() {
+        assert_eq!(format_allowance(U256::MAX, 6), "unlimited");
+        assert_eq!(format_allowance(U256::from(1_500_000u64), 6), "1.500000");
+        assert_eq!(format_allowance(U256::ZERO, 18), "0.000000000000000000");
+        assert_eq!(
+            format_allowance(U256::from(u128::MAX) + U256::from(1u8), 6),
+            "340282366920938463463374607431768211456 base units"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Periodic batch-auction queries.
+//!
+//! On stacks that run batch auctions, orders sent with `auction = true`
+//! queue until the market's next clear and then all fill at one uniform
+//! clearing price. [`get_auction_schedule`] reports a market's cadence and
+//! [`get_auction_results`] its recent clears; markets that match
+//! continuously report `enabled = false`.
+
+/// Generated protobuf bindings for the `arborter.v1` trading service.
+#[allow(missing_docs)]
+pub mod arborter_pb {
+    include!("../../../proto/generated/xyz.aspens.arborter.v1.rs");
+}
+
+use std::fmt;
+
+use arborter_pb::arborter_service_client::ArborterServiceClient;
+use arborter_pb::{AuctionResult, AuctionResultsRequest, AuctionSchedule, AuctionScheduleRequest};
+
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::commands::trading::send_order::lookup_market;
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+use crate::grpc::create_channel;
+use crate::util::format_utc;
+
+/// How many recent clears [`auction_status`] fetches.
+pub const DEFAULT_RESULTS_LIMIT: u32 = 10;
+
+/// A market's auction schedule and recent clears, ready for display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuctionStatus {
+    /// Market name from the config.
+    pub market_name: String,
+    /// Pair decimals used to format prices and quantities.
+    pub pair_decimals: u32,
+    /// The market's auction cadence.
+    pub schedule: AuctionSchedule,
+    /// Recent clears, newest first.
+    pub results: Vec<AuctionResult>,
+}
+
+/// Fetch the batch-auction schedule for `market_id` (any form
+/// [`lookup_market`] accepts).
+pub async fn get_auction_schedule(
+    url: String,
+    market_id: &str,
+    config: &GetConfigResponse,
+) -> Result<AuctionSchedule> {
+    let market = lookup_market(config, market_id)?;
+    let channel = create_channel(&url).await?;
+    let mut client = ArborterServiceClient::new(channel);
+    let response = client
+        .get_auction_schedule(AuctionScheduleRequest {
+            market_id: market.market_id.clone(),
+        })
+        .await?;
+    Ok(response.into_inner())
+}
+
+/// Fetch up to `limit` recent batch-auction clears for `market_id`, newest
+/// first. A `limit` of 0 lets the server pick.
+pub async fn get_auction_results(
+    url: String,
+    market_id: &str,
+    config: &GetConfigResponse,
+    limit: u32,
+) -> Result<Vec<AuctionResult>> {
+    let market = lookup_market(config, market_id)?;
+    let channel = create_channel(&url).await?;
+    let mut client = ArborterServiceClient::new(channel);
+    let response = client
+        .get_auction_results(AuctionResultsRequest {
+            market_id: market.market_id.clone(),
+            limit,
+        })
+        .await?;
+    Ok(response.into_inner().results)
+}
+
+/// Fetch the schedule and the last [`DEFAULT_RESULTS_LIMIT`] clears for
+/// `market_id`.
+pub async fn auction_status(
+    url: String,
+    market_id: &str,
+    config: &GetConfigResponse,
+) -> Result<AuctionStatus> {
+    let market = lookup_market(config, market_id)?;
+    let schedule = get_auction_schedule(url.clone(), market_id, config).await?;
+    let results = if schedule.enabled {
+        get_auction_results(url, market_id, config, DEFAULT_RESULTS_LIMIT).await?
+    } else {
+        Vec::new()
+    };
+    Ok(AuctionStatus {
+        market_name: market.name.clone(),
+        pair_decimals: market.pair_decimals as u32,
+        schedule,
+        results,
+    })
+}
+
+impl fmt::Display for AuctionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = &self.schedule;
+        if !s.enabled {
+            return write!(
+                f,
+                "{}: continuous matching (no batch auctions)",
+                self.market_name
+            );
+        }
+        writeln!(
+            f,
+            "{}: batch auction every {}s, next clear at {} UTC ({} order(s) queued)",
+            self.market_name,
+            s.interval_secs,
+            format_utc(s.next_auction_at),
+            s.pending_orders
+        )?;
+        if self.results.is_empty() {
+            return write!(f, "  no recent clears");
+        }
+        let amount = |raw: &str| {
+            raw.parse::<u128>()
+                .map(|v| format_decimal_amount(v, self.pair_decimals))
+                .unwrap_or_else(|_| raw.to_string())
+        };
+        for (i, r) in self.results.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "  #{} at {} UTC: ",
+                r.auction_id,
+                format_utc(r.cleared_at)
+            )?;
+            if r.clearing_price.is_empty() {
+                write!(f, "no cross")?;
+            } else {
+                write!(
+                    f,
+                    "{} @ {} ({} order(s) filled)",
+                    amount(&r.matched_quantity),
+                    amount(&r.clearing_price),
+                    r.orders_filled
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_schedule_and_clears() {
+        let mut status = AuctionStatus {
+            market_name: "WETH/USDC".into(),
+            pair_decimals: 2,
+            schedule: AuctionSchedule {
+                market_id: "m".into(),
+                enabled: true,
+                interval_secs: 60,
+                next_auction_at: 1_699_833_660,
+                pending_orders: 3,
+            },
+            results: vec![
+                AuctionResult {
+                    auction_id: 8,
+                    cleared_at: 1_699_833_600,
+                    clearing_price: "250050".into(),
+                    matched_quantity: "150".into(),
+                    orders_filled: 4,
+                },
+                AuctionResult {
+                    auction_id: 7,
+                    cleared_at: 1_699_833_540,
+                    ..Default::default()
+                },
+            ],
+        };
+        assert_eq!(
+            status.to_string(),
+            "WETH/USDC: batch auction every 60s, next clear at 2023-11-13 00:01:00 UTC \
+             (3 order(s) queued)\n  \
+             #8 at 2023-11-13 00:00:00 UTC: 1.50 @ 2500.50 (4 order(s) filled)\n  \
+             #7 at 2023-11-12 23:59:00 UTC: no cross"
+        );
+
+        status.schedule.enabled = false;
+        assert_eq!(
+            status.to_string(),
+            "WETH/USDC: continuous matching (no batch auctions)"
+        );
+    }
+}
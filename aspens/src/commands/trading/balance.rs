@@ -1,47 +1,273 @@
-use alloy::primitives::{Address, Uint};
+use alloy::primitives::{Address, I256, U256, Uint};
 use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::{Filter, Log};
 use alloy_chains::NamedChain;
+use alloy_sol_types::SolEvent;
 use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
-use eyre::Result;
+use futures::{StreamExt, stream};
+use serde::Serialize;
 use std::collections::HashMap;
-use tracing::{info, warn};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 use url::Url;
 
 use crate::chain_client::{ARCH_SOLANA, ChainClient};
 use crate::commands::config::config_pb::{Chain, Configuration, GetConfigResponse};
+use crate::error::{AspensError, Result};
 use crate::evm::rpc::{IERC20, MidribV3};
+use crate::signer::AspensSigner;
+use crate::util::csv_row;
 #[cfg(test)]
 use crate::wallet::CurveType;
-use crate::wallet::Wallet;
 
-/// Represents a unique token across all chains
-#[derive(Debug, Clone)]
-struct TokenInfo {
-    symbol: String,
-    decimals: u32,
+/// A unique token symbol across all chains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenInfo {
+    /// Token symbol (e.g. `"USDC"`).
+    pub symbol: String,
+    /// Native decimals of the first chain that lists this symbol.
+    pub decimals: u32,
 }
 
-/// Balance information for a token on a specific chain
-#[derive(Debug)]
-struct ChainBalance {
-    chain_network: String,
-    wallet_balance: String,
-    available_balance: String,
-    locked_balance: String,
+/// Balance information for a token on a specific chain.
+///
+/// Values are raw base-unit integers rendered as strings, or a short status
+/// (`"error"`, `"no wallet"`, `"not deployed"`, …) when the query failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainBalance {
+    /// Network name of the chain.
+    pub chain_network: String,
+    /// Balance held in the trader's wallet.
+    pub wallet_balance: String,
+    /// Balance deposited in the trade contract and available to trade.
+    pub available_balance: String,
+    /// Balance locked by open orders (always `"0"` on EVM — optimistic ledger).
+    pub locked_balance: String,
+}
+
+/// Native gas token balance for a chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NativeBalance {
+    /// Network name of the chain.
+    pub chain_network: String,
+    /// Raw base-unit balance as a string, or a short status on failure.
+    pub balance: String,
+}
+
+/// Aggregated balance for a single token across all chains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenBalance {
+    /// The token these balances are for.
+    pub token_info: TokenInfo,
+    /// One entry per chain that lists the token.
+    pub chain_balances: Vec<ChainBalance>,
+}
+
+/// Every balance [`balance_from_config_with_wallets`] displays, as data.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BalanceSnapshot {
+    /// Token balances, sorted by symbol.
+    pub tokens: Vec<TokenBalance>,
+    /// Native gas balances, one per chain in config order.
+    pub native: Vec<NativeBalance>,
+}
+
+/// Symbol of the native gas balance rows in a [`BalanceReport`].
+pub const GAS_SYMBOL: &str = "GAS";
+
+/// Decimals native gas balances are shown with.
+const GAS_DECIMALS: u32 = 18;
+
+/// Environment variable holding the gas balance below which a chain is
+/// flagged as low, in native-token units (e.g. `0.01`). `0` turns the
+/// warning off.
+pub const LOW_GAS_THRESHOLD_ENV: &str = "ASPENS_LOW_GAS_THRESHOLD";
+
+/// The low-gas threshold when [`LOW_GAS_THRESHOLD_ENV`] is unset.
+pub const DEFAULT_LOW_GAS_THRESHOLD: &str = "0.001";
+
+/// The low-gas threshold in base units: [`LOW_GAS_THRESHOLD_ENV`], else
+/// [`DEFAULT_LOW_GAS_THRESHOLD`]. An unparseable value is warned about and
+/// the default used.
+pub fn low_gas_threshold() -> u128 {
+    let default = || {
+        crate::decimals::parse_decimal_amount(DEFAULT_LOW_GAS_THRESHOLD, GAS_DECIMALS)
+            .unwrap_or_default()
+    };
+    match std::env::var(LOW_GAS_THRESHOLD_ENV) {
+        Ok(value) => crate::decimals::parse_decimal_amount(value.trim(), GAS_DECIMALS)
+            .unwrap_or_else(|e| {
+                warn!("Ignoring {}={}: {}", LOW_GAS_THRESHOLD_ENV, value, e);
+                default()
+            }),
+        Err(_) => default(),
+    }
 }
 
-/// Native gas token balance for a chain
-#[derive(Debug)]
-struct NativeBalance {
-    chain_network: String,
-    balance: String,
+/// Which balances [`fetch_balance_report`] queries. The default selects
+/// every chain and token.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceFilter {
+    /// Only the chain with this network name (e.g. `"base-sepolia"`).
+    pub network: Option<String>,
+    /// Only this token symbol, case-insensitively. Gas rows are left out
+    /// when a token is given.
+    pub token: Option<String>,
+}
+
+impl BalanceFilter {
+    /// Drop the chains and tokens this filter doesn't select from `config`,
+    /// so they aren't queried. Errors with
+    /// [`NotFound`](AspensError::NotFound) when nothing is left.
+    pub fn apply(&self, config: &mut Configuration) -> Result<()> {
+        if let Some(network) = &self.network {
+            config.chains.retain(|chain| chain.network == *network);
+            if config.chains.is_empty() {
+                return Err(AspensError::not_found(format!(
+                    "Chain '{}' not found in configuration",
+                    network
+                )));
+            }
+        }
+        if let Some(token) = &self.token {
+            for chain in &mut config.chains {
+                chain
+                    .tokens
+                    .retain(|symbol, _| symbol.eq_ignore_ascii_case(token));
+            }
+            if config.chains.iter().all(|chain| chain.tokens.is_empty()) {
+                return Err(AspensError::not_found(format!(
+                    "Token '{}' not found in configuration",
+                    token
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One row of a [`BalanceReport`]: a token, or the native gas token
+/// ([`GAS_SYMBOL`]), on one chain.
+///
+/// Amounts are in token units with all `decimals` places (`"1.500000"`),
+/// or a short status (`"error"`, `"no wallet"`, …) when the query failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BalanceRow {
+    /// Network name of the chain.
+    pub network: String,
+    /// Token symbol, or [`GAS_SYMBOL`].
+    pub token: String,
+    /// Decimals the amounts are scaled by.
+    pub decimals: u32,
+    /// Balance held in the trader's wallet.
+    pub wallet: String,
+    /// Balance deposited and available to trade; `None` for gas.
+    pub deposited: Option<String>,
+    /// Balance locked by open orders; `None` for gas.
+    pub locked: Option<String>,
+    /// True for a gas row below the low-gas threshold.
+    pub low_gas: bool,
+}
+
+/// A [`BalanceSnapshot`] as flat rows, for printing or export: grouped by
+/// network in name order, each network's gas row first, then its tokens
+/// by symbol.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BalanceReport {
+    /// The rows, in display order.
+    pub balances: Vec<BalanceRow>,
+}
+
+impl From<&BalanceSnapshot> for BalanceReport {
+    /// Flags gas rows below [`low_gas_threshold`].
+    fn from(snapshot: &BalanceSnapshot) -> Self {
+        BalanceReport::from_snapshot(snapshot, low_gas_threshold())
+    }
 }
 
-/// Aggregated balance for a single token across all chains
-#[derive(Debug)]
-struct TokenBalance {
-    token_info: TokenInfo,
-    chain_balances: Vec<ChainBalance>,
+impl BalanceReport {
+    /// The rows of `snapshot`, flagging gas balances below
+    /// `low_gas_threshold` (in base units; `0` flags none).
+    pub fn from_snapshot(snapshot: &BalanceSnapshot, low_gas_threshold: u128) -> Self {
+        let mut networks: Vec<&str> = Vec::new();
+        for token in &snapshot.tokens {
+            for chain in &token.chain_balances {
+                if !networks.contains(&chain.chain_network.as_str()) {
+                    networks.push(&chain.chain_network);
+                }
+            }
+        }
+        networks.sort();
+
+        let mut balances = Vec::new();
+        for network in networks {
+            if let Some(native) = snapshot.native.iter().find(|n| n.chain_network == network) {
+                balances.push(BalanceRow {
+                    network: network.to_string(),
+                    token: GAS_SYMBOL.to_string(),
+                    decimals: GAS_DECIMALS,
+                    wallet: format_balance_with_decimals(&native.balance, GAS_DECIMALS),
+                    deposited: None,
+                    locked: None,
+                    low_gas: native
+                        .balance
+                        .parse::<u128>()
+                        .is_ok_and(|balance| balance < low_gas_threshold),
+                });
+            }
+            for token in &snapshot.tokens {
+                let Some(chain) = token
+                    .chain_balances
+                    .iter()
+                    .find(|c| c.chain_network == network)
+                else {
+                    continue;
+                };
+                let decimals = token.token_info.decimals;
+                balances.push(BalanceRow {
+                    network: network.to_string(),
+                    token: token.token_info.symbol.clone(),
+                    decimals,
+                    wallet: format_balance_with_decimals(&chain.wallet_balance, decimals),
+                    deposited: Some(format_balance_with_decimals(
+                        &chain.available_balance,
+                        decimals,
+                    )),
+                    locked: Some(format_balance_with_decimals(
+                        &chain.locked_balance,
+                        decimals,
+                    )),
+                    low_gas: false,
+                });
+            }
+        }
+        BalanceReport { balances }
+    }
+
+    /// The gas rows flagged as low.
+    pub fn low_gas(&self) -> impl Iterator<Item = &BalanceRow> {
+        self.balances.iter().filter(|row| row.low_gas)
+    }
+
+    /// The rows as CSV, with a
+    /// `network,token,decimals,wallet,deposited,locked,low_gas` header. Gas
+    /// rows leave `deposited` and `locked` empty.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("network,token,decimals,wallet,deposited,locked,low_gas\n");
+        for row in &self.balances {
+            out.push_str(&csv_row([
+                row.network.as_str(),
+                row.token.as_str(),
+                row.decimals.to_string().as_str(),
+                row.wallet.as_str(),
+                row.deposited.as_deref().unwrap_or_default(),
+                row.locked.as_deref().unwrap_or_default(),
+                row.low_gas.to_string().as_str(),
+            ]));
+            out.push('\n');
+        }
+        out
+    }
 }
 
 /// Extract all unique tokens from configuration chains
@@ -84,25 +310,20 @@ fn format_balance_with_decimals(balance_str: &str, decimals: u32) -> String {
     }
 }
 
-/// Display all token balances in a single table grouped by chain
-fn display_all_token_balances(
-    all_token_balances: &[TokenBalance],
-    native_balances: &[NativeBalance],
-) -> String {
-    if all_token_balances.is_empty() {
-        return String::new();
-    }
+/// Render a [`BalanceSnapshot`] as the table
+/// [`balance_from_config_with_wallets`] logs: one section per chain with its
+/// gas balance and tokens, amounts scaled by each token's decimals. Empty
+/// when the snapshot has no tokens.
+pub fn display_balances(snapshot: &BalanceSnapshot) -> String {
+    display_balance_report(&BalanceReport::from(snapshot))
+}
 
-    // Collect all unique chain networks across all tokens and sort them
-    let mut all_chains: Vec<String> = Vec::new();
-    for token_balance in all_token_balances {
-        for chain_balance in &token_balance.chain_balances {
-            if !all_chains.contains(&chain_balance.chain_network) {
-                all_chains.push(chain_balance.chain_network.clone());
-            }
-        }
+/// Render a [`BalanceReport`] as the balance table. Empty when the report
+/// has no rows.
+pub fn display_balance_report(report: &BalanceReport) -> String {
+    if report.balances.is_empty() {
+        return String::new();
     }
-    all_chains.sort();
 
     let mut output = String::new();
     output.push('\n');
@@ -120,52 +341,33 @@ fn display_all_token_balances(
     // Set header: Token, Wallet, Deposited, Locked
     table.set_header(vec!["Token", "Wallet", "Deposited", "Locked"]);
 
-    // Group by chain - add rows for each chain section
-    for (chain_idx, chain) in all_chains.iter().enumerate() {
-        // Add chain header row (spans conceptually as a section header)
-        if chain_idx > 0 {
-            // Add separator row between chains
-            table.add_row(vec!["", "", "", ""]);
-        }
-
-        // Add chain name as a section header
-        table.add_row(vec![
-            format!("── {} ──", chain),
-            String::new(),
-            String::new(),
-            String::new(),
-        ]);
-
-        // Add native gas balance for this chain
-        if let Some(native) = native_balances.iter().find(|nb| nb.chain_network == *chain) {
-            let gas_balance = format_balance_with_decimals(&native.balance, 18);
+    // Rows come grouped by chain; start a section at each new one.
+    let mut current: Option<&str> = None;
+    for row in &report.balances {
+        if current != Some(row.network.as_str()) {
+            if current.is_some() {
+                // Add separator row between chains
+                table.add_row(vec!["", "", "", ""]);
+            }
             table.add_row(vec![
-                "GAS".to_string(),
-                gas_balance,
+                format!("── {} ──", row.network),
+                String::new(),
                 String::new(),
                 String::new(),
             ]);
+            current = Some(&row.network);
         }
-
-        // Add tokens for this chain
-        for token_balance in all_token_balances {
-            // Find if this token exists on this chain
-            if let Some(chain_balance) = token_balance
-                .chain_balances
-                .iter()
-                .find(|cb| cb.chain_network == *chain)
-            {
-                let decimals = token_balance.token_info.decimals;
-                let symbol = &token_balance.token_info.symbol;
-
-                let wallet = format_balance_with_decimals(&chain_balance.wallet_balance, decimals);
-                let deposited =
-                    format_balance_with_decimals(&chain_balance.available_balance, decimals);
-                let locked = format_balance_with_decimals(&chain_balance.locked_balance, decimals);
-
-                table.add_row(vec![symbol.clone(), wallet, deposited, locked]);
-            }
-        }
+        let wallet = if row.low_gas {
+            format!("{} (low)", row.wallet)
+        } else {
+            row.wallet.clone()
+        };
+        table.add_row(vec![
+            row.token.clone(),
+            wallet,
+            row.deposited.clone().unwrap_or_default(),
+            row.locked.clone().unwrap_or_default(),
+        ]);
     }
 
     output.push_str(&table.to_string());
@@ -296,7 +498,7 @@ async fn query_token_balance_via_client(
                     NamedChain::try_from(chain.chain_id as u64).unwrap_or(NamedChain::BaseSepolia);
                 // Reuse existing EVM helpers — they need a privkey to derive the address,
                 // but we already have the address. Use *_for_address variants.
-                let owner: Address = match owner_address.parse() {
+                let owner = match crate::util::parse_address(owner_address) {
                     Ok(a) => a,
                     Err(_) => {
                         return ChainBalance {
@@ -359,15 +561,18 @@ async fn query_token_balance_via_client(
 ///   balances are scaffolded as "not deployed" until the on-chain program lands
 pub async fn balance_from_config_with_wallet(
     config: GetConfigResponse,
-    wallet: &Wallet,
-) -> Result<()> {
+    wallet: &dyn AspensSigner,
+) -> Result<BalanceReport> {
     balance_from_config_with_wallets(config, &[wallet]).await
 }
 
 /// Pick the first wallet matching `chain`'s curve. Returns `None` when no
 /// caller-supplied wallet matches — the chain's rows are reported as
 /// "no wallet" rather than erroring out.
-fn select_wallet_for_chain<'a>(chain: &Chain, wallets: &'a [&'a Wallet]) -> Option<&'a Wallet> {
+fn select_wallet_for_chain<'a>(
+    chain: &Chain,
+    wallets: &'a [&'a dyn AspensSigner],
+) -> Option<&'a dyn AspensSigner> {
     let wanted = crate::wallet::chain_curve(chain);
     wallets.iter().copied().find(|w| w.curve() == wanted)
 }
@@ -377,101 +582,208 @@ fn select_wallet_for_chain<'a>(chain: &Chain, wallets: &'a [&'a Wallet]) -> Opti
 /// Each chain in the config gets matched to a wallet with the same curve
 /// (EVM chains → `Wallet::Evm`, Solana chains → `Wallet::Solana`). A chain
 /// with no matching wallet is reported as "no wallet" rather than failing
-/// the whole call.
+/// the whole call. Logs the balance table and returns it as a
+/// [`BalanceReport`].
 pub async fn balance_from_config_with_wallets(
     config: GetConfigResponse,
-    wallets: &[&Wallet],
-) -> Result<()> {
+    wallets: &[&dyn AspensSigner],
+) -> Result<BalanceReport> {
+    let report = fetch_balance_report(config, wallets, &BalanceFilter::default()).await?;
+
+    if report.balances.is_empty() {
+        info!("No tokens found in configuration");
+        return Ok(report);
+    }
+
+    info!("{}", display_balance_report(&report));
+
+    Ok(report)
+}
+
+/// The balances `filter` selects, as a [`BalanceReport`]. Chains and tokens
+/// outside the filter aren't queried.
+pub async fn fetch_balance_report(
+    mut config: GetConfigResponse,
+    wallets: &[&dyn AspensSigner],
+    filter: &BalanceFilter,
+) -> Result<BalanceReport> {
+    if let Some(configuration) = config.config.as_mut() {
+        filter.apply(configuration)?;
+    }
+    let mut snapshot = fetch_balances_with_wallets(config, wallets).await?;
+    if filter.token.is_some() {
+        snapshot.native.clear();
+    }
+    let report = BalanceReport::from(&snapshot);
+    for row in report.low_gas() {
+        warn!(
+            "Low gas on {}: {} left; fund the wallet before it can't pay for transactions",
+            row.network, row.wallet
+        );
+    }
+    Ok(report)
+}
+
+/// Most balance queries [`fetch_balances_with_wallets`] has in flight at
+/// once, so a config with many chains doesn't flood their RPC endpoints.
+pub const MAX_CONCURRENT_BALANCE_QUERIES: usize = 8;
+
+/// One RPC round trip [`fetch_balances_with_wallets`] makes. It owns what
+/// it queries, so the fan-out future borrows nothing but the wallets.
+enum BalanceQuery {
+    /// The token with this symbol, at this index in the sorted token list,
+    /// on `chain`.
+    Token(usize, String, Chain),
+    /// The native gas balance on `chain`.
+    Native(Chain),
+}
+
+/// The answer to a [`BalanceQuery`].
+enum BalanceAnswer {
+    Token(usize, ChainBalance),
+    Native(NativeBalance),
+}
+
+/// Collect the balances [`balance_from_config_with_wallets`] displays
+/// without rendering them.
+///
+/// Every (token, chain) pair and every chain's gas balance is queried
+/// concurrently, at most [`MAX_CONCURRENT_BALANCE_QUERIES`] at a time; each
+/// query's latency is logged at debug level. Per-chain failures are
+/// reported in-band as status strings, so this only errors when `config`
+/// carries no configuration at all.
+pub async fn fetch_balances_with_wallets(
+    config: GetConfigResponse,
+    wallets: &[&dyn AspensSigner],
+) -> Result<BalanceSnapshot> {
     let configuration = config
         .config
-        .ok_or_else(|| eyre::eyre!("No configuration found in response"))?;
+        .ok_or_else(|| AspensError::not_found("No configuration found in response"))?;
 
-    let tokens = extract_all_tokens_from_config(&configuration);
+    let mut tokens: Vec<TokenInfo> = extract_all_tokens_from_config(&configuration)
+        .into_values()
+        .collect();
 
     if tokens.is_empty() {
-        info!("No tokens found in configuration");
-        return Ok(());
+        return Ok(BalanceSnapshot::default());
     }
 
     info!("Found {} unique token(s) across all chains", tokens.len());
+    tokens.sort_by(|a, b| a.symbol.cmp(&b.symbol));
 
-    let mut all_token_balances: Vec<TokenBalance> = Vec::new();
-
-    for (symbol, token_info) in tokens {
-        let mut chain_balances = Vec::new();
-
+    let mut queries = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
         for chain in &configuration.chains {
-            if !chain.tokens.contains_key(&symbol) {
-                continue;
+            if chain.tokens.contains_key(&token.symbol) {
+                queries.push(BalanceQuery::Token(
+                    index,
+                    token.symbol.clone(),
+                    chain.clone(),
+                ));
             }
-            let cb = match select_wallet_for_chain(chain, wallets) {
-                Some(w) => query_token_balance_via_client(chain, &symbol, &w.address()).await,
-                None => ChainBalance {
-                    chain_network: chain.network.clone(),
-                    wallet_balance: "no wallet".to_string(),
-                    available_balance: "no wallet".to_string(),
-                    locked_balance: "no wallet".to_string(),
-                },
-            };
-            chain_balances.push(cb);
         }
-
-        all_token_balances.push(TokenBalance {
-            token_info: token_info.clone(),
-            chain_balances,
-        });
+    }
+    // Native gas balances, once per network
+    let mut seen: Vec<&str> = Vec::new();
+    for chain in &configuration.chains {
+        if !seen.contains(&chain.network.as_str()) {
+            seen.push(&chain.network);
+            queries.push(BalanceQuery::Native(chain.clone()));
+        }
     }
 
-    all_token_balances.sort_by(|a, b| a.token_info.symbol.cmp(&b.token_info.symbol));
+    let started = Instant::now();
+    let query_count = queries.len();
+    let answers: Vec<BalanceAnswer> = stream::iter(queries)
+        .map(|query| run_balance_query(query, wallets))
+        .buffered(MAX_CONCURRENT_BALANCE_QUERIES)
+        .collect()
+        .await;
+    debug!(
+        "{} balance queries took {:?}",
+        query_count,
+        started.elapsed()
+    );
 
-    // Native gas balances per chain
+    let mut all_token_balances: Vec<TokenBalance> = tokens
+        .into_iter()
+        .map(|token_info| TokenBalance {
+            token_info,
+            chain_balances: Vec::new(),
+        })
+        .collect();
     let mut native_balances: Vec<NativeBalance> = Vec::new();
-    let mut seen: HashMap<String, ()> = HashMap::new();
-    for chain in &configuration.chains {
-        if seen.contains_key(&chain.network) {
-            continue;
+    for answer in answers {
+        match answer {
+            BalanceAnswer::Token(index, cb) => all_token_balances[index].chain_balances.push(cb),
+            BalanceAnswer::Native(nb) => native_balances.push(nb),
         }
-        seen.insert(chain.network.clone(), ());
+    }
 
-        let owner_address = match select_wallet_for_chain(chain, wallets) {
-            Some(w) => w.address(),
-            None => {
-                native_balances.push(NativeBalance {
-                    chain_network: chain.network.clone(),
-                    balance: "no wallet".to_string(),
-                });
-                continue;
-            }
-        };
+    Ok(BalanceSnapshot {
+        tokens: all_token_balances,
+        native: native_balances,
+    })
+}
 
-        let client = match ChainClient::from_chain_config(chain) {
-            Ok(c) => c,
-            Err(e) => {
-                warn!("Failed to build client for {}: {}", chain.network, e);
-                native_balances.push(NativeBalance {
+/// Run one [`BalanceQuery`] with the wallet matching its chain, logging
+/// how long it took.
+async fn run_balance_query(query: BalanceQuery, wallets: &[&dyn AspensSigner]) -> BalanceAnswer {
+    let started = Instant::now();
+    match query {
+        BalanceQuery::Token(index, symbol, chain) => {
+            let cb = match select_wallet_for_chain(&chain, wallets) {
+                Some(w) => query_token_balance_via_client(&chain, &symbol, &w.address()).await,
+                None => ChainBalance {
                     chain_network: chain.network.clone(),
-                    balance: "error".to_string(),
-                });
-                continue;
-            }
-        };
-        let balance = client.native_balance(&owner_address).await.map_or_else(
-            |e| {
-                warn!("Failed to get native balance on {}: {}", chain.network, e);
-                "error".to_string()
-            },
-            |v| v.to_string(),
-        );
-        native_balances.push(NativeBalance {
-            chain_network: chain.network.clone(),
-            balance,
-        });
+                    wallet_balance: "no wallet".to_string(),
+                    available_balance: "no wallet".to_string(),
+                    locked_balance: "no wallet".to_string(),
+                },
+            };
+            debug!(
+                "{} balance on {} in {:?}",
+                symbol,
+                chain.network,
+                started.elapsed()
+            );
+            BalanceAnswer::Token(index, cb)
+        }
+        BalanceQuery::Native(chain) => {
+            let balance = query_native_balance(&chain, wallets).await;
+            debug!(
+                "Gas balance on {} in {:?}",
+                chain.network,
+                started.elapsed()
+            );
+            BalanceAnswer::Native(NativeBalance {
+                chain_network: chain.network.clone(),
+                balance,
+            })
+        }
     }
+}
 
-    let output = display_all_token_balances(&all_token_balances, &native_balances);
-    info!("{}", output);
-
-    Ok(())
+/// The native gas balance on `chain` of the wallet matching it, or a
+/// status string.
+async fn query_native_balance(chain: &Chain, wallets: &[&dyn AspensSigner]) -> String {
+    let Some(wallet) = select_wallet_for_chain(chain, wallets) else {
+        return "no wallet".to_string();
+    };
+    let client = match ChainClient::from_chain_config(chain) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("Failed to build client for {}: {}", chain.network, e);
+            return "error".to_string();
+        }
+    };
+    client.native_balance(&wallet.address()).await.map_or_else(
+        |e| {
+            warn!("Failed to get native balance on {}: {}", chain.network, e);
+            "error".to_string()
+        },
+        |v| v.to_string(),
+    )
 }
 
 /// Read the trader's available trade balance from MidribV3's
@@ -483,8 +795,8 @@ pub async fn call_get_balance_for_address(
     contract_address: &str,
     depositer_address: Address,
 ) -> Result<Uint<256, 4>> {
-    let contract_addr: Address = contract_address.parse()?;
-    let token_addr: Address = token_address.parse()?;
+    let contract_addr = crate::util::parse_address(contract_address)?;
+    let token_addr = crate::util::parse_address(token_address)?;
     let rpc_url = Url::parse(rpc_url)?;
     let provider = ProviderBuilder::new()
         .with_chain(chain)
@@ -529,7 +841,7 @@ pub async fn call_get_erc20_balance_for_address(
     token_address: &str,
     holder: Address,
 ) -> Result<Uint<256, 4>> {
-    let token_addr: Address = token_address.parse()?;
+    let token_addr = crate::util::parse_address(token_address)?;
     let rpc_url = Url::parse(rpc_url)?;
     let provider = ProviderBuilder::new().connect_http(rpc_url);
     let contract = IERC20::new(token_addr, &provider);
@@ -555,9 +867,214 @@ pub fn format_balance(value: Uint<256, 4>, decimals: u32) -> String {
     }
 }
 
+/// How often [`watch_balances`] polls each chain for new Midrib events.
+pub const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(4);
+
+/// Largest block range requested in one `eth_getLogs` call; public RPCs
+/// commonly reject wider ranges.
+pub(crate) const MAX_LOG_BLOCK_RANGE: u64 = 2_000;
+
+/// Which Midrib event produced a [`BalanceDelta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceChangeKind {
+    /// `TokenDeposited` — wallet funds moved into the trade contract.
+    Deposit,
+    /// `Withdrawn` — a voucher withdrawal moved funds back to the wallet.
+    Withdraw,
+    /// `NetSettled` — the arborter settled net trade results on-chain.
+    Settlement,
+}
+
+/// A change to a trader's deposited balance, decoded from a Midrib event.
+///
+/// There is no lock event: under the optimistic ledger order reservations
+/// never touch the chain, so only deposits, withdrawals and net settlement
+/// move the on-chain balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceDelta {
+    /// Network name of the chain the event was emitted on.
+    pub chain_network: String,
+    /// Token symbol from the config, or the token address if it isn't listed.
+    pub token_symbol: String,
+    /// Token contract address as emitted by the event.
+    pub token_address: String,
+    /// The event that produced this delta.
+    pub kind: BalanceChangeKind,
+    /// Signed change in base units (negative for withdrawals).
+    pub delta: I256,
+    /// Block the event was included in.
+    pub block_number: u64,
+    /// Transaction hash, when the RPC reports it.
+    pub tx_hash: Option<String>,
+}
+
+/// Decode a Midrib log into a [`BalanceDelta`].
+///
+/// Returns `None` for events that don't move a trader balance, or that fail
+/// to decode.
+pub(crate) fn delta_from_log(chain: &Chain, log: &Log) -> Option<BalanceDelta> {
+    let (kind, token, delta) = match log.topic0()? {
+        t if *t == MidribV3::TokenDeposited::SIGNATURE_HASH => {
+            let ev = log
+                .log_decode::<MidribV3::TokenDeposited>()
+                .ok()?
+                .inner
+                .data;
+            let amount = I256::try_from(U256::from(ev.amount)).ok()?;
+            (BalanceChangeKind::Deposit, ev.tokenContract, amount)
+        }
+        t if *t == MidribV3::Withdrawn::SIGNATURE_HASH => {
+            let ev = log.log_decode::<MidribV3::Withdrawn>().ok()?.inner.data;
+            let amount = I256::try_from(ev.amount).ok()?;
+            (BalanceChangeKind::Withdraw, ev.tokenContract, -amount)
+        }
+        t if *t == MidribV3::NetSettled::SIGNATURE_HASH => {
+            let ev = log.log_decode::<MidribV3::NetSettled>().ok()?.inner.data;
+            (BalanceChangeKind::Settlement, ev.tokenContract, ev.delta)
+        }
+        _ => return None,
+    };
+
+    let token_symbol = chain
+        .tokens
+        .iter()
+        .find(|(_, t)| crate::util::parse_address(&t.address).is_ok_and(|address| address == token))
+        .map(|(symbol, _)| symbol.clone())
+        .unwrap_or_else(|| token.to_string());
+
+    Some(BalanceDelta {
+        chain_network: chain.network.clone(),
+        token_symbol,
+        token_address: token.to_string(),
+        kind,
+        delta,
+        block_number: log.block_number.unwrap_or_default(),
+        tx_hash: log.transaction_hash.map(|h| h.to_string()),
+    })
+}
+
+/// An EVM chain [`watch_balances`] follows, with its log cursor.
+struct WatchedChain {
+    chain: Chain,
+    contract: Address,
+    /// First block not yet scanned; `None` until the first poll pins it to
+    /// the chain head, so only events after the watch starts are reported.
+    next_block: Option<u64>,
+}
+
+/// Fetch the Midrib events for `owner` that landed since the last poll.
+async fn poll_chain(watched: &mut WatchedChain, owner: Address) -> Result<Vec<BalanceDelta>> {
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&watched.chain.rpc_url)?);
+    let head = provider.get_block_number().await?;
+    let from = *watched.next_block.get_or_insert(head + 1);
+    if head < from {
+        return Ok(Vec::new());
+    }
+    let to = head.min(from + MAX_LOG_BLOCK_RANGE - 1);
+
+    let filter = Filter::new()
+        .address(watched.contract)
+        .event_signature(vec![
+            MidribV3::TokenDeposited::SIGNATURE_HASH,
+            MidribV3::Withdrawn::SIGNATURE_HASH,
+            MidribV3::NetSettled::SIGNATURE_HASH,
+        ])
+        .topic1(owner.into_word())
+        .from_block(from)
+        .to_block(to);
+    let mut logs = provider.get_logs(&filter).await?;
+    logs.sort_by_key(|l| (l.block_number, l.log_index));
+    watched.next_block = Some(to + 1);
+
+    Ok(logs
+        .iter()
+        .filter_map(|log| delta_from_log(&watched.chain, log))
+        .collect())
+}
+
+/// Stream changes to `address`'s deposited balances as they land on-chain.
+///
+/// Follows the `TokenDeposited`, `Withdrawn` and `NetSettled` events of every
+/// EVM chain's trade contract in `config`, calling `callback` once per event
+/// in block order. Only events emitted after the call starts are reported —
+/// pair it with [`fetch_balances_with_wallets`] for the starting point.
+///
+/// This is polling, not a subscription: the SDK's alloy build has no
+/// pub-sub transport, so there is no `ws://` / `wss://` path. Each chain is
+/// polled with `eth_getLogs` every [`WATCH_POLL_INTERVAL`] over its HTTP
+/// `rpc_url`, the endpoint the balance queries use, so deltas arrive up to
+/// one interval after their block. Solana chains and chains without a
+/// deployed trade contract are skipped. A failed poll is logged and retried
+/// from the same block on the next tick, so no events are lost to transient
+/// RPC errors.
+///
+/// Runs until the future is dropped; errors only if nothing can be watched.
+pub async fn watch_balances<F>(
+    config: GetConfigResponse,
+    address: &str,
+    mut callback: F,
+) -> Result<()>
+where
+    F: FnMut(BalanceDelta),
+{
+    let configuration = config
+        .config
+        .ok_or_else(|| AspensError::not_found("No configuration found in response"))?;
+    let owner = crate::util::parse_address(address)?;
+
+    let mut watched: Vec<WatchedChain> = Vec::new();
+    for chain in configuration.chains {
+        if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) {
+            debug!(
+                "Not watching {}: Solana balance events are not supported",
+                chain.network
+            );
+            continue;
+        }
+        let contract = chain
+            .trade_contract
+            .as_ref()
+            .and_then(|tc| crate::util::parse_address(&tc.address).ok());
+        match contract {
+            Some(contract) => watched.push(WatchedChain {
+                chain,
+                contract,
+                next_block: None,
+            }),
+            None => debug!("Not watching {}: no trade contract deployed", chain.network),
+        }
+    }
+    if watched.is_empty() {
+        return Err(AspensError::not_found(
+            "no EVM chain with a deployed trade contract to watch",
+        ));
+    }
+    info!(
+        "Watching balances of {} on {} chain(s)",
+        owner,
+        watched.len()
+    );
+
+    let mut ticker = tokio::time::interval(WATCH_POLL_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        for chain in &mut watched {
+            match poll_chain(chain, owner).await {
+                Ok(deltas) => deltas.into_iter().for_each(&mut callback),
+                Err(e) => warn!(
+                    "Balance watch poll failed on {}: {}",
+                    chain.chain.network, e
+                ),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wallet::Wallet;
 
     // -- format_balance_with_decimals ------------------------------------
 
@@ -662,7 +1179,7 @@ mod tests {
     fn select_wallet_for_chain_routes_evm_to_secp256k1() {
         let w = evm_wallet();
         let chain = evm_chain();
-        let wallets: [&Wallet; 1] = [&w];
+        let wallets: [&dyn AspensSigner; 1] = [&w];
         let picked = select_wallet_for_chain(&chain, &wallets);
         assert!(picked.is_some());
         assert_eq!(picked.unwrap().curve(), CurveType::Secp256k1);
@@ -673,7 +1190,7 @@ mod tests {
     fn select_wallet_for_chain_routes_solana_to_ed25519() {
         let w = solana_wallet();
         let chain = solana_chain();
-        let wallets: [&Wallet; 1] = [&w];
+        let wallets: [&dyn AspensSigner; 1] = [&w];
         let picked = select_wallet_for_chain(&chain, &wallets);
         assert!(picked.is_some());
         assert_eq!(picked.unwrap().curve(), CurveType::Ed25519);
@@ -689,12 +1206,12 @@ mod tests {
         let sol = solana_wallet();
         let sol_addr = sol.address();
 
-        let mixed_a: [&Wallet; 2] = [&evm, &sol];
+        let mixed_a: [&dyn AspensSigner; 2] = [&evm, &sol];
         let pick = select_wallet_for_chain(&solana_chain(), &mixed_a).unwrap();
         assert_eq!(pick.curve(), CurveType::Ed25519);
         assert_eq!(pick.address(), sol_addr);
 
-        let mixed_b: [&Wallet; 2] = [&sol, &evm];
+        let mixed_b: [&dyn AspensSigner; 2] = [&sol, &evm];
         let pick = select_wallet_for_chain(&evm_chain(), &mixed_b).unwrap();
         assert_eq!(pick.curve(), CurveType::Secp256k1);
     }
@@ -704,7 +1221,273 @@ mod tests {
         // EVM-only caller, Solana chain → no match (reported as
         // "no wallet" downstream rather than erroring out).
         let w = evm_wallet();
-        let wallets: [&Wallet; 1] = [&w];
+        let wallets: [&dyn AspensSigner; 1] = [&w];
         assert!(select_wallet_for_chain(&solana_chain(), &wallets).is_none());
     }
+
+    // -- BalanceFilter / BalanceReport ----------------------------------
+
+    fn chain_with(network: &str, symbols: &[&str]) -> Chain {
+        Chain {
+            network: network.to_string(),
+            tokens: symbols
+                .iter()
+                .map(|s| (s.to_string(), Default::default()))
+                .collect(),
+            ..evm_chain()
+        }
+    }
+
+    #[test]
+    fn balance_filter_prunes_chains_and_tokens() {
+        let config = Configuration {
+            chains: vec![
+                chain_with("base-sepolia", &["USDC", "WETH"]),
+                chain_with("anvil-1", &["USDC"]),
+            ],
+            ..Default::default()
+        };
+
+        let mut pruned = config.clone();
+        BalanceFilter {
+            network: Some("base-sepolia".into()),
+            token: Some("usdc".into()),
+        }
+        .apply(&mut pruned)
+        .unwrap();
+        assert_eq!(pruned.chains.len(), 1);
+        assert_eq!(
+            pruned.chains[0].tokens.keys().collect::<Vec<_>>(),
+            vec!["USDC"]
+        );
+
+        let err = BalanceFilter {
+            network: Some("mainnet".into()),
+            token: None,
+        }
+        .apply(&mut config.clone())
+        .unwrap_err();
+        assert!(matches!(err, AspensError::NotFound(_)));
+        let err = BalanceFilter {
+            network: None,
+            token: Some("DOGE".into()),
+        }
+        .apply(&mut config.clone())
+        .unwrap_err();
+        assert!(matches!(err, AspensError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn concurrent_queries_land_under_their_token_and_chain() {
+        // No wallets: every query answers "no wallet" without touching an
+        // RPC, which is enough to check the answers are regrouped in order.
+        let config = GetConfigResponse {
+            config: Some(Configuration {
+                chains: vec![
+                    chain_with("base-sepolia", &["WETH", "USDC"]),
+                    chain_with("anvil-1", &["USDC"]),
+                    chain_with("base-sepolia", &["USDC"]),
+                ],
+                ..Default::default()
+            }),
+        };
+        let snapshot = fetch_balances_with_wallets(config, &[]).await.unwrap();
+
+        let tokens: Vec<_> = snapshot
+            .tokens
+            .iter()
+            .map(|t| {
+                let networks: Vec<_> = t
+                    .chain_balances
+                    .iter()
+                    .map(|c| c.chain_network.as_str())
+                    .collect();
+                (t.token_info.symbol.as_str(), networks)
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                ("USDC", vec!["base-sepolia", "anvil-1", "base-sepolia"]),
+                ("WETH", vec!["base-sepolia"]),
+            ]
+        );
+        let native: Vec<_> = snapshot
+            .native
+            .iter()
+            .map(|n| (n.chain_network.as_str(), n.balance.as_str()))
+            .collect();
+        assert_eq!(
+            native,
+            vec![("base-sepolia", "no wallet"), ("anvil-1", "no wallet")]
+        );
+    }
+
+    fn chain_balance(network: &str, wallet: &str) -> ChainBalance {
+        ChainBalance {
+            chain_network: network.to_string(),
+            wallet_balance: wallet.to_string(),
+            available_balance: "0".to_string(),
+            locked_balance: "0".to_string(),
+        }
+    }
+
+    #[test]
+    fn balance_report_groups_rows_by_network_gas_first() {
+        let token = |symbol: &str, chains| TokenBalance {
+            token_info: TokenInfo {
+                symbol: symbol.to_string(),
+                decimals: 6,
+            },
+            chain_balances: chains,
+        };
+        let snapshot = BalanceSnapshot {
+            tokens: vec![
+                token(
+                    "USDC",
+                    vec![
+                        chain_balance("base-sepolia", "1500000"),
+                        chain_balance("anvil-1", "error"),
+                    ],
+                ),
+                token("USDT", vec![chain_balance("anvil-1", "1")]),
+            ],
+            native: vec![NativeBalance {
+                chain_network: "anvil-1".to_string(),
+                balance: "1000000000000000000".to_string(),
+            }],
+        };
+
+        let report = BalanceReport::from_snapshot(&snapshot, 0);
+        let rows: Vec<_> = report
+            .balances
+            .iter()
+            .map(|r| (r.network.as_str(), r.token.as_str(), r.wallet.as_str()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("anvil-1", "GAS", "1.000000000000000000"),
+                ("anvil-1", "USDC", "error"),
+                ("anvil-1", "USDT", "0.000001"),
+                ("base-sepolia", "USDC", "1.500000"),
+            ]
+        );
+        assert_eq!(report.balances[0].deposited, None);
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("network,token,decimals,wallet,deposited,locked,low_gas")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("anvil-1,GAS,18,1.000000000000000000,,,false")
+        );
+        assert_eq!(
+            lines.last(),
+            Some("base-sepolia,USDC,6,1.500000,0.000000,0.000000,false")
+        );
+
+        let table = display_balance_report(&report);
+        assert!(table.contains("── anvil-1 ──"));
+        assert!(table.contains("── base-sepolia ──"));
+        assert!(display_balance_report(&BalanceReport::default()).is_empty());
+
+        // 1 native token left is low against a threshold of 2.
+        let low = BalanceReport::from_snapshot(&snapshot, 2_000_000_000_000_000_000);
+        let flagged: Vec<_> = low.low_gas().map(|r| r.network.as_str()).collect();
+        assert_eq!(flagged, vec!["anvil-1"]);
+        assert!(display_balance_report(&low).contains("1.000000000000000000 (low)"));
+    }
+
+    // -- delta_from_log --------------------------------------------------
+
+    fn log_for<E: SolEvent>(event: &E, block: u64) -> Log {
+        Log {
+            inner: alloy::primitives::Log {
+                address: Address::ZERO,
+                data: event.encode_log_data(),
+            },
+            block_number: Some(block),
+            ..Default::default()
+        }
+    }
+
+    fn chain_with_usdc(usdc: Address) -> Chain {
+        let mut chain = evm_chain();
+        chain.network = "base-sepolia".to_string();
+        chain.tokens.insert(
+            "USDC".to_string(),
+            crate::commands::config::config_pb::Token {
+                symbol: "USDC".to_string(),
+                address: usdc.to_string(),
+                decimals: 6,
+                ..Default::default()
+            },
+        );
+        chain
+    }
+
+    #[test]
+    fn delta_from_log_decodes_deposit_withdraw_and_settlement() {
+        let user = Address::repeat_byte(0x11);
+        let usdc = Address::repeat_byte(0x22);
+        let chain = chain_with_usdc(usdc);
+
+        let deposit = MidribV3::TokenDeposited {
+            user,
+            tokenContract: usdc,
+            amount: Uint::from(1_500_000u64),
+        };
+        let d = delta_from_log(&chain, &log_for(&deposit, 7)).unwrap();
+        assert_eq!(d.kind, BalanceChangeKind::Deposit);
+        assert_eq!(d.token_symbol, "USDC");
+        assert_eq!(d.delta, I256::try_from(1_500_000i64).unwrap());
+        assert_eq!(d.block_number, 7);
+        assert_eq!(d.chain_network, "base-sepolia");
+
+        let withdraw = MidribV3::Withdrawn {
+            account: user,
+            tokenContract: usdc,
+            amount: U256::from(500_000u64),
+            nonce: U256::from(1u64),
+        };
+        let d = delta_from_log(&chain, &log_for(&withdraw, 8)).unwrap();
+        assert_eq!(d.kind, BalanceChangeKind::Withdraw);
+        assert_eq!(d.delta, I256::try_from(-500_000i64).unwrap());
+
+        let settled = MidribV3::NetSettled {
+            user,
+            tokenContract: usdc,
+            delta: I256::try_from(-42i64).unwrap(),
+        };
+        let d = delta_from_log(&chain, &log_for(&settled, 9)).unwrap();
+        assert_eq!(d.kind, BalanceChangeKind::Settlement);
+        assert_eq!(d.delta, I256::try_from(-42i64).unwrap());
+    }
+
+    #[test]
+    fn delta_from_log_falls_back_to_address_for_unlisted_tokens() {
+        let unlisted = Address::repeat_byte(0x33);
+        let chain = chain_with_usdc(Address::repeat_byte(0x22));
+        let deposit = MidribV3::TokenDeposited {
+            user: Address::repeat_byte(0x11),
+            tokenContract: unlisted,
+            amount: Uint::from(1u64),
+        };
+        let d = delta_from_log(&chain, &log_for(&deposit, 1)).unwrap();
+        assert_eq!(d.token_symbol, unlisted.to_string());
+    }
+
+    #[test]
+    fn delta_from_log_ignores_unrelated_events() {
+        let chain = chain_with_usdc(Address::repeat_byte(0x22));
+        let fee = MidribV3::OperatorFeeSet {
+            recipient: Address::repeat_byte(0x44),
+            bps: 5,
+        };
+        assert!(delta_from_log(&chain, &log_for(&fee, 1)).is_none());
+    }
 }
@@ -0,0 +1,395 @@
+//! Bounded buffering between a stream and a slow consumer.
+//!
+//! The channel variants of the orderbook and trades streams block the
+//! stream when their consumer falls behind, which stalls it without saying
+//! so. A [`BufferedReceiver`] holds at most `capacity` events and applies an
+//! [`OverflowPolicy`] when full: wait for the consumer, drop the oldest
+//! event, or coalesce an order's updates so only its latest state is
+//! queued. [`BufferedReceiver::stats`] reports what was dropped.
+//!
+//! Use it through `stream_orderbook_buffered` / `stream_trades_buffered`.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+
+use super::stream_orderbook::arborter_pb::OrderbookEntry;
+use super::stream_trades::arborter_pb::Trade;
+
+/// Events buffered by default.
+pub const DEFAULT_BUFFER_CAPACITY: usize = 1_024;
+
+/// What a full buffer does with a new event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Wait for the consumer to make room. Nothing is lost, but the stream
+    /// stalls behind a slow consumer.
+    #[default]
+    Block,
+    /// Drop the oldest queued event.
+    DropOldest,
+    /// Replace a queued event the new one supersedes (an earlier update to
+    /// the same order); drop the oldest if there is none.
+    Coalesce,
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverflowPolicy::Block => write!(f, "block"),
+            OverflowPolicy::DropOldest => write!(f, "drop-oldest"),
+            OverflowPolicy::Coalesce => write!(f, "coalesce"),
+        }
+    }
+}
+
+impl std::str::FromStr for OverflowPolicy {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s {
+            "block" => Ok(OverflowPolicy::Block),
+            "drop-oldest" => Ok(OverflowPolicy::DropOldest),
+            "coalesce" => Ok(OverflowPolicy::Coalesce),
+            _ => Err(eyre::eyre!(
+                "unknown overflow policy '{}': expected block, drop-oldest or coalesce",
+                s
+            )),
+        }
+    }
+}
+
+/// Buffer size and overflow policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferOptions {
+    /// Most events held at once; at least 1.
+    pub capacity: usize,
+    /// What to do when full.
+    pub policy: OverflowPolicy,
+}
+
+impl Default for BufferOptions {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_BUFFER_CAPACITY,
+            policy: OverflowPolicy::Block,
+        }
+    }
+}
+
+/// Events that can replace an earlier, queued event under
+/// [`OverflowPolicy::Coalesce`].
+pub trait Coalesce {
+    /// True if `self` makes the queued `older` event redundant.
+    fn supersedes(&self, older: &Self) -> bool;
+}
+
+/// An order's newer state replaces its older one: consumers that render
+/// the book only need the latest.
+impl Coalesce for OrderbookEntry {
+    fn supersedes(&self, older: &Self) -> bool {
+        self.order_id == older.order_id && self.market_id == older.market_id
+    }
+}
+
+/// Every trade is distinct, so coalescing trades falls back to dropping the
+/// oldest.
+impl Coalesce for Trade {
+    fn supersedes(&self, _older: &Self) -> bool {
+        false
+    }
+}
+
+/// Counters for one buffer, as a snapshot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferStats {
+    /// Events received from the stream.
+    pub received: u64,
+    /// Events handed to the consumer.
+    pub delivered: u64,
+    /// Events dropped to make room.
+    pub dropped: u64,
+    /// Events replaced by a newer update to the same order.
+    pub coalesced: u64,
+    /// Times the stream waited on the consumer ([`OverflowPolicy::Block`]).
+    pub blocked: u64,
+    /// Most events queued at once.
+    pub high_water: usize,
+}
+
+impl BufferStats {
+    /// True if any event was lost to a full buffer.
+    pub fn lost_events(&self) -> bool {
+        self.dropped > 0 || self.coalesced > 0
+    }
+}
+
+impl fmt::Display for BufferStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} received, {} delivered, {} dropped, {} coalesced, {} blocked, peak depth {}",
+            self.received,
+            self.delivered,
+            self.dropped,
+            self.coalesced,
+            self.blocked,
+            self.high_water
+        )
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    received: AtomicU64,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    coalesced: AtomicU64,
+    blocked: AtomicU64,
+    high_water: AtomicUsize,
+}
+
+struct Shared<T> {
+    queue: Mutex<VecDeque<T>>,
+    options: BufferOptions,
+    counters: Counters,
+    /// The stream has ended; the consumer drains what's left.
+    closed: AtomicBool,
+    /// The consumer is gone; new events are discarded.
+    abandoned: AtomicBool,
+    item_ready: Notify,
+    space_ready: Notify,
+}
+
+/// Producer half, fed by the stream task.
+pub(crate) struct BufferSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Consumer half of a bounded stream buffer.
+pub struct BufferedReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Create a connected sender / receiver pair.
+pub(crate) fn buffer<T>(options: BufferOptions) -> (BufferSender<T>, BufferedReceiver<T>) {
+    let options = BufferOptions {
+        capacity: options.capacity.max(1),
+        ..options
+    };
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(
+            options.capacity.min(DEFAULT_BUFFER_CAPACITY),
+        )),
+        options,
+        counters: Counters::default(),
+        closed: AtomicBool::new(false),
+        abandoned: AtomicBool::new(false),
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+    });
+    (
+        BufferSender {
+            shared: shared.clone(),
+        },
+        BufferedReceiver { shared },
+    )
+}
+
+impl<T: Coalesce> BufferSender<T> {
+    /// Queue `item`, applying the overflow policy if the buffer is full.
+    pub(crate) async fn push(&self, item: T) {
+        let shared = &*self.shared;
+        let counters = &shared.counters;
+        counters.received.fetch_add(1, Ordering::Relaxed);
+        let mut item = Some(item);
+        loop {
+            if shared.abandoned.load(Ordering::Acquire) {
+                return;
+            }
+            {
+                let mut queue = shared.queue.lock().unwrap_or_else(|e| e.into_inner());
+                if queue.len() < shared.options.capacity {
+                    queue.push_back(item.take().expect("pushed once"));
+                    counters
+                        .high_water
+                        .fetch_max(queue.len(), Ordering::Relaxed);
+                    drop(queue);
+                    shared.item_ready.notify_one();
+                    return;
+                }
+                let new = item.take().expect("pushed once");
+                let policy = shared.options.policy;
+                if policy == OverflowPolicy::Block {
+                    item = Some(new);
+                } else {
+                    if policy == OverflowPolicy::Coalesce
+                        && let Some(old) = queue.iter_mut().rev().find(|q| new.supersedes(q))
+                    {
+                        *old = new;
+                        counters.coalesced.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    queue.pop_front();
+                    queue.push_back(new);
+                    if counters.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                        tracing::warn!(
+                            "stream consumer is falling behind; dropping events ({})",
+                            policy
+                        );
+                    }
+                    drop(queue);
+                    shared.item_ready.notify_one();
+                    return;
+                }
+            }
+            counters.blocked.fetch_add(1, Ordering::Relaxed);
+            shared.space_ready.notified().await;
+        }
+    }
+}
+
+impl<T> Drop for BufferSender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.item_ready.notify_one();
+    }
+}
+
+impl<T> BufferedReceiver<T> {
+    /// The next event, waiting for one if the buffer is empty. `None` once
+    /// the stream has ended and the buffer is drained.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(item) = self.try_recv() {
+                return Some(item);
+            }
+            if self.shared.closed.load(Ordering::Acquire) {
+                // The stream may have pushed its last event just before
+                // closing.
+                return self.try_recv();
+            }
+            self.shared.item_ready.notified().await;
+        }
+    }
+
+    /// The next event if one is queued, without waiting.
+    pub fn try_recv(&mut self) -> Option<T> {
+        let item = self
+            .shared
+            .queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()?;
+        self.shared
+            .counters
+            .delivered
+            .fetch_add(1, Ordering::Relaxed);
+        self.shared.space_ready.notify_one();
+        Some(item)
+    }
+
+    /// Events currently queued.
+    pub fn len(&self) -> usize {
+        self.shared
+            .queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// True when nothing is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The buffer's size and policy.
+    pub fn options(&self) -> BufferOptions {
+        self.shared.options
+    }
+
+    /// A snapshot of the buffer's counters.
+    pub fn stats(&self) -> BufferStats {
+        let c = &self.shared.counters;
+        BufferStats {
+            received: c.received.load(Ordering::Relaxed),
+            delivered: c.delivered.load(Ordering::Relaxed),
+            dropped: c.dropped.load(Ordering::Relaxed),
+            coalesced: c.coalesced.load(Ordering::Relaxed),
+            blocked: c.blocked.load(Ordering::Relaxed),
+            high_water: c.high_water.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<T> Drop for BufferedReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.abandoned.store(true, Ordering::Release);
+        self.shared.space_ready.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(order_id: u64, quantity: &str) -> OrderbookEntry {
+        OrderbookEntry {
+            order_id,
+            quantity: quantity.into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn overflow_policies_drop_or_coalesce() {
+        let options = |policy| BufferOptions {
+            capacity: 2,
+            policy,
+        };
+
+        let (tx, mut rx) = buffer(options(OverflowPolicy::DropOldest));
+        for id in 1..=3 {
+            tx.push(entry(id, "1")).await;
+        }
+        drop(tx);
+        assert_eq!(rx.recv().await.unwrap().order_id, 2);
+        assert_eq!(rx.recv().await.unwrap().order_id, 3);
+        assert!(rx.recv().await.is_none());
+        let stats = rx.stats();
+        assert_eq!((stats.received, stats.delivered, stats.dropped), (3, 2, 1));
+        assert!(stats.lost_events());
+
+        let (tx, mut rx) = buffer(options(OverflowPolicy::Coalesce));
+        tx.push(entry(1, "5")).await;
+        tx.push(entry(2, "5")).await;
+        tx.push(entry(1, "3")).await;
+        assert_eq!(rx.stats().coalesced, 1);
+        let first = rx.recv().await.unwrap();
+        assert_eq!((first.order_id, first.quantity.as_str()), (1, "3"));
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_the_consumer() {
+        let (tx, mut rx) = buffer(BufferOptions {
+            capacity: 1,
+            policy: OverflowPolicy::Block,
+        });
+        let producer = tokio::spawn(async move {
+            for id in 1..=3 {
+                tx.push(entry(id, "1")).await;
+            }
+        });
+        let mut seen = Vec::new();
+        while let Some(e) = rx.recv().await {
+            seen.push(e.order_id);
+        }
+        producer.await.unwrap();
+        assert_eq!(seen, vec![1, 2, 3]);
+        assert_eq!(rx.stats().dropped, 0);
+        assert_eq!(rx.stats().high_water, 1);
+    }
+}
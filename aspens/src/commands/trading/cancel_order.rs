@@ -8,12 +8,12 @@ use std::fmt;
 
 use arborter_pb::arborter_service_client::ArborterServiceClient;
 use arborter_pb::{CancelOrderRequest, CancelOrderResponse, OrderToCancel, Side};
-use eyre::Result;
-use prost::Message;
 
+use super::signing::{SignedPayload, SigningVersion};
 use crate::commands::config::config_pb::GetConfigResponse;
+use crate::error::{AspensError, Result};
 use crate::grpc::create_channel;
-use crate::wallet::Wallet;
+use crate::signer::AspensSigner;
 
 impl fmt::Display for CancelOrderResponse {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -41,13 +41,37 @@ impl CancelOrderResponse {
 }
 
 /// Cancel an order using a curve-agnostic wallet (EVM or Solana).
+///
+/// Signs with [`SigningVersion::V1`], which every stack verifies; the
+/// config-driven variant negotiates the version with the stack.
 pub async fn call_cancel_order_with_wallet(
     url: String,
     market_id: String,
     side: i32,
     token_address: String,
     order_id: u64,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
+) -> Result<CancelOrderResponse> {
+    cancel_signed(
+        url,
+        OrderToCancel {
+            market_id,
+            side,
+            token_address,
+            order_id,
+        },
+        wallet,
+        SigningVersion::V1,
+    )
+    .await
+}
+
+/// Sign `order_to_cancel` under `signing_version` and send it.
+async fn cancel_signed(
+    url: String,
+    order_to_cancel: OrderToCancel,
+    wallet: &dyn AspensSigner,
+    signing_version: SigningVersion,
 ) -> Result<CancelOrderResponse> {
     // Create a channel to connect to the gRPC server
     let channel = create_channel(&url).await?;
@@ -55,17 +79,8 @@ pub async fn call_cancel_order_with_wallet(
     // Instantiate the client
     let mut client = ArborterServiceClient::new(channel);
 
-    // Create the order to cancel
-    let order_to_cancel = OrderToCancel {
-        market_id,
-        side,
-        token_address,
-        order_id,
-    };
-
     // Serialize for signing
-    let mut buffer = Vec::new();
-    order_to_cancel.encode(&mut buffer)?;
+    let buffer = order_to_cancel.canonical_bytes(signing_version);
 
     // Sign the cancel request. Send the full curve-native length — the
     // arborter's curve-aware verifier requires exactly 64 bytes for
@@ -79,6 +94,7 @@ pub async fn call_cancel_order_with_wallet(
     let request = CancelOrderRequest {
         order: Some(order_to_cancel),
         signature_hash: signature_bytes,
+        signing_version: signing_version.as_u32(),
     };
 
     // Create a tonic request
@@ -109,11 +125,12 @@ pub async fn call_cancel_order_from_config_with_wallet(
     market_id: String,
     side: String,
     order_id: u64,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     config: GetConfigResponse,
 ) -> Result<CancelOrderResponse> {
     // Look up market info
     let market = super::send_order::lookup_market(&config, &market_id)?;
+    let signing_version = SigningVersion::for_config(&config)?;
 
     // Convert side string to Side enum value
     let (side_value, token_address) = match side.to_lowercase().as_str() {
@@ -122,20 +139,19 @@ pub async fn call_cancel_order_from_config_with_wallet(
             let quote_chain = config
                 .get_chain(&market.quote_chain_network)
                 .ok_or_else(|| {
-                    eyre::eyre!(
+                    AspensError::not_found(format!(
                         "Quote chain '{}' not found in configuration",
                         market.quote_chain_network
-                    )
+                    ))
                 })?;
             let token = quote_chain
                 .tokens
                 .get(&market.quote_chain_token_symbol)
                 .ok_or_else(|| {
-                    eyre::eyre!(
+                    AspensError::not_found(format!(
                         "Token '{}' not found on chain '{}'",
-                        market.quote_chain_token_symbol,
-                        market.quote_chain_network
-                    )
+                        market.quote_chain_token_symbol, market.quote_chain_network
+                    ))
                 })?;
             (Side::Bid as i32, token.address.clone())
         }
@@ -144,28 +160,27 @@ pub async fn call_cancel_order_from_config_with_wallet(
             let base_chain = config
                 .get_chain(&market.base_chain_network)
                 .ok_or_else(|| {
-                    eyre::eyre!(
+                    AspensError::not_found(format!(
                         "Base chain '{}' not found in configuration",
                         market.base_chain_network
-                    )
+                    ))
                 })?;
             let token = base_chain
                 .tokens
                 .get(&market.base_chain_token_symbol)
                 .ok_or_else(|| {
-                    eyre::eyre!(
+                    AspensError::not_found(format!(
                         "Token '{}' not found on chain '{}'",
-                        market.base_chain_token_symbol,
-                        market.base_chain_network
-                    )
+                        market.base_chain_token_symbol, market.base_chain_network
+                    ))
                 })?;
             (Side::Ask as i32, token.address.clone())
         }
         _ => {
-            return Err(eyre::eyre!(
+            return Err(AspensError::invalid_input(format!(
                 "Invalid side '{}'. Must be 'buy' or 'sell'",
                 side
-            ));
+            )));
         }
     };
 
@@ -177,13 +192,105 @@ pub async fn call_cancel_order_from_config_with_wallet(
         token_address
     );
 
-    call_cancel_order_with_wallet(
+    cancel_signed(
         url,
-        market.market_id.clone(),
-        side_value,
-        token_address,
-        order_id,
+        OrderToCancel {
+            market_id: market.market_id.clone(),
+            side: side_value,
+            token_address,
+            order_id,
+        },
         wallet,
+        signing_version,
     )
     .await
 }
+
+/// Outcome of [`cancel_all_orders`].
+#[derive(Debug, Default)]
+pub struct CancelAllSummary {
+    /// Orders the stack canceled.
+    pub canceled: Vec<u64>,
+    /// Orders the stack no longer had (filled or canceled meanwhile).
+    pub not_found: Vec<u64>,
+    /// Orders whose cancel failed, with the error.
+    pub failed: Vec<(u64, AspensError)>,
+}
+
+impl CancelAllSummary {
+    /// True if no cancel failed.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl fmt::Display for CancelAllSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} canceled, {} already gone, {} failed",
+            self.canceled.len(),
+            self.not_found.len(),
+            self.failed.len()
+        )?;
+        for (order_id, err) in &self.failed {
+            write!(f, "\n  order {}: {}", order_id, err)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cancel every open order `wallet` has on `market_id`: a kill switch for
+/// market makers.
+///
+/// The arborter has no bulk cancel, so this lists the wallet's resting
+/// orders with [`get_orders`](super::open_orders::get_orders) and sends
+/// one signed cancel per order, all at once. A failed cancel doesn't stop
+/// the others; check [`CancelAllSummary::failed`].
+pub async fn cancel_all_orders(
+    url: String,
+    market_id: String,
+    wallet: &dyn AspensSigner,
+    config: GetConfigResponse,
+) -> Result<CancelAllSummary> {
+    let market = super::send_order::lookup_market(&config, &market_id)?.clone();
+    let orders =
+        super::open_orders::get_orders(url.clone(), market.market_id.clone(), wallet.address())
+            .await?;
+    tracing::info!(
+        "Canceling {} open order(s) on {}",
+        orders.len(),
+        market.name
+    );
+
+    let cancels = orders.iter().map(|order| {
+        let side = match Side::try_from(order.side) {
+            Ok(Side::Bid) => "buy",
+            _ => "sell",
+        };
+        let (url, market_id, config) = (url.clone(), market.market_id.clone(), config.clone());
+        let order_id = order.order_id;
+        async move {
+            let result = call_cancel_order_from_config_with_wallet(
+                url,
+                market_id,
+                side.to_string(),
+                order_id,
+                wallet,
+                config,
+            )
+            .await;
+            (order_id, result)
+        }
+    });
+
+    let mut summary = CancelAllSummary::default();
+    for (order_id, result) in futures::future::join_all(cancels).await {
+        match result {
+            Ok(response) if response.order_canceled => summary.canceled.push(order_id),
+            Ok(_) => summary.not_found.push(order_id),
+            Err(e) => summary.failed.push((order_id, e)),
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,371 @@
+//! OHLCV candles aggregated from the trades stream.
+//!
+//! [`CandleAggregator`] folds trades into fixed-interval buckets aligned to
+//! the unix epoch (a `1m` candle starts on the minute) and hands back each
+//! candle once a trade for a later bucket arrives. [`stream_candles`] runs
+//! it over a trades subscription and also closes a candle when its
+//! interval has passed on the wall clock and no trade has arrived for
+//! [`CLOSE_GRACE`], so a quiet market still gets its last candle on time.
+//! Intervals without trades produce no candle.
+//!
+//! Subscribe with `historical_closed_trades` (bounded by a
+//! [`HistoricalReplay`](super::replay::HistoricalReplay)) to build candles
+//! for the past as well; replayed trades close their candles as the replay
+//! moves on.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use eyre::eyre;
+use futures::{Stream, StreamExt};
+use tokio::time::Instant;
+
+use super::stream_trades::{StreamTradesOptions, arborter_pb::Trade, stream_trades_stream};
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+use crate::util::{csv_row, format_utc, parse_duration, unix_now_ms};
+
+/// How long [`stream_candles`] waits for a late trade after a candle's
+/// interval has ended before closing it.
+pub const CLOSE_GRACE: Duration = Duration::from_millis(500);
+
+/// Header row matching [`Candle::to_csv_row`].
+pub const CSV_HEADER: &str = "start_utc,open,high,low,close,volume,trades";
+
+/// Length of one candle: a whole number of seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CandleInterval {
+    millis: u64,
+}
+
+impl CandleInterval {
+    /// One-second candles.
+    pub const SECOND: Self = Self { millis: 1_000 };
+    /// One-minute candles.
+    pub const MINUTE: Self = Self { millis: 60_000 };
+    /// Five-minute candles.
+    pub const FIVE_MINUTES: Self = Self { millis: 300_000 };
+    /// One-hour candles.
+    pub const HOUR: Self = Self { millis: 3_600_000 };
+
+    /// An interval of `duration`, which must be a non-zero whole number of
+    /// seconds.
+    pub fn new(duration: Duration) -> eyre::Result<Self> {
+        if duration.is_zero() || duration.subsec_nanos() != 0 {
+            return Err(eyre!(
+                "candle interval must be a whole number of seconds, got {:?}",
+                duration
+            ));
+        }
+        Ok(Self {
+            millis: duration.as_millis() as u64,
+        })
+    }
+
+    /// The interval as a [`Duration`].
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.millis)
+    }
+
+    /// Start of the bucket holding `timestamp_ms`.
+    fn bucket(&self, timestamp_ms: u64) -> u64 {
+        timestamp_ms - timestamp_ms % self.millis
+    }
+}
+
+impl FromStr for CandleInterval {
+    type Err = eyre::Report;
+
+    /// Parse `1s`, `1m`, `5m`, `1h` or any other whole-second duration.
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        Self::new(parse_duration(s)?)
+    }
+}
+
+impl fmt::Display for CandleInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.millis / 1_000;
+        match secs {
+            s if s % 3_600 == 0 => write!(f, "{}h", s / 3_600),
+            s if s % 60 == 0 => write!(f, "{}m", s / 60),
+            s => write!(f, "{}s", s),
+        }
+    }
+}
+
+/// Open, high, low, close and volume of one interval. Prices and volume
+/// are raw pair decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candle {
+    /// Start of the interval, unix milliseconds.
+    pub start_ms: u64,
+    /// End of the interval (exclusive), unix milliseconds.
+    pub end_ms: u64,
+    /// Price of the first trade.
+    pub open: u128,
+    /// Highest trade price.
+    pub high: u128,
+    /// Lowest trade price.
+    pub low: u128,
+    /// Price of the last trade.
+    pub close: u128,
+    /// Traded quantity.
+    pub volume: u128,
+    /// Number of trades.
+    pub trades: u64,
+}
+
+impl Candle {
+    fn open_with(start_ms: u64, interval: CandleInterval, price: u128, qty: u128) -> Self {
+        Self {
+            start_ms,
+            end_ms: start_ms + interval.millis,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: qty,
+            trades: 1,
+        }
+    }
+
+    /// One CSV row under [`CSV_HEADER`], amounts scaled by `pair_decimals`.
+    pub fn to_csv_row(&self, pair_decimals: u32) -> String {
+        let scaled = |raw| format_decimal_amount(raw, pair_decimals);
+        csv_row([
+            format_utc(self.start_ms / 1_000),
+            scaled(self.open),
+            scaled(self.high),
+            scaled(self.low),
+            scaled(self.close),
+            scaled(self.volume),
+            self.trades.to_string(),
+        ])
+    }
+
+    /// One aligned line for a terminal, amounts scaled by `pair_decimals`.
+    pub fn display(&self, pair_decimals: u32) -> String {
+        let scaled = |raw| format_decimal_amount(raw, pair_decimals);
+        format!(
+            "{}  O {}  H {}  L {}  C {}  V {}  ({} trades)",
+            format_utc(self.start_ms / 1_000),
+            scaled(self.open),
+            scaled(self.high),
+            scaled(self.low),
+            scaled(self.close),
+            scaled(self.volume),
+            self.trades
+        )
+    }
+}
+
+/// Folds trades into candles of one interval.
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    interval: CandleInterval,
+    current: Option<Candle>,
+}
+
+impl CandleAggregator {
+    /// An aggregator for `interval` candles.
+    pub fn new(interval: CandleInterval) -> Self {
+        Self {
+            interval,
+            current: None,
+        }
+    }
+
+    /// Fold one trade in. Returns the previous candle once `trade` falls
+    /// in a later interval. A trade older than the open candle (delivered
+    /// late) counts towards its high, low and volume but not its open or
+    /// close. Trades with unparseable amounts are ignored.
+    pub fn push(&mut self, trade: &Trade) -> Option<Candle> {
+        let (Ok(price), Ok(qty)) = (trade.price.parse::<u128>(), trade.qty.parse::<u128>()) else {
+            return None;
+        };
+        let start = self.interval.bucket(trade.timestamp);
+        match &mut self.current {
+            Some(candle) if start <= candle.start_ms => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.volume += qty;
+                candle.trades += 1;
+                if start == candle.start_ms {
+                    candle.close = price;
+                }
+                None
+            }
+            current => current.replace(Candle::open_with(start, self.interval, price, qty)),
+        }
+    }
+
+    /// Close the open candle, if any, and hand it back.
+    pub fn flush(&mut self) -> Option<Candle> {
+        self.current.take()
+    }
+
+    /// The candle being built.
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+}
+
+/// Stream completed `interval` candles of a market's trades.
+///
+/// Like [`stream_trades_stream`], nothing connects until the stream is
+/// first polled, an error is yielded once and ends the stream, and the
+/// stream isn't [`Unpin`]. When the trades stream ends, the open candle is
+/// yielded before the stream ends too.
+pub fn stream_candles(
+    url: String,
+    options: StreamTradesOptions,
+    interval: CandleInterval,
+) -> impl Stream<Item = Result<Candle>> + Send + 'static {
+    candles_from_trades(stream_trades_stream(url, options), interval)
+}
+
+/// Aggregate any stream of trades into `interval` candles, with the same
+/// closing rules as [`stream_candles`].
+pub fn candles_from_trades<S>(
+    trades: S,
+    interval: CandleInterval,
+) -> impl Stream<Item = Result<Candle>> + Send + 'static
+where
+    S: Stream<Item = Result<Trade>> + Send + 'static,
+{
+    let state = CandleStreamState {
+        trades: Box::pin(trades),
+        aggregator: CandleAggregator::new(interval),
+        last_arrival: Instant::now(),
+        ended: false,
+    };
+    futures::stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        match state.next().await {
+            Some(Ok(candle)) => Some((Ok(candle), Some(state))),
+            // An error ends the stream.
+            Some(Err(e)) => Some((Err(e), None)),
+            None => None,
+        }
+    })
+}
+
+/// Where [`candles_from_trades`] is between polls.
+struct CandleStreamState<S> {
+    trades: std::pin::Pin<Box<S>>,
+    aggregator: CandleAggregator,
+    /// When the last trade arrived, to give late trades [`CLOSE_GRACE`].
+    last_arrival: Instant,
+    ended: bool,
+}
+
+impl<S: Stream<Item = Result<Trade>>> CandleStreamState<S> {
+    async fn next(&mut self) -> Option<Result<Candle>> {
+        loop {
+            if self.ended {
+                return self.aggregator.flush().map(Ok);
+            }
+            let close_at = self.aggregator.current().map(|candle| {
+                let left = Duration::from_millis(candle.end_ms.saturating_sub(unix_now_ms()));
+                (Instant::now() + left).max(self.last_arrival + CLOSE_GRACE)
+            });
+            let close = async {
+                match close_at {
+                    Some(at) => tokio::time::sleep_until(at).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                trade = self.trades.next() => match trade {
+                    Some(Ok(trade)) => {
+                        self.last_arrival = Instant::now();
+                        if let Some(candle) = self.aggregator.push(&trade) {
+                            return Some(Ok(candle));
+                        }
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => self.ended = true,
+                },
+                _ = close => {
+                    if let Some(candle) = self.aggregator.flush() {
+                        return Some(Ok(candle));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::trade;
+
+    #[test]
+    fn intervals_parse_and_display() {
+        for (s, interval) in [
+            ("1s", CandleInterval::SECOND),
+            ("1m", CandleInterval::MINUTE),
+            ("5m", CandleInterval::FIVE_MINUTES),
+            ("1h", CandleInterval::HOUR),
+        ] {
+            assert_eq!(s.parse::<CandleInterval>().unwrap(), interval);
+            assert_eq!(interval.to_string(), s);
+        }
+        assert!("500ms".parse::<CandleInterval>().is_err());
+        assert!("0s".parse::<CandleInterval>().is_err());
+    }
+
+    #[test]
+    fn aggregates_trades_into_aligned_candles() {
+        let mut agg = CandleAggregator::new(CandleInterval::MINUTE);
+        assert_eq!(agg.push(&trade(60_500, "100", "2")), None);
+        assert_eq!(agg.push(&trade(70_000, "105", "1")), None);
+        assert_eq!(agg.push(&trade(80_000, "98", "3")), None);
+        assert_eq!(agg.push(&trade(119_999, "101", "1")), None);
+
+        let candle = agg.push(&trade(125_000, "102", "4")).unwrap();
+        assert_eq!(
+            candle,
+            Candle {
+                start_ms: 60_000,
+                end_ms: 120_000,
+                open: 100,
+                high: 105,
+                low: 98,
+                close: 101,
+                volume: 7,
+                trades: 4,
+            }
+        );
+
+        // A late trade widens the open candle without moving its close.
+        assert_eq!(agg.push(&trade(90_000, "90", "1")), None);
+        let candle = agg.flush().unwrap();
+        assert_eq!(
+            (candle.start_ms, candle.low, candle.close),
+            (120_000, 90, 102)
+        );
+        assert_eq!(candle.volume, 5);
+    }
+
+    #[tokio::test]
+    async fn stream_flushes_the_open_candle_when_trades_end() {
+        let trades = futures::stream::iter([
+            Ok(trade(1_000, "10", "1")),
+            Ok(trade(1_500, "12", "1")),
+            Ok(trade(2_100, "11", "2")),
+        ]);
+        let candles: Vec<_> = candles_from_trades(trades, CandleInterval::SECOND)
+            .map(|c| c.unwrap())
+            .collect()
+            .await;
+        assert_eq!(candles.len(), 2);
+        assert_eq!(
+            (candles[0].open, candles[0].close, candles[0].trades),
+            (10, 12, 2)
+        );
+        assert_eq!((candles[1].start_ms, candles[1].volume), (2_000, 2));
+    }
+}
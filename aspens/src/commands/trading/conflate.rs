@@ -0,0 +1,283 @@
+//! Conflated orderbook snapshots for UI consumers.
+//!
+//! The raw orderbook stream emits one event per order update, far more
+//! often than a dashboard can redraw. [`stream_orderbook_conflated`] folds
+//! the raw events into a [`BookState`] and publishes an aggregated
+//! [`BookSnapshot`] at most once per interval (default
+//! [`DEFAULT_CONFLATION_INTERVAL`]), on a `watch` channel that only ever
+//! holds the latest. Latency-sensitive bots keep using the raw
+//! `stream_orderbook` / `stream_orderbook_channel`.
+
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+
+use super::live_book::OrderBook;
+use super::stream_orderbook::arborter_pb::{OrderbookEntry, Side};
+use super::stream_orderbook::{
+    BookIndicators, StreamOrderbookOptions, book_indicators, stream_orderbook_channel,
+};
+use crate::error::Result;
+
+/// How often [`stream_orderbook_conflated`] publishes by default.
+pub const DEFAULT_CONFLATION_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The resting orders of one market, folded from orderbook events.
+///
+/// A market-less [`OrderBook`] under the conflation and snapshot helpers;
+/// the fold rules live in [`OrderBook::apply`].
+#[derive(Debug, Clone, Default)]
+pub struct BookState {
+    book: OrderBook,
+}
+
+impl BookState {
+    /// Fold one event in; see [`OrderBook::apply`].
+    pub fn apply(&mut self, entry: &OrderbookEntry) {
+        self.book.apply(entry);
+    }
+
+    /// Drop order `order_id`, e.g. one about to be replaced.
+    pub fn remove(&mut self, order_id: u64) {
+        self.book.remove(order_id);
+    }
+
+    /// Resting orders tracked.
+    pub fn len(&self) -> usize {
+        self.book.len()
+    }
+
+    /// True when no order is resting.
+    pub fn is_empty(&self) -> bool {
+        self.book.is_empty()
+    }
+
+    /// Resting orders as `(side, price, quantity)`, in no particular order.
+    pub fn resting(&self) -> impl Iterator<Item = (Side, u128, u128)> + '_ {
+        self.book.resting()
+    }
+
+    /// The book's price levels, labelled `market_id`.
+    pub fn snapshot(&self, market_id: &str) -> BookSnapshot {
+        BookSnapshot {
+            market_id: market_id.to_string(),
+            ..self.book.snapshot()
+        }
+    }
+}
+
+impl From<OrderBook> for BookState {
+    fn from(book: OrderBook) -> Self {
+        Self { book }
+    }
+}
+
+/// An aggregated view of the book at one instant.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookSnapshot {
+    /// Market the book is for.
+    pub market_id: String,
+    /// `(price, quantity)` levels, best (highest) first. Raw pair decimals.
+    pub bids: Vec<(u128, u128)>,
+    /// `(price, quantity)` levels, best (lowest) first. Raw pair decimals.
+    pub asks: Vec<(u128, u128)>,
+    /// Resting orders behind the levels.
+    pub orders: usize,
+    /// Raw events folded into this snapshot since the previous one.
+    pub updates: u64,
+}
+
+impl BookSnapshot {
+    /// Highest bid price.
+    pub fn best_bid(&self) -> Option<u128> {
+        self.bids.first().map(|(p, _)| *p)
+    }
+
+    /// Lowest ask price.
+    pub fn best_ask(&self) -> Option<u128> {
+        self.asks.first().map(|(p, _)| *p)
+    }
+
+    /// Depth, imbalance and mid prices over the top `depth` levels.
+    pub fn indicators(&self, depth: usize) -> BookIndicators {
+        book_indicators(&self.bids, &self.asks, depth)
+    }
+}
+
+/// Stream the orderbook as conflated snapshots, published at most once per
+/// `interval` and only when something changed.
+///
+/// Set `historical_open_orders` in `options` so the first snapshot holds
+/// the resting book rather than only what changes after subscribing.
+///
+/// # Returns
+/// A `watch` receiver holding the latest snapshot (initially empty), and a
+/// handle to the background task. The task ends with the underlying
+/// stream, or when every receiver is dropped.
+pub async fn stream_orderbook_conflated(
+    url: String,
+    options: StreamOrderbookOptions,
+    interval: Duration,
+) -> Result<(
+    watch::Receiver<BookSnapshot>,
+    tokio::task::JoinHandle<Result<()>>,
+)> {
+    let market_id = options.market_id.clone();
+    let (mut rx, stream) = stream_orderbook_channel(url, options).await?;
+    let (tx, snapshots) = watch::channel(BookSnapshot {
+        market_id: market_id.clone(),
+        ..BookSnapshot::default()
+    });
+
+    let handle = tokio::spawn(async move {
+        let mut book = BookState::default();
+        let mut pending: u64 = 0;
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let publish = |book: &BookState, pending: u64| BookSnapshot {
+            updates: pending,
+            ..book.snapshot(&market_id)
+        };
+        loop {
+            tokio::select! {
+                entry = rx.recv() => match entry {
+                    Some(entry) => {
+                        book.apply(&entry);
+                        pending += 1;
+                    }
+                    None => break,
+                },
+                _ = ticker.tick(), if pending > 0 => {
+                    if tx.send(publish(&book, pending)).is_err() {
+                        // Every receiver is gone.
+                        stream.abort();
+                        return Ok(());
+                    }
+                    pending = 0;
+                }
+            }
+        }
+        // Publish the tail before reporting how the stream ended.
+        if pending > 0 {
+            let _ = tx.send(publish(&book, pending));
+        }
+        stream.await?
+    });
+
+    Ok((snapshots, handle))
+}
+
+/// Snapshot the resting book of `market_id` once: replay the open orders
+/// for up to `collection_window`, fold them into a [`BookState`] and
+/// aggregate it.
+///
+/// Like [`fetch_top_of_book`](super::stream_orderbook::fetch_top_of_book),
+/// meant for one-off lookups; dashboards that redraw should use
+/// [`stream_orderbook_conflated`].
+pub async fn fetch_book_snapshot(
+    url: String,
+    market_id: String,
+    collection_window: Duration,
+) -> Result<BookSnapshot> {
+    let (book, updates) = collect_book(url, market_id.clone(), collection_window).await?;
+    Ok(BookSnapshot {
+        updates,
+        ..book.snapshot(&market_id)
+    })
+}
+
+/// Collect the resting orders of `market_id` like [`fetch_book_snapshot`],
+/// without aggregating them into levels.
+pub async fn fetch_book_state(
+    url: String,
+    market_id: String,
+    collection_window: Duration,
+) -> Result<BookState> {
+    Ok(collect_book(url, market_id, collection_window).await?.0)
+}
+
+/// The book folded from `collection_window` of the orderbook stream, and
+/// the number of events folded.
+async fn collect_book(
+    url: String,
+    market_id: String,
+    collection_window: Duration,
+) -> Result<(BookState, u64)> {
+    let (mut rx, stream) = stream_orderbook_channel(
+        url,
+        StreamOrderbookOptions {
+            market_id,
+            historical_open_orders: true,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let mut book = BookState::default();
+    let mut updates: u64 = 0;
+    let deadline = tokio::time::sleep(collection_window);
+    tokio::pin!(deadline);
+    let closed = loop {
+        tokio::select! {
+            _ = &mut deadline => break false,
+            entry = rx.recv() => match entry {
+                Some(entry) => {
+                    book.apply(&entry);
+                    updates += 1;
+                }
+                None => break true,
+            },
+        }
+    };
+    if closed {
+        // A stream that failed part-way would leave a partial book.
+        stream.await??;
+    } else {
+        stream.abort();
+    }
+    Ok((book, updates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::trading::stream_orderbook::arborter_pb::OrderState;
+
+    fn entry(order_id: u64, side: Side, price: &str, quantity: &str) -> OrderbookEntry {
+        OrderbookEntry {
+            order_id,
+            side: side as i32,
+            price: price.into(),
+            quantity: quantity.into(),
+            state: OrderState::Confirmed as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn folds_updates_into_price_levels() {
+        let mut book = BookState::default();
+        book.apply(&entry(1, Side::Bid, "100", "5"));
+        book.apply(&entry(2, Side::Bid, "100", "3"));
+        book.apply(&entry(3, Side::Bid, "99", "1"));
+        book.apply(&entry(4, Side::Ask, "101", "2"));
+        book.apply(&entry(5, Side::Ask, "102", "4"));
+        // Order 2 partially fills, order 5 is cancelled.
+        book.apply(&entry(2, Side::Bid, "100", "1"));
+        book.apply(&OrderbookEntry {
+            state: OrderState::Canceled as i32,
+            ..entry(5, Side::Ask, "102", "4")
+        });
+
+        let snapshot = book.snapshot("m");
+        assert_eq!(snapshot.bids, vec![(100, 6), (99, 1)]);
+        assert_eq!(snapshot.asks, vec![(101, 2)]);
+        assert_eq!(snapshot.orders, 4);
+        assert_eq!(
+            (snapshot.best_bid(), snapshot.best_ask()),
+            (Some(100), Some(101))
+        );
+        assert_eq!(snapshot.indicators(1).bid_depth, 6);
+    }
+}
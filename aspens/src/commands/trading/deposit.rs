@@ -1,19 +1,82 @@
-use alloy::network::EthereumWallet;
-use alloy::primitives::{Address, U160, U256};
+use alloy::network::{EthereumWallet, TxSigner};
+use alloy::primitives::{Signature, U160, U256};
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::signers::local::PrivateKeySigner;
 use alloy_chains::NamedChain;
-use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use url::Url;
 
-use crate::chain_client::ARCH_SOLANA;
+use crate::chain::tx::{
+    ConfirmationPolicy, StuckPolicy, legacy_gas_price, reserve_nonce, wait_for_confirmations,
+    wait_for_inclusion,
+};
+use crate::chain_client::{ARCH_SOLANA, ChainClient};
 use crate::commands::config::config_pb::GetConfigResponse;
+use crate::error::{AspensError, Result};
 use crate::evm::rpc::{IERC20, MidribV3};
-use crate::wallet::{CurveType, Wallet};
+use crate::signer::AspensSigner;
+use crate::wallet::CurveType;
 
 /// Minimum gas balance required for transactions (0.0001 ETH = 100000 gwei)
 const MIN_GAS_BALANCE: u128 = 100_000_000_000_000; // 0.0001 ETH in wei
 
+/// Lamports a wrapped-SOL deposit keeps back for the fee and a possible
+/// WSOL account rent (~0.003 SOL).
+const WRAP_FEE_HEADROOM: u64 = 3_000_000;
+
+/// The most the wallet at `owner` can deposit of `token_symbol` on
+/// `network`, in base units, for `amount = max`: its whole token balance,
+/// or for the chain's native asset (and wrapped SOL) its native balance
+/// less the gas headroom a deposit checks for.
+pub async fn max_deposit_amount(
+    network: String,
+    token_symbol: String,
+    owner: String,
+    config: GetConfigResponse,
+) -> Result<u128> {
+    let chain = config.get_chain(&network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    let token = config.get_token(&network, &token_symbol).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "Token '{}' not found on chain '{}'",
+            token_symbol, network
+        ))
+    })?;
+    let client = ChainClient::from_chain_config(chain)?;
+
+    #[cfg(feature = "solana")]
+    let wrapped_sol = crate::solana::is_wsol_mint(&token.address);
+    #[cfg(not(feature = "solana"))]
+    let wrapped_sol = false;
+    let amount = if crate::evm::is_native_token(&token.address) {
+        client
+            .native_balance(&owner)
+            .await?
+            .saturating_sub(MIN_GAS_BALANCE)
+    } else if wrapped_sol {
+        client
+            .native_balance(&owner)
+            .await?
+            .saturating_sub(WRAP_FEE_HEADROOM.into())
+    } else {
+        client.token_balance(token, &owner).await?
+    };
+    if amount == 0 {
+        return Err(AspensError::insufficient_balance(format!(
+            "{} has no {} to deposit on '{}' after keeping gas headroom",
+            owner, token_symbol, network
+        )));
+    }
+    tracing::info!(
+        "Maximum deposit of {} on {}: {} base units",
+        token_symbol,
+        network,
+        amount
+    );
+    Ok(amount)
+}
+
 /// Deposit tokens using a curve-agnostic wallet.
 ///
 /// Branches on `chain.architecture`:
@@ -24,13 +87,42 @@ pub async fn call_deposit_from_config_with_wallet(
     network: String,
     token_symbol: String,
     amount: u128,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
+    config: GetConfigResponse,
+) -> Result<()> {
+    call_deposit_from_config_with_wallet_opts(
+        network,
+        token_symbol,
+        amount,
+        wallet,
+        config,
+        DepositOpts::default(),
+    )
+    .await
+}
+
+/// Behavior options for [`call_deposit_from_config_with_wallet_opts`].
+#[derive(Debug, Clone, Default)]
+pub struct DepositOpts {
+    /// EVM only: confirmations the deposit waits for before returning. Use
+    /// [`AspensClient::confirmation_policy`](crate::AspensClient::confirmation_policy)
+    /// to follow the client's setting.
+    pub confirmations: ConfirmationPolicy,
+}
+
+/// [`call_deposit_from_config_with_wallet`] with explicit [`DepositOpts`].
+pub async fn call_deposit_from_config_with_wallet_opts(
+    network: String,
+    token_symbol: String,
+    amount: u128,
+    wallet: &dyn AspensSigner,
     config: GetConfigResponse,
+    opts: DepositOpts,
 ) -> Result<()> {
     // Look up chain to determine the dispatch path
-    let chain_for_arch = config
-        .get_chain(&network)
-        .ok_or_else(|| eyre::eyre!("Chain '{}' not found in configuration", network))?;
+    let chain_for_arch = config.get_chain(&network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
 
     if chain_for_arch
         .architecture
@@ -39,25 +131,30 @@ pub async fn call_deposit_from_config_with_wallet(
         // Solana SPL token amounts are natively u64 — downcast (checked) at the
         // boundary, since `deposit_ix` takes a u64 (DEC-1: u128 upstream, u64 on Solana).
         let spl_amount: u64 = amount.try_into().map_err(|_| {
-            eyre::eyre!("amount {amount} exceeds the SPL token u64 max on Solana chain '{network}'")
+            AspensError::invalid_input(format!(
+                "amount {amount} exceeds the SPL token u64 max on Solana chain '{network}'"
+            ))
         })?;
         return solana_deposit(chain_for_arch, &token_symbol, spl_amount, wallet).await;
     }
 
     // EVM path requires an EVM wallet
     if wallet.curve() != CurveType::Secp256k1 {
-        return Err(eyre::eyre!(
+        return Err(AspensError::invalid_input(format!(
             "EVM chain '{}' requires a secp256k1 wallet, got {:?}",
             network,
             wallet.curve()
-        ));
+        )));
     }
-    let signer = wallet
-        .as_evm()
-        .ok_or_else(|| eyre::eyre!("expected EVM wallet for chain '{}'", network))?
-        .clone();
+    let signer = wallet.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(format!(
+            "depositing on chain '{}' needs a wallet that signs transactions \
+             (a local key or a remote signer)",
+            network
+        ))
+    })?;
 
-    call_deposit_from_config_evm(network, token_symbol, amount, signer, config).await
+    call_deposit_from_config_evm(network, token_symbol, amount, signer, config, opts).await
 }
 
 /// Solana deposit — builds and submits the user-signed Midrib `deposit`
@@ -67,30 +164,30 @@ async fn solana_deposit(
     chain: &crate::commands::config::config_pb::Chain,
     token_symbol: &str,
     amount: u64,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
 ) -> Result<()> {
     use solana_sdk::pubkey::Pubkey;
     use std::str::FromStr;
 
     let token = chain.tokens.get(token_symbol).ok_or_else(|| {
-        eyre::eyre!(
+        AspensError::not_found(format!(
             "Token '{}' not found on Solana chain '{}'",
-            token_symbol,
-            chain.network
-        )
+            token_symbol, chain.network
+        ))
     })?;
 
     let keypair = wallet.as_solana().ok_or_else(|| {
-        eyre::eyre!(
+        AspensError::invalid_input(format!(
             "Solana chain '{}' requires an Ed25519 wallet (TRADER_PRIVKEY_SOLANA)",
             chain.network
-        )
+        ))
     })?;
 
     let (program_id, instance) = crate::solana::client::resolve_program_and_instance(chain)?;
     let user = solana_sdk::signer::Signer::pubkey(keypair);
-    let mint = Pubkey::from_str(&token.address)
-        .map_err(|e| eyre::eyre!("invalid Solana mint '{}': {}", token.address, e))?;
+    let mint = Pubkey::from_str(&token.address).map_err(|e| {
+        AspensError::invalid_input(format!("invalid Solana mint '{}': {}", token.address, e))
+    })?;
     let user_ata = crate::solana::derive_associated_token_account(&user, &mint);
 
     tracing::info!(
@@ -113,16 +210,14 @@ async fn solana_deposit(
 
         // The wrap spends `amount` lamports from the fee-payer itself; fail
         // fast with a clear error instead of an opaque simulation failure.
-        // Headroom: tx fee + possible ATA rent (~0.002 SOL).
-        const WRAP_FEE_HEADROOM: u64 = 3_000_000;
         let rpc = RpcClient::new(chain.rpc_url.clone());
         let lamports = rpc.get_balance(&user).await.unwrap_or(0);
         let required = amount.saturating_add(WRAP_FEE_HEADROOM);
         if lamports < required {
-            return Err(eyre::eyre!(
+            return Err(AspensError::insufficient_balance(format!(
                 "insufficient SOL: wallet {user} has {lamports} lamports, needs \
                  {required} (deposit amount + ~0.003 SOL fee/rent headroom)"
-            ));
+            )));
         }
 
         let ata_ix = crate::solana::create_idempotent_ata_ix(&user, &user, &mint, &user_ata);
@@ -145,12 +240,12 @@ async fn solana_deposit(
     chain: &crate::commands::config::config_pb::Chain,
     _token_symbol: &str,
     _amount: u64,
-    _wallet: &Wallet,
+    _wallet: &dyn AspensSigner,
 ) -> Result<()> {
-    Err(eyre::eyre!(
+    Err(AspensError::invalid_input(format!(
         "chain '{}' is Solana but the `solana` feature is disabled",
         chain.network
-    ))
+    )))
 }
 
 /// Original EVM deposit logic — kept private and called from the wallet-aware
@@ -159,8 +254,9 @@ async fn call_deposit_from_config_evm(
     network: String,
     token_symbol: String,
     amount: u128,
-    signer: PrivateKeySigner,
+    signer: Arc<dyn TxSigner<Signature> + Send + Sync>,
     config: GetConfigResponse,
+    opts: DepositOpts,
 ) -> Result<()> {
     // Look up chain info
     let chain = config.get_chain(&network).ok_or_else(|| {
@@ -175,11 +271,10 @@ async fn call_deposit_from_config_evm(
                     .join(", ")
             })
             .unwrap_or_default();
-        eyre::eyre!(
+        AspensError::not_found(format!(
             "Chain '{}' not found in configuration. Available chains: {}",
-            network,
-            available_chains
-        )
+            network, available_chains
+        ))
     })?;
 
     // Look up token info
@@ -190,12 +285,10 @@ async fn call_deposit_from_config_evm(
             .map(|s| s.as_str())
             .collect::<Vec<_>>()
             .join(", ");
-        eyre::eyre!(
+        AspensError::not_found(format!(
             "Token '{}' not found on chain '{}'. Available tokens: {}",
-            token_symbol,
-            network,
-            available_tokens
-        )
+            token_symbol, network, available_tokens
+        ))
     })?;
 
     // Get trade contract address
@@ -203,10 +296,10 @@ async fn call_deposit_from_config_evm(
         .trade_contract
         .as_ref()
         .ok_or_else(|| {
-            eyre::eyre!(
+            AspensError::not_found(format!(
                 "Trade contract not found for chain '{}'. Please ensure the contract is deployed.",
                 network
-            )
+            ))
         })?
         .address
         .clone();
@@ -227,7 +320,7 @@ async fn call_deposit_from_config_evm(
                 "Unknown chain ID {}, using chain ID directly",
                 chain.chain_id
             );
-            NamedChain::try_from(chain.chain_id as u64)?
+            NamedChain::try_from(chain.chain_id as u64).map_err(AspensError::invalid_input)?
         }
     };
 
@@ -244,9 +337,10 @@ async fn call_deposit_from_config_evm(
     let native = crate::evm::is_native_token(&token.address);
     let allowance_amount = U256::from(amount.saturating_add(1000));
     let deposit_amount = U160::from(amount);
-    let contract_addr: Address = contract_address.parse()?;
-    let token_addr: Address = token.address.parse()?;
+    let contract_addr = crate::util::parse_address(&contract_address)?;
+    let token_addr = crate::util::parse_address(&token.address)?;
     let signer_address = signer.address();
+    super::hedera::ensure_associated(chain, token, signer_address).await?;
     let wallet = EthereumWallet::new(signer);
     let rpc_url = Url::parse(&chain.rpc_url)?;
 
@@ -255,6 +349,8 @@ async fn call_deposit_from_config_evm(
         .with_chain(chain_type)
         .wallet(wallet)
         .connect_http(rpc_url);
+    // Chains without EIP-1559 are sent legacy transactions at this price.
+    let gas_price = legacy_gas_price(&provider, &network, chain.chain_id.into()).await?;
 
     // Check the wallet balance before attempting any transactions. A native
     // deposit spends the deposit amount ITSELF from the gas balance, so it
@@ -269,7 +365,7 @@ async fn call_deposit_from_config_evm(
     };
     if gas_balance < required {
         let balance_eth = gas_balance.to::<u128>() as f64 / 1e18;
-        return Err(eyre::eyre!(
+        return Err(AspensError::insufficient_balance(format!(
             "insufficient native balance: wallet has {:.6}, needs {} wei \
             ({}). Fund your wallet ({}) on {}.",
             balance_eth,
@@ -281,7 +377,7 @@ async fn call_deposit_from_config_evm(
             },
             signer_address,
             network
-        ));
+        )));
     }
 
     // Get an instance of the contract
@@ -291,6 +387,10 @@ async fn call_deposit_from_config_evm(
     if native {
         tracing::info!("Attempting NATIVE deposit of {amount} wei to contract {contract_addr}");
         let deposit_tx = contract.depositNative().value(U256::from(amount));
+        let deposit_tx = match gas_price {
+            Some(price) => deposit_tx.gas_price(price),
+            None => deposit_tx,
+        };
         match deposit_tx.estimate_gas().await {
             Ok(gas_estimate) => {
                 tracing::info!("Gas estimate for depositNative: {gas_estimate:?}");
@@ -300,10 +400,21 @@ async fn call_deposit_from_config_evm(
                 return Err(e.into());
             }
         }
-        let result = deposit_tx.send().await?;
+        let nonce = reserve_nonce(&provider, chain.chain_id.into(), signer_address).await?;
+        let result = deposit_tx.nonce(nonce.nonce()).send().await?;
+        nonce.commit();
         tracing::info!("Native deposit transaction sent: {result:?}");
-        let receipt = result.with_required_confirmations(1).watch().await?;
-        tracing::info!("Native deposit transaction hash: {receipt:?}");
+        let receipt = wait_for_confirmations(
+            &provider,
+            *result.tx_hash(),
+            StuckPolicy::from_env(),
+            opts.confirmations,
+        )
+        .await?;
+        tracing::info!(
+            "Native deposit transaction hash: {:?}",
+            receipt.transaction_hash
+        );
         return Ok(());
     }
 
@@ -322,13 +433,22 @@ async fn call_deposit_from_config_evm(
             "Current allowance insufficient, approving {} tokens",
             allowance_amount
         );
-        let approve_result = erc20
+        let nonce = reserve_nonce(&provider, chain.chain_id.into(), signer_address).await?;
+        let approve_tx = erc20
             .approve(contract_addr, allowance_amount)
-            .send()
-            .await?
-            .watch()
-            .await?;
-        tracing::info!("Set allowance result: {approve_result:?}");
+            .nonce(nonce.nonce());
+        let approve_tx = match gas_price {
+            Some(price) => approve_tx.gas_price(price),
+            None => approve_tx,
+        };
+        let pending = approve_tx.send().await?;
+        nonce.commit();
+        let approve_result =
+            wait_for_inclusion(&provider, *pending.tx_hash(), StuckPolicy::from_env()).await?;
+        tracing::info!(
+            "Set allowance result: {:?}",
+            approve_result.transaction_hash
+        );
     } else {
         tracing::info!("Sufficient allowance already set: {}", allowance_result);
     }
@@ -337,6 +457,10 @@ async fn call_deposit_from_config_evm(
     tracing::info!("Attempting deposit of {deposit_amount} tokens to contract {contract_addr}");
 
     let deposit_tx = contract.deposit(token_addr, deposit_amount);
+    let deposit_tx = match gas_price {
+        Some(price) => deposit_tx.gas_price(price),
+        None => deposit_tx,
+    };
 
     // Try to estimate gas first to see if the transaction would succeed
     match deposit_tx.estimate_gas().await {
@@ -349,13 +473,296 @@ async fn call_deposit_from_config_evm(
         }
     }
 
-    let result = deposit_tx.send().await?;
+    let nonce = reserve_nonce(&provider, chain.chain_id.into(), signer_address).await?;
+    let result = deposit_tx.nonce(nonce.nonce()).send().await?;
+    nonce.commit();
 
     tracing::info!("Deposit transaction sent: {result:?}");
 
-    let receipt = result.with_required_confirmations(1).watch().await?;
+    let receipt = wait_for_confirmations(
+        &provider,
+        *result.tx_hash(),
+        StuckPolicy::from_env(),
+        opts.confirmations,
+    )
+    .await?;
 
-    tracing::info!("Deposit transaction hash: {receipt:?}");
+    tracing::info!("Deposit transaction hash: {:?}", receipt.transaction_hash);
 
     Ok(())
 }
+
+/// One deposit in a [`DepositBatch`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositItem {
+    /// Network to deposit on, as named in the stack config.
+    pub network: String,
+    /// Token symbol.
+    pub token: String,
+    /// Amount in token units (`"1000"`, `"0.5"`), or `max`.
+    pub amount: String,
+}
+
+/// A list of deposits, read from TOML with one `[[deposit]]` table per
+/// [`DepositItem`]:
+///
+/// ```toml
+/// [[deposit]]
+/// network = "base-sepolia"
+/// token = "USDC"
+/// amount = "1000"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositBatch {
+    /// The deposits, in file order.
+    #[serde(rename = "deposit", default)]
+    pub deposits: Vec<DepositItem>,
+}
+
+impl DepositBatch {
+    /// Parse a batch from TOML.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Read a batch from a TOML file.
+    pub fn load(path: &std::path::Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AspensError::invalid_input(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        Self::from_toml(&contents)
+    }
+}
+
+/// How one [`DepositItem`] of a [`deposit_many`] call went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DepositStatus {
+    /// Network, as in the item.
+    pub network: String,
+    /// Token symbol, as in the item.
+    pub token: String,
+    /// The amount requested, as in the item.
+    pub amount: String,
+    /// Amount deposited in token units (a `max` resolved), when it
+    /// succeeded.
+    pub deposited: Option<String>,
+    /// Why it failed.
+    pub error: Option<String>,
+}
+
+impl DepositStatus {
+    /// True if the deposit went through.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Run the deposits in `items`, returning a status for each, in order.
+///
+/// Each item uses the first of `wallets` whose curve matches its chain.
+/// Networks are worked through concurrently, but deposits on the same
+/// network run one after another, since they share the wallet's nonce. A
+/// failed item is reported in its status and doesn't stop the others.
+/// Every deposit runs with `opts`.
+pub async fn deposit_many(
+    items: Vec<DepositItem>,
+    wallets: &[&dyn AspensSigner],
+    config: GetConfigResponse,
+    opts: DepositOpts,
+) -> Vec<DepositStatus> {
+    let total = items.len();
+    let mut by_network: Vec<(String, Vec<(usize, DepositItem)>)> = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        match by_network.iter_mut().find(|(n, _)| *n == item.network) {
+            Some((_, queue)) => queue.push((index, item)),
+            None => by_network.push((item.network.clone(), vec![(index, item)])),
+        }
+    }
+
+    let (config, opts) = (&config, &opts);
+    let lanes = by_network.into_iter().map(|(_, queue)| async move {
+        let mut done = Vec::with_capacity(queue.len());
+        for (index, item) in queue {
+            let result = deposit_item(&item, wallets, config, opts).await;
+            match &result {
+                Ok(deposited) => tracing::info!(
+                    "[{}/{}] Deposited {} {} on {}",
+                    index + 1,
+                    total,
+                    deposited,
+                    item.token,
+                    item.network
+                ),
+                Err(e) => tracing::warn!(
+                    "[{}/{}] Deposit of {} {} on {} failed: {}",
+                    index + 1,
+                    total,
+                    item.amount,
+                    item.token,
+                    item.network,
+                    e
+                ),
+            }
+            let (deposited, error) = match result {
+                Ok(deposited) => (Some(deposited), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            done.push((
+                index,
+                DepositStatus {
+                    network: item.network,
+                    token: item.token,
+                    amount: item.amount,
+                    deposited,
+                    error,
+                },
+            ));
+        }
+        done
+    });
+
+    let mut statuses: Vec<_> = futures::future::join_all(lanes)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    statuses.sort_by_key(|(index, _)| *index);
+    statuses.into_iter().map(|(_, status)| status).collect()
+}
+
+/// Resolve and run one batch deposit, returning the amount in token units.
+async fn deposit_item(
+    item: &DepositItem,
+    wallets: &[&dyn AspensSigner],
+    config: &GetConfigResponse,
+    opts: &DepositOpts,
+) -> Result<String> {
+    let chain = config.get_chain(&item.network).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "Chain '{}' not found in configuration",
+            item.network
+        ))
+    })?;
+    let token = config
+        .get_token(&item.network, &item.token)
+        .ok_or_else(|| {
+            AspensError::not_found(format!(
+                "Token '{}' not found on chain '{}'",
+                item.token, item.network
+            ))
+        })?;
+    let wanted = crate::wallet::chain_curve(chain);
+    let wallet = wallets
+        .iter()
+        .copied()
+        .find(|w| w.curve() == wanted)
+        .ok_or_else(|| {
+            AspensError::invalid_input(format!(
+                "no {:?} wallet for chain '{}'",
+                wanted, item.network
+            ))
+        })?;
+
+    let amount = if item.amount == crate::util::MAX_AMOUNT {
+        max_deposit_amount(
+            item.network.clone(),
+            item.token.clone(),
+            wallet.address(),
+            config.clone(),
+        )
+        .await?
+    } else {
+        crate::util::parse_amount(&item.amount, token.decimals).map_err(|e| {
+            AspensError::invalid_input(format!(
+                "Invalid amount '{}' for {}: {}",
+                item.amount, item.token, e
+            ))
+        })?
+    };
+    call_deposit_from_config_with_wallet_opts(
+        item.network.clone(),
+        item.token.clone(),
+        amount,
+        wallet,
+        config.clone(),
+        opts.clone(),
+    )
+    .await?;
+    Ok(crate::decimals::format_decimal_amount(
+        amount,
+        token.decimals,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::config_pb::{Chain, Configuration};
+    use crate::wallet::Wallet;
+
+    #[test]
+    fn deposit_batches_parse_from_toml() {
+        let batch = DepositBatch::from_toml(
+            r#"
+            [[deposit]]
+            network = "base-sepolia"
+            token = "USDC"
+            amount = "1000"
+
+            [[deposit]]
+            network = "anvil-1"
+            token = "WETH"
+            amount = "max"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(batch.deposits.len(), 2);
+        assert_eq!(batch.deposits[1].amount, "max");
+        assert_eq!(
+            DepositBatch::from_toml("").unwrap(),
+            DepositBatch::default()
+        );
+        assert!(DepositBatch::from_toml("[[deposit]]\nnetwork = \"x\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_items_are_reported_in_order() {
+        let config = GetConfigResponse {
+            config: Some(Configuration {
+                chains: vec![Chain {
+                    network: "anvil-1".into(),
+                    architecture: "EVM".into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+        };
+        let item = |network: &str, token: &str| DepositItem {
+            network: network.into(),
+            token: token.into(),
+            amount: "1".into(),
+        };
+        // Anvil test key #0.
+        let wallet = Wallet::from_evm_hex(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+
+        let statuses = deposit_many(
+            vec![
+                item("anvil-1", "USDC"),
+                item("missing", "USDC"),
+                item("anvil-1", "WETH"),
+            ],
+            &[&wallet],
+            config,
+            DepositOpts::default(),
+        )
+        .await;
+        let networks: Vec<_> = statuses.iter().map(|s| s.network.as_str()).collect();
+        assert_eq!(networks, ["anvil-1", "missing", "anvil-1"]);
+        assert!(statuses.iter().all(|s| !s.is_ok() && s.deposited.is_none()));
+        assert!(statuses[1].error.as_deref().unwrap().contains("missing"));
+        assert!(statuses[2].error.as_deref().unwrap().contains("WETH"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Size available on the book up to a limit price.
+//!
+//! Sizing a limit order on a thin book means adding up the resting orders
+//! on the other side until the price you're willing to pay: how much a buy
+//! at 1.05 would fill right now, at what notional, against how many orders.
+//! [`depth_at_price`] answers that from a [`BookState`];
+//! [`fetch_depth_at_price`] collects the book first.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
+
+use super::conflate::{BookState, fetch_book_state};
+use super::stream_orderbook::arborter_pb::Side;
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+
+/// What the resting book fills for an order up to a limit price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthAtPrice {
+    /// Side of the order being sized: a [`Side::Bid`] fills against the
+    /// asks at or below the limit, a [`Side::Ask`] against the bids at or
+    /// above it.
+    pub side: Side,
+    /// The limit price. Raw pair decimals.
+    pub limit_price: u128,
+    /// Quantity fillable without crossing the limit. Raw pair decimals.
+    pub quantity: u128,
+    /// Cumulative price × quantity of that fill. Raw pair decimals.
+    pub notional: u128,
+    /// Resting orders the fill would hit.
+    pub orders: usize,
+    /// Price levels the fill would walk.
+    pub levels: usize,
+    /// The furthest price the fill reaches; `None` if nothing fills.
+    pub worst_price: Option<u128>,
+}
+
+/// What `book` fills for a `side` order limited at `limit_price`.
+///
+/// Prices and quantities are raw pair decimals (`pair_decimals` scales the
+/// notional back down after multiplying them).
+pub fn depth_at_price(
+    book: &BookState,
+    side: Side,
+    limit_price: u128,
+    pair_decimals: u32,
+) -> DepthAtPrice {
+    let opposite = match side {
+        Side::Bid => Side::Ask,
+        _ => Side::Bid,
+    };
+    // price -> (quantity, orders)
+    let mut levels: BTreeMap<u128, (u128, usize)> = BTreeMap::new();
+    for (order_side, price, quantity) in book.resting() {
+        if order_side == opposite {
+            let level = levels.entry(price).or_default();
+            level.0 += quantity;
+            level.1 += 1;
+        }
+    }
+    let levels = levels
+        .into_iter()
+        .map(|(price, (quantity, orders))| (price, quantity, orders));
+    match side {
+        Side::Bid => walk_levels(side, limit_price, pair_decimals, levels),
+        _ => walk_levels(side, limit_price, pair_decimals, levels.rev()),
+    }
+}
+
+/// Fill a `side` order limited at `limit_price` against the opposite
+/// side's `(price, quantity, orders)` levels, given best first.
+pub(crate) fn walk_levels(
+    side: Side,
+    limit_price: u128,
+    pair_decimals: u32,
+    levels: impl Iterator<Item = (u128, u128, usize)>,
+) -> DepthAtPrice {
+    let within = |price: u128| match side {
+        Side::Bid => price <= limit_price,
+        Side::Ask => price >= limit_price,
+        Side::Unspecified => false,
+    };
+    let scale = 10u128.pow(pair_decimals);
+    let mut depth = DepthAtPrice {
+        side,
+        limit_price,
+        quantity: 0,
+        notional: 0,
+        orders: 0,
+        levels: 0,
+        worst_price: None,
+    };
+    for (price, quantity, orders) in levels.take_while(|&(price, _, _)| within(price)) {
+        depth.quantity += quantity;
+        depth.notional += quantity.saturating_mul(price) / scale;
+        depth.orders += orders;
+        depth.levels += 1;
+        depth.worst_price = Some(price);
+    }
+    depth
+}
+
+/// Collect the book of `market_id` for `collection_window` and size a
+/// `side` order limited at `limit_price` against it.
+pub async fn fetch_depth_at_price(
+    url: String,
+    market_id: String,
+    side: Side,
+    limit_price: u128,
+    pair_decimals: u32,
+    collection_window: Duration,
+) -> Result<DepthAtPrice> {
+    let book = fetch_book_state(url, market_id, collection_window).await?;
+    Ok(depth_at_price(&book, side, limit_price, pair_decimals))
+}
+
+/// Render depth answers as a table, amounts scaled by `pair_decimals`.
+pub fn display_depth(depths: &[DepthAtPrice], pair_decimals: u32) -> String {
+    let scaled = |raw: u128| format_decimal_amount(raw, pair_decimals);
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header(vec![
+        "Side", "Limit", "Quantity", "Notional", "Orders", "Levels", "Worst",
+    ]);
+    for depth in depths {
+        let side = match depth.side {
+            Side::Bid => "buy",
+            Side::Ask => "sell",
+            Side::Unspecified => "unknown",
+        };
+        table.add_row(vec![
+            side.to_string(),
+            scaled(depth.limit_price),
+            scaled(depth.quantity),
+            scaled(depth.notional),
+            depth.orders.to_string(),
+            depth.levels.to_string(),
+            depth.worst_price.map_or_else(|| "-".to_string(), scaled),
+        ]);
+    }
+    table.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::book_entry;
+
+    fn book() -> BookState {
+        let mut book = BookState::default();
+        let orders = [
+            (1, Side::Ask, "10100", "200"),
+            (2, Side::Ask, "10100", "100"),
+            (3, Side::Ask, "10500", "400"),
+            (4, Side::Ask, "11000", "900"),
+            (5, Side::Bid, "9900", "300"),
+            (6, Side::Bid, "9500", "500"),
+        ];
+        for (order_id, side, price, quantity) in orders {
+            book.apply(&book_entry(order_id, side, price, quantity));
+        }
+        book
+    }
+
+    #[test]
+    fn buy_walks_asks_up_to_the_limit() {
+        // Two pair decimals: a buy limited at 105.00.
+        let depth = depth_at_price(&book(), Side::Bid, 10500, 2);
+        assert_eq!(depth.quantity, 700);
+        // 3.00 at 101.00 plus 4.00 at 105.00.
+        assert_eq!(depth.notional, 30300 + 42000);
+        assert_eq!(depth.orders, 3);
+        assert_eq!(depth.levels, 2);
+        assert_eq!(depth.worst_price, Some(10500));
+    }
+
+    #[test]
+    fn sell_walks_bids_down_to_the_limit() {
+        let depth = depth_at_price(&book(), Side::Ask, 9600, 2);
+        assert_eq!(depth.quantity, 300);
+        assert_eq!(depth.orders, 1);
+        assert_eq!(depth.worst_price, Some(9900));
+
+        let none = depth_at_price(&book(), Side::Ask, 10000, 2);
+        assert_eq!(none.quantity, 0);
+        assert_eq!(none.worst_price, None);
+    }
+}
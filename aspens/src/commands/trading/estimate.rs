@@ -0,0 +1,199 @@
+//! Preview what a market order would fill at against the current book.
+//!
+//! [`estimate_fill`] snapshots the book the way
+//! [`get_orderbook`](super::orderbook::get_orderbook) does and walks the
+//! opposite side best price first until the quantity is used up: the
+//! average and worst price the order would pay, how far the average sits
+//! from the best price, and what the book can't fill. The book moves, and
+//! hidden orders aren't in the snapshot, so this is an estimate rather
+//! than a guarantee.
+
+use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
+
+use super::live_book::Level;
+use super::orderbook::{OrderbookLevels, get_orderbook};
+use super::stream_orderbook::arborter_pb::Side;
+use crate::commands::config::config_pb::Market;
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+
+/// What a market order would fill against the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillEstimate {
+    /// Side of the order: a [`Side::Bid`] fills against the asks, a
+    /// [`Side::Ask`] against the bids.
+    pub side: Side,
+    /// Quantity asked for. Raw pair decimals.
+    pub quantity: u128,
+    /// Quantity the book fills. Raw pair decimals.
+    pub filled: u128,
+    /// Quantity left once the book runs out. Raw pair decimals.
+    pub unfilled: u128,
+    /// Price × quantity of the fill. Raw pair decimals.
+    pub notional: u128,
+    /// Quantity-weighted average fill price; `None` if nothing fills.
+    pub average_price: Option<u128>,
+    /// Best price on the opposite side, where the fill starts.
+    pub best_price: Option<u128>,
+    /// The furthest price the fill reaches.
+    pub worst_price: Option<u128>,
+    /// Price levels the fill would walk.
+    pub levels: usize,
+}
+
+impl FillEstimate {
+    /// Walk `book`'s opposite side for a `side` order of `quantity`.
+    /// Prices and quantities are raw pair decimals.
+    pub fn from_levels(
+        book: &OrderbookLevels,
+        side: Side,
+        quantity: u128,
+        pair_decimals: u32,
+    ) -> Self {
+        let levels: &[Level] = match side {
+            Side::Bid => &book.asks,
+            Side::Ask => &book.bids,
+            Side::Unspecified => &[],
+        };
+        let mut estimate = Self {
+            side,
+            quantity,
+            filled: 0,
+            unfilled: quantity,
+            notional: 0,
+            average_price: None,
+            best_price: levels.first().map(|level| level.price),
+            worst_price: None,
+            levels: 0,
+        };
+        // Sum price × quantity unscaled so the average keeps every digit.
+        let mut cost: u128 = 0;
+        for level in levels {
+            if estimate.unfilled == 0 {
+                break;
+            }
+            let take = level.quantity.min(estimate.unfilled);
+            cost = cost.saturating_add(take.saturating_mul(level.price));
+            estimate.filled += take;
+            estimate.unfilled -= take;
+            estimate.levels += 1;
+            estimate.worst_price = Some(level.price);
+        }
+        if let Some(average) = cost.checked_div(estimate.filled) {
+            estimate.notional = cost / 10u128.pow(pair_decimals);
+            estimate.average_price = Some(average);
+        }
+        estimate
+    }
+
+    /// How far the average price sits from the best price, in basis
+    /// points; `None` if nothing fills.
+    pub fn slippage_bps(&self) -> Option<u128> {
+        let (average, best) = (self.average_price?, self.best_price?);
+        (best > 0).then(|| average.abs_diff(best).saturating_mul(10_000) / best)
+    }
+
+    /// True if the book fills the whole quantity.
+    pub fn is_complete(&self) -> bool {
+        self.unfilled == 0
+    }
+}
+
+/// Estimate how a `side` market order for `quantity` (raw pair decimals)
+/// would fill on `market` right now.
+pub async fn estimate_fill(
+    url: String,
+    market: &Market,
+    side: Side,
+    quantity: u128,
+) -> Result<FillEstimate> {
+    let book = get_orderbook(url, market.market_id.clone(), usize::MAX).await?;
+    Ok(FillEstimate::from_levels(
+        &book,
+        side,
+        quantity,
+        market.pair_decimals as u32,
+    ))
+}
+
+/// Render an estimate as a table, amounts scaled by `pair_decimals`.
+pub fn display_estimate(estimate: &FillEstimate, pair_decimals: u32) -> String {
+    let scaled = |raw: u128| format_decimal_amount(raw, pair_decimals);
+    let price = |raw: Option<u128>| raw.map_or_else(|| "-".to_string(), scaled);
+    let side = match estimate.side {
+        Side::Bid => "buy",
+        Side::Ask => "sell",
+        Side::Unspecified => "unknown",
+    };
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header(vec![
+        "Side",
+        "Quantity",
+        "Filled",
+        "Unfilled",
+        "Avg price",
+        "Best",
+        "Worst",
+        "Notional",
+        "Slippage",
+    ]);
+    table.add_row(vec![
+        side.to_string(),
+        scaled(estimate.quantity),
+        scaled(estimate.filled),
+        scaled(estimate.unfilled),
+        price(estimate.average_price),
+        price(estimate.best_price),
+        price(estimate.worst_price),
+        scaled(estimate.notional),
+        estimate
+            .slippage_bps()
+            .map_or_else(|| "-".to_string(), |bps| format!("{} bps", bps)),
+    ]);
+    table.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::trading::conflate::BookState;
+    use crate::fixtures::book_entry;
+
+    fn book() -> OrderbookLevels {
+        let mut book = BookState::default();
+        for e in [
+            book_entry(1, Side::Ask, "10000", "200"),
+            book_entry(2, Side::Ask, "10200", "300"),
+            book_entry(3, Side::Bid, "9900", "100"),
+        ] {
+            book.apply(&e);
+        }
+        OrderbookLevels::from_state("m", &book, usize::MAX)
+    }
+
+    #[test]
+    fn buy_walks_asks_and_averages() {
+        // Two pair decimals: buy 4.00 against 2.00 @ 100 and 3.00 @ 102.
+        let estimate = FillEstimate::from_levels(&book(), Side::Bid, 400, 2);
+        assert!(estimate.is_complete());
+        assert_eq!(estimate.levels, 2);
+        assert_eq!(estimate.worst_price, Some(10200));
+        // (2 × 100 + 2 × 102) / 4 = 101.00, 100 bps over the best ask.
+        assert_eq!(estimate.average_price, Some(10100));
+        assert_eq!(estimate.notional, 40400);
+        assert_eq!(estimate.slippage_bps(), Some(100));
+    }
+
+    #[test]
+    fn reports_what_the_book_cannot_fill() {
+        let estimate = FillEstimate::from_levels(&book(), Side::Ask, 250, 2);
+        assert_eq!((estimate.filled, estimate.unfilled), (100, 150));
+        assert_eq!(estimate.average_price, Some(9900));
+
+        let empty = FillEstimate::from_levels(&OrderbookLevels::default(), Side::Bid, 100, 2);
+        assert_eq!(empty.average_price, None);
+        assert_eq!(empty.slippage_bps(), None);
+        assert!(!empty.is_complete());
+    }
+}
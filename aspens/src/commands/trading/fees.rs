@@ -0,0 +1,69 @@
+//! Resolve a market's fee rates, from config or the instance contracts.
+//!
+//! Stacks that publish [`MarketFees`] in their config are taken at their
+//! word. Otherwise the rates are read from the MidribV3 instances on the
+//! market's chains: each charges `maintenanceBps + operatorBps` on settled
+//! notional, for makers and takers alike.
+
+use alloy::providers::ProviderBuilder;
+use url::Url;
+
+use crate::chain_client::ARCH_SOLANA;
+use crate::commands::config::config_pb::{Chain, GetConfigResponse, Market, MarketFees};
+use crate::error::{AspensError, Result};
+use crate::evm::rpc::MidribV3;
+
+/// Read an EVM instance's total fee rate (`maintenanceBps + operatorBps`).
+pub async fn instance_fee_bps(chain: &Chain) -> Result<u32> {
+    if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) {
+        return Err(AspensError::invalid_input(format!(
+            "on-chain fee lookup is not supported on Solana chain '{}'",
+            chain.network
+        )));
+    }
+    let address = chain
+        .trade_contract
+        .as_ref()
+        .map(|tc| tc.address.as_str())
+        .filter(|a| !a.is_empty())
+        .ok_or_else(|| {
+            AspensError::not_found(format!("no trade contract deployed on '{}'", chain.network))
+        })?;
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+    let contract = MidribV3::new(crate::util::parse_address(address)?, &provider);
+    let maintenance = contract.maintenanceBps().call().await?;
+    let operator = contract.operatorBps().call().await?;
+    Ok(u32::from(maintenance) + u32::from(operator))
+}
+
+/// The fee rates for `market`: the published ones if the config carries
+/// them, else the higher of the two legs' on-chain instance rates.
+///
+/// Legs whose rate can't be read (Solana, or no instance deployed) are
+/// skipped; errors only if neither leg yields a rate.
+pub async fn market_fees(config: &GetConfigResponse, market: &Market) -> Result<MarketFees> {
+    if let Some(fees) = market.fees() {
+        return Ok(*fees);
+    }
+    let mut best: Option<u32> = None;
+    let mut last_err = None;
+    for network in [&market.base_chain_network, &market.quote_chain_network] {
+        let Some(chain) = config.get_chain(network) else {
+            continue;
+        };
+        match instance_fee_bps(chain).await {
+            Ok(bps) => best = Some(best.map_or(bps, |b| b.max(bps))),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    match (best, last_err) {
+        (Some(bps), _) => Ok(MarketFees::flat(bps)),
+        (None, Some(e)) => Err(eyre::Report::new(e)
+            .wrap_err(format!("fee rates for market '{}'", market.name))
+            .into()),
+        (None, None) => Err(AspensError::not_found(format!(
+            "market '{}' has no chains in config",
+            market.name
+        ))),
+    }
+}
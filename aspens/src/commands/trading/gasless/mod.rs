@@ -28,11 +28,11 @@
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use eyre::{Result, eyre};
-
 use crate::commands::config::config_pb::{Chain, GetConfigResponse, Market};
+use crate::error::{AspensError, Result};
 use crate::orders::derive_order_id;
-use crate::wallet::{CurveType, Wallet};
+use crate::signer::AspensSigner;
+use crate::wallet::CurveType;
 
 use super::send_order::arborter_pb::OrderAuthorization;
 
@@ -47,7 +47,7 @@ pub fn build_gasless_authorization(
     config: &GetConfigResponse,
     market: &Market,
     side: i32,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     quantity_raw: &str,
     price_raw: Option<&str>,
 ) -> Result<OrderAuthorization> {
@@ -110,7 +110,7 @@ struct OrderResolution<'a> {
 /// Mirrors `arborter/app/chain-traits/src/convert_decimals.rs::normalize_decimals`
 /// so the SDK and arborter agree on the scale of every amount that flows
 /// through the EIP-712 / Ed25519 digest.
-fn normalize(amount: u128, from_decimals: u32, to_decimals: u32) -> Result<u128> {
+pub(crate) fn normalize(amount: u128, from_decimals: u32, to_decimals: u32) -> Result<u128> {
     use std::cmp::Ordering;
     match from_decimals.cmp(&to_decimals) {
         Ordering::Equal => Ok(amount),
@@ -118,10 +118,10 @@ fn normalize(amount: u128, from_decimals: u32, to_decimals: u32) -> Result<u128>
             let scale = 10u128
                 .checked_pow(from_decimals - to_decimals)
                 .ok_or_else(|| {
-                    eyre!(
+                    AspensError::invalid_input(format!(
                         "normalize scale 10^{} overflows u128",
                         from_decimals - to_decimals
-                    )
+                    ))
                 })?;
             Ok(amount / scale)
         }
@@ -129,16 +129,16 @@ fn normalize(amount: u128, from_decimals: u32, to_decimals: u32) -> Result<u128>
             let scale = 10u128
                 .checked_pow(to_decimals - from_decimals)
                 .ok_or_else(|| {
-                    eyre!(
+                    AspensError::invalid_input(format!(
                         "normalize scale 10^{} overflows u128",
                         to_decimals - from_decimals
-                    )
+                    ))
                 })?;
             amount.checked_mul(scale).ok_or_else(|| {
-                eyre!(
+                AspensError::invalid_input(format!(
                     "normalize: {amount} * 10^{} overflows u128",
                     to_decimals - from_decimals
-                )
+                ))
             })
         }
     }
@@ -167,35 +167,37 @@ fn resolve_order<'a>(
             &market.quote_chain_token_symbol,
         ),
         other => {
-            return Err(eyre!(
+            return Err(AspensError::invalid_input(format!(
                 "unsupported side {other} — expected 1 (Bid) or 2 (Ask)"
-            ));
+            )));
         }
     };
 
-    let origin_chain = config
-        .get_chain(origin_net)
-        .ok_or_else(|| eyre!("origin chain {origin_net:?} not found in config"))?;
-    let destination_chain = config
-        .get_chain(dest_net)
-        .ok_or_else(|| eyre!("destination chain {dest_net:?} not found in config"))?;
-    let input_token = config
-        .get_token(origin_net, origin_sym)
-        .ok_or_else(|| eyre!("token {origin_sym} on {origin_net} not found"))?;
-    let output_token = config
-        .get_token(dest_net, dest_sym)
-        .ok_or_else(|| eyre!("token {dest_sym} on {dest_net} not found"))?;
+    let origin_chain = config.get_chain(origin_net).ok_or_else(|| {
+        AspensError::not_found(format!("origin chain {origin_net:?} not found in config"))
+    })?;
+    let destination_chain = config.get_chain(dest_net).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "destination chain {dest_net:?} not found in config"
+        ))
+    })?;
+    let input_token = config.get_token(origin_net, origin_sym).ok_or_else(|| {
+        AspensError::not_found(format!("token {origin_sym} on {origin_net} not found"))
+    })?;
+    let output_token = config.get_token(dest_net, dest_sym).ok_or_else(|| {
+        AspensError::not_found(format!("token {dest_sym} on {dest_net} not found"))
+    })?;
     let input_decimals = input_token.decimals;
     let output_decimals = output_token.decimals;
     let pair_decimals = market.pair_decimals as u32;
 
-    let quantity: u128 = quantity_raw
-        .parse()
-        .map_err(|e| eyre!("quantity_raw {quantity_raw:?} is not a u128: {e}"))?;
+    let quantity: u128 = quantity_raw.parse().map_err(|e| {
+        AspensError::invalid_input(format!("quantity_raw {quantity_raw:?} is not a u128: {e}"))
+    })?;
     let price: u128 = match price_raw {
-        Some(s) => s
-            .parse::<u128>()
-            .map_err(|e| eyre!("price_raw {s:?} is not a u128: {e}"))?,
+        Some(s) => s.parse::<u128>().map_err(|e| {
+            AspensError::invalid_input(format!("price_raw {s:?} is not a u128: {e}"))
+        })?,
         None => {
             // Gasless cross-chain orders require the user to pre-commit
             // a specific `amount_in` (the lock amount the EIP-712 /
@@ -205,11 +207,11 @@ fn resolve_order<'a>(
             // reserves. Force the user to commit explicit slippage via a
             // buy-limit / sell-limit at a price ceiling / floor they're
             // willing to accept.
-            return Err(eyre!(
+            return Err(AspensError::invalid_input(
                 "cross-chain orders require a limit price — a market order \
                  can't pre-commit the `amount_in` the arborter reserves. Use \
                  buy-limit / sell-limit with a slippage-capped price (e.g. \
-                 price ≥ best ask × (1 + slippage) for a buy)."
+                 price ≥ best ask × (1 + slippage) for a buy).",
             ));
         }
     };
@@ -228,9 +230,9 @@ fn resolve_order<'a>(
         // Bid: pay quote = qty * price (in input=quote decimals),
         //      receive base = qty (in output=base decimals).
         1 => {
-            let qty_quote_pair2 = quantity
-                .checked_mul(price)
-                .ok_or_else(|| eyre!("amount_in overflow: {quantity} * {price}"))?;
+            let qty_quote_pair2 = quantity.checked_mul(price).ok_or_else(|| {
+                AspensError::invalid_input(format!("amount_in overflow: {quantity} * {price}"))
+            })?;
             (
                 normalize(qty_quote_pair2, pair_decimals * 2, input_decimals)?,
                 normalize(quantity, pair_decimals, output_decimals)?,
@@ -239,9 +241,9 @@ fn resolve_order<'a>(
         // Ask: pay base = qty (in input=base decimals),
         //      receive quote = qty * price (in output=quote decimals).
         2 => {
-            let qty_quote_pair2 = quantity
-                .checked_mul(price)
-                .ok_or_else(|| eyre!("amount_out overflow: {quantity} * {price}"))?;
+            let qty_quote_pair2 = quantity.checked_mul(price).ok_or_else(|| {
+                AspensError::invalid_input(format!("amount_out overflow: {quantity} * {price}"))
+            })?;
             (
                 normalize(quantity, pair_decimals, input_decimals)?,
                 normalize(qty_quote_pair2, pair_decimals * 2, output_decimals)?,
@@ -260,7 +262,7 @@ fn resolve_order<'a>(
     })
 }
 
-fn wallet_pubkey_bytes(wallet: &Wallet) -> Vec<u8> {
+fn wallet_pubkey_bytes(wallet: &dyn AspensSigner) -> Vec<u8> {
     // EVM: 20-byte address. Solana: 32-byte Ed25519 pubkey. The
     // `derive_order_id` hash treats the pubkey as opaque bytes so both
     // chains pass their canonical form.
@@ -280,9 +282,9 @@ fn wallet_pubkey_bytes(wallet: &Wallet) -> Vec<u8> {
 fn unix_millis() -> Result<u64> {
     let ms = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map_err(|e| eyre!("system clock before epoch: {e}"))?
+        .map_err(|e| AspensError::other(format!("system clock before epoch: {e}")))?
         .as_millis();
-    u64::try_from(ms).map_err(|_| eyre!("unix millis overflow"))
+    u64::try_from(ms).map_err(|_| AspensError::other("unix millis overflow"))
 }
 
 #[cfg(test)]
@@ -404,11 +406,16 @@ mod tests {
             quote_chain_token_decimals: quote_dec as i32,
             pair_decimals: pair_dec,
             market_id: "base-net::0xbase::quote-net::0xquote".into(),
+            schedule: None,
+            fees: None,
+            limits: None,
         };
         let config = GetConfigResponse {
             config: Some(Configuration {
                 chains: vec![base_chain, quote_chain],
                 markets: vec![market.clone()],
+                maintenance: None,
+                signing_versions: vec![],
             }),
         };
         (config, market)
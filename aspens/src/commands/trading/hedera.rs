@@ -0,0 +1,215 @@
+//! Hedera token association.
+//!
+//! Hedera chains are reached through their EVM JSON-RPC relay, so deposits and
+//! withdrawals take the EVM path. Hedera Token Service tokens have one extra
+//! rule: an account must be *associated* with a token before it can hold it,
+//! and a transfer to an unassociated account reverts with little more than
+//! `CONTRACT_REVERT_EXECUTED`. Association is a transaction the account sends
+//! to the token's HIP-719 facade (`associate()`); [`associate_token`] sends
+//! it, and [`ensure_associated`] is the check deposits and withdrawals run
+//! first so they fail with an actionable error instead.
+
+use alloy::network::{EthereumWallet, TxSigner};
+use alloy::primitives::Address;
+use alloy::providers::ProviderBuilder;
+use alloy_sol_types::sol;
+use url::Url;
+
+use crate::chain_client::ARCH_HEDERA;
+use crate::commands::config::config_pb::{Chain, GetConfigResponse, Token};
+use crate::error::{AspensError, Result};
+use crate::signer::AspensSigner;
+use crate::util::parse_address;
+
+sol! {
+    /// HIP-719 facade every HTS token exposes at its EVM address. Each call
+    /// acts on `msg.sender`.
+    #[sol(rpc)]
+    interface IHRC719 {
+        function associate() external returns (uint256 responseCode);
+        function isAssociated() external view returns (bool associated);
+    }
+}
+
+/// True if `chain` is a Hedera network.
+pub fn is_hedera(chain: &Chain) -> bool {
+    chain.architecture.eq_ignore_ascii_case(ARCH_HEDERA)
+}
+
+/// The EVM ("long-zero") address of a Hedera entity ID such as `0.0.12345`:
+/// 4 bytes of shard, 8 of realm and 8 of entity number.
+pub fn token_id_to_address(token_id: &str) -> Result<Address> {
+    let parts: Vec<&str> = token_id.trim().split('.').collect();
+    let [shard, realm, num] = parts.as_slice() else {
+        return Err(AspensError::invalid_input(format!(
+            "invalid Hedera token ID '{}': expected shard.realm.num, e.g. 0.0.12345",
+            token_id
+        )));
+    };
+    let parse = |s: &str| {
+        s.parse::<u64>().map_err(|e| {
+            AspensError::invalid_input(format!("invalid Hedera token ID '{}': {}", token_id, e))
+        })
+    };
+    let shard = u32::try_from(parse(shard)?).map_err(|_| {
+        AspensError::invalid_input(format!(
+            "invalid Hedera token ID '{}': shard too large",
+            token_id
+        ))
+    })?;
+    let mut bytes = [0u8; 20];
+    bytes[..4].copy_from_slice(&shard.to_be_bytes());
+    bytes[4..12].copy_from_slice(&parse(realm)?.to_be_bytes());
+    bytes[12..].copy_from_slice(&parse(num)?.to_be_bytes());
+    Ok(Address::from(bytes))
+}
+
+/// The EVM address of a configured token: its `address`, or else the
+/// address of its `token_id`.
+pub fn token_address(token: &Token) -> Result<Address> {
+    if !token.address.is_empty() {
+        return Ok(parse_address(&token.address)?);
+    }
+    let token_id = token.token_id.as_deref().ok_or_else(|| {
+        AspensError::invalid_input(format!(
+            "token '{}' has neither an address nor a token ID",
+            token.symbol
+        ))
+    })?;
+    token_id_to_address(token_id)
+}
+
+/// True if `account` is associated with the token at `token`.
+pub async fn is_associated(rpc_url: &str, token: Address, account: Address) -> Result<bool> {
+    let provider = ProviderBuilder::new().connect_http(Url::parse(rpc_url)?);
+    IHRC719::new(token, &provider)
+        .isAssociated()
+        .from(account)
+        .call()
+        .await
+        .map_err(|e| {
+            AspensError::chain(format!(
+                "Failed to check association of {} with {}: {}",
+                account, token, e
+            ))
+        })
+}
+
+/// Fail unless both `account` and the chain's trade contract are associated
+/// with `token`. A no-op on non-Hedera chains.
+pub async fn ensure_associated(chain: &Chain, token: &Token, account: Address) -> Result<()> {
+    if !is_hedera(chain) || crate::evm::is_native_token(&token.address) {
+        return Ok(());
+    }
+    let token_addr = token_address(token)?;
+    if !is_associated(&chain.rpc_url, token_addr, account).await? {
+        return Err(AspensError::invalid_input(format!(
+            "{} is not associated with {} on Hedera network '{}'; Hedera accounts must \
+             associate with a token before holding it. Associate it first with \
+             `aspens-cli associate-token {} {}` (or `associate_token`).",
+            account, token.symbol, chain.network, chain.network, token.symbol
+        )));
+    }
+    if let Some(contract) = chain
+        .trade_contract
+        .as_ref()
+        .filter(|tc| !tc.address.is_empty())
+    {
+        let contract_addr = parse_address(&contract.address)?;
+        if !is_associated(&chain.rpc_url, token_addr, contract_addr).await? {
+            return Err(AspensError::invalid_input(format!(
+                "the trade contract {} on '{}' is not associated with {}, so it can't \
+                 hold it; ask the stack operator to associate it",
+                contract_addr, chain.network, token.symbol
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Associate the wallet with `token` on Hedera network `network`. `token` is
+/// a symbol from the config or a token ID such as `0.0.12345`.
+///
+/// Returns the transaction hash, or `None` if the wallet was already
+/// associated.
+pub async fn associate_token(
+    network: String,
+    token: String,
+    wallet: &dyn AspensSigner,
+    config: GetConfigResponse,
+) -> Result<Option<String>> {
+    let chain = config.get_chain(&network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    if !is_hedera(chain) {
+        return Err(AspensError::invalid_input(format!(
+            "'{}' is a {} chain; token association only applies to Hedera",
+            network, chain.architecture
+        )));
+    }
+    let token_addr = match config.get_token(&network, &token) {
+        Some(configured) => token_address(configured)?,
+        None => token_id_to_address(&token).map_err(|_| {
+            AspensError::not_found(format!(
+                "Token '{}' not found on chain '{}', and it isn't a token ID like 0.0.12345",
+                token, network
+            ))
+        })?,
+    };
+    let signer = wallet.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(format!(
+            "Hedera chain '{}' requires a secp256k1 wallet that signs transactions",
+            network
+        ))
+    })?;
+    let account = signer.address();
+
+    if is_associated(&chain.rpc_url, token_addr, account).await? {
+        tracing::info!("{} is already associated with {}", account, token);
+        return Ok(None);
+    }
+
+    let provider = ProviderBuilder::new()
+        .with_chain_id(chain.chain_id as u64)
+        .wallet(EthereumWallet::new(signer))
+        .connect_http(Url::parse(&chain.rpc_url)?);
+    let receipt = IHRC719::new(token_addr, &provider)
+        .associate()
+        .send()
+        .await
+        .map_err(|e| {
+            AspensError::chain(format!("Failed to send associate() for {}: {}", token, e))
+        })?
+        .get_receipt()
+        .await?;
+    if !receipt.status() {
+        return Err(AspensError::chain(format!(
+            "associate() for {} reverted in {}",
+            token, receipt.transaction_hash
+        )));
+    }
+    Ok(Some(format!("{:?}", receipt.transaction_hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_ids_map_to_long_zero_addresses() {
+        assert_eq!(
+            token_id_to_address("0.0.1234").unwrap(),
+            "0x00000000000000000000000000000000000004d2"
+                .parse::<Address>()
+                .unwrap()
+        );
+        assert_eq!(
+            token_id_to_address("1.2.3").unwrap(),
+            "0x0000000100000000000000020000000000000003"
+                .parse::<Address>()
+                .unwrap()
+        );
+        assert!(token_id_to_address("0.0").is_err());
+        assert!(token_id_to_address("0.0.x").is_err());
+    }
+}
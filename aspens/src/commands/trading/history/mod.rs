@@ -0,0 +1,260 @@
+//! Deposit / withdraw history reconstructed from Midrib contract logs.
+//!
+//! The arborter doesn't keep a transfer ledger a trader can query, but every
+//! deposit and voucher withdrawal emits an indexed event on the chain's trade
+//! contract. [`transfer_history`] scans those logs for one wallet so users
+//! don't have to dig through a block explorer.
+//!
+//! Closed trades come from the stack instead; [`trades`] replays and
+//! exports them.
+
+/// Closed-trade history replayed from the stack, exported as CSV or JSON.
+pub mod trades;
+
+pub use trades::{ExportFormat, TradeRecord, trade_history, write_trades};
+
+use alloy::primitives::{Address, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::Filter;
+use alloy_sol_types::SolEvent;
+use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use tracing::warn;
+use url::Url;
+
+use super::balance::{BalanceChangeKind, MAX_LOG_BLOCK_RANGE, delta_from_log, format_balance};
+use crate::chain_client::ARCH_SOLANA;
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::error::{AspensError, Result};
+use crate::evm::rpc::MidribV3;
+use crate::util::{format_utc, parse_address};
+
+/// Number of blocks [`BlockRange::default`] looks back from the chain head.
+pub const DEFAULT_HISTORY_BLOCKS: u64 = 10_000;
+
+/// Inclusive block range to scan.
+///
+/// `to` defaults to the chain head and `from` to [`DEFAULT_HISTORY_BLOCKS`]
+/// before `to`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockRange {
+    /// First block to scan.
+    pub from: Option<u64>,
+    /// Last block to scan.
+    pub to: Option<u64>,
+}
+
+impl BlockRange {
+    /// Resolve the range against the current chain `head`.
+    fn resolve(self, head: u64) -> Result<(u64, u64)> {
+        let to = self.to.unwrap_or(head).min(head);
+        let from = self
+            .from
+            .unwrap_or_else(|| to.saturating_sub(DEFAULT_HISTORY_BLOCKS - 1));
+        if from > to {
+            return Err(AspensError::invalid_input(format!(
+                "empty block range: from block {} is after to block {}",
+                from, to
+            )));
+        }
+        Ok((from, to))
+    }
+}
+
+/// One deposit or withdrawal found in the trade contract's logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferRecord {
+    /// [`BalanceChangeKind::Deposit`] or [`BalanceChangeKind::Withdraw`].
+    pub kind: BalanceChangeKind,
+    /// Token symbol from the config, or the token address if it isn't listed.
+    pub token_symbol: String,
+    /// Token contract address as emitted by the event.
+    pub token_address: String,
+    /// Amount moved, in base units.
+    pub amount: U256,
+    /// Block the transfer was included in.
+    pub block_number: u64,
+    /// Transaction hash, when the RPC reports it.
+    pub tx_hash: Option<String>,
+    /// Block timestamp (unix seconds), when it could be fetched.
+    pub timestamp: Option<u64>,
+}
+
+/// Reconstruct `address`'s deposits and withdrawals on `network` from the
+/// trade contract's `TokenDeposited` / `Withdrawn` events.
+///
+/// `token` narrows the result to one symbol. Records come back in block
+/// order. Only EVM chains are supported.
+pub async fn transfer_history(
+    network: &str,
+    token: Option<&str>,
+    address: &str,
+    block_range: BlockRange,
+    config: &GetConfigResponse,
+) -> Result<Vec<TransferRecord>> {
+    let chain = config.get_chain(network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) {
+        return Err(AspensError::invalid_input(format!(
+            "transfer history is only available on EVM chains; '{}' is Solana",
+            network
+        )));
+    }
+    let contract = parse_address(
+        chain
+            .trade_contract
+            .as_ref()
+            .map(|tc| tc.address.as_str())
+            .filter(|a| !a.is_empty())
+            .ok_or_else(|| {
+                AspensError::not_found(format!("no trade contract deployed on '{}'", network))
+            })?,
+    )?;
+    let owner = parse_address(address)?;
+    let token_filter: Option<Address> = match token {
+        Some(symbol) => Some(parse_address(
+            &config
+                .get_token(network, symbol)
+                .ok_or_else(|| {
+                    AspensError::not_found(format!(
+                        "Token '{}' not found on chain '{}'",
+                        symbol, network
+                    ))
+                })?
+                .address,
+        )?),
+        None => None,
+    };
+
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+    let head = provider.get_block_number().await?;
+    let (from, to) = block_range.resolve(head)?;
+
+    let mut records = Vec::new();
+    let mut chunk_start = from;
+    while chunk_start <= to {
+        let chunk_end = to.min(chunk_start + MAX_LOG_BLOCK_RANGE - 1);
+        let mut filter = Filter::new()
+            .address(contract)
+            .event_signature(vec![
+                MidribV3::TokenDeposited::SIGNATURE_HASH,
+                MidribV3::Withdrawn::SIGNATURE_HASH,
+            ])
+            .topic1(owner.into_word())
+            .from_block(chunk_start)
+            .to_block(chunk_end);
+        if let Some(token) = token_filter {
+            filter = filter.topic2(token.into_word());
+        }
+        let mut logs = provider.get_logs(&filter).await?;
+        logs.sort_by_key(|l| (l.block_number, l.log_index));
+        for log in &logs {
+            if let Some(delta) = delta_from_log(chain, log) {
+                records.push(TransferRecord {
+                    kind: delta.kind,
+                    token_symbol: delta.token_symbol,
+                    token_address: delta.token_address,
+                    amount: delta.delta.unsigned_abs(),
+                    block_number: delta.block_number,
+                    tx_hash: delta.tx_hash,
+                    timestamp: log.block_timestamp,
+                });
+            }
+        }
+        chunk_start = chunk_end + 1;
+    }
+
+    // Not every RPC includes `blockTimestamp` in logs; fill the gaps from the
+    // block headers, once per block.
+    let mut timestamps: HashMap<u64, Option<u64>> = HashMap::new();
+    for record in records.iter_mut().filter(|r| r.timestamp.is_none()) {
+        if let Entry::Vacant(slot) = timestamps.entry(record.block_number) {
+            let ts = match provider
+                .get_block_by_number(record.block_number.into())
+                .await
+            {
+                Ok(block) => block.map(|b| b.header.timestamp),
+                Err(e) => {
+                    warn!(
+                        "Failed to fetch block {} on {}: {}",
+                        record.block_number, network, e
+                    );
+                    None
+                }
+            };
+            slot.insert(ts);
+        }
+        record.timestamp = timestamps[&record.block_number];
+    }
+
+    Ok(records)
+}
+
+/// Render transfer history as a table, amounts scaled by each token's
+/// decimals on `network`.
+pub fn display_transfer_history(
+    network: &str,
+    records: &[TransferRecord],
+    config: &GetConfigResponse,
+) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header(vec!["Time (UTC)", "Type", "Token", "Amount", "Block", "Tx"]);
+
+    for record in records {
+        let kind = match record.kind {
+            BalanceChangeKind::Deposit => "deposit",
+            BalanceChangeKind::Withdraw => "withdraw",
+            BalanceChangeKind::Settlement => "settlement",
+        };
+        let amount = match config.get_token(network, &record.token_symbol) {
+            Some(token) => format_balance(record.amount, token.decimals),
+            None => record.amount.to_string(),
+        };
+        table.add_row(vec![
+            record
+                .timestamp
+                .map(format_utc)
+                .unwrap_or_else(|| "-".to_string()),
+            kind.to_string(),
+            record.token_symbol.clone(),
+            amount,
+            record.block_number.to_string(),
+            record.tx_hash.clone().unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+
+    table.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_range_defaults_to_recent_blocks() {
+        assert_eq!(
+            BlockRange::default().resolve(50_000).unwrap(),
+            (50_000 - DEFAULT_HISTORY_BLOCKS + 1, 50_000)
+        );
+        // Young chains clamp at genesis.
+        assert_eq!(BlockRange::default().resolve(10).unwrap(), (0, 10));
+    }
+
+    #[test]
+    fn block_range_clamps_to_head_and_rejects_inverted_ranges() {
+        let range = BlockRange {
+            from: Some(5),
+            to: Some(1_000),
+        };
+        assert_eq!(range.resolve(100).unwrap(), (5, 100));
+
+        let inverted = BlockRange {
+            from: Some(10),
+            to: Some(5),
+        };
+        assert!(inverted.resolve(100).is_err());
+    }
+}
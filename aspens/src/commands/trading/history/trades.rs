@@ -0,0 +1,280 @@
+//! Closed-trade history of a market, exported as CSV or JSON.
+//!
+//! [`trade_history`] replays the market's closed trades over a time range
+//! through the trades stream's historical option, paging through them
+//! [`HistoricalReplay::batch_size`] at a time, and normalizes each into a
+//! [`TradeRecord`]: decimal amounts, who bought and sold, and the maker
+//! and taker fees at the market's published rates. [`write_trades`] writes
+//! them out in an [`ExportFormat`].
+//!
+//! The trades stream carries no settlement transaction, so a record has
+//! no hash to link; the settlement shows up in each side's
+//! [`transfer_history`](super::transfer_history) as a balance change.
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+use eyre::eyre;
+use futures::StreamExt;
+use serde::Serialize;
+
+use crate::commands::config::FeeRole;
+use crate::commands::config::config_pb::Market;
+use crate::commands::trading::replay::HistoricalReplay;
+use crate::commands::trading::stream_trades::arborter_pb::{Trade, TradeRole};
+use crate::commands::trading::stream_trades::{StreamTradesOptions, stream_trades_stream};
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+use crate::util::{csv_row, format_utc, unix_now_ms};
+
+/// How long [`trade_history`] waits for another trade before treating the
+/// replay as complete.
+pub const DEFAULT_REPLAY_QUIET: Duration = Duration::from_secs(2);
+
+/// Header row of [`ExportFormat::Csv`].
+pub const CSV_HEADER: &str = "time_utc,timestamp_ms,market_id,side,price,quantity,notional,\
+                              buyer,seller,maker,taker,order_hit,maker_fee,taker_fee";
+
+/// File format for [`write_trades`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One row per trade under [`CSV_HEADER`].
+    Csv,
+    /// A JSON array of [`TradeRecord`]s.
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            other => Err(eyre!("unknown export format '{}': use csv or json", other)),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv => write!(f, "csv"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// One closed trade, normalized for export. Amounts are decimal strings
+/// scaled by the market's pair decimals.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TradeRecord {
+    /// When the engine registered the trade, unix milliseconds.
+    pub timestamp_ms: u64,
+    /// The market.
+    pub market_id: String,
+    /// The taker's side: `buy` or `sell`.
+    pub side: String,
+    /// Settled price.
+    pub price: String,
+    /// Traded quantity.
+    pub quantity: String,
+    /// Price × quantity.
+    pub notional: String,
+    /// The buyer's base-chain address.
+    pub buyer: String,
+    /// The seller's base-chain address.
+    pub seller: String,
+    /// The maker's base-chain address.
+    pub maker: String,
+    /// The taker's base-chain address.
+    pub taker: String,
+    /// The resting order the taker hit.
+    pub order_hit: u64,
+    /// Maker fee at the market's published rate; `None` if the market
+    /// publishes none.
+    pub maker_fee: Option<String>,
+    /// Taker fee at the market's published rate.
+    pub taker_fee: Option<String>,
+}
+
+impl TradeRecord {
+    /// Normalize `trade` on `market`. `None` if its price or quantity
+    /// doesn't parse.
+    pub fn from_trade(trade: &Trade, market: &Market) -> Option<Self> {
+        let pair_decimals = market.pair_decimals as u32;
+        let price: u128 = trade.price.parse().ok()?;
+        let quantity: u128 = trade.qty.parse().ok()?;
+        let scaled = |raw| format_decimal_amount(raw, pair_decimals);
+        let taker_buys = TradeRole::try_from(trade.buyer_is) == Ok(TradeRole::Taker);
+        let (buyer, seller) = if taker_buys {
+            (&trade.taker_base_address, &trade.maker_base_address)
+        } else {
+            (&trade.maker_base_address, &trade.taker_base_address)
+        };
+        let fee = |role| {
+            market
+                .fees()
+                .map(|fees| scaled(fees.estimate(role, quantity, price, pair_decimals).fee))
+        };
+        Some(Self {
+            timestamp_ms: trade.timestamp,
+            market_id: market.market_id.clone(),
+            side: if taker_buys { "buy" } else { "sell" }.to_string(),
+            price: scaled(price),
+            quantity: scaled(quantity),
+            notional: scaled(quantity.saturating_mul(price) / 10u128.pow(pair_decimals)),
+            buyer: buyer.clone(),
+            seller: seller.clone(),
+            maker: trade.maker_base_address.clone(),
+            taker: trade.taker_base_address.clone(),
+            order_hit: trade.order_hit,
+            maker_fee: fee(FeeRole::Maker),
+            taker_fee: fee(FeeRole::Taker),
+        })
+    }
+
+    /// One CSV row under [`CSV_HEADER`].
+    pub fn to_csv_row(&self) -> String {
+        let opt = |fee: &Option<String>| fee.clone().unwrap_or_default();
+        csv_row([
+            format_utc(self.timestamp_ms / 1_000),
+            self.timestamp_ms.to_string(),
+            self.market_id.clone(),
+            self.side.clone(),
+            self.price.clone(),
+            self.quantity.clone(),
+            self.notional.clone(),
+            self.buyer.clone(),
+            self.seller.clone(),
+            self.maker.clone(),
+            self.taker.clone(),
+            self.order_hit.to_string(),
+            opt(&self.maker_fee),
+            opt(&self.taker_fee),
+        ])
+    }
+}
+
+/// Replay `market`'s closed trades from `from_ms` up to `to_ms` (inclusive,
+/// unix milliseconds; `None` is now) and normalize them.
+///
+/// `page_size` is how many trades the stack reads per page (default
+/// [`DEFAULT_BATCH_SIZE`](crate::commands::trading::replay::DEFAULT_BATCH_SIZE)). The trades stream has no end-of-history
+/// marker, so the replay ends at the first trade past `to_ms` or once no
+/// trade has arrived for [`DEFAULT_REPLAY_QUIET`].
+pub async fn trade_history(
+    url: String,
+    market: &Market,
+    from_ms: u64,
+    to_ms: Option<u64>,
+    page_size: Option<u32>,
+) -> Result<Vec<TradeRecord>> {
+    let to_ms = to_ms.unwrap_or_else(unix_now_ms);
+    let mut replay = HistoricalReplay::since(from_ms);
+    replay.batch_size = page_size;
+    replay.validate()?;
+    let mut trades = std::pin::pin!(stream_trades_stream(
+        url,
+        StreamTradesOptions {
+            market_id: market.market_id.clone(),
+            historical_closed_trades: true,
+            replay,
+            ..Default::default()
+        },
+    ));
+
+    let mut records = Vec::new();
+    while let Ok(Some(trade)) = tokio::time::timeout(DEFAULT_REPLAY_QUIET, trades.next()).await {
+        let trade = trade?;
+        if trade.timestamp > to_ms {
+            break;
+        }
+        if trade.timestamp < from_ms {
+            continue;
+        }
+        records.extend(TradeRecord::from_trade(&trade, market));
+    }
+    records.sort_by_key(|r| r.timestamp_ms);
+    Ok(records)
+}
+
+/// Write `records` to `out` as `format`.
+pub fn write_trades(
+    records: &[TradeRecord],
+    format: ExportFormat,
+    mut out: impl Write,
+) -> eyre::Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            writeln!(out, "{}", CSV_HEADER)?;
+            for record in records {
+                writeln!(out, "{}", record.to_csv_row())?;
+            }
+        }
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, records)?;
+            writeln!(out)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ConfigFixture, trade};
+
+    fn market() -> Market {
+        let config = ConfigFixture::two_chain_usdc().fees(2, 5).build();
+        config
+            .get_market_by_id(ConfigFixture::TWO_CHAIN_MARKET_ID)
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn normalizes_sides_amounts_and_fees() {
+        let trade = Trade {
+            maker_base_address: "0xmaker".into(),
+            taker_base_address: "0xtaker".into(),
+            buyer_is: TradeRole::Taker as i32,
+            seller_is: TradeRole::Maker as i32,
+            order_hit: 9,
+            ..trade(1_700_000_000_000, "1500000", "2000000")
+        };
+        let record = TradeRecord::from_trade(&trade, &market()).unwrap();
+        assert_eq!(record.side, "buy");
+        assert_eq!(
+            (record.buyer.as_str(), record.seller.as_str()),
+            ("0xtaker", "0xmaker")
+        );
+        assert_eq!(record.price, "1.500000");
+        assert_eq!(record.notional, "3.000000");
+        // 5 bps of 3.00, rounded up to the micro-unit.
+        assert_eq!(record.taker_fee.as_deref(), Some("0.001500"));
+        assert_eq!(record.maker_fee.as_deref(), Some("0.000600"));
+    }
+
+    #[test]
+    fn writes_csv_and_json() {
+        let record =
+            TradeRecord::from_trade(&trade(1_000, "1000000", "1000000"), &market()).unwrap();
+        let mut csv = Vec::new();
+        write_trades(std::slice::from_ref(&record), ExportFormat::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines[1].split(',').count(), CSV_HEADER.split(',').count());
+
+        let mut json = Vec::new();
+        write_trades(&[record], ExportFormat::Json, &mut json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed[0]["side"], "sell");
+        assert_eq!(parsed[0]["quantity"], "1.000000");
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+}
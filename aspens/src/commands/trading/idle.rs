@@ -0,0 +1,166 @@
+//! Idle detection for long-running orderbook and trades streams.
+//!
+//! A quiet market and a silently dropped stream look the same from the
+//! consumer's side: no events. HTTP/2 keepalive pings
+//! ([`KeepAlive`](crate::grpc::KeepAlive)) catch a dead connection, but not a
+//! stream a proxy or the stack stopped feeding while the connection stays up.
+//! With [`IdleOptions`] set on a stream, a stream that has been silent for
+//! `threshold` probes the stack with a cheap unary call on the same channel:
+//! if the stack answers, the market is just quiet and the stream keeps
+//! waiting; if it doesn't, the stream resubscribes on a fresh connection.
+//!
+//! A resubscribe sends the original request again, so a stream that asked for
+//! history replays it again. Consumers that key state by order ID (such as
+//! [`BookState`](super::conflate::BookState)) absorb the repeats. Events
+//! published between the drop and the resubscribe are not recovered unless
+//! the stream also has [`ReconnectOptions`](super::reconnect::ReconnectOptions)
+//! with `resume` on, in which case the resubscribe replays from the last
+//! delivered event instead.
+
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use tonic::Code;
+use tonic::transport::Channel;
+
+use crate::commands::config::config_pb::GetConfigRequest;
+use crate::commands::config::config_pb::config_service_client::ConfigServiceClient;
+
+/// Silence after which a stream probes the stack by default.
+pub const DEFAULT_IDLE_THRESHOLD: Duration = Duration::from_secs(120);
+
+/// How long a probe waits for the stack to answer by default.
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// When to suspect a silent stream, and how often to resubscribe it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleOptions {
+    /// Silence after which the stream probes the stack.
+    pub threshold: Duration,
+    /// How long the probe waits for an answer.
+    pub probe_timeout: Duration,
+    /// Most resubscribes before the stream gives up with an error; `None`
+    /// resubscribes indefinitely.
+    pub max_resubscribes: Option<u32>,
+}
+
+impl Default for IdleOptions {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_IDLE_THRESHOLD,
+            probe_timeout: DEFAULT_PROBE_TIMEOUT,
+            max_resubscribes: None,
+        }
+    }
+}
+
+impl IdleOptions {
+    /// Probe after `threshold` of silence, with the default probe timeout
+    /// and no resubscribe limit.
+    pub fn after(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            ..Self::default()
+        }
+    }
+
+    /// Give up after `max` resubscribes.
+    pub fn with_max_resubscribes(mut self, max: u32) -> Self {
+        self.max_resubscribes = Some(max);
+        self
+    }
+
+    /// True if another resubscribe is allowed after `done` of them.
+    pub(crate) fn may_resubscribe(&self, done: u32) -> bool {
+        self.max_resubscribes.is_none_or(|max| done < max)
+    }
+}
+
+/// What waiting on a stream produced.
+pub(crate) enum Next<T> {
+    /// The stream yielded an item or ended.
+    Item(Option<T>),
+    /// The stream was silent and the stack didn't answer a probe.
+    Dead,
+}
+
+/// The stream's next item, probing the stack over `channel` whenever it has
+/// been silent for `idle.threshold`. Without `idle`, waits indefinitely.
+pub(crate) async fn next_or_dead<S>(
+    stream: &mut S,
+    channel: &Channel,
+    idle: Option<&IdleOptions>,
+) -> Next<S::Item>
+where
+    S: Stream + Unpin,
+{
+    let Some(idle) = idle else {
+        return Next::Item(stream.next().await);
+    };
+    loop {
+        match tokio::time::timeout(idle.threshold, stream.next()).await {
+            Ok(item) => return Next::Item(item),
+            Err(_) if probe(channel.clone(), idle.probe_timeout).await => {
+                tracing::debug!(
+                    "stream quiet for {:?}; the stack answered a probe, still waiting",
+                    idle.threshold
+                );
+            }
+            Err(_) => return Next::Dead,
+        }
+    }
+}
+
+/// True if the stack answers a unary call over `channel` within `timeout`.
+///
+/// Any answer counts, including an error status: it proves the connection
+/// still carries calls. Only timeouts and transport failures count as dead.
+pub(crate) async fn probe(channel: Channel, timeout: Duration) -> bool {
+    let mut client = ConfigServiceClient::new(channel);
+    let call = client.get_config(tonic::Request::new(GetConfigRequest {}));
+    match tokio::time::timeout(timeout, call).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(status)) => !is_transport_failure(status.code()),
+        Err(_) => false,
+    }
+}
+
+/// Status codes tonic reports when the call never reached the stack.
+fn is_transport_failure(code: Code) -> bool {
+    matches!(
+        code,
+        Code::Unavailable | Code::Cancelled | Code::DeadlineExceeded | Code::Unknown
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn quiet_stream_with_unreachable_stack_is_dead() {
+        // Nothing listens on the discard port; the lazy channel fails the
+        // probe on first use.
+        let channel = Channel::from_static("http://127.0.0.1:9").connect_lazy();
+        let mut silent = futures::stream::pending::<u32>();
+        let idle = IdleOptions {
+            threshold: Duration::from_millis(10),
+            probe_timeout: Duration::from_millis(500),
+            max_resubscribes: Some(1),
+        };
+        assert!(matches!(
+            next_or_dead(&mut silent, &channel, Some(&idle)).await,
+            Next::Dead
+        ));
+
+        let mut ready = futures::stream::iter([7u32]);
+        assert!(matches!(
+            next_or_dead(&mut ready, &channel, Some(&idle)).await,
+            Next::Item(Some(7))
+        ));
+
+        assert!(idle.may_resubscribe(0));
+        assert!(!idle.may_resubscribe(1));
+        assert!(IdleOptions::default().may_resubscribe(u32::MAX));
+    }
+}
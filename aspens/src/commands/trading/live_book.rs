@@ -0,0 +1,340 @@
+//! A local L2 order book kept in step with the orderbook stream.
+//!
+//! [`OrderBook`] folds orderbook events into sorted bid and ask levels as
+//! they arrive, so a bot can ask for the best bid, the mid or the size up
+//! to a price at any point without replaying the stream itself. It updates
+//! its levels on every event and answers queries without rebuilding them;
+//! [`BookState`](super::conflate::BookState), the conflation and snapshot
+//! state, is a thin wrapper over it.
+//!
+//! Subscribe with `historical_open_orders` so the book starts from the
+//! resting orders rather than only what changes afterwards:
+//!
+//! ```no_run
+//! # async fn example(url: String) -> aspens::error::Result<()> {
+//! use aspens::commands::trading::OrderBook;
+//! use aspens::commands::trading::stream_orderbook::{
+//!     StreamOrderbookOptions, stream_orderbook_stream,
+//! };
+//!
+//! let options = StreamOrderbookOptions {
+//!     market_id: "market-1".into(),
+//!     historical_open_orders: true,
+//!     ..Default::default()
+//! };
+//! let mut stream = std::pin::pin!(stream_orderbook_stream(url, options));
+//! let mut book = OrderBook::new("market-1");
+//! while book.apply_next(&mut stream).await.transpose()?.is_some() {
+//!     if let (Some(bid), Some(ask)) = (book.best_bid(), book.best_ask()) {
+//!         println!("{} x {}", bid.price, ask.price);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use futures::{Stream, StreamExt};
+
+use super::conflate::BookSnapshot;
+use super::depth::{DepthAtPrice, walk_levels};
+use super::stream_orderbook::arborter_pb::{OrderState, OrderbookEntry, Side};
+use super::stream_orderbook::{BookIndicators, book_indicators};
+use crate::error::Result;
+
+/// One price level of an [`OrderBook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    /// Level price. Raw pair decimals.
+    pub price: u128,
+    /// Total resting quantity at the price. Raw pair decimals.
+    pub quantity: u128,
+    /// Resting orders behind the quantity.
+    pub orders: usize,
+}
+
+/// Aggregate quantity and order count of one level.
+#[derive(Debug, Clone, Copy, Default)]
+struct LevelTotals {
+    quantity: u128,
+    orders: usize,
+}
+
+/// Sorted bid and ask levels of one market, maintained from orderbook
+/// events.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    market_id: String,
+    /// Resting orders by ID, to undo their contribution when they change.
+    orders: HashMap<u64, (Side, u128, u128)>,
+    bids: BTreeMap<u128, LevelTotals>,
+    asks: BTreeMap<u128, LevelTotals>,
+}
+
+impl OrderBook {
+    /// An empty book for `market_id`.
+    pub fn new(market_id: impl Into<String>) -> Self {
+        Self {
+            market_id: market_id.into(),
+            ..Self::default()
+        }
+    }
+
+    /// The market this book mirrors.
+    pub fn market_id(&self) -> &str {
+        &self.market_id
+    }
+
+    /// Fold one event in: an order with quantity left is inserted or
+    /// updated, a cancelled, settled or fully filled one removed. Events
+    /// with unparseable amounts are ignored.
+    pub fn apply(&mut self, entry: &OrderbookEntry) {
+        let closed = matches!(
+            OrderState::try_from(entry.state),
+            Ok(OrderState::Canceled | OrderState::Settled)
+        );
+        let side = match Side::try_from(entry.side) {
+            Ok(side @ (Side::Bid | Side::Ask)) => side,
+            _ => return,
+        };
+        let (Ok(price), Ok(quantity)) = (entry.price.parse(), entry.quantity.parse()) else {
+            return;
+        };
+        self.remove(entry.order_id);
+        if !closed && quantity > 0 {
+            self.orders.insert(entry.order_id, (side, price, quantity));
+            let level = self.levels_mut(side).entry(price).or_default();
+            level.quantity += quantity;
+            level.orders += 1;
+        }
+    }
+
+    /// Drop order `order_id`, e.g. one about to be replaced.
+    pub fn remove(&mut self, order_id: u64) {
+        if let Some((side, price, quantity)) = self.orders.remove(&order_id) {
+            self.remove_from_level(side, price, quantity);
+        }
+    }
+
+    /// Wait for the next event on `stream`, apply it and hand it back.
+    /// `None` once the stream ends; a stream error is returned as is and
+    /// leaves the book untouched.
+    pub async fn apply_next<S>(&mut self, stream: &mut S) -> Option<Result<OrderbookEntry>>
+    where
+        S: Stream<Item = Result<OrderbookEntry>> + Unpin,
+    {
+        let entry = stream.next().await?;
+        if let Ok(entry) = &entry {
+            self.apply(entry);
+        }
+        Some(entry)
+    }
+
+    /// Highest bid level.
+    pub fn best_bid(&self) -> Option<Level> {
+        self.bids.iter().next_back().map(to_level)
+    }
+
+    /// Lowest ask level.
+    pub fn best_ask(&self) -> Option<Level> {
+        self.asks.iter().next().map(to_level)
+    }
+
+    /// Mid of best bid and best ask, in raw pair decimals (fractional, so
+    /// a half tick survives). `None` unless both sides quote.
+    pub fn mid_price(&self) -> Option<f64> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        Some((bid.price as f64 + ask.price as f64) / 2.0)
+    }
+
+    /// Best ask minus best bid, zero if the book is crossed. `None` unless
+    /// both sides quote.
+    pub fn spread(&self) -> Option<u128> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        Some(ask.price.saturating_sub(bid.price))
+    }
+
+    /// Resting quantity at exactly `price` on `side`.
+    pub fn quantity_at(&self, side: Side, price: u128) -> u128 {
+        let levels = match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+            Side::Unspecified => return 0,
+        };
+        levels.get(&price).map_or(0, |l| l.quantity)
+    }
+
+    /// What the book fills for a `side` order limited at `limit_price`;
+    /// see [`depth_at_price`](super::depth::depth_at_price).
+    pub fn depth_at_price(
+        &self,
+        side: Side,
+        limit_price: u128,
+        pair_decimals: u32,
+    ) -> DepthAtPrice {
+        let levels =
+            |(&price, totals): (&u128, &LevelTotals)| (price, totals.quantity, totals.orders);
+        match side {
+            Side::Bid => walk_levels(
+                side,
+                limit_price,
+                pair_decimals,
+                self.asks.iter().map(levels),
+            ),
+            _ => walk_levels(
+                side,
+                limit_price,
+                pair_decimals,
+                self.bids.iter().rev().map(levels),
+            ),
+        }
+    }
+
+    /// Bid levels, best (highest) first.
+    pub fn bids(&self) -> impl Iterator<Item = Level> + '_ {
+        self.bids.iter().rev().map(to_level)
+    }
+
+    /// Ask levels, best (lowest) first.
+    pub fn asks(&self) -> impl Iterator<Item = Level> + '_ {
+        self.asks.iter().map(to_level)
+    }
+
+    /// Resting orders tracked.
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// True when no order is resting.
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Resting orders as `(side, price, quantity)`, in no particular order.
+    pub fn resting(&self) -> impl Iterator<Item = (Side, u128, u128)> + '_ {
+        self.orders.values().copied()
+    }
+
+    /// Depth, imbalance and mid prices over the top `depth` levels, as of
+    /// the last event applied; see
+    /// [`book_indicators`](super::stream_orderbook::book_indicators).
+    pub fn indicators(&self, depth: usize) -> BookIndicators {
+        let bids: Vec<_> = self
+            .bids()
+            .take(depth)
+            .map(|l| (l.price, l.quantity))
+            .collect();
+        let asks: Vec<_> = self
+            .asks()
+            .take(depth)
+            .map(|l| (l.price, l.quantity))
+            .collect();
+        book_indicators(&bids, &asks, depth)
+    }
+
+    /// The current levels as a [`BookSnapshot`].
+    pub fn snapshot(&self) -> BookSnapshot {
+        BookSnapshot {
+            market_id: self.market_id.clone(),
+            bids: self.bids().map(|l| (l.price, l.quantity)).collect(),
+            asks: self.asks().map(|l| (l.price, l.quantity)).collect(),
+            orders: self.orders.len(),
+            updates: 0,
+        }
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<u128, LevelTotals> {
+        if side == Side::Bid {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        }
+    }
+
+    fn remove_from_level(&mut self, side: Side, price: u128, quantity: u128) {
+        let levels = self.levels_mut(side);
+        let Some(level) = levels.get_mut(&price) else {
+            return;
+        };
+        level.quantity = level.quantity.saturating_sub(quantity);
+        level.orders = level.orders.saturating_sub(1);
+        if level.orders == 0 {
+            levels.remove(&price);
+        }
+    }
+}
+
+fn to_level((&price, totals): (&u128, &LevelTotals)) -> Level {
+    Level {
+        price,
+        quantity: totals.quantity,
+        orders: totals.orders,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::trading::conflate::BookState;
+    use crate::commands::trading::depth::depth_at_price;
+    use crate::fixtures::book_entry as entry;
+
+    #[test]
+    fn keeps_sorted_levels_through_updates() {
+        let mut book = OrderBook::new("m");
+        book.apply(&entry(1, Side::Bid, "100", "5"));
+        book.apply(&entry(2, Side::Bid, "100", "3"));
+        book.apply(&entry(3, Side::Bid, "99", "1"));
+        book.apply(&entry(4, Side::Ask, "101", "2"));
+        book.apply(&entry(5, Side::Ask, "103", "4"));
+
+        let best_bid = book.best_bid().unwrap();
+        assert_eq!(
+            (best_bid.price, best_bid.quantity, best_bid.orders),
+            (100, 8, 2)
+        );
+        assert_eq!(book.best_ask().unwrap().price, 101);
+        assert_eq!(book.mid_price(), Some(100.5));
+        assert_eq!(book.spread(), Some(1));
+
+        // Order 2 partially fills, order 4 is cancelled, order 1 moves.
+        book.apply(&entry(2, Side::Bid, "100", "1"));
+        book.apply(&OrderbookEntry {
+            state: OrderState::Canceled as i32,
+            ..entry(4, Side::Ask, "101", "2")
+        });
+        book.apply(&entry(1, Side::Bid, "98", "5"));
+
+        assert_eq!(book.quantity_at(Side::Bid, 100), 1);
+        assert_eq!(book.quantity_at(Side::Bid, 98), 5);
+        assert_eq!(book.best_ask().unwrap().price, 103);
+        let bids: Vec<_> = book.bids().map(|l| (l.price, l.quantity)).collect();
+        assert_eq!(bids, [(100, 1), (99, 1), (98, 5)]);
+        assert_eq!(book.len(), 4);
+        assert_eq!(book.indicators(2), book.snapshot().indicators(2));
+        assert_eq!(book.indicators(1).ask_depth, 4);
+    }
+
+    #[test]
+    fn remove_takes_the_order_out_of_its_level() {
+        let mut book = OrderBook::new("m");
+        book.apply(&entry(1, Side::Ask, "10100", "200"));
+        book.apply(&entry(2, Side::Ask, "10100", "100"));
+        book.apply(&entry(3, Side::Ask, "10500", "400"));
+        book.remove(1);
+        book.remove(3);
+        book.remove(42);
+
+        let asks: Vec<_> = book
+            .asks()
+            .map(|l| (l.price, l.quantity, l.orders))
+            .collect();
+        assert_eq!(asks, [(10100, 100, 1)]);
+        assert_eq!(book.len(), 1);
+        assert_eq!(
+            book.depth_at_price(Side::Bid, 10500, 2),
+            depth_at_price(&BookState::from(book.clone()), Side::Bid, 10500, 2)
+        );
+    }
+}
@@ -2,19 +2,77 @@
 // `aspens::evm::rpc` (gated on the `client` feature). Trading commands
 // import them via `use crate::evm::rpc::{MidribV3, IERC20};`.
 
+/// Trade volume by hour and day, top traders and spread for operator reports.
+pub mod activity;
+/// Inspect and set ERC-20 allowances for the trade contract and Permit2.
+pub mod allowance;
+/// Batch-auction schedule and results for markets that clear periodically.
+pub mod auction;
 /// Query balances across chains (native gas, ERC-20 / SPL, locked / withdrawable).
 pub mod balance;
+/// Bounded stream buffers with overflow policies and drop statistics.
+pub mod buffer;
 /// Submit a `cancel_order` request and decode the gRPC response.
 pub mod cancel_order;
+/// OHLCV candles aggregated from the trades stream.
+pub mod candles;
+/// Conflate the orderbook stream into periodic aggregated snapshots for UIs.
+pub mod conflate;
 /// Deposit tokens into the trading contract so they're available to trade.
 pub mod deposit;
+/// Size fillable on the book up to a limit price, with notional and order count.
+pub mod depth;
+/// Estimate a market order's average and worst fill price from the book.
+pub mod estimate;
+/// Resolve a market's maker/taker fee rates from config or the instance contracts.
+pub mod fees;
 /// Build the gasless cross-chain order envelope used by `send_order`.
 pub mod gasless;
+/// Hedera token association, required before an account can hold a token.
+pub mod hedera;
+/// Deposit / withdraw history from trade-contract logs, and closed-trade export.
+pub mod history;
+/// Idle detection and resubscribe for long-running streams.
+pub mod idle;
+/// Local L2 order book kept in step with the orderbook stream.
+pub mod live_book;
+/// Unsigned transaction files for cold-key signing and separate broadcast.
+pub mod offline;
+/// List a trader's resting orders on a market, from the orderbook replay.
+pub mod open_orders;
+/// Aggregated orderbook snapshot for a market, drawn as a price ladder.
+pub mod orderbook;
+/// Per-market positions, average entry and realized / unrealized PnL from fills.
+pub mod portfolio;
+/// Report rebates accrued by a referral code.
+pub mod rebates;
+/// Reconnect dropped streams with backoff, resuming where they left off.
+pub mod reconnect;
+/// Move a resting order to a new price or size: cancel, then resend.
+pub mod replace_order;
+/// Bounds on historical replay for the orderbook and trades streams.
+pub mod replay;
+/// Request dealer quotes for a size and accept one with a signed execution.
+pub mod rfq;
+/// Client-side risk limits checked before an order is signed.
+pub mod risk;
 /// Build, sign, and submit a buy/sell order envelope.
 pub mod send_order;
+/// Canonical, versioned encoding of signed orders, cancels and quote acceptances.
+pub mod signing;
 /// Subscribe to the orderbook stream for a given market.
 pub mod stream_orderbook;
 /// Subscribe to the trades stream for a given market.
 pub mod stream_trades;
+/// Best bid, best ask, spread and last trade price as one stream.
+pub mod ticker;
 /// Withdraw tokens from the trading contract back to the user's wallet.
 pub mod withdraw;
+
+pub use estimate::{FillEstimate, estimate_fill};
+pub use history::transfer_history;
+pub use live_book::OrderBook;
+pub use open_orders::get_orders;
+pub use orderbook::{OrderbookLevels, get_orderbook};
+pub use portfolio::{Portfolio, Position, mark_to_book, portfolio};
+pub use ticker::{Ticker, ticker};
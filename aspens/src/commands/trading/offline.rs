@@ -0,0 +1,559 @@
+//! Unsigned transaction files, for keys that never touch a networked machine.
+//!
+//! The deposit, approve, withdraw and deploy flows normally build, sign and
+//! broadcast in one call. Here the three steps are split:
+//!
+//! 1. On a networked machine, an `unsigned_*` function resolves everything
+//!    that needs the chain or the stack (nonce, gas, fees, calldata, a
+//!    withdrawal voucher) into an [`UnsignedTx`], saved with
+//!    [`UnsignedTx::write`].
+//! 2. On the air-gapped machine, [`UnsignedTx::sign`] turns the file into
+//!    EIP-2718 bytes without any network access, with any
+//!    [`AspensSigner`] that signs transactions.
+//! 3. Back online, [`broadcast`] sends the signed bytes.
+//!
+//! The transaction type is fixed when the file is built too: legacy on a
+//! chain without EIP-1559, as [`TxType::for_network`] resolves it.
+//!
+//! The nonce is fixed when the file is built, so transactions from the same
+//! account must be built, signed and broadcast one at a time: an ERC-20
+//! deposit needs its `approve` mined before the deposit is built, since the
+//! deposit's gas estimate depends on the allowance.
+//!
+//! A withdrawal voucher is requested with a signature over
+//! [`withdraw_request_message`], which the cold key also has to produce
+//! ([`sign_withdraw_request`]), and it expires; broadcast the withdraw before
+//! the expiry recorded in its description.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use alloy::consensus::{SignableTransaction, TxEip1559, TxEnvelope, TxLegacy};
+use alloy::eips::eip2718::Encodable2718;
+use alloy::network::{TransactionBuilder, TxSigner};
+use alloy::primitives::{Address, Bytes, TxKind, U160, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionRequest;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::withdraw::arborter_pb::WithdrawRequest;
+use super::withdraw::arborter_pb::arborter_service_client::ArborterServiceClient;
+use crate::chain::tx::TxType;
+use crate::chain_client::ARCH_SOLANA;
+use crate::commands::config::config_pb::{Chain, GetConfigResponse, Token};
+use crate::error::{AspensError, Result};
+use crate::evm::rpc::{IERC20, MidribV3};
+use crate::grpc::create_channel;
+use crate::signer::AspensSigner;
+use crate::util::parse_address;
+
+/// Format version written to [`UnsignedTx::version`].
+pub const UNSIGNED_TX_VERSION: u32 = 1;
+
+/// A fully specified transaction, waiting for a signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    /// File format version; see [`UNSIGNED_TX_VERSION`].
+    pub version: u32,
+    /// Network the transaction is for, as named in the stack config.
+    pub network: String,
+    /// EVM chain ID.
+    pub chain_id: u64,
+    /// Account that must sign (0x-prefixed).
+    pub from: String,
+    /// Contract the transaction calls (0x-prefixed).
+    pub to: String,
+    /// Account nonce at build time.
+    pub nonce: u64,
+    /// Gas limit, with headroom over the estimate.
+    pub gas_limit: u64,
+    /// [`TxType::Legacy`] or [`TxType::Eip1559`], resolved for the network
+    /// at build time.
+    pub tx_type: TxType,
+    /// Legacy gas price, in wei; unset for EIP-1559.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<u128>,
+    /// EIP-1559 fee cap, in wei; unset for legacy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<u128>,
+    /// EIP-1559 priority fee, in wei; unset for legacy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// Native value sent, in wei, as a decimal string.
+    pub value: String,
+    /// Calldata (0x-prefixed hex).
+    pub input: String,
+    /// What the transaction does, for the person signing it.
+    pub description: String,
+}
+
+impl UnsignedTx {
+    /// Read a file written by [`UnsignedTx::write`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            AspensError::invalid_input(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let tx: Self = serde_json::from_str(&json).map_err(|e| {
+            AspensError::invalid_input(format!(
+                "invalid unsigned transaction {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if tx.version != UNSIGNED_TX_VERSION {
+            return Err(AspensError::invalid_input(format!(
+                "{} is unsigned transaction format v{}; this SDK reads v{}",
+                path.display(),
+                tx.version,
+                UNSIGNED_TX_VERSION
+            )));
+        }
+        Ok(tx)
+    }
+
+    /// Write the transaction as pretty-printed JSON.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .map_err(|e| AspensError::other(format!("Failed to write {}: {}", path.display(), e)))
+    }
+
+    /// The transaction to sign.
+    fn to_envelope_tx(&self) -> Result<UnsignedEnvelope> {
+        let input = hex::decode(self.input.trim_start_matches("0x"))
+            .map_err(|e| AspensError::invalid_input(format!("invalid calldata: {}", e)))?;
+        let to = TxKind::Call(parse_address(&self.to)?);
+        let value = U256::from_str(&self.value).map_err(|e| {
+            AspensError::invalid_input(format!("invalid value '{}': {}", self.value, e))
+        })?;
+        let missing = |field: &str| {
+            AspensError::invalid_input(format!("{} transaction without {}", self.tx_type, field))
+        };
+        match self.tx_type {
+            TxType::Legacy => Ok(UnsignedEnvelope::Legacy(TxLegacy {
+                chain_id: Some(self.chain_id),
+                nonce: self.nonce,
+                gas_price: self.gas_price.ok_or_else(|| missing("gas_price"))?,
+                gas_limit: self.gas_limit,
+                to,
+                value,
+                input: Bytes::from(input),
+            })),
+            TxType::Eip1559 => Ok(UnsignedEnvelope::Eip1559(TxEip1559 {
+                chain_id: self.chain_id,
+                nonce: self.nonce,
+                gas_limit: self.gas_limit,
+                max_fee_per_gas: self
+                    .max_fee_per_gas
+                    .ok_or_else(|| missing("max_fee_per_gas"))?,
+                max_priority_fee_per_gas: self
+                    .max_priority_fee_per_gas
+                    .ok_or_else(|| missing("max_priority_fee_per_gas"))?,
+                to,
+                value,
+                access_list: Default::default(),
+                input: Bytes::from(input),
+            })),
+            TxType::Auto => Err(AspensError::invalid_input(
+                "unsigned transaction has no resolved tx_type; rebuild it",
+            )),
+        }
+    }
+
+    /// Sign with `signer`, which must be the `from` account, and return the
+    /// EIP-2718 bytes [`broadcast`] sends. Needs no network access unless
+    /// the signer itself is remote.
+    pub async fn sign(&self, signer: &dyn AspensSigner) -> Result<Vec<u8>> {
+        let from = parse_address(&self.from)?;
+        let signer = signer.tx_signer().ok_or_else(|| {
+            AspensError::invalid_input(format!(
+                "the transaction must be signed by {}, with a secp256k1 key that signs \
+                 transactions",
+                from
+            ))
+        })?;
+        if signer.address() != from {
+            return Err(AspensError::invalid_input(format!(
+                "the transaction must be signed by {}, but the key is for {}",
+                from,
+                signer.address()
+            )));
+        }
+        let envelope = match self.to_envelope_tx()? {
+            UnsignedEnvelope::Legacy(mut tx) => {
+                let signature = signer.sign_transaction(&mut tx).await?;
+                TxEnvelope::Legacy(tx.into_signed(signature))
+            }
+            UnsignedEnvelope::Eip1559(mut tx) => {
+                let signature = signer.sign_transaction(&mut tx).await?;
+                TxEnvelope::Eip1559(tx.into_signed(signature))
+            }
+        };
+        let mut encoded = Vec::new();
+        envelope.encode_2718(&mut encoded);
+        Ok(encoded)
+    }
+}
+
+/// An [`UnsignedTx`] as the transaction type it was built for.
+enum UnsignedEnvelope {
+    Legacy(TxLegacy),
+    Eip1559(TxEip1559),
+}
+
+/// Build an [`UnsignedTx`] calling `to` with `input`, taking the nonce, gas
+/// estimate (plus 10%) and fees from the chain. The transaction type is
+/// [`TxType::for_network`] for `chain`, resolved against its node.
+pub async fn unsigned_call(
+    chain: &Chain,
+    from: Address,
+    to: Address,
+    input: Bytes,
+    value: U256,
+    description: String,
+) -> Result<UnsignedTx> {
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+    let nonce = provider.get_transaction_count(from).await?;
+    let request = TransactionRequest::default()
+        .with_from(from)
+        .with_to(to)
+        .with_value(value)
+        .with_input(input.clone());
+    let gas_estimate = provider
+        .estimate_gas(request)
+        .await
+        .map_err(|e| AspensError::chain(format!("gas estimate failed ({}): {}", description, e)))?;
+    let chain_id = chain.chain_id as u64;
+    let tx_type = TxType::for_network(&chain.network)
+        .resolve(&provider, chain_id)
+        .await?;
+    let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = match tx_type {
+        TxType::Legacy => (Some(provider.get_gas_price().await?), None, None),
+        _ => {
+            let fees = provider.estimate_eip1559_fees().await?;
+            (
+                None,
+                Some(fees.max_fee_per_gas),
+                Some(fees.max_priority_fee_per_gas),
+            )
+        }
+    };
+
+    Ok(UnsignedTx {
+        version: UNSIGNED_TX_VERSION,
+        network: chain.network.clone(),
+        chain_id,
+        from: from.to_string(),
+        to: to.to_string(),
+        nonce,
+        gas_limit: gas_estimate + gas_estimate / 10,
+        tx_type,
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        value: value.to_string(),
+        input: format!("0x{}", hex::encode(&input)),
+        description,
+    })
+}
+
+/// The EVM chain, token and trade contract an unsigned transaction targets.
+fn evm_target<'a>(
+    config: &'a GetConfigResponse,
+    network: &str,
+    token_symbol: &str,
+) -> Result<(&'a Chain, &'a Token, Address)> {
+    let chain = config.get_chain(network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) {
+        return Err(AspensError::invalid_input(format!(
+            "unsigned transaction files are EVM-only; '{}' is a Solana chain",
+            network
+        )));
+    }
+    let token = config.get_token(network, token_symbol).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "Token '{}' not found on chain '{}'",
+            token_symbol, network
+        ))
+    })?;
+    let contract = chain
+        .trade_contract
+        .as_ref()
+        .filter(|tc| !tc.address.is_empty())
+        .ok_or_else(|| {
+            AspensError::not_found(format!("Trade contract not found for chain '{}'", network))
+        })?;
+    Ok((chain, token, parse_address(&contract.address)?))
+}
+
+/// An unsigned deposit of `amount` base units of `token_symbol` from
+/// `from`. An ERC-20 deposit needs an allowance first; see
+/// [`unsigned_approve`].
+pub async fn unsigned_deposit(
+    network: String,
+    token_symbol: String,
+    amount: u128,
+    from: Address,
+    config: &GetConfigResponse,
+) -> Result<UnsignedTx> {
+    let (chain, token, contract_addr) = evm_target(config, &network, &token_symbol)?;
+    super::hedera::ensure_associated(chain, token, from).await?;
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+    let contract = MidribV3::new(contract_addr, &provider);
+    let description = format!(
+        "deposit {} base units of {} on {}",
+        amount, token_symbol, network
+    );
+
+    if crate::evm::is_native_token(&token.address) {
+        let input = contract.depositNative().calldata().clone();
+        return unsigned_call(
+            chain,
+            from,
+            contract_addr,
+            input,
+            U256::from(amount),
+            description,
+        )
+        .await;
+    }
+
+    let token_addr = parse_address(&token.address)?;
+    let allowance = IERC20::new(token_addr, &provider)
+        .allowance(from, contract_addr)
+        .call()
+        .await?;
+    if allowance < U256::from(amount) {
+        return Err(AspensError::invalid_input(format!(
+            "{} has approved {} base units of {} for the trade contract, less than {}; \
+             build, sign and broadcast an approve first",
+            from, allowance, token_symbol, amount
+        )));
+    }
+    let input = contract
+        .deposit(token_addr, U160::from(amount))
+        .calldata()
+        .clone();
+    unsigned_call(chain, from, contract_addr, input, U256::ZERO, description).await
+}
+
+/// An unsigned ERC-20 `approve` letting the trade contract take `amount`
+/// base units of `token_symbol` from `from`.
+pub async fn unsigned_approve(
+    network: String,
+    token_symbol: String,
+    amount: u128,
+    from: Address,
+    config: &GetConfigResponse,
+) -> Result<UnsignedTx> {
+    let (chain, token, contract_addr) = evm_target(config, &network, &token_symbol)?;
+    if crate::evm::is_native_token(&token.address) {
+        return Err(AspensError::invalid_input(format!(
+            "{} is the native asset on '{}' and needs no approval",
+            token_symbol, network
+        )));
+    }
+    let token_addr = parse_address(&token.address)?;
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+    let input = IERC20::new(token_addr, &provider)
+        .approve(contract_addr, U256::from(amount))
+        .calldata()
+        .clone();
+    let description = format!(
+        "approve {} base units of {} for the trade contract on {}",
+        amount, token_symbol, network
+    );
+    unsigned_call(chain, from, token_addr, input, U256::ZERO, description).await
+}
+
+/// The message the account signs (EIP-191) to request a withdrawal voucher:
+/// `network|token|account|amount`, as the stack rebuilds it.
+pub fn withdraw_request_message(
+    network: &str,
+    token_symbol: &str,
+    amount: u128,
+    from: Address,
+    config: &GetConfigResponse,
+) -> Result<String> {
+    let (_, token, _) = evm_target(config, network, token_symbol)?;
+    Ok(format!("{}|{}|{}|{}", network, token.address, from, amount))
+}
+
+/// Sign a [`withdraw_request_message`] offline with an EVM `signer`,
+/// returning the 65-byte signature [`unsigned_withdraw`] takes.
+pub async fn sign_withdraw_request(message: &str, signer: &dyn AspensSigner) -> Result<Vec<u8>> {
+    if signer.curve() != crate::wallet::CurveType::Secp256k1 {
+        return Err(AspensError::invalid_input(format!(
+            "withdrawal requests are signed by an EVM key, not {}",
+            signer.address()
+        )));
+    }
+    Ok(signer.sign_message(message.as_bytes()).await?)
+}
+
+/// Request a withdrawal voucher from the stack at `url` with
+/// `request_signature` (see [`sign_withdraw_request`]), and build the
+/// unsigned transaction that submits it.
+///
+/// Requesting the voucher places a hold on the funds until the voucher is
+/// used or expires, so broadcast the result promptly.
+pub async fn unsigned_withdraw(
+    url: String,
+    network: String,
+    token_symbol: String,
+    amount: u128,
+    from: Address,
+    request_signature: Vec<u8>,
+    config: &GetConfigResponse,
+) -> Result<UnsignedTx> {
+    let (chain, token, contract_addr) = evm_target(config, &network, &token_symbol)?;
+    let token_addr = parse_address(&token.address)?;
+    super::hedera::ensure_associated(chain, token, from).await?;
+
+    let mut client = ArborterServiceClient::new(create_channel(&url).await?);
+    let voucher = client
+        .withdraw(tonic::Request::new(WithdrawRequest {
+            network: network.clone(),
+            token: token.address.clone(),
+            account: from.to_string(),
+            amount: amount.to_string(),
+            signature: request_signature,
+        }))
+        .await?
+        .into_inner();
+
+    let onchain_voucher = MidribV3::WithdrawalVoucher {
+        account: from,
+        token: token_addr,
+        amount: U256::from_str(&voucher.amount).unwrap_or(U256::from(amount)),
+        nonce: U256::from(voucher.nonce),
+        expiry: U256::from(voucher.expiry),
+    };
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+    let input = MidribV3::new(contract_addr, &provider)
+        .withdraw(onchain_voucher, Bytes::from(voucher.signature))
+        .calldata()
+        .clone();
+    let description = format!(
+        "withdraw {} base units of {} on {} (voucher nonce {}, expires at unix {})",
+        amount, token_symbol, network, voucher.nonce, voucher.expiry
+    );
+    unsigned_call(chain, from, contract_addr, input, U256::ZERO, description).await
+}
+
+/// Read a signed transaction saved as hex, with or without `0x`.
+pub fn read_signed(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        AspensError::invalid_input(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    hex::decode(text.trim().trim_start_matches("0x")).map_err(|e| {
+        AspensError::invalid_input(format!(
+            "{} is not a hex-encoded transaction: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Broadcast a signed transaction and return its hash (0x-prefixed hex).
+pub async fn broadcast(rpc_url: String, signed_tx: Vec<u8>) -> Result<String> {
+    let provider = ProviderBuilder::new().connect_http(Url::parse(&rpc_url)?);
+    let pending = provider
+        .send_raw_transaction(&signed_tx)
+        .await
+        .map_err(|e| AspensError::chain(format!("Failed to broadcast transaction: {}", e)))?;
+    Ok(format!("{:?}", pending.tx_hash()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::eips::eip2718::Decodable2718;
+    use alloy::signers::local::PrivateKeySigner;
+
+    // alloy-consensus only implements `SignerRecoverable` with its `k256`
+    // feature, which this crate doesn't enable; recover from the prehash.
+    fn recover_signer(envelope: &TxEnvelope) -> Address {
+        envelope
+            .signature()
+            .recover_address_from_prehash(&envelope.signature_hash())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn signs_offline_for_the_from_account_only() {
+        let signer = PrivateKeySigner::random();
+        let tx = UnsignedTx {
+            version: UNSIGNED_TX_VERSION,
+            network: "base-sepolia".into(),
+            chain_id: 84532,
+            from: signer.address().to_string(),
+            to: "0x1111111111111111111111111111111111111111".into(),
+            nonce: 7,
+            gas_limit: 60_000,
+            tx_type: TxType::Eip1559,
+            gas_price: None,
+            max_fee_per_gas: Some(2_000_000_000),
+            max_priority_fee_per_gas: Some(1_000_000),
+            value: "0".into(),
+            input: "0x095ea7b3".into(),
+            description: "approve".into(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tx.json");
+        tx.write(&path).unwrap();
+        let read = UnsignedTx::read(&path).unwrap();
+        assert_eq!(read, tx);
+
+        let signed = read.sign(&signer).await.unwrap();
+        let envelope = TxEnvelope::decode_2718(&mut signed.as_slice()).unwrap();
+        assert!(matches!(envelope, TxEnvelope::Eip1559(_)));
+        assert_eq!(recover_signer(&envelope), signer.address());
+
+        assert!(tx.sign(&PrivateKeySigner::random()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn legacy_files_sign_as_gas_priced_transactions() {
+        let signer = PrivateKeySigner::random();
+        let tx = UnsignedTx {
+            version: UNSIGNED_TX_VERSION,
+            network: "hedera-testnet".into(),
+            chain_id: 296,
+            from: signer.address().to_string(),
+            to: "0x1111111111111111111111111111111111111111".into(),
+            nonce: 0,
+            gas_limit: 60_000,
+            tx_type: TxType::Legacy,
+            gas_price: Some(500_000_000_000),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            value: "0".into(),
+            input: "0x".into(),
+            description: "deposit".into(),
+        };
+        let json = serde_json::to_string(&tx).unwrap();
+        assert!(json.contains("\"tx_type\":\"legacy\""), "{json}");
+        assert!(!json.contains("max_fee_per_gas"), "{json}");
+
+        let signed = tx.sign(&signer).await.unwrap();
+        let envelope = TxEnvelope::decode_2718(&mut signed.as_slice()).unwrap();
+        let TxEnvelope::Legacy(legacy) = &envelope else {
+            panic!("expected a legacy transaction, got {envelope:?}");
+        };
+        assert_eq!(legacy.tx().gas_price, 500_000_000_000);
+        assert_eq!(recover_signer(&envelope), signer.address());
+
+        let unpriced = UnsignedTx {
+            gas_price: None,
+            ..tx
+        };
+        assert!(unpriced.sign(&signer).await.is_err());
+    }
+}
@@ -0,0 +1,223 @@
+//! A trader's resting orders on one market.
+//!
+//! The arborter has no request/response call for open orders; the orderbook
+//! stream is the source of truth. [`get_orders`] subscribes with
+//! `historical_open_orders` and `filter_by_trader` set, collects the replay
+//! of the trader's orders for a short window, and keeps the latest state of
+//! each order that is still on the book.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
+use serde::Serialize;
+
+use super::replay::HistoricalReplay;
+use super::stream_orderbook::{
+    StreamOrderbookOptions,
+    arborter_pb::{OrderState, OrderbookEntry, Side},
+    stream_orderbook_channel,
+};
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+use crate::util::format_utc;
+
+/// How long [`get_orders`] collects the orderbook replay by default.
+pub const DEFAULT_ORDERS_WINDOW: Duration = Duration::from_millis(1_500);
+
+/// The open orders `trader` has on `market_id`, oldest first.
+///
+/// Listens to the orderbook stream for [`DEFAULT_ORDERS_WINDOW`]; use
+/// [`get_orders_within`] to wait longer on a slow stack.
+pub async fn get_orders(
+    url: String,
+    market_id: String,
+    trader: String,
+) -> Result<Vec<OrderbookEntry>> {
+    get_orders_within(url, market_id, trader, DEFAULT_ORDERS_WINDOW).await
+}
+
+/// [`get_orders`], collecting the replay for `window`.
+pub async fn get_orders_within(
+    url: String,
+    market_id: String,
+    trader: String,
+    window: Duration,
+) -> Result<Vec<OrderbookEntry>> {
+    let (mut rx, _handle) = stream_orderbook_channel(
+        url,
+        StreamOrderbookOptions {
+            market_id,
+            historical_open_orders: true,
+            filter_by_trader: Some(trader),
+            grpc: None,
+            replay: HistoricalReplay::default(),
+            idle: None,
+            reconnect: None,
+        },
+    )
+    .await?;
+
+    let mut entries = Vec::new();
+    let deadline = tokio::time::sleep(window);
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            entry = rx.recv() => match entry {
+                Some(entry) => entries.push(entry),
+                None => break,
+            },
+        }
+    }
+    Ok(open_orders(entries))
+}
+
+/// Reduce a replay of orderbook updates to the orders still resting:
+/// the latest update per order ID, kept if it is pending or confirmed with
+/// quantity left. Sorted by timestamp, then order ID.
+pub fn open_orders(entries: impl IntoIterator<Item = OrderbookEntry>) -> Vec<OrderbookEntry> {
+    // Latest state per order ID; the stream replays updates in order.
+    let mut latest: HashMap<u64, OrderbookEntry> = HashMap::new();
+    for entry in entries {
+        latest.insert(entry.order_id, entry);
+    }
+    let mut open: Vec<OrderbookEntry> = latest
+        .into_values()
+        .filter(|e| {
+            matches!(
+                OrderState::try_from(e.state),
+                Ok(OrderState::Pending | OrderState::Confirmed)
+            ) && e.quantity.parse::<u128>().is_ok_and(|q| q > 0)
+        })
+        .collect();
+    open.sort_by_key(|e| (e.timestamp, e.order_id));
+    open
+}
+
+/// One open order as shown to users: price and remaining quantity scaled
+/// by the market's pair decimals.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrderSummary {
+    /// Arborter order ID.
+    pub order_id: u64,
+    /// `buy` or `sell`.
+    pub side: String,
+    /// Limit price.
+    pub price: String,
+    /// Quantity still on the book.
+    pub remaining: String,
+    /// `pending` or `confirmed`.
+    pub state: String,
+    /// When the order was placed, in Unix seconds.
+    pub placed_at: u64,
+}
+
+impl OrderSummary {
+    /// Summarize `order` on a market with `pair_decimals`.
+    pub fn new(order: &OrderbookEntry, pair_decimals: u32) -> Self {
+        let scaled = |raw: &str| match raw.parse::<u128>() {
+            Ok(v) => format_decimal_amount(v, pair_decimals),
+            Err(_) => raw.to_string(),
+        };
+        let side = match Side::try_from(order.side) {
+            Ok(Side::Bid) => "buy",
+            Ok(Side::Ask) => "sell",
+            _ => "unknown",
+        };
+        let state = match OrderState::try_from(order.state) {
+            Ok(OrderState::Pending) => "pending",
+            Ok(OrderState::Confirmed) => "confirmed",
+            _ => "unknown",
+        };
+        Self {
+            order_id: order.order_id,
+            side: side.to_string(),
+            price: scaled(&order.price),
+            remaining: scaled(&order.quantity),
+            state: state.to_string(),
+            placed_at: order.timestamp / 1_000,
+        }
+    }
+}
+
+/// Render open orders as a table, prices and remaining quantities scaled by
+/// the market's `pair_decimals`.
+pub fn display_orders(orders: &[OrderbookEntry], pair_decimals: u32) -> String {
+    let summaries: Vec<OrderSummary> = orders
+        .iter()
+        .map(|order| OrderSummary::new(order, pair_decimals))
+        .collect();
+    display_order_summaries(&summaries)
+}
+
+/// Render [`OrderSummary`] rows as the table [`display_orders`] prints.
+pub fn display_order_summaries(orders: &[OrderSummary]) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header(vec![
+        "Order ID",
+        "Side",
+        "Price",
+        "Remaining",
+        "State",
+        "Placed (UTC)",
+    ]);
+    for order in orders {
+        table.add_row(vec![
+            order.order_id.to_string(),
+            order.side.clone(),
+            order.price.clone(),
+            order.remaining.clone(),
+            order.state.clone(),
+            format_utc(order.placed_at),
+        ]);
+    }
+    table.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(order_id: u64, timestamp: u64, quantity: &str, state: OrderState) -> OrderbookEntry {
+        OrderbookEntry {
+            timestamp,
+            order_id,
+            price: "1500000".to_string(),
+            quantity: quantity.to_string(),
+            side: Side::Bid as i32,
+            state: state as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keeps_latest_resting_state_per_order() {
+        let open = open_orders(vec![
+            entry(1, 2_000, "100", OrderState::Pending),
+            entry(2, 1_000, "50", OrderState::Confirmed),
+            entry(1, 2_000, "40", OrderState::Confirmed),
+            entry(3, 3_000, "10", OrderState::Confirmed),
+            entry(3, 3_000, "10", OrderState::Canceled),
+            entry(4, 4_000, "0", OrderState::Confirmed),
+        ]);
+        let ids: Vec<(u64, &str)> = open
+            .iter()
+            .map(|e| (e.order_id, e.quantity.as_str()))
+            .collect();
+        assert_eq!(ids, vec![(2, "50"), (1, "40")]);
+
+        let table = display_orders(&open, 6);
+        assert!(table.contains("1.5"), "{table}");
+        assert!(table.contains("buy"), "{table}");
+        assert!(table.contains("1970-01-01 00:00:02"), "{table}");
+
+        let summary = OrderSummary::new(&open[1], 6);
+        assert_eq!(
+            (summary.side.as_str(), summary.remaining.as_str()),
+            ("buy", "0.000040")
+        );
+        assert_eq!(summary.placed_at, 2);
+    }
+}
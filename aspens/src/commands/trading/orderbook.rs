@@ -0,0 +1,179 @@
+//! Aggregated orderbook snapshot and its price-ladder rendering.
+//!
+//! The stack has no unary `GetOrderbook` RPC, so [`get_orderbook`] takes
+//! the snapshot the way [`fetch_book_state`] does: it subscribes with
+//! `historical_open_orders`, folds the resting orders replayed within
+//! [`SNAPSHOT_WINDOW`], and aggregates them into price levels.
+//! [`OrderbookLevels::ladder`] draws them as a price ladder for a terminal.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::conflate::{BookState, fetch_book_state};
+use super::live_book::Level;
+use super::stream_orderbook::arborter_pb::Side;
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+
+/// How long [`get_orderbook`] collects the replayed book for.
+pub const SNAPSHOT_WINDOW: Duration = Duration::from_millis(1_500);
+
+/// Widest size bar [`OrderbookLevels::ladder`] draws.
+const BAR_WIDTH: usize = 30;
+
+/// Bid and ask levels of a market at one point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderbookLevels {
+    /// The market.
+    pub market_id: String,
+    /// Bid levels, best (highest) first.
+    pub bids: Vec<Level>,
+    /// Ask levels, best (lowest) first.
+    pub asks: Vec<Level>,
+}
+
+impl OrderbookLevels {
+    /// Aggregate the resting orders of `book` into levels, keeping the best
+    /// `depth` on each side.
+    pub fn from_state(market_id: impl Into<String>, book: &BookState, depth: usize) -> Self {
+        let mut bids: BTreeMap<u128, Level> = BTreeMap::new();
+        let mut asks: BTreeMap<u128, Level> = BTreeMap::new();
+        for (side, price, quantity) in book.resting() {
+            let levels = match side {
+                Side::Bid => &mut bids,
+                Side::Ask => &mut asks,
+                Side::Unspecified => continue,
+            };
+            let level = levels.entry(price).or_insert(Level {
+                price,
+                quantity: 0,
+                orders: 0,
+            });
+            level.quantity += quantity;
+            level.orders += 1;
+        }
+        Self {
+            market_id: market_id.into(),
+            bids: bids.into_values().rev().take(depth).collect(),
+            asks: asks.into_values().take(depth).collect(),
+        }
+    }
+
+    /// Best ask minus best bid, zero if the book is crossed. `None` unless
+    /// both sides quote.
+    pub fn spread(&self) -> Option<u128> {
+        let (bid, ask) = (self.bids.first()?, self.asks.first()?);
+        Some(ask.price.saturating_sub(bid.price))
+    }
+
+    /// Draw the levels as a price ladder: asks above the spread, bids
+    /// below, prices falling from top to bottom, each size with a bar
+    /// scaled to the largest level shown. Amounts are scaled by
+    /// `pair_decimals`.
+    pub fn ladder(&self, pair_decimals: u32) -> String {
+        let scaled = |raw: u128| format_decimal_amount(raw, pair_decimals);
+        // Asks worst first, so the best ask sits right above the spread.
+        let rows: Vec<(&Level, String, String)> = self
+            .asks
+            .iter()
+            .rev()
+            .chain(&self.bids)
+            .map(|level| (level, scaled(level.price), scaled(level.quantity)))
+            .collect();
+        let price_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(5);
+        let size_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0).max(4);
+        let largest = rows.iter().map(|r| r.0.quantity).max().unwrap_or(0);
+        let bar = |quantity: u128| {
+            if largest == 0 {
+                return String::new();
+            }
+            // At least one mark for any resting size.
+            let len = (quantity.saturating_mul(BAR_WIDTH as u128) / largest).max(1);
+            "#".repeat(len as usize)
+        };
+
+        let mut out = format!(
+            "{:>price_width$}  {:>size_width$}  Orders\n",
+            "Price", "Size"
+        );
+        let line = |out: &mut String, (level, price, size): &(&Level, String, String)| {
+            out.push_str(&format!(
+                "{:>price_width$}  {:>size_width$}  {:>6}  {}\n",
+                price,
+                size,
+                level.orders,
+                bar(level.quantity)
+            ));
+        };
+        let (asks, bids) = rows.split_at(self.asks.len());
+        asks.iter().for_each(|row| line(&mut out, row));
+        let spread = self.spread().map_or_else(|| "-".to_string(), scaled);
+        out.push_str(&format!(
+            "{:-^width$}\n",
+            format!(" spread {} ", spread),
+            width = price_width + size_width + 10
+        ));
+        bids.iter().for_each(|row| line(&mut out, row));
+        out
+    }
+}
+
+/// Snapshot the book of `market_id`, keeping the best `depth` levels on
+/// each side.
+pub async fn get_orderbook(
+    url: String,
+    market_id: String,
+    depth: usize,
+) -> Result<OrderbookLevels> {
+    let book = fetch_book_state(url, market_id.clone(), SNAPSHOT_WINDOW).await?;
+    Ok(OrderbookLevels::from_state(market_id, &book, depth))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::book_entry;
+
+    fn levels(depth: usize) -> OrderbookLevels {
+        let mut book = BookState::default();
+        for e in [
+            book_entry(1, Side::Ask, "10100", "200"),
+            book_entry(2, Side::Ask, "10100", "100"),
+            book_entry(3, Side::Ask, "10500", "600"),
+            book_entry(4, Side::Bid, "9900", "300"),
+            book_entry(5, Side::Bid, "9500", "150"),
+        ] {
+            book.apply(&e);
+        }
+        OrderbookLevels::from_state("m", &book, depth)
+    }
+
+    #[test]
+    fn aggregates_and_truncates_levels() {
+        let book = levels(1);
+        assert_eq!(
+            book.asks,
+            [Level {
+                price: 10100,
+                quantity: 300,
+                orders: 2
+            }]
+        );
+        assert_eq!(book.bids.len(), 1);
+        assert_eq!(book.bids[0].price, 9900);
+        assert_eq!(book.spread(), Some(200));
+    }
+
+    #[test]
+    fn ladder_puts_asks_above_bids() {
+        let ladder = levels(20).ladder(2);
+        let lines: Vec<_> = ladder.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[1].starts_with("105.00"));
+        assert!(lines[2].starts_with("101.00"));
+        assert!(lines[3].contains(" spread 2.00 "));
+        assert!(lines[4].starts_with(" 99.00"));
+        // The largest level gets the full bar.
+        assert!(lines[1].ends_with(&"#".repeat(BAR_WIDTH)));
+    }
+}
@@ -0,0 +1,366 @@
+//! Positions and PnL derived from a trader's fills.
+//!
+//! A [`Portfolio`] folds [`Fill`]s into one [`Position`] per market: the
+//! signed quantity held, the average entry price of that quantity, the
+//! PnL realized by closing it down and the fees paid along the way. Given
+//! a mark price, a position also has an unrealized PnL.
+//!
+//! Fills come from the trades stream ([`Fill::from_record`], matching the
+//! trader's address against both sides of each
+//! [`trade_history`](super::history::trade_history) record) or, with the
+//! `journal` feature, from the local journal ([`Fill::from_journal`]).
+//! [`portfolio`] does the former for a set of markets, and
+//! [`mark_to_book`] marks each position at the middle of the current top
+//! of book.
+//!
+//! Average entry is the weighted average of the fills that opened the
+//! position; fills that reduce it realize `(exit - entry) × quantity`, and
+//! a fill that crosses zero closes the old position and opens the rest at
+//! its own price. PnL is in the quote token, before fees.
+
+use std::collections::BTreeMap;
+
+use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
+
+use super::history::{TradeRecord, trade_history};
+use super::orderbook::get_orderbook;
+use super::stream_orderbook::arborter_pb::Side;
+use crate::commands::config::config_pb::Market;
+use crate::decimals::{format_decimal_amount, parse_decimal_amount};
+use crate::error::Result;
+
+/// One of the trader's fills. Prices and amounts are raw pair decimals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fill {
+    /// The market.
+    pub market_id: String,
+    /// The trader's side: [`Side::Bid`] bought, [`Side::Ask`] sold.
+    pub side: Side,
+    /// Fill price.
+    pub price: u128,
+    /// Filled quantity.
+    pub quantity: u128,
+    /// Fee the trader paid on the fill, in the quote token.
+    pub fee: u128,
+    /// When it filled, unix milliseconds.
+    pub timestamp_ms: u64,
+}
+
+impl Fill {
+    /// The trader's side of `record`, if `address` bought or sold in it.
+    /// The fee is the maker or taker fee, whichever role `address` had.
+    pub fn from_record(record: &TradeRecord, address: &str, pair_decimals: u32) -> Option<Self> {
+        let side = if record.buyer.eq_ignore_ascii_case(address) {
+            Side::Bid
+        } else if record.seller.eq_ignore_ascii_case(address) {
+            Side::Ask
+        } else {
+            return None;
+        };
+        let fee = if record.taker.eq_ignore_ascii_case(address) {
+            &record.taker_fee
+        } else {
+            &record.maker_fee
+        };
+        let raw = |amount: &str| parse_decimal_amount(amount, pair_decimals).ok();
+        Some(Self {
+            market_id: record.market_id.clone(),
+            side,
+            price: raw(&record.price)?,
+            quantity: raw(&record.quantity)?,
+            fee: fee.as_deref().and_then(raw).unwrap_or(0),
+            timestamp_ms: record.timestamp_ms,
+        })
+    }
+
+    /// A fill recorded in the local journal. `None` for any other kind of
+    /// entry. The journal records no fees.
+    #[cfg(feature = "journal")]
+    pub fn from_journal(entry: &crate::journal::JournalEntry, pair_decimals: u32) -> Option<Self> {
+        if entry.kind != crate::journal::EntryKind::Fill {
+            return None;
+        }
+        let raw =
+            |amount: &Option<String>| parse_decimal_amount(amount.as_deref()?, pair_decimals).ok();
+        Some(Self {
+            market_id: entry.market_id.clone()?,
+            side: match entry.side.as_deref()? {
+                "buy" => Side::Bid,
+                "sell" => Side::Ask,
+                _ => return None,
+            },
+            price: raw(&entry.price)?,
+            quantity: raw(&entry.quantity)?,
+            fee: 0,
+            timestamp_ms: entry.timestamp_ms,
+        })
+    }
+}
+
+/// The trader's holding in one market. Amounts are raw pair decimals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Position {
+    /// The market.
+    pub market_id: String,
+    /// Pair decimals of the market.
+    pub pair_decimals: u32,
+    /// Base quantity held: positive long, negative short.
+    pub quantity: i128,
+    /// Average price the open quantity was entered at; 0 when flat.
+    pub avg_entry_price: u128,
+    /// PnL locked in by reducing the position.
+    pub realized_pnl: i128,
+    /// Fees paid on every fill.
+    pub fees: u128,
+    /// Price the open quantity is valued at.
+    pub mark_price: Option<u128>,
+    /// Price of the latest fill.
+    pub last_fill_price: Option<u128>,
+}
+
+impl Position {
+    /// Fold `fill` into the position.
+    pub fn apply(&mut self, fill: &Fill) {
+        let scale = 10i128.pow(self.pair_decimals);
+        let signed = match fill.side {
+            Side::Bid => fill.quantity as i128,
+            Side::Ask => -(fill.quantity as i128),
+            Side::Unspecified => return,
+        };
+        let price = fill.price as i128;
+        let entry = self.avg_entry_price as i128;
+        let held = self.quantity;
+
+        if held == 0 || held.signum() == signed.signum() {
+            // Opening or adding: average the entry.
+            let total = held.abs() + signed.abs();
+            self.avg_entry_price = ((entry * held.abs() + price * signed.abs()) / total) as u128;
+        } else {
+            // Reducing: realize on the part that closes.
+            let closed = held.abs().min(signed.abs());
+            self.realized_pnl += (price - entry) * closed * held.signum() / scale;
+            if signed.abs() > held.abs() {
+                // Flipped: the rest opens at the fill price.
+                self.avg_entry_price = fill.price;
+            } else if signed.abs() == held.abs() {
+                self.avg_entry_price = 0;
+            }
+        }
+        self.quantity = held + signed;
+        self.fees += fill.fee;
+        self.last_fill_price = Some(fill.price);
+    }
+
+    /// `(mark - entry) × quantity` of the open position; `None` without a
+    /// mark price.
+    pub fn unrealized_pnl(&self) -> Option<i128> {
+        let mark = self.mark_price? as i128;
+        let entry = self.avg_entry_price as i128;
+        Some((mark - entry) * self.quantity / 10i128.pow(self.pair_decimals))
+    }
+}
+
+/// Positions across markets, keyed by market ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Portfolio {
+    positions: BTreeMap<String, Position>,
+}
+
+impl Portfolio {
+    /// An empty portfolio.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold `fill` into its market's position, opening one with
+    /// `pair_decimals` if it's the first fill there.
+    pub fn apply(&mut self, fill: &Fill, pair_decimals: u32) {
+        self.positions
+            .entry(fill.market_id.clone())
+            .or_insert_with(|| Position {
+                market_id: fill.market_id.clone(),
+                pair_decimals,
+                ..Position::default()
+            })
+            .apply(fill);
+    }
+
+    /// Value `market_id`'s position at `price`.
+    pub fn set_mark(&mut self, market_id: &str, price: u128) {
+        if let Some(position) = self.positions.get_mut(market_id) {
+            position.mark_price = Some(price);
+        }
+    }
+
+    /// The position in `market_id`, if the trader has filled there.
+    pub fn position(&self, market_id: &str) -> Option<&Position> {
+        self.positions.get(market_id)
+    }
+
+    /// Every position, by market ID.
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+
+    /// Whether there are no positions.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+/// Replay the fills of `addresses` (the trader's wallets) on `markets`
+/// since `since_ms` from the trades stream, and [`mark_to_book`] the
+/// positions.
+///
+/// Each market is replayed in full from `since_ms`, which takes at least
+/// [`DEFAULT_REPLAY_QUIET`](super::history::trades::DEFAULT_REPLAY_QUIET)
+/// per market. A position opened before `since_ms` is missing its opening
+/// fills.
+pub async fn portfolio(
+    url: String,
+    markets: &[&Market],
+    addresses: &[String],
+    since_ms: u64,
+) -> Result<Portfolio> {
+    let mut portfolio = Portfolio::new();
+    for market in markets {
+        let pair_decimals = market.pair_decimals as u32;
+        let records = trade_history(url.clone(), market, since_ms, None, None).await?;
+        for record in &records {
+            let fill = addresses
+                .iter()
+                .find_map(|address| Fill::from_record(record, address, pair_decimals));
+            if let Some(fill) = fill {
+                portfolio.apply(&fill, pair_decimals);
+            }
+        }
+    }
+    mark_to_book(url, &mut portfolio).await?;
+    Ok(portfolio)
+}
+
+/// Mark every position at the middle of its market's top of book: one
+/// side if only one quotes, the last fill if neither does.
+pub async fn mark_to_book(url: String, portfolio: &mut Portfolio) -> Result<()> {
+    for position in portfolio.positions.values_mut() {
+        let book = get_orderbook(url.clone(), position.market_id.clone(), 1).await?;
+        position.mark_price = match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) / 2),
+            (Some(level), None) | (None, Some(level)) => Some(level.price),
+            (None, None) => position.last_fill_price,
+        };
+    }
+    Ok(())
+}
+
+/// Render `portfolio` as a table, one row per position. `name` turns a
+/// market ID into the label shown.
+pub fn display_portfolio(portfolio: &Portfolio, name: impl Fn(&str) -> String) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header(vec![
+        "Market",
+        "Position",
+        "Avg Entry",
+        "Mark",
+        "Realized",
+        "Unrealized",
+        "Fees",
+    ]);
+    for p in portfolio.positions() {
+        let d = p.pair_decimals;
+        let price = |raw: Option<u128>| {
+            raw.map_or_else(|| "-".to_string(), |raw| format_decimal_amount(raw, d))
+        };
+        table.add_row(vec![
+            name(&p.market_id),
+            format_signed(p.quantity, d),
+            price((p.quantity != 0).then_some(p.avg_entry_price)),
+            price(p.mark_price),
+            format_signed(p.realized_pnl, d),
+            p.unrealized_pnl()
+                .map_or_else(|| "-".to_string(), |pnl| format_signed(pnl, d)),
+            format_decimal_amount(p.fees, d),
+        ]);
+    }
+    table.to_string()
+}
+
+fn format_signed(raw: i128, decimals: u32) -> String {
+    let amount = format_decimal_amount(raw.unsigned_abs(), decimals);
+    if raw < 0 {
+        format!("-{}", amount)
+    } else {
+        amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(side: Side, price: u128, quantity: u128) -> Fill {
+        Fill {
+            market_id: "m".into(),
+            side,
+            price,
+            quantity,
+            fee: 1,
+            timestamp_ms: 0,
+        }
+    }
+
+    #[test]
+    fn averages_entries_and_realizes_on_reduce_and_flip() {
+        let mut book = Portfolio::new();
+        // Buy 2 @ 1.00, buy 2 @ 2.00: long 4 @ 1.50.
+        book.apply(&fill(Side::Bid, 100, 200), 2);
+        book.apply(&fill(Side::Bid, 200, 200), 2);
+        let p = book.position("m").unwrap();
+        assert_eq!((p.quantity, p.avg_entry_price), (400, 150));
+
+        // Sell 1 @ 2.50: realize 1 × 1.00.
+        book.apply(&fill(Side::Ask, 250, 100), 2);
+        assert_eq!(book.position("m").unwrap().realized_pnl, 100);
+
+        // Sell 5 @ 1.00: close 3 for -1.50, then short 2 @ 1.00.
+        book.apply(&fill(Side::Ask, 100, 500), 2);
+        let p = book.position("m").unwrap();
+        assert_eq!((p.quantity, p.avg_entry_price), (-200, 100));
+        assert_eq!(p.realized_pnl, -50);
+        assert_eq!(p.fees, 4);
+
+        // Short 2 marked at 0.75 is up 0.50.
+        book.set_mark("m", 75);
+        assert_eq!(book.position("m").unwrap().unrealized_pnl(), Some(50));
+    }
+
+    #[test]
+    fn fills_from_records_take_the_traders_side_and_fee() {
+        let record = TradeRecord {
+            timestamp_ms: 1,
+            market_id: "m".into(),
+            side: "buy".into(),
+            price: "1.50".into(),
+            quantity: "2.00".into(),
+            notional: "3.00".into(),
+            buyer: "0xTaker".into(),
+            seller: "0xmaker".into(),
+            maker: "0xmaker".into(),
+            taker: "0xTaker".into(),
+            order_hit: 1,
+            maker_fee: Some("0.01".into()),
+            taker_fee: Some("0.02".into()),
+        };
+        let taker = Fill::from_record(&record, "0xtaker", 2).unwrap();
+        assert_eq!((taker.side, taker.price, taker.fee), (Side::Bid, 150, 2));
+        let maker = Fill::from_record(&record, "0xMAKER", 2).unwrap();
+        assert_eq!((maker.side, maker.fee), (Side::Ask, 1));
+        assert!(Fill::from_record(&record, "0xother", 2).is_none());
+
+        let mut book = Portfolio::new();
+        book.apply(&taker, 2);
+        let table = display_portfolio(&book, str::to_string);
+        assert!(table.contains("2.00") && table.contains("1.50"));
+    }
+}
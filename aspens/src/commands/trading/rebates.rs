@@ -0,0 +1,136 @@
+//! Referral rebate reporting.
+//!
+//! Orders sent with a referral code (see
+//! [`OrderTags::referral`](super::send_order::OrderTags::referral)) credit the
+//! code's owner with a share of their fees on stacks that run affiliate
+//! attribution. [`get_rebates`] reports what a code has earned so far.
+
+use std::fmt;
+
+use super::send_order::arborter_pb::arborter_service_client::ArborterServiceClient;
+use super::send_order::arborter_pb::{RebatesRequest, RebatesResponse};
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::decimals::format_decimal_amount;
+use crate::error::{AspensError, Result};
+use crate::grpc::create_channel;
+
+/// Rebates accrued by one referral code, ready for display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebateReport {
+    /// The stack's response.
+    pub rebates: RebatesResponse,
+    /// Token decimals per rebate entry, from the config; `None` prints the
+    /// raw amount.
+    pub decimals: Vec<Option<u32>>,
+}
+
+/// Fetch the rebates accrued by `referral_code`.
+pub async fn get_rebates(url: String, referral_code: &str) -> Result<RebatesResponse> {
+    if referral_code.is_empty() {
+        return Err(AspensError::invalid_input(
+            "referral code must not be empty",
+        ));
+    }
+    let channel = create_channel(&url).await?;
+    let mut client = ArborterServiceClient::new(channel);
+    let response = client
+        .get_rebates(RebatesRequest {
+            referral_code: referral_code.to_string(),
+        })
+        .await?;
+    Ok(response.into_inner())
+}
+
+/// Fetch the rebates for `referral_code` and resolve each token's decimals
+/// from `config`.
+pub async fn rebate_report(
+    url: String,
+    referral_code: &str,
+    config: &GetConfigResponse,
+) -> Result<RebateReport> {
+    let rebates = get_rebates(url, referral_code).await?;
+    Ok(RebateReport::new(rebates, config))
+}
+
+impl RebateReport {
+    /// Pair `rebates` with the token decimals in `config`.
+    pub fn new(rebates: RebatesResponse, config: &GetConfigResponse) -> Self {
+        let decimals = rebates
+            .rebates
+            .iter()
+            .map(|r| {
+                config
+                    .get_token(&r.network, &r.token_symbol)
+                    .map(|t| t.decimals)
+            })
+            .collect();
+        Self { rebates, decimals }
+    }
+}
+
+impl fmt::Display for RebateReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let r = &self.rebates;
+        write!(
+            f,
+            "Referral code {}: {} referred order(s), {} bps of fees rebated",
+            r.referral_code, r.referred_orders, r.rebate_bps
+        )?;
+        if r.rebates.is_empty() {
+            return write!(f, "\n  no rebates accrued");
+        }
+        for (rebate, decimals) in r.rebates.iter().zip(&self.decimals) {
+            let amount = |raw: &str| match (raw.parse::<u128>(), decimals) {
+                (Ok(v), Some(d)) => format_decimal_amount(v, *d),
+                _ => raw.to_string(),
+            };
+            write!(
+                f,
+                "\n  {} {}: accrued {}, paid {}",
+                rebate.network,
+                rebate.token_symbol,
+                amount(&rebate.accrued),
+                amount(&rebate.paid)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::trading::send_order::arborter_pb::Rebate;
+
+    #[test]
+    fn formats_rebates_with_token_decimals() {
+        let report = RebateReport {
+            rebates: RebatesResponse {
+                referral_code: "desk-42".into(),
+                rebate_bps: 2_000,
+                referred_orders: 7,
+                rebates: vec![
+                    Rebate {
+                        network: "base-sepolia".into(),
+                        token_symbol: "USDC".into(),
+                        accrued: "1250000".into(),
+                        paid: "0".into(),
+                    },
+                    Rebate {
+                        network: "unknown".into(),
+                        token_symbol: "XYZ".into(),
+                        accrued: "5".into(),
+                        paid: "1".into(),
+                    },
+                ],
+            },
+            decimals: vec![Some(6), None],
+        };
+        assert_eq!(
+            report.to_string(),
+            "Referral code desk-42: 7 referred order(s), 2000 bps of fees rebated\n  \
+             base-sepolia USDC: accrued 1.250000, paid 0.000000\n  \
+             unknown XYZ: accrued 5, paid 1"
+        );
+    }
+}
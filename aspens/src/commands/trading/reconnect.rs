@@ -0,0 +1,206 @@
+//! Automatic reconnection for long-running orderbook and trades streams.
+//!
+//! Without [`ReconnectOptions`], a stream that drops (the stack restarts, a
+//! proxy resets the connection, the network blips) ends with an error and
+//! the consumer has to notice and start over. With them set, the stream
+//! reconnects on its own: it waits an exponentially growing backoff between
+//! attempts and gives up with a connection error after `max_attempts` failures
+//! in a row. An event delivered in between resets the count.
+//!
+//! With `resume` on (the default), the new subscription asks the stack to
+//! replay history since the timestamp of the last event delivered, and the
+//! stream drops events at or before that point it has already delivered, so
+//! the consumer sees each event once and nothing published during the gap
+//! is lost. For the orderbook, the replay carries the orders still open;
+//! an order that opened and closed entirely inside the gap isn't replayed.
+//!
+//! A reconnect also follows a stream the server ended cleanly: both streams
+//! are requested with `continue_stream`, so an end means the stack went away.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::time::Duration;
+
+use tonic::Code;
+
+/// First wait before reconnecting by default.
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest wait between reconnect attempts by default.
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Failed reconnect attempts in a row before giving up by default.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 10;
+
+/// How a dropped stream reconnects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectOptions {
+    /// Wait before the first attempt; doubles with every failed attempt.
+    pub initial_backoff: Duration,
+    /// Cap on the wait between attempts.
+    pub max_backoff: Duration,
+    /// Failed attempts in a row before the stream gives up with an error;
+    /// `None` retries indefinitely.
+    pub max_attempts: Option<u32>,
+    /// Replay events since the last one delivered after reconnecting, and
+    /// skip the ones already delivered.
+    pub resume: bool,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            max_attempts: Some(DEFAULT_MAX_ATTEMPTS),
+            resume: true,
+        }
+    }
+}
+
+impl ReconnectOptions {
+    /// Give up after `max` failed attempts in a row.
+    pub fn with_max_attempts(mut self, max: u32) -> Self {
+        self.max_attempts = Some(max);
+        self
+    }
+
+    /// Retry indefinitely.
+    pub fn forever(mut self) -> Self {
+        self.max_attempts = None;
+        self
+    }
+
+    /// True if another attempt is allowed after `failed` in a row.
+    pub(crate) fn may_retry(&self, failed: u32) -> bool {
+        self.max_attempts.is_none_or(|max| failed < max)
+    }
+
+    /// The wait before attempt number `attempt` (0-based).
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// True if a stream failing mid-flight with `status` most likely lost its
+/// connection rather than being refused by the stack.
+///
+/// A connection torn down under a live HTTP/2 stream surfaces as
+/// `Unknown` or `Internal` as often as `Unavailable`.
+pub(crate) fn is_drop(status: &tonic::Status) -> bool {
+    matches!(
+        status.code(),
+        Code::Unavailable
+            | Code::Unknown
+            | Code::Internal
+            | Code::Cancelled
+            | Code::DeadlineExceeded
+    )
+}
+
+/// The last point a stream delivered, for resuming after a reconnect
+/// without repeating events.
+///
+/// Timestamps only have millisecond resolution, so the events delivered at
+/// the latest timestamp are kept to tell a replayed one from a new one.
+#[derive(Debug)]
+pub(crate) struct ResumePoint<T> {
+    last_ms: Option<u64>,
+    at_last: HashSet<T>,
+    /// The point of the latest resume, and the events delivered at it.
+    floor: Option<(u64, HashSet<T>)>,
+}
+
+impl<T> Default for ResumePoint<T> {
+    fn default() -> Self {
+        Self {
+            last_ms: None,
+            at_last: HashSet::new(),
+            floor: None,
+        }
+    }
+}
+
+impl<T: Eq + Hash + Clone> ResumePoint<T> {
+    /// Note `item`, stamped `timestamp_ms`, as delivered.
+    pub(crate) fn record(&mut self, timestamp_ms: u64, item: &T) {
+        match self.last_ms {
+            Some(last) if timestamp_ms < last => {}
+            Some(last) if timestamp_ms == last => {
+                self.at_last.insert(item.clone());
+            }
+            _ => {
+                self.last_ms = Some(timestamp_ms);
+                self.at_last.clear();
+                self.at_last.insert(item.clone());
+            }
+        }
+    }
+
+    /// Start a resume: events at or before the current point that were
+    /// already delivered are skipped from now on. Returns the timestamp to
+    /// replay from, or `None` if nothing was delivered yet.
+    pub(crate) fn resume(&mut self) -> Option<u64> {
+        let last = self.last_ms?;
+        self.floor = Some((last, self.at_last.clone()));
+        Some(last)
+    }
+
+    /// True if `item`, stamped `timestamp_ms`, hasn't been delivered
+    /// before the latest resume.
+    pub(crate) fn is_new(&self, timestamp_ms: u64, item: &T) -> bool {
+        match &self.floor {
+            None => true,
+            Some((floor, seen)) => {
+                timestamp_ms > *floor || (timestamp_ms == *floor && !seen.contains(item))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let options = ReconnectOptions {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            ..ReconnectOptions::default()
+        };
+        assert_eq!(options.backoff(0), Duration::from_millis(100));
+        assert_eq!(options.backoff(1), Duration::from_millis(200));
+        assert_eq!(options.backoff(3), Duration::from_millis(800));
+        assert_eq!(options.backoff(4), Duration::from_secs(1));
+        assert_eq!(options.backoff(40), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn attempts_are_bounded_unless_forever() {
+        let options = ReconnectOptions::default().with_max_attempts(2);
+        assert!(options.may_retry(1));
+        assert!(!options.may_retry(2));
+        assert!(options.forever().may_retry(u32::MAX));
+    }
+
+    #[test]
+    fn resume_skips_delivered_events_only() {
+        let mut point = ResumePoint::default();
+        assert_eq!(point.resume(), None);
+        point.record(10, &"a");
+        point.record(20, &"b");
+        point.record(20, &"c");
+        assert_eq!(point.resume(), Some(20));
+
+        assert!(!point.is_new(10, &"a"));
+        assert!(!point.is_new(20, &"b"));
+        assert!(!point.is_new(20, &"c"));
+        // Same millisecond, not delivered before the drop.
+        assert!(point.is_new(20, &"d"));
+        assert!(point.is_new(21, &"b"));
+    }
+}
@@ -0,0 +1,182 @@
+//! Move a resting order to a new price or size.
+//!
+//! The arborter has no amend call, so [`replace_order`] sequences the two
+//! halves on the client: it cancels the existing order and, only once the
+//! stack confirms the cancel, sends the replacement. Everything about the
+//! replacement that can be checked locally (amounts, options, trading
+//! hours, wallets, risk limits) is checked before the cancel is signed, so
+//! a bad replacement never takes the original off the book.
+//!
+//! The sequence is not atomic. Between the cancel and the new order the
+//! trader has nothing resting; if the original fills in that window the
+//! cancel reports it gone and no replacement is sent, so the position is
+//! never doubled.
+
+use std::fmt;
+
+use super::cancel_order::{
+    arborter_pb::CancelOrderResponse, call_cancel_order_from_config_with_wallet,
+};
+use super::risk::{self, RiskConfig};
+use super::send_order::arborter_pb::{SendOrderResponse, Side};
+use super::send_order::{
+    OrderOptions, convert_to_pair_decimals, leg_wallets, lookup_market, send_order_with_options,
+};
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::error::{AspensError, Result};
+use crate::signer::AspensSigner;
+
+/// Outcome of [`replace_order`]: the cancel of the original and the order
+/// that replaced it.
+#[derive(Debug)]
+pub struct ReplaceOrderResponse {
+    /// The original order's ID.
+    pub replaced_order_id: u64,
+    /// The stack's answer to the cancel.
+    pub canceled: CancelOrderResponse,
+    /// The stack's answer to the replacement order.
+    pub placed: SendOrderResponse,
+}
+
+impl fmt::Display for ReplaceOrderResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "order {} replaced by order {}",
+            self.replaced_order_id, self.placed.order_id
+        )
+    }
+}
+
+/// Replace resting order `order_id` on `market_id` with a new limit order:
+/// cancel it, then send `quantity` at `price` on the same `side`.
+///
+/// `side` is the original order's side and must match it; the cancel is
+/// signed by the wallet that locked for that side. `wallets` and `options`
+/// apply to the replacement as in [`send_order_with_options`]. The risk
+/// limits are checked before the cancel, against the book and open orders
+/// without the original; the deposit check of
+/// [`RiskConfig::check_balance`](super::risk::RiskConfig::check_balance)
+/// runs with the replacement, once the cancel has released the original.
+///
+/// Fails with [`AspensError::NotFound`] and sends nothing if the original is
+/// no longer on the book. If the cancel succeeds but the replacement is
+/// rejected, the error says so: the original is gone either way.
+// Public top-level API — same rationale as `send_order_with_options`.
+#[allow(clippy::too_many_arguments)]
+pub async fn replace_order(
+    url: String,
+    market_id: String,
+    order_id: u64,
+    side: i32,
+    quantity: String,
+    price: String,
+    wallets: &[&dyn AspensSigner],
+    config: GetConfigResponse,
+    options: OrderOptions,
+) -> Result<ReplaceOrderResponse> {
+    // Check the replacement before touching the original.
+    options.validate(true)?;
+    let side_name = match Side::try_from(side) {
+        Ok(Side::Bid) => "buy",
+        Ok(Side::Ask) => "sell",
+        _ => {
+            return Err(AspensError::invalid_input(format!(
+                "invalid side {} (expected buy or sell)",
+                side
+            )));
+        }
+    };
+    let market = lookup_market(&config, &market_id)?;
+    let pair_decimals = market.pair_decimals as u32;
+    market.ensure_open(crate::util::unix_now())?;
+    let quantity_raw = convert_to_pair_decimals(&quantity, pair_decimals, options.precision)
+        .map_err(|e| {
+            AspensError::invalid_input(format!("Invalid quantity '{}': {}", quantity, e))
+        })?;
+    let price_raw = convert_to_pair_decimals(&price, pair_decimals, options.precision)
+        .map_err(|e| AspensError::invalid_input(format!("Invalid price: {}", e)))?;
+    market.check_order_limits(quantity_raw, Some(price_raw))?;
+    let (base_wallet, quote_wallet) = leg_wallets(&config, market, wallets)?;
+    let cancel_wallet = if side_name == "buy" {
+        quote_wallet
+    } else {
+        base_wallet
+    };
+    let resolved_market_id = market.market_id.clone();
+
+    // The replacement only rests once the original is gone, so check it
+    // against the book without the original.
+    if !options.risk.is_empty() {
+        let context = risk::fetch_context_without(
+            url.clone(),
+            resolved_market_id.clone(),
+            Some(base_wallet.address()),
+            &options.risk,
+            Some(order_id),
+        )
+        .await?;
+        options
+            .risk
+            .check(market, side, quantity_raw, Some(price_raw), &context)
+            .map_err(|violation| AspensError::RiskLimit(Box::new(violation)))?;
+    }
+
+    tracing::info!(
+        "Replacing order {} on {}: {} {} at {}",
+        order_id,
+        market.name,
+        side_name,
+        quantity,
+        price
+    );
+
+    let canceled = call_cancel_order_from_config_with_wallet(
+        url.clone(),
+        resolved_market_id.clone(),
+        side_name.to_string(),
+        order_id,
+        cancel_wallet,
+        config.clone(),
+    )
+    .await?;
+    if !canceled.order_canceled {
+        return Err(AspensError::not_found(format!(
+            "order {} is no longer on the book (filled or canceled); \
+             the replacement was not sent",
+            order_id
+        )));
+    }
+
+    let placed = send_order_with_options(
+        url,
+        resolved_market_id,
+        side,
+        quantity,
+        Some(price),
+        wallets,
+        config,
+        // Limits checked above; only the deposit check is left.
+        OrderOptions {
+            risk: RiskConfig {
+                check_balance: options.risk.check_balance,
+                ..RiskConfig::default()
+            },
+            ..options
+        },
+    )
+    .await
+    .map_err(|e| {
+        let msg = format!(
+            "order {} was canceled, but its replacement failed: {}",
+            order_id, e
+        );
+        AspensError::from(eyre::Report::new(e).wrap_err(msg))
+    })?;
+
+    Ok(ReplaceOrderResponse {
+        replaced_order_id: order_id,
+        canceled,
+        placed,
+    })
+}
@@ -0,0 +1,321 @@
+//! Bounded, paced historical replay for the orderbook and trades streams.
+//!
+//! A stream asked for history replays every open order or closed trade the
+//! market has, which on a busy market is effectively unbounded.
+//! [`HistoricalReplay`] bounds it: only events since a timestamp, only the
+//! last N, and the page size the server reads history in. Arborter builds
+//! that predate these fields replay everything, so `since` is also enforced
+//! client-side.
+//!
+//! The channel variants (`stream_orderbook_channel`, `stream_trades_channel`)
+//! buffer at most [`HistoricalReplay::buffer_size`] events and wait for the
+//! consumer when it's full, so memory stays flat however long the replay.
+//! Drain them a chunk at a time with `Receiver::recv_many`.
+//!
+//! [`pace`] re-times a replay for debugging a strategy against a
+//! "live-like" session: events are released at their original spacing, a
+//! multiple of it, or as fast as possible ([`ReplaySpeed`]), each tagged
+//! with the simulated time it happened at.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use eyre::{Result, eyre};
+use tokio::sync::mpsc;
+
+use super::stream_orderbook::arborter_pb::OrderbookEntry;
+use super::stream_trades::arborter_pb::Trade;
+use crate::error::{self, AspensError};
+
+/// Events buffered between the stream and a channel consumer by default.
+pub const DEFAULT_BATCH_SIZE: u32 = 100;
+
+/// Bounds on a stream's historical replay. The default is unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoricalReplay {
+    /// Only replay events at or after this unix timestamp, in milliseconds
+    /// (the unit of `Trade::timestamp` / `OrderbookEntry::timestamp`).
+    pub since_ms: Option<u64>,
+    /// Only replay the most recent `limit` events. Needs server support.
+    pub limit: Option<u32>,
+    /// Page size the server reads history in, and the number of events the
+    /// channel variants buffer (default [`DEFAULT_BATCH_SIZE`]).
+    pub batch_size: Option<u32>,
+}
+
+impl HistoricalReplay {
+    /// Replay only events at or after `since_ms`.
+    pub fn since(since_ms: u64) -> Self {
+        Self {
+            since_ms: Some(since_ms),
+            ..Self::default()
+        }
+    }
+
+    /// Replay only the most recent `limit` events.
+    pub fn last(limit: u32) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::default()
+        }
+    }
+
+    /// Set the page / buffer size.
+    pub fn with_batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// True when any bound is set.
+    pub fn is_bounded(&self) -> bool {
+        self.since_ms.is_some() || self.limit.is_some()
+    }
+
+    /// True if an event stamped `timestamp_ms` falls inside the replay
+    /// window. Live events are always newer than `since`, so this is safe
+    /// to apply to the whole stream.
+    pub fn admits(&self, timestamp_ms: u64) -> bool {
+        self.since_ms.is_none_or(|since| timestamp_ms >= since)
+    }
+
+    /// Events the channel variants buffer before waiting on the consumer.
+    pub fn buffer_size(&self) -> usize {
+        self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE) as usize
+    }
+
+    /// Check the limit and batch size are non-zero.
+    pub fn validate(&self) -> error::Result<()> {
+        if self.limit == Some(0) {
+            return Err(AspensError::invalid_input(
+                "replay limit must be at least 1",
+            ));
+        }
+        if self.batch_size == Some(0) {
+            return Err(AspensError::invalid_input(
+                "replay batch size must be at least 1",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How fast [`pace`] releases replayed events.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReplaySpeed {
+    /// No pacing: release each event as soon as it arrives.
+    #[default]
+    AsFastAsPossible,
+    /// Scale the original spacing by this factor: `1.0` is the original
+    /// speed, `10.0` ten times faster, `0.5` half speed.
+    Multiple(f64),
+}
+
+impl ReplaySpeed {
+    /// The original speed.
+    pub const REALTIME: ReplaySpeed = ReplaySpeed::Multiple(1.0);
+}
+
+impl fmt::Display for ReplaySpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplaySpeed::AsFastAsPossible => write!(f, "max"),
+            ReplaySpeed::Multiple(factor) => write!(f, "{}x", factor),
+        }
+    }
+}
+
+/// Parses `max` (as fast as possible), `realtime` (`1x`) or a positive
+/// multiple such as `10x`, `0.5x` or `2`.
+impl FromStr for ReplaySpeed {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "max" | "fast" => Ok(ReplaySpeed::AsFastAsPossible),
+            "realtime" | "live" => Ok(ReplaySpeed::REALTIME),
+            other => {
+                let factor: f64 =
+                    other
+                        .strip_suffix('x')
+                        .unwrap_or(other)
+                        .parse()
+                        .map_err(|_| {
+                            eyre!(
+                                "invalid replay speed '{}': expected max, realtime or e.g. 10x",
+                                s
+                            )
+                        })?;
+                if !(factor.is_finite() && factor > 0.0) {
+                    return Err(eyre!(
+                        "replay speed must be a positive multiple, got '{}'",
+                        s
+                    ));
+                }
+                Ok(ReplaySpeed::Multiple(factor))
+            }
+        }
+    }
+}
+
+/// Events that carry the time they happened, in unix milliseconds.
+pub trait Timestamped {
+    /// When the event happened.
+    fn timestamp_ms(&self) -> u64;
+}
+
+impl Timestamped for Trade {
+    fn timestamp_ms(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+impl Timestamped for OrderbookEntry {
+    fn timestamp_ms(&self) -> u64 {
+        self.timestamp
+    }
+}
+
+/// A replayed event and the simulated time it was released at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paced<T> {
+    /// The event.
+    pub event: T,
+    /// Simulated unix milliseconds: the event's own timestamp, held
+    /// monotonic so out-of-order events never move the clock backwards.
+    pub simulated_ms: u64,
+}
+
+/// Decides when each replayed event is due. [`pace`] drives one; it is
+/// public so consumers with their own event loop can pace without the
+/// extra task.
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    speed: ReplaySpeed,
+    /// The first event's timestamp and the instant it was released.
+    origin: Option<(u64, Instant)>,
+    simulated_ms: u64,
+}
+
+impl Pacer {
+    /// A pacer for `speed`.
+    pub fn new(speed: ReplaySpeed) -> Self {
+        Self {
+            speed,
+            origin: None,
+            simulated_ms: 0,
+        }
+    }
+
+    /// The simulated time of the latest scheduled event, in unix ms.
+    pub fn simulated_ms(&self) -> u64 {
+        self.simulated_ms
+    }
+
+    /// Schedule an event stamped `timestamp_ms`, seen at `now`. Returns how
+    /// long to wait before releasing it and its simulated time.
+    pub fn schedule(&mut self, timestamp_ms: u64, now: Instant) -> (Duration, u64) {
+        self.simulated_ms = self.simulated_ms.max(timestamp_ms);
+        let ReplaySpeed::Multiple(factor) = self.speed else {
+            return (Duration::ZERO, self.simulated_ms);
+        };
+        let (origin_ms, origin_at) = *self.origin.get_or_insert((self.simulated_ms, now));
+        let elapsed = Duration::from_millis(self.simulated_ms - origin_ms).div_f64(factor);
+        let wait = (origin_at + elapsed).saturating_duration_since(now);
+        (wait, self.simulated_ms)
+    }
+}
+
+/// Re-time `events` at `speed`, on a background task.
+///
+/// Meant for historical sessions: when the stream also continues live,
+/// live events queue behind the paced replay.
+///
+/// # Returns
+/// A receiver of [`Paced`] events, and a handle to the pacing task.
+pub fn pace<T>(
+    mut events: mpsc::Receiver<T>,
+    speed: ReplaySpeed,
+) -> (mpsc::Receiver<Paced<T>>, tokio::task::JoinHandle<()>)
+where
+    T: Timestamped + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(DEFAULT_BATCH_SIZE as usize);
+    let handle = tokio::spawn(async move {
+        let mut pacer = Pacer::new(speed);
+        while let Some(event) = events.recv().await {
+            let (wait, simulated_ms) = pacer.schedule(event.timestamp_ms(), Instant::now());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+            if tx
+                .send(Paced {
+                    event,
+                    simulated_ms,
+                })
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    (rx, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_filter_and_size_the_buffer() {
+        let replay = HistoricalReplay::since(1_000).with_batch_size(16);
+        assert!(replay.is_bounded());
+        assert!(!replay.admits(999));
+        assert!(replay.admits(1_000));
+        assert_eq!(replay.buffer_size(), 16);
+
+        let unbounded = HistoricalReplay::default();
+        assert!(!unbounded.is_bounded());
+        assert!(unbounded.admits(0));
+        assert_eq!(unbounded.buffer_size(), DEFAULT_BATCH_SIZE as usize);
+
+        assert!(HistoricalReplay::last(0).validate().is_err());
+        assert!(
+            HistoricalReplay::last(5)
+                .with_batch_size(0)
+                .validate()
+                .is_err()
+        );
+        assert!(HistoricalReplay::last(5).validate().is_ok());
+    }
+
+    #[test]
+    fn pacer_scales_original_spacing() {
+        let start = Instant::now();
+        let mut pacer = Pacer::new("10x".parse().unwrap());
+        assert_eq!(pacer.schedule(1_000, start), (Duration::ZERO, 1_000));
+        // 5 s later in the session is 500 ms later at 10x.
+        assert_eq!(
+            pacer.schedule(6_000, start),
+            (Duration::from_millis(500), 6_000)
+        );
+        // A late, out-of-order event doesn't rewind the clock.
+        assert_eq!(
+            pacer.schedule(2_000, start + Duration::from_secs(1)),
+            (Duration::ZERO, 6_000)
+        );
+
+        let mut fast = Pacer::new(ReplaySpeed::AsFastAsPossible);
+        assert_eq!(fast.schedule(1_000, start).0, Duration::ZERO);
+        assert_eq!(fast.schedule(90_000, start).0, Duration::ZERO);
+
+        assert_eq!(
+            "realtime".parse::<ReplaySpeed>().unwrap(),
+            ReplaySpeed::REALTIME
+        );
+        assert_eq!("0.5x".parse::<ReplaySpeed>().unwrap().to_string(), "0.5x");
+        assert!("0x".parse::<ReplaySpeed>().is_err());
+        assert!("warp".parse::<ReplaySpeed>().is_err());
+    }
+}
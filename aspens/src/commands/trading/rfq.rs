@@ -0,0 +1,272 @@
+//! Request-for-quote trading.
+//!
+//! Large cross-chain trades often price better from dealers than from the
+//! open book. [`request_quotes`] broadcasts a size to the stack's dealers
+//! and streams their [`Quote`]s back until the request's TTL lapses;
+//! [`accept_quote`] signs an execution against one of them, which settles
+//! like a matched order under the same authorization `send_order` derives.
+
+use std::fmt;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::sync::mpsc;
+
+use super::gasless::build_gasless_authorization;
+use super::send_order::arborter_pb::arborter_service_client::ArborterServiceClient;
+use super::send_order::arborter_pb::{
+    AcceptQuoteRequest, AcceptQuoteResponse, Quote, QuoteAcceptance, QuoteRequest, Side,
+};
+use super::send_order::{convert_to_pair_decimals, leg_wallets, lookup_market};
+use super::signing::{SignedPayload, SigningVersion};
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::decimals::{PrecisionMode, format_decimal_amount};
+use crate::error::{AspensError, Result};
+use crate::grpc::create_channel;
+use crate::signer::AspensSigner;
+use crate::util::{format_utc, unix_now};
+
+/// How long dealers get to respond by default.
+pub const DEFAULT_QUOTE_TTL: Duration = Duration::from_secs(10);
+
+impl fmt::Display for Quote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} @ {} from {} (expires {})",
+            self.quote_id, self.quantity, self.price, self.dealer, self.expires_at
+        )
+    }
+}
+
+impl Quote {
+    /// True once the quote can no longer be accepted at `now` (unix
+    /// seconds).
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Format the quote for CLI display with human-readable amounts.
+    pub fn format_for_cli(&self, pair_decimals: u32) -> String {
+        let amount = |raw: &str| {
+            raw.parse::<u128>()
+                .map(|v| format_decimal_amount(v, pair_decimals))
+                .unwrap_or_else(|_| raw.to_string())
+        };
+        format!(
+            "{} @ {} from {} (expires {} UTC)",
+            amount(&self.quantity),
+            amount(&self.price),
+            self.dealer,
+            format_utc(self.expires_at)
+        )
+    }
+}
+
+impl AcceptQuoteResponse {
+    /// Get formatted transaction hashes for CLI display
+    pub fn get_formatted_transaction_hashes(&self) -> Vec<String> {
+        self.transaction_hashes
+            .iter()
+            .map(|th| th.format_for_cli())
+            .collect()
+    }
+}
+
+/// The best unexpired quote at `now`: the lowest price for a bid, the
+/// highest for an ask.
+pub fn best_quote(quotes: &[Quote], now: u64) -> Option<&Quote> {
+    let live = quotes
+        .iter()
+        .filter(|q| !q.is_expired(now))
+        .filter_map(|q| q.price.parse::<u128>().ok().map(|p| (p, q)));
+    let best = if quotes.first().is_some_and(|q| q.side == Side::Bid as i32) {
+        live.min_by_key(|(p, _)| *p)
+    } else {
+        live.max_by_key(|(p, _)| *p)
+    };
+    best.map(|(_, q)| q)
+}
+
+/// Ask the stack's dealers to quote `quantity` (human-readable) on
+/// `market_id`, and stream their quotes back.
+///
+/// `side` is the requester's side (1 = buy base, 2 = sell it). The stream
+/// ends once `ttl` has passed on the server; quotes arrive on the returned
+/// channel as dealers respond.
+///
+/// # Returns
+/// A receiver for the quotes, and a handle to the background task.
+pub async fn request_quotes(
+    url: String,
+    market_id: &str,
+    side: i32,
+    quantity: &str,
+    wallets: &[&dyn AspensSigner],
+    config: &GetConfigResponse,
+    ttl: Duration,
+) -> Result<(mpsc::Receiver<Quote>, tokio::task::JoinHandle<Result<()>>)> {
+    if side != Side::Bid as i32 && side != Side::Ask as i32 {
+        return Err(AspensError::invalid_input(format!(
+            "invalid side {}: expected 1 (buy) or 2 (sell)",
+            side
+        )));
+    }
+    let market = lookup_market(config, market_id)?;
+    market.ensure_open(unix_now())?;
+    let quantity_raw = convert_to_pair_decimals(
+        quantity,
+        market.pair_decimals as u32,
+        PrecisionMode::Truncate,
+    )
+    .map_err(|e| AspensError::invalid_input(format!("Invalid quantity '{}': {}", quantity, e)))?;
+    let (base_wallet, quote_wallet) = leg_wallets(config, market, wallets)?;
+
+    let request = QuoteRequest {
+        market_id: market.market_id.clone(),
+        side,
+        quantity: quantity_raw.to_string(),
+        base_account_address: base_wallet.address(),
+        quote_account_address: quote_wallet.address(),
+        ttl_secs: u32::try_from(ttl.as_secs()).unwrap_or(u32::MAX),
+    };
+
+    // Open the stream before spawning so a rejected request surfaces here
+    // rather than as a silently closed channel.
+    let channel = create_channel(&url).await?;
+    let mut client = ArborterServiceClient::new(channel);
+    let mut stream = client.request_quotes(request).await?.into_inner();
+
+    let (tx, rx) = mpsc::channel(100);
+    let handle = tokio::spawn(async move {
+        while let Some(quote) = stream.next().await {
+            match quote {
+                Ok(quote) => {
+                    if tx.send(quote).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Quote stream error: {}", e);
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    });
+    Ok((rx, handle))
+}
+
+/// Accept `quote` with a signed execution.
+///
+/// The wallet that locks funds for the quote's side signs the
+/// [`QuoteAcceptance`]'s canonical bytes, and the order authorization is derived for the
+/// quoted price and quantity. Errors if the quote has expired or the stack
+/// declines it.
+pub async fn accept_quote(
+    url: String,
+    quote: &Quote,
+    wallets: &[&dyn AspensSigner],
+    config: &GetConfigResponse,
+) -> Result<AcceptQuoteResponse> {
+    if quote.is_expired(unix_now()) {
+        return Err(AspensError::invalid_input(format!(
+            "quote {} expired at {} UTC",
+            quote.quote_id,
+            format_utc(quote.expires_at)
+        )));
+    }
+    let market = lookup_market(config, &quote.market_id)?;
+    let (base_wallet, quote_wallet) = leg_wallets(config, market, wallets)?;
+    // As for orders: a bid locks quote, an ask locks base.
+    let signing_wallet = if quote.side == Side::Bid as i32 {
+        quote_wallet
+    } else {
+        base_wallet
+    };
+
+    let acceptance = QuoteAcceptance {
+        rfq_id: quote.rfq_id.clone(),
+        quote_id: quote.quote_id.clone(),
+        market_id: market.market_id.clone(),
+        side: quote.side,
+        price: quote.price.clone(),
+        quantity: quote.quantity.clone(),
+        base_account_address: base_wallet.address(),
+        quote_account_address: quote_wallet.address(),
+    };
+    let signing_version = SigningVersion::for_config(config)?;
+    let signature_hash = signing_wallet
+        .sign_message(&acceptance.canonical_bytes(signing_version))
+        .await?;
+    let authorization = build_gasless_authorization(
+        config,
+        market,
+        quote.side,
+        signing_wallet,
+        &quote.quantity,
+        Some(&quote.price),
+    )?;
+
+    let channel = create_channel(&url).await?;
+    let mut client = ArborterServiceClient::new(channel);
+    let response = client
+        .accept_quote(AcceptQuoteRequest {
+            acceptance: Some(acceptance),
+            signature_hash,
+            authorization: Some(authorization),
+            signing_version: signing_version.as_u32(),
+        })
+        .await?
+        .into_inner();
+    if !response.accepted {
+        return Err(AspensError::protocol(format!(
+            "quote {} was not accepted: {}",
+            quote.quote_id,
+            if response.reason.is_empty() {
+                "no reason given"
+            } else {
+                response.reason.as_str()
+            }
+        )));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(id: &str, side: Side, price: &str, expires_at: u64) -> Quote {
+        Quote {
+            quote_id: id.into(),
+            side: side as i32,
+            price: price.into(),
+            quantity: "100".into(),
+            dealer: "d1".into(),
+            expires_at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn best_quote_skips_expired_and_prefers_the_better_price() {
+        let bids = [
+            quote("a", Side::Bid, "101", 100),
+            quote("b", Side::Bid, "99", 50),
+            quote("c", Side::Bid, "100", 100),
+        ];
+        assert_eq!(best_quote(&bids, 60).unwrap().quote_id, "c");
+        assert!(best_quote(&bids, 100).is_none());
+
+        let asks = [
+            quote("a", Side::Ask, "101", 100),
+            quote("b", Side::Ask, "102", 100),
+        ];
+        assert_eq!(best_quote(&asks, 60).unwrap().quote_id, "b");
+        assert_eq!(
+            asks[1].format_for_cli(2),
+            "1.00 @ 1.02 from d1 (expires 1970-01-01 00:01:40 UTC)"
+        );
+    }
+}
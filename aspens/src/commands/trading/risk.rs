@@ -0,0 +1,584 @@
+//! Client-side risk limits, checked before an order is signed.
+//!
+//! A [`RiskConfig`] caps what a bot can do if its logic goes wrong: the
+//! notional of one order, the position it can build on one market, how
+//! many orders it keeps resting, and how far a limit price may sit from
+//! the book. [`check_order`] fetches what the enabled limits need (the
+//! trader's open orders, the top of book) and rejects an order that would
+//! break one with [`AspensError::RiskLimit`] holding a [`RiskViolation`].
+//!
+//! [`send_order_with_options`] checks every order against the limits it is
+//! given before signing, so [`replace_order`] and anything built on them
+//! are covered too.
+//! [`AspensClient`](crate::AspensClient) passes the limits set with
+//! [`with_risk_limits`](crate::AspensClientBuilder::with_risk_limits),
+//! overridden by the `ASPENS_RISK_*` variables of the env file, from
+//! [`AspensApi::send_order`](crate::AspensApi::send_order) and the CLIs.
+//!
+//! [`send_order_with_options`]: super::send_order::send_order_with_options
+//! [`replace_order`]: super::replace_order::replace_order
+//!
+//! The stack doesn't know a trader's position, so the position limit
+//! counts the order plus the trader's resting orders on the same side,
+//! starting from [`RiskContext::position`] when the caller tracks one (say
+//! from a [`Portfolio`](super::portfolio::Portfolio)).
+//!
+//! [`RiskConfig::check_balance`] opts into one more check: that the wallet
+//! locking the order has deposited enough to cover it, via
+//! [`check_deposited_balance`]. A shortfall fails with
+//! [`AspensError::InsufficientBalance`] rather than a [`RiskViolation`].
+
+use super::conflate::fetch_book_state;
+use super::open_orders::get_orders;
+use super::orderbook::SNAPSHOT_WINDOW;
+use super::send_order::{check_deposited_balance, leg_wallets, lookup_market};
+use super::stream_orderbook::arborter_pb::{OrderbookEntry, Side};
+use crate::api::OrderRequest;
+use crate::commands::config::config_pb::{GetConfigResponse, Market};
+use crate::decimals::{
+    PrecisionMode, format_decimal_amount, parse_decimal_amount, parse_decimal_amount_with,
+};
+use crate::error::{AspensError, Result};
+use crate::signer::AspensSigner;
+
+/// Env variable overriding [`RiskConfig::max_order_notional`].
+pub const ENV_MAX_ORDER_NOTIONAL: &str = "ASPENS_RISK_MAX_ORDER_NOTIONAL";
+/// Env variable overriding [`RiskConfig::max_position`].
+pub const ENV_MAX_POSITION: &str = "ASPENS_RISK_MAX_POSITION";
+/// Env variable overriding [`RiskConfig::max_open_orders`].
+pub const ENV_MAX_OPEN_ORDERS: &str = "ASPENS_RISK_MAX_OPEN_ORDERS";
+/// Env variable overriding [`RiskConfig::price_band_bps`].
+pub const ENV_PRICE_BAND_BPS: &str = "ASPENS_RISK_PRICE_BAND_BPS";
+/// Env variable overriding [`RiskConfig::check_balance`]: `true` or `false`.
+pub const ENV_CHECK_BALANCE: &str = "ASPENS_RISK_CHECK_BALANCE";
+
+/// Limits an order must stay within. Unset limits aren't checked; the
+/// default checks nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskConfig {
+    /// Largest price × quantity of one order, as a decimal string in the
+    /// quote token. A market order is valued at the opposite best price.
+    pub max_order_notional: Option<String>,
+    /// Largest base quantity, as a decimal string, the trader may hold on
+    /// one market if the order and their resting orders on its side fill.
+    pub max_position: Option<String>,
+    /// Most orders the trader may have resting on one market; an order is
+    /// refused once this many rest.
+    pub max_open_orders: Option<usize>,
+    /// Furthest a limit price may sit from the book's mid, in basis
+    /// points.
+    pub price_band_bps: Option<u32>,
+    /// Query the deposited balance of the wallet that locks the order and
+    /// refuse the order if it falls short. Costs an RPC call per order.
+    pub check_balance: bool,
+}
+
+impl RiskConfig {
+    /// True if no limit is set and the balance check is off.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Replace limits with the `ASPENS_RISK_*` variables `get` returns.
+    /// An empty variable leaves its limit as it was.
+    pub fn with_env_overrides(mut self, get: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let get = |key: &str| get(key).filter(|v| !v.trim().is_empty());
+        if let Some(v) = get(ENV_MAX_ORDER_NOTIONAL) {
+            self.max_order_notional = Some(v.trim().to_string());
+        }
+        if let Some(v) = get(ENV_MAX_POSITION) {
+            self.max_position = Some(v.trim().to_string());
+        }
+        if let Some(v) = get(ENV_MAX_OPEN_ORDERS) {
+            self.max_open_orders = Some(v.trim().parse().map_err(|e| {
+                AspensError::invalid_input(format!("{ENV_MAX_OPEN_ORDERS} '{v}': {e}"))
+            })?);
+        }
+        if let Some(v) = get(ENV_PRICE_BAND_BPS) {
+            self.price_band_bps = Some(v.trim().parse().map_err(|e| {
+                AspensError::invalid_input(format!("{ENV_PRICE_BAND_BPS} '{v}': {e}"))
+            })?);
+        }
+        if let Some(v) = get(ENV_CHECK_BALANCE) {
+            self.check_balance = v.trim().parse().map_err(|e| {
+                AspensError::invalid_input(format!("{ENV_CHECK_BALANCE} '{v}': {e}"))
+            })?;
+        }
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Check that the decimal limits parse.
+    pub fn validate(&self) -> Result<()> {
+        for (name, limit) in [
+            ("max_order_notional", &self.max_order_notional),
+            ("max_position", &self.max_position),
+        ] {
+            if let Some(limit) = limit {
+                // Any market's pair decimals will do to check the format.
+                parse_decimal_amount(limit, 18).map_err(|e| {
+                    AspensError::invalid_input(format!("risk limit {name} '{limit}': {e}"))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a `side` order (1 = bid, 2 = ask) for `quantity` at `price`
+    /// on `market`, all raw pair decimals, against the limits.
+    pub fn check(
+        &self,
+        market: &Market,
+        side: i32,
+        quantity: u128,
+        price: Option<u128>,
+        context: &RiskContext,
+    ) -> std::result::Result<(), RiskViolation> {
+        let pair_decimals = market.pair_decimals as u32;
+        let scaled = |raw: u128| format_decimal_amount(raw, pair_decimals);
+        // Places past the market's decimals are cut off, which only
+        // tightens a limit. One that doesn't parse at all refuses the
+        // order rather than leaving it unchecked.
+        let limit = |name: &'static str, limit: &Option<String>| {
+            limit
+                .as_deref()
+                .map(|l| {
+                    parse_decimal_amount_with(l, pair_decimals, PrecisionMode::Truncate).map_err(
+                        |e| RiskViolation::InvalidLimit {
+                            name,
+                            limit: l.to_string(),
+                            reason: e.to_string(),
+                        },
+                    )
+                })
+                .transpose()
+        };
+        let side = Side::try_from(side).unwrap_or(Side::Unspecified);
+
+        if let Some(max) = self.max_open_orders
+            && context.open_orders.len() >= max
+        {
+            return Err(RiskViolation::OpenOrders {
+                market_id: market.market_id.clone(),
+                open: context.open_orders.len(),
+                limit: max,
+            });
+        }
+
+        // A market order is valued at the price it would take first.
+        let reference = price.or(match side {
+            Side::Bid => context.best_ask,
+            _ => context.best_bid,
+        });
+        if let (Some(max), Some(reference)) = (
+            limit("max_order_notional", &self.max_order_notional)?,
+            reference,
+        ) {
+            let notional = quantity.saturating_mul(reference) / 10u128.pow(pair_decimals);
+            if notional > max {
+                return Err(RiskViolation::OrderNotional {
+                    notional: scaled(notional),
+                    limit: scaled(max),
+                });
+            }
+        }
+
+        if let (Some(band), Some(price), Some(mid)) = (self.price_band_bps, price, context.mid())
+            && mid > 0
+        {
+            let distance_bps = price.abs_diff(mid).saturating_mul(10_000) / mid;
+            if distance_bps > band as u128 {
+                return Err(RiskViolation::PriceBand {
+                    price: scaled(price),
+                    reference: scaled(mid),
+                    distance_bps,
+                    band_bps: band,
+                });
+            }
+        }
+
+        if let Some(max) = limit("max_position", &self.max_position)? {
+            let resting: u128 = context
+                .open_orders
+                .iter()
+                .filter(|o| o.side == side as i32)
+                .filter_map(|o| o.quantity.parse::<u128>().ok())
+                .sum();
+            let added = resting.saturating_add(quantity) as i128;
+            let exposure = match side {
+                Side::Ask => context.position - added,
+                _ => context.position + added,
+            };
+            if exposure.unsigned_abs() > max {
+                return Err(RiskViolation::Position {
+                    market_id: market.market_id.clone(),
+                    exposure: scaled(exposure.unsigned_abs()),
+                    limit: scaled(max),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What [`RiskConfig::check`] knows about the market and the trader.
+/// Prices and quantities are raw pair decimals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskContext {
+    /// The trader's resting orders on the market.
+    pub open_orders: Vec<OrderbookEntry>,
+    /// Base quantity the trader holds: positive long, negative short. The
+    /// stack doesn't report it, so [`fetch_risk_context`] leaves it 0.
+    pub position: i128,
+    /// Highest resting bid.
+    pub best_bid: Option<u128>,
+    /// Lowest resting ask.
+    pub best_ask: Option<u128>,
+}
+
+impl RiskContext {
+    /// Middle of the top of book, or the one side that quotes.
+    pub fn mid(&self) -> Option<u128> {
+        match (self.best_bid, self.best_ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2),
+            (bid, ask) => bid.or(ask),
+        }
+    }
+}
+
+/// Why [`RiskConfig::check`] refused an order. Amounts are decimal strings.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RiskViolation {
+    /// The order's notional is over [`RiskConfig::max_order_notional`].
+    #[error("order notional {notional} is over the risk limit of {limit}")]
+    OrderNotional {
+        /// Price × quantity of the order.
+        notional: String,
+        /// The limit.
+        limit: String,
+    },
+    /// Filling the order could take the position past
+    /// [`RiskConfig::max_position`].
+    #[error("position on {market_id} could reach {exposure}, over the risk limit of {limit}")]
+    Position {
+        /// The market.
+        market_id: String,
+        /// Position if the order and the resting orders on its side fill.
+        exposure: String,
+        /// The limit.
+        limit: String,
+    },
+    /// The trader already has [`RiskConfig::max_open_orders`] resting.
+    #[error("{open} orders already resting on {market_id}, the risk limit is {limit}")]
+    OpenOrders {
+        /// The market.
+        market_id: String,
+        /// Orders resting now.
+        open: usize,
+        /// The limit.
+        limit: usize,
+    },
+    /// The limit price is outside [`RiskConfig::price_band_bps`] of the
+    /// book's mid.
+    #[error(
+        "price {price} is {distance_bps} bps from the book's mid of {reference}, \
+         outside the {band_bps} bps risk band"
+    )]
+    PriceBand {
+        /// The order's limit price.
+        price: String,
+        /// The book's mid.
+        reference: String,
+        /// How far the price is from the mid.
+        distance_bps: u128,
+        /// The band.
+        band_bps: u32,
+    },
+    /// A decimal limit doesn't parse, so the order can't be checked
+    /// against it.
+    #[error("risk limit {name} '{limit}' is invalid: {reason}")]
+    InvalidLimit {
+        /// The [`RiskConfig`] field.
+        name: &'static str,
+        /// Its value.
+        limit: String,
+        /// Why it doesn't parse.
+        reason: String,
+    },
+}
+
+impl RiskViolation {
+    /// The violation behind `err`, if it is an [`AspensError::RiskLimit`].
+    pub fn of(err: &AspensError) -> Option<&RiskViolation> {
+        match err {
+            AspensError::RiskLimit(e) => e.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Fetch what `limits` need to check an order on `market_id`: the open
+/// orders of `trader` (the base-chain address) if an order or position
+/// limit is set, and the top of book if a notional or price band is.
+///
+/// Those limits can't be checked without a trader, so `None` is an error
+/// when one is set.
+pub async fn fetch_risk_context(
+    url: String,
+    market_id: String,
+    trader: Option<String>,
+    limits: &RiskConfig,
+) -> Result<RiskContext> {
+    fetch_context_without(url, market_id, trader, limits, None).await
+}
+
+/// [`fetch_risk_context`], leaving resting order `replaced` out of both the
+/// trader's open orders and the top of book.
+pub(crate) async fn fetch_context_without(
+    url: String,
+    market_id: String,
+    trader: Option<String>,
+    limits: &RiskConfig,
+    replaced: Option<u64>,
+) -> Result<RiskContext> {
+    let mut context = RiskContext::default();
+    if limits.max_open_orders.is_some() || limits.max_position.is_some() {
+        let trader = trader.ok_or_else(|| {
+            AspensError::invalid_input(
+                "the open-order and position risk limits need the trader's address",
+            )
+        })?;
+        context.open_orders = get_orders(url.clone(), market_id.clone(), trader).await?;
+        if let Some(replaced) = replaced {
+            context.open_orders.retain(|o| o.order_id != replaced);
+        }
+    }
+    if limits.max_order_notional.is_some() || limits.price_band_bps.is_some() {
+        let mut state = fetch_book_state(url, market_id.clone(), SNAPSHOT_WINDOW).await?;
+        if let Some(replaced) = replaced {
+            state.remove(replaced);
+        }
+        let book = state.snapshot(&market_id);
+        context.best_bid = book.best_bid();
+        context.best_ask = book.best_ask();
+    }
+    Ok(context)
+}
+
+/// Check `order` against `limits`, fetching the [`RiskContext`] it needs.
+/// Errors with [`AspensError::RiskLimit`] if the order breaks a limit, and
+/// with [`AspensError::InsufficientBalance`] if
+/// [`RiskConfig::check_balance`] is on and the deposit doesn't cover it;
+/// does nothing if no limit is set.
+///
+/// Sending the order checks it again before signing; this is for asking
+/// ahead of time.
+pub async fn check_order(
+    url: String,
+    config: &GetConfigResponse,
+    order: &OrderRequest,
+    wallets: &[&dyn AspensSigner],
+    limits: &RiskConfig,
+) -> Result<()> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    let market = lookup_market(config, &order.market_id)?;
+    let pair_decimals = market.pair_decimals as u32;
+    let quantity = parse_decimal_amount_with(&order.quantity, pair_decimals, order.precision)
+        .map_err(|e| {
+            AspensError::invalid_input(format!("Invalid quantity '{}': {}", order.quantity, e))
+        })?;
+    let price = order
+        .price
+        .as_deref()
+        .map(|p| parse_decimal_amount_with(p, pair_decimals, order.precision))
+        .transpose()
+        .map_err(|e| AspensError::invalid_input(format!("Invalid price: {}", e)))?;
+    if limits.check_balance {
+        check_deposited_balance(config, market, order.side, quantity, price, wallets).await?;
+    }
+    let (base_wallet, _) = leg_wallets(config, market, wallets)?;
+    check_resolved(
+        url,
+        market,
+        order.side,
+        quantity,
+        price,
+        Some(base_wallet.address()),
+        limits,
+    )
+    .await
+}
+
+/// The limits of [`check_order`], for an order already resolved to raw
+/// pair decimals on `market`, whose base leg is `trader`. The balance
+/// check is the caller's.
+pub(crate) async fn check_resolved(
+    url: String,
+    market: &Market,
+    side: i32,
+    quantity: u128,
+    price: Option<u128>,
+    trader: Option<String>,
+    limits: &RiskConfig,
+) -> Result<()> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    let context = fetch_risk_context(url, market.market_id.clone(), trader, limits).await?;
+    limits
+        .check(market, side, quantity, price, &context)
+        .map_err(|violation| AspensError::RiskLimit(Box::new(violation)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{ConfigFixture, book_entry};
+
+    fn market() -> Market {
+        ConfigFixture::two_chain_usdc()
+            .build()
+            .get_market_by_id(ConfigFixture::TWO_CHAIN_MARKET_ID)
+            .unwrap()
+            .clone()
+    }
+
+    fn context() -> RiskContext {
+        RiskContext {
+            open_orders: vec![book_entry(1, Side::Bid, "990000", "3000000")],
+            position: 0,
+            best_bid: Some(990_000),
+            best_ask: Some(1_010_000),
+        }
+    }
+
+    #[test]
+    fn rejects_each_limit_with_its_violation() {
+        let market = market();
+        let limits = RiskConfig {
+            max_order_notional: Some("100".into()),
+            max_position: Some("10".into()),
+            max_open_orders: Some(2),
+            price_band_bps: Some(500),
+            check_balance: false,
+        };
+        let check =
+            |side, quantity, price| limits.check(&market, side, quantity, price, &context());
+
+        // 5 at 1.00: within every limit.
+        assert_eq!(check(1, 5_000_000, Some(1_000_000)), Ok(()));
+        // A market buy of 200 is valued at the best ask.
+        assert!(matches!(
+            check(1, 200_000_000, None),
+            Err(RiskViolation::OrderNotional { ref notional, .. }) if notional == "202.000000"
+        ));
+        // 10% under the mid of 1.00.
+        assert!(matches!(
+            check(2, 1_000_000, Some(900_000)),
+            Err(RiskViolation::PriceBand {
+                distance_bps: 1000,
+                ..
+            })
+        ));
+        // 8 more on top of the 3 resting bids.
+        assert!(matches!(
+            check(1, 8_000_000, Some(1_000_000)),
+            Err(RiskViolation::Position { ref exposure, .. }) if exposure == "11.000000"
+        ));
+        // Selling 8 doesn't add to the resting bids.
+        assert_eq!(check(2, 8_000_000, Some(1_000_000)), Ok(()));
+
+        let mut full = context();
+        full.open_orders
+            .push(book_entry(2, Side::Ask, "1010000", "1000000"));
+        let err = limits
+            .check(&market, 1, 1, Some(1_000_000), &full)
+            .unwrap_err();
+        assert!(matches!(err, RiskViolation::OpenOrders { open: 2, .. }));
+
+        let err = AspensError::RiskLimit(Box::new(err));
+        assert!(matches!(
+            RiskViolation::of(&err),
+            Some(RiskViolation::OpenOrders { .. })
+        ));
+    }
+
+    #[test]
+    fn limits_finer_than_the_market_round_down_and_bad_ones_refuse() {
+        let market = market();
+        // 100.0000009 at 6 decimals is enforced as 100.
+        let limits = RiskConfig {
+            max_order_notional: Some("100.0000009".into()),
+            ..RiskConfig::default()
+        };
+        assert_eq!(
+            limits.check(&market, 1, 100_000_000, Some(1_000_000), &context()),
+            Ok(())
+        );
+        assert!(matches!(
+            limits.check(&market, 1, 100_000_001, Some(1_000_000), &context()),
+            Err(RiskViolation::OrderNotional { ref limit, .. }) if limit == "100.000000"
+        ));
+
+        // A limit that never went through `validate` fails closed.
+        let limits = RiskConfig {
+            max_position: Some("ten".into()),
+            ..RiskConfig::default()
+        };
+        assert!(matches!(
+            limits.check(&market, 1, 1, Some(1_000_000), &context()),
+            Err(RiskViolation::InvalidLimit {
+                name: "max_position",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn env_overrides_replace_and_validate() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        let base = RiskConfig {
+            max_open_orders: Some(5),
+            ..RiskConfig::default()
+        };
+        let limits = base
+            .clone()
+            .with_env_overrides(env(&[
+                (ENV_MAX_OPEN_ORDERS, "10"),
+                (ENV_MAX_ORDER_NOTIONAL, "2500.5"),
+                (ENV_PRICE_BAND_BPS, ""),
+                (ENV_CHECK_BALANCE, "true"),
+            ]))
+            .unwrap();
+        assert_eq!(limits.max_open_orders, Some(10));
+        assert_eq!(limits.max_order_notional.as_deref(), Some("2500.5"));
+        assert_eq!(limits.price_band_bps, None);
+        assert!(limits.check_balance);
+        assert!(RiskConfig::default().is_empty());
+
+        assert!(
+            base.clone()
+                .with_env_overrides(env(&[(ENV_MAX_OPEN_ORDERS, "many")]))
+                .is_err()
+        );
+        assert!(
+            base.clone()
+                .with_env_overrides(env(&[(ENV_MAX_POSITION, "1.2.3")]))
+                .is_err()
+        );
+        assert!(
+            base.with_env_overrides(env(&[(ENV_CHECK_BALANCE, "maybe")]))
+                .is_err()
+        );
+    }
+}
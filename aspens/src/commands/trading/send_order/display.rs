@@ -80,10 +80,13 @@ impl SendOrderResponse {
 
 impl fmt::Display for SendOrderResponse {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "SendOrderResponse {{\n  order_id: {},", self.order_id)?;
+        if !self.client_order_id.is_empty() {
+            writeln!(f, "  client_order_id: {},", self.client_order_id)?;
+        }
         write!(
             f,
-            "SendOrderResponse {{\n  order_id: {},\n  order_in_book: {},\n  order: {},\n  trades: [{}],\n  transaction_hashes: [{}]\n}}",
-            self.order_id,
+            "  order_in_book: {},\n  order: {},\n  trades: [{}],\n  transaction_hashes: [{}]\n}}",
             self.order_in_book,
             self.order
                 .as_ref()
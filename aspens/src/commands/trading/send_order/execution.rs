@@ -0,0 +1,158 @@
+//! Time-in-force for submitted orders.
+//!
+//! [`ExecutionType`] is the caller-facing choice of how long an order may
+//! stay on the book. It maps onto two fields of the signed `Order`: the
+//! proto `execution_type` (immediate-or-cancel, fill-or-kill) and the
+//! `post_only` flag. Good-till-cancel leaves both at their defaults, so
+//! those envelopes are byte-identical to orders signed before the field was
+//! exposed.
+//!
+//! Good-till-time is orthogonal: any order that can rest may also carry an
+//! `expires_at`, after which the stack cancels what is left of it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::arborter_pb;
+use crate::error::{AspensError, Result};
+
+/// How long an order may rest, chosen at submission.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutionType {
+    /// Match what crosses, rest the remainder until filled or canceled.
+    #[default]
+    GoodTillCancel,
+    /// Match what crosses on arrival, cancel the remainder.
+    ImmediateOrCancel,
+    /// Fill the whole quantity on arrival or reject the order untouched.
+    FillOrKill,
+    /// Rest the whole order, or reject it if it would cross (limit only).
+    PostOnly,
+}
+
+impl ExecutionType {
+    /// The proto `execution_type` this maps to.
+    pub fn proto(self) -> arborter_pb::ExecutionType {
+        match self {
+            Self::GoodTillCancel | Self::PostOnly => arborter_pb::ExecutionType::Unspecified,
+            Self::ImmediateOrCancel => arborter_pb::ExecutionType::ImmediateOrCancel,
+            Self::FillOrKill => arborter_pb::ExecutionType::FillOrKill,
+        }
+    }
+
+    /// True for [`PostOnly`](Self::PostOnly), which sets the order's
+    /// `post_only` flag.
+    pub fn is_post_only(self) -> bool {
+        self == Self::PostOnly
+    }
+
+    /// True if the order never rests on the book.
+    pub fn is_immediate(self) -> bool {
+        matches!(self, Self::ImmediateOrCancel | Self::FillOrKill)
+    }
+}
+
+impl fmt::Display for ExecutionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::GoodTillCancel => "gtc",
+            Self::ImmediateOrCancel => "ioc",
+            Self::FillOrKill => "fok",
+            Self::PostOnly => "post-only",
+        })
+    }
+}
+
+impl FromStr for ExecutionType {
+    type Err = AspensError;
+
+    /// `gtc`, `ioc`, `fok` or `post-only`, or the long kebab-case names
+    /// (`fill-or-kill`); case-insensitive.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "gtc" | "good-till-cancel" => Ok(Self::GoodTillCancel),
+            "ioc" | "immediate-or-cancel" => Ok(Self::ImmediateOrCancel),
+            "fok" | "fill-or-kill" => Ok(Self::FillOrKill),
+            "post-only" | "postonly" => Ok(Self::PostOnly),
+            other => Err(AspensError::invalid_input(format!(
+                "invalid time in force '{}' (expected gtc, ioc, fok or post-only)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Check a good-till-time expiry (unix seconds) against the rest of the
+/// order before anything is signed.
+pub(super) fn validate_expiry(
+    expires_at: u64,
+    is_limit: bool,
+    execution: ExecutionType,
+    now: u64,
+) -> Result<()> {
+    if expires_at <= now {
+        return Err(AspensError::invalid_input(format!(
+            "expiry {} is not in the future (now {})",
+            crate::util::format_utc(expires_at),
+            crate::util::format_utc(now)
+        )));
+    }
+    if !is_limit {
+        return Err(AspensError::invalid_input(
+            "an expiry needs a limit order; market orders never rest",
+        ));
+    }
+    if execution.is_immediate() {
+        return Err(AspensError::invalid_input(format!(
+            "time in force '{}' never rests on the book, so it can't expire",
+            execution
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_maps_to_the_order_fields() {
+        for (s, tif) in [
+            ("gtc", ExecutionType::GoodTillCancel),
+            ("IOC", ExecutionType::ImmediateOrCancel),
+            ("fill_or_kill", ExecutionType::FillOrKill),
+            ("post-only", ExecutionType::PostOnly),
+        ] {
+            assert_eq!(s.parse::<ExecutionType>().unwrap(), tif);
+            assert_eq!(tif.to_string().parse::<ExecutionType>().unwrap(), tif);
+        }
+        assert!("day".parse::<ExecutionType>().is_err());
+
+        assert_eq!(
+            ExecutionType::default().proto(),
+            arborter_pb::ExecutionType::Unspecified
+        );
+        assert_eq!(
+            ExecutionType::FillOrKill.proto(),
+            arborter_pb::ExecutionType::FillOrKill
+        );
+        assert!(ExecutionType::PostOnly.is_post_only());
+        assert_eq!(
+            ExecutionType::PostOnly.proto(),
+            arborter_pb::ExecutionType::Unspecified
+        );
+    }
+
+    #[test]
+    fn expiry_must_be_future_and_resting() {
+        let now = 1_800_000_000;
+        assert!(validate_expiry(now + 60, true, ExecutionType::GoodTillCancel, now).is_ok());
+        assert!(validate_expiry(now + 60, true, ExecutionType::PostOnly, now).is_ok());
+        assert!(validate_expiry(now, true, ExecutionType::GoodTillCancel, now).is_err());
+        assert!(validate_expiry(now + 60, false, ExecutionType::GoodTillCancel, now).is_err());
+        assert!(validate_expiry(now + 60, true, ExecutionType::FillOrKill, now).is_err());
+    }
+}
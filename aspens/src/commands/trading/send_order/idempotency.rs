@@ -0,0 +1,288 @@
+//! Client order IDs and idempotent submission.
+//!
+//! An order sent with a client order ID is signed once. Its signed request
+//! is kept in the [`IdempotencyCache`] under the account and ID, together
+//! with the stack's answer once there is one. Retrying a submission that
+//! timed out therefore resends the identical envelope, which the stack
+//! refuses as a duplicate if the first attempt did land, instead of signing
+//! a fresh order with a new lock ID that would be placed a second time; and
+//! retrying one that succeeded returns the recorded response without
+//! sending anything. A duplicate refusal is reported as [`AlreadyPlaced`],
+//! and the submission stays remembered, so later retries keep resending
+//! the same envelope.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use super::arborter_pb::{SendOrderRequest, SendOrderResponse};
+use crate::error::{AspensError, Result};
+
+/// How long [`IdempotencyCache::global`] remembers a submission.
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Check a client order ID is non-empty and at most
+/// [`MAX_TAG_LEN`](super::MAX_TAG_LEN) bytes of printable ASCII without
+/// spaces.
+pub fn validate_client_order_id(client_order_id: &str) -> Result<()> {
+    super::tags::validate_value("client order ID", client_order_id)
+}
+
+/// A submission the cache remembers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedSubmission {
+    /// The signed request as first sent.
+    pub request: SendOrderRequest,
+    /// The stack's answer; `None` while the outcome is unknown.
+    pub response: Option<SendOrderResponse>,
+}
+
+/// The stack refused a submission as a duplicate: an earlier attempt with
+/// the same signed envelope was placed, though its answer never arrived.
+/// Look the order up by client order ID rather than sending it again.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("order with client order ID '{client_order_id}' was already placed: {message}")]
+pub struct AlreadyPlaced {
+    /// The order's client order ID.
+    pub client_order_id: String,
+    /// The stack's message.
+    pub message: String,
+}
+
+impl AlreadyPlaced {
+    /// The duplicate refusal behind `err`, if it is one.
+    pub fn of(err: &AspensError) -> Option<&AlreadyPlaced> {
+        match err {
+            AspensError::InvalidInput(e) => e.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+type Key = (String, String);
+
+/// Signed submissions by account and client order ID.
+///
+/// Order submission uses [`IdempotencyCache::global`]; entries expire
+/// after its TTL, so a long-running bot can eventually reuse an ID.
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Key, (Instant, CachedSubmission)>>,
+}
+
+impl IdempotencyCache {
+    /// An empty cache whose entries expire after `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The process-wide cache order submission consults, with
+    /// [`DEFAULT_IDEMPOTENCY_TTL`].
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<IdempotencyCache> = OnceLock::new();
+        GLOBAL.get_or_init(|| Self::new(DEFAULT_IDEMPOTENCY_TTL))
+    }
+
+    /// The submission `account` made under `client_order_id`, if it hasn't
+    /// expired.
+    pub fn get(&self, account: &str, client_order_id: &str) -> Option<CachedSubmission> {
+        let mut entries = self.lock();
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+        entries
+            .get(&key(account, client_order_id))
+            .map(|(_, submission)| submission.clone())
+    }
+
+    /// Remember `request` as sent, outcome unknown.
+    pub(crate) fn begin(&self, account: &str, client_order_id: &str, request: SendOrderRequest) {
+        self.lock().insert(
+            key(account, client_order_id),
+            (
+                Instant::now(),
+                CachedSubmission {
+                    request,
+                    response: None,
+                },
+            ),
+        );
+    }
+
+    /// Record the stack's answer to a remembered submission.
+    pub(crate) fn finish(
+        &self,
+        account: &str,
+        client_order_id: &str,
+        response: &SendOrderResponse,
+    ) {
+        if let Some((_, submission)) = self.lock().get_mut(&key(account, client_order_id)) {
+            submission.response = Some(response.clone());
+        }
+    }
+
+    /// The error for the stack refusing the submission `account` made under
+    /// `client_order_id` with `status`; `resent` when the envelope came from
+    /// this cache rather than being signed for this attempt.
+    ///
+    /// Only an outright rejection of a freshly signed envelope frees the ID.
+    /// A resent envelope may have been placed by the attempt that timed
+    /// out, and a duplicate refusal says it was, so both stay remembered:
+    /// signing afresh would place the order a second time.
+    pub(crate) fn rejected(
+        &self,
+        account: &str,
+        client_order_id: &str,
+        status: tonic::Status,
+        resent: bool,
+    ) -> AspensError {
+        if is_duplicate(&status) {
+            return AspensError::InvalidInput(Box::new(AlreadyPlaced {
+                client_order_id: client_order_id.to_string(),
+                message: status.message().to_string(),
+            }));
+        }
+        let err = AspensError::from(status);
+        if !resent && !err.is_retryable() {
+            self.forget(account, client_order_id);
+        }
+        err
+    }
+
+    /// Drop a submission, e.g. after the stack rejected it outright, so the
+    /// ID can be signed afresh.
+    pub fn forget(&self, account: &str, client_order_id: &str) {
+        self.lock().remove(&key(account, client_order_id));
+    }
+
+    /// Number of remembered submissions, expired ones included until the
+    /// next [`get`](Self::get).
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// True when nothing is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Key, (Instant, CachedSubmission)>> {
+        self.entries.lock().expect("IdempotencyCache lock poisoned")
+    }
+}
+
+/// Whether `status` refuses an order the stack has already seen.
+fn is_duplicate(status: &tonic::Status) -> bool {
+    status.code() == tonic::Code::AlreadyExists
+        || status.message().to_lowercase().contains("duplicate")
+}
+
+fn key(account: &str, client_order_id: &str) -> Key {
+    (account.to_string(), client_order_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::trading::send_order::arborter_pb::Order;
+
+    fn request(client_order_id: &str) -> SendOrderRequest {
+        SendOrderRequest {
+            order: Some(Order {
+                client_order_id: client_order_id.into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn remembers_the_signed_request_then_the_answer() {
+        let cache = IdempotencyCache::new(DEFAULT_IDEMPOTENCY_TTL);
+        cache.begin("0xa", "bot-1", request("bot-1"));
+        let pending = cache.get("0xa", "bot-1").unwrap();
+        assert_eq!(pending.request, request("bot-1"));
+        assert_eq!(pending.response, None);
+        // IDs are per account.
+        assert!(cache.get("0xb", "bot-1").is_none());
+
+        let response = SendOrderResponse {
+            order_id: 7,
+            ..Default::default()
+        };
+        cache.finish("0xa", "bot-1", &response);
+        assert_eq!(cache.get("0xa", "bot-1").unwrap().response, Some(response));
+
+        cache.forget("0xa", "bot-1");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn a_duplicate_after_a_timeout_keeps_the_signed_envelope() {
+        let cache = IdempotencyCache::new(DEFAULT_IDEMPOTENCY_TTL);
+        // Attempt 1 is signed and sent, then times out after it landed.
+        cache.begin("0xa", "bot-1", request("bot-1"));
+        let err = cache.rejected(
+            "0xa",
+            "bot-1",
+            tonic::Status::deadline_exceeded("timed out"),
+            false,
+        );
+        assert!(err.is_retryable());
+
+        // Attempt 2 resends the envelope and is refused as a duplicate.
+        let resent = cache.get("0xa", "bot-1").unwrap();
+        assert_eq!(resent.request, request("bot-1"));
+        let err = cache.rejected(
+            "0xa",
+            "bot-1",
+            tonic::Status::already_exists("duplicate order"),
+            true,
+        );
+        let placed = AlreadyPlaced::of(&err).unwrap();
+        assert_eq!(placed.client_order_id, "bot-1");
+        assert!(!err.is_retryable());
+
+        // Attempt 3 still finds the envelope to resend, not a blank ID to
+        // sign a second order under.
+        assert_eq!(cache.get("0xa", "bot-1").unwrap().request, request("bot-1"));
+
+        // Any other refusal of a resend keeps it too.
+        let err = cache.rejected(
+            "0xa",
+            "bot-1",
+            tonic::Status::invalid_argument("expired"),
+            true,
+        );
+        assert!(AlreadyPlaced::of(&err).is_none());
+        assert!(cache.get("0xa", "bot-1").is_some());
+
+        // An outright rejection of a freshly signed envelope frees the ID.
+        cache.begin("0xa", "bot-2", request("bot-2"));
+        cache.rejected(
+            "0xa",
+            "bot-2",
+            tonic::Status::invalid_argument("bad price"),
+            false,
+        );
+        assert!(cache.get("0xa", "bot-2").is_none());
+    }
+
+    #[test]
+    fn entries_expire() {
+        let cache = IdempotencyCache::new(Duration::ZERO);
+        cache.begin("0xa", "bot-1", request("bot-1"));
+        assert!(cache.get("0xa", "bot-1").is_none());
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn validates_ids() {
+        assert!(validate_client_order_id("bot-1").is_ok());
+        assert!(validate_client_order_id("").is_err());
+        assert!(validate_client_order_id("has space").is_err());
+        assert!(validate_client_order_id(&"x".repeat(65)).is_err());
+    }
+}
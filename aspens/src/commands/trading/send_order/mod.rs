@@ -8,19 +8,35 @@ pub mod arborter_pb {
 // types live next door in `display.rs` so this file can focus on the
 // call / signing logic.
 mod display;
-
-use crate::wallet::Wallet;
+mod execution;
+mod idempotency;
+mod options;
+mod tags;
+
+pub use execution::ExecutionType;
+pub use idempotency::{
+    AlreadyPlaced, CachedSubmission, DEFAULT_IDEMPOTENCY_TTL, IdempotencyCache,
+    validate_client_order_id,
+};
+pub use options::OrderOptions;
+pub use tags::{
+    MAX_TAG_LEN, OrderTags, REFERRAL_METADATA_KEY, STRATEGY_METADATA_KEY, TAGS_METADATA_KEY,
+};
+
+use crate::signer::AspensSigner;
 use alloy::primitives::{Address, U256};
 use alloy::providers::ProviderBuilder;
 use alloy::signers::local::PrivateKeySigner;
 use alloy_chains::NamedChain;
 use arborter_pb::arborter_service_client::ArborterServiceClient;
 use arborter_pb::{Order, SendOrderRequest, SendOrderResponse};
-use eyre::Result;
-use prost::Message;
 use url::Url;
 
+use super::risk;
+use super::signing::{SignedPayload, SigningVersion};
 use crate::commands::config::config_pb::GetConfigResponse;
+use crate::decimals::PrecisionMode;
+use crate::error::{AspensError, Result};
 use crate::evm::rpc::MidribV3;
 use crate::grpc::create_channel;
 
@@ -37,17 +53,17 @@ async fn call_send_order(
     market_id: String,
     base_account_address: String,
     quote_account_address: String,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     authorization: Option<arborter_pb::OrderAuthorization>,
+    execution: ExecutionType,
     post_only: bool,
     hidden: bool,
+    auction: bool,
+    expires_at: Option<u64>,
+    client_order_id: Option<&str>,
+    tags: &OrderTags,
+    signing_version: SigningVersion,
 ) -> Result<SendOrderResponse> {
-    // Create a channel to connect to the gRPC server (with TLS support for HTTPS)
-    let channel = create_channel(&url).await?;
-
-    // Instantiate the client
-    let mut client = ArborterServiceClient::new(channel);
-
     // Create the order for sending with original pair decimal values.
     // `post_only=false` is the proto3 default and is wire-skipped on encode,
     // so existing callers' signed envelopes are byte-identical to pre-feature
@@ -61,42 +77,99 @@ async fn call_send_order(
         market_id: market_id.clone(),
         base_account_address: base_account_address.clone(),
         quote_account_address: quote_account_address.clone(),
-        execution_type: 0,
+        // Good-till-cancel and post-only encode as 0, which is wire-skipped
+        // like the flags below.
+        execution_type: execution.proto() as i32,
         matching_order_ids: vec![],
         post_only,
         hidden,
+        auction,
+        // No expiry encodes as 0 and is wire-skipped too.
+        expires_at: expires_at.unwrap_or(0),
+        // No client order ID encodes as "" and is wire-skipped too.
+        client_order_id: client_order_id.unwrap_or_default().to_string(),
     };
 
-    // Serialize the order to a byte vector for signing
-    let mut buffer = Vec::new();
-    order_for_sending.encode(&mut buffer)?;
-
-    // Sign the order. EVM signatures are 65 bytes (r||s||v); Solana Ed25519 are 64
-    // bytes. Send the full curve-native length — the arborter's curve-aware
-    // verifier (`onchain::verify::is_signature_valid_with_curve`) requires
-    // exactly 65 for Secp256k1 and 64 for Ed25519, with no length tolerance.
-    let signature_bytes = wallet.sign_message(&buffer).await?;
-
-    // Create the request with the original order and signature
-    let request = SendOrderRequest {
-        order: Some(order_for_sending),
-        signature_hash: signature_bytes,
-        authorization,
+    // A client order ID seen before: answer from the cache if the stack
+    // already placed the order, otherwise resend the envelope signed the
+    // first time rather than a new one with a fresh lock ID.
+    let cache = IdempotencyCache::global();
+    let cached = client_order_id.and_then(|id| cache.get(&base_account_address, id));
+    let resent = cached.is_some();
+    let request = match cached {
+        Some(cached) => {
+            if cached.request.order.as_ref() != Some(&order_for_sending) {
+                return Err(AspensError::invalid_input(format!(
+                    "client order ID '{}' was already used for a different order",
+                    order_for_sending.client_order_id
+                )));
+            }
+            if let Some(response) = cached.response {
+                tracing::info!(
+                    "Order with client order ID '{}' was already placed as order {}",
+                    order_for_sending.client_order_id,
+                    response.order_id
+                );
+                return Ok(response);
+            }
+            cached.request
+        }
+        None => {
+            // Serialize the order to its canonical bytes for signing
+            let buffer = order_for_sending.canonical_bytes(signing_version);
+
+            // Sign the order. EVM signatures are 65 bytes (r||s||v); Solana Ed25519 are 64
+            // bytes. Send the full curve-native length — the arborter's curve-aware
+            // verifier (`onchain::verify::is_signature_valid_with_curve`) requires
+            // exactly 65 for Secp256k1 and 64 for Ed25519, with no length tolerance.
+            let signature_bytes = wallet.sign_message(&buffer).await?;
+
+            // Create the request with the original order and signature
+            let request = SendOrderRequest {
+                order: Some(order_for_sending),
+                signature_hash: signature_bytes,
+                authorization,
+                signing_version: signing_version.as_u32(),
+            };
+            if let Some(id) = client_order_id {
+                cache.begin(&base_account_address, id, request.clone());
+            }
+            request
+        }
     };
 
-    // Create a tonic request
-    let request = tonic::Request::new(request);
+    // Create a channel to connect to the gRPC server (with TLS support for HTTPS)
+    let channel = create_channel(&url).await?;
 
-    // Call the send_order endpoint
-    let response = client.send_order(request).await?;
+    // Instantiate the client
+    let mut client = ArborterServiceClient::new(channel);
 
-    // Get the response data
-    let response_data = response.into_inner();
+    // Create a tonic request; strategy / tags ride along as metadata, outside
+    // the signed payload
+    let mut request = tonic::Request::new(request);
+    tags.apply(&mut request)?;
 
-    // Print the response from the server
-    tracing::info!("Response received: {}", response_data);
+    // Call the send_order endpoint
+    let result = client.send_order(request).await;
 
-    Ok(response_data)
+    let Some(id) = client_order_id else {
+        let response_data = result?.into_inner();
+        tracing::info!("Response received: {}", response_data);
+        return Ok(response_data);
+    };
+    match result {
+        Ok(response) => {
+            let mut response_data = response.into_inner();
+            // Stacks that predate client order IDs don't echo them.
+            if response_data.client_order_id.is_empty() {
+                response_data.client_order_id = id.to_string();
+            }
+            tracing::info!("Response received: {}", response_data);
+            cache.finish(&base_account_address, id, &response_data);
+            Ok(response_data)
+        }
+        Err(status) => Err(cache.rejected(&base_account_address, id, status, resent)),
+    }
 }
 
 /// Query deposited (available) balance for a token on a specific chain
@@ -107,8 +180,8 @@ async fn query_deposited_balance(
     user_address: Address,
     chain_id: u32,
 ) -> Result<U256> {
-    let contract_addr: Address = contract_address.parse()?;
-    let token_addr: Address = token_address.parse()?;
+    let contract_addr = crate::util::parse_address(contract_address)?;
+    let token_addr = crate::util::parse_address(token_address)?;
     let rpc_url = Url::parse(rpc_url)?;
 
     // Try to get NamedChain, fallback to a default
@@ -139,14 +212,72 @@ fn format_balance_for_display(balance: U256, decimals: u32) -> String {
     )
 }
 
-/// Convert a human-readable amount (e.g., `"1.001"`) to pair decimals format
-/// as a decimal string suitable for the gRPC payload.
+/// Convert a human-readable amount (e.g., `"1.001"`) to raw pair decimals,
+/// with `precision` deciding what happens to digits past `decimals`.
 ///
-/// Thin wrapper over [`crate::decimals::parse_decimal_amount`]; kept as
-/// a private alias so the existing `String`-returning order-encoding path
-/// stays untouched.
-fn convert_to_pair_decimals(amount: &str, decimals: u32) -> Result<String> {
-    Ok(crate::decimals::parse_decimal_amount(amount, decimals)?.to_string())
+/// Thin wrapper over [`crate::decimals::parse_decimal_amount_with`] that
+/// returns the crate error type.
+pub(crate) fn convert_to_pair_decimals(
+    amount: &str,
+    decimals: u32,
+    precision: PrecisionMode,
+) -> Result<u128> {
+    Ok(crate::decimals::parse_decimal_amount_with(
+        amount, decimals, precision,
+    )?)
+}
+
+/// Pick the wallets for a market's base and quote legs from `wallets`.
+///
+/// Errors if a wallet of the right curve is missing for either chain.
+pub(crate) fn leg_wallets<'w>(
+    config: &GetConfigResponse,
+    market: &crate::commands::config::config_pb::Market,
+    wallets: &[&'w dyn AspensSigner],
+) -> Result<(&'w dyn AspensSigner, &'w dyn AspensSigner)> {
+    // Pick the wallet whose curve matches each chain's architecture. The
+    // SDK's `chain_curve` helper is the single source of truth for the
+    // arch→curve mapping; using it here keeps order routing aligned with
+    // deposit / balance / cancel flows.
+    let base_chain = config
+        .get_chain(&market.base_chain_network)
+        .ok_or_else(|| {
+            AspensError::not_found(format!(
+                "base chain '{}' not in config",
+                market.base_chain_network
+            ))
+        })?;
+    let quote_chain = config
+        .get_chain(&market.quote_chain_network)
+        .ok_or_else(|| {
+            AspensError::not_found(format!(
+                "quote chain '{}' not in config",
+                market.quote_chain_network
+            ))
+        })?;
+    let base_curve = crate::wallet::chain_curve(base_chain);
+    let quote_curve = crate::wallet::chain_curve(quote_chain);
+    let base_wallet = wallets
+        .iter()
+        .copied()
+        .find(|w| w.curve() == base_curve)
+        .ok_or_else(|| {
+            AspensError::invalid_input(format!(
+                "no wallet of curve {:?} available for base chain '{}'",
+                base_curve, market.base_chain_network
+            ))
+        })?;
+    let quote_wallet = wallets
+        .iter()
+        .copied()
+        .find(|w| w.curve() == quote_curve)
+        .ok_or_else(|| {
+            AspensError::invalid_input(format!(
+                "no wallet of curve {:?} available for quote chain '{}'",
+                quote_curve, market.quote_chain_network
+            ))
+        })?;
+    Ok((base_wallet, quote_wallet))
 }
 
 /// Look up a market from the configuration
@@ -204,11 +335,10 @@ pub fn lookup_market<'a>(
                 .join(", ")
         })
         .unwrap_or_default();
-    Err(eyre::eyre!(
+    Err(AspensError::not_found(format!(
         "Market '{}' not found in configuration. Available markets: {}",
-        market_id,
-        available_markets
-    ))
+        market_id, available_markets
+    )))
 }
 
 /// Resolve the origin chain network for a (market, side) pair — the chain
@@ -230,7 +360,9 @@ pub fn origin_network_for_side<'a>(
         arborter_pb::Side::Bid => &market.quote_chain_network,
         arborter_pb::Side::Ask => &market.base_chain_network,
         arborter_pb::Side::Unspecified => {
-            return Err(eyre::eyre!("Side::Unspecified has no origin chain"));
+            return Err(AspensError::invalid_input(
+                "Side::Unspecified has no origin chain",
+            ));
         }
     })
 }
@@ -242,16 +374,18 @@ pub fn parse_side(s: &str) -> Result<arborter_pb::Side> {
     match s.to_lowercase().as_str() {
         "buy" | "bid" => Ok(arborter_pb::Side::Bid),
         "sell" | "ask" => Ok(arborter_pb::Side::Ask),
-        other => Err(eyre::eyre!(
+        other => Err(AspensError::invalid_input(format!(
             "invalid side '{}' (expected 'buy'/'bid' or 'sell'/'ask')",
             other
-        )),
+        ))),
     }
 }
 
 /// Derive the account address from a private key
 pub fn derive_address(privkey: &str) -> Result<(Address, String)> {
-    let signer = privkey.parse::<PrivateKeySigner>()?;
+    let signer = privkey
+        .parse::<PrivateKeySigner>()
+        .map_err(|e| AspensError::InvalidInput(Box::new(e)))?;
     let address = signer.address();
     let checksum = address.to_checksum(None);
     Ok((address, checksum))
@@ -291,7 +425,7 @@ pub async fn send_order_with_wallet(
     side: i32,
     quantity: String,
     price: Option<String>,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     config: GetConfigResponse,
     post_only: bool,
     hidden: bool,
@@ -325,6 +459,9 @@ pub async fn send_order_with_wallet(
 /// `post_only`: see [`send_order_with_wallet`].
 /// `hidden`: see [`send_order_with_wallet`]. No client-side validation —
 /// hidden combines legally with market, limit, and post_only orders.
+///
+/// Thin wrapper over [`send_order_with_options`] with every other option
+/// at its default.
 // Public top-level API — same rationale as `send_order_with_wallet`
 // for keeping the argument list flat.
 #[allow(clippy::too_many_arguments)]
@@ -334,75 +471,121 @@ pub async fn send_order_with_wallets(
     side: i32,
     quantity: String,
     price: Option<String>,
-    wallets: &[&Wallet],
+    wallets: &[&dyn AspensSigner],
     config: GetConfigResponse,
     post_only: bool,
     hidden: bool,
 ) -> Result<SendOrderResponse> {
-    if wallets.is_empty() {
-        return Err(eyre::eyre!(
-            "send_order_with_wallets requires at least one wallet"
-        ));
-    }
+    send_order_with_options(
+        url,
+        market_id,
+        side,
+        quantity,
+        price,
+        wallets,
+        config,
+        OrderOptions {
+            post_only,
+            hidden,
+            ..OrderOptions::default()
+        },
+    )
+    .await
+}
 
-    // Reject post-only without a price up front — arborter will reject it
-    // anyway, but failing here saves a round-trip + signature work and
-    // surfaces a clearer error to scripts.
-    if post_only && price.is_none() {
-        return Err(eyre::eyre!(
-            "post_only is incompatible with market orders (no price); \
-             pass an explicit limit price or set post_only=false"
+/// [`send_order_with_wallets`] with every per-order option — time in
+/// force, expiry, precision, client order ID, tags and risk limits — set
+/// through [`OrderOptions`].
+///
+/// The options are validated before anything is signed. The risk limits
+/// are checked once the order is resolved (see [`risk`](super::risk)); with
+/// [`RiskConfig::check_balance`](super::risk::RiskConfig::check_balance)
+/// on, the deposit is checked alongside the market's own limits, see
+/// [`check_deposited_balance`].
+// Public top-level API — the seven order fields mirror
+// `send_order_with_wallets`, so this is one past clippy's limit.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_order_with_options(
+    url: String,
+    market_id: String,
+    side: i32,
+    quantity: String,
+    price: Option<String>,
+    wallets: &[&dyn AspensSigner],
+    config: GetConfigResponse,
+    options: OrderOptions,
+) -> Result<SendOrderResponse> {
+    options.validate(price.is_some())?;
+    if wallets.is_empty() {
+        return Err(AspensError::invalid_input(
+            "send_order_with_wallets requires at least one wallet",
         ));
     }
+    let post_only = options.is_post_only();
+    let OrderOptions {
+        hidden,
+        auction,
+        execution,
+        expires_at,
+        precision,
+        client_order_id,
+        tags,
+        risk: risk_limits,
+        ..
+    } = options;
 
     // Look up market
     let market = lookup_market(&config, &market_id)?;
     let pair_decimals = market.pair_decimals as u32;
+    let signing_version = SigningVersion::for_config(&config)?;
+
+    // Refuse orders outside the market's trading hours before signing.
+    market.ensure_open(crate::util::unix_now())?;
 
     // Convert amounts
-    let quantity_raw = convert_to_pair_decimals(&quantity, pair_decimals)
-        .map_err(|e| eyre::eyre!("Invalid quantity '{}': {}", quantity, e))?;
-    let price_raw = price
+    let quantity_units =
+        convert_to_pair_decimals(&quantity, pair_decimals, precision).map_err(|e| {
+            AspensError::invalid_input(format!(
+                "Invalid quantity '{}': {} (market {} has {} pair decimals)",
+                quantity, e, market.name, pair_decimals
+            ))
+        })?;
+    let price_units = price
         .as_ref()
-        .map(|p| convert_to_pair_decimals(p, pair_decimals))
+        .map(|p| convert_to_pair_decimals(p, pair_decimals, precision))
         .transpose()
-        .map_err(|e| eyre::eyre!("Invalid price: {}", e))?;
-
-    // Pick the wallet whose curve matches each chain's architecture. The
-    // SDK's `chain_curve` helper is the single source of truth for the
-    // arch→curve mapping; using it here keeps order routing aligned with
-    // deposit / balance / cancel flows.
-    let base_chain = config
-        .get_chain(&market.base_chain_network)
-        .ok_or_else(|| eyre::eyre!("base chain '{}' not in config", market.base_chain_network))?;
-    let quote_chain = config
-        .get_chain(&market.quote_chain_network)
-        .ok_or_else(|| eyre::eyre!("quote chain '{}' not in config", market.quote_chain_network))?;
-    let base_curve = crate::wallet::chain_curve(base_chain);
-    let quote_curve = crate::wallet::chain_curve(quote_chain);
-    let base_wallet = wallets
-        .iter()
-        .copied()
-        .find(|w| w.curve() == base_curve)
-        .ok_or_else(|| {
-            eyre::eyre!(
-                "no wallet of curve {:?} available for base chain '{}'",
-                base_curve,
-                market.base_chain_network
-            )
-        })?;
-    let quote_wallet = wallets
-        .iter()
-        .copied()
-        .find(|w| w.curve() == quote_curve)
-        .ok_or_else(|| {
-            eyre::eyre!(
-                "no wallet of curve {:?} available for quote chain '{}'",
-                quote_curve,
-                market.quote_chain_network
-            )
+        .map_err(|e| {
+            AspensError::invalid_input(format!(
+                "Invalid price: {} (market {} has {} pair decimals)",
+                e, market.name, pair_decimals
+            ))
         })?;
 
+    // The wire carries raw amounts as decimal strings.
+    let quantity_raw = quantity_units.to_string();
+    let price_raw = price_units.map(|p| p.to_string());
+
+    // Check the market's tick, lot and minimum notional before signing.
+    market.check_order_limits(quantity_units, price_units)?;
+    if risk_limits.check_balance {
+        check_deposited_balance(&config, market, side, quantity_units, price_units, wallets)
+            .await?;
+    }
+
+    let (base_wallet, quote_wallet) = leg_wallets(&config, market, wallets)?;
+
+    // Then the client's risk limits.
+    risk::check_resolved(
+        url.clone(),
+        market,
+        side,
+        quantity_units,
+        price_units,
+        Some(base_wallet.address()),
+        &risk_limits,
+    )
+    .await?;
+
     // The signing wallet is whichever side locks for this order:
     //   Bid (BUY)  → origin = quote chain  → user locks quote
     //   Ask (SELL) → origin = base  chain  → user locks base
@@ -450,8 +633,14 @@ pub async fn send_order_with_wallets(
         quote_account_address,
         signing_wallet,
         Some(authorization),
+        execution,
         post_only,
         hidden,
+        auction,
+        expires_at,
+        client_order_id.as_deref(),
+        &tags,
+        signing_version,
     )
     .await;
 
@@ -467,15 +656,14 @@ pub async fn send_order_with_wallets(
                 .find(|w| w.curve() == crate::wallet::CurveType::Secp256k1)
         {
             // Re-parse the EVM address for the balance enhancement helper.
-            if let Ok(user_address) = evm_wallet.address().parse::<Address>()
-                && let Some(enhanced) = enhance_balance_error(
+            if let Ok(user_address) = crate::util::parse_address(&evm_wallet.address())
+                && let Ok(Some(enhanced)) = balance_shortfall(
                     &config,
                     market,
                     side,
-                    &quantity_raw,
-                    price_raw.as_deref(),
+                    quantity_units,
+                    price_units,
                     user_address,
-                    pair_decimals,
                 )
                 .await
             {
@@ -487,34 +675,86 @@ pub async fn send_order_with_wallets(
     result
 }
 
-/// Try to provide a more helpful error message for balance issues
-async fn enhance_balance_error(
+/// Fail fast if the wallet that locks a `side` order (1 = bid, 2 = ask)
+/// hasn't deposited enough to cover it, with an
+/// [`AspensError::InsufficientBalance`] giving the exact shortfall instead
+/// of the server's bare "insufficient balance". `quantity` and `price` are
+/// raw pair decimals.
+///
+/// Only EVM origin chains are checked, and never a market buy, whose cost
+/// isn't known until it fills.
+pub async fn check_deposited_balance(
+    config: &GetConfigResponse,
+    market: &crate::commands::config::config_pb::Market,
+    side: i32,
+    quantity: u128,
+    price: Option<u128>,
+    wallets: &[&dyn AspensSigner],
+) -> Result<()> {
+    let (base_wallet, quote_wallet) = leg_wallets(config, market, wallets)?;
+    let wallet = if side == 1 { quote_wallet } else { base_wallet };
+    if wallet.curve() != crate::wallet::CurveType::Secp256k1 {
+        return Ok(());
+    }
+    let user_address = crate::util::parse_address(&wallet.address())?;
+    match balance_shortfall(config, market, side, quantity, price, user_address).await? {
+        Some(shortfall) => Err(shortfall),
+        None => Ok(()),
+    }
+}
+
+/// Compare `user_address`'s deposited balance on the chain a `side` order
+/// locks on with what the order locks. `Ok(None)` if the balance covers
+/// it, or for a market buy.
+async fn balance_shortfall(
     config: &GetConfigResponse,
     market: &crate::commands::config::config_pb::Market,
     side: i32,
-    quantity_raw: &str,
-    price_raw: Option<&str>,
+    quantity: u128,
+    price: Option<u128>,
     user_address: Address,
-    pair_decimals: u32,
-) -> Option<eyre::Report> {
+) -> Result<Option<AspensError>> {
     // BUY: need quote token, SELL: need base token
-    let (chain_network, token_symbol, token_decimals) = if side == 1 {
+    let (chain_network, token_symbol) = if side == 1 {
         (
             &market.quote_chain_network,
             &market.quote_chain_token_symbol,
-            market.quote_chain_token_decimals as u32,
         )
     } else {
-        (
-            &market.base_chain_network,
-            &market.base_chain_token_symbol,
-            market.base_chain_token_decimals as u32,
-        )
+        (&market.base_chain_network, &market.base_chain_token_symbol)
     };
 
-    let chain = config.get_chain(chain_network)?;
-    let trade_contract = chain.trade_contract.as_ref()?;
-    let token = chain.tokens.get(token_symbol)?;
+    let chain = config.get_chain(chain_network).ok_or_else(|| {
+        AspensError::not_found(format!("chain '{}' not in config", chain_network))
+    })?;
+    let trade_contract = chain.trade_contract.as_ref().ok_or_else(|| {
+        AspensError::not_found(format!("no trade contract on '{}'", chain_network))
+    })?;
+    let token = chain.tokens.get(token_symbol).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "token {} on {} not found",
+            token_symbol, chain_network
+        ))
+    })?;
+    let token_decimals = token.decimals;
+    let pair_decimals = market.pair_decimals as u32;
+
+    // The order is in pair decimals, the deposited balance in the token's
+    // own, so scale what the order locks the way the gasless lock does.
+    let required = if side == 1 {
+        // BUY: need quantity * price
+        let Some(price) = price else {
+            return Ok(None);
+        };
+        let notional = quantity.checked_mul(price).ok_or_else(|| {
+            AspensError::invalid_input(format!("order notional overflow: {quantity} * {price}"))
+        })?;
+        super::gasless::normalize(notional, pair_decimals * 2, token_decimals)?
+    } else {
+        // SELL: need quantity
+        super::gasless::normalize(quantity, pair_decimals, token_decimals)?
+    };
+    let required = U256::from(required);
 
     let deposited_balance = query_deposited_balance(
         &chain.rpc_url,
@@ -523,50 +763,31 @@ async fn enhance_balance_error(
         user_address,
         chain.chain_id,
     )
-    .await
-    .ok()?;
-
-    let deposited_formatted = format_balance_for_display(deposited_balance, token_decimals);
+    .await?;
 
-    let required_amount = if side == 1 {
-        // BUY: need quantity * price
-        if let Some(p) = price_raw {
-            let qty: u128 = quantity_raw.parse().unwrap_or(0);
-            let prc: u128 = p.parse().unwrap_or(0);
-            let pair_dec_factor = 10_u128.pow(pair_decimals);
-            Some(U256::from(qty * prc / pair_dec_factor))
-        } else {
-            None
-        }
-    } else {
-        // SELL: need quantity
-        let qty: u128 = quantity_raw.parse().unwrap_or(0);
-        Some(U256::from(qty))
-    };
-
-    // Only enhance if we can confirm the balance is actually insufficient
-    let required = required_amount?;
+    // Only report if we can confirm the balance is actually insufficient
     if deposited_balance >= required {
-        return None;
+        return Ok(None);
     }
 
-    let required_str = format_balance_for_display(required, token_decimals);
-
-    Some(eyre::eyre!(
+    Ok(Some(AspensError::insufficient_balance(format!(
         "Insufficient deposited balance on {}.\n\
          Token: {}\n\
          Required: {} {}\n\
-         Available: {} {}\n\n\
+         Available: {} {}\n\
+         Short by: {} {}\n\n\
          Deposit more {} on {} before placing this order.",
         chain_network,
         token_symbol,
-        required_str,
+        format_balance_for_display(required, token_decimals),
+        token_symbol,
+        format_balance_for_display(deposited_balance, token_decimals),
         token_symbol,
-        deposited_formatted,
+        format_balance_for_display(required - deposited_balance, token_decimals),
         token_symbol,
         token_symbol,
         chain_network
-    ))
+    ))))
 }
 
 #[cfg(test)]
@@ -582,6 +803,7 @@ mod tests {
             trades: vec![],
             transaction_hashes: vec![],
             current_orderbook: vec![],
+            client_order_id: String::new(),
         };
 
         assert_eq!(response.order_id, 12345);
@@ -596,6 +818,7 @@ mod tests {
             trades: vec![],
             transaction_hashes: vec![],
             current_orderbook: vec![],
+            client_order_id: String::new(),
         };
 
         assert_eq!(response.order_id, 0);
@@ -610,6 +833,7 @@ mod tests {
             trades: vec![],
             transaction_hashes: vec![],
             current_orderbook: vec![],
+            client_order_id: String::new(),
         };
 
         assert_eq!(response.order_id, u64::MAX);
@@ -624,6 +848,7 @@ mod tests {
             trades: vec![],
             transaction_hashes: vec![],
             current_orderbook: vec![],
+            client_order_id: String::new(),
         };
 
         let display_str = format!("{}", response);
@@ -647,6 +872,9 @@ mod tests {
             matching_order_ids: vec![],
             post_only: false,
             hidden: false,
+            auction: false,
+            expires_at: 0,
+            client_order_id: String::new(),
         };
 
         let response = SendOrderResponse {
@@ -656,6 +884,7 @@ mod tests {
             trades: vec![],
             transaction_hashes: vec![],
             current_orderbook: vec![],
+            client_order_id: String::new(),
         };
 
         assert_eq!(response.order_id, 42);
@@ -666,53 +895,107 @@ mod tests {
     #[test]
     fn test_convert_to_pair_decimals_integer() {
         // 6 decimals (like USDC)
-        assert_eq!(convert_to_pair_decimals("1", 6).unwrap(), "1000000");
-        assert_eq!(convert_to_pair_decimals("100", 6).unwrap(), "100000000");
-        assert_eq!(convert_to_pair_decimals("0", 6).unwrap(), "0");
+        assert_eq!(
+            convert_to_pair_decimals("1", 6, PrecisionMode::Truncate).unwrap(),
+            1_000_000
+        );
+        assert_eq!(
+            convert_to_pair_decimals("100", 6, PrecisionMode::Truncate).unwrap(),
+            100_000_000
+        );
+        assert_eq!(
+            convert_to_pair_decimals("0", 6, PrecisionMode::Truncate).unwrap(),
+            0
+        );
     }
 
     #[test]
     fn test_convert_to_pair_decimals_with_fraction() {
         // 6 decimals
-        assert_eq!(convert_to_pair_decimals("1.5", 6).unwrap(), "1500000");
-        assert_eq!(convert_to_pair_decimals("1.001", 6).unwrap(), "1001000");
-        assert_eq!(convert_to_pair_decimals("0.5", 6).unwrap(), "500000");
-        assert_eq!(convert_to_pair_decimals("0.000001", 6).unwrap(), "1");
+        assert_eq!(
+            convert_to_pair_decimals("1.5", 6, PrecisionMode::Truncate).unwrap(),
+            1_500_000
+        );
+        assert_eq!(
+            convert_to_pair_decimals("1.001", 6, PrecisionMode::Truncate).unwrap(),
+            1_001_000
+        );
+        assert_eq!(
+            convert_to_pair_decimals("0.5", 6, PrecisionMode::Truncate).unwrap(),
+            500_000
+        );
+        assert_eq!(
+            convert_to_pair_decimals("0.000001", 6, PrecisionMode::Truncate).unwrap(),
+            1
+        );
     }
 
     #[test]
     fn test_convert_to_pair_decimals_truncates_extra_precision() {
         // 6 decimals - extra precision should be truncated
-        assert_eq!(convert_to_pair_decimals("1.0000001", 6).unwrap(), "1000000");
-        assert_eq!(convert_to_pair_decimals("1.1234567", 6).unwrap(), "1123456");
+        assert_eq!(
+            convert_to_pair_decimals("1.0000001", 6, PrecisionMode::Truncate).unwrap(),
+            1_000_000
+        );
+        assert_eq!(
+            convert_to_pair_decimals("1.1234567", 6, PrecisionMode::Truncate).unwrap(),
+            1_123_456
+        );
+    }
+
+    #[test]
+    fn test_convert_to_pair_decimals_strict_and_rounded() {
+        assert!(convert_to_pair_decimals("1.1234567", 6, PrecisionMode::Error).is_err());
+        assert_eq!(
+            convert_to_pair_decimals("1.1234567", 6, PrecisionMode::RoundHalfEven).unwrap(),
+            1_123_457
+        );
     }
 
     #[test]
     fn test_convert_to_pair_decimals_18_decimals() {
         // 18 decimals (like ETH)
         assert_eq!(
-            convert_to_pair_decimals("1", 18).unwrap(),
-            "1000000000000000000"
+            convert_to_pair_decimals("1", 18, PrecisionMode::Truncate).unwrap(),
+            1_000_000_000_000_000_000
         );
         assert_eq!(
-            convert_to_pair_decimals("0.1", 18).unwrap(),
-            "100000000000000000"
+            convert_to_pair_decimals("0.1", 18, PrecisionMode::Truncate).unwrap(),
+            100_000_000_000_000_000
         );
     }
 
     #[test]
     fn test_convert_to_pair_decimals_whitespace() {
-        assert_eq!(convert_to_pair_decimals("  1.5  ", 6).unwrap(), "1500000");
+        assert_eq!(
+            convert_to_pair_decimals("  1.5  ", 6, PrecisionMode::Truncate).unwrap(),
+            1_500_000
+        );
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn convert_to_pair_decimals_round_trips_formatting(raw: u128, decimals in 0..=18u32) {
+            // The order path's string form must agree with the formatter
+            // the CLI prints amounts with.
+            let formatted = crate::decimals::format_decimal_amount(raw, decimals);
+            proptest::prop_assert_eq!(
+                convert_to_pair_decimals(&formatted, decimals, PrecisionMode::Error).unwrap(),
+                raw
+            );
+        }
     }
 }
 
 #[cfg(test)]
 mod order_flag_wire_pinning_tests {
     //! Wire-encoding pinning tests for the boolean `Order` flags
-    //! (`post_only` = field 9, `hidden` = field 10).
+    //! (`post_only` = field 9, `hidden` = field 10, `auction` = field 11),
+    //! the `expires_at` expiry (field 12) and `client_order_id` (field 13).
     //!
     //! The envelope signature in `call_send_order` is computed over the
-    //! prost-encoded Order proto, so two invariants must hold for every
+    //! Order's canonical v1 bytes (`signing`), which equal its prost
+    //! encoding, so two invariants must hold for every
     //! flag or signed orders silently fail arborter's verifier:
     //!
     //!   1. **flag = false is wire-skipped** (proto3 default scalars are
@@ -744,6 +1027,9 @@ mod order_flag_wire_pinning_tests {
             matching_order_ids: vec![],
             post_only: false,
             hidden: false,
+            auction: false,
+            expires_at: 0,
+            client_order_id: String::new(),
         }
     }
 
@@ -773,6 +1059,10 @@ mod order_flag_wire_pinning_tests {
             "flag=true must encode as the plain bytes + appended [0x{tag:02x}, 0x01]"
         );
 
+        // The signed bytes are the wire bytes.
+        assert_eq!(buf_plain, plain.canonical_bytes(SigningVersion::V1));
+        assert_eq!(buf_flagged, flagged.canonical_bytes(SigningVersion::V1));
+
         // Round-trips preserve the flag in both states.
         assert_eq!(Order::decode(&*buf_plain).unwrap(), plain);
         assert_eq!(Order::decode(&*buf_flagged).unwrap(), flagged);
@@ -787,4 +1077,40 @@ mod order_flag_wire_pinning_tests {
     fn hidden_wire_pinned() {
         assert_bool_flag_wire_pinned(10, |o, v| o.hidden = v);
     }
+
+    #[test]
+    fn auction_wire_pinned() {
+        assert_bool_flag_wire_pinned(11, |o, v| o.auction = v);
+    }
+
+    /// `expires_at` (field 12) follows the same two invariants: no expiry
+    /// is wire-skipped, and a set expiry appends its varint.
+    #[test]
+    fn expires_at_wire_pinned() {
+        let plain = sample_order();
+        let mut expiring = sample_order();
+        expiring.expires_at = 300;
+
+        let mut expected = plain.encode_to_vec();
+        expected.extend_from_slice(&[12 << 3, 0xac, 0x02]);
+        assert_eq!(expiring.encode_to_vec(), expected);
+        assert_eq!(expected, expiring.canonical_bytes(SigningVersion::V1));
+        assert_eq!(Order::decode(&*expected).unwrap(), expiring);
+    }
+
+    /// `client_order_id` (field 13) likewise: none is wire-skipped, and a
+    /// set ID appends a length-delimited string.
+    #[test]
+    fn client_order_id_wire_pinned() {
+        let plain = sample_order();
+        let mut identified = sample_order();
+        identified.client_order_id = "bot-1".into();
+
+        let mut expected = plain.encode_to_vec();
+        expected.extend_from_slice(&[(13 << 3) | 2, 5]);
+        expected.extend_from_slice(b"bot-1");
+        assert_eq!(identified.encode_to_vec(), expected);
+        assert_eq!(expected, identified.canonical_bytes(SigningVersion::V1));
+        assert_eq!(Order::decode(&*expected).unwrap(), identified);
+    }
 }
@@ -0,0 +1,169 @@
+//! Per-order options for [`send_order_with_options`](super::send_order_with_options).
+//!
+//! Everything an order can carry beyond its market, side, quantity and
+//! price. The default is a plain, visible good-till-cancel order with no
+//! expiry, truncated amounts, no tags and no risk limits — what
+//! [`send_order_with_wallets`](super::send_order_with_wallets) sends.
+
+use super::execution::{ExecutionType, validate_expiry};
+use super::idempotency::validate_client_order_id;
+use super::tags::OrderTags;
+use crate::commands::trading::risk::RiskConfig;
+use crate::decimals::PrecisionMode;
+use crate::error::{AspensError, Result};
+
+/// Options applied to a single order.
+///
+/// Build one with struct-update syntax so unset options keep their
+/// defaults:
+///
+/// ```
+/// use aspens::commands::trading::send_order::{ExecutionType, OrderOptions};
+///
+/// let options = OrderOptions {
+///     post_only: true,
+///     execution: ExecutionType::GoodTillCancel,
+///     ..OrderOptions::default()
+/// };
+/// assert!(!options.hidden);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderOptions {
+    /// Reject the order if it would cross at submission. Limit orders only;
+    /// a market order with `post_only` is refused before signing.
+    pub post_only: bool,
+    /// Invisible order: matched normally (normal price-time priority) but
+    /// excluded from the public orderbook stream and response-embedded
+    /// books; appears in NO stream, not even your own — track it via
+    /// `SendOrderResponse.order_id`. Fills print publicly with your side's
+    /// identity redacted. Combines with market, limit and post-only orders.
+    pub hidden: bool,
+    /// Queue the order for the market's next batch auction instead of
+    /// matching it on arrival (see
+    /// [`get_auction_schedule`](crate::commands::trading::auction::get_auction_schedule)).
+    /// Limit orders only; markets without batch auctions reject it.
+    pub auction: bool,
+    /// Time in force. [`ExecutionType::PostOnly`] is the same as
+    /// `post_only`; immediate-or-cancel and fill-or-kill can't be combined
+    /// with `post_only` or `auction`.
+    pub execution: ExecutionType,
+    /// Good-till-time expiry in unix seconds: the stack cancels whatever is
+    /// still resting at that moment. Limit orders only; it must be in the
+    /// future and can't be combined with immediate-or-cancel or
+    /// fill-or-kill.
+    pub expires_at: Option<u64>,
+    /// What happens to a quantity or price with more decimals than the
+    /// market's `pair_decimals`: truncated, rounded, or refused with an
+    /// error naming the market's precision.
+    pub precision: PrecisionMode,
+    /// Caller-chosen ID signed into the order and echoed in the response.
+    /// Submitting the same ID again from the same account returns the first
+    /// response if the stack answered, or resends the originally signed
+    /// envelope if it didn't (e.g. the call timed out), so a retry can't
+    /// place the order twice; see [`IdempotencyCache`](super::IdempotencyCache).
+    /// Reusing an ID for a different order is an error.
+    pub client_order_id: Option<String>,
+    /// Strategy ID, free-form tags and referral code, sent as request
+    /// metadata rather than inside the signed order, so the envelope digest
+    /// is the same as an untagged order's. Validated (see
+    /// [`OrderTags::validate`]) before anything is signed.
+    pub tags: OrderTags,
+    /// Limits checked once the order is resolved and before it is signed
+    /// (see [`risk`](crate::commands::trading::risk)); an order breaking one
+    /// fails with [`AspensError::RiskLimit`](crate::error::AspensError::RiskLimit).
+    /// [`AspensClient`](crate::AspensClient) callers pass
+    /// [`risk_limits`](crate::AspensClient::risk_limits); the default checks
+    /// nothing.
+    pub risk: RiskConfig,
+}
+
+impl OrderOptions {
+    /// `post_only`, or a post-only time in force.
+    pub fn is_post_only(&self) -> bool {
+        self.post_only || self.execution.is_post_only()
+    }
+
+    /// Check the options for an order with or without a limit price before
+    /// anything is signed: the tags, the client order ID, and the flags
+    /// that need a price or can't be combined.
+    pub(crate) fn validate(&self, has_price: bool) -> Result<()> {
+        self.tags.validate()?;
+        if let Some(id) = &self.client_order_id {
+            validate_client_order_id(id)?;
+        }
+        let post_only = self.is_post_only();
+        // An immediate order never rests, so it can neither wait on the book
+        // (post-only) nor for the next auction.
+        if self.execution.is_immediate() && (post_only || self.auction) {
+            return Err(AspensError::invalid_input(format!(
+                "time in force '{}' never rests on the book, so it can't be combined with \
+                 post_only or auction",
+                self.execution
+            )));
+        }
+        if let Some(expires_at) = self.expires_at {
+            validate_expiry(
+                expires_at,
+                has_price,
+                self.execution,
+                crate::util::unix_now(),
+            )?;
+        }
+        // Reject post-only without a price up front — arborter will reject
+        // it anyway, but failing here saves a round-trip + signature work
+        // and surfaces a clearer error to scripts.
+        if post_only && !has_price {
+            return Err(AspensError::invalid_input(
+                "post_only is incompatible with market orders (no price); \
+                 pass an explicit limit price or set post_only=false",
+            ));
+        }
+        // An auction clears at a single price; a market order has no limit
+        // to clear against.
+        if self.auction && !has_price {
+            return Err(AspensError::invalid_input(
+                "auction orders need a limit price; \
+                 pass an explicit limit price or set auction=false",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_refuses_contradictory_options() {
+        assert!(OrderOptions::default().validate(false).is_ok());
+
+        let post_only = OrderOptions {
+            execution: ExecutionType::PostOnly,
+            ..OrderOptions::default()
+        };
+        assert!(post_only.is_post_only());
+        assert!(post_only.validate(true).is_ok());
+        assert!(post_only.validate(false).is_err());
+
+        let auction = OrderOptions {
+            auction: true,
+            ..OrderOptions::default()
+        };
+        assert!(auction.validate(true).is_ok());
+        assert!(auction.validate(false).is_err());
+
+        let immediate = OrderOptions {
+            execution: ExecutionType::ImmediateOrCancel,
+            auction: true,
+            ..OrderOptions::default()
+        };
+        assert!(immediate.validate(true).is_err());
+
+        let bad_id = OrderOptions {
+            client_order_id: Some(String::new()),
+            ..OrderOptions::default()
+        };
+        assert!(bad_id.validate(true).is_err());
+    }
+}
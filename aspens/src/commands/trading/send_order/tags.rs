@@ -0,0 +1,200 @@
+//! Free-form strategy / tag attribution for submitted orders.
+//!
+//! Tags aren't part of the signed `Order` payload — adding fields there would
+//! change every envelope digest — so they travel as gRPC request metadata
+//! instead. Arborter builds that don't read the headers ignore them.
+//!
+//! The same channel carries an optional referral code, which stacks with
+//! affiliate attribution credit to the code's owner as rebates.
+
+use crate::error::{AspensError, Result};
+
+/// Metadata key carrying [`OrderTags::strategy`].
+pub const STRATEGY_METADATA_KEY: &str = "x-aspens-strategy";
+/// Metadata key carrying [`OrderTags::tags`], comma-separated.
+pub const TAGS_METADATA_KEY: &str = "x-aspens-tags";
+/// Metadata key carrying [`OrderTags::referral`].
+pub const REFERRAL_METADATA_KEY: &str = "x-aspens-referral";
+/// Longest accepted strategy ID or tag, in bytes.
+pub const MAX_TAG_LEN: usize = 64;
+
+/// Strategy ID and free-form tags attached to an order at submission.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderTags {
+    /// Strategy the order belongs to (e.g. `mm-eth-usdc`).
+    pub strategy: Option<String>,
+    /// Free-form labels (e.g. `rebalance`, `manual`).
+    pub tags: Vec<String>,
+    /// Referral / affiliate code the order is attributed to.
+    pub referral: Option<String>,
+}
+
+impl OrderTags {
+    /// Tags with just a strategy ID.
+    pub fn strategy(strategy: impl Into<String>) -> Self {
+        Self {
+            strategy: Some(strategy.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Add a free-form tag.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Attribute the order to a referral code.
+    pub fn with_referral(mut self, code: impl Into<String>) -> Self {
+        self.referral = Some(code.into());
+        self
+    }
+
+    /// True when no strategy, tag or referral code is set.
+    pub fn is_empty(&self) -> bool {
+        self.strategy.is_none() && self.tags.is_empty() && self.referral.is_none()
+    }
+
+    /// True if `tag` is the strategy ID or one of the tags. Reporting code
+    /// uses this to filter by tag.
+    pub fn matches(&self, tag: &str) -> bool {
+        self.strategy.as_deref() == Some(tag) || self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Check every value is non-empty, at most [`MAX_TAG_LEN`] bytes of
+    /// printable ASCII, and (for tags) free of the `,` separator.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(strategy) = &self.strategy {
+            validate_value("strategy", strategy)?;
+        }
+        for tag in &self.tags {
+            validate_value("tag", tag)?;
+            if tag.contains(',') {
+                return Err(AspensError::invalid_input(format!(
+                    "invalid tag '{}': tags can't contain ','",
+                    tag
+                )));
+            }
+        }
+        if let Some(referral) = &self.referral {
+            validate_value("referral code", referral)?;
+        }
+        Ok(())
+    }
+
+    /// Attach the strategy / tags / referral code to an outgoing request as
+    /// metadata.
+    pub(crate) fn apply<T>(&self, request: &mut tonic::Request<T>) -> Result<()> {
+        self.validate()?;
+        let metadata = request.metadata_mut();
+        if let Some(strategy) = &self.strategy {
+            metadata.insert(
+                STRATEGY_METADATA_KEY,
+                strategy.parse().map_err(AspensError::invalid_input)?,
+            );
+        }
+        if !self.tags.is_empty() {
+            metadata.insert(
+                TAGS_METADATA_KEY,
+                self.tags
+                    .join(",")
+                    .parse()
+                    .map_err(AspensError::invalid_input)?,
+            );
+        }
+        if let Some(referral) = &self.referral {
+            metadata.insert(
+                REFERRAL_METADATA_KEY,
+                referral.parse().map_err(AspensError::invalid_input)?,
+            );
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn validate_value(what: &str, value: &str) -> Result<()> {
+    if value.is_empty() {
+        return Err(AspensError::invalid_input(format!(
+            "{} must not be empty",
+            what
+        )));
+    }
+    if value.len() > MAX_TAG_LEN {
+        return Err(AspensError::invalid_input(format!(
+            "invalid {} '{}': longer than {} bytes",
+            what, value, MAX_TAG_LEN
+        )));
+    }
+    if !value.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err(AspensError::invalid_input(format!(
+            "invalid {} '{}': only printable ASCII without spaces is allowed",
+            what, value
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_strategy_and_tags_as_metadata() {
+        let tags = OrderTags::strategy("mm-eth")
+            .with_tag("rebalance")
+            .with_tag("manual");
+        let mut request = tonic::Request::new(());
+        tags.apply(&mut request).unwrap();
+        let metadata = request.metadata();
+        assert_eq!(metadata.get(STRATEGY_METADATA_KEY).unwrap(), "mm-eth");
+        assert_eq!(metadata.get(TAGS_METADATA_KEY).unwrap(), "rebalance,manual");
+        assert!(metadata.get(REFERRAL_METADATA_KEY).is_none());
+    }
+
+    #[test]
+    fn applies_referral_code_as_metadata() {
+        let tags = OrderTags::default().with_referral("desk-42");
+        assert!(!tags.is_empty());
+        let mut request = tonic::Request::new(());
+        tags.apply(&mut request).unwrap();
+        assert_eq!(
+            request.metadata().get(REFERRAL_METADATA_KEY).unwrap(),
+            "desk-42"
+        );
+        assert!(
+            OrderTags::default()
+                .with_referral("bad code")
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn empty_tags_add_no_metadata() {
+        let mut request = tonic::Request::new(());
+        OrderTags::default().apply(&mut request).unwrap();
+        assert!(request.metadata().is_empty());
+    }
+
+    #[test]
+    fn rejects_unsendable_values() {
+        assert!(OrderTags::strategy("").validate().is_err());
+        assert!(OrderTags::strategy("has space").validate().is_err());
+        assert!(OrderTags::strategy("x".repeat(65)).validate().is_err());
+        assert!(OrderTags::default().with_tag("a,b").validate().is_err());
+        assert!(
+            OrderTags::strategy("ok")
+                .with_tag("fine")
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn matches_strategy_or_tag() {
+        let tags = OrderTags::strategy("arb").with_tag("night");
+        assert!(tags.matches("arb"));
+        assert!(tags.matches("night"));
+        assert!(!tags.matches("mm"));
+    }
+}
@@ -5,14 +5,21 @@ pub mod arborter_pb {
 }
 
 use std::fmt;
+use std::sync::Arc;
 
 use arborter_pb::arborter_service_client::ArborterServiceClient;
 use arborter_pb::{OrderState, OrderbookEntry, OrderbookRequest, Side};
-use eyre::Result;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use tokio::sync::mpsc;
 
-use crate::grpc::create_channel;
+use super::buffer::{self, BufferOptions, BufferedReceiver};
+use super::idle::{self, IdleOptions, Next};
+use super::reconnect::{self, ReconnectOptions, ResumePoint};
+use super::replay::HistoricalReplay;
+use crate::error::{AspensError, Result};
+use crate::grpc::{GrpcOptions, create_channel_with, impl_configurable_client};
+
+impl_configurable_client!(ArborterServiceClient<tonic::transport::Channel>);
 
 impl fmt::Display for OrderbookEntry {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -52,6 +59,19 @@ pub struct StreamOrderbookOptions {
     pub historical_open_orders: bool,
     /// If set, filter by a specific trader address
     pub filter_by_trader: Option<String>,
+    /// Message size / compression settings; `None` uses
+    /// [`GrpcOptions::historical`] when `historical_open_orders` is set and the
+    /// defaults otherwise.
+    pub grpc: Option<GrpcOptions>,
+    /// Bounds on the historical replay (ignored unless `historical_open_orders` is
+    /// set).
+    pub replay: HistoricalReplay,
+    /// Probe a silent stream and resubscribe if the stack doesn't answer;
+    /// `None` waits on a silent stream indefinitely.
+    pub idle: Option<IdleOptions>,
+    /// Reconnect a dropped stream with backoff, resuming from the last
+    /// entry delivered; `None` ends the stream with the error instead.
+    pub reconnect: Option<ReconnectOptions>,
 }
 
 /// Stream orderbook entries from the server.
@@ -83,13 +103,229 @@ where
     .await
 }
 
-/// Internal core: stream orderbook entries, invoking an **async** callback
+/// Stream orderbook entries from the server as a [`Stream`].
+///
+/// Unlike the callback and channel variants, the caller drives the stream:
+/// it composes with `select!` and other combinators, nothing is read from
+/// the server while the caller isn't polling (backpressure), and dropping
+/// the stream closes the subscription. The stream ends when the server
+/// closes it; an error is yielded once and ends it too. With
+/// `options.reconnect` set, drops and closes reconnect instead (see
+/// [`reconnect`](super::reconnect)). The returned
+/// stream isn't [`Unpin`]; pin it (`std::pin::pin!` or `Box::pin`) before
+/// calling `next`.
+///
+/// Nothing connects until the stream is first polled.
+pub fn stream_orderbook_stream(
+    url: String,
+    options: StreamOrderbookOptions,
+) -> impl Stream<Item = Result<OrderbookEntry>> + Send + 'static {
+    let state = StreamOrderbookState::new(url, options);
+    futures::stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        match state.next().await {
+            Some(Ok(entry)) => Some((Ok(entry), Some(state))),
+            // An error ends the stream.
+            Some(Err(e)) => Some((Err(e), None)),
+            None => None,
+        }
+    })
+}
+
+/// Where [`stream_orderbook_stream`] is between polls.
+struct StreamOrderbookState {
+    url: String,
+    grpc: GrpcOptions,
+    /// Sent again on every resubscribe.
+    request: OrderbookRequest,
+    replay: HistoricalReplay,
+    idle: Option<IdleOptions>,
+    reconnect: Option<ReconnectOptions>,
+    /// Where a resumed subscription picks up.
+    resume: ResumePoint<OrderbookEntry>,
+    /// Invalid options, reported on the first poll.
+    invalid: Option<AspensError>,
+    live: Option<(tonic::Streaming<OrderbookEntry>, tonic::transport::Channel)>,
+    resubscribes: u32,
+    /// Reconnect attempts since the last delivered entry.
+    failed: u32,
+}
+
+impl StreamOrderbookState {
+    fn new(url: String, options: StreamOrderbookOptions) -> Self {
+        let invalid = options.replay.validate().err();
+        // Historical replays can exceed tonic's 4 MiB default message limit, so
+        // they get larger limits and compression.
+        let grpc = options.grpc.unwrap_or_else(|| {
+            if options.historical_open_orders {
+                GrpcOptions::historical()
+            } else {
+                GrpcOptions::default()
+            }
+        });
+        let replay = options.replay;
+        let idle = options.idle;
+        let reconnect = options.reconnect;
+        let request = OrderbookRequest {
+            continue_stream: true,
+            market_id: options.market_id,
+            historical_open_orders: Some(options.historical_open_orders),
+            filter_by_trader: options.filter_by_trader,
+            historical_since: options.replay.since_ms,
+            historical_limit: options.replay.limit,
+            historical_batch_size: options.replay.batch_size,
+        };
+        Self {
+            url,
+            grpc,
+            request,
+            replay,
+            idle,
+            reconnect,
+            resume: ResumePoint::default(),
+            invalid,
+            live: None,
+            resubscribes: 0,
+            failed: 0,
+        }
+    }
+
+    /// The next entry, subscribing (again) as needed. `None` once the
+    /// server closes the stream and reconnecting is off.
+    async fn next(&mut self) -> Option<Result<OrderbookEntry>> {
+        if let Some(e) = self.invalid.take() {
+            return Some(Err(e));
+        }
+        loop {
+            if self.live.is_none() {
+                match self.subscribe().await {
+                    Ok(live) => self.live = Some(live),
+                    Err(e) if e.is_retryable() && self.reconnect.is_some() => {
+                        if let Err(e) = self.reconnect(e).await {
+                            return Some(Err(e));
+                        }
+                        continue;
+                    }
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            let (stream, channel) = self.live.as_mut()?;
+            match idle::next_or_dead(stream, channel, self.idle.as_ref()).await {
+                Next::Item(None) if self.reconnect.is_some() => {
+                    let e = AspensError::connection(format!(
+                        "orderbook stream for {} closed by the stack",
+                        self.request.market_id
+                    ));
+                    if let Err(e) = self.reconnect(e).await {
+                        return Some(Err(e));
+                    }
+                }
+                Next::Item(None) => return None,
+                // Older servers ignore `historical_since`; hold the bound here.
+                Next::Item(Some(Ok(entry))) if !self.replay.admits(entry.timestamp) => {}
+                // Already delivered before a reconnect.
+                Next::Item(Some(Ok(entry))) if !self.resume.is_new(entry.timestamp, &entry) => {}
+                Next::Item(Some(Ok(entry))) => {
+                    self.failed = 0;
+                    if self.reconnect.is_some_and(|r| r.resume) {
+                        self.resume.record(entry.timestamp, &entry);
+                    }
+                    return Some(Ok(entry));
+                }
+                Next::Item(Some(Err(status)))
+                    if self.reconnect.is_some() && reconnect::is_drop(&status) =>
+                {
+                    if let Err(e) = self.reconnect(status.into()).await {
+                        return Some(Err(e));
+                    }
+                }
+                Next::Item(Some(Err(e))) => {
+                    tracing::error!("Stream error: {}", e);
+                    return Some(Err(e.into()));
+                }
+                Next::Dead => {
+                    self.live = None;
+                    // `next_or_dead` only reports dead streams when `idle` is set.
+                    let idle = self.idle.unwrap_or_default();
+                    if !idle.may_resubscribe(self.resubscribes) {
+                        return Some(Err(AspensError::connection(format!(
+                            "orderbook stream for {} went silent and the stack stopped answering; \
+                             gave up after {} resubscribes",
+                            self.request.market_id, self.resubscribes
+                        ))));
+                    }
+                    self.resubscribes += 1;
+                    self.resume_request();
+                    tracing::warn!(
+                        "orderbook stream for {} went silent and the stack didn't answer a probe; \
+                         resubscribing ({})",
+                        self.request.market_id,
+                        self.resubscribes
+                    );
+                }
+            }
+        }
+    }
+
+    /// Wait out the backoff before the next attempt after the stream
+    /// dropped with `cause`, or give up once `reconnect.max_attempts` have
+    /// failed in a row.
+    async fn reconnect(&mut self, cause: AspensError) -> Result<()> {
+        self.live = None;
+        // Only called with `reconnect` set.
+        let options = self.reconnect.unwrap_or_default();
+        if !options.may_retry(self.failed) {
+            return Err(AspensError::connection(format!(
+                "orderbook stream for {} dropped and didn't come back after {} reconnect attempts: {}",
+                self.request.market_id, self.failed, cause
+            )));
+        }
+        let wait = options.backoff(self.failed);
+        self.failed += 1;
+        tracing::warn!(
+            "orderbook stream for {} dropped ({}); reconnecting in {:?} (attempt {})",
+            self.request.market_id,
+            cause,
+            wait,
+            self.failed
+        );
+        tokio::time::sleep(wait).await;
+        self.resume_request();
+        Ok(())
+    }
+
+    /// Ask the next subscription to replay from the last delivered entry,
+    /// if resuming is on and anything was delivered.
+    fn resume_request(&mut self) {
+        if !self.reconnect.is_some_and(|r| r.resume) {
+            return;
+        }
+        if let Some(since) = self.resume.resume() {
+            self.request.historical_open_orders = Some(true);
+            self.request.historical_since = Some(since);
+            self.request.historical_limit = None;
+        }
+    }
+
+    async fn subscribe(
+        &self,
+    ) -> Result<(tonic::Streaming<OrderbookEntry>, tonic::transport::Channel)> {
+        let channel = create_channel_with(&self.url, self.grpc.keepalive).await?;
+        let mut client = self.grpc.apply(ArborterServiceClient::new(channel.clone()));
+        let response = client
+            .orderbook(tonic::Request::new(self.request.clone()))
+            .await?;
+        Ok((response.into_inner(), channel))
+    }
+}
+
+/// Internal core: drive [`stream_orderbook_stream`], invoking an **async** callback
 /// per entry.
 ///
-/// Both the sync-callback [`stream_orderbook`] and the channel-based
-/// [`stream_orderbook_channel`] delegate here. The async callback is what
-/// lets the channel variant `await` a send rather than block the runtime
-/// thread (`tx.blocking_send` panics inside a multi-thread tokio runtime).
+/// The sync-callback [`stream_orderbook`], the channel-based [`stream_orderbook_channel`] and
+/// the buffered variant delegate here. The async callback is what lets the
+/// channel variant `await` a send rather than block the runtime thread
+/// (`tx.blocking_send` panics inside a multi-thread tokio runtime).
 async fn stream_orderbook_with<F, Fut>(
     url: String,
     options: StreamOrderbookOptions,
@@ -99,42 +335,10 @@ where
     F: FnMut(OrderbookEntry) -> Fut,
     Fut: std::future::Future<Output = ()>,
 {
-    // Create a channel to connect to the gRPC server
-    let channel = create_channel(&url).await?;
-
-    // Instantiate the client
-    let mut client = ArborterServiceClient::new(channel);
-
-    // Create the request
-    let request = OrderbookRequest {
-        continue_stream: true,
-        market_id: options.market_id,
-        historical_open_orders: Some(options.historical_open_orders),
-        filter_by_trader: options.filter_by_trader,
-    };
-
-    // Create a tonic request
-    let request = tonic::Request::new(request);
-
-    // Call the orderbook streaming endpoint
-    let response = client.orderbook(request).await?;
-
-    // Get the streaming response
-    let mut stream = response.into_inner();
-
-    // Process each entry from the stream
-    while let Some(entry_result) = stream.next().await {
-        match entry_result {
-            Ok(entry) => {
-                callback(entry).await;
-            }
-            Err(e) => {
-                tracing::error!("Stream error: {}", e);
-                return Err(e.into());
-            }
-        }
+    let mut stream = std::pin::pin!(stream_orderbook_stream(url, options));
+    while let Some(entry) = stream.next().await {
+        callback(entry?).await;
     }
-
     Ok(())
 }
 
@@ -175,6 +379,10 @@ pub async fn fetch_top_of_book(
             market_id,
             historical_open_orders: true,
             filter_by_trader: None,
+            grpc: None,
+            replay: HistoricalReplay::default(),
+            idle: None,
+            reconnect: None,
         },
     )
     .await?;
@@ -218,6 +426,88 @@ pub async fn fetch_top_of_book(
     Ok(top)
 }
 
+/// Microstructure indicators for one side-by-side view of the book.
+///
+/// Computed by [`book_indicators`] from aggregated price levels. Prices are
+/// in the same raw pair-decimals scale as [`TopOfBook`]; the derived prices
+/// are `f64` so sub-unit precision survives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookIndicators {
+    /// Total bid quantity over the top `depth` levels.
+    pub bid_depth: u128,
+    /// Total ask quantity over the top `depth` levels.
+    pub ask_depth: u128,
+    /// `(bid_depth - ask_depth) / (bid_depth + ask_depth)`, in `[-1, 1]`;
+    /// positive means more resting buy interest. `None` on an empty book.
+    pub imbalance: Option<f64>,
+    /// Plain mid of best bid and best ask. `None` unless both sides quote.
+    pub mid: Option<f64>,
+    /// Quantity-weighted average price of the top `depth` levels on both
+    /// sides. `None` unless both sides quote.
+    pub weighted_mid: Option<f64>,
+    /// Best bid and ask weighted by the opposite side's top quantity, which
+    /// leans towards the side more likely to trade through. `None` unless
+    /// both sides quote.
+    pub microprice: Option<f64>,
+}
+
+/// Compute [`BookIndicators`] from aggregated `(price, quantity)` levels.
+///
+/// `bids` and `asks` must be sorted best-first (bids descending, asks
+/// ascending); only the first `depth` levels of each contribute to the
+/// depth, imbalance and weighted mid. Depths saturate at `u128::MAX`.
+///
+/// For indicators kept current per event, fold the stream into an
+/// [`OrderBook`](super::live_book::OrderBook) and call its
+/// [`indicators`](super::live_book::OrderBook::indicators).
+pub fn book_indicators(
+    bids: &[(u128, u128)],
+    asks: &[(u128, u128)],
+    depth: usize,
+) -> BookIndicators {
+    let bids = &bids[..bids.len().min(depth)];
+    let asks = &asks[..asks.len().min(depth)];
+    // Saturate rather than overflow: quantities are raw u128 amounts, and
+    // the ratios below only need their magnitude.
+    let depth_of = |levels: &[(u128, u128)]| {
+        levels
+            .iter()
+            .fold(0u128, |sum, &(_, q)| sum.saturating_add(q))
+    };
+    let bid_depth = depth_of(bids);
+    let ask_depth = depth_of(asks);
+    let total = bid_depth.saturating_add(ask_depth) as f64;
+
+    let imbalance = (total > 0.0).then(|| (bid_depth as f64 - ask_depth as f64) / total);
+
+    let (mid, weighted_mid, microprice) = match (bids.first(), asks.first()) {
+        (Some(&(bid, bid_qty)), Some(&(ask, ask_qty))) => {
+            let mid = (bid as f64 + ask as f64) / 2.0;
+            let notional: f64 = bids
+                .iter()
+                .chain(asks)
+                .map(|&(p, q)| p as f64 * q as f64)
+                .sum();
+            let weighted_mid = (total > 0.0).then(|| notional / total);
+            let top_qty = bid_qty.saturating_add(ask_qty) as f64;
+            let microprice = (top_qty > 0.0)
+                .then(|| (bid as f64 * ask_qty as f64 + ask as f64 * bid_qty as f64) / top_qty);
+            (Some(mid), weighted_mid, microprice)
+        }
+        _ => (None, None, None),
+    };
+
+    BookIndicators {
+        bid_depth,
+        ask_depth,
+        imbalance,
+        mid,
+        weighted_mid,
+        microprice,
+    }
+}
+
 /// Apply a slippage cap to a raw pair-decimal price.
 ///
 /// - `is_buy = true`: `reference * (10_000 + slippage_bps) / 10_000` — the
@@ -242,15 +532,15 @@ pub fn apply_slippage(reference_price: u128, slippage_bps: u32, is_buy: bool) ->
     let scale = if is_buy {
         10_000u128
             .checked_add(bps)
-            .ok_or_else(|| eyre::eyre!("slippage scale overflow"))?
+            .ok_or_else(|| AspensError::invalid_input("slippage scale overflow"))?
     } else {
         10_000u128
             .checked_sub(bps)
-            .ok_or_else(|| eyre::eyre!("slippage scale underflow"))?
+            .ok_or_else(|| AspensError::invalid_input("slippage scale underflow"))?
     };
     reference_price
         .checked_mul(scale)
-        .ok_or_else(|| eyre::eyre!("slippage * price overflow"))
+        .ok_or_else(|| AspensError::invalid_input("slippage * price overflow"))
         .map(|v| v / 10_000)
 }
 
@@ -272,7 +562,7 @@ pub async fn stream_orderbook_channel(
     mpsc::Receiver<OrderbookEntry>,
     tokio::task::JoinHandle<Result<()>>,
 )> {
-    let (tx, rx) = mpsc::channel(100);
+    let (tx, rx) = mpsc::channel(options.replay.buffer_size());
 
     let handle = tokio::spawn(async move {
         stream_orderbook_with(url, options, |entry| {
@@ -292,6 +582,37 @@ pub async fn stream_orderbook_channel(
     Ok((rx, handle))
 }
 
+/// Stream orderbook entries into a bounded buffer with an overflow policy.
+///
+/// Unlike [`stream_orderbook_channel`], a slow consumer doesn't have to stall the
+/// stream: with [`OverflowPolicy::DropOldest`] or
+/// [`OverflowPolicy::Coalesce`] the buffer sheds events instead, and
+/// [`BufferedReceiver::stats`] reports how many.
+///
+/// [`OverflowPolicy::DropOldest`]: super::buffer::OverflowPolicy::DropOldest
+/// [`OverflowPolicy::Coalesce`]: super::buffer::OverflowPolicy::Coalesce
+pub async fn stream_orderbook_buffered(
+    url: String,
+    options: StreamOrderbookOptions,
+    buffer_options: BufferOptions,
+) -> Result<(
+    BufferedReceiver<OrderbookEntry>,
+    tokio::task::JoinHandle<Result<()>>,
+)> {
+    let (tx, rx) = buffer::buffer(buffer_options);
+    let tx = Arc::new(tx);
+
+    let handle = tokio::spawn(async move {
+        stream_orderbook_with(url, options, |entry| {
+            let tx = tx.clone();
+            async move { tx.push(entry).await }
+        })
+        .await
+    });
+
+    Ok((rx, handle))
+}
+
 /// Format an orderbook entry for CLI display
 pub fn format_orderbook_entry(entry: &OrderbookEntry) -> String {
     let side_str = match Side::try_from(entry.side) {
@@ -353,6 +674,26 @@ fn truncate_address(address: &str) -> String {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn stream_reports_invalid_options_once_without_connecting() {
+        let options = StreamOrderbookOptions {
+            market_id: "m".into(),
+            historical_open_orders: true,
+            replay: HistoricalReplay::last(0),
+            ..Default::default()
+        };
+        // Nothing listens here; polling past the error would try to connect.
+        let mut stream = std::pin::pin!(stream_orderbook_stream(
+            "http://127.0.0.1:1".into(),
+            options
+        ));
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(AspensError::InvalidInput(_)))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+
     #[test]
     fn test_truncate_address() {
         assert_eq!(
@@ -422,4 +763,37 @@ mod tests {
         // u128::MAX * 1.0001 overflows the checked_mul step.
         assert!(apply_slippage(u128::MAX, 1, true).is_err());
     }
+
+    #[test]
+    fn book_indicators_on_a_two_sided_book() {
+        let bids = [(99, 30), (98, 10), (97, 1_000)];
+        let asks = [(101, 10), (102, 30)];
+        let ind = book_indicators(&bids, &asks, 2);
+        assert_eq!(ind.bid_depth, 40);
+        assert_eq!(ind.ask_depth, 40);
+        assert_eq!(ind.imbalance, Some(0.0));
+        assert_eq!(ind.mid, Some(100.0));
+        // (99*30 + 98*10 + 101*10 + 102*30) / 80 = 100.25
+        assert_eq!(ind.weighted_mid, Some(100.25));
+        // Heavy top bid leans the microprice towards the ask:
+        // (99*10 + 101*30) / 40 = 100.5
+        assert_eq!(ind.microprice, Some(100.5));
+    }
+
+    #[test]
+    fn book_indicators_on_a_one_sided_or_empty_book() {
+        let ind = book_indicators(&[(99, 5)], &[], 5);
+        assert_eq!(ind.imbalance, Some(1.0));
+        assert_eq!(ind.mid, None);
+        assert_eq!(ind.microprice, None);
+        assert_eq!(book_indicators(&[], &[], 5), BookIndicators::default());
+    }
+
+    #[test]
+    fn book_indicators_saturate_instead_of_overflowing() {
+        let ind = book_indicators(&[(99, u128::MAX), (98, 1)], &[(101, u128::MAX)], 5);
+        assert_eq!((ind.bid_depth, ind.ask_depth), (u128::MAX, u128::MAX));
+        assert_eq!(ind.imbalance, Some(0.0));
+        assert!(ind.microprice.is_some());
+    }
 }
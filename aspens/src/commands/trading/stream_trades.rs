@@ -5,14 +5,21 @@ pub mod arborter_pb {
 }
 
 use std::fmt;
+use std::sync::Arc;
 
 use arborter_pb::arborter_service_client::ArborterServiceClient;
 use arborter_pb::{Trade, TradeRequest, TradeRole};
-use eyre::Result;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use tokio::sync::mpsc;
 
-use crate::grpc::create_channel;
+use super::buffer::{self, BufferOptions, BufferedReceiver};
+use super::idle::{self, IdleOptions, Next};
+use super::reconnect::{self, ReconnectOptions, ResumePoint};
+use super::replay::HistoricalReplay;
+use crate::error::{AspensError, Result};
+use crate::grpc::{GrpcOptions, create_channel_with, impl_configurable_client};
+
+impl_configurable_client!(ArborterServiceClient<tonic::transport::Channel>);
 
 impl fmt::Display for Trade {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -43,6 +50,19 @@ pub struct StreamTradesOptions {
     pub historical_closed_trades: bool,
     /// If set, filter by a specific trader address
     pub filter_by_trader: Option<String>,
+    /// Message size / compression settings; `None` uses
+    /// [`GrpcOptions::historical`] when `historical_closed_trades` is set and the
+    /// defaults otherwise.
+    pub grpc: Option<GrpcOptions>,
+    /// Bounds on the historical replay (ignored unless `historical_closed_trades` is
+    /// set).
+    pub replay: HistoricalReplay,
+    /// Probe a silent stream and resubscribe if the stack doesn't answer;
+    /// `None` waits on a silent stream indefinitely.
+    pub idle: Option<IdleOptions>,
+    /// Reconnect a dropped stream with backoff, resuming from the last
+    /// trade delivered; `None` ends the stream with the error instead.
+    pub reconnect: Option<ReconnectOptions>,
 }
 
 /// Stream trades from the server.
@@ -65,42 +85,249 @@ pub async fn stream_trades<F>(
 where
     F: FnMut(Trade),
 {
-    // Create a channel to connect to the gRPC server
-    let channel = create_channel(&url).await?;
-
-    // Instantiate the client
-    let mut client = ArborterServiceClient::new(channel);
-
-    // Create the request
-    let request = TradeRequest {
-        continue_stream: true,
-        market_id: options.market_id,
-        historical_closed_trades: Some(options.historical_closed_trades),
-        filter_by_trader: options.filter_by_trader,
-    };
+    // Adapt the synchronous callback into the async-callback core by
+    // returning an already-ready future for each trade.
+    stream_trades_with(url, options, |trade| {
+        callback(trade);
+        std::future::ready(())
+    })
+    .await
+}
 
-    // Create a tonic request
-    let request = tonic::Request::new(request);
+/// Stream trades from the server as a [`Stream`].
+///
+/// Unlike the callback and channel variants, the caller drives the stream:
+/// it composes with `select!` and other combinators, nothing is read from
+/// the server while the caller isn't polling (backpressure), and dropping
+/// the stream closes the subscription. The stream ends when the server
+/// closes it; an error is yielded once and ends it too. With
+/// `options.reconnect` set, drops and closes reconnect instead (see
+/// [`reconnect`](super::reconnect)). The returned
+/// stream isn't [`Unpin`]; pin it (`std::pin::pin!` or `Box::pin`) before
+/// calling `next`.
+///
+/// Nothing connects until the stream is first polled.
+pub fn stream_trades_stream(
+    url: String,
+    options: StreamTradesOptions,
+) -> impl Stream<Item = Result<Trade>> + Send + 'static {
+    let state = StreamTradesState::new(url, options);
+    futures::stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        match state.next().await {
+            Some(Ok(trade)) => Some((Ok(trade), Some(state))),
+            // An error ends the stream.
+            Some(Err(e)) => Some((Err(e), None)),
+            None => None,
+        }
+    })
+}
 
-    // Call the trades streaming endpoint
-    let response = client.trades(request).await?;
+/// Where [`stream_trades_stream`] is between polls.
+struct StreamTradesState {
+    url: String,
+    grpc: GrpcOptions,
+    /// Sent again on every resubscribe.
+    request: TradeRequest,
+    replay: HistoricalReplay,
+    idle: Option<IdleOptions>,
+    reconnect: Option<ReconnectOptions>,
+    /// Where a resumed subscription picks up.
+    resume: ResumePoint<Trade>,
+    /// Invalid options, reported on the first poll.
+    invalid: Option<AspensError>,
+    live: Option<(tonic::Streaming<Trade>, tonic::transport::Channel)>,
+    resubscribes: u32,
+    /// Reconnect attempts since the last delivered trade.
+    failed: u32,
+}
 
-    // Get the streaming response
-    let mut stream = response.into_inner();
+impl StreamTradesState {
+    fn new(url: String, options: StreamTradesOptions) -> Self {
+        let invalid = options.replay.validate().err();
+        // Historical replays can exceed tonic's 4 MiB default message limit, so
+        // they get larger limits and compression.
+        let grpc = options.grpc.unwrap_or_else(|| {
+            if options.historical_closed_trades {
+                GrpcOptions::historical()
+            } else {
+                GrpcOptions::default()
+            }
+        });
+        let replay = options.replay;
+        let idle = options.idle;
+        let reconnect = options.reconnect;
+        let request = TradeRequest {
+            continue_stream: true,
+            market_id: options.market_id,
+            historical_closed_trades: Some(options.historical_closed_trades),
+            filter_by_trader: options.filter_by_trader,
+            historical_since: options.replay.since_ms,
+            historical_limit: options.replay.limit,
+            historical_batch_size: options.replay.batch_size,
+        };
+        Self {
+            url,
+            grpc,
+            request,
+            replay,
+            idle,
+            reconnect,
+            resume: ResumePoint::default(),
+            invalid,
+            live: None,
+            resubscribes: 0,
+            failed: 0,
+        }
+    }
 
-    // Process each trade from the stream
-    while let Some(trade_result) = stream.next().await {
-        match trade_result {
-            Ok(trade) => {
-                callback(trade);
+    /// The next trade, subscribing (again) as needed. `None` once the
+    /// server closes the stream and reconnecting is off.
+    async fn next(&mut self) -> Option<Result<Trade>> {
+        if let Some(e) = self.invalid.take() {
+            return Some(Err(e));
+        }
+        loop {
+            if self.live.is_none() {
+                match self.subscribe().await {
+                    Ok(live) => self.live = Some(live),
+                    Err(e) if e.is_retryable() && self.reconnect.is_some() => {
+                        if let Err(e) = self.reconnect(e).await {
+                            return Some(Err(e));
+                        }
+                        continue;
+                    }
+                    Err(e) => return Some(Err(e)),
+                }
             }
-            Err(e) => {
-                tracing::error!("Stream error: {}", e);
-                return Err(e.into());
+            let (stream, channel) = self.live.as_mut()?;
+            match idle::next_or_dead(stream, channel, self.idle.as_ref()).await {
+                Next::Item(None) if self.reconnect.is_some() => {
+                    let e = AspensError::connection(format!(
+                        "trades stream for {} closed by the stack",
+                        self.request.market_id
+                    ));
+                    if let Err(e) = self.reconnect(e).await {
+                        return Some(Err(e));
+                    }
+                }
+                Next::Item(None) => return None,
+                // Older servers ignore `historical_since`; hold the bound here.
+                Next::Item(Some(Ok(trade))) if !self.replay.admits(trade.timestamp) => {}
+                // Already delivered before a reconnect.
+                Next::Item(Some(Ok(trade))) if !self.resume.is_new(trade.timestamp, &trade) => {}
+                Next::Item(Some(Ok(trade))) => {
+                    self.failed = 0;
+                    if self.reconnect.is_some_and(|r| r.resume) {
+                        self.resume.record(trade.timestamp, &trade);
+                    }
+                    return Some(Ok(trade));
+                }
+                Next::Item(Some(Err(status)))
+                    if self.reconnect.is_some() && reconnect::is_drop(&status) =>
+                {
+                    if let Err(e) = self.reconnect(status.into()).await {
+                        return Some(Err(e));
+                    }
+                }
+                Next::Item(Some(Err(e))) => {
+                    tracing::error!("Stream error: {}", e);
+                    return Some(Err(e.into()));
+                }
+                Next::Dead => {
+                    self.live = None;
+                    // `next_or_dead` only reports dead streams when `idle` is set.
+                    let idle = self.idle.unwrap_or_default();
+                    if !idle.may_resubscribe(self.resubscribes) {
+                        return Some(Err(AspensError::connection(format!(
+                            "trades stream for {} went silent and the stack stopped answering; \
+                             gave up after {} resubscribes",
+                            self.request.market_id, self.resubscribes
+                        ))));
+                    }
+                    self.resubscribes += 1;
+                    self.resume_request();
+                    tracing::warn!(
+                        "trades stream for {} went silent and the stack didn't answer a probe; \
+                         resubscribing ({})",
+                        self.request.market_id,
+                        self.resubscribes
+                    );
+                }
             }
         }
     }
 
+    /// Wait out the backoff before the next attempt after the stream
+    /// dropped with `cause`, or give up once `reconnect.max_attempts` have
+    /// failed in a row.
+    async fn reconnect(&mut self, cause: AspensError) -> Result<()> {
+        self.live = None;
+        // Only called with `reconnect` set.
+        let options = self.reconnect.unwrap_or_default();
+        if !options.may_retry(self.failed) {
+            return Err(AspensError::connection(format!(
+                "trades stream for {} dropped and didn't come back after {} reconnect attempts: {}",
+                self.request.market_id, self.failed, cause
+            )));
+        }
+        let wait = options.backoff(self.failed);
+        self.failed += 1;
+        tracing::warn!(
+            "trades stream for {} dropped ({}); reconnecting in {:?} (attempt {})",
+            self.request.market_id,
+            cause,
+            wait,
+            self.failed
+        );
+        tokio::time::sleep(wait).await;
+        self.resume_request();
+        Ok(())
+    }
+
+    /// Ask the next subscription to replay from the last delivered trade,
+    /// if resuming is on and anything was delivered.
+    fn resume_request(&mut self) {
+        if !self.reconnect.is_some_and(|r| r.resume) {
+            return;
+        }
+        if let Some(since) = self.resume.resume() {
+            self.request.historical_closed_trades = Some(true);
+            self.request.historical_since = Some(since);
+            self.request.historical_limit = None;
+        }
+    }
+
+    async fn subscribe(&self) -> Result<(tonic::Streaming<Trade>, tonic::transport::Channel)> {
+        let channel = create_channel_with(&self.url, self.grpc.keepalive).await?;
+        let mut client = self.grpc.apply(ArborterServiceClient::new(channel.clone()));
+        let response = client
+            .trades(tonic::Request::new(self.request.clone()))
+            .await?;
+        Ok((response.into_inner(), channel))
+    }
+}
+
+/// Internal core: drive [`stream_trades_stream`], invoking an **async** callback
+/// per trade.
+///
+/// The sync-callback [`stream_trades`], the channel-based [`stream_trades_channel`] and
+/// the buffered variant delegate here. The async callback is what lets the
+/// channel variant `await` a send rather than block the runtime thread
+/// (`tx.blocking_send` panics inside a multi-thread tokio runtime).
+async fn stream_trades_with<F, Fut>(
+    url: String,
+    options: StreamTradesOptions,
+    mut callback: F,
+) -> Result<()>
+where
+    F: FnMut(Trade) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    let mut stream = std::pin::pin!(stream_trades_stream(url, options));
+    while let Some(trade) = stream.next().await {
+        callback(trade?).await;
+    }
     Ok(())
 }
 
@@ -119,12 +346,45 @@ pub async fn stream_trades_channel(
     url: String,
     options: StreamTradesOptions,
 ) -> Result<(mpsc::Receiver<Trade>, tokio::task::JoinHandle<Result<()>>)> {
-    let (tx, rx) = mpsc::channel(100);
+    let (tx, rx) = mpsc::channel(options.replay.buffer_size());
+
+    let handle = tokio::spawn(async move {
+        stream_trades_with(url, options, |trade| {
+            // Async send: `await`s backpressure instead of blocking the
+            // runtime thread. Clone the sender so the returned future owns it.
+            let tx = tx.clone();
+            async move {
+                // Ignore the error if the receiver has been dropped.
+                let _ = tx.send(trade).await;
+            }
+        })
+        .await
+    });
+
+    Ok((rx, handle))
+}
+
+/// Stream trades into a bounded buffer with an overflow policy.
+///
+/// Unlike [`stream_trades_channel`], a slow consumer doesn't have to stall the
+/// stream: with [`OverflowPolicy::DropOldest`] or
+/// [`OverflowPolicy::Coalesce`] the buffer sheds events instead, and
+/// [`BufferedReceiver::stats`] reports how many.
+///
+/// [`OverflowPolicy::DropOldest`]: super::buffer::OverflowPolicy::DropOldest
+/// [`OverflowPolicy::Coalesce`]: super::buffer::OverflowPolicy::Coalesce
+pub async fn stream_trades_buffered(
+    url: String,
+    options: StreamTradesOptions,
+    buffer_options: BufferOptions,
+) -> Result<(BufferedReceiver<Trade>, tokio::task::JoinHandle<Result<()>>)> {
+    let (tx, rx) = buffer::buffer(buffer_options);
+    let tx = Arc::new(tx);
 
     let handle = tokio::spawn(async move {
-        stream_trades(url, options, |trade| {
-            // Try to send, ignore if receiver is dropped
-            let _ = tx.blocking_send(trade);
+        stream_trades_with(url, options, |trade| {
+            let tx = tx.clone();
+            async move { tx.push(trade).await }
         })
         .await
     });
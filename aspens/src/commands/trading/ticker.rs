@@ -0,0 +1,222 @@
+//! Top-of-book ticker from the orderbook and trades streams.
+//!
+//! [`ticker`] subscribes to both streams of a market, keeps an
+//! [`OrderBook`] for the best bid and ask and remembers the last trade
+//! price, and yields a [`Ticker`] whenever one of those changes. Events
+//! that leave them as they were (a resting order deep in the book
+//! changing size, say) yield nothing.
+//!
+//! Both subscriptions reconnect with the [`ReconnectOptions`] defaults.
+//! The orderbook replays its open orders first, so the first tickers walk
+//! through the resting book as it's rebuilt; the trades stream asks for
+//! the latest trade only, so the last price is known before anything
+//! trades.
+
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt};
+
+use super::live_book::OrderBook;
+use super::reconnect::ReconnectOptions;
+use super::replay::HistoricalReplay;
+use super::stream_orderbook::arborter_pb::OrderbookEntry;
+use super::stream_orderbook::{StreamOrderbookOptions, stream_orderbook_stream};
+use super::stream_trades::arborter_pb::Trade;
+use super::stream_trades::{StreamTradesOptions, stream_trades_stream};
+use crate::decimals::format_decimal_amount;
+use crate::error::Result;
+
+/// Best bid, best ask and last trade of a market. Prices are raw pair
+/// decimals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ticker {
+    /// The market.
+    pub market_id: String,
+    /// Highest resting bid.
+    pub best_bid: Option<u128>,
+    /// Lowest resting ask.
+    pub best_ask: Option<u128>,
+    /// Best ask minus best bid, zero if the book is crossed; `None` unless
+    /// both sides quote.
+    pub spread: Option<u128>,
+    /// Price of the latest trade.
+    pub last_price: Option<u128>,
+    /// When the latest trade happened, unix milliseconds.
+    pub last_trade_ms: Option<u64>,
+}
+
+impl Ticker {
+    /// One line for a terminal, prices scaled by `pair_decimals`; `-` for
+    /// anything not known yet.
+    pub fn display(&self, pair_decimals: u32) -> String {
+        let scaled = |raw: Option<u128>| {
+            raw.map_or_else(
+                || "-".to_string(),
+                |raw| format_decimal_amount(raw, pair_decimals),
+            )
+        };
+        format!(
+            "{}  bid {}  ask {}  spread {}  last {}",
+            self.market_id,
+            scaled(self.best_bid),
+            scaled(self.best_ask),
+            scaled(self.spread),
+            scaled(self.last_price)
+        )
+    }
+}
+
+/// Stream the ticker of `market_id`.
+///
+/// Like [`stream_orderbook_stream`], nothing connects until the stream is
+/// first polled, an error from either subscription is yielded once and
+/// ends the stream, and the stream isn't [`Unpin`].
+pub fn ticker(
+    url: String,
+    market_id: String,
+) -> impl Stream<Item = Result<Ticker>> + Send + 'static {
+    let book = stream_orderbook_stream(
+        url.clone(),
+        StreamOrderbookOptions {
+            market_id: market_id.clone(),
+            historical_open_orders: true,
+            reconnect: Some(ReconnectOptions::default()),
+            ..Default::default()
+        },
+    );
+    let trades = stream_trades_stream(
+        url,
+        StreamTradesOptions {
+            market_id: market_id.clone(),
+            historical_closed_trades: true,
+            replay: HistoricalReplay::last(1),
+            reconnect: Some(ReconnectOptions::default()),
+            ..Default::default()
+        },
+    );
+    ticker_from_streams(market_id, book, trades)
+}
+
+/// Derive the ticker of `market_id` from any orderbook and trades streams,
+/// with the same rules as [`ticker`]. The ticker ends once both streams
+/// have.
+pub fn ticker_from_streams<B, T>(
+    market_id: String,
+    book: B,
+    trades: T,
+) -> impl Stream<Item = Result<Ticker>> + Send + 'static
+where
+    B: Stream<Item = Result<OrderbookEntry>> + Send + 'static,
+    T: Stream<Item = Result<Trade>> + Send + 'static,
+{
+    let events: Pin<Box<dyn Stream<Item = Event> + Send>> = Box::pin(futures::stream::select(
+        book.map(Event::Book),
+        trades.map(Event::Trade),
+    ));
+    let state = TickerState {
+        events,
+        book: OrderBook::new(market_id.clone()),
+        last: Ticker {
+            market_id,
+            ..Ticker::default()
+        },
+    };
+    futures::stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        match state.next().await {
+            Some(Ok(ticker)) => Some((Ok(ticker), Some(state))),
+            // An error ends the stream.
+            Some(Err(e)) => Some((Err(e), None)),
+            None => None,
+        }
+    })
+}
+
+/// An event from either subscription.
+enum Event {
+    Book(Result<OrderbookEntry>),
+    Trade(Result<Trade>),
+}
+
+/// Where [`ticker_from_streams`] is between polls.
+struct TickerState {
+    events: Pin<Box<dyn Stream<Item = Event> + Send>>,
+    book: OrderBook,
+    /// The ticker last yielded.
+    last: Ticker,
+}
+
+impl TickerState {
+    async fn next(&mut self) -> Option<Result<Ticker>> {
+        loop {
+            let mut ticker = self.last.clone();
+            match self.events.next().await? {
+                Event::Book(Ok(entry)) => self.book.apply(&entry),
+                Event::Trade(Ok(trade)) => {
+                    let Ok(price) = trade.price.parse() else {
+                        continue;
+                    };
+                    // A replayed trade older than the one already seen
+                    // doesn't move the last price.
+                    if ticker.last_trade_ms.is_some_and(|ms| trade.timestamp < ms) {
+                        continue;
+                    }
+                    ticker.last_price = Some(price);
+                    ticker.last_trade_ms = Some(trade.timestamp);
+                }
+                Event::Book(Err(e)) | Event::Trade(Err(e)) => return Some(Err(e)),
+            }
+            ticker.best_bid = self.book.best_bid().map(|l| l.price);
+            ticker.best_ask = self.book.best_ask().map(|l| l.price);
+            ticker.spread = self.book.spread();
+            if ticker != self.last {
+                self.last = ticker.clone();
+                return Some(Ok(ticker));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::trading::stream_orderbook::arborter_pb::Side;
+    use crate::fixtures::{book_entry, trade};
+
+    #[tokio::test]
+    async fn yields_only_when_the_top_changes() {
+        let book = futures::stream::iter([
+            Ok(book_entry(1, Side::Bid, "99", "5")),
+            Ok(book_entry(2, Side::Ask, "101", "3")),
+            // Behind the best bid: no new ticker.
+            Ok(book_entry(3, Side::Bid, "98", "1")),
+        ]);
+        let trades = futures::stream::iter([Ok(trade(1_000, "100", "1"))]);
+        let tickers: Vec<_> = ticker_from_streams("m".into(), book, trades)
+            .map(|t| t.unwrap())
+            .collect()
+            .await;
+
+        let last = tickers.last().unwrap();
+        assert_eq!(
+            (last.best_bid, last.best_ask, last.spread, last.last_price),
+            (Some(99), Some(101), Some(2), Some(100))
+        );
+        // Bid, ask and trade each moved the ticker once.
+        assert_eq!(tickers.len(), 3);
+    }
+
+    #[test]
+    fn display_marks_unknown_prices() {
+        let ticker = Ticker {
+            market_id: "m".into(),
+            best_bid: Some(9950),
+            last_price: Some(10000),
+            ..Ticker::default()
+        };
+        assert_eq!(
+            ticker.display(2),
+            "m  bid 99.50  ask -  spread -  last 100.00"
+        );
+    }
+}
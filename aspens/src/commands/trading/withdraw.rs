@@ -1,19 +1,24 @@
 use std::str::FromStr;
+use std::sync::Arc;
 
-use alloy::network::EthereumWallet;
-use alloy::primitives::{Address, Bytes, U256};
+use alloy::network::{EthereumWallet, TransactionBuilder, TxSigner};
+use alloy::primitives::{Address, Bytes, Signature, U256};
 use alloy::providers::{Provider, ProviderBuilder};
-use alloy::signers::Signer;
-use alloy::signers::local::PrivateKeySigner;
+use alloy::rpc::types::TransactionRequest;
 use alloy_chains::NamedChain;
-use eyre::Result;
 use url::Url;
 
+use crate::chain::tx::{
+    ConfirmationPolicy, ConfirmationTimeout, StuckPolicy, legacy_gas_price, reserve_nonce,
+    wait_for_confirmations,
+};
 use crate::chain_client::ARCH_SOLANA;
 use crate::commands::config::config_pb::GetConfigResponse;
-use crate::evm::rpc::MidribV3;
+use crate::error::{AspensError, Result};
+use crate::evm::rpc::{IERC20, MidribV3};
 use crate::grpc::create_channel;
-use crate::wallet::{CurveType, Wallet};
+use crate::signer::AspensSigner;
+use crate::wallet::CurveType;
 
 /// Generated protobuf bindings for the `arborter.v1` trading service.
 #[allow(missing_docs)]
@@ -26,6 +31,62 @@ use arborter_pb::arborter_service_client::ArborterServiceClient;
 /// Minimum gas balance required for transactions (0.0001 ETH = 100000 gwei)
 const MIN_GAS_BALANCE: u128 = 100_000_000_000_000; // 0.0001 ETH in wei
 
+/// The most the wallet at `owner` can withdraw of `token_symbol` from
+/// `network`, in base units, for `amount = max`: its available balance in
+/// the trade contract. On EVM chains, reservations for resting orders are
+/// held by the stack rather than the contract, so with orders open the
+/// stack may still refuse the full amount.
+pub async fn max_withdraw_amount(
+    network: String,
+    token_symbol: String,
+    owner: String,
+    config: GetConfigResponse,
+) -> Result<u128> {
+    let chain = config.get_chain(&network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    let token = config.get_token(&network, &token_symbol).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "Token '{}' not found on chain '{}'",
+            token_symbol, network
+        ))
+    })?;
+
+    let amount = if chain.architecture.eq_ignore_ascii_case(ARCH_SOLANA) {
+        solana_available_balance(chain, &token.address, &owner).await?
+    } else {
+        let contract = chain
+            .trade_contract
+            .as_ref()
+            .filter(|tc| !tc.address.is_empty())
+            .ok_or_else(|| {
+                AspensError::not_found(format!("Trade contract not found for chain '{}'", network))
+            })?;
+        let contract_addr = crate::util::parse_address(&contract.address)?;
+        let token_addr = crate::util::parse_address(&token.address)?;
+        let owner_addr = crate::util::parse_address(&owner)?;
+        let provider = ProviderBuilder::new().connect_http(Url::parse(&chain.rpc_url)?);
+        let balance = MidribV3::new(contract_addr, &provider)
+            .tradeBalance(owner_addr, token_addr)
+            .call()
+            .await?;
+        balance.try_into().unwrap_or(u128::MAX)
+    };
+    if amount == 0 {
+        return Err(AspensError::insufficient_balance(format!(
+            "{} has no {} available to withdraw on '{}'",
+            owner, token_symbol, network
+        )));
+    }
+    tracing::info!(
+        "Maximum withdrawal of {} on {}: {} base units",
+        token_symbol,
+        network,
+        amount
+    );
+    Ok(amount)
+}
+
 /// Withdraw tokens using a curve-agnostic wallet.
 ///
 /// Branches on `chain.architecture`:
@@ -40,7 +101,7 @@ pub async fn call_withdraw_from_config_with_wallet(
     network: String,
     token_symbol: String,
     amount: u128,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     config: GetConfigResponse,
 ) -> Result<()> {
     call_withdraw_from_config_with_wallet_opts(
@@ -64,12 +125,24 @@ pub struct WithdrawOpts {
     /// rent back to SOL — standard wallet behavior. Set `false` to keep the
     /// withdrawn funds as WSOL (e.g. when deliberately holding wrapped SOL).
     pub unwrap_native: bool,
+    /// EVM only: where the withdrawn funds end up, if not the wallet. A
+    /// voucher always pays the account that requested it, so the funds are
+    /// forwarded from the wallet in a second transaction once the withdraw
+    /// lands. The address is checked before the voucher is requested.
+    pub to: Option<String>,
+    /// EVM only: confirmations the withdraw (and the forwarding transfer)
+    /// waits for before returning. Use
+    /// [`AspensClient::confirmation_policy`](crate::AspensClient::confirmation_policy)
+    /// to follow the client's setting.
+    pub confirmations: ConfirmationPolicy,
 }
 
 impl Default for WithdrawOpts {
     fn default() -> Self {
         Self {
             unwrap_native: true,
+            to: None,
+            confirmations: ConfirmationPolicy::default(),
         }
     }
 }
@@ -80,22 +153,30 @@ pub async fn call_withdraw_from_config_with_wallet_opts(
     network: String,
     token_symbol: String,
     amount: u128,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     config: GetConfigResponse,
     opts: WithdrawOpts,
 ) -> Result<()> {
-    let chain_for_arch = config
-        .get_chain(&network)
-        .ok_or_else(|| eyre::eyre!("Chain '{}' not found in configuration", network))?;
+    let chain_for_arch = config.get_chain(&network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
 
     if chain_for_arch
         .architecture
         .eq_ignore_ascii_case(ARCH_SOLANA)
     {
+        if opts.to.is_some() {
+            return Err(AspensError::invalid_input(format!(
+                "withdrawing to another address is EVM-only; '{}' is a Solana chain",
+                network
+            )));
+        }
         // Solana SPL token amounts are natively u64 — downcast (checked) at the
         // boundary (DEC-1: u128 upstream, u64 on Solana).
         let spl_amount: u64 = amount.try_into().map_err(|_| {
-            eyre::eyre!("amount {amount} exceeds the SPL token u64 max on Solana chain '{network}'")
+            AspensError::invalid_input(format!(
+                "amount {amount} exceeds the SPL token u64 max on Solana chain '{network}'"
+            ))
         })?;
         return solana_withdraw(
             url,
@@ -109,18 +190,171 @@ pub async fn call_withdraw_from_config_with_wallet_opts(
     }
 
     if wallet.curve() != CurveType::Secp256k1 {
-        return Err(eyre::eyre!(
+        return Err(AspensError::invalid_input(format!(
             "EVM chain '{}' requires a secp256k1 wallet, got {:?}",
             network,
             wallet.curve()
-        ));
+        )));
     }
-    let signer = wallet
-        .as_evm()
-        .ok_or_else(|| eyre::eyre!("expected EVM wallet for chain '{}'", network))?
-        .clone();
+    let signer = wallet.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(format!(
+            "withdrawing on chain '{}' needs a wallet that signs transactions \
+             (a local key or a remote signer)",
+            network
+        ))
+    })?;
+
+    // Check the destination before the voucher places a hold on the funds.
+    let to = match opts.to.as_deref() {
+        Some(to) => {
+            let to = crate::util::parse_address(to)?;
+            check_destination(&config, &network, &token_symbol, to).await?;
+            Some(to).filter(|to| *to != signer.address())
+        }
+        None => None,
+    };
 
-    call_withdraw_from_config_evm(url, network, token_symbol, amount, signer, config).await
+    call_withdraw_from_config_evm(
+        url,
+        network.clone(),
+        token_symbol.clone(),
+        amount,
+        wallet,
+        config.clone(),
+        opts.confirmations,
+    )
+    .await?;
+
+    if let Some(to) = to {
+        let tx_hash = forward_withdrawal(
+            &config,
+            &network,
+            &token_symbol,
+            amount,
+            to,
+            signer,
+            opts.confirmations,
+        )
+        .await?;
+        tracing::info!(
+            "Forwarded {} {} to {} in {}",
+            amount,
+            token_symbol,
+            to,
+            tx_hash
+        );
+    }
+    Ok(())
+}
+
+/// Fail if `to` can't receive `token_symbol` on `network`: on Hedera it
+/// must be associated with the token.
+async fn check_destination(
+    config: &GetConfigResponse,
+    network: &str,
+    token_symbol: &str,
+    to: Address,
+) -> Result<()> {
+    let (Some(chain), Some(token)) = (
+        config.get_chain(network),
+        config.get_token(network, token_symbol),
+    ) else {
+        // The withdraw itself reports the missing chain or token.
+        return Ok(());
+    };
+    if !super::hedera::is_hedera(chain) || crate::evm::is_native_token(&token.address) {
+        return Ok(());
+    }
+    let token_addr = super::hedera::token_address(token)?;
+    if !super::hedera::is_associated(&chain.rpc_url, token_addr, to).await? {
+        return Err(AspensError::invalid_input(format!(
+            "{} is not associated with {} on Hedera network '{}', so it can't receive it",
+            to, token_symbol, network
+        )));
+    }
+    Ok(())
+}
+
+/// Send `amount` base units of `token_symbol` from the signer's wallet to
+/// `to`, the second leg of a withdrawal to another address. Returns the
+/// transaction hash.
+async fn forward_withdrawal(
+    config: &GetConfigResponse,
+    network: &str,
+    token_symbol: &str,
+    amount: u128,
+    to: Address,
+    signer: Arc<dyn TxSigner<Signature> + Send + Sync>,
+    confirmations: ConfirmationPolicy,
+) -> Result<String> {
+    let chain = config.get_chain(network).ok_or_else(|| {
+        AspensError::not_found(format!("Chain '{}' not found in configuration", network))
+    })?;
+    let token = config.get_token(network, token_symbol).ok_or_else(|| {
+        AspensError::not_found(format!(
+            "Token '{}' not found on chain '{}'",
+            token_symbol, network
+        ))
+    })?;
+    let from = signer.address();
+    let provider = ProviderBuilder::new()
+        .with_chain_id(chain.chain_id as u64)
+        .wallet(EthereumWallet::new(signer))
+        .connect_http(Url::parse(&chain.rpc_url)?);
+    let gas_price = legacy_gas_price(&provider, network, chain.chain_id.into()).await?;
+    let nonce = reserve_nonce(&provider, chain.chain_id.into(), from).await?;
+    let send_err = |e: String| {
+        AspensError::chain(format!(
+            "withdrew {} {} but failed to forward it to {}: {}",
+            amount, token_symbol, to, e
+        ))
+    };
+    let receipt = if crate::evm::is_native_token(&token.address) {
+        let mut tx = TransactionRequest::default()
+            .with_to(to)
+            .with_value(U256::from(amount))
+            .with_nonce(nonce.nonce());
+        if let Some(price) = gas_price {
+            tx.set_gas_price(price);
+        }
+        let pending = provider
+            .send_transaction(tx)
+            .await
+            .map_err(|e| send_err(e.to_string()))?;
+        nonce.commit();
+        wait_for_confirmations(
+            &provider,
+            *pending.tx_hash(),
+            StuckPolicy::from_env(),
+            confirmations,
+        )
+        .await?
+    } else {
+        let contract = IERC20::new(super::hedera::token_address(token)?, &provider);
+        let transfer = contract
+            .transfer(to, U256::from(amount))
+            .nonce(nonce.nonce());
+        let transfer = match gas_price {
+            Some(price) => transfer.gas_price(price),
+            None => transfer,
+        };
+        let pending = transfer.send().await.map_err(|e| send_err(e.to_string()))?;
+        nonce.commit();
+        wait_for_confirmations(
+            &provider,
+            *pending.tx_hash(),
+            StuckPolicy::from_env(),
+            confirmations,
+        )
+        .await?
+    };
+    if !receipt.status() {
+        return Err(AspensError::chain(format!(
+            "withdrew {} {} but forwarding it to {} reverted in {}",
+            amount, token_symbol, to, receipt.transaction_hash
+        )));
+    }
+    Ok(format!("{:?}", receipt.transaction_hash))
 }
 
 /// Solana withdraw — builds and submits the user-signed Midrib `withdraw`
@@ -131,30 +365,30 @@ async fn solana_withdraw(
     chain: &crate::commands::config::config_pb::Chain,
     token_symbol: &str,
     amount: u64,
-    wallet: &Wallet,
+    wallet: &dyn AspensSigner,
     unwrap_native: bool,
 ) -> Result<()> {
     use solana_sdk::pubkey::Pubkey;
     use std::str::FromStr;
 
     let token = chain.tokens.get(token_symbol).ok_or_else(|| {
-        eyre::eyre!(
+        AspensError::not_found(format!(
             "Token '{}' not found on Solana chain '{}'",
-            token_symbol,
-            chain.network
-        )
+            token_symbol, chain.network
+        ))
     })?;
     let keypair = wallet.as_solana().ok_or_else(|| {
-        eyre::eyre!(
+        AspensError::invalid_input(format!(
             "Solana chain '{}' requires an Ed25519 wallet (TRADER_PRIVKEY_SOLANA)",
             chain.network
-        )
+        ))
     })?;
 
     let (program_id, instance) = crate::solana::client::resolve_program_and_instance(chain)?;
     let user = solana_sdk::signer::Signer::pubkey(keypair);
-    let mint = Pubkey::from_str(&token.address)
-        .map_err(|e| eyre::eyre!("invalid Solana mint '{}': {}", token.address, e))?;
+    let mint = Pubkey::from_str(&token.address).map_err(|e| {
+        AspensError::invalid_input(format!("invalid Solana mint '{}': {}", token.address, e))
+    })?;
     let user_ata = crate::solana::derive_associated_token_account(&user, &mint);
 
     // 0) Pre-flight SOL check BEFORE requesting a voucher. A voucher places an
@@ -169,11 +403,11 @@ async fn solana_withdraw(
         let rpc = RpcClient::new(chain.rpc_url.clone());
         let lamports = rpc.get_balance(&user).await.unwrap_or(0);
         if lamports < MIN_SOL_LAMPORTS {
-            return Err(eyre::eyre!(
+            return Err(AspensError::insufficient_balance(format!(
                 "insufficient SOL for fees: wallet {user} has {lamports} lamports, \
                  need >= {MIN_SOL_LAMPORTS}. Fund/airdrop SOL before withdrawing — \
                  requesting a voucher now would place an off-chain hold you can't submit.",
-            ));
+            )));
         }
     }
 
@@ -214,22 +448,27 @@ async fn solana_withdraw(
         .chain_keys
         .get(&chain.network)
         .map(|k| k.public_key.clone())
-        .ok_or_else(|| eyre::eyre!("no signer public key for chain '{}'", chain.network))?;
-    let signer_pk = Pubkey::from_str(&signer_str)
-        .map_err(|e| eyre::eyre!("invalid signer pubkey '{}': {}", signer_str, e))?;
+        .ok_or_else(|| {
+            AspensError::not_found(format!(
+                "no signer public key for chain '{}'",
+                chain.network
+            ))
+        })?;
+    let signer_pk = Pubkey::from_str(&signer_str).map_err(|e| {
+        AspensError::protocol(format!("invalid signer pubkey '{}': {}", signer_str, e))
+    })?;
 
     // 4) Rebuild the exact signed payload + the voucher signature.
     let deadline = voucher.expiry; // Solana on-chain deadline is a slot
     let nonce = voucher.nonce;
-    let voucher_amount: u64 = voucher
-        .amount
-        .parse()
-        .map_err(|_| eyre::eyre!("invalid voucher amount '{}'", voucher.amount))?;
+    let voucher_amount: u64 = voucher.amount.parse().map_err(|_| {
+        AspensError::protocol(format!("invalid voucher amount '{}'", voucher.amount))
+    })?;
     let voucher_sig: [u8; 64] = voucher
         .signature
         .as_slice()
         .try_into()
-        .map_err(|_| eyre::eyre!("voucher signature must be 64 bytes (Ed25519)"))?;
+        .map_err(|_| AspensError::protocol("voucher signature must be 64 bytes (Ed25519)"))?;
     let msg = crate::solana::withdrawal_voucher_signing_message(
         &instance,
         &user,
@@ -304,12 +543,15 @@ async fn solana_withdraw(
                     last_err = Some(e);
                     continue;
                 }
-                return Err(e);
+                return Err(e.into());
             }
         }
     }
     let sig = sig.ok_or_else(|| {
-        last_err.unwrap_or_else(|| eyre::eyre!("voucher submit failed after retries"))
+        last_err.map_or_else(
+            || AspensError::chain("voucher submit failed after retries"),
+            AspensError::from,
+        )
     })?;
     tracing::info!("Solana withdraw voucher submitted: {}", sig);
     Ok(())
@@ -330,19 +572,58 @@ const VOUCHER_SUBMIT_MAX_ATTEMPTS: usize = 6;
 #[cfg(feature = "solana")]
 const VOUCHER_SUBMIT_RETRY_MS: u64 = 700;
 
+/// Deposited less locked in the wallet's Solana `UserBalance` account.
+#[cfg(feature = "solana")]
+async fn solana_available_balance(
+    chain: &crate::commands::config::config_pb::Chain,
+    mint: &str,
+    owner: &str,
+) -> Result<u128> {
+    use solana_sdk::pubkey::Pubkey;
+
+    let (program_id, instance) = crate::solana::client::resolve_program_and_instance(chain)?;
+    let user = Pubkey::from_str(owner).map_err(|e| {
+        AspensError::invalid_input(format!("invalid Solana address '{}': {}", owner, e))
+    })?;
+    let mint = Pubkey::from_str(mint).map_err(|e| {
+        AspensError::invalid_input(format!("invalid Solana mint '{}': {}", mint, e))
+    })?;
+    let (deposited, locked) = crate::solana::client::fetch_user_balance(
+        &chain.rpc_url,
+        &instance,
+        &user,
+        &mint,
+        &program_id,
+    )
+    .await?;
+    Ok(deposited.saturating_sub(locked).into())
+}
+
+#[cfg(not(feature = "solana"))]
+async fn solana_available_balance(
+    chain: &crate::commands::config::config_pb::Chain,
+    _mint: &str,
+    _owner: &str,
+) -> Result<u128> {
+    Err(AspensError::invalid_input(format!(
+        "chain '{}' is Solana but the `solana` feature is disabled",
+        chain.network
+    )))
+}
+
 #[cfg(not(feature = "solana"))]
 async fn solana_withdraw(
     _url: String,
     chain: &crate::commands::config::config_pb::Chain,
     _token_symbol: &str,
     _amount: u64,
-    _wallet: &Wallet,
+    _wallet: &dyn AspensSigner,
     _unwrap_native: bool,
 ) -> Result<()> {
-    Err(eyre::eyre!(
+    Err(AspensError::invalid_input(format!(
         "chain '{}' is Solana but the `solana` feature is disabled",
         chain.network
-    ))
+    )))
 }
 
 /// EVM withdraw via the TEE voucher flow (Track A §8): authenticate the request,
@@ -352,9 +633,16 @@ async fn call_withdraw_from_config_evm(
     network: String,
     token_symbol: String,
     amount: u128,
-    signer: PrivateKeySigner,
+    wallet: &dyn AspensSigner,
     config: GetConfigResponse,
+    confirmations: ConfirmationPolicy,
 ) -> Result<()> {
+    let signer = wallet.tx_signer().ok_or_else(|| {
+        AspensError::invalid_input(format!(
+            "withdrawing on chain '{}' needs a wallet that signs transactions",
+            network
+        ))
+    })?;
     // Look up chain info
     let chain = config.get_chain(&network).ok_or_else(|| {
         let available_chains = config
@@ -368,11 +656,10 @@ async fn call_withdraw_from_config_evm(
                     .join(", ")
             })
             .unwrap_or_default();
-        eyre::eyre!(
+        AspensError::not_found(format!(
             "Chain '{}' not found in configuration. Available chains: {}",
-            network,
-            available_chains
-        )
+            network, available_chains
+        ))
     })?;
 
     // Look up token info
@@ -383,12 +670,10 @@ async fn call_withdraw_from_config_evm(
             .map(|s| s.as_str())
             .collect::<Vec<_>>()
             .join(", ");
-        eyre::eyre!(
+        AspensError::not_found(format!(
             "Token '{}' not found on chain '{}'. Available tokens: {}",
-            token_symbol,
-            network,
-            available_tokens
-        )
+            token_symbol, network, available_tokens
+        ))
     })?;
 
     // Get trade contract address
@@ -396,10 +681,10 @@ async fn call_withdraw_from_config_evm(
         .trade_contract
         .as_ref()
         .ok_or_else(|| {
-            eyre::eyre!(
+            AspensError::not_found(format!(
                 "Trade contract not found for chain '{}'. Please ensure the contract is deployed.",
                 network
-            )
+            ))
         })?
         .address
         .clone();
@@ -420,7 +705,7 @@ async fn call_withdraw_from_config_evm(
                 "Unknown chain ID {}, using chain ID directly",
                 chain.chain_id
             );
-            NamedChain::try_from(chain.chain_id as u64)?
+            NamedChain::try_from(chain.chain_id as u64).map_err(AspensError::invalid_input)?
         }
     };
 
@@ -433,18 +718,22 @@ async fn call_withdraw_from_config_evm(
         chain.rpc_url
     );
 
-    let contract_addr: Address = contract_address.parse()?;
-    let token_addr: Address = token.address.parse()?;
+    let contract_addr = crate::util::parse_address(&contract_address)?;
+    let token_addr = crate::util::parse_address(&token.address)?;
     let signer_address = signer.address();
+    // On Hedera the wallet must be associated to receive the token; check
+    // before the voucher places a hold on the funds.
+    super::hedera::ensure_associated(chain, token, signer_address).await?;
 
     // Build the wallet-enabled provider up front so the gas pre-check and the
     // submit share it.
-    let wallet = EthereumWallet::new(signer.clone());
     let rpc_url = Url::parse(&chain.rpc_url)?;
     let provider = ProviderBuilder::new()
         .with_chain(chain_type)
-        .wallet(wallet)
+        .wallet(EthereumWallet::new(signer))
         .connect_http(rpc_url);
+    // Chains without EIP-1559 are sent legacy transactions at this price.
+    let gas_price = legacy_gas_price(&provider, &network, chain.chain_id.into()).await?;
 
     // 1) Pre-flight gas check BEFORE requesting a voucher. A voucher places an
     //    off-chain withdraw HOLD on the funds (reserved until the voucher lands
@@ -455,14 +744,12 @@ async fn call_withdraw_from_config_evm(
     tracing::info!("Gas balance: {} wei", gas_balance);
     if gas_balance < U256::from(MIN_GAS_BALANCE) {
         let balance_eth = gas_balance.to::<u128>() as f64 / 1e18;
-        return Err(eyre::eyre!(
+        return Err(AspensError::insufficient_balance(format!(
             "insufficient gas: wallet has {:.6} native tokens, need at least 0.0001 for gas. \
             Fund your wallet ({}) with native tokens on {} to pay for transaction fees. \
             (No voucher requested — your withdrawable balance is untouched.)",
-            balance_eth,
-            signer_address,
-            network
-        ));
+            balance_eth, signer_address, network
+        )));
     }
 
     // 2) Request a TEE-signed voucher from the arborter. Authenticate the
@@ -473,7 +760,7 @@ async fn call_withdraw_from_config_evm(
     let req_token = token.address.clone();
     let req_amount = amount.to_string();
     let canonical = format!("{network}|{req_token}|{req_account}|{req_amount}");
-    let req_sig = signer.sign_message(canonical.as_bytes()).await?;
+    let req_sig = wallet.sign_message(canonical.as_bytes()).await?;
 
     let channel = create_channel(&url).await?;
     let mut client = ArborterServiceClient::new(channel);
@@ -483,7 +770,7 @@ async fn call_withdraw_from_config_evm(
             token: req_token,
             account: req_account,
             amount: req_amount,
-            signature: req_sig.as_bytes().to_vec(),
+            signature: req_sig,
         }))
         .await?
         .into_inner();
@@ -514,23 +801,41 @@ async fn call_withdraw_from_config_evm(
     for attempt in 0..EVM_VOUCHER_SUBMIT_MAX_ATTEMPTS {
         // `send` and `watch` surface distinct alloy error types, so match each
         // rather than `?`-unify them; flatten both into the retry's last_err.
-        let outcome = match contract
-            .withdraw(onchain_voucher.clone(), voucher_sig.clone())
-            .send()
-            .await
-        {
-            Ok(pending) => pending
-                .with_required_confirmations(1)
-                .watch()
-                .await
-                .map_err(|e| eyre::eyre!("{e}")),
-            Err(e) => Err(eyre::eyre!("{e}")),
+        // A send that fails gives its nonce back, so each attempt reserves
+        // afresh.
+        let outcome = match reserve_nonce(&provider, chain.chain_id.into(), signer_address).await {
+            Ok(nonce) => {
+                let call = contract
+                    .withdraw(onchain_voucher.clone(), voucher_sig.clone())
+                    .nonce(nonce.nonce());
+                let call = match gas_price {
+                    Some(price) => call.gas_price(price),
+                    None => call,
+                };
+                match call.send().await {
+                    Ok(pending) => {
+                        nonce.commit();
+                        wait_for_confirmations(
+                            &provider,
+                            *pending.tx_hash(),
+                            StuckPolicy::from_env(),
+                            confirmations,
+                        )
+                        .await
+                        .map(|receipt| receipt.transaction_hash)
+                    }
+                    Err(e) => Err(AspensError::from(e)),
+                }
+            }
+            Err(e) => Err(e),
         };
         match outcome {
             Ok(tx) => {
                 result = Some(tx);
                 break;
             }
+            // The voucher went out; sending it again would only fail.
+            Err(e) if ConfirmationTimeout::of(&e).is_some() => return Err(e),
             Err(e) => {
                 last_err = Some(e);
                 if attempt + 1 < EVM_VOUCHER_SUBMIT_MAX_ATTEMPTS {
@@ -548,7 +853,7 @@ async fn call_withdraw_from_config_evm(
         }
     }
     let result = result.ok_or_else(|| {
-        last_err.unwrap_or_else(|| eyre::eyre!("voucher submit failed after retries"))
+        last_err.unwrap_or_else(|| AspensError::chain("voucher submit failed after retries"))
     })?;
 
     tracing::info!("Withdraw voucher submitted on-chain: {result:?}");
@@ -0,0 +1,142 @@
+//! The last configuration fetched from each stack, kept on disk.
+//!
+//! `aspens-cli` saves every config it fetches here, and its shell
+//! completion reads it back to offer market names, networks and token
+//! symbols without a round trip to the stack on each keypress. The files
+//! live under [`cache_dir`](crate::storage::cache_dir) in
+//! [`CONFIG_CACHE_DIR`], one per stack URL; deleting them only costs a
+//! fetch.
+
+use std::path::{Path, PathBuf};
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::storage::cache_dir;
+use crate::util::{read_profile_file, unix_now, write_profile_file};
+
+/// Subdirectory of the cache directory holding the cached configs.
+pub const CONFIG_CACHE_DIR: &str = "configs";
+
+/// A cached configuration and when it was fetched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedConfig {
+    /// The stack it came from.
+    pub stack_url: String,
+    /// Unix timestamp (seconds) of the fetch.
+    pub fetched_at: u64,
+    /// The configuration.
+    pub config: GetConfigResponse,
+}
+
+/// Save `config` as the latest fetched from `stack_url`.
+pub fn save(stack_url: &str, config: &GetConfigResponse) -> Result<()> {
+    save_to(&cache_file(stack_url)?, stack_url, config)
+}
+
+/// The config last saved for `stack_url`, if any.
+pub fn load(stack_url: &str) -> Result<Option<CachedConfig>> {
+    load_from(&cache_file(stack_url)?)
+}
+
+/// The most recently fetched config of any stack, for when the stack in
+/// use isn't known yet (e.g. during shell completion, before the env file
+/// is read).
+pub fn load_latest() -> Result<Option<CachedConfig>> {
+    let dir = cache_dir()?.join(CONFIG_CACHE_DIR);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(eyre!("failed to read {}: {}", dir.display(), e)),
+    };
+    let mut latest: Option<CachedConfig> = None;
+    for entry in entries.flatten() {
+        // A file that no longer parses is skipped, not fatal: it's a cache.
+        if let Ok(Some(cached)) = load_from(&entry.path())
+            && latest
+                .as_ref()
+                .is_none_or(|l| cached.fetched_at > l.fetched_at)
+        {
+            latest = Some(cached);
+        }
+    }
+    Ok(latest)
+}
+
+/// [`save`] to `path`.
+pub fn save_to(path: &Path, stack_url: &str, config: &GetConfigResponse) -> Result<()> {
+    let cached = CachedConfig {
+        stack_url: stack_url.trim_end_matches('/').to_string(),
+        fetched_at: unix_now(),
+        config: config.clone(),
+    };
+    write_profile_file(path, &crate::json::to_json_line(&cached)?)
+}
+
+/// [`load`] from `path`.
+pub fn load_from(path: &Path) -> Result<Option<CachedConfig>> {
+    match read_profile_file(path)? {
+        Some(contents) => crate::json::from_json(&contents)
+            .map(Some)
+            .map_err(|e| eyre!("invalid cached config {}: {}", path.display(), e)),
+        None => Ok(None),
+    }
+}
+
+/// The cache file for `stack_url`: the URL with everything but letters,
+/// digits, `.` and `-` replaced by `_`, so `http://localhost:50051` and
+/// `http://localhost:50051/` share one.
+fn cache_file(stack_url: &str) -> Result<PathBuf> {
+    let name: String = stack_url
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Ok(cache_dir()?
+        .join(CONFIG_CACHE_DIR)
+        .join(format!("{}.json", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::config::config_pb::{Configuration, Market};
+
+    #[test]
+    fn cached_config_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_CACHE_DIR).join("stack.json");
+        assert_eq!(load_from(&path).unwrap(), None);
+
+        let config = GetConfigResponse {
+            config: Some(Configuration {
+                markets: vec![Market {
+                    name: "ETH-USDC".into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+        };
+        save_to(&path, "http://localhost:50051/", &config).unwrap();
+        let cached = load_from(&path).unwrap().unwrap();
+        assert_eq!(cached.stack_url, "http://localhost:50051");
+        assert_eq!(cached.config, config);
+        assert!(cached.fetched_at > 0);
+    }
+
+    #[test]
+    fn stack_urls_map_to_safe_file_names() {
+        let file = cache_file("https://stack.example.com:50051/").unwrap();
+        assert_eq!(
+            file.file_name().unwrap(),
+            "https___stack.example.com_50051.json"
+        );
+    }
+}
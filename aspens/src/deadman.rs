@@ -0,0 +1,427 @@
+//! Dead-man's switch: cancel resting orders when the bot loses the stack.
+//!
+//! An unattended market maker that loses its connection keeps quoting
+//! stale prices from the book's point of view. A [`DeadMansSwitch`] tracks
+//! the orders the bot has resting, runs a heartbeat against the stack, and
+//! once the heartbeat has failed for longer than
+//! [`DeadMansSwitchConfig::disconnect_timeout`] it trips: every tracked
+//! order is cancelled, retrying on each heartbeat until the cancels go
+//! through. [`DeadMansSwitch::supervise`] trips the same way when the bot's
+//! own task panics.
+//!
+//! The arborter has no cancel-all RPC, so the switch cancels order by
+//! order: those registered with [`DeadMansSwitch::track`], plus, on markets
+//! registered with [`DeadMansSwitch::sweep`], every order the wallet has
+//! resting there as listed by [`AspensApi::open_orders`] — including ones
+//! the bot never tracked, such as orders placed before a restart.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use eyre::{Result, eyre};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::api::AspensApi;
+use crate::health::ping_grpc_server_with_timeout;
+use crate::wallet::Wallet;
+
+/// Heartbeat timing for a [`DeadMansSwitch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadMansSwitchConfig {
+    /// How often the heartbeat probes the stack.
+    pub heartbeat_interval: Duration,
+    /// How long the heartbeat may fail before the switch trips.
+    pub disconnect_timeout: Duration,
+}
+
+impl Default for DeadMansSwitchConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(5),
+            disconnect_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A resting order the switch will cancel when it trips.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedOrder {
+    /// Market the order rests on.
+    pub market_id: String,
+    /// `buy` or `sell`.
+    pub side: String,
+    /// Order ID returned by `send_order`.
+    pub order_id: u64,
+}
+
+struct Inner<A> {
+    api: A,
+    wallet: Wallet,
+    config: DeadMansSwitchConfig,
+    orders: Mutex<Vec<TrackedOrder>>,
+    swept_markets: Mutex<Vec<String>>,
+    sweep_pending: AtomicBool,
+    tripped: AtomicBool,
+}
+
+/// Cancels the tracked orders through `A` when the heartbeat is lost or a
+/// supervised task panics.
+///
+/// Cheap to clone; clones share the tracked orders and trip state.
+pub struct DeadMansSwitch<A> {
+    inner: Arc<Inner<A>>,
+}
+
+impl<A> Clone for DeadMansSwitch<A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<A: AspensApi + 'static> DeadMansSwitch<A> {
+    /// Create a switch that cancels through `api`, signing with `wallet`.
+    pub fn new(api: A, wallet: Wallet, config: DeadMansSwitchConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                api,
+                wallet,
+                config,
+                orders: Mutex::new(Vec::new()),
+                swept_markets: Mutex::new(Vec::new()),
+                sweep_pending: AtomicBool::new(false),
+                tripped: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// The API the switch cancels through.
+    pub fn api(&self) -> &A {
+        &self.inner.api
+    }
+
+    /// Register a resting order to cancel if the switch trips.
+    pub fn track(&self, market_id: impl Into<String>, side: impl Into<String>, order_id: u64) {
+        self.orders().push(TrackedOrder {
+            market_id: market_id.into(),
+            side: side.into(),
+            order_id,
+        });
+    }
+
+    /// Forget an order that filled or was cancelled normally.
+    pub fn untrack(&self, order_id: u64) {
+        self.orders().retain(|o| o.order_id != order_id);
+    }
+
+    /// Orders currently tracked.
+    pub fn tracked(&self) -> Vec<TrackedOrder> {
+        self.orders().clone()
+    }
+
+    /// Also cancel every order the wallet has resting on `market_id` when
+    /// the switch trips, tracked or not.
+    pub fn sweep(&self, market_id: impl Into<String>) {
+        let market_id = market_id.into();
+        let mut markets = self.swept_markets();
+        if !markets.contains(&market_id) {
+            markets.push(market_id);
+        }
+    }
+
+    /// True once the switch has tripped. A bot should stop quoting until it
+    /// calls [`reset`](Self::reset).
+    pub fn is_tripped(&self) -> bool {
+        self.inner.tripped.load(Ordering::SeqCst)
+    }
+
+    /// Re-arm the switch after the bot has recovered.
+    pub fn reset(&self) {
+        self.inner.tripped.store(false, Ordering::SeqCst);
+    }
+
+    /// Trip the switch now and cancel every tracked order, then every order
+    /// resting on the swept markets.
+    ///
+    /// Orders whose cancel fails stay tracked, and a sweep that fails stays
+    /// pending, so a later call (or the next heartbeat) retries them.
+    /// Returns how many were cancelled.
+    pub async fn trip(&self, reason: &str) -> usize {
+        if !self.inner.tripped.swap(true, Ordering::SeqCst) {
+            warn!("Dead-man's switch tripped: {}", reason);
+        }
+        self.inner.sweep_pending.store(true, Ordering::SeqCst);
+        self.cancel_resting().await
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.orders().is_empty() || self.inner.sweep_pending.load(Ordering::SeqCst)
+    }
+
+    async fn cancel_resting(&self) -> usize {
+        let mut cancelled = self.cancel_tracked().await;
+        if self.inner.sweep_pending.load(Ordering::SeqCst) {
+            cancelled += self.cancel_swept().await;
+        }
+        if cancelled > 0 {
+            info!("Dead-man's switch cancelled {} order(s)", cancelled);
+        }
+        cancelled
+    }
+
+    async fn cancel_tracked(&self) -> usize {
+        let mut cancelled = 0;
+        for order in self.tracked() {
+            match self
+                .inner
+                .api
+                .cancel_order(
+                    &order.market_id,
+                    &order.side,
+                    order.order_id,
+                    &self.inner.wallet,
+                )
+                .await
+            {
+                Ok(_) => {
+                    self.untrack(order.order_id);
+                    cancelled += 1;
+                }
+                Err(e) => warn!(
+                    "Dead-man's switch failed to cancel order {}: {}",
+                    order.order_id, e
+                ),
+            }
+        }
+        cancelled
+    }
+
+    async fn cancel_swept(&self) -> usize {
+        let markets = self.swept_markets().clone();
+        let (api, wallet) = (&self.inner.api, &self.inner.wallet);
+        let mut cancelled = 0;
+        let mut complete = true;
+        for market_id in markets {
+            let open = match api.open_orders(&market_id, wallet).await {
+                Ok(open) => open,
+                Err(e) => {
+                    warn!(
+                        "Dead-man's switch failed to list open orders on {}: {}",
+                        market_id, e
+                    );
+                    complete = false;
+                    continue;
+                }
+            };
+            for order in open {
+                let side = if order.side == 1 { "buy" } else { "sell" };
+                match api
+                    .cancel_order(&market_id, side, order.order_id, wallet)
+                    .await
+                {
+                    Ok(_) => cancelled += 1,
+                    Err(e) => {
+                        warn!(
+                            "Dead-man's switch failed to cancel order {}: {}",
+                            order.order_id, e
+                        );
+                        complete = false;
+                    }
+                }
+            }
+        }
+        if complete {
+            self.inner.sweep_pending.store(false, Ordering::SeqCst);
+        }
+        cancelled
+    }
+
+    /// Spawn a heartbeat that connects to the stack at `url` every
+    /// interval.
+    pub fn spawn_heartbeat(&self, url: String) -> JoinHandle<()> {
+        let timeout = self.inner.config.heartbeat_interval;
+        self.spawn_heartbeat_with_probe(move || {
+            let url = url.clone();
+            async move { ping_grpc_server_with_timeout(url, timeout).await.success }
+        })
+    }
+
+    /// Spawn a heartbeat driven by a custom `probe`, which resolves to
+    /// whether the stack is reachable.
+    ///
+    /// Runs until aborted. After tripping it keeps retrying the cancels of
+    /// any orders still tracked, and any sweep that failed, on every
+    /// successful probe.
+    pub fn spawn_heartbeat_with_probe<P, F>(&self, probe: P) -> JoinHandle<()>
+    where
+        P: Fn() -> F + Send + 'static,
+        F: Future<Output = bool> + Send + 'static,
+    {
+        let switch = self.clone();
+        tokio::spawn(async move {
+            let config = switch.inner.config;
+            let mut last_ok = Instant::now();
+            let mut interval = tokio::time::interval(config.heartbeat_interval);
+            loop {
+                interval.tick().await;
+                if probe().await {
+                    last_ok = Instant::now();
+                    if switch.is_tripped() && switch.has_pending() {
+                        switch.cancel_resting().await;
+                    }
+                } else if last_ok.elapsed() >= config.disconnect_timeout {
+                    let reason = format!(
+                        "no heartbeat for {}s",
+                        last_ok.elapsed().as_secs_f64().round()
+                    );
+                    switch.trip(&reason).await;
+                }
+            }
+        })
+    }
+
+    /// Await the bot's `task`; if it panics or is cancelled, trip the
+    /// switch before returning the error.
+    pub async fn supervise<T>(&self, task: JoinHandle<T>) -> Result<T> {
+        match task.await {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                let reason = if e.is_panic() {
+                    "supervised task panicked"
+                } else {
+                    "supervised task was cancelled"
+                };
+                let cancelled = self.trip(reason).await;
+                Err(eyre!(
+                    "{}; cancelled {} resting order(s)",
+                    reason,
+                    cancelled
+                ))
+            }
+        }
+    }
+
+    fn orders(&self) -> std::sync::MutexGuard<'_, Vec<TrackedOrder>> {
+        self.inner
+            .orders
+            .lock()
+            .expect("DeadMansSwitch lock poisoned")
+    }
+
+    fn swept_markets(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+        self.inner
+            .swept_markets
+            .lock()
+            .expect("DeadMansSwitch lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock::MockAspensApi;
+    use crate::commands::config::config_pb::{Configuration, GetConfigResponse, Market};
+
+    fn switch(config: DeadMansSwitchConfig) -> DeadMansSwitch<MockAspensApi> {
+        let api = MockAspensApi::new(GetConfigResponse {
+            config: Some(Configuration {
+                chains: vec![],
+                markets: vec![Market {
+                    market_id: "m".into(),
+                    ..Default::default()
+                }],
+                maintenance: None,
+                signing_versions: vec![],
+            }),
+        });
+        let wallet = Wallet::from_evm_hex(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        DeadMansSwitch::new(api, wallet, config)
+    }
+
+    #[tokio::test]
+    async fn lost_heartbeat_cancels_tracked_orders() {
+        let switch = switch(DeadMansSwitchConfig {
+            heartbeat_interval: Duration::from_millis(5),
+            disconnect_timeout: Duration::from_millis(20),
+        });
+        switch.track("m", "buy", 1);
+        switch.track("m", "sell", 2);
+        switch.track("m", "buy", 3);
+        switch.untrack(3);
+
+        let heartbeat = switch.spawn_heartbeat_with_probe(|| async { false });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        heartbeat.abort();
+
+        assert!(switch.is_tripped());
+        assert!(switch.tracked().is_empty());
+        let cancelled: Vec<u64> = switch.api().cancels().iter().map(|c| c.order_id).collect();
+        assert_eq!(cancelled, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn sweep_cancels_untracked_resting_orders() {
+        let switch = switch(DeadMansSwitchConfig::default());
+        for (side, price) in [(1, Some("0.99")), (2, Some("1.01")), (1, None)] {
+            let order = crate::api::OrderRequest {
+                market_id: "m".into(),
+                side,
+                quantity: "1".into(),
+                price: price.map(str::to_string),
+                ..Default::default()
+            };
+            switch
+                .api()
+                .send_order(order, &[&switch.inner.wallet])
+                .await
+                .unwrap();
+        }
+        switch.track("m", "buy", 1);
+        switch.sweep("m");
+
+        assert_eq!(switch.trip("test").await, 2);
+        let cancelled: Vec<(u64, String)> = switch
+            .api()
+            .cancels()
+            .into_iter()
+            .map(|c| (c.order_id, c.side))
+            .collect();
+        assert_eq!(cancelled, vec![(1, "buy".into()), (2, "sell".into())]);
+        assert!(!switch.has_pending());
+    }
+
+    #[tokio::test]
+    async fn healthy_heartbeat_never_trips() {
+        let switch = switch(DeadMansSwitchConfig {
+            heartbeat_interval: Duration::from_millis(5),
+            disconnect_timeout: Duration::from_millis(20),
+        });
+        switch.track("m", "buy", 1);
+        let heartbeat = switch.spawn_heartbeat_with_probe(|| async { true });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        heartbeat.abort();
+        assert!(!switch.is_tripped());
+        assert_eq!(switch.tracked().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn panicking_task_trips_the_switch() {
+        let switch = switch(DeadMansSwitchConfig::default());
+        switch.track("m", "buy", 7);
+        let task = tokio::spawn(async { panic!("strategy bug") });
+        let err = switch.supervise::<()>(task).await.unwrap_err();
+        assert!(err.to_string().contains("panicked"), "{err}");
+        assert!(switch.is_tripped());
+        assert_eq!(switch.api().cancels()[0].order_id, 7);
+
+        switch.reset();
+        assert!(!switch.is_tripped());
+    }
+}
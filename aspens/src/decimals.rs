@@ -7,7 +7,56 @@
 //! place that bridges the two so every CLI / REPL / lib call site
 //! produces identical scaled values.
 
+use std::fmt;
+use std::str::FromStr;
+
 use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+/// Most decimals a `u128` amount can be scaled by: `10^38` is the largest
+/// power of ten below `u128::MAX`.
+pub const MAX_DECIMALS: u32 = 38;
+
+/// What [`parse_decimal_amount_with`] does with an amount that has more
+/// fractional digits than `decimals`. Zeros past `decimals` never count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrecisionMode {
+    /// Drop the extra digits (no rounding), as [`parse_decimal_amount`]
+    /// does.
+    #[default]
+    Truncate,
+    /// Round to the nearest base unit, ties to even.
+    RoundHalfEven,
+    /// Refuse the amount, so a typo can't silently change the order.
+    Error,
+}
+
+impl FromStr for PrecisionMode {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "truncate" => Ok(Self::Truncate),
+            "round" | "round-half-even" => Ok(Self::RoundHalfEven),
+            "error" | "strict" => Ok(Self::Error),
+            other => Err(eyre!(
+                "unknown precision mode '{}': use truncate, round or error",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PrecisionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncate => write!(f, "truncate"),
+            Self::RoundHalfEven => write!(f, "round"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
 
 /// Parse a human-readable decimal amount into a `u128` of base units.
 ///
@@ -15,7 +64,8 @@ use eyre::{Result, eyre};
 /// (`".5"`), and tolerates surrounding whitespace. Trailing fractional
 /// digits beyond `decimals` places are truncated (no rounding) to match
 /// on-chain semantics. Negative numbers, scientific notation, and
-/// thousands separators are not accepted.
+/// thousands separators are not accepted. Errors if `decimals` exceeds
+/// [`MAX_DECIMALS`].
 ///
 /// # Examples
 ///
@@ -27,6 +77,30 @@ use eyre::{Result, eyre};
 /// assert_eq!(parse_decimal_amount("1.0000001", 6).unwrap(), 1_000_000); // truncated
 /// ```
 pub fn parse_decimal_amount(amount: &str, decimals: u32) -> Result<u128> {
+    parse_decimal_amount_with(amount, decimals, PrecisionMode::Truncate)
+}
+
+/// [`parse_decimal_amount`], with `mode` deciding what happens to
+/// fractional digits past `decimals`.
+///
+/// # Examples
+///
+/// ```
+/// use aspens::decimals::{PrecisionMode, parse_decimal_amount_with};
+/// let round = PrecisionMode::RoundHalfEven;
+/// assert_eq!(parse_decimal_amount_with("1.1234567", 6, round).unwrap(), 1_123_457);
+/// assert_eq!(parse_decimal_amount_with("0.0000025", 6, round).unwrap(), 2);
+/// assert!(parse_decimal_amount_with("1.1234567", 6, PrecisionMode::Error).is_err());
+/// assert_eq!(parse_decimal_amount_with("1.1234560", 6, PrecisionMode::Error).unwrap(), 1_123_456);
+/// ```
+pub fn parse_decimal_amount_with(amount: &str, decimals: u32, mode: PrecisionMode) -> Result<u128> {
+    if decimals > MAX_DECIMALS {
+        return Err(eyre!(
+            "{} decimals is more than a u128 amount can hold (max {})",
+            decimals,
+            MAX_DECIMALS
+        ));
+    }
     let amount = amount.trim();
 
     if amount.is_empty() {
@@ -55,6 +129,21 @@ pub fn parse_decimal_amount(amount: &str, decimals: u32) -> Result<u128> {
         return Err(eyre!("Invalid amount format: {} (no digits)", amount));
     }
 
+    // Check every digit up front: truncation below would otherwise drop
+    // junk past `decimals` (`"1.5e2"` at 1 decimal), slice a multi-byte
+    // character, or let `u128::from_str` take a sign (`"1.+5"`).
+    if let Some(bad) = integer_part
+        .chars()
+        .chain(fractional_part.chars())
+        .find(|c| !c.is_ascii_digit())
+    {
+        return Err(eyre!(
+            "Invalid amount format: {} ('{}' is not a digit)",
+            amount,
+            bad
+        ));
+    }
+
     let integer: u128 = if integer_part.is_empty() {
         0
     } else {
@@ -63,11 +152,13 @@ pub fn parse_decimal_amount(amount: &str, decimals: u32) -> Result<u128> {
             .map_err(|_| eyre!("Invalid integer part: {}", integer_part))?
     };
 
-    // Truncate fractional digits beyond `decimals` (no rounding).
-    let fractional_str = if fractional_part.len() >= decimals as usize {
-        &fractional_part[..decimals as usize]
+    // Split off fractional digits beyond `decimals`; `mode` decides below
+    // whether they're dropped, rounded or refused. All ASCII, so slicing
+    // by byte is safe.
+    let (fractional_str, excess) = if fractional_part.len() >= decimals as usize {
+        fractional_part.split_at(decimals as usize)
     } else {
-        fractional_part
+        (fractional_part, "")
     };
 
     let fractional: u128 = if fractional_str.is_empty() {
@@ -84,10 +175,36 @@ pub fn parse_decimal_amount(amount: &str, decimals: u32) -> Result<u128> {
         .ok_or_else(|| eyre!("Amount overflow: {}", amount))?;
 
     let multiplier = 10_u128.pow(decimals);
-    integer
+    let truncated = integer
         .checked_mul(multiplier)
         .and_then(|v| v.checked_add(fractional_padded))
-        .ok_or_else(|| eyre!("Amount overflow: {}", amount))
+        .ok_or_else(|| eyre!("Amount overflow: {}", amount))?;
+
+    if !excess.bytes().any(|b| b != b'0') {
+        return Ok(truncated);
+    }
+    match mode {
+        PrecisionMode::Truncate => Ok(truncated),
+        PrecisionMode::Error => Err(eyre!(
+            "{} has more than {} decimal places",
+            amount,
+            decimals
+        )),
+        PrecisionMode::RoundHalfEven => {
+            let (first, rest) = excess.split_at(1);
+            let round_up = match first {
+                "5" => rest.bytes().any(|b| b != b'0') || truncated % 2 == 1,
+                digit => digit > "5",
+            };
+            if round_up {
+                truncated
+                    .checked_add(1)
+                    .ok_or_else(|| eyre!("Amount overflow: {}", amount))
+            } else {
+                Ok(truncated)
+            }
+        }
+    }
 }
 
 /// Same as [`parse_decimal_amount`] but downcasts to `u64`, returning a
@@ -118,7 +235,10 @@ pub fn format_decimal_amount(raw: u128, decimals: u32) -> String {
     if decimals == 0 {
         return raw.to_string();
     }
-    let scale = 10u128.pow(decimals);
+    // Past `MAX_DECIMALS` every `u128` is a pure fraction.
+    let Some(scale) = 10u128.checked_pow(decimals) else {
+        return format!("0.{:0width$}", raw, width = decimals as usize);
+    };
     let int_part = raw / scale;
     let frac_part = raw % scale;
     format!(
@@ -358,12 +478,23 @@ mod tests {
 
     #[test]
     fn rejects_decimals_too_large_for_u128_multiplier() {
-        // 10^39 > u128::MAX, so the multiplier itself would overflow.
-        // 10_u128.pow(39) panics, but we rely on the assumption that no
-        // real token has > 38 decimals. Document the behaviour: if any
-        // future caller passes a too-large decimals value, the function
-        // panics in pow() — explicit guardrail is left to the caller.
-        // (No assert here; this is a documentation test of the contract.)
+        // 10^38 still fits in a u128 (max ~3.4 * 10^38), but 4 whole units
+        // at that scale don't, and 10^39 — the multiplier one past
+        // `MAX_DECIMALS` — would overflow; both are errors, not panics.
+        assert_eq!(
+            parse_decimal_amount("1", MAX_DECIMALS).unwrap(),
+            10u128.pow(38)
+        );
+        assert!(parse_decimal_amount("4", MAX_DECIMALS).is_err());
+        assert_eq!(
+            parse_decimal_amount("0.1", MAX_DECIMALS).unwrap(),
+            10u128.pow(37)
+        );
+        assert!(parse_decimal_amount("0", MAX_DECIMALS + 1).is_err());
+        assert_eq!(
+            format_decimal_amount(5, 40),
+            format!("0.{}5", "0".repeat(39))
+        );
     }
 
     // ----- u64 downcast wrapper ----------------------------------------
@@ -446,6 +577,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rejects_junk_in_truncated_digits() {
+        // Found by the property tests: digits past `decimals` are still
+        // validated, and a sign inside the fraction is not a digit.
+        assert!(parse_decimal_amount("1.5e2", 1).is_err());
+        assert!(parse_decimal_amount("1.5é", 1).is_err());
+        assert!(parse_decimal_amount("1.+5", 6).is_err());
+    }
+
+    #[test]
+    fn precision_modes_handle_excess_digits() {
+        let round = |s| parse_decimal_amount_with(s, 2, PrecisionMode::RoundHalfEven).unwrap();
+        assert_eq!(round("1.234"), 123);
+        assert_eq!(round("1.236"), 124);
+        // Ties go to the even unit; anything past the tie breaks it.
+        assert_eq!(round("1.235"), 124);
+        assert_eq!(round("1.245"), 124);
+        assert_eq!(round("1.2451"), 125);
+        assert_eq!(round("0.999"), 100);
+
+        let strict = |s| parse_decimal_amount_with(s, 2, PrecisionMode::Error);
+        let err = strict("1.234").unwrap_err().to_string();
+        assert!(err.contains("more than 2 decimal places"), "got: {err}");
+        // Trailing zeros lose nothing.
+        assert_eq!(strict("1.2300").unwrap(), 123);
+        assert_eq!(strict("1.2").unwrap(), 120);
+
+        assert_eq!(
+            parse_decimal_amount_with("1.239", 2, PrecisionMode::Truncate).unwrap(),
+            123
+        );
+        assert_eq!(
+            "strict".parse::<PrecisionMode>().unwrap(),
+            PrecisionMode::Error
+        );
+        assert_eq!(
+            "round".parse::<PrecisionMode>().unwrap(),
+            PrecisionMode::RoundHalfEven
+        );
+        assert!("nearest".parse::<PrecisionMode>().is_err());
+    }
+
     #[test]
     fn truncation_is_lossy_in_the_documented_direction() {
         // Truncate, never round. So values just below a unit boundary
@@ -455,3 +628,47 @@ mod tests {
         assert_eq!(parse_decimal_amount("0.0000009", 6).unwrap(), 0);
     }
 }
+
+#[cfg(test)]
+mod proptests {
+    //! Properties over the whole input space, for the functions that turn
+    //! typed amounts into money on the wire.
+
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn format_then_parse_round_trips(raw in any::<u128>(), decimals in 0..=MAX_DECIMALS) {
+            let formatted = format_decimal_amount(raw, decimals);
+            prop_assert_eq!(parse_decimal_amount(&formatted, decimals).unwrap(), raw);
+        }
+
+        #[test]
+        fn parse_truncates_to_decimals(
+            int in "[0-9]{1,12}",
+            frac in "[0-9]{0,24}",
+            decimals in 0..=18u32,
+        ) {
+            let raw = parse_decimal_amount(&format!("{}.{}", int, frac), decimals).unwrap();
+            // Truncated (never rounded) to `decimals`, then zero-padded.
+            let kept: String = frac.chars().take(decimals as usize).collect();
+            let expected: u128 = format!("{}{:0<width$}", int, kept, width = decimals as usize)
+                .parse()
+                .unwrap();
+            prop_assert_eq!(raw, expected);
+        }
+
+        #[test]
+        fn parse_never_panics(amount in "\\PC{0,64}", decimals in 0..=64u32) {
+            let _ = parse_decimal_amount(&amount, decimals);
+            let _ = parse_decimal_amount_u64(&amount, decimals);
+        }
+
+        #[test]
+        fn overflow_is_an_error_not_a_wrap(int in any::<u128>(), decimals in 1..=MAX_DECIMALS) {
+            let expected = int.checked_mul(10u128.pow(decimals));
+            prop_assert_eq!(parse_decimal_amount(&int.to_string(), decimals).ok(), expected);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Typed errors for the `commands` functions.
+//!
+//! Every public function in [`crate::commands`] returns
+//! [`Result<T, AspensError>`](Result), so callers can branch on the kind of
+//! failure instead of matching on message text:
+//!
+//! ```no_run
+//! use aspens::AspensError;
+//! use aspens::commands::config;
+//!
+//! # async fn run(url: String) {
+//! match config::get_config(url).await {
+//!     Ok(config) => println!("{} chains", config.config.map_or(0, |c| c.chains.len())),
+//!     Err(AspensError::Connection(e)) => eprintln!("stack unreachable, retry later: {e}"),
+//!     Err(e) => eprintln!("{e}"),
+//! }
+//! # }
+//! ```
+//!
+//! Each variant wraps the underlying error and displays exactly as it did
+//! before, so log output and messages are unchanged. `AspensError`
+//! implements `std::error::Error`, so `?` still converts it into an
+//! `eyre::Report` in eyre-based code; going the other way,
+//! `AspensError::from(report)` recovers the typed error inside a report, or
+//! classifies the report by the gRPC and RPC errors in its source chain.
+
+use std::error::Error as StdError;
+use std::fmt::Display;
+
+use tonic::Code;
+
+/// Boxed underlying error carried by every [`AspensError`] variant.
+pub type BoxError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// `Result` with [`AspensError`] as the default error type.
+pub type Result<T, E = AspensError> = std::result::Result<T, E>;
+
+/// Why an Aspens operation failed.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum AspensError {
+    /// The stack or an RPC endpoint could not be reached, or the connection
+    /// dropped or timed out. Usually worth retrying.
+    #[error(transparent)]
+    Connection(BoxError),
+    /// The stack rejected the caller's credentials: a missing, expired or
+    /// invalid JWT, or a signer that isn't allowed to do this.
+    #[error(transparent)]
+    Auth(BoxError),
+    /// A chain, token, market, order or other named entity doesn't exist in
+    /// the stack's configuration or state.
+    #[error(transparent)]
+    NotFound(BoxError),
+    /// A wallet or deposit balance (or gas balance) is too low for the
+    /// operation.
+    #[error(transparent)]
+    InsufficientBalance(BoxError),
+    /// An argument was rejected before anything was sent: a bad amount,
+    /// side, address, file or wallet type.
+    #[error(transparent)]
+    InvalidInput(BoxError),
+    /// The stack answered with an error, or with a response that doesn't
+    /// follow the protocol.
+    #[error(transparent)]
+    Protocol(BoxError),
+    /// A chain RPC call, contract call or transaction failed.
+    #[error(transparent)]
+    Chain(BoxError),
+    /// An order broke a client-side risk limit and wasn't sent. The inner
+    /// error is a `RiskViolation` (see `commands::trading::risk`).
+    #[error(transparent)]
+    RiskLimit(BoxError),
+    /// Anything else.
+    #[error(transparent)]
+    Other(BoxError),
+}
+
+impl AspensError {
+    /// A [`Connection`](Self::Connection) error with message `msg`.
+    pub fn connection(msg: impl Display) -> Self {
+        Self::Connection(msg.to_string().into())
+    }
+
+    /// An [`Auth`](Self::Auth) error with message `msg`.
+    pub fn auth(msg: impl Display) -> Self {
+        Self::Auth(msg.to_string().into())
+    }
+
+    /// A [`NotFound`](Self::NotFound) error with message `msg`.
+    pub fn not_found(msg: impl Display) -> Self {
+        Self::NotFound(msg.to_string().into())
+    }
+
+    /// An [`InsufficientBalance`](Self::InsufficientBalance) error with
+    /// message `msg`.
+    pub fn insufficient_balance(msg: impl Display) -> Self {
+        Self::InsufficientBalance(msg.to_string().into())
+    }
+
+    /// An [`InvalidInput`](Self::InvalidInput) error with message `msg`.
+    pub fn invalid_input(msg: impl Display) -> Self {
+        Self::InvalidInput(msg.to_string().into())
+    }
+
+    /// A [`Protocol`](Self::Protocol) error with message `msg`.
+    pub fn protocol(msg: impl Display) -> Self {
+        Self::Protocol(msg.to_string().into())
+    }
+
+    /// A [`Chain`](Self::Chain) error with message `msg`.
+    pub fn chain(msg: impl Display) -> Self {
+        Self::Chain(msg.to_string().into())
+    }
+
+    /// An [`Other`](Self::Other) error with message `msg`.
+    pub fn other(msg: impl Display) -> Self {
+        Self::Other(msg.to_string().into())
+    }
+
+    /// True for errors a retry may fix (currently [`Connection`](Self::Connection)).
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Connection(_))
+    }
+
+    /// The gRPC status the stack answered with, if this error came from one.
+    pub fn grpc_status(&self) -> Option<&tonic::Status> {
+        let mut cause: Option<&(dyn StdError + 'static)> = Some(self.inner());
+        while let Some(err) = cause {
+            if let Some(status) = err.downcast_ref::<tonic::Status>() {
+                return Some(status);
+            }
+            cause = err.source();
+        }
+        None
+    }
+
+    /// The constructor of this error's variant.
+    fn kind(&self) -> fn(BoxError) -> AspensError {
+        match self {
+            Self::Connection(_) => Self::Connection,
+            Self::Auth(_) => Self::Auth,
+            Self::NotFound(_) => Self::NotFound,
+            Self::InsufficientBalance(_) => Self::InsufficientBalance,
+            Self::InvalidInput(_) => Self::InvalidInput,
+            Self::Protocol(_) => Self::Protocol,
+            Self::Chain(_) => Self::Chain,
+            Self::RiskLimit(_) => Self::RiskLimit,
+            Self::Other(_) => Self::Other,
+        }
+    }
+
+    fn inner(&self) -> &(dyn StdError + 'static) {
+        match self {
+            Self::Connection(e)
+            | Self::Auth(e)
+            | Self::NotFound(e)
+            | Self::InsufficientBalance(e)
+            | Self::InvalidInput(e)
+            | Self::Protocol(e)
+            | Self::Chain(e)
+            | Self::RiskLimit(e)
+            | Self::Other(e) => e.as_ref(),
+        }
+    }
+}
+
+/// The variant constructor for an error with gRPC status `status`.
+fn status_kind(status: &tonic::Status) -> fn(BoxError) -> AspensError {
+    match status.code() {
+        Code::Unavailable | Code::DeadlineExceeded | Code::Cancelled => AspensError::Connection,
+        Code::Unauthenticated | Code::PermissionDenied => AspensError::Auth,
+        Code::NotFound => AspensError::NotFound,
+        Code::InvalidArgument | Code::OutOfRange | Code::AlreadyExists => AspensError::InvalidInput,
+        Code::FailedPrecondition | Code::ResourceExhausted
+            if status.message().to_lowercase().contains("insufficient") =>
+        {
+            AspensError::InsufficientBalance
+        }
+        _ => AspensError::Protocol,
+    }
+}
+
+/// The variant constructor for an error whose source chain contains `err`,
+/// if `err` is a type this module knows how to classify.
+fn classify(err: &(dyn StdError + 'static)) -> Option<fn(BoxError) -> AspensError> {
+    if let Some(err) = err.downcast_ref::<AspensError>() {
+        return Some(err.kind());
+    }
+    if let Some(status) = err.downcast_ref::<tonic::Status>() {
+        return Some(status_kind(status));
+    }
+    if err.is::<tonic::transport::Error>() {
+        return Some(AspensError::Connection);
+    }
+    if let Some(rpc) = err.downcast_ref::<alloy::transports::TransportError>() {
+        return Some(if rpc.is_transport_error() {
+            AspensError::Connection
+        } else {
+            AspensError::Chain
+        });
+    }
+    if err.is::<alloy::contract::Error>() || err.is::<alloy::providers::PendingTransactionError>() {
+        return Some(AspensError::Chain);
+    }
+    None
+}
+
+impl From<tonic::Status> for AspensError {
+    fn from(status: tonic::Status) -> Self {
+        status_kind(&status)(Box::new(status))
+    }
+}
+
+impl From<tonic::transport::Error> for AspensError {
+    fn from(err: tonic::transport::Error) -> Self {
+        Self::Connection(Box::new(err))
+    }
+}
+
+impl From<alloy::transports::TransportError> for AspensError {
+    fn from(err: alloy::transports::TransportError) -> Self {
+        if err.is_transport_error() {
+            Self::Connection(Box::new(err))
+        } else {
+            Self::Chain(Box::new(err))
+        }
+    }
+}
+
+impl From<alloy::contract::Error> for AspensError {
+    fn from(err: alloy::contract::Error) -> Self {
+        Self::Chain(Box::new(err))
+    }
+}
+
+impl From<alloy::providers::PendingTransactionError> for AspensError {
+    fn from(err: alloy::providers::PendingTransactionError) -> Self {
+        Self::Chain(Box::new(err))
+    }
+}
+
+impl From<tokio::task::JoinError> for AspensError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+impl From<alloy::signers::Error> for AspensError {
+    fn from(err: alloy::signers::Error) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+impl From<std::io::Error> for AspensError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+impl From<serde_json::Error> for AspensError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::InvalidInput(Box::new(err))
+    }
+}
+
+impl From<toml::de::Error> for AspensError {
+    fn from(err: toml::de::Error) -> Self {
+        Self::InvalidInput(Box::new(err))
+    }
+}
+
+impl From<toml::ser::Error> for AspensError {
+    fn from(err: toml::ser::Error) -> Self {
+        Self::Other(Box::new(err))
+    }
+}
+
+impl From<url::ParseError> for AspensError {
+    fn from(err: url::ParseError) -> Self {
+        Self::InvalidInput(Box::new(err))
+    }
+}
+
+impl From<eyre::Report> for AspensError {
+    /// The `AspensError` `report` holds if nothing was added to it;
+    /// otherwise `report` wrapped in the variant its source chain points to,
+    /// or [`Other`](Self::Other).
+    fn from(report: eyre::Report) -> Self {
+        if report.chain().next().is_some_and(|e| e.is::<AspensError>()) {
+            match report.downcast::<AspensError>() {
+                Ok(err) => return err,
+                Err(report) => return Self::Other(report.into()),
+            }
+        }
+        let kind = report.chain().find_map(classify).unwrap_or(Self::Other);
+        kind(report.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_status_codes_and_keeps_messages() {
+        let err = AspensError::from(tonic::Status::unauthenticated("token expired"));
+        assert!(matches!(err, AspensError::Auth(_)));
+        assert_eq!(err.grpc_status().unwrap().message(), "token expired");
+
+        let err = AspensError::from(tonic::Status::failed_precondition(
+            "insufficient deposited balance",
+        ));
+        assert!(matches!(err, AspensError::InsufficientBalance(_)));
+
+        let err = AspensError::from(tonic::Status::unavailable("down"));
+        assert!(err.is_retryable());
+
+        // A status wrapped in a report is still found.
+        let report =
+            eyre::Report::new(tonic::Status::not_found("no order 7")).wrap_err("cancel order");
+        let err = AspensError::from(report);
+        assert!(matches!(err, AspensError::NotFound(_)));
+        assert_eq!(err.to_string(), "cancel order");
+
+        // Round trip through eyre keeps the variant.
+        let report: eyre::Report = AspensError::not_found("Chain 'x' not found").into();
+        let err = AspensError::from(report);
+        assert!(matches!(err, AspensError::NotFound(_)));
+        assert_eq!(err.to_string(), "Chain 'x' not found");
+
+        // Context added on the way keeps both the variant and the message.
+        let report = eyre::Report::new(AspensError::chain("reverted")).wrap_err("fee rates");
+        let err = AspensError::from(report);
+        assert!(matches!(err, AspensError::Chain(_)));
+        assert_eq!(err.to_string(), "fee rates");
+
+        let err = AspensError::from(eyre::eyre!("something else"));
+        assert!(matches!(err, AspensError::Other(_)));
+    }
+}
@@ -2,8 +2,9 @@
 //!
 //! `#[sol(rpc)]` so callers can build alloy contract handles
 //! (`MidribV3::new(addr, provider)`) and dispatch on-chain calls
-//! (deposit, withdraw-voucher, tradeBalance). Pulls `alloy-contract`,
-//! which is why this submodule is gated on the `client` feature.
+//! (deposit, withdraw-voucher, tradeBalance) and read token metadata.
+//! Pulls `alloy-contract`, which is why this submodule is gated on the
+//! `client` feature.
 //!
 //! MidribV3 is the optimistic-ledger contract — the V2 on-chain order
 //! machinery is burned. The kept surface the SDK calls here is deposit /
@@ -28,5 +29,11 @@ sol! {
         function approve(address spender, uint256 amount) external returns (bool);
         #[derive(Debug)]
         function balanceOf(address) external view returns (uint256);
+        #[derive(Debug)]
+        function decimals() external view returns (uint8);
+        #[derive(Debug)]
+        function symbol() external view returns (string);
+        #[derive(Debug)]
+        function transfer(address to, uint256 amount) external returns (bool);
     }
 }
@@ -3,6 +3,7 @@
 //! runtime); the REPL owns its own runtime via [`BlockingExecutor`].
 
 use std::future::Future;
+use std::time::Duration;
 
 /// Run a `Send + 'static` future to completion from a synchronous caller.
 pub trait AsyncExecutor {
@@ -11,6 +12,36 @@ pub trait AsyncExecutor {
     where
         F: Future<Output = T> + Send + 'static,
         T: Send + 'static;
+
+    /// Like [`execute`](Self::execute), but give up after `timeout`.
+    ///
+    /// The deadline is enforced on the runtime that drives the future, so
+    /// the semantics are identical for every executor: on expiry the future
+    /// is dropped (cancelling any in-flight request) and an error naming the
+    /// timeout is returned. A zero `timeout` disables the deadline. The
+    /// future's error, such as an `AspensError`, comes back as an
+    /// `eyre::Report`.
+    fn execute_with_timeout<F, T, E>(&self, future: F, timeout: Duration) -> eyre::Result<T>
+    where
+        F: Future<Output = Result<T, E>> + Send + 'static,
+        T: Send + 'static,
+        E: Into<eyre::Report> + Send + 'static,
+    {
+        let future = async move { future.await.map_err(Into::into) };
+        if timeout.is_zero() {
+            return self.execute(future);
+        }
+        self.execute(async move {
+            tokio::time::timeout(timeout, future)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(eyre::eyre!(
+                        "operation timed out after {}s",
+                        timeout.as_secs_f64()
+                    ))
+                })
+        })
+    }
 }
 
 /// Executor that piggybacks on the caller's existing tokio runtime.
@@ -74,3 +105,54 @@ impl AsyncExecutor for BlockingExecutor {
         self.rt.block_on(future)
     }
 }
+
+/// Aborts the task when dropped, so a task that owns a stream's handle
+/// takes the stream down with it when it's aborted in turn.
+pub(crate) struct AbortOnDrop<T>(pub(crate) tokio::task::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execute_with_timeout_returns_result_within_deadline() {
+        let executor = BlockingExecutor::new();
+        let out = executor
+            .execute_with_timeout(async { Ok::<_, eyre::Report>(7) }, Duration::from_secs(5));
+        assert_eq!(out.unwrap(), 7);
+    }
+
+    #[test]
+    fn execute_with_timeout_errors_on_expiry() {
+        let executor = BlockingExecutor::new();
+        let err = executor
+            .execute_with_timeout(
+                async {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    Ok::<_, eyre::Report>(())
+                },
+                Duration::from_millis(20),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
+
+    #[test]
+    fn zero_timeout_disables_the_deadline() {
+        let executor = BlockingExecutor::new();
+        let out = executor.execute_with_timeout(
+            async {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok::<_, eyre::Report>("done")
+            },
+            Duration::ZERO,
+        );
+        assert_eq!(out.unwrap(), "done");
+    }
+}
@@ -0,0 +1,527 @@
+//! What to tell a person when an Aspens operation fails.
+//!
+//! [`user_message`] reads an [`AspensError`] and picks the [`UserMessage`]
+//! that explains it: a short title, likely causes and hints on what to try
+//! next. The aspens-cli, aspens-repl and aspens-admin binaries all render
+//! their errors through it (via `aspens_cliutil::format_error`), so a hint
+//! fixed here is fixed everywhere; bots can use it to log something more
+//! useful than a gRPC status.
+//!
+//! The message text is matched first, against a battery of known failure
+//! modes (transport, auth, config lookups, gas, balance, signing), because
+//! it is more specific than the error kind; the kind decides only when no
+//! message matches. The branch order matters: specific "not found"
+//! branches come before the generic one, gas before balance.
+//!
+//! ```
+//! use aspens::AspensError;
+//! use aspens::explain::{UserMessage, user_message};
+//!
+//! let err = AspensError::not_found("Chain 'flare-mainnet' not found in configuration");
+//! let message = user_message(&err);
+//! assert_eq!(message, UserMessage::ChainNotFound);
+//! let text = message.render("withdraw", "aspens-cli", "TRADER_PRIVKEY");
+//! assert!(text.starts_with("Failed to withdraw: Chain/network not found"));
+//! ```
+
+use std::error::Error as StdError;
+
+use crate::error::AspensError;
+
+/// The explanation for a failure, chosen by [`user_message`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UserMessage {
+    /// The stack refused the connection.
+    ConnectionRefused,
+    /// The stack's hostname didn't resolve.
+    DnsFailure,
+    /// The TLS handshake failed.
+    Tls,
+    /// The endpoint doesn't speak gRPC over the scheme in the URL.
+    ProtocolMismatch,
+    /// The request timed out.
+    Timeout,
+    /// The JWT is missing, invalid or expired.
+    Auth,
+    /// The signing wallet isn't the stack's admin.
+    NotAdmin,
+    /// The caller lacks permission for the operation.
+    PermissionDenied,
+    /// `init-admin` on a stack that already has one.
+    AdminAlreadyInitialized,
+    /// A chain or network name isn't configured.
+    ChainNotFound,
+    /// A token symbol isn't configured.
+    TokenNotFound,
+    /// A market isn't configured.
+    MarketNotFound,
+    /// The resource being created already exists.
+    AlreadyExists,
+    /// Some other named resource doesn't exist.
+    NotFound,
+    /// The wallet can't pay for gas.
+    InsufficientGas,
+    /// A wallet or deposit balance is too low.
+    InsufficientBalance,
+    /// The stack rejected an amount's format.
+    InvalidAmountFormat,
+    /// A transaction or contract call failed.
+    TransactionFailed,
+    /// An address didn't parse.
+    InvalidAddress,
+    /// The signing key didn't parse.
+    InvalidPrivateKey,
+    /// Nothing more specific is known.
+    Unknown,
+}
+
+/// The explanation for `err`.
+pub fn user_message(err: &AspensError) -> UserMessage {
+    UserMessage::of(err)
+}
+
+/// True if `err` reads as the stack rejecting the caller's JWT: the check
+/// behind [`UserMessage::Auth`].
+pub fn is_auth_error(err: &(dyn StdError + 'static)) -> bool {
+    let err_string = err.to_string().to_lowercase();
+    err_string.contains("unauthenticated")
+        || err_string.contains("unauthorized")
+        || err_string.contains("401")
+        || err_string.contains("invalid token")
+        || err_string.contains("token expired")
+}
+
+impl UserMessage {
+    /// The explanation for any error, typed or not: by its message (and its
+    /// root cause's), else by the kind of the first [`AspensError`] in its
+    /// source chain.
+    pub fn of(err: &(dyn StdError + 'static)) -> Self {
+        let by_text = Self::by_text(err);
+        if by_text != UserMessage::Unknown {
+            return by_text;
+        }
+        let mut cause = Some(err);
+        while let Some(e) = cause {
+            if let Some(typed) = e.downcast_ref::<AspensError>() {
+                return Self::by_kind(typed);
+            }
+            cause = e.source();
+        }
+        UserMessage::Unknown
+    }
+
+    fn by_text(err: &(dyn StdError + 'static)) -> Self {
+        let err_string = err.to_string().to_lowercase();
+        let mut root = err;
+        while let Some(source) = root.source() {
+            root = source;
+        }
+        let root_cause = root.to_string().to_lowercase();
+        let has = |s: &str| err_string.contains(s);
+
+        // -- Transport / network
+        if has("failed to connect")
+            || has("connection refused")
+            || root_cause.contains("connection refused")
+        {
+            return UserMessage::ConnectionRefused;
+        }
+        if has("dns error")
+            || has("no such host")
+            || has("name or service not known")
+            || root_cause.contains("dns")
+        {
+            return UserMessage::DnsFailure;
+        }
+        if has("tls") || has("ssl") || has("certificate") || root_cause.contains("certificate") {
+            return UserMessage::Tls;
+        }
+        if has("compression flag") || has("protocol error") || has("invalid compression") {
+            return UserMessage::ProtocolMismatch;
+        }
+        if has("timeout") || has("timed out") {
+            return UserMessage::Timeout;
+        }
+
+        // -- Auth / admin
+        if is_auth_error(err) {
+            return UserMessage::Auth;
+        }
+        if has("not authorized as an admin") || has("address is not authorized") {
+            return UserMessage::NotAdmin;
+        }
+        if has("permission denied") || has("forbidden") || has("403") {
+            return UserMessage::PermissionDenied;
+        }
+        if has("admin already") || has("already initialized") {
+            return UserMessage::AdminAlreadyInitialized;
+        }
+
+        // -- Config / lookup: specific resources before the generic 404
+        if has("chain not found") || has("network not found") || (has("not found") && has("chain"))
+        {
+            return UserMessage::ChainNotFound;
+        }
+        if has("token not found") || (has("not found") && has("token")) {
+            return UserMessage::TokenNotFound;
+        }
+        if has("market not found") || (has("not found") && has("market")) {
+            return UserMessage::MarketNotFound;
+        }
+        if has("already exists") || has("duplicate") {
+            return UserMessage::AlreadyExists;
+        }
+        if has("not found") || has("404") {
+            return UserMessage::NotFound;
+        }
+
+        // -- Trading / on-chain
+        if has("insufficient gas") || has("insufficient funds for gas") {
+            return UserMessage::InsufficientGas;
+        }
+        if has("insufficient") || has("not enough") || has("balance too low") {
+            return UserMessage::InsufficientBalance;
+        }
+        if has("invalid string length") {
+            return UserMessage::InvalidAmountFormat;
+        }
+        if has("transaction") || has("revert") || has("execution reverted") {
+            return UserMessage::TransactionFailed;
+        }
+
+        // -- Signing key / address format
+        if has("invalid address") || has("invalid checksum") {
+            return UserMessage::InvalidAddress;
+        }
+        if has("invalid private key") || has("privkey") || has("secret key") || has("hex decode") {
+            return UserMessage::InvalidPrivateKey;
+        }
+
+        UserMessage::Unknown
+    }
+
+    fn by_kind(err: &AspensError) -> Self {
+        match err {
+            AspensError::Connection(_) => UserMessage::ConnectionRefused,
+            AspensError::Auth(_) => UserMessage::Auth,
+            AspensError::NotFound(_) => UserMessage::NotFound,
+            AspensError::InsufficientBalance(_) => UserMessage::InsufficientBalance,
+            AspensError::Chain(_) => UserMessage::TransactionFailed,
+            _ => UserMessage::Unknown,
+        }
+    }
+
+    /// One line saying what went wrong, or `None` for [`Unknown`](Self::Unknown).
+    pub fn title(self) -> Option<&'static str> {
+        Some(match self {
+            UserMessage::ConnectionRefused => "Could not connect to the server",
+            UserMessage::DnsFailure => "Could not resolve server hostname",
+            UserMessage::Tls => "TLS/SSL error",
+            UserMessage::ProtocolMismatch => "Protocol mismatch",
+            UserMessage::Timeout => "Request timed out",
+            UserMessage::Auth => "Authentication failed",
+            UserMessage::NotAdmin => "Address is not authorized as admin",
+            UserMessage::PermissionDenied => "Permission denied",
+            UserMessage::AdminAlreadyInitialized => "Admin has already been initialized",
+            UserMessage::ChainNotFound => "Chain/network not found",
+            UserMessage::TokenNotFound => "Token not found",
+            UserMessage::MarketNotFound => "Market not found",
+            UserMessage::AlreadyExists => "Resource already exists",
+            UserMessage::NotFound => "Resource not found",
+            UserMessage::InsufficientGas => "Insufficient gas for transaction fees",
+            UserMessage::InsufficientBalance => "Insufficient balance",
+            UserMessage::InvalidAmountFormat => "Invalid amount format",
+            UserMessage::TransactionFailed => "Transaction failed",
+            UserMessage::InvalidAddress => "Invalid Ethereum address format",
+            UserMessage::InvalidPrivateKey => "Invalid private key",
+            UserMessage::Unknown => return None,
+        })
+    }
+
+    /// A paragraph expanding on the title, for the few failures that need
+    /// one. `privkey_env` names the variable holding the signing key.
+    pub fn explanation(self, privkey_env: &str) -> Option<String> {
+        match self {
+            UserMessage::NotAdmin => Some(format!(
+                "The wallet address derived from {privkey_env} is not registered as an admin\n\
+                 on this Aspens server."
+            )),
+            UserMessage::InsufficientGas => {
+                Some("Your wallet needs native tokens (ETH, FLR, etc.) to pay for gas.".to_string())
+            }
+            UserMessage::InvalidAmountFormat => {
+                Some("The server rejected the order due to an invalid amount format.".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// What probably caused it.
+    pub fn causes(self) -> &'static [&'static str] {
+        match self {
+            UserMessage::ConnectionRefused => &[
+                "The Aspens server is not running",
+                "The server URL is incorrect",
+                "A firewall is blocking the connection",
+            ],
+            UserMessage::DnsFailure => &[
+                "The server hostname is incorrect",
+                "DNS is not configured properly",
+                "No internet connection",
+            ],
+            UserMessage::Tls => &[
+                "The server's SSL certificate is invalid or expired",
+                "Certificate chain is incomplete",
+                "Using HTTP URL for HTTPS server or vice versa",
+            ],
+            UserMessage::ProtocolMismatch => &[
+                "Using HTTP to connect to an HTTPS server",
+                "Using HTTPS to connect to an HTTP server",
+                "The server is not a gRPC endpoint",
+            ],
+            UserMessage::Timeout => &[
+                "The server is overloaded or unresponsive",
+                "Network latency is too high",
+                "The operation is taking longer than expected",
+            ],
+            UserMessage::Auth => &[
+                "JWT token is missing, invalid, or expired",
+                "You don't have admin privileges",
+            ],
+            UserMessage::NotAdmin => &[
+                "Using the wrong private key (not the admin wallet)",
+                "The admin address was changed on the server",
+                "This is a fresh server and admin hasn't been initialized",
+            ],
+            UserMessage::PermissionDenied => &[
+                "Your account doesn't have admin privileges",
+                "The operation requires a different permission level",
+            ],
+            UserMessage::InvalidAmountFormat => &[
+                "Amount or price is too small or has too few digits",
+                "Values need to be in the correct decimal format",
+            ],
+            UserMessage::TransactionFailed => &[
+                "Insufficient token balance or allowance",
+                "Contract execution reverted",
+                "Gas estimation failed",
+            ],
+            _ => &[],
+        }
+    }
+
+    /// What to try next. `binary` is the command the hints tell the user to
+    /// run (e.g. `aspens-cli`); `privkey_env` names the variable holding
+    /// the signing key.
+    pub fn hints(self, binary: &str, privkey_env: &str) -> Vec<String> {
+        let name = binary;
+        let privkey = privkey_env;
+        let hints: &[&str] = match self {
+            UserMessage::ConnectionRefused => &[
+                "Check that the server is running",
+                "Verify the stack URL with '{name} status'",
+                "Check ASPENS_MARKET_STACK_URL in your .env file",
+            ],
+            UserMessage::DnsFailure => &[
+                "Verify the stack URL is correct",
+                "Check your internet connection",
+                "Try using an IP address instead of hostname",
+            ],
+            UserMessage::Tls => &[
+                "Verify you're using the correct protocol (http:// vs https://)",
+                "For local development, use http://localhost:50051",
+                "For remote servers, use https://",
+            ],
+            UserMessage::ProtocolMismatch => &[
+                "For remote servers, use https://",
+                "For local development, use http://",
+                "Verify ASPENS_MARKET_STACK_URL in your .env file",
+            ],
+            UserMessage::Timeout => &[
+                "Try again in a few moments",
+                "Check server status with '{name} status'",
+                "Verify network connectivity",
+            ],
+            UserMessage::Auth => &[
+                "Run '{name} login' to get a fresh JWT token",
+                "Set ASPENS_JWT in your .env file or use --jwt flag",
+                "Verify {privkey} is set correctly",
+            ],
+            UserMessage::NotAdmin => &[
+                "Run '{name} admin-public-key' to see your wallet address",
+                "Compare with the registered admin address on the server",
+                "If this is a new server, use '{name} init-admin --address <your-address>'",
+                "Check that {privkey} in .env matches the expected admin wallet",
+            ],
+            UserMessage::PermissionDenied => &[
+                "Verify you are using the correct admin wallet",
+                "Contact the system administrator",
+            ],
+            UserMessage::AdminAlreadyInitialized => &[
+                "Use '{name} login' to authenticate with the existing admin",
+                "Use '{name} update-admin' to change the admin address (requires auth)",
+            ],
+            UserMessage::ChainNotFound => &[
+                "Check available chains with '{name} config'",
+                "Verify the network name is spelled correctly",
+                "The chain may not be configured on this server",
+            ],
+            UserMessage::TokenNotFound => &[
+                "Check available tokens with '{name} config'",
+                "Verify the token symbol is spelled correctly (case-sensitive)",
+                "The token may not be configured on this chain",
+            ],
+            UserMessage::MarketNotFound => &[
+                "Check available markets with '{name} config'",
+                "Verify the market ID is correct",
+                "Markets are identified by their full ID (e.g., chain_id::token::chain_id::token)",
+            ],
+            UserMessage::AlreadyExists => &[
+                "Use the appropriate delete command first if you want to replace it",
+                "Check existing configuration with '{name} config'",
+            ],
+            UserMessage::NotFound => &[
+                "Verify the resource name/ID is correct",
+                "Check existing configuration with '{name} config'",
+                "The resource may have been deleted",
+            ],
+            UserMessage::InsufficientGas => &[
+                "Fund your wallet with native tokens on the target chain",
+                "For testnets, use a faucet to get free test tokens:\n  \
+                 - Base Sepolia: https://www.alchemy.com/faucets/base-sepolia\n  \
+                 - Flare Coston2: https://faucet.flare.network",
+            ],
+            UserMessage::InsufficientBalance => &[
+                "Check your balances with '{name} balance'",
+                "For trading: ensure you have deposited tokens first",
+                "For deposits: ensure your wallet has enough tokens",
+            ],
+            UserMessage::InvalidAmountFormat => &[
+                "Use decimal notation for amounts (e.g., '1.5' instead of '1')",
+                "Check '{name} config' to see the market's pairDecimals setting",
+                "For market with pairDecimals=4: '1' becomes '10000', '0.5' becomes '5000'",
+            ],
+            UserMessage::TransactionFailed => &[
+                "Check your wallet balance",
+                "Verify you have approved the contract to spend tokens",
+                "Try with a smaller amount",
+            ],
+            UserMessage::InvalidAddress => &[
+                "Ensure the address starts with '0x'",
+                "Verify the address is 42 characters long (including '0x')",
+                "Use a checksummed address format",
+            ],
+            UserMessage::InvalidPrivateKey => &[
+                "Ensure {privkey} is set correctly in your .env file",
+                "The private key should be a 64-character hex string",
+                "Do not include the '0x' prefix",
+            ],
+            UserMessage::Unknown => &[
+                "Check server status with '{name} status'",
+                "Verify your configuration in .env file",
+                "Use -v flag for more detailed output",
+            ],
+        };
+        hints
+            .iter()
+            .map(|h| h.replace("{name}", name).replace("{privkey}", privkey))
+            .collect()
+    }
+
+    /// The full message: `Failed to {context}: {title}`, then the
+    /// explanation, causes and hints. `context` is a short verb phrase
+    /// naming the operation (e.g. `"fetch balance"`).
+    pub fn render(self, context: &str, binary: &str, privkey_env: &str) -> String {
+        let mut out = match self.title() {
+            Some(title) => format!("Failed to {context}: {title}"),
+            None => format!("Failed to {context}"),
+        };
+        if let Some(explanation) = self.explanation(privkey_env) {
+            out.push_str("\n\n");
+            out.push_str(&explanation);
+        }
+        let causes = self.causes();
+        if !causes.is_empty() {
+            out.push_str("\n\nPossible causes:");
+            for cause in causes {
+                out.push_str("\n- ");
+                out.push_str(cause);
+            }
+        }
+        out.push_str("\n\nHints:");
+        for hint in self.hints(binary, privkey_env) {
+            out.push_str("\n- ");
+            out.push_str(&hint);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specific_messages_beat_the_error_kind() {
+        // A NotFound error about a chain gets the chain hints, not the
+        // generic 404 ones.
+        let err = AspensError::not_found("Chain 'flare-mainnet' not found in configuration");
+        assert_eq!(user_message(&err), UserMessage::ChainNotFound);
+
+        let err = AspensError::insufficient_balance("insufficient funds for gas * price + value");
+        assert_eq!(user_message(&err), UserMessage::InsufficientGas);
+
+        let err = AspensError::from(tonic::Status::unauthenticated("token expired"));
+        assert_eq!(user_message(&err), UserMessage::Auth);
+    }
+
+    #[test]
+    fn the_kind_decides_when_the_text_does_not() {
+        assert_eq!(
+            user_message(&AspensError::connection("stream closed")),
+            UserMessage::ConnectionRefused
+        );
+        assert_eq!(
+            user_message(&AspensError::chain("nonce too low")),
+            UserMessage::TransactionFailed
+        );
+        assert_eq!(
+            user_message(&AspensError::other("something exotic")),
+            UserMessage::Unknown
+        );
+
+        // Also through context added on the way.
+        let report = eyre::Report::new(AspensError::auth("no")).wrap_err("list orders");
+        assert_eq!(UserMessage::of(report.as_ref()), UserMessage::Auth);
+    }
+
+    #[test]
+    fn root_causes_are_read() {
+        let report = eyre::Report::new(std::io::Error::other("Connection refused (os error 111)"))
+            .wrap_err("transport error");
+        assert_eq!(
+            UserMessage::of(report.as_ref()),
+            UserMessage::ConnectionRefused
+        );
+    }
+
+    #[test]
+    fn renders_binary_and_key_variable() {
+        let text = UserMessage::NotAdmin.render("log in", "aspens-admin", "ADMIN_PRIVKEY");
+        assert!(text.starts_with("Failed to log in: Address is not authorized as admin\n\n"));
+        assert!(text.contains("derived from ADMIN_PRIVKEY is not registered"));
+        assert!(text.contains("\n\nPossible causes:\n- Using the wrong private key"));
+        assert!(text.contains("- Run 'aspens-admin admin-public-key'"));
+
+        let text = UserMessage::Unknown.render("do thing", "aspens-repl", "TRADER_PRIVKEY");
+        assert_eq!(
+            text,
+            "Failed to do thing\n\n\
+             Hints:\n\
+             - Check server status with 'aspens-repl status'\n\
+             - Verify your configuration in .env file\n\
+             - Use -v flag for more detailed output"
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! Builders for the protobuf types tests keep constructing by hand.
+//!
+//! A [`GetConfigResponse`] with two chains, their token maps and a market
+//! takes fifty lines of struct literals; [`ConfigFixture::two_chain_usdc`]
+//! is one call, and the builder methods add or adjust the parts a test
+//! cares about. [`OrderFixture`] does the same for [`OrderRequest`]s and
+//! the [`SendOrderResponse`] a stack would answer with, and
+//! [`book_entry`] / [`trade`] build stream events.
+//!
+//! Compiled for the crate's own tests and, behind the `test-util` feature,
+//! for downstream projects:
+//!
+//! ```
+//! use aspens::fixtures::{ConfigFixture, OrderFixture};
+//!
+//! let config = ConfigFixture::two_chain_usdc().build();
+//! let order = OrderFixture::limit_buy().quantity("2.5").build();
+//! assert_eq!(order.market_id, ConfigFixture::TWO_CHAIN_MARKET_ID);
+//! assert!(config.get_market_by_id(&order.market_id).is_some());
+//! ```
+
+use crate::api::OrderRequest;
+use crate::commands::config::config_pb::{
+    Chain, Configuration, GetConfigResponse, Market, MarketFees, MarketLimits, Token, TradeContract,
+};
+use crate::commands::trading::send_order::arborter_pb::{Order, SendOrderResponse};
+use crate::commands::trading::stream_orderbook::arborter_pb::{OrderState, OrderbookEntry, Side};
+use crate::commands::trading::stream_trades::arborter_pb::Trade;
+
+/// Builds a [`Chain`] and its tokens.
+#[derive(Debug, Clone)]
+pub struct ChainFixture {
+    chain: Chain,
+}
+
+impl ChainFixture {
+    /// An EVM chain named `network` with `chain_id`, a local RPC URL and no
+    /// tokens.
+    pub fn evm(network: &str, chain_id: u32) -> Self {
+        Self {
+            chain: Chain {
+                architecture: "evm".into(),
+                canonical_name: network.into(),
+                network: network.into(),
+                chain_id,
+                rpc_url: "http://localhost:8545".into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// A Solana cluster named `network`.
+    pub fn solana(network: &str, chain_id: u32) -> Self {
+        let mut fixture = Self::evm(network, chain_id);
+        fixture.chain.architecture = "solana".into();
+        fixture.chain.rpc_url = "http://localhost:8899".into();
+        fixture
+    }
+
+    /// Register `symbol` at `address` with `decimals`.
+    pub fn token(mut self, symbol: &str, address: &str, decimals: u32) -> Self {
+        self.chain.tokens.insert(
+            symbol.to_string(),
+            Token {
+                name: symbol.into(),
+                symbol: symbol.into(),
+                address: address.into(),
+                token_id: None,
+                decimals,
+            },
+        );
+        self
+    }
+
+    /// Set the trade contract address.
+    pub fn trade_contract(mut self, address: &str) -> Self {
+        self.chain.trade_contract = Some(TradeContract {
+            contract_id: None,
+            address: address.into(),
+        });
+        self
+    }
+
+    /// Set the factory and instance signer addresses.
+    pub fn factory(mut self, factory: &str, instance_signer: &str) -> Self {
+        self.chain.factory_address = factory.into();
+        self.chain.instance_signer_address = instance_signer.into();
+        self
+    }
+
+    /// The chain.
+    pub fn build(self) -> Chain {
+        self.chain
+    }
+}
+
+/// Builds a [`GetConfigResponse`].
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFixture {
+    chains: Vec<Chain>,
+    markets: Vec<Market>,
+}
+
+impl ConfigFixture {
+    /// Market ID of the [`two_chain_usdc`](Self::two_chain_usdc) market.
+    pub const TWO_CHAIN_MARKET_ID: &'static str = "84532::0xbase-usdc::11155420::0xop-usdc";
+
+    /// An empty configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Two EVM chains, `base-sepolia` (84532) and `op-sepolia` (11155420),
+    /// each with a 6-decimal USDC and a trade contract, and a
+    /// `USDC/USDC` market between them with 6 pair decimals.
+    pub fn two_chain_usdc() -> Self {
+        Self::new()
+            .chain(
+                ChainFixture::evm("base-sepolia", 84532)
+                    .token("USDC", "0xbase-usdc", 6)
+                    .trade_contract("0xbase-trade"),
+            )
+            .chain(
+                ChainFixture::evm("op-sepolia", 11155420)
+                    .token("USDC", "0xop-usdc", 6)
+                    .trade_contract("0xop-trade"),
+            )
+            .market("USDC@base-sepolia", "USDC@op-sepolia", 6)
+    }
+
+    /// Add a chain.
+    pub fn chain(mut self, chain: ChainFixture) -> Self {
+        self.chains.push(chain.build());
+        self
+    }
+
+    /// Add a market between two `SYMBOL@network` tokens already added with
+    /// [`chain`](Self::chain). Decimals come from the tokens and the market
+    /// ID is built like the stack's, `chain_id::address::chain_id::address`.
+    ///
+    /// # Panics
+    /// If either token isn't on a chain added before.
+    pub fn market(mut self, base: &str, quote: &str, pair_decimals: i32) -> Self {
+        let (base_chain, base_token) = self.find(base);
+        let (quote_chain, quote_token) = self.find(quote);
+        self.markets.push(Market {
+            name: format!("{}/{}", base_token.symbol, quote_token.symbol),
+            market_id: format!(
+                "{}::{}::{}::{}",
+                base_chain.chain_id, base_token.address, quote_chain.chain_id, quote_token.address
+            ),
+            base_chain_network: base_chain.network.clone(),
+            quote_chain_network: quote_chain.network.clone(),
+            base_chain_token_symbol: base_token.symbol.clone(),
+            quote_chain_token_symbol: quote_token.symbol.clone(),
+            base_chain_token_decimals: base_token.decimals as i32,
+            quote_chain_token_decimals: quote_token.decimals as i32,
+            pair_decimals,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Set maker and taker fees on the most recently added market.
+    ///
+    /// # Panics
+    /// If no market was added yet.
+    pub fn fees(mut self, maker_bps: u32, taker_bps: u32) -> Self {
+        let market = self
+            .markets
+            .last_mut()
+            .expect("add a market before its fees");
+        market.fees = Some(MarketFees {
+            maker_bps,
+            taker_bps,
+        });
+        self
+    }
+
+    /// Set tick size, lot size and minimum notional (decimal strings, empty
+    /// for none) on the most recently added market.
+    ///
+    /// # Panics
+    /// If no market was added yet.
+    pub fn limits(mut self, tick_size: &str, lot_size: &str, min_notional: &str) -> Self {
+        let market = self
+            .markets
+            .last_mut()
+            .expect("add a market before its limits");
+        market.limits = Some(MarketLimits {
+            tick_size: tick_size.to_string(),
+            lot_size: lot_size.to_string(),
+            min_notional: min_notional.to_string(),
+        });
+        self
+    }
+
+    /// The configuration.
+    pub fn build(self) -> GetConfigResponse {
+        GetConfigResponse {
+            config: Some(Configuration {
+                chains: self.chains,
+                markets: self.markets,
+                maintenance: None,
+                signing_versions: vec![],
+            }),
+        }
+    }
+
+    fn find(&self, token: &str) -> (Chain, Token) {
+        let (symbol, network) = token
+            .split_once('@')
+            .unwrap_or_else(|| panic!("expected SYMBOL@network, got '{token}'"));
+        self.chains
+            .iter()
+            .find(|c| c.network == network)
+            .and_then(|c| Some((c.clone(), c.tokens.get(symbol)?.clone())))
+            .unwrap_or_else(|| panic!("no token '{token}' in the fixture's chains"))
+    }
+}
+
+/// Builds an [`OrderRequest`] on the
+/// [`two_chain_usdc`](ConfigFixture::two_chain_usdc) market unless told
+/// otherwise.
+#[derive(Debug, Clone)]
+pub struct OrderFixture {
+    order: OrderRequest,
+}
+
+impl OrderFixture {
+    /// Buy 1 at 1.00.
+    pub fn limit_buy() -> Self {
+        Self::with_side(Side::Bid, Some("1.00"))
+    }
+
+    /// Sell 1 at 1.00.
+    pub fn limit_sell() -> Self {
+        Self::with_side(Side::Ask, Some("1.00"))
+    }
+
+    /// Buy 1 at market.
+    pub fn market_buy() -> Self {
+        Self::with_side(Side::Bid, None)
+    }
+
+    /// Sell 1 at market.
+    pub fn market_sell() -> Self {
+        Self::with_side(Side::Ask, None)
+    }
+
+    fn with_side(side: Side, price: Option<&str>) -> Self {
+        Self {
+            order: OrderRequest {
+                market_id: ConfigFixture::TWO_CHAIN_MARKET_ID.into(),
+                side: side as i32,
+                quantity: "1".into(),
+                price: price.map(str::to_string),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Trade on `market_id` instead.
+    pub fn market(mut self, market_id: &str) -> Self {
+        self.order.market_id = market_id.into();
+        self
+    }
+
+    /// Set the quantity (decimal string).
+    pub fn quantity(mut self, quantity: &str) -> Self {
+        self.order.quantity = quantity.into();
+        self
+    }
+
+    /// Set the limit price (decimal string).
+    pub fn price(mut self, price: &str) -> Self {
+        self.order.price = Some(price.into());
+        self
+    }
+
+    /// Make the order post-only.
+    pub fn post_only(mut self) -> Self {
+        self.order.post_only = true;
+        self
+    }
+
+    /// Set the client order ID.
+    pub fn client_order_id(mut self, client_order_id: &str) -> Self {
+        self.order.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    /// The order request.
+    pub fn build(self) -> OrderRequest {
+        self.order
+    }
+
+    /// What the stack answers when it rests the order as `order_id`
+    /// without matching it.
+    pub fn accepted(&self, order_id: u64) -> SendOrderResponse {
+        SendOrderResponse {
+            order_in_book: true,
+            order: Some(Order {
+                side: self.order.side,
+                quantity: self.order.quantity.clone(),
+                price: self.order.price.clone(),
+                market_id: self.order.market_id.clone(),
+                client_order_id: self.order.client_order_id.clone().unwrap_or_default(),
+                ..Default::default()
+            }),
+            order_id,
+            client_order_id: self.order.client_order_id.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A confirmed orderbook event for `order_id`. Prices and quantities are
+/// raw pair-decimal strings.
+pub fn book_entry(order_id: u64, side: Side, price: &str, quantity: &str) -> OrderbookEntry {
+    OrderbookEntry {
+        order_id,
+        side: side as i32,
+        price: price.into(),
+        quantity: quantity.into(),
+        state: OrderState::Confirmed as i32,
+        ..Default::default()
+    }
+}
+
+/// A trade at unix milliseconds `timestamp`. Price and quantity are raw
+/// pair-decimal strings.
+pub fn trade(timestamp: u64, price: &str, qty: &str) -> Trade {
+    Trade {
+        timestamp,
+        price: price.into(),
+        qty: qty.into(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_chain_config_resolves_its_market_and_tokens() {
+        let config = ConfigFixture::two_chain_usdc().fees(2, 5).build();
+        let market = config
+            .get_market_by_id(ConfigFixture::TWO_CHAIN_MARKET_ID)
+            .unwrap();
+        assert_eq!(market.name, "USDC/USDC");
+        assert_eq!(market.fees.as_ref().unwrap().taker_bps, 5);
+        assert_eq!(config.get_token("op-sepolia", "USDC").unwrap().decimals, 6);
+        assert_eq!(config.get_chain("base-sepolia").unwrap().chain_id, 84532);
+    }
+
+    #[test]
+    fn order_fixture_fills_in_defaults() {
+        let fixture = OrderFixture::limit_sell().price("1.02").post_only();
+        let response = fixture.accepted(7);
+        let order = fixture.build();
+        assert_eq!(order.side, Side::Ask as i32);
+        assert_eq!(order.price.as_deref(), Some("1.02"));
+        assert!(order.post_only);
+        assert_eq!(response.order_id, 7);
+        assert!(OrderFixture::market_buy().build().price.is_none());
+    }
+}
@@ -2,23 +2,244 @@
 //!
 //! This module provides helpers for creating gRPC channels that work with both
 //! HTTP (local/development) and HTTPS (remote/production) endpoints.
+//!
+//! Message size limits and compression are per-client rather than per-channel
+//! in tonic, so they're carried by [`GrpcOptions`] and applied to a generated
+//! client with [`GrpcOptions::apply`]. HTTP/2 keepalive is per-channel:
+//! [`GrpcOptions::keepalive`] is applied by [`create_channel_with`].
+//!
+//! Channels are pooled: every command dialing the same URL with the same
+//! keepalive shares one HTTP/2 connection, and tonic multiplexes their
+//! requests and streams over it and reconnects it when it drops. See
+//! [`create_channel_with`] for when a fresh connection is dialed instead.
 
-use eyre::{Context, Result};
+use eyre::{Context, Result, eyre};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
 use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tonic::codec::CompressionEncoding;
 use tonic::transport::{Channel, ClientTlsConfig};
 
 /// Default timeout for gRPC operations (1 minute)
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// tonic's default limit on a decoded message (4 MiB).
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Decoded-message limit for historical replays (64 MiB). A stack replaying a
+/// busy market's open orders or closed trades can exceed the default.
+pub const HISTORICAL_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Default interval between HTTP/2 keepalive pings.
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Default time to wait for a keepalive ping's ack before closing the
+/// connection.
+pub const DEFAULT_KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// HTTP/2 keepalive pings on a channel. They keep NATs and load balancers
+/// from reaping a quiet stream's connection, and detect a dead one: a ping
+/// left unanswered for `timeout` closes the connection, failing its streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeepAlive {
+    /// Time between pings.
+    pub interval: Duration,
+    /// Time to wait for a ping's ack.
+    pub timeout: Duration,
+    /// Also ping while no request or stream is open.
+    pub while_idle: bool,
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_KEEPALIVE_INTERVAL,
+            timeout: DEFAULT_KEEPALIVE_TIMEOUT,
+            while_idle: true,
+        }
+    }
+}
+
+/// A gRPC message compression algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// gzip: supported by every tonic server with compression enabled.
+    Gzip,
+    /// zstd: better ratio and speed, if the server supports it.
+    Zstd,
+}
+
+impl From<Compression> for CompressionEncoding {
+    fn from(compression: Compression) -> Self {
+        match compression {
+            Compression::Gzip => CompressionEncoding::Gzip,
+            Compression::Zstd => CompressionEncoding::Zstd,
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(eyre!("unknown compression '{}': expected gzip or zstd", s)),
+        }
+    }
+}
+
+/// Per-client message size and compression settings.
+///
+/// The default matches tonic's: 4 MiB decoded messages and no compression.
+/// [`GrpcOptions::historical`] is what the orderbook and trades streams use
+/// when asked to replay history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrpcOptions {
+    /// Largest message the client will decode, in bytes.
+    pub max_decoding_message_size: usize,
+    /// Largest message the client will encode, in bytes.
+    pub max_encoding_message_size: usize,
+    /// Ask the server to compress responses with this algorithm. Servers
+    /// that don't support it answer uncompressed.
+    pub compression: Option<Compression>,
+    /// Also compress requests with [`compression`](Self::compression). Only
+    /// set this if the server is known to accept it; it rejects otherwise.
+    pub compress_requests: bool,
+    /// HTTP/2 keepalive pings for channels built with
+    /// [`create_channel_with`].
+    pub keepalive: KeepAlive,
+}
+
+impl Default for GrpcOptions {
+    fn default() -> Self {
+        Self {
+            max_decoding_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_encoding_message_size: usize::MAX,
+            compression: None,
+            compress_requests: false,
+            keepalive: KeepAlive::default(),
+        }
+    }
+}
+
+impl GrpcOptions {
+    /// Settings for historical replays: a
+    /// [`HISTORICAL_MAX_MESSAGE_SIZE`] decode limit and gzip responses.
+    pub fn historical() -> Self {
+        Self {
+            max_decoding_message_size: HISTORICAL_MAX_MESSAGE_SIZE,
+            compression: Some(Compression::Gzip),
+            ..Self::default()
+        }
+    }
+
+    /// Set the decoded-message limit.
+    pub fn with_max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.max_decoding_message_size = limit;
+        self
+    }
+
+    /// Set the encoded-message limit.
+    pub fn with_max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.max_encoding_message_size = limit;
+        self
+    }
+
+    /// Ask for compressed responses.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Set the HTTP/2 keepalive pings.
+    pub fn with_keepalive(mut self, keepalive: KeepAlive) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    /// Apply the settings to a generated tonic client, e.g.
+    /// `options.apply(ArborterServiceClient::new(channel))`.
+    pub fn apply<C: ConfigurableClient>(&self, client: C) -> C {
+        let mut client = client
+            .max_decoding_message_size(self.max_decoding_message_size)
+            .max_encoding_message_size(self.max_encoding_message_size);
+        if let Some(compression) = self.compression {
+            client = client.accept_compressed(compression.into());
+            if self.compress_requests {
+                client = client.send_compressed(compression.into());
+            }
+        }
+        client
+    }
+}
+
+/// The builder methods every tonic-generated client has, so
+/// [`GrpcOptions::apply`] works with any of them.
+pub trait ConfigurableClient: Sized {
+    /// Limit the size of a decoded message.
+    fn max_decoding_message_size(self, limit: usize) -> Self;
+    /// Limit the size of an encoded message.
+    fn max_encoding_message_size(self, limit: usize) -> Self;
+    /// Enable decompressing responses.
+    fn accept_compressed(self, encoding: CompressionEncoding) -> Self;
+    /// Compress requests.
+    fn send_compressed(self, encoding: CompressionEncoding) -> Self;
+}
+
+/// Implement [`ConfigurableClient`] for generated clients by forwarding to
+/// their inherent methods. Each module that `include!`s the bindings gets
+/// its own client types, so each names the ones it uses.
+macro_rules! impl_configurable_client {
+    ($($client:ty),+ $(,)?) => {
+        $(
+            impl crate::grpc::ConfigurableClient for $client {
+                fn max_decoding_message_size(self, limit: usize) -> Self {
+                    <$client>::max_decoding_message_size(self, limit)
+                }
+                fn max_encoding_message_size(self, limit: usize) -> Self {
+                    <$client>::max_encoding_message_size(self, limit)
+                }
+                fn accept_compressed(
+                    self,
+                    encoding: tonic::codec::CompressionEncoding,
+                ) -> Self {
+                    <$client>::accept_compressed(self, encoding)
+                }
+                fn send_compressed(self, encoding: tonic::codec::CompressionEncoding) -> Self {
+                    <$client>::send_compressed(self, encoding)
+                }
+            }
+        )+
+    };
+}
+pub(crate) use impl_configurable_client;
+
 /// Create a gRPC channel that automatically configures TLS for HTTPS URLs.
 ///
 /// This function detects whether the URL uses `https://` and configures
 /// TLS accordingly. For `http://` URLs, it connects without TLS.
 ///
 /// The channel is configured with:
-/// - 2 minute operation timeout
+/// - 1 minute operation timeout
 /// - 10 second connection timeout
-/// - HTTP/2 keep-alive to prevent connection drops
+/// - HTTP/2 keep-alive to prevent connection drops ([`KeepAlive::default`])
+///
+/// Repeated calls for the same URL share one pooled connection; see
+/// [`create_channel_with`].
 ///
 /// # Arguments
 /// * `url` - The gRPC server URL (e.g., "http://localhost:50051" or "<https://grpc.example.com:50051>")
@@ -37,6 +258,97 @@ const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
 /// let channel = create_channel("https://grpc.example.com:50051").await?;
 /// ```
 pub async fn create_channel(url: &str) -> Result<Channel> {
+    create_channel_with(url, KeepAlive::default()).await
+}
+
+/// [`create_channel`] with explicit HTTP/2 keepalive settings, for
+/// long-lived streams that want to notice a dead connection sooner (or ping
+/// less often).
+///
+/// The channel comes from the pool when one was already dialed to `url`
+/// with the same `keepalive` on a live multi-threaded runtime. On a
+/// current-thread runtime (`#[tokio::test]`, one-off `block_on`s) a fresh
+/// connection is dialed each time, since that runtime only drives the
+/// connection while it is itself blocked on something.
+pub async fn create_channel_with(url: &str, keepalive: KeepAlive) -> Result<Channel> {
+    let pooled = Handle::try_current()
+        .is_ok_and(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread);
+    if !pooled {
+        return connect(url, keepalive).await;
+    }
+    let key = (url.to_string(), keepalive);
+    if let Some(channel) = pooled_channel(&key) {
+        return Ok(channel);
+    }
+    let channel = connect(url, keepalive).await?;
+    Ok(pool_channel(key, channel))
+}
+
+/// Drop every pooled channel. Connections close once the commands still
+/// using them finish; the next command dials afresh.
+pub fn clear_channel_pool() {
+    if let Some(pool) = POOL.get() {
+        pool.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+type PoolKey = (String, KeepAlive);
+
+/// A pooled channel and a marker that lives exactly as long as the runtime
+/// driving its connection.
+struct PoolEntry {
+    channel: Channel,
+    runtime: Weak<()>,
+}
+
+/// Channels shared by [`create_channel_with`].
+static POOL: OnceLock<Mutex<HashMap<PoolKey, PoolEntry>>> = OnceLock::new();
+
+fn pool() -> std::sync::MutexGuard<'static, HashMap<PoolKey, PoolEntry>> {
+    POOL.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// The pooled channel for `key`, unless the runtime it was dialed on has
+/// shut down (taking the connection's background task with it).
+fn pooled_channel(key: &PoolKey) -> Option<Channel> {
+    let mut pool = pool();
+    match pool.get(key) {
+        Some(entry) if entry.runtime.strong_count() > 0 => Some(entry.channel.clone()),
+        Some(_) => {
+            pool.remove(key);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Pool `channel` under `key` and return it, or return the channel another
+/// task pooled while this one was dialing.
+fn pool_channel(key: PoolKey, channel: Channel) -> Channel {
+    if let Some(existing) = pooled_channel(&key) {
+        return existing;
+    }
+    // Parked on the current runtime; dropped with it when it shuts down.
+    let alive = Arc::new(());
+    let runtime = Arc::downgrade(&alive);
+    tokio::spawn(async move {
+        let _alive = alive;
+        std::future::pending::<()>().await
+    });
+    pool().insert(
+        key,
+        PoolEntry {
+            channel: channel.clone(),
+            runtime,
+        },
+    );
+    channel
+}
+
+/// Dial `url`: TLS for `https://`, the default timeouts, and `keepalive`.
+async fn connect(url: &str, keepalive: KeepAlive) -> Result<Channel> {
     let is_https = url.starts_with("https://");
 
     let endpoint = Channel::from_shared(url.to_string())
@@ -44,9 +356,9 @@ pub async fn create_channel(url: &str) -> Result<Channel> {
         .timeout(DEFAULT_TIMEOUT)
         .connect_timeout(Duration::from_secs(10))
         // HTTP/2 keep-alive settings to prevent "h2 protocol error" issues
-        .http2_keep_alive_interval(Duration::from_secs(10))
-        .keep_alive_timeout(Duration::from_secs(20))
-        .keep_alive_while_idle(true);
+        .http2_keep_alive_interval(keepalive.interval)
+        .keep_alive_timeout(keepalive.timeout)
+        .keep_alive_while_idle(keepalive.while_idle);
 
     let endpoint = if is_https {
         // Configure TLS for HTTPS connections
@@ -66,6 +378,41 @@ pub async fn create_channel(url: &str) -> Result<Channel> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn historical_options_raise_the_limit_and_accept_gzip() {
+        let options = GrpcOptions::historical();
+        assert_eq!(
+            options.max_decoding_message_size,
+            HISTORICAL_MAX_MESSAGE_SIZE
+        );
+        assert_eq!(options.compression, Some(Compression::Gzip));
+        assert!(!options.compress_requests);
+        assert_eq!(
+            GrpcOptions::default().max_decoding_message_size,
+            DEFAULT_MAX_MESSAGE_SIZE
+        );
+        assert_eq!("ZSTD".parse::<Compression>().unwrap(), Compression::Zstd);
+        assert!("brotli".parse::<Compression>().is_err());
+    }
+
+    #[test]
+    fn pooled_channels_are_shared_until_their_runtime_shuts_down() {
+        let key = ("http://127.0.0.1:1".to_string(), KeepAlive::default());
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let channel = Channel::from_static("http://127.0.0.1:1").connect_lazy();
+            pool_channel(key.clone(), channel);
+            assert!(pooled_channel(&key).is_some());
+        });
+        drop(rt);
+        assert!(pooled_channel(&key).is_none());
+    }
+
     #[test]
     fn test_https_detection() {
         assert!("https://example.com:50051".starts_with("https://"));
@@ -0,0 +1,651 @@
+//! Local journal of trading activity, kept in SQLite.
+//!
+//! The stack prunes what it keeps and only streams the present, so a trader
+//! who wants to know what they did last month needs their own record. A
+//! [`Journal`] is a SQLite database of [`JournalEntry`]s: each order
+//! submitted, cancel, fill reported back by a submission, deposit and
+//! withdrawal. [`Journaled`] wraps any [`AspensApi`] and records its orders,
+//! cancels and fills as they go through; deposits and withdrawals, which
+//! aren't on the trait, are recorded with [`Journal::record_transfer`].
+//! [`Journal::query`] reads entries back, newest first.
+//!
+//! The default database is [`JOURNAL_FILE`] in the profile directory (see
+//! [`storage`](crate::storage)). It is a plain SQLite file: profile
+//! encryption doesn't cover it.
+//!
+//! A failure to write the journal never fails the trade it records;
+//! [`Journaled`] logs it and moves on.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use comfy_table::{Table, presets::UTF8_BORDERS_ONLY};
+use eyre::{Result, eyre};
+use rusqlite::{Connection, params, params_from_iter};
+use tracing::warn;
+
+use crate::api::{AspensApi, OrderRequest, StreamHandle};
+use crate::commands::config::config_pb::{GetConfigResponse, Market};
+use crate::commands::trading::balance::BalanceSnapshot;
+use crate::commands::trading::cancel_order::arborter_pb::CancelOrderResponse;
+use crate::commands::trading::send_order::{self, arborter_pb::SendOrderResponse};
+use crate::commands::trading::stream_orderbook::{
+    StreamOrderbookOptions, arborter_pb::OrderbookEntry,
+};
+use crate::commands::trading::stream_trades::{StreamTradesOptions, arborter_pb::Trade};
+use crate::decimals::format_decimal_amount;
+use crate::storage::FsStorage;
+use crate::util::{format_utc, unix_now_ms};
+use crate::wallet::Wallet;
+
+/// File name of the journal in the profile directory.
+pub const JOURNAL_FILE: &str = "journal.sqlite3";
+
+/// Version of the table layout, kept in SQLite's `user_version`.
+const SCHEMA_VERSION: i32 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        id           INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp_ms INTEGER NOT NULL,
+        kind         TEXT NOT NULL,
+        market_id    TEXT,
+        network      TEXT,
+        token        TEXT,
+        side         TEXT,
+        order_id     INTEGER,
+        quantity     TEXT,
+        price        TEXT,
+        tx_hashes    TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX IF NOT EXISTS entries_by_time ON entries (timestamp_ms);
+";
+
+/// What a [`JournalEntry`] records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    /// An order accepted by the stack.
+    Order,
+    /// A cancel the stack acknowledged.
+    Cancel,
+    /// A fill reported back when an order was submitted.
+    Fill,
+    /// A deposit into the trade contract.
+    Deposit,
+    /// A withdrawal from the trade contract.
+    Withdraw,
+}
+
+impl EntryKind {
+    /// The name stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Order => "order",
+            Self::Cancel => "cancel",
+            Self::Fill => "fill",
+            Self::Deposit => "deposit",
+            Self::Withdraw => "withdraw",
+        }
+    }
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EntryKind {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "order" => Ok(Self::Order),
+            "cancel" => Ok(Self::Cancel),
+            "fill" => Ok(Self::Fill),
+            "deposit" => Ok(Self::Deposit),
+            "withdraw" => Ok(Self::Withdraw),
+            other => Err(eyre!(
+                "unknown journal entry kind '{}': use order, cancel, fill, deposit or withdraw",
+                other
+            )),
+        }
+    }
+}
+
+/// One recorded action. Amounts are decimal strings, as the trader would
+/// type them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// Row ID; 0 until recorded.
+    pub id: i64,
+    /// When it was recorded, unix milliseconds.
+    pub timestamp_ms: u64,
+    /// What happened.
+    pub kind: EntryKind,
+    /// Market of an order, cancel or fill.
+    pub market_id: Option<String>,
+    /// Chain of a deposit or withdrawal.
+    pub network: Option<String>,
+    /// Token symbol of a deposit or withdrawal.
+    pub token: Option<String>,
+    /// `buy` or `sell`.
+    pub side: Option<String>,
+    /// The order placed, canceled or hit.
+    pub order_id: Option<u64>,
+    /// Quantity ordered, filled or transferred.
+    pub quantity: Option<String>,
+    /// Limit or fill price.
+    pub price: Option<String>,
+    /// Settlement transactions the stack reported, as `type:hash`.
+    pub tx_hashes: Vec<String>,
+}
+
+impl JournalEntry {
+    /// An empty entry of `kind`, stamped now.
+    pub fn new(kind: EntryKind) -> Self {
+        Self {
+            id: 0,
+            timestamp_ms: unix_now_ms(),
+            kind,
+            market_id: None,
+            network: None,
+            token: None,
+            side: None,
+            order_id: None,
+            quantity: None,
+            price: None,
+            tx_hashes: Vec::new(),
+        }
+    }
+}
+
+/// Which entries [`Journal::query`] returns. The default is every entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JournalQuery {
+    /// Only entries of this kind.
+    pub kind: Option<EntryKind>,
+    /// Only entries on this market.
+    pub market_id: Option<String>,
+    /// Only entries at or after this unix millisecond.
+    pub since_ms: Option<u64>,
+    /// Only entries at or before this unix millisecond.
+    pub until_ms: Option<u64>,
+    /// At most this many entries, newest first.
+    pub limit: Option<u32>,
+}
+
+/// A SQLite journal of trading activity.
+#[derive(Debug)]
+pub struct Journal {
+    conn: Mutex<Connection>,
+}
+
+impl Journal {
+    /// Open the journal at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| eyre!("failed to open journal {}: {}", path.display(), e))?;
+        Self::init(conn)
+    }
+
+    /// Open [`JOURNAL_FILE`] in the profile directory.
+    pub fn open_default() -> Result<Self> {
+        Self::open(FsStorage::open_default()?.root().join(JOURNAL_FILE))
+    }
+
+    /// A journal that lives only as long as the value, for tests.
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(eyre!(
+                "journal was written by a newer release (version {}, this release reads {})",
+                version,
+                SCHEMA_VERSION
+            ));
+        }
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Record `entry` and return its row ID.
+    pub fn record(&self, entry: &JournalEntry) -> Result<i64> {
+        let conn = self.conn.lock().expect("journal lock poisoned");
+        conn.execute(
+            "INSERT INTO entries (timestamp_ms, kind, market_id, network, token, side, \
+             order_id, quantity, price, tx_hashes) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                entry.timestamp_ms as i64,
+                entry.kind.as_str(),
+                entry.market_id,
+                entry.network,
+                entry.token,
+                entry.side,
+                entry.order_id.map(|id| id as i64),
+                entry.quantity,
+                entry.price,
+                entry.tx_hashes.join(","),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record an accepted `order` on `market` and each fill in `response`.
+    /// Entries carry the full market ID even if the order used a shorthand;
+    /// fill prices and quantities are scaled by the market's pair decimals.
+    pub fn record_order(
+        &self,
+        order: &OrderRequest,
+        market: &Market,
+        response: &SendOrderResponse,
+    ) -> Result<()> {
+        let pair_decimals = market.pair_decimals as u32;
+        let side = side_name(order.side);
+        let tx_hashes: Vec<String> = response
+            .transaction_hashes
+            .iter()
+            .map(|h| format!("{}:{}", h.hash_type, h.hash_value))
+            .collect();
+        self.record(&JournalEntry {
+            market_id: Some(market.market_id.clone()),
+            side: side.clone(),
+            order_id: Some(response.order_id),
+            quantity: Some(order.quantity.clone()),
+            price: order.price.clone(),
+            tx_hashes: tx_hashes.clone(),
+            ..JournalEntry::new(EntryKind::Order)
+        })?;
+        for trade in &response.trades {
+            let scaled = |raw: &str| {
+                raw.parse()
+                    .map(|raw| format_decimal_amount(raw, pair_decimals))
+                    .unwrap_or_else(|_| raw.to_string())
+            };
+            self.record(&JournalEntry {
+                market_id: Some(market.market_id.clone()),
+                side: side.clone(),
+                order_id: Some(trade.order_hit),
+                quantity: Some(scaled(&trade.qty)),
+                price: Some(scaled(&trade.price)),
+                tx_hashes: tx_hashes.clone(),
+                ..JournalEntry::new(EntryKind::Fill)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Record the cancel of `order_id`, if the stack canceled it. `side`
+    /// accepts `buy`/`bid`/`sell`/`ask`.
+    pub fn record_cancel(
+        &self,
+        market_id: &str,
+        side: &str,
+        order_id: u64,
+        response: &CancelOrderResponse,
+    ) -> Result<()> {
+        if !response.order_canceled {
+            return Ok(());
+        }
+        self.record(&JournalEntry {
+            market_id: Some(market_id.to_string()),
+            side: side_name(send_order::parse_side(side)? as i32),
+            order_id: Some(order_id),
+            tx_hashes: response
+                .transaction_hashes
+                .iter()
+                .map(|h| format!("{}:{}", h.hash_type, h.hash_value))
+                .collect(),
+            ..JournalEntry::new(EntryKind::Cancel)
+        })?;
+        Ok(())
+    }
+
+    /// Record a deposit or withdrawal of `amount` (decimal) `token` on
+    /// `network`.
+    pub fn record_transfer(
+        &self,
+        kind: EntryKind,
+        network: &str,
+        token: &str,
+        amount: &str,
+    ) -> Result<()> {
+        if !matches!(kind, EntryKind::Deposit | EntryKind::Withdraw) {
+            return Err(eyre!("{} is not a transfer", kind));
+        }
+        self.record(&JournalEntry {
+            network: Some(network.to_string()),
+            token: Some(token.to_string()),
+            quantity: Some(amount.to_string()),
+            ..JournalEntry::new(kind)
+        })?;
+        Ok(())
+    }
+
+    /// Entries matching `query`, newest first.
+    pub fn query(&self, query: &JournalQuery) -> Result<Vec<JournalEntry>> {
+        let mut clauses = Vec::new();
+        let mut args: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(kind) = query.kind {
+            clauses.push("kind = ?");
+            args.push(kind.as_str().to_string().into());
+        }
+        if let Some(market_id) = &query.market_id {
+            clauses.push("market_id = ?");
+            args.push(market_id.clone().into());
+        }
+        if let Some(since) = query.since_ms {
+            clauses.push("timestamp_ms >= ?");
+            args.push((since as i64).into());
+        }
+        if let Some(until) = query.until_ms {
+            clauses.push("timestamp_ms <= ?");
+            args.push((until as i64).into());
+        }
+        let mut sql = "SELECT id, timestamp_ms, kind, market_id, network, token, side, \
+                       order_id, quantity, price, tx_hashes FROM entries"
+            .to_string();
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+        sql.push_str(" ORDER BY timestamp_ms DESC, id DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let conn = self.conn.lock().expect("journal lock poisoned");
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(args), |row| {
+            let kind: String = row.get(2)?;
+            let tx_hashes: String = row.get(10)?;
+            Ok((
+                kind,
+                JournalEntry {
+                    id: row.get(0)?,
+                    timestamp_ms: row.get::<_, i64>(1)? as u64,
+                    kind: EntryKind::Order,
+                    market_id: row.get(3)?,
+                    network: row.get(4)?,
+                    token: row.get(5)?,
+                    side: row.get(6)?,
+                    order_id: row.get::<_, Option<i64>>(7)?.map(|id| id as u64),
+                    quantity: row.get(8)?,
+                    price: row.get(9)?,
+                    tx_hashes: tx_hashes
+                        .split(',')
+                        .filter(|h| !h.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                },
+            ))
+        })?;
+        rows.map(|row| -> Result<JournalEntry> {
+            let (kind, entry) = row?;
+            Ok(JournalEntry {
+                kind: kind.parse()?,
+                ..entry
+            })
+        })
+        .collect()
+    }
+
+    /// The most recent entry, if any.
+    pub fn last(&self) -> Result<Option<JournalEntry>> {
+        Ok(self
+            .query(&JournalQuery {
+                limit: Some(1),
+                ..JournalQuery::default()
+            })?
+            .pop())
+    }
+}
+
+/// Render entries as a table.
+pub fn display_journal(entries: &[JournalEntry]) -> String {
+    let dash = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header(vec![
+        "Time (UTC)",
+        "Kind",
+        "Market / Token",
+        "Side",
+        "Order",
+        "Quantity",
+        "Price",
+    ]);
+    for entry in entries {
+        let place = match (&entry.market_id, &entry.network, &entry.token) {
+            (Some(market), _, _) => market.clone(),
+            (None, Some(network), Some(token)) => format!("{} on {}", token, network),
+            _ => "-".to_string(),
+        };
+        table.add_row(vec![
+            format_utc(entry.timestamp_ms / 1_000),
+            entry.kind.to_string(),
+            place,
+            dash(&entry.side),
+            entry
+                .order_id
+                .map_or_else(|| "-".to_string(), |id| id.to_string()),
+            dash(&entry.quantity),
+            dash(&entry.price),
+        ]);
+    }
+    table.to_string()
+}
+
+fn side_name(side: i32) -> Option<String> {
+    match side {
+        1 => Some("buy".to_string()),
+        2 => Some("sell".to_string()),
+        _ => None,
+    }
+}
+
+/// An [`AspensApi`] that records the orders, cancels and fills going
+/// through it in a [`Journal`].
+#[derive(Debug)]
+pub struct Journaled<A> {
+    api: A,
+    journal: Journal,
+}
+
+impl<A: AspensApi> Journaled<A> {
+    /// Record `api`'s activity in `journal`.
+    pub fn new(api: A, journal: Journal) -> Self {
+        Self { api, journal }
+    }
+
+    /// The journal being written.
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// The wrapped API.
+    pub fn inner(&self) -> &A {
+        &self.api
+    }
+
+    fn log_failure(result: Result<()>) {
+        if let Err(e) = result {
+            warn!("Could not write to the journal: {e}");
+        }
+    }
+}
+
+impl<A: AspensApi> AspensApi for Journaled<A> {
+    async fn get_config(&self) -> crate::error::Result<GetConfigResponse> {
+        self.api.get_config().await
+    }
+
+    async fn send_order(
+        &self,
+        order: OrderRequest,
+        wallets: &[&Wallet],
+    ) -> crate::error::Result<SendOrderResponse> {
+        let response = self.api.send_order(order.clone(), wallets).await?;
+        Self::log_failure(
+            self.api
+                .get_config()
+                .await
+                .map_err(eyre::Report::from)
+                .and_then(|config| {
+                    let market = send_order::lookup_market(&config, &order.market_id)?;
+                    self.journal.record_order(&order, market, &response)
+                }),
+        );
+        Ok(response)
+    }
+
+    async fn open_orders(
+        &self,
+        market_id: &str,
+        wallet: &Wallet,
+    ) -> crate::error::Result<Vec<OrderbookEntry>> {
+        self.api.open_orders(market_id, wallet).await
+    }
+
+    async fn cancel_order(
+        &self,
+        market_id: &str,
+        side: &str,
+        order_id: u64,
+        wallet: &Wallet,
+    ) -> crate::error::Result<CancelOrderResponse> {
+        let response = self
+            .api
+            .cancel_order(market_id, side, order_id, wallet)
+            .await?;
+        Self::log_failure(
+            self.journal
+                .record_cancel(market_id, side, order_id, &response),
+        );
+        Ok(response)
+    }
+
+    async fn balances(&self, wallets: &[&Wallet]) -> crate::error::Result<BalanceSnapshot> {
+        self.api.balances(wallets).await
+    }
+
+    async fn stream_orderbook(
+        &self,
+        options: StreamOrderbookOptions,
+    ) -> crate::error::Result<StreamHandle<OrderbookEntry>> {
+        self.api.stream_orderbook(options).await
+    }
+
+    async fn stream_trades(
+        &self,
+        options: StreamTradesOptions,
+    ) -> crate::error::Result<StreamHandle<Trade>> {
+        self.api.stream_trades(options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock::MockAspensApi;
+    use crate::fixtures::{ConfigFixture, OrderFixture};
+
+    #[test]
+    fn records_and_queries_newest_first() {
+        let journal = Journal::in_memory().unwrap();
+        journal
+            .record_transfer(EntryKind::Deposit, "base-sepolia", "USDC", "100")
+            .unwrap();
+        let config = ConfigFixture::two_chain_usdc().build();
+        let market = config
+            .get_market_by_id(ConfigFixture::TWO_CHAIN_MARKET_ID)
+            .unwrap();
+        let fixture = OrderFixture::limit_buy().price("0.99");
+        let mut response = fixture.accepted(4);
+        response.trades.push(send_order::arborter_pb::Trade {
+            price: "990000".into(),
+            qty: "250000".into(),
+            order_hit: 2,
+            ..Default::default()
+        });
+        let order = fixture.build();
+        journal.record_order(&order, market, &response).unwrap();
+        journal
+            .record_cancel(
+                &order.market_id,
+                "bid",
+                4,
+                &CancelOrderResponse {
+                    order_canceled: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let all = journal.query(&JournalQuery::default()).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].kind, EntryKind::Cancel);
+        assert_eq!(all[0].side.as_deref(), Some("buy"));
+        assert_eq!(all[3].token.as_deref(), Some("USDC"));
+
+        let fills = journal
+            .query(&JournalQuery {
+                kind: Some(EntryKind::Fill),
+                ..JournalQuery::default()
+            })
+            .unwrap();
+        assert_eq!(fills[0].quantity.as_deref(), Some("0.250000"));
+        assert_eq!(fills[0].order_id, Some(2));
+
+        let orders = journal
+            .query(&JournalQuery {
+                kind: Some(EntryKind::Order),
+                ..JournalQuery::default()
+            })
+            .unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].price.as_deref(), Some("0.99"));
+        assert_eq!(orders[0].order_id, Some(4));
+        assert!(
+            journal
+                .record_transfer(EntryKind::Fill, "n", "T", "1")
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn journaled_api_records_orders_and_cancels() {
+        let api = Journaled::new(
+            MockAspensApi::new(ConfigFixture::two_chain_usdc().build()),
+            Journal::in_memory().unwrap(),
+        );
+        let wallet = Wallet::from_evm_hex(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let order = OrderFixture::limit_sell().build();
+        let placed = api.send_order(order.clone(), &[&wallet]).await.unwrap();
+        api.cancel_order(&order.market_id, "sell", placed.order_id, &wallet)
+            .await
+            .unwrap();
+
+        let entries = api.journal().query(&JournalQuery::default()).unwrap();
+        let kinds: Vec<_> = entries.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, [EntryKind::Cancel, EntryKind::Order]);
+        assert_eq!(entries[1].side.as_deref(), Some("sell"));
+        assert_eq!(api.journal().last().unwrap().unwrap().id, entries[0].id);
+    }
+}
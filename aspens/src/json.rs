@@ -0,0 +1,89 @@
+//! JSON round-tripping for the generated protobuf messages.
+//!
+//! Every message under `aspens::proto` and `aspens::attestation` derives
+//! serde (camelCase field names, except the auth messages which keep
+//! snake_case), so orders, stream events and responses can be logged,
+//! cached or printed by the CLI's JSON output modes through the same two
+//! helpers.
+
+use eyre::{Result, eyre};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Serialize `value` as pretty-printed JSON.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string_pretty(value).map_err(|e| eyre!("failed to serialize as JSON: {}", e))
+}
+
+/// Serialize `value` as single-line JSON, suitable for line-delimited output.
+pub fn to_json_line<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| eyre!("failed to serialize as JSON: {}", e))
+}
+
+/// Deserialize a value previously produced by [`to_json`] or [`to_json_line`].
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    serde_json::from_str(json).map_err(|e| eyre!("failed to parse JSON: {}", e))
+}
+
+#[cfg(all(test, feature = "client", any(feature = "trader", feature = "admin")))]
+mod tests {
+    use super::*;
+    use crate::proto::config::{GetConfigResponse, SetMarketRequest};
+    use crate::proto::v1::arborter::{Order, OrderbookEntry, Side};
+
+    #[test]
+    fn order_round_trips() {
+        let order = Order {
+            side: Side::Bid as i32,
+            quantity: "1000000".into(),
+            price: Some("2500000".into()),
+            market_id: "1::0xbase::2::0xquote".into(),
+            base_account_address: "0xabc".into(),
+            quote_account_address: "0xdef".into(),
+            post_only: true,
+            ..Default::default()
+        };
+        let json = to_json(&order).unwrap();
+        assert!(json.contains("\"marketId\""));
+        assert_eq!(from_json::<Order>(&json).unwrap(), order);
+    }
+
+    #[test]
+    fn stream_event_round_trips_on_one_line() {
+        let entry = OrderbookEntry {
+            timestamp: 1_700_000_000,
+            order_id: 42,
+            quantity: "5".into(),
+            price: "10".into(),
+            ..Default::default()
+        };
+        let line = to_json_line(&entry).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(from_json::<OrderbookEntry>(&line).unwrap(), entry);
+    }
+
+    #[test]
+    fn config_and_admin_messages_round_trip() {
+        let config: GetConfigResponse = from_json(r#"{"config":null}"#).unwrap();
+        assert_eq!(config, GetConfigResponse { config: None });
+
+        let req = SetMarketRequest {
+            pair_decimals: 6,
+            ..Default::default()
+        };
+        assert_eq!(
+            from_json::<SetMarketRequest>(&to_json(&req).unwrap()).unwrap(),
+            req
+        );
+    }
+
+    #[test]
+    fn side_enum_serializes_by_name() {
+        assert_eq!(to_json_line(&Side::Ask).unwrap(), "\"Ask\"");
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        assert!(from_json::<Order>("{not json").is_err());
+    }
+}
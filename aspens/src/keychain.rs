@@ -0,0 +1,125 @@
+//! Admin JWTs kept in the OS keychain.
+//!
+//! `aspens-admin login --save` stores the token it gets, with its expiry,
+//! the stack URL and the admin address, in the OS keychain (Keychain on
+//! macOS, Secret Service on Linux, Credential Manager on Windows): one entry
+//! per stack under [`JWT_KEYCHAIN_SERVICE`]. [`AspensClient::get_jwt_token`]
+//! and the [`JwtManager`] fall back to the entry for their stack when no
+//! valid token is cached, so later commands need no `ASPENS_JWT`.
+//!
+//! [`AspensClient::get_jwt_token`]: crate::AspensClient::get_jwt_token
+//! [`JwtManager`]: crate::client::JwtManager
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::auth::{AuthToken, is_token_valid};
+
+/// Keychain service admin JWTs are stored under, one account per stack URL.
+pub const JWT_KEYCHAIN_SERVICE: &str = "aspens-jwt";
+
+/// An admin JWT saved in the keychain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredJwt {
+    /// The token.
+    pub jwt_token: String,
+    /// Unix timestamp (seconds) when the token expires.
+    pub expires_at: u64,
+    /// The stack the token was issued by.
+    pub stack_url: String,
+    /// The admin address the token was issued to.
+    pub address: String,
+}
+
+impl StoredJwt {
+    /// The token `stack_url` issued at login.
+    pub fn new(stack_url: &str, token: &AuthToken) -> Self {
+        Self {
+            jwt_token: token.jwt_token.clone(),
+            expires_at: token.expires_at,
+            stack_url: stack_account(stack_url),
+            address: token.address.clone(),
+        }
+    }
+
+    /// True while the token has more than the clock-skew margin left.
+    pub fn is_valid(&self) -> bool {
+        is_token_valid(self.expires_at)
+    }
+}
+
+/// Save `jwt` as the token for its stack, replacing any earlier one.
+pub fn save_jwt(jwt: &StoredJwt) -> Result<()> {
+    let secret = serde_json::to_string(jwt)?;
+    keychain_entry(&jwt.stack_url)?
+        .set_password(&secret)
+        .map_err(|e| eyre!("failed to save the JWT to the OS keychain: {}", e))
+}
+
+/// The saved token for `stack_url`, if there is one and it is still valid.
+pub fn load_jwt(stack_url: &str) -> Result<Option<StoredJwt>> {
+    match keychain_entry(stack_url)?.get_password() {
+        Ok(secret) => Ok(decode(&secret, stack_url)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(eyre!("failed to read the JWT from the OS keychain: {}", e)),
+    }
+}
+
+/// Remove the saved token for `stack_url`. Returns false if there was none.
+pub fn delete_jwt(stack_url: &str) -> Result<bool> {
+    match keychain_entry(stack_url)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(eyre!(
+            "failed to remove the JWT from the OS keychain: {}",
+            e
+        )),
+    }
+}
+
+/// A saved entry, if it parses, belongs to `stack_url` and hasn't expired.
+fn decode(secret: &str, stack_url: &str) -> Option<StoredJwt> {
+    let jwt: StoredJwt = serde_json::from_str(secret).ok()?;
+    (jwt.stack_url == stack_account(stack_url) && jwt.is_valid()).then_some(jwt)
+}
+
+/// Keychain account for a stack: its URL without a trailing slash, so
+/// `http://host:50051` and `http://host:50051/` share an entry.
+fn stack_account(stack_url: &str) -> String {
+    stack_url.trim_end_matches('/').to_string()
+}
+
+fn keychain_entry(stack_url: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(JWT_KEYCHAIN_SERVICE, &stack_account(stack_url))
+        .map_err(|e| eyre!("failed to open the OS keychain: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(expires_at: u64) -> StoredJwt {
+        StoredJwt::new(
+            "http://localhost:50051/",
+            &AuthToken {
+                jwt_token: "header.payload.sig".into(),
+                expires_at,
+                address: "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".into(),
+            },
+        )
+    }
+
+    #[test]
+    fn decodes_only_valid_tokens_for_the_same_stack() {
+        let jwt = stored(u64::MAX);
+        let secret = serde_json::to_string(&jwt).unwrap();
+        assert_eq!(jwt.stack_url, "http://localhost:50051");
+        assert_eq!(decode(&secret, "http://localhost:50051"), Some(jwt.clone()));
+        assert_eq!(decode(&secret, "http://localhost:50051/"), Some(jwt));
+        assert_eq!(decode(&secret, "http://other:50051"), None);
+
+        let expired = serde_json::to_string(&stored(1)).unwrap();
+        assert_eq!(decode(&expired, "http://localhost:50051"), None);
+        assert_eq!(decode("not json", "http://localhost:50051"), None);
+    }
+}
@@ -0,0 +1,91 @@
+//! AWS KMS signer.
+//!
+//! [`AwsKmsSigner`] signs with an asymmetric `ECC_SECG_P256K1` KMS key:
+//! the private key stays in KMS and each signature is a `Sign` call.
+//! Credentials and region come from the standard AWS provider chain
+//! (environment, profile, instance or task role). Wrap one in
+//! [`Wallet::Remote`](crate::Wallet::Remote) (via `From`) to use it
+//! wherever the SDK takes a wallet.
+
+use std::sync::Arc;
+
+use alloy_primitives::{Address, B256, Signature};
+use alloy_signer::Signer;
+use alloy_signer_aws::AwsSigner;
+use async_trait::async_trait;
+use eyre::{Result, eyre};
+
+use crate::remote_signer::RemoteSigner;
+
+/// Environment variable holding the trader's KMS key ID or ARN.
+pub const TRADER_KMS_KEY_ENV: &str = "TRADER_KMS_KEY_ID";
+
+/// Environment variable holding the admin's KMS key ID or ARN.
+pub const ADMIN_KMS_KEY_ENV: &str = "ADMIN_KMS_KEY_ID";
+
+/// An EVM signer over an AWS KMS key. Clones share the KMS client.
+#[derive(Debug, Clone)]
+pub struct AwsKmsSigner {
+    signer: AwsSigner,
+    key_id: String,
+}
+
+impl AwsKmsSigner {
+    /// Connect with the default AWS configuration and read `key_id`'s
+    /// public key. `key_id` is a key ID, alias or ARN.
+    pub async fn connect(key_id: impl Into<String>) -> Result<Self> {
+        let config = aws_config::load_from_env().await;
+        Self::with_client(aws_sdk_kms::Client::new(&config), key_id).await
+    }
+
+    /// [`connect`](Self::connect) with an already configured KMS client.
+    pub async fn with_client(
+        client: aws_sdk_kms::Client,
+        key_id: impl Into<String>,
+    ) -> Result<Self> {
+        let key_id = key_id.into();
+        let signer = AwsSigner::new(client, key_id.clone(), None)
+            .await
+            .map_err(|e| {
+                eyre!(
+                    "cannot use KMS key '{}': {}\n\n\
+                     Hints:\n\
+                     - The key must be an asymmetric ECC_SECG_P256K1 signing key\n\
+                     - The credentials need kms:GetPublicKey and kms:Sign on it\n\
+                     - Check AWS_REGION matches the key's region",
+                    key_id,
+                    e
+                )
+            })?;
+        Ok(Self { signer, key_id })
+    }
+
+    /// The KMS key ID, alias or ARN.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+}
+
+#[async_trait]
+impl RemoteSigner for AwsKmsSigner {
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    fn backend(&self) -> &str {
+        "aws-kms"
+    }
+
+    async fn sign_hash(&self, hash: &B256) -> Result<Signature> {
+        self.signer
+            .sign_hash(hash)
+            .await
+            .map_err(|e| eyre!("KMS key '{}' did not sign: {}", self.key_id, e))
+    }
+}
+
+impl From<AwsKmsSigner> for crate::Wallet {
+    fn from(signer: AwsKmsSigner) -> Self {
+        crate::Wallet::Remote(Arc::new(signer))
+    }
+}
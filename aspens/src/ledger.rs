@@ -0,0 +1,187 @@
+//! Ledger hardware wallet signing.
+//!
+//! A [`LedgerWallet`] keeps the EVM key on a Ledger device running the
+//! Ethereum app: orders are signed as personal messages and admin logins as
+//! EIP-712 typed data, each confirmed on the device. Wrap one in
+//! [`Wallet::Ledger`](crate::Wallet::Ledger) (via `From`) to use it
+//! wherever the SDK takes a wallet. Ledger wallets can't sign raw digests
+//! or transactions for deposits and withdrawals; keep a software key for
+//! those.
+
+use std::sync::Arc;
+
+use alloy_primitives::{Address, Signature};
+use alloy_signer::Signer;
+use alloy_signer_ledger::LedgerSigner;
+use alloy_sol_types::{Eip712Domain, SolStruct};
+use eyre::{Result, eyre};
+
+pub use alloy_signer_ledger::HDPath;
+
+/// Called with a description of what is about to be signed, before the
+/// device asks for confirmation, so a CLI can tell the user to look at it.
+pub type ConfirmPrompt = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Parse a derivation path: `live:N` (Ledger Live account `N`,
+/// `m/44'/60'/N'/0/0`), `legacy:N` (`m/44'/60'/0'/N`) or a full path
+/// starting with `m/`. A bare number is a Ledger Live account.
+pub fn parse_hd_path(s: &str) -> Result<HDPath> {
+    let s = s.trim();
+    let index = |n: &str| {
+        n.parse::<usize>()
+            .map_err(|_| eyre!("invalid Ledger account index '{}'", n))
+    };
+    if let Some(n) = s.strip_prefix("live:") {
+        Ok(HDPath::LedgerLive(index(n)?))
+    } else if let Some(n) = s.strip_prefix("legacy:") {
+        Ok(HDPath::Legacy(index(n)?))
+    } else if s.starts_with("m/") {
+        Ok(HDPath::Other(s.to_string()))
+    } else if let Ok(n) = s.parse::<usize>() {
+        Ok(HDPath::LedgerLive(n))
+    } else {
+        Err(eyre!(
+            "invalid derivation path '{}': expected live:N, legacy:N or m/44'/60'/...",
+            s
+        ))
+    }
+}
+
+/// An EVM signer on a Ledger device.
+pub struct LedgerWallet {
+    signer: LedgerSigner,
+    path: HDPath,
+    prompt: Option<ConfirmPrompt>,
+}
+
+impl LedgerWallet {
+    /// Connect to the first Ledger found and read the address at `path`.
+    /// The device must be unlocked with the Ethereum app open.
+    pub async fn connect(path: HDPath) -> Result<Self> {
+        let signer = LedgerSigner::new(path.clone(), None).await.map_err(|e| {
+            eyre!(
+                "cannot reach the Ledger: {}\n\n\
+                 Hints:\n\
+                 - Connect and unlock the device\n\
+                 - Open the Ethereum app on it\n\
+                 - Close Ledger Live and other apps using the device",
+                e
+            )
+        })?;
+        Ok(Self {
+            signer,
+            path,
+            prompt: None,
+        })
+    }
+
+    /// Call `prompt` before each signature the device must confirm.
+    /// Without one, the request is logged at info level.
+    pub fn with_prompt<F>(mut self, prompt: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.prompt = Some(Arc::new(prompt));
+        self
+    }
+
+    /// The account's address.
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// The derivation path the account was read from.
+    pub fn path(&self) -> &HDPath {
+        &self.path
+    }
+
+    /// Sign `msg` as an EIP-191 personal message, after confirmation on
+    /// the device.
+    pub async fn sign_message(&self, msg: &[u8]) -> Result<Signature> {
+        self.confirm("the message");
+        self.signer
+            .sign_message(msg)
+            .await
+            .map_err(|e| eyre!("the Ledger did not sign the message: {}", e))
+    }
+
+    /// Sign `payload` as EIP-712 typed data under `domain`, after
+    /// confirmation on the device.
+    pub async fn sign_typed_data<T: SolStruct + Send + Sync>(
+        &self,
+        payload: &T,
+        domain: &Eip712Domain,
+    ) -> Result<Signature> {
+        self.confirm(&format!("the {} request", T::NAME));
+        self.signer
+            .sign_typed_data(payload, domain)
+            .await
+            .map_err(|e| eyre!("the Ledger did not sign the typed data: {}", e))
+    }
+
+    fn confirm(&self, what: &str) {
+        match &self.prompt {
+            Some(prompt) => prompt(what),
+            None => tracing::info!(
+                "Confirm signing {} on your Ledger ({})",
+                what,
+                self.address()
+            ),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::AspensSigner for LedgerWallet {
+    fn curve(&self) -> crate::CurveType {
+        crate::CurveType::Secp256k1
+    }
+
+    fn address(&self) -> String {
+        LedgerWallet::address(self).to_checksum(None)
+    }
+
+    async fn sign_message(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        Ok(LedgerWallet::sign_message(self, msg)
+            .await?
+            .as_bytes()
+            .to_vec())
+    }
+
+    fn as_ledger(&self) -> Option<&LedgerWallet> {
+        Some(self)
+    }
+}
+
+impl std::fmt::Debug for LedgerWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LedgerWallet")
+            .field("address", &self.address())
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_derivation_paths() {
+        assert!(matches!(
+            parse_hd_path("live:3").unwrap(),
+            HDPath::LedgerLive(3)
+        ));
+        assert!(matches!(parse_hd_path("2").unwrap(), HDPath::LedgerLive(2)));
+        assert!(matches!(
+            parse_hd_path("legacy:1").unwrap(),
+            HDPath::Legacy(1)
+        ));
+        assert!(matches!(
+            parse_hd_path("m/44'/60'/0'/0/7").unwrap(),
+            HDPath::Other(p) if p == "m/44'/60'/0'/0/7"
+        ));
+        assert!(parse_hd_path("live:x").is_err());
+        assert!(parse_hd_path("44/60").is_err());
+    }
+}
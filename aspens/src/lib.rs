@@ -15,46 +15,187 @@
 //!   (PDA derivations, instruction builders, borsh payload encoder).
 //!   Pulls `solana-sdk`, `bs58`, `ed25519-dalek`, and `borsh`.
 //! - **`client`** (default) — full gRPC + RPC runtime: [`AspensClient`],
-//!   the [`commands`] modules, [`chain_client`], the [`executor`]
+//!   the `commands` modules, [`chain_client`], the [`executor`]
 //!   abstraction, and Solana RPC submission. Pulls `tonic`, `prost`,
 //!   `tokio`, `solana-client`, and the proto-generated bindings.
+//! - **`tower`** / **`axum`** — [`service`]: a tower layer that shares one
+//!   client across a web service's requests, and (with `axum`) the
+//!   `Aspens` extractor. Pulls `tower-layer`, `tower-service`, `http` and
+//!   `axum-core`.
+//! - **`encryption`** — `storage::EncryptedStorage`: seal a profile's
+//!   persisted files at rest, keyed by a passphrase. Pulls
+//!   `chacha20poly1305` and `argon2`.
+//! - **`encryption-keychain`** — encrypted profiles keyed from the OS
+//!   keychain instead. Implies `encryption`; pulls `keyring`, which needs
+//!   the system libdbus on Linux.
+//! - **`keychain`** — [`keychain`]: admin JWTs saved in the OS keychain,
+//!   which [`AspensClient::get_jwt_token`] loads when no valid token is
+//!   cached. Implies `client` and `admin`; pulls `keyring`, which needs
+//!   the system libdbus on Linux.
+//! - **`journal`** — `journal`: a local SQLite record of the orders,
+//!   cancels, fills, deposits and withdrawals made through the SDK. Pulls
+//!   `rusqlite` with SQLite bundled.
+//! - **`keystore`** — [`Wallet::from_keystore`] and
+//!   [`Wallet::unlock_keystore`]: EVM keys from encrypted Web3 Secret
+//!   Storage files, which the wallet loaders and
+//!   `AspensClientBuilder::with_keystore` use in place of plaintext
+//!   `*_PRIVKEY` variables. Pulls alloy's keystore support and `rpassword`.
+//! - **`mnemonic`** — [`Wallet::from_mnemonic`]: EVM trader keys derived
+//!   from a BIP-39 phrase, one account per index, which
+//!   [`load_trader_wallet`] reads from `TRADER_MNEMONIC` and
+//!   `TRADER_ACCOUNT_INDEX`. Pulls alloy's mnemonic support.
+//! - **`ledger`** — [`ledger`]: sign orders and admin logins on a Ledger
+//!   device through [`Wallet::Ledger`]. Pulls `alloy-signer-ledger`.
+//! - **`remote-signer`** — [`remote_signer::RemoteSigner`]: EVM keys held
+//!   by a signing service, used through [`Wallet::Remote`] for orders,
+//!   logins, deposits, withdrawals and deployments. Implies `client`.
+//! - **`kms`** — [`kms::AwsKmsSigner`], a remote signer over AWS KMS.
+//!   Pulls `alloy-signer-aws` and the AWS SDK.
 //!
 //! Lean signing consumers (browser, embedded, etc.) can build with
 //! `--no-default-features --features evm,solana` to skip all of tonic /
 //! prost / tokio / solana-client.
+//!
+//! # API stability
+//!
+//! The crate root and [`prelude`] are the stable surface: [`AspensClient`]
+//! and its [`AspensApi`] methods, the wallet, and the request, response and
+//! stream types re-exported here. They follow semver. Most bots only need
+//! `use aspens::prelude::*;`.
+//!
+//! The `commands` modules are the per-RPC plumbing behind that surface. They
+//! stay public for the workspace binaries but are hidden from the docs and
+//! exempt from semver: paths under `aspens::commands` may move or change
+//! signature in any release. Prefer the crate-root re-exports of their
+//! types.
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+/// Façade trait over the high-level trading operations, with a mock for tests.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod api;
+/// On-chain transaction plumbing: nonces shared across concurrent senders.
+#[cfg(feature = "client")]
+pub mod chain;
 #[cfg(feature = "client")]
 pub mod chain_client;
 /// gRPC client and builder for the Aspens Market Stack.
 #[cfg(feature = "client")]
 pub mod client;
-/// gRPC command implementations (config, trading, admin, auth).
+// Semver-exempt — per-RPC command implementations (config, trading, admin,
+// auth) behind the stable surface re-exported below. Public for the
+// workspace binaries; may change without notice. See "API stability".
 #[cfg(feature = "client")]
+#[doc(hidden)]
 pub mod commands;
+/// The last configuration fetched from each stack, cached on disk.
+#[cfg(feature = "client")]
+pub mod config_cache;
+/// Dead-man's switch that cancels tracked orders when the stack is lost.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod deadman;
 /// Decimal-string ↔ base-units conversion shared by all amount-parsing
 /// call sites (CLI, REPL, library).
 pub mod decimals;
+/// Typed errors returned by the `commands` functions.
+#[cfg(feature = "client")]
+pub mod error;
 #[cfg(feature = "evm")]
 pub mod evm;
 /// Async/sync execution strategies used by binaries to drive the client.
 #[cfg(feature = "client")]
 pub mod executor;
+/// User-facing explanations and hints for [`AspensError`]s.
+#[cfg(feature = "client")]
+pub mod explain;
+/// Builders for proto types in tests: configs, orders, stream events.
+#[cfg(all(
+    feature = "client",
+    any(feature = "trader", feature = "admin"),
+    any(test, feature = "test-util")
+))]
+pub mod fixtures;
 // Internal — gRPC channel construction helpers shared by the commands
 // modules. Not part of the stable public API; may change without notice.
+// `GrpcOptions` / `Compression` are re-exported at the crate root.
 #[cfg(feature = "client")]
 #[doc(hidden)]
 pub mod grpc;
 /// gRPC health-check helpers used to probe stack readiness.
 #[cfg(feature = "client")]
 pub mod health;
+/// Local SQLite journal of orders, cancels, fills and transfers.
+#[cfg(all(feature = "journal", any(feature = "trader", feature = "admin")))]
+pub mod journal;
+/// JSON `to_json` / `from_json` helpers for the generated protobuf messages.
+pub mod json;
+/// Admin JWTs saved in the OS keychain.
+#[cfg(feature = "keychain")]
+pub mod keychain;
+/// AWS KMS remote signer.
+#[cfg(feature = "kms")]
+pub mod kms;
+/// Ledger hardware wallet signer.
+#[cfg(feature = "ledger")]
+pub mod ledger;
+/// Flagging and re-placing resting orders when a market's config changes.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod migration;
+/// Notifications for unattended processes: log, channel and webhook sinks.
+#[cfg(feature = "client")]
+pub mod notify;
 pub mod orders;
+/// One-line import of the stable API: `use aspens::prelude::*;`.
+pub mod prelude;
+/// Named order presets persisted to the profile directory.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod presets;
+/// Named profiles (stack URL, env file, market, output, signer) in the
+/// user's config file.
+#[cfg(feature = "client")]
+pub mod profiles;
+/// Anonymous, rate-limited read-only client for public dashboards.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod public;
+/// Startup reconciliation of a bot's known orders with the stack.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod recovery;
+/// Signing with keys held by a remote service (a cloud KMS or HSM).
+#[cfg(feature = "remote-signer")]
+pub mod remote_signer;
+/// Scheduled and recurring orders persisted to the profile directory.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod scheduler;
+/// Versioned on-disk formats and the migrations that upgrade old files.
+pub mod schema;
+/// Tower layer and axum extractor sharing one client across a web service.
+#[cfg(feature = "tower")]
+pub mod service;
+/// The [`AspensSigner`] trait command functions take.
+pub mod signer;
 #[cfg(feature = "solana")]
 pub mod solana;
+/// Where persisted state lives: XDG paths, profiles, locking, in-memory backend.
+pub mod storage;
+/// Reference-counted per-market streams shared across a process.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod subscriptions;
+/// Pluggable detectors that flag suspicious trading on a market's streams.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub mod surveillance;
 /// Relying-party TDX attestation verification (REPORTDATA/manifest reconstruction
 /// + the verify pipeline). Pure `sha2`; the DCAP backend is a separate phase.
 pub mod tdx_verify;
+/// Opt-in anonymous usage statistics, queued in the profile directory.
+pub mod telemetry;
+/// Release-feed check for newer and stack-required SDK versions.
+#[cfg(all(feature = "client", feature = "update-check"))]
+pub mod update;
+/// Address parsing and normalization shared by every address boundary.
+pub mod util;
+/// Minisign signature checks for release artifacts and out-of-band configs.
+#[cfg(feature = "verify")]
+pub mod verify;
 pub mod wallet;
 
 /// Generated protobuf bindings for the attestation service.
@@ -67,30 +208,105 @@ pub mod attestation {
     }
 }
 
-/// Generated protobuf bindings for the arborter config and auth services.
+/// Generated protobuf bindings for the arborter services, grouped by wire
+/// version.
+///
+/// Each `vN` module mirrors the `xyz.aspens.*.vN` proto packages and
+/// re-exports the bindings the commands use, so its types are the ones the
+/// SDK's functions take and return. Only `v1` exists today, so there are
+/// no conversion shims between versions yet. When the stack ships a second
+/// version it lands as a sibling module with shims from the previous one,
+/// which is kept for one minor release (recorded in `CHANGELOG.md`) before
+/// it is removed.
+///
+/// The unversioned [`proto::config`](crate::proto::config) and
+/// `proto::auth` paths alias the current version.
 #[cfg(feature = "client")]
 pub mod proto {
-    /// Arborter config service protobuf bindings.
-    #[allow(missing_docs)]
-    pub mod config {
-        include!("../proto/generated/xyz.aspens.arborter_config.v1.rs");
+    /// Wire version the unversioned aliases below point at.
+    pub const CURRENT_VERSION: u32 = 1;
+
+    /// `xyz.aspens.*.v1` bindings.
+    pub mod v1 {
+        /// Arborter trading service protobuf bindings, as the orderbook and
+        /// trade streams return them.
+        #[cfg(any(feature = "trader", feature = "admin"))]
+        pub mod arborter {
+            pub use crate::commands::trading::stream_orderbook::arborter_pb::*;
+        }
+        /// Arborter config service protobuf bindings.
+        pub mod config {
+            pub use crate::commands::config::config_pb::*;
+        }
+        /// Arborter auth service protobuf bindings (admin feature only).
+        #[cfg(feature = "admin")]
+        pub mod auth {
+            pub use crate::commands::auth::auth_pb::*;
+        }
     }
-    /// Arborter auth service protobuf bindings (admin feature only).
+
     #[cfg(feature = "admin")]
-    #[allow(missing_docs)]
-    pub mod auth {
-        include!("../proto/generated/xyz.aspens.arborter_auth.v1.rs");
-    }
+    pub use v1::auth;
+    pub use v1::config;
 }
 
-// Re-export commonly used types
+// Re-export commonly used types. Everything re-exported here is the stable
+// API surface; keep `prelude` in step.
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub use api::{AspensApi, OrderRequest, StreamHandle};
+#[cfg(feature = "client")]
+pub use chain::tx::{ConfirmationPolicy, ConfirmationTimeout};
 #[cfg(feature = "client")]
 pub use chain_client::ChainClient;
+#[cfg(all(feature = "client", feature = "admin"))]
+pub use client::JwtManager;
 #[cfg(feature = "client")]
 pub use client::{AspensClient, AspensClientBuilder, JwtToken};
 #[cfg(feature = "client")]
+pub use commands::config::config_pb::{
+    Chain, GetConfigResponse, Market, MarketFees, MarketLimits, Token,
+};
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub use commands::trading::{
+    balance::{BalanceFilter, BalanceReport, BalanceSnapshot},
+    buffer::{BufferOptions, OverflowPolicy},
+    cancel_order::arborter_pb::CancelOrderResponse,
+    candles::{Candle, CandleInterval},
+    conflate::BookSnapshot,
+    depth::DepthAtPrice,
+    estimate::FillEstimate,
+    idle::IdleOptions,
+    live_book::OrderBook,
+    orderbook::OrderbookLevels,
+    portfolio::{Portfolio, Position},
+    reconnect::ReconnectOptions,
+    replay::{HistoricalReplay, ReplaySpeed},
+    risk::{RiskConfig, RiskViolation},
+    send_order::{
+        OrderTags,
+        arborter_pb::{SendOrderResponse, Side},
+    },
+    signing::SigningVersion,
+    stream_orderbook::{StreamOrderbookOptions, arborter_pb::OrderbookEntry},
+    stream_trades::{StreamTradesOptions, arborter_pb::Trade},
+    ticker::Ticker,
+};
+#[cfg(feature = "client")]
+pub use error::AspensError;
+#[cfg(feature = "client")]
 pub use executor::{AsyncExecutor, BlockingExecutor, DirectExecutor};
+#[cfg(feature = "client")]
+pub use grpc::{Compression, GrpcOptions, KeepAlive};
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub use public::{PublicClient, RateLimits};
+pub use signer::AspensSigner;
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub use subscriptions::{Subscription, SubscriptionHub};
+#[cfg(feature = "keystore")]
+pub use wallet::KEYSTORE_PASSPHRASE_ENV;
 pub use wallet::{CurveType, Wallet, load_admin_wallet, load_trader_wallet};
+#[cfg(feature = "mnemonic")]
+pub use wallet::{TRADER_ACCOUNT_INDEX_ENV, TRADER_MNEMONIC_ENV};
 
 // Chain-aware wallet helpers depend on the proto-generated `Chain` /
 // `GetConfigResponse` types under `commands::config`, which only exist
@@ -0,0 +1,490 @@
+//! Migrating resting orders when a market's configuration changes.
+//!
+//! Raw prices and quantities are scaled by the market's `pair_decimals`, and
+//! the market ID embeds both token addresses. If the stack admin changes
+//! either mid-session, orders already on the book were sized for the old
+//! market, and every conversion the bot makes with the old config is wrong.
+//!
+//! [`next_market_change`] polls the stack until the markets differ from the
+//! client's cached config. [`plan_migration`] then picks out the bot's
+//! resting orders on the changed markets and recomputes each one's raw
+//! amounts for the new market, and [`migrate_orders`] cancels them and
+//! re-places the replacements. Every detected change is logged at `warn`
+//! so an operator watching the logs sees it even if the bot only flags
+//! orders.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use tracing::{info, warn};
+
+use crate::api::{AspensApi, OrderRequest};
+use crate::client::AspensClient;
+use crate::commands::config::config_pb::{GetConfigResponse, Market};
+use crate::commands::trading::stream_orderbook::arborter_pb::{OrderState, OrderbookEntry};
+use crate::decimals::format_decimal_amount;
+use crate::wallet::Wallet;
+
+/// How often [`next_market_change`] re-fetches the configuration.
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What changed about a market, matched by name across two configs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketChange {
+    /// The market before the change.
+    pub old: Market,
+    /// The market after the change; `None` if it was removed.
+    pub new: Option<Market>,
+}
+
+impl MarketChange {
+    /// True when `pair_decimals` changed.
+    pub fn pair_decimals_changed(&self) -> bool {
+        self.new
+            .as_ref()
+            .is_some_and(|m| m.pair_decimals != self.old.pair_decimals)
+    }
+
+    /// True when the market ID, and so a token address, changed.
+    pub fn market_id_changed(&self) -> bool {
+        self.new
+            .as_ref()
+            .is_some_and(|m| m.market_id != self.old.market_id)
+    }
+}
+
+impl fmt::Display for MarketChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(new) = &self.new else {
+            return write!(f, "market '{}' was removed", self.old.name);
+        };
+        let mut parts = Vec::new();
+        if self.pair_decimals_changed() {
+            parts.push(format!(
+                "pair_decimals {} -> {}",
+                self.old.pair_decimals, new.pair_decimals
+            ));
+        }
+        if self.market_id_changed() {
+            parts.push(format!(
+                "market_id {} -> {}",
+                self.old.market_id, new.market_id
+            ));
+        }
+        write!(
+            f,
+            "market '{}' changed: {}",
+            self.old.name,
+            parts.join(", ")
+        )
+    }
+}
+
+/// Markets in `old` whose pair decimals or market ID differ in `new`, or
+/// that `new` no longer has.
+///
+/// Markets are matched by name, since the market ID itself changes when a
+/// token address does. Markets only in `new` aren't reported; no resting
+/// order can be affected by them.
+pub fn diff_markets(old: &GetConfigResponse, new: &GetConfigResponse) -> Vec<MarketChange> {
+    markets(old)
+        .iter()
+        .filter_map(|o| {
+            let n = markets(new).iter().find(|n| n.name == o.name);
+            let change = MarketChange {
+                old: o.clone(),
+                new: n.cloned(),
+            };
+            (n.is_none() || change.pair_decimals_changed() || change.market_id_changed())
+                .then_some(change)
+        })
+        .collect()
+}
+
+/// The markets in `config`, empty when the stack served none.
+fn markets(config: &GetConfigResponse) -> &[Market] {
+    config.config.as_ref().map_or(&[], |c| &c.markets)
+}
+
+/// A config change noticed by [`next_market_change`].
+#[derive(Debug, Clone)]
+pub struct ConfigChange {
+    /// The client's cached configuration.
+    pub old: GetConfigResponse,
+    /// The configuration the stack now serves.
+    pub new: GetConfigResponse,
+    /// Markets that differ between the two.
+    pub changes: Vec<MarketChange>,
+}
+
+/// Poll the stack every `interval` (typically [`CONFIG_POLL_INTERVAL`])
+/// until its markets differ from the client's cached configuration, then
+/// return the difference.
+///
+/// The cache is left alone so the caller can still cancel orders against
+/// the old markets; [`migrate_orders`] refreshes it once the cancels are
+/// through. Until the cache is refreshed, every call reports the same
+/// change again.
+pub async fn next_market_change(client: &AspensClient, interval: Duration) -> Result<ConfigChange> {
+    let old = client.get_config().await?;
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let new = match crate::commands::config::get_config(client.stack_url.to_string()).await {
+            Ok(new) => new,
+            Err(e) => {
+                warn!("Config poll failed: {}", e);
+                continue;
+            }
+        };
+        let changes = diff_markets(&old, &new);
+        if !changes.is_empty() {
+            for change in &changes {
+                warn!("Stack configuration changed: {}", change);
+            }
+            return Ok(ConfigChange { old, new, changes });
+        }
+    }
+}
+
+/// A resting order on a changed market.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AffectedOrder {
+    /// The order as it rests on the book.
+    pub entry: OrderbookEntry,
+    /// The change that affects it.
+    pub change: MarketChange,
+    /// The same price and remaining quantity on the new market; `None` if
+    /// the market was removed.
+    pub replacement: Option<OrderRequest>,
+    /// True when the new `pair_decimals` can't represent the old price or
+    /// quantity exactly, so the replacement is truncated.
+    pub lossy: bool,
+}
+
+/// Pick the open orders in `resting` that sit on a changed market and
+/// compute their replacements.
+///
+/// `resting` is usually the trader's own open orders, e.g. a historical
+/// `stream_orderbook` drained with `filter_by_trader` set. Raw amounts are read with the old `pair_decimals`, so the replacement keeps
+/// the same human price and quantity.
+pub fn plan_migration(
+    changes: &[MarketChange],
+    resting: &[OrderbookEntry],
+) -> Result<Vec<AffectedOrder>> {
+    let mut affected = Vec::new();
+    for entry in resting {
+        let open = matches!(
+            OrderState::try_from(entry.state),
+            Ok(OrderState::Pending | OrderState::Confirmed)
+        );
+        if !open {
+            continue;
+        }
+        let Some(change) = changes.iter().find(|c| c.old.market_id == entry.market_id) else {
+            continue;
+        };
+        let old_decimals = change.old.pair_decimals as u32;
+        let price = parse_raw(&entry.price, "price", entry.order_id)?;
+        let quantity = parse_raw(&entry.quantity, "quantity", entry.order_id)?;
+        let (replacement, lossy) = match &change.new {
+            Some(new) => {
+                let new_decimals = new.pair_decimals as u32;
+                let lossy = new_decimals < old_decimals && {
+                    let unit = 10u128.pow(old_decimals - new_decimals);
+                    price % unit != 0 || quantity % unit != 0
+                };
+                let replacement = OrderRequest {
+                    market_id: new.market_id.clone(),
+                    side: entry.side,
+                    quantity: format_decimal_amount(quantity, old_decimals),
+                    price: Some(format_decimal_amount(price, old_decimals)),
+                    post_only: entry.post_only,
+                    ..Default::default()
+                };
+                (Some(replacement), lossy)
+            }
+            None => (None, false),
+        };
+        affected.push(AffectedOrder {
+            entry: entry.clone(),
+            change: change.clone(),
+            replacement,
+            lossy,
+        });
+    }
+    Ok(affected)
+}
+
+fn parse_raw(value: &str, what: &str, order_id: u64) -> Result<u128> {
+    value
+        .parse()
+        .map_err(|_| eyre!("order {}: invalid raw {} '{}'", order_id, what, value))
+}
+
+/// How [`migrate_orders`] treats the affected orders.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MigrationMode {
+    /// Only log the affected orders.
+    #[default]
+    FlagOnly,
+    /// Cancel the affected orders.
+    Cancel,
+    /// Cancel the affected orders and re-place their replacements.
+    CancelAndReplace,
+}
+
+/// Outcome of [`migrate_orders`] for one order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationOutcome {
+    /// Left on the book.
+    Flagged,
+    /// Cancelled and not re-placed.
+    Cancelled,
+    /// Cancelled and re-placed under a new order ID.
+    Replaced {
+        /// ID of the replacement order.
+        new_order_id: u64,
+    },
+    /// A cancel or re-place failed; the message says which.
+    Failed(String),
+}
+
+/// Flag, cancel, or cancel and re-place every `affected` order.
+///
+/// All cancels go out first, through `api` with its current (old) config.
+/// In [`MigrationMode::CancelAndReplace`], `refresh` is then awaited so
+/// `api` picks up the new config (for [`AspensClient`], pass
+/// `|| client.fetch_config()`), and the replacements are placed with
+/// `wallets`. Orders whose cancel failed are never re-placed, so a failure
+/// can't leave a duplicate on the book. Lossy replacements are placed
+/// anyway and logged.
+pub async fn migrate_orders<A, R, F>(
+    api: &A,
+    wallets: &[&Wallet],
+    affected: &[AffectedOrder],
+    mode: MigrationMode,
+    refresh: R,
+) -> Result<Vec<MigrationOutcome>>
+where
+    A: AspensApi,
+    R: FnOnce() -> F,
+    F: Future<Output = Result<()>>,
+{
+    for order in affected {
+        warn!(
+            "Order {} on '{}' is affected: {}",
+            order.entry.order_id, order.change.old.name, order.change
+        );
+    }
+    if mode == MigrationMode::FlagOnly {
+        return Ok(vec![MigrationOutcome::Flagged; affected.len()]);
+    }
+    let wallet = wallets
+        .first()
+        .ok_or_else(|| eyre!("migrate_orders needs at least one wallet"))?;
+
+    let mut outcomes = Vec::with_capacity(affected.len());
+    for order in affected {
+        let side = if order.entry.side == 1 { "buy" } else { "sell" };
+        let outcome = match api
+            .cancel_order(
+                &order.change.old.market_id,
+                side,
+                order.entry.order_id,
+                wallet,
+            )
+            .await
+        {
+            Ok(_) => MigrationOutcome::Cancelled,
+            Err(e) => {
+                warn!(
+                    "Failed to cancel order {} for migration: {}",
+                    order.entry.order_id, e
+                );
+                MigrationOutcome::Failed(format!("cancel failed: {}", e))
+            }
+        };
+        outcomes.push(outcome);
+    }
+    if mode == MigrationMode::Cancel {
+        return Ok(outcomes);
+    }
+
+    refresh().await?;
+    for (order, outcome) in affected.iter().zip(outcomes.iter_mut()) {
+        let Some(replacement) = &order.replacement else {
+            continue;
+        };
+        if *outcome != MigrationOutcome::Cancelled {
+            continue;
+        }
+        if order.lossy {
+            warn!(
+                "Replacement for order {} is truncated to the new pair decimals",
+                order.entry.order_id
+            );
+        }
+        *outcome = match api.send_order(replacement.clone(), wallets).await {
+            Ok(response) => {
+                info!(
+                    "Re-placed order {} as {}",
+                    order.entry.order_id, response.order_id
+                );
+                MigrationOutcome::Replaced {
+                    new_order_id: response.order_id,
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to re-place order {} after migration: {}",
+                    order.entry.order_id, e
+                );
+                MigrationOutcome::Failed(format!("re-place failed: {}", e))
+            }
+        };
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock::MockAspensApi;
+    use crate::commands::config::config_pb::Configuration;
+
+    fn market(name: &str, market_id: &str, pair_decimals: i32) -> Market {
+        Market {
+            name: name.into(),
+            market_id: market_id.into(),
+            pair_decimals,
+            ..Default::default()
+        }
+    }
+
+    fn config(markets: Vec<Market>) -> GetConfigResponse {
+        GetConfigResponse {
+            config: Some(Configuration {
+                chains: vec![],
+                markets,
+                maintenance: None,
+                signing_versions: vec![],
+            }),
+        }
+    }
+
+    fn entry(order_id: u64, side: i32, price: &str, quantity: &str) -> OrderbookEntry {
+        OrderbookEntry {
+            order_id,
+            side,
+            price: price.into(),
+            quantity: quantity.into(),
+            market_id: "m".into(),
+            state: OrderState::Confirmed as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diffs_markets_by_name() {
+        let old = config(vec![
+            market("a", "m", 4),
+            market("b", "b1", 2),
+            market("c", "c1", 2),
+        ]);
+        let new = config(vec![
+            market("a", "m", 2),
+            market("b", "b2", 2),
+            market("d", "d1", 2),
+        ]);
+        let changes = diff_markets(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert!(changes[0].pair_decimals_changed());
+        assert!(changes[1].market_id_changed());
+        assert!(changes[2].new.is_none());
+        assert_eq!(
+            changes[0].to_string(),
+            "market 'a' changed: pair_decimals 4 -> 2"
+        );
+        assert!(diff_markets(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn plans_replacements_with_old_decimals() {
+        let old = config(vec![market("a", "m", 4)]);
+        let new = config(vec![market("a", "m", 2)]);
+        let changes = diff_markets(&old, &new);
+        let mut filled = entry(3, 1, "10000", "10000");
+        filled.state = OrderState::Settled as i32;
+        let affected = plan_migration(
+            &changes,
+            &[
+                entry(1, 1, "12500", "30000"),
+                entry(2, 2, "12345", "10000"),
+                filled,
+            ],
+        )
+        .unwrap();
+        assert_eq!(affected.len(), 2);
+        let replacement = affected[0].replacement.as_ref().unwrap();
+        assert_eq!(replacement.price.as_deref(), Some("1.2500"));
+        assert_eq!(replacement.quantity, "3.0000");
+        assert!(!affected[0].lossy);
+        assert!(affected[1].lossy);
+    }
+
+    #[tokio::test]
+    async fn cancels_then_replaces() {
+        let old = config(vec![market("a", "m", 4)]);
+        let new = config(vec![market("a", "m", 2)]);
+        let affected = plan_migration(
+            &diff_markets(&old, &new),
+            &[entry(1, 1, "12500", "30000"), entry(2, 2, "13000", "10000")],
+        )
+        .unwrap();
+        let api = MockAspensApi::new(new);
+        let wallet = Wallet::from_evm_hex(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+
+        let outcomes = migrate_orders(
+            &api,
+            &[&wallet],
+            &affected,
+            MigrationMode::CancelAndReplace,
+            || async { Ok(()) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            outcomes,
+            vec![
+                MigrationOutcome::Replaced { new_order_id: 1 },
+                MigrationOutcome::Replaced { new_order_id: 2 },
+            ]
+        );
+        let cancels: Vec<(u64, String)> = api
+            .cancels()
+            .into_iter()
+            .map(|c| (c.order_id, c.side))
+            .collect();
+        assert_eq!(cancels, vec![(1, "buy".into()), (2, "sell".into())]);
+        assert_eq!(api.sent_orders()[1].price.as_deref(), Some("1.3000"));
+
+        let flagged = migrate_orders(
+            &api,
+            &[&wallet],
+            &affected,
+            MigrationMode::FlagOnly,
+            || async { Ok(()) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(flagged, vec![MigrationOutcome::Flagged; 2]);
+        assert_eq!(api.cancels().len(), 2);
+    }
+}
@@ -0,0 +1,159 @@
+//! Notifications for unattended processes.
+//!
+//! Long-running SDK components (market surveillance, bots) report things an
+//! operator should see as [`Notification`]s handed to one or more
+//! [`Notifier`]s. Delivery is fire-and-forget: `notify` never blocks the
+//! caller's event loop and never fails it, so a slow or broken sink can't
+//! stall the stream that produced the notification.
+//!
+//! Built-in sinks: [`LogNotifier`] writes to `tracing`, [`ChannelNotifier`]
+//! hands notifications to application code, and `WebhookNotifier` (the
+//! `webhook` feature) POSTs them as JSON.
+
+use std::fmt;
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// How urgent a [`Notification`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth recording, no action needed.
+    Info,
+    /// Worth a look.
+    Warning,
+    /// Needs attention now.
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// Something an operator should hear about.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    /// How urgent it is.
+    pub severity: Severity,
+    /// What raised it, e.g. `surveillance/wash-trade`.
+    pub source: String,
+    /// One-line human-readable summary.
+    pub message: String,
+    /// When it happened, unix milliseconds.
+    pub timestamp_ms: u64,
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.source, self.message)
+    }
+}
+
+/// A destination for [`Notification`]s.
+pub trait Notifier: Send + Sync {
+    /// Deliver `notification`. Must not block; failures are the
+    /// notifier's to log.
+    fn notify(&self, notification: &Notification);
+}
+
+/// Writes notifications to `tracing`: warnings and above at WARN, the rest
+/// at INFO.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify(&self, notification: &Notification) {
+        if notification.severity >= Severity::Warning {
+            warn!("{}", notification);
+        } else {
+            info!("{}", notification);
+        }
+    }
+}
+
+/// Sends notifications down a channel for application code to handle.
+/// Notifications are dropped once the receiver is gone.
+#[derive(Debug, Clone)]
+pub struct ChannelNotifier {
+    tx: mpsc::UnboundedSender<Notification>,
+}
+
+impl ChannelNotifier {
+    /// A notifier and the receiver its notifications arrive on.
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<Notification>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx }, rx)
+    }
+}
+
+impl Notifier for ChannelNotifier {
+    fn notify(&self, notification: &Notification) {
+        let _ = self.tx.send(notification.clone());
+    }
+}
+
+/// POSTs each notification as JSON to a URL (a chat webhook, an incident
+/// tool). Each delivery runs on its own tokio task; failures are logged.
+#[cfg(feature = "webhook")]
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "webhook")]
+impl WebhookNotifier {
+    /// How long one delivery may take.
+    pub const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+    /// A notifier posting to `url`. Notifications must be sent from inside
+    /// a tokio runtime.
+    pub fn new(url: impl Into<String>) -> eyre::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Self::TIMEOUT)
+            .user_agent(concat!("aspens-sdk/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| eyre::eyre!("building HTTP client: {}", e))?;
+        Ok(Self {
+            url: url.into(),
+            client,
+        })
+    }
+}
+
+#[cfg(feature = "webhook")]
+impl Notifier for WebhookNotifier {
+    fn notify(&self, notification: &Notification) {
+        let body = match serde_json::to_vec(notification) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("webhook {}: encoding notification: {}", self.url, e);
+                return;
+            }
+        };
+        let request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+        let url = self.url.clone();
+        tokio::spawn(async move {
+            match request.send().await {
+                Ok(response) if !response.status().is_success() => {
+                    warn!("webhook {} returned HTTP {}", url, response.status());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("webhook {} failed: {}", url, e),
+            }
+        });
+    }
+}
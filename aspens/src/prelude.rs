@@ -0,0 +1,50 @@
+//! The types most bots need, for a single glob import.
+//!
+//! ```
+//! use aspens::prelude::*;
+//!
+//! fn limit_buy(market_id: &str, quantity: &str, price: &str) -> OrderRequest {
+//!     OrderRequest {
+//!         market_id: market_id.to_string(),
+//!         side: Side::Bid as i32,
+//!         quantity: quantity.to_string(),
+//!         price: Some(price.to_string()),
+//!         ..Default::default()
+//!     }
+//! }
+//!
+//! async fn place(
+//!     client: &AspensClient,
+//!     wallet: &Wallet,
+//! ) -> Result<SendOrderResponse, AspensError> {
+//!     client.send_order(limit_buy("base/USDC::op/USDC", "10", "1.01"), &[wallet]).await
+//! }
+//! ```
+//!
+//! Everything here comes from the crate root or its documented modules,
+//! and is covered by semver; see [the crate docs](crate#api-stability).
+
+pub use crate::decimals::{
+    PrecisionMode, format_decimal_amount, parse_decimal_amount, parse_decimal_amount_with,
+};
+pub use crate::signer::AspensSigner;
+pub use crate::wallet::{CurveType, Wallet};
+
+#[cfg(feature = "client")]
+pub use crate::{
+    AspensClient, AspensClientBuilder, AspensError, Chain, Compression, ConfirmationPolicy,
+    GetConfigResponse, GrpcOptions, Market, MarketFees, MarketLimits, Token,
+};
+
+#[cfg(all(feature = "client", any(feature = "trader", feature = "admin")))]
+pub use crate::{
+    AspensApi, BalanceSnapshot, BookSnapshot, BufferOptions, CancelOrderResponse, Candle,
+    CandleInterval, DepthAtPrice, FillEstimate, HistoricalReplay, IdleOptions, OrderBook,
+    OrderRequest, OrderTags, OrderbookEntry, OrderbookLevels, OverflowPolicy, Portfolio, Position,
+    PublicClient, RateLimits, ReconnectOptions, ReplaySpeed, RiskConfig, RiskViolation,
+    SendOrderResponse, Side, StreamHandle, StreamOrderbookOptions, StreamTradesOptions,
+    Subscription, SubscriptionHub, Ticker, Trade,
+};
+
+#[cfg(all(feature = "client", feature = "admin"))]
+pub use crate::JwtManager;
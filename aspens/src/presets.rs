@@ -0,0 +1,468 @@
+//! Named order presets.
+//!
+//! An [`OrderPreset`] captures the arguments of an order — market, side,
+//! amount, and either a fixed price or an offset from the current mid — so a
+//! long command line can be saved once and re-run by name. Presets live in
+//! a JSON file under the profile directory (see
+//! [`crate::storage`]), managed by [`PresetStore`].
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::api::OrderRequest;
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::commands::trading::send_order::{self, ExecutionType, OrderTags, arborter_pb::Side};
+use crate::commands::trading::stream_orderbook::{self, TopOfBook};
+use crate::decimals::{PrecisionMode, format_decimal_amount};
+use crate::schema::{Schema, wrap_in};
+use crate::storage::{Storage, default_storage, storage_for_path};
+use crate::util::{normalize_amount, parse_signed_bps};
+
+/// File name of the preset store inside the profile directory.
+pub const PRESETS_FILE: &str = "presets.json";
+
+/// v1 was the bare name-to-preset map; v2 moves it under `presets`.
+static PRESETS_SCHEMA: Schema = Schema::new(
+    "preset file",
+    &[|doc| {
+        wrap_in(doc, "presets");
+        Ok(())
+    }],
+);
+
+#[derive(Deserialize)]
+struct PresetsFile {
+    #[serde(default)]
+    presets: BTreeMap<String, OrderPreset>,
+}
+
+/// How long [`OrderPreset::resolve`] listens to the orderbook stream to find
+/// the mid price for an offset preset.
+pub const PRESET_BOOK_WINDOW: Duration = Duration::from_millis(1_500);
+
+/// A saved order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderPreset {
+    /// Market ID or `base_network/SYMBOL::quote_network/SYMBOL` shorthand.
+    pub market: String,
+    /// `buy`/`bid` or `sell`/`ask`.
+    pub side: String,
+    /// Quantity as a decimal string.
+    pub amount: String,
+    /// Fixed limit price as a decimal string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+    /// Limit price as a signed offset from the mid, in basis points
+    /// (`-10` = 0.1% below mid).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_offset_bps: Option<i32>,
+    /// Submit as post-only.
+    #[serde(default)]
+    pub post_only: bool,
+    /// Submit as a hidden order.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Submit as a batch-auction order.
+    #[serde(default)]
+    pub auction: bool,
+    /// Time in force.
+    #[serde(default)]
+    pub execution: ExecutionType,
+    /// Strategy ID attached to the order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    /// Tags attached to the order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl OrderPreset {
+    /// Typed side.
+    pub fn side(&self) -> Result<Side> {
+        Ok(send_order::parse_side(&self.side)?)
+    }
+
+    /// The strategy / tags as [`OrderTags`].
+    pub fn order_tags(&self) -> OrderTags {
+        OrderTags {
+            strategy: self.strategy.clone(),
+            tags: self.tags.clone(),
+            referral: None,
+        }
+    }
+
+    /// Check the fields parse and don't contradict each other.
+    pub fn validate(&self) -> Result<()> {
+        if self.market.is_empty() {
+            return Err(eyre!("preset has no market"));
+        }
+        self.side()?;
+        normalize_amount(&self.amount)?;
+        if let Some(price) = &self.price {
+            normalize_amount(price)?;
+        }
+        if self.price.is_some() && self.price_offset_bps.is_some() {
+            return Err(eyre!("a preset takes a price or a price offset, not both"));
+        }
+        if self.price_offset_bps.is_some_and(|bps| bps <= -10_000) {
+            return Err(eyre!("price offset must be above -100%"));
+        }
+        if (self.post_only || self.auction)
+            && self.price.is_none()
+            && self.price_offset_bps.is_none()
+        {
+            return Err(eyre!(
+                "post_only and auction need a limit price; set a price or a price offset"
+            ));
+        }
+        if self.execution.is_immediate() && (self.post_only || self.auction) {
+            return Err(eyre!(
+                "time in force '{}' can't be combined with post_only or auction",
+                self.execution
+            ));
+        }
+        Ok(self.order_tags().validate()?)
+    }
+
+    /// Set one field from its CLI spelling, for `--override key=value`.
+    ///
+    /// Keys: `market`, `side`, `amount`, `price`, `price-offset`,
+    /// `post-only`, `hidden`, `auction`, `tif` (time in force), `strategy`
+    /// and `tags` (comma-separated).
+    /// Setting `price` clears `price-offset` and vice versa.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let flag = |v: &str| {
+            v.parse::<bool>()
+                .map_err(|_| eyre!("'{}' must be true or false, got '{}'", key, v))
+        };
+        match key.replace('_', "-").as_str() {
+            "market" => self.market = value.to_string(),
+            "side" => self.side = value.to_string(),
+            "amount" => self.amount = normalize_amount(value)?,
+            "price" => {
+                self.price = Some(normalize_amount(value)?);
+                self.price_offset_bps = None;
+            }
+            "price-offset" => {
+                self.price_offset_bps = Some(parse_signed_bps(value)?);
+                self.price = None;
+            }
+            "post-only" => self.post_only = flag(value)?,
+            "hidden" => self.hidden = flag(value)?,
+            "auction" => self.auction = flag(value)?,
+            "tif" => self.execution = value.parse()?,
+            "strategy" => self.strategy = Some(value.to_string()).filter(|s| !s.is_empty()),
+            "tags" => {
+                self.tags = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect()
+            }
+            other => {
+                return Err(eyre!(
+                    "unknown preset field '{}' (use market, side, amount, price, price-offset, \
+                     post-only, hidden, auction, tif, strategy or tags)",
+                    other
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn the preset into an order, using `top` for an offset price.
+    ///
+    /// The offset applies to the mid of `top`, or to whichever side exists
+    /// when the book is one-sided.
+    pub fn to_order_request(&self, top: &TopOfBook, pair_decimals: u32) -> Result<OrderRequest> {
+        self.validate()?;
+        let price = match (self.price.clone(), self.price_offset_bps) {
+            (Some(price), _) => Some(price),
+            (None, Some(bps)) => {
+                let mid = match (top.best_bid, top.best_ask) {
+                    (Some(bid), Some(ask)) => bid / 2 + ask / 2 + (bid % 2 + ask % 2) / 2,
+                    (Some(price), None) | (None, Some(price)) => price,
+                    (None, None) => {
+                        return Err(eyre!(
+                            "no resting orders on {} to take a price offset from",
+                            self.market
+                        ));
+                    }
+                };
+                Some(format_decimal_amount(
+                    apply_price_offset(mid, bps)?,
+                    pair_decimals,
+                ))
+            }
+            (None, None) => None,
+        };
+        Ok(OrderRequest {
+            market_id: self.market.clone(),
+            side: self.side()? as i32,
+            quantity: self.amount.clone(),
+            price,
+            post_only: self.post_only,
+            hidden: self.hidden,
+            auction: self.auction,
+            execution: self.execution,
+            expires_at: None,
+            precision: PrecisionMode::default(),
+            client_order_id: None,
+            tags: self.order_tags(),
+        })
+    }
+
+    /// Resolve the preset into an order against a live stack, snapshotting
+    /// the book when the price is an offset.
+    pub async fn resolve(&self, url: String, config: &GetConfigResponse) -> Result<OrderRequest> {
+        let market = send_order::lookup_market(config, &self.market)?;
+        let top = match self.price_offset_bps {
+            Some(_) => {
+                stream_orderbook::fetch_top_of_book(
+                    url,
+                    market.market_id.clone(),
+                    PRESET_BOOK_WINDOW,
+                )
+                .await?
+            }
+            None => TopOfBook::default(),
+        };
+        self.to_order_request(&top, market.pair_decimals as u32)
+    }
+}
+
+/// Move a raw pair-scale price by `offset_bps` basis points (rounded down).
+pub fn apply_price_offset(price: u128, offset_bps: i32) -> Result<u128> {
+    let factor = 10_000i64 + i64::from(offset_bps);
+    if factor <= 0 {
+        return Err(eyre!("price offset must be above -100%"));
+    }
+    price
+        .checked_mul(factor as u128)
+        .map(|p| p / 10_000)
+        .ok_or_else(|| eyre!("price offset overflows"))
+}
+
+/// The saved presets, keyed by name.
+#[derive(Debug)]
+pub struct PresetStore {
+    storage: Arc<dyn Storage>,
+    name: String,
+    presets: BTreeMap<String, OrderPreset>,
+}
+
+impl PresetStore {
+    /// Open the preset file at `path`; a missing file is an empty store.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let (storage, name) = storage_for_path(&path.into())?;
+        Self::load(storage, name)
+    }
+
+    /// Open [`PRESETS_FILE`] in `storage`.
+    pub fn open_in(storage: Arc<dyn Storage>) -> Result<Self> {
+        Self::load(storage, PRESETS_FILE.to_string())
+    }
+
+    /// Open [`PRESETS_FILE`] in the default storage (see [`crate::storage`]).
+    pub fn open_default() -> Result<Self> {
+        Self::open_in(default_storage()?)
+    }
+
+    fn load(storage: Arc<dyn Storage>, name: String) -> Result<Self> {
+        let presets = match storage.read(&name)? {
+            Some(json) => {
+                PRESETS_SCHEMA
+                    .load::<PresetsFile>(&json)
+                    .map_err(|e| {
+                        eyre!(
+                            "invalid preset file {}: {}",
+                            storage.path(&name).display(),
+                            e
+                        )
+                    })?
+                    .presets
+            }
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            storage,
+            name,
+            presets,
+        })
+    }
+
+    /// Path of the preset file.
+    pub fn path(&self) -> PathBuf {
+        self.storage.path(&self.name)
+    }
+
+    /// All presets, sorted by name.
+    pub fn presets(&self) -> &BTreeMap<String, OrderPreset> {
+        &self.presets
+    }
+
+    /// Look up a preset by name.
+    pub fn get(&self, name: &str) -> Result<&OrderPreset> {
+        self.presets.get(name).ok_or_else(|| {
+            eyre!(
+                "no preset named '{}' (saved: {})",
+                name,
+                if self.presets.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.presets.keys().cloned().collect::<Vec<_>>().join(", ")
+                }
+            )
+        })
+    }
+
+    /// Validate and save `preset` as `name`, replacing any preset of that
+    /// name.
+    pub fn save(&mut self, name: &str, preset: OrderPreset) -> Result<()> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(eyre!(
+                "invalid preset name '{}': use letters, digits, '-' and '_'",
+                name
+            ));
+        }
+        preset.validate()?;
+        self.presets.insert(name.to_string(), preset);
+        self.write()
+    }
+
+    /// Delete preset `name`. Returns false if there was no such preset.
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        if self.presets.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.write()?;
+        Ok(true)
+    }
+
+    fn write(&self) -> Result<()> {
+        let _lock = self.storage.lock(&self.name)?;
+        self.storage.write(
+            &self.name,
+            &PRESETS_SCHEMA.to_string(&serde_json::json!({ "presets": self.presets }))?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mm_bid() -> OrderPreset {
+        OrderPreset {
+            market: "m".into(),
+            side: "buy".into(),
+            amount: "100".into(),
+            price_offset_bps: Some(-10),
+            post_only: true,
+            strategy: Some("mm".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn offset_prices_from_mid() {
+        let top = TopOfBook {
+            best_bid: Some(99_000_000),
+            best_ask: Some(101_000_000),
+        };
+        let order = mm_bid().to_order_request(&top, 6).unwrap();
+        assert_eq!(order.price.as_deref(), Some("99.900000"));
+        assert_eq!(order.side, Side::Bid as i32);
+        assert!(order.post_only);
+        assert!(order.tags.matches("mm"));
+
+        assert!(mm_bid().to_order_request(&TopOfBook::default(), 6).is_err());
+    }
+
+    #[test]
+    fn overrides_replace_fields() {
+        let mut preset = mm_bid();
+        preset.set("price", "1_000").unwrap();
+        assert_eq!(preset.price.as_deref(), Some("1000"));
+        assert_eq!(preset.price_offset_bps, None);
+        preset.set("price-offset", "+0.5%").unwrap();
+        assert_eq!(preset.price_offset_bps, Some(50));
+        assert_eq!(preset.price, None);
+        preset.set("hidden", "true").unwrap();
+        assert!(preset.hidden);
+        preset.set("auction", "true").unwrap();
+        assert!(preset.auction);
+        preset.set("tif", "fok").unwrap();
+        assert_eq!(preset.execution, ExecutionType::FillOrKill);
+        // Fill-or-kill never rests, so post-only / auction contradict it.
+        assert!(preset.validate().is_err());
+        assert!(preset.set("colour", "blue").is_err());
+        assert!(preset.set("post_only", "maybe").is_err());
+    }
+
+    #[test]
+    fn rejects_contradictory_presets() {
+        let both = OrderPreset {
+            price: Some("1".into()),
+            ..mm_bid()
+        };
+        assert!(both.validate().is_err());
+        let post_only_market = OrderPreset {
+            price_offset_bps: None,
+            ..mm_bid()
+        };
+        assert!(post_only_market.validate().is_err());
+        assert!(apply_price_offset(100, -10_000).is_err());
+    }
+
+    #[test]
+    fn store_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PRESETS_FILE);
+        let mut store = PresetStore::open(&path).unwrap();
+        store.save("mm-bid", mm_bid()).unwrap();
+        assert!(store.save("bad name", mm_bid()).is_err());
+
+        let reopened = PresetStore::open(&path).unwrap();
+        assert_eq!(reopened.get("mm-bid").unwrap(), &mm_bid());
+        assert!(reopened.get("nope").is_err());
+
+        assert!(store.remove("mm-bid").unwrap());
+        assert!(!store.remove("mm-bid").unwrap());
+    }
+
+    #[test]
+    fn store_opens_in_any_storage() {
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let mut store = PresetStore::open_in(storage.clone()).unwrap();
+        store.save("mm-bid", mm_bid()).unwrap();
+        assert!(storage.read(PRESETS_FILE).unwrap().is_some());
+        let reopened = PresetStore::open_in(storage).unwrap();
+        assert_eq!(reopened.get("mm-bid").unwrap(), &mm_bid());
+    }
+
+    #[test]
+    fn store_upgrades_unversioned_file() {
+        let storage = Arc::new(crate::storage::MemoryStorage::new());
+        let v1 = serde_json::json!({ "mm-bid": mm_bid() }).to_string();
+        storage.write(PRESETS_FILE, &v1).unwrap();
+
+        let mut store = PresetStore::open_in(storage.clone()).unwrap();
+        assert_eq!(store.get("mm-bid").unwrap(), &mm_bid());
+        store.save("mm-ask", mm_bid()).unwrap();
+        let saved: serde_json::Value =
+            serde_json::from_str(&storage.read(PRESETS_FILE).unwrap().unwrap()).unwrap();
+        assert_eq!(saved[crate::schema::VERSION_KEY], 2);
+        assert!(saved["presets"]["mm-bid"].is_object());
+    }
+}
@@ -0,0 +1,383 @@
+//! Named profiles in the user's config file.
+//!
+//! `~/.config/aspens/config.toml` (see [`config_file`]) keeps one
+//! `[profiles.<name>]` table per setup, e.g. a testnet and a mainnet stack:
+//!
+//! ```toml
+//! default = "testnet"
+//!
+//! [profiles.testnet]
+//! stack_url = "https://testnet.example.com:50051"
+//! env_file = "~/aspens/testnet.env"
+//! market = "ETH-USDC"
+//! output = "json"
+//! signer = "keystore:~/keys/trader.json"
+//! ```
+//!
+//! The binaries pick a profile with `--profile <name>`, falling back to
+//! `ASPENS_PROFILE` and then to `default`. A profile's settings are
+//! defaults: a flag given on the command line still wins. The active
+//! profile's name also scopes [`FsStorage::open_default`], so presets and
+//! journals stay per profile.
+//!
+//! [`FsStorage::open_default`]: crate::storage::FsStorage::open_default
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::PROFILE_ENV;
+use crate::util::{read_profile_file, write_profile_file};
+
+/// Environment variable overriding [`config_file`].
+pub const CONFIG_FILE_ENV: &str = "ASPENS_CONFIG";
+
+/// Environment variable holding the active profile's default market.
+pub const MARKET_ENV: &str = "ASPENS_MARKET";
+
+/// Environment variable holding the active profile's output format.
+pub const OUTPUT_ENV: &str = "ASPENS_OUTPUT";
+
+/// The user's config file: `$ASPENS_CONFIG` if set; `$ASPENS_HOME/config.toml`
+/// with `$ASPENS_HOME` set; otherwise `$XDG_CONFIG_HOME/aspens/config.toml`
+/// (`~/.config/aspens/config.toml` by default).
+pub fn config_file() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_FILE_ENV).filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    if let Some(dir) = std::env::var_os(crate::util::PROFILE_DIR_ENV).filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir).join("config.toml"));
+    }
+    let dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => crate::util::home_dir()?.join(".config"),
+    };
+    Ok(dir.join("aspens").join("config.toml"))
+}
+
+/// Where a profile's signing key comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerSource {
+    /// The `*_PRIVKEY` (or `TRADER_MNEMONIC`) variable in the env file.
+    Env,
+    /// An encrypted keystore file.
+    Keystore(PathBuf),
+    /// Account `N` of `TRADER_MNEMONIC`.
+    Mnemonic(u32),
+    /// A Ledger device, at a derivation path (`live:0` when `None`).
+    Ledger(Option<String>),
+    /// An AWS KMS key ID, alias or ARN.
+    Kms(String),
+}
+
+impl FromStr for SignerSource {
+    type Err = eyre::Report;
+
+    /// `env`, `keystore:<path>`, `mnemonic:<index>`, `ledger`,
+    /// `ledger:<path>` or `kms:<key>`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, value) = match s.trim().split_once(':') {
+            Some((kind, value)) => (kind, Some(value.trim())),
+            None => (s.trim(), None),
+        };
+        match (kind, value) {
+            ("env", None) => Ok(SignerSource::Env),
+            ("keystore", Some(path)) if !path.is_empty() => {
+                Ok(SignerSource::Keystore(PathBuf::from(path)))
+            }
+            ("mnemonic", Some(index)) => index
+                .parse()
+                .map(SignerSource::Mnemonic)
+                .map_err(|_| eyre!("invalid mnemonic account index '{}'", index)),
+            ("ledger", None) => Ok(SignerSource::Ledger(None)),
+            ("ledger", Some(path)) if !path.is_empty() => {
+                Ok(SignerSource::Ledger(Some(path.to_string())))
+            }
+            ("kms", Some(key)) if !key.is_empty() => Ok(SignerSource::Kms(key.to_string())),
+            _ => Err(eyre!(
+                "invalid signer '{}': expected env, keystore:<path>, mnemonic:<index>, \
+                 ledger[:<path>] or kms:<key>",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SignerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerSource::Env => write!(f, "env"),
+            SignerSource::Keystore(path) => write!(f, "keystore:{}", path.display()),
+            SignerSource::Mnemonic(index) => write!(f, "mnemonic:{}", index),
+            SignerSource::Ledger(None) => write!(f, "ledger"),
+            SignerSource::Ledger(Some(path)) => write!(f, "ledger:{}", path),
+            SignerSource::Kms(key) => write!(f, "kms:{}", key),
+        }
+    }
+}
+
+impl Serialize for SignerSource {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SignerSource {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// One named setup. Every field is optional; unset ones leave the
+/// binary's usual default in place.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// The Aspens stack URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_url: Option<String>,
+    /// The env file to load instead of `./.env`. A leading `~/` is the
+    /// home directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+    /// Market ID for commands that take one, when none is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>,
+    /// Output format, e.g. `text` or `json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Where the signing key comes from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<SignerSource>,
+}
+
+impl Profile {
+    /// [`env_file`](Self::env_file) with a leading `~/` expanded.
+    pub fn env_file_path(&self) -> Option<PathBuf> {
+        self.env_file.as_deref().map(expand_home)
+    }
+
+    /// The keystore file of a `keystore:` signer, `~/` expanded.
+    pub fn keystore(&self) -> Option<PathBuf> {
+        match &self.signer {
+            Some(SignerSource::Keystore(path)) => Some(expand_home(path)),
+            _ => None,
+        }
+    }
+
+    /// Export the profile's market and output format as [`MARKET_ENV`] and
+    /// [`OUTPUT_ENV`], for arguments that default from the environment.
+    /// Variables already set are left alone.
+    pub fn export_defaults(&self) {
+        for (var, value) in [(MARKET_ENV, &self.market), (OUTPUT_ENV, &self.output)] {
+            if let Some(value) = value
+                && std::env::var_os(var).is_none()
+            {
+                // SAFETY: called at startup, before the binaries spawn
+                // threads or a runtime.
+                unsafe { std::env::set_var(var, value) };
+            }
+        }
+    }
+}
+
+/// The profiles in the config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfilesConfig {
+    /// Profile used when none is named.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Profiles by name.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfilesConfig {
+    /// Read [`config_file`]; empty if it doesn't exist.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&config_file()?)
+    }
+
+    /// Read `path`; empty if it doesn't exist.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match read_profile_file(path)? {
+            Some(contents) => toml::from_str(&contents)
+                .map_err(|e| eyre!("invalid config file {}: {}", path.display(), e)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Write [`config_file`].
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&config_file()?)
+    }
+
+    /// Write `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string_pretty(self)
+            .map_err(|e| eyre!("failed to encode the config file: {}", e))?;
+        write_profile_file(path, &contents)
+    }
+
+    /// Add `profile` as `name`, replacing one of the same name. The first
+    /// profile added becomes the default.
+    pub fn add(&mut self, name: &str, profile: Profile) -> Result<()> {
+        validate_name(name)?;
+        self.profiles.insert(name.to_string(), profile);
+        if self.default.is_none() {
+            self.default = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Make `name` the default profile.
+    pub fn set_default(&mut self, name: &str) -> Result<()> {
+        self.get(name)?;
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    /// The profile called `name`.
+    pub fn get(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let known = self.profiles.keys().cloned().collect::<Vec<_>>();
+            eyre!(
+                "no profile named '{}' (known: {})",
+                name,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+        })
+    }
+
+    /// The active profile: `name` if given, else `$ASPENS_PROFILE`, else
+    /// the default. `None` when none is named and there is no default.
+    ///
+    /// An `ASPENS_PROFILE` missing from the file isn't an error: it still
+    /// scopes storage on its own, as it did before profiles had settings.
+    /// Nor is a dangling default, which `profile use` must be able to fix.
+    pub fn resolve(&self, name: Option<&str>) -> Result<Option<(String, Profile)>> {
+        if let Some(name) = name {
+            return Ok(Some((name.to_string(), self.get(name)?.clone())));
+        }
+        if let Ok(name) = std::env::var(PROFILE_ENV)
+            && !name.trim().is_empty()
+        {
+            let name = name.trim();
+            return Ok(self
+                .profiles
+                .get(name)
+                .map(|profile| (name.to_string(), profile.clone())));
+        }
+        Ok(self.default.as_ref().and_then(|name| {
+            self.profiles
+                .get(name)
+                .map(|profile| (name.clone(), profile.clone()))
+        }))
+    }
+}
+
+/// Make `name` the active profile for this process: storage is scoped to it
+/// (via `ASPENS_PROFILE`) and its market and output defaults are exported.
+pub fn activate(name: &str, profile: &Profile) {
+    // SAFETY: called at startup, before the binaries spawn threads or a
+    // runtime.
+    unsafe { std::env::set_var(PROFILE_ENV, name) };
+    profile.export_defaults();
+}
+
+/// Profile names double as storage directory names.
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(eyre!(
+            "invalid profile name '{}': use letters, digits, '-' and '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => match crate::util::home_dir() {
+            Ok(home) => home.join(rest),
+            Err(_) => path.to_path_buf(),
+        },
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_round_trip_through_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aspens").join("config.toml");
+        assert_eq!(
+            ProfilesConfig::load_from(&path).unwrap(),
+            ProfilesConfig::default()
+        );
+
+        let mut config = ProfilesConfig::default();
+        config
+            .add(
+                "testnet",
+                Profile {
+                    stack_url: Some("http://localhost:50051".into()),
+                    market: Some("ETH-USDC".into()),
+                    signer: Some("keystore:~/keys/trader.json".parse().unwrap()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        config.add("mainnet", Profile::default()).unwrap();
+        assert_eq!(config.default.as_deref(), Some("testnet"));
+        config.set_default("mainnet").unwrap();
+        assert!(config.set_default("devnet").is_err());
+        assert!(config.add("../up", Profile::default()).is_err());
+
+        config.save_to(&path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("signer = \"keystore:~/keys/trader.json\""));
+        assert_eq!(ProfilesConfig::load_from(&path).unwrap(), config);
+
+        let (name, profile) = config.resolve(Some("testnet")).unwrap().unwrap();
+        assert_eq!(name, "testnet");
+        assert_eq!(profile.market.as_deref(), Some("ETH-USDC"));
+        assert!(config.resolve(Some("devnet")).is_err());
+    }
+
+    #[test]
+    fn parses_signer_sources() {
+        for s in [
+            "env",
+            "keystore:/keys/a.json",
+            "mnemonic:3",
+            "ledger",
+            "ledger:legacy:1",
+            "kms:alias/trader",
+        ] {
+            assert_eq!(s.parse::<SignerSource>().unwrap().to_string(), s);
+        }
+        assert_eq!(
+            "ledger:legacy:1".parse::<SignerSource>().unwrap(),
+            SignerSource::Ledger(Some("legacy:1".into()))
+        );
+        assert!("mnemonic:x".parse::<SignerSource>().is_err());
+        assert!("keystore".parse::<SignerSource>().is_err());
+        assert!("vault:x".parse::<SignerSource>().is_err());
+    }
+}
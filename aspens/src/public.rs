@@ -0,0 +1,263 @@
+//! Anonymous, rate-limited access to a stack's public data.
+//!
+//! [`PublicClient`] is for community dashboards and other integrations that
+//! only read: the stack configuration, the orderbook and trades streams,
+//! and book snapshots. It has no order, cancel, balance or login methods,
+//! never reads an env file and never holds a wallet, so code written
+//! against it can't come to depend on local secrets. Build one with
+//! [`AspensClient::public`](crate::AspensClient::public).
+//!
+//! Every call goes through built-in [`RateLimits`]: requests beyond the
+//! per-minute budget wait for a slot instead of failing, and streams beyond
+//! the concurrent cap are refused until one is stopped. The defaults are
+//! deliberately conservative for a shared, unauthenticated endpoint.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwapOption;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::api::StreamHandle;
+use crate::commands::config::config_pb::GetConfigResponse;
+use crate::commands::trading::conflate::{BookSnapshot, fetch_book_snapshot};
+use crate::commands::trading::stream_orderbook::{
+    self, StreamOrderbookOptions, TopOfBook, arborter_pb::OrderbookEntry,
+};
+use crate::commands::trading::stream_trades::{self, StreamTradesOptions, arborter_pb::Trade};
+use crate::error::{AspensError, Result};
+use crate::executor::AbortOnDrop;
+
+/// How long a book snapshot collects the resting orders.
+pub const SNAPSHOT_WINDOW: Duration = Duration::from_secs(2);
+
+/// Limits a [`PublicClient`] applies to itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimits {
+    /// Requests (config fetches, snapshots, stream opens) per minute.
+    pub requests_per_minute: u32,
+    /// Streams open at once, snapshots included.
+    pub max_streams: usize,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 30,
+            max_streams: 4,
+        }
+    }
+}
+
+/// Read-only, unauthenticated client for a stack's public endpoints.
+///
+/// Cheap to [`Clone`]; clones share the cached config and the rate limits.
+#[derive(Clone)]
+pub struct PublicClient {
+    stack_url: Arc<Url>,
+    limits: RateLimits,
+    config: Arc<ArcSwapOption<GetConfigResponse>>,
+    requests: Arc<Mutex<VecDeque<Instant>>>,
+    streams: Arc<Semaphore>,
+}
+
+impl PublicClient {
+    /// A client for the stack at `url` with the default [`RateLimits`].
+    pub fn new(url: impl AsRef<str>) -> eyre::Result<Self> {
+        Self::with_limits(url, RateLimits::default())
+    }
+
+    /// A client for the stack at `url` with custom `limits`.
+    pub fn with_limits(url: impl AsRef<str>, limits: RateLimits) -> eyre::Result<Self> {
+        let url = url.as_ref();
+        let stack_url = Url::parse(url).map_err(|e| eyre::eyre!("invalid URL '{}': {}", url, e))?;
+        if limits.requests_per_minute == 0 || limits.max_streams == 0 {
+            return Err(eyre::eyre!(
+                "public client limits must allow at least one request and one stream"
+            ));
+        }
+        Ok(Self {
+            stack_url: Arc::new(stack_url),
+            limits,
+            config: Arc::new(ArcSwapOption::empty()),
+            requests: Arc::new(Mutex::new(VecDeque::new())),
+            streams: Arc::new(Semaphore::new(limits.max_streams)),
+        })
+    }
+
+    /// The Aspens Market Stack URL.
+    pub fn stack_url(&self) -> &Url {
+        &self.stack_url
+    }
+
+    /// The limits this client applies.
+    pub fn rate_limits(&self) -> RateLimits {
+        self.limits
+    }
+
+    /// The cached configuration, fetching it on first use.
+    pub async fn get_config(&self) -> Result<GetConfigResponse> {
+        if let Some(config) = self.config.load_full() {
+            return Ok((*config).clone());
+        }
+        self.fetch_config().await
+    }
+
+    /// Fetch the configuration again and cache it.
+    pub async fn fetch_config(&self) -> Result<GetConfigResponse> {
+        self.throttle().await;
+        let config = crate::commands::config::get_config(self.stack_url.to_string()).await?;
+        self.config.store(Some(Arc::new(config.clone())));
+        Ok(config)
+    }
+
+    /// Stream a market's orderbook. Counts against
+    /// [`RateLimits::max_streams`] until the stream ends or its task is
+    /// aborted.
+    pub async fn stream_orderbook(
+        &self,
+        options: StreamOrderbookOptions,
+    ) -> Result<StreamHandle<OrderbookEntry>> {
+        let permit = self.open_stream().await?;
+        let (rx, task) =
+            stream_orderbook::stream_orderbook_channel(self.stack_url.to_string(), options).await?;
+        Ok((rx, hold_permit(task, permit)))
+    }
+
+    /// Stream a market's trades. Counts against
+    /// [`RateLimits::max_streams`] like
+    /// [`stream_orderbook`](Self::stream_orderbook).
+    pub async fn stream_trades(&self, options: StreamTradesOptions) -> Result<StreamHandle<Trade>> {
+        let permit = self.open_stream().await?;
+        let (rx, task) =
+            stream_trades::stream_trades_channel(self.stack_url.to_string(), options).await?;
+        Ok((rx, hold_permit(task, permit)))
+    }
+
+    /// The resting book of `market_id`, aggregated into price levels.
+    pub async fn book_snapshot(&self, market_id: &str) -> Result<BookSnapshot> {
+        let _permit = self.open_stream().await?;
+        fetch_book_snapshot(
+            self.stack_url.to_string(),
+            market_id.to_string(),
+            SNAPSHOT_WINDOW,
+        )
+        .await
+    }
+
+    /// Best bid and ask of `market_id`.
+    pub async fn top_of_book(&self, market_id: &str) -> Result<TopOfBook> {
+        let _permit = self.open_stream().await?;
+        stream_orderbook::fetch_top_of_book(
+            self.stack_url.to_string(),
+            market_id.to_string(),
+            SNAPSHOT_WINDOW,
+        )
+        .await
+    }
+
+    /// Take a stream slot and a request slot.
+    async fn open_stream(&self) -> Result<OwnedSemaphorePermit> {
+        let permit = self.streams.clone().try_acquire_owned().map_err(|_| {
+            AspensError::invalid_input(format!(
+                "the public client allows {} streams at once; stop one before opening another",
+                self.limits.max_streams
+            ))
+        })?;
+        self.throttle().await;
+        Ok(permit)
+    }
+
+    /// Wait until a request fits in the per-minute budget, then record it.
+    async fn throttle(&self) {
+        const WINDOW: Duration = Duration::from_secs(60);
+        loop {
+            let wait = {
+                let mut requests = self.requests.lock().await;
+                let now = Instant::now();
+                while requests.front().is_some_and(|&t| now - t >= WINDOW) {
+                    requests.pop_front();
+                }
+                if requests.len() < self.limits.requests_per_minute as usize {
+                    requests.push_back(now);
+                    return;
+                }
+                // The oldest request leaves the window first.
+                WINDOW - (now - requests[0])
+            };
+            tracing::debug!("public client rate limit reached; waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Release `permit` when `task` ends. Aborting the returned handle aborts
+/// `task` too.
+fn hold_permit(
+    task: JoinHandle<Result<()>>,
+    permit: OwnedSemaphorePermit,
+) -> JoinHandle<Result<()>> {
+    tokio::spawn(async move {
+        let _permit = permit;
+        let mut task = AbortOnDrop(task);
+        (&mut task.0).await?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn throttles_beyond_the_request_budget() {
+        let client = PublicClient::with_limits(
+            "http://example.com",
+            RateLimits {
+                requests_per_minute: 2,
+                max_streams: 1,
+            },
+        )
+        .unwrap();
+        let start = Instant::now();
+        client.throttle().await;
+        client.throttle().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        client.throttle().await;
+        assert!(start.elapsed() >= Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn caps_concurrent_streams() {
+        let client = PublicClient::with_limits(
+            "http://example.com",
+            RateLimits {
+                requests_per_minute: 10,
+                max_streams: 1,
+            },
+        )
+        .unwrap();
+        let permit = client.open_stream().await.unwrap();
+        assert!(matches!(
+            client.open_stream().await,
+            Err(AspensError::InvalidInput(_))
+        ));
+
+        // The slot frees when the stream's task ends.
+        let task = hold_permit(tokio::spawn(async { Ok(()) }), permit);
+        task.await.unwrap().unwrap();
+        assert!(client.open_stream().await.is_ok());
+    }
+
+    #[test]
+    fn rejects_zero_limits() {
+        let limits = RateLimits {
+            requests_per_minute: 0,
+            ..RateLimits::default()
+        };
+        assert!(PublicClient::with_limits("http://example.com", limits).is_err());
+    }
+}
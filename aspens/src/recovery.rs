@@ -0,0 +1,371 @@
+//! Startup reconciliation for bots that crashed mid-session.
+//!
+//! A bot that dies between submitting an order and recording the response
+//! can't tell on restart whether that order exists. [`recover`] compares
+//! what the bot believes (its [`KnownOrder`]s, each with the bot's own
+//! idempotency key) against the trader's live open orders and recent trades
+//! on the stack, and returns a [`RecoveryReport`]: which orders are still
+//! resting, which traded or closed, which unrecorded submissions turned out
+//! to be on the book, and which open orders the bot doesn't know about.
+//!
+//! The orderbook stream doesn't carry client order IDs, so an order whose
+//! response was never recorded is matched to an open order by side, price
+//! and quantity. Within one process, resubmitting it under its
+//! [`client_order_id`](OrderRequest::client_order_id) is the simpler fix.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+
+use crate::api::{AspensApi, OrderRequest, StreamHandle};
+use crate::commands::trading::open_orders::open_orders;
+use crate::commands::trading::send_order;
+use crate::commands::trading::stream_orderbook::{
+    StreamOrderbookOptions, arborter_pb::OrderbookEntry,
+};
+use crate::commands::trading::stream_trades::{StreamTradesOptions, arborter_pb::Trade};
+use crate::util::parse_amount;
+
+/// How long [`recover`] listens to each stream by default.
+pub const DEFAULT_RECOVERY_WINDOW: Duration = Duration::from_millis(1_500);
+
+/// An order the bot believes it placed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownOrder {
+    /// The bot's own idempotency key for the submission.
+    pub key: String,
+    /// The order as submitted.
+    pub order: OrderRequest,
+    /// Order ID from the `SendOrderResponse`, if it was recorded.
+    pub order_id: Option<u64>,
+}
+
+/// What to reconcile against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryOptions {
+    /// Market to reconcile.
+    pub market_id: String,
+    /// The trader's address, used to filter both streams.
+    pub trader: String,
+    /// How long to collect historical orders and trades.
+    pub window: Duration,
+}
+
+impl RecoveryOptions {
+    /// Options with the default collection window.
+    pub fn new(market_id: impl Into<String>, trader: impl Into<String>) -> Self {
+        Self {
+            market_id: market_id.into(),
+            trader: trader.into(),
+            window: DEFAULT_RECOVERY_WINDOW,
+        }
+    }
+}
+
+/// Where a known order stands on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveredStatus {
+    /// Resting on the book (pending or confirmed).
+    Open,
+    /// Not on the book, and at least one recent trade hit it.
+    Traded,
+    /// Not on the book and no recent trade: cancelled, or filled before the
+    /// trade history window.
+    Closed,
+}
+
+/// A known order reconciled against the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredOrder {
+    /// The bot's idempotency key.
+    pub key: String,
+    /// Order ID on the stack.
+    pub order_id: u64,
+    /// Where the order stands.
+    pub status: RecoveredStatus,
+    /// Remaining quantity (raw pair decimals) for open orders.
+    pub remaining: Option<String>,
+}
+
+/// Result of [`recover`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecoveryReport {
+    /// Known orders with an order ID, now or after resolution.
+    pub orders: Vec<RecoveredOrder>,
+    /// Keys of unrecorded submissions matched to an open order; their IDs
+    /// are in `orders`.
+    pub resolved: Vec<String>,
+    /// Keys of unrecorded submissions with no matching open order. They
+    /// most likely never reached the book, but may have filled immediately;
+    /// check `trades` before re-placing them.
+    pub unmatched: Vec<String>,
+    /// Open orders on the stack the bot doesn't know about.
+    pub unknown_open: Vec<OrderbookEntry>,
+    /// The trader's recent trades on the market.
+    pub trades: Vec<Trade>,
+}
+
+impl RecoveryReport {
+    /// True when every known order is accounted for and nothing unknown is
+    /// resting, so the bot can resume without cleanup.
+    pub fn is_consistent(&self) -> bool {
+        self.unmatched.is_empty() && self.unknown_open.is_empty()
+    }
+
+    /// Known orders still resting.
+    pub fn open_orders(&self) -> impl Iterator<Item = &RecoveredOrder> {
+        self.orders
+            .iter()
+            .filter(|o| o.status == RecoveredStatus::Open)
+    }
+}
+
+/// Reconcile `known` orders with the trader's open orders and recent trades
+/// on `options.market_id`.
+///
+/// Known orders for other markets are ignored.
+pub async fn recover<A: AspensApi>(
+    api: &A,
+    options: &RecoveryOptions,
+    known: &[KnownOrder],
+) -> Result<RecoveryReport> {
+    let config = api.get_config().await?;
+    let market = send_order::lookup_market(&config, &options.market_id)?;
+    let pair_decimals = market.pair_decimals as u32;
+
+    let book = drain(
+        api.stream_orderbook(StreamOrderbookOptions {
+            market_id: market.market_id.clone(),
+            historical_open_orders: true,
+            filter_by_trader: Some(options.trader.clone()),
+            grpc: None,
+            replay: Default::default(),
+            idle: None,
+            reconnect: None,
+        })
+        .await?,
+        options.window,
+    )
+    .await;
+    let trades = drain(
+        api.stream_trades(StreamTradesOptions {
+            market_id: market.market_id.clone(),
+            historical_closed_trades: true,
+            filter_by_trader: Some(options.trader.clone()),
+            grpc: None,
+            replay: Default::default(),
+            idle: None,
+            reconnect: None,
+        })
+        .await?,
+        options.window,
+    )
+    .await;
+
+    let open = open_orders(book);
+    let traded: HashSet<u64> = trades.iter().map(|t| t.order_hit).collect();
+
+    let mut report = RecoveryReport {
+        trades,
+        ..Default::default()
+    };
+    let mut claimed: HashSet<u64> = HashSet::new();
+    let in_market: Vec<&KnownOrder> = known
+        .iter()
+        .filter(|k| {
+            send_order::lookup_market(&config, &k.order.market_id)
+                .is_ok_and(|m| m.market_id == market.market_id)
+        })
+        .collect();
+
+    // Orders with a recorded ID first, so resolution can't steal them.
+    for (k, order_id) in in_market
+        .iter()
+        .filter_map(|k| k.order_id.map(|id| (k, id)))
+    {
+        claimed.insert(order_id);
+        report.orders.push(status_of(k, order_id, &open, &traded));
+    }
+    for k in in_market.iter().filter(|k| k.order_id.is_none()) {
+        let quantity = parse_amount(&k.order.quantity, pair_decimals)
+            .map_err(|e| eyre!("known order '{}': invalid quantity: {}", k.key, e))?;
+        let price = k
+            .order
+            .price
+            .as_deref()
+            .map(|p| parse_amount(p, pair_decimals))
+            .transpose()
+            .map_err(|e| eyre!("known order '{}': invalid price: {}", k.key, e))?;
+        let candidate = open.iter().find(|e| {
+            !claimed.contains(&e.order_id)
+                && e.side == k.order.side
+                && price.is_some_and(|p| e.price.parse::<u128>().is_ok_and(|ep| ep == p))
+                && e.quantity.parse::<u128>().is_ok_and(|q| q <= quantity)
+        });
+        match candidate {
+            Some(entry) => {
+                claimed.insert(entry.order_id);
+                report.resolved.push(k.key.clone());
+                report
+                    .orders
+                    .push(status_of(k, entry.order_id, &open, &traded));
+            }
+            None => report.unmatched.push(k.key.clone()),
+        }
+    }
+
+    report.unknown_open = open
+        .into_iter()
+        .filter(|e| !claimed.contains(&e.order_id))
+        .collect();
+    Ok(report)
+}
+
+fn status_of(
+    known: &KnownOrder,
+    order_id: u64,
+    open: &[OrderbookEntry],
+    traded: &HashSet<u64>,
+) -> RecoveredOrder {
+    let resting = open.iter().find(|e| e.order_id == order_id);
+    let status = match resting {
+        Some(_) => RecoveredStatus::Open,
+        None if traded.contains(&order_id) => RecoveredStatus::Traded,
+        None => RecoveredStatus::Closed,
+    };
+    RecoveredOrder {
+        key: known.key.clone(),
+        order_id,
+        status,
+        remaining: resting.map(|e| e.quantity.clone()),
+    }
+}
+
+/// Collect everything a stream yields within `window`, then stop it.
+async fn drain<T>((mut rx, task): StreamHandle<T>, window: Duration) -> Vec<T> {
+    let mut items = Vec::new();
+    let deadline = tokio::time::sleep(window);
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            item = rx.recv() => match item {
+                Some(item) => items.push(item),
+                None => break,
+            },
+        }
+    }
+    task.abort();
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock::MockAspensApi;
+    use crate::commands::config::config_pb::{Configuration, GetConfigResponse, Market};
+    use crate::commands::trading::stream_orderbook::arborter_pb::OrderState;
+
+    fn entry(
+        order_id: u64,
+        side: i32,
+        price: &str,
+        quantity: &str,
+        state: OrderState,
+    ) -> OrderbookEntry {
+        OrderbookEntry {
+            timestamp: order_id,
+            order_id,
+            side,
+            price: price.into(),
+            quantity: quantity.into(),
+            market_id: "m".into(),
+            state: state as i32,
+            ..Default::default()
+        }
+    }
+
+    fn known(key: &str, order_id: Option<u64>, side: i32, price: &str, qty: &str) -> KnownOrder {
+        KnownOrder {
+            key: key.into(),
+            order: OrderRequest {
+                market_id: "m".into(),
+                side,
+                quantity: qty.into(),
+                price: Some(price.into()),
+                ..Default::default()
+            },
+            order_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn reconciles_recorded_unrecorded_and_unknown_orders() {
+        let mut api = MockAspensApi::new(GetConfigResponse {
+            config: Some(Configuration {
+                chains: vec![],
+                markets: vec![Market {
+                    market_id: "m".into(),
+                    pair_decimals: 2,
+                    ..Default::default()
+                }],
+                maintenance: None,
+                signing_versions: vec![],
+            }),
+        });
+        api.orderbook = vec![
+            entry(1, 1, "100", "500", OrderState::Confirmed),
+            // Order 2 was cancelled after being placed.
+            entry(2, 2, "110", "500", OrderState::Confirmed),
+            entry(2, 2, "110", "500", OrderState::Canceled),
+            // The unrecorded submission, partially filled.
+            entry(3, 2, "120", "200", OrderState::Confirmed),
+            // Placed by someone else with this key (or a previous run).
+            entry(4, 1, "90", "100", OrderState::Pending),
+        ];
+        api.trades = vec![Trade {
+            order_hit: 5,
+            ..Default::default()
+        }];
+
+        let known = [
+            known("a", Some(1), 1, "1", "5"),
+            known("b", Some(2), 2, "1.1", "5"),
+            known("c", None, 2, "1.2", "5"),
+            known("d", None, 1, "0.5", "1"),
+            known("e", Some(5), 1, "1", "1"),
+        ];
+        let report = recover(
+            &api,
+            &RecoveryOptions {
+                window: Duration::from_millis(50),
+                ..RecoveryOptions::new("m", "0xtrader")
+            },
+            &known,
+        )
+        .await
+        .unwrap();
+
+        let status: Vec<(&str, u64, RecoveredStatus)> = report
+            .orders
+            .iter()
+            .map(|o| (o.key.as_str(), o.order_id, o.status))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                ("a", 1, RecoveredStatus::Open),
+                ("b", 2, RecoveredStatus::Closed),
+                ("e", 5, RecoveredStatus::Traded),
+                ("c", 3, RecoveredStatus::Open),
+            ]
+        );
+        assert_eq!(report.resolved, vec!["c"]);
+        assert_eq!(report.unmatched, vec!["d"]);
+        assert_eq!(report.unknown_open.len(), 1);
+        assert_eq!(report.unknown_open[0].order_id, 4);
+        assert!(!report.is_consistent());
+        assert_eq!(report.open_orders().count(), 2);
+    }
+}
@@ -0,0 +1,127 @@
+//! Signing with keys held by a remote service.
+//!
+//! A [`RemoteSigner`] is an EVM key that never leaves a signing service —
+//! a cloud KMS, an HSM, a custody API — which signs 32-byte hashes on
+//! request. That is all the SDK needs: orders are EIP-191 hashes, admin
+//! logins EIP-712 digests, and transactions their signing hash, so a
+//! remote signer wrapped in [`Wallet::Remote`](crate::Wallet::Remote) signs
+//! orders, logins, deposits, withdrawals and deployments alike.
+//!
+//! [`AwsKmsSigner`](crate::kms::AwsKmsSigner) (the `kms` feature)
+//! implements it for AWS KMS; other backends implement the trait the same
+//! way.
+
+use std::fmt;
+use std::sync::Arc;
+
+use alloy::consensus::SignableTransaction;
+use alloy::network::TxSigner;
+use alloy_primitives::{Address, B256, Signature};
+use async_trait::async_trait;
+use eyre::Result;
+
+/// An EVM (secp256k1) key held by a signing service.
+#[async_trait]
+pub trait RemoteSigner: Send + Sync + fmt::Debug {
+    /// The key's address.
+    fn address(&self) -> Address;
+
+    /// Short name of the service for logs and errors, e.g. `aws-kms`.
+    fn backend(&self) -> &str;
+
+    /// Sign a 32-byte hash. The signature's parity must recover
+    /// [`address`](Self::address).
+    async fn sign_hash(&self, hash: &B256) -> Result<Signature>;
+}
+
+/// Every `Clone` remote signer signs commands directly; a shared
+/// `Arc<dyn RemoteSigner>` does through [`Wallet::Remote`](crate::Wallet::Remote).
+#[async_trait]
+impl<T: RemoteSigner + Clone + 'static> crate::AspensSigner for T {
+    fn curve(&self) -> crate::CurveType {
+        crate::CurveType::Secp256k1
+    }
+
+    fn address(&self) -> String {
+        RemoteSigner::address(self).to_checksum(None)
+    }
+
+    async fn sign_message(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        let hash = alloy_primitives::eip191_hash_message(msg);
+        Ok(self.sign_hash(&hash).await?.as_bytes().to_vec())
+    }
+
+    async fn sign_eip712_digest(&self, digest: B256) -> Result<Vec<u8>> {
+        Ok(self.sign_hash(&digest).await?.as_bytes().to_vec())
+    }
+
+    fn tx_signer(&self) -> Option<crate::signer::SharedTxSigner> {
+        Some(Arc::new(RemoteTxSigner(Arc::new(self.clone()))))
+    }
+}
+
+/// Adapts a [`RemoteSigner`] to alloy's transaction signing, for
+/// providers and hand-built transactions.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteTxSigner(pub(crate) Arc<dyn RemoteSigner>);
+
+#[async_trait]
+impl TxSigner<Signature> for RemoteTxSigner {
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &mut dyn SignableTransaction<Signature>,
+    ) -> alloy_signer::Result<Signature> {
+        let hash = tx.signature_hash();
+        self.0.sign_hash(&hash).await.map_err(|e| {
+            alloy_signer::Error::other(format!("{} did not sign: {}", self.0.backend(), e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::consensus::TxEip1559;
+    use alloy_signer::Signer;
+    use alloy_signer_local::PrivateKeySigner;
+
+    /// A "remote" signer backed by a local key.
+    #[derive(Debug)]
+    struct LocalRemote(PrivateKeySigner);
+
+    #[async_trait]
+    impl RemoteSigner for LocalRemote {
+        fn address(&self) -> Address {
+            self.0.address()
+        }
+
+        fn backend(&self) -> &str {
+            "local"
+        }
+
+        async fn sign_hash(&self, hash: &B256) -> Result<Signature> {
+            Ok(self.0.sign_hash(hash).await?)
+        }
+    }
+
+    #[tokio::test]
+    async fn signs_transactions_the_sender_recovers_from() {
+        let remote = Arc::new(LocalRemote(PrivateKeySigner::random()));
+        let signer = RemoteTxSigner(remote.clone());
+        let mut tx = TxEip1559 {
+            chain_id: 84532,
+            nonce: 3,
+            gas_limit: 21_000,
+            ..Default::default()
+        };
+        let signature = signer.sign_transaction(&mut tx).await.unwrap();
+        let recovered = signature
+            .recover_address_from_prehash(&tx.signature_hash())
+            .unwrap();
+        assert_eq!(recovered, remote.address());
+    }
+}
@@ -0,0 +1,613 @@
+//! Scheduled and recurring orders.
+//!
+//! A [`Scheduler`] keeps a list of [`ScheduledJob`]s in a JSON file under the
+//! profile directory (see [`crate::storage`]) and submits each one
+//! through an [`AspensApi`] when it falls due, for DCA-style buying and
+//! periodic rebalancing. Jobs repeat either on a fixed interval or on a
+//! five-field cron expression evaluated in UTC.
+//!
+//! The scheduler only fires while something is running it
+//! ([`Scheduler::run`], or `aspens-cli schedule run`); runs missed while it
+//! was stopped are skipped, not replayed.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::api::{AspensApi, OrderRequest};
+use crate::commands::trading::send_order::arborter_pb::SendOrderResponse;
+use crate::commands::trading::send_order::{ExecutionType, OrderTags};
+use crate::decimals::PrecisionMode;
+use crate::schema::Schema;
+use crate::storage::{Storage, default_storage, storage_for_path};
+use crate::util::{civil_from_days, unix_now};
+use crate::wallet::Wallet;
+
+/// File name of the job list inside the profile directory.
+pub const SCHEDULE_FILE: &str = "schedule.json";
+
+static SCHEDULE_SCHEMA: Schema = Schema::new("schedule file", &[]);
+
+/// Longest [`Scheduler::run`] sleeps before re-reading the job file, so jobs
+/// added from another process are picked up.
+pub const SCHEDULER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// When a job repeats.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// Every `secs` seconds after the previous run.
+    Every {
+        /// Interval in seconds; must be non-zero.
+        secs: u64,
+    },
+    /// On a five-field cron expression (see [`CronExpr`]).
+    Cron {
+        /// The expression as written, e.g. `0 9 * * MON-FRI`.
+        expr: String,
+    },
+}
+
+impl Schedule {
+    /// A fixed-interval schedule.
+    pub fn every(interval: Duration) -> Self {
+        Schedule::Every {
+            secs: interval.as_secs(),
+        }
+    }
+
+    /// A cron schedule.
+    pub fn cron(expr: impl Into<String>) -> Self {
+        Schedule::Cron { expr: expr.into() }
+    }
+
+    /// First run strictly after `after` (unix seconds).
+    pub fn next_after(&self, after: u64) -> Result<u64> {
+        match self {
+            Schedule::Every { secs: 0 } => Err(eyre!("schedule interval must be at least 1s")),
+            Schedule::Every { secs } => Ok(after + secs),
+            Schedule::Cron { expr } => CronExpr::parse(expr)?
+                .next_after(after)
+                .ok_or_else(|| eyre!("cron expression '{}' never fires", expr)),
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Every { secs } if secs % 3_600 == 0 => write!(f, "every {}h", secs / 3_600),
+            Schedule::Every { secs } if secs % 60 == 0 => write!(f, "every {}m", secs / 60),
+            Schedule::Every { secs } => write!(f, "every {}s", secs),
+            Schedule::Cron { expr } => write!(f, "cron '{}'", expr),
+        }
+    }
+}
+
+/// A parsed five-field cron expression: minute, hour, day of month, month,
+/// day of week, all in UTC.
+///
+/// Each field takes `*`, a value, a range `a-b`, a step `*/n` or `a-b/n`, or
+/// a comma-separated list of those. Months accept `JAN`–`DEC` and days of
+/// week `SUN`–`SAT` (`0` and `7` are both Sunday). As in classic cron, when
+/// both day fields are restricted a day matching either one fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How far ahead [`CronExpr::next_after`] searches: long enough to reach a
+/// Feb 29 across a skipped century leap year.
+const CRON_SEARCH_DAYS: i64 = 366 * 9;
+
+impl CronExpr {
+    /// Parse an expression like `0 9 * * MON-FRI` or `*/15 * * * *`.
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(eyre!(
+                "invalid cron expression '{}': expected 5 fields (minute hour day-of-month \
+                 month day-of-week), got {}",
+                expr,
+                fields.len()
+            ));
+        };
+        let field = |name: &str, spec: &str, min: u32, max: u32, names: &[&str]| {
+            parse_cron_field(spec, min, max, names)
+                .map_err(|e| eyre!("invalid cron expression '{}': {} field: {}", expr, name, e))
+        };
+
+        let mut days_of_week = field("day-of-week", dow, 0, 7, &DAY_NAMES)?;
+        // 7 is an alias for Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: field("minute", minute, 0, 59, &[])?,
+            hours: field("hour", hour, 0, 23, &[])?,
+            days_of_month: field("day-of-month", dom, 1, 31, &[])?,
+            months: field("month", month, 1, 12, &MONTH_NAMES)?,
+            days_of_week,
+            dom_restricted: dom != "*",
+            dow_restricted: dow != "*",
+        })
+    }
+
+    /// First matching minute strictly after `after` (unix seconds), or
+    /// `None` if nothing matches within the next nine years.
+    pub fn next_after(&self, after: u64) -> Option<u64> {
+        let start = (after / 60 + 1) * 60;
+        let first_day = (start / 86_400) as i64;
+        let first_minute = ((start % 86_400) / 60) as u32;
+
+        for day in first_day..first_day + CRON_SEARCH_DAYS {
+            if !self.matches_day(day) {
+                continue;
+            }
+            let from = if day == first_day { first_minute } else { 0 };
+            let hit = (from..24 * 60)
+                .find(|m| self.hours & (1 << (m / 60)) != 0 && self.minutes & (1 << (m % 60)) != 0);
+            if let Some(minute_of_day) = hit {
+                return Some(day as u64 * 86_400 + u64::from(minute_of_day) * 60);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, day: i64) -> bool {
+        let (_, month, dom) = civil_from_days(day);
+        // 1970-01-01 was a Thursday (day 4 with Sunday = 0).
+        let dow = (day + 4).rem_euclid(7) as u32;
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        let dom_hit = self.days_of_month & (1 << dom) != 0;
+        let dow_hit = self.days_of_week & (1 << dow) != 0;
+        if self.dom_restricted && self.dow_restricted {
+            dom_hit || dow_hit
+        } else {
+            dom_hit && dow_hit
+        }
+    }
+}
+
+/// Parse one cron field into a bitmask of the values it allows.
+fn parse_cron_field(spec: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |s: &str| -> Result<u32> {
+        let upper = s.to_ascii_uppercase();
+        let v = match names.iter().position(|n| *n == upper) {
+            // Month names start at 1, day names at 0 — `min` covers both.
+            Some(i) => i as u32 + min,
+            None => s
+                .parse()
+                .map_err(|_| eyre!("'{}' is not a number or name", s))?,
+        };
+        if !(min..=max).contains(&v) {
+            return Err(eyre!("{} is outside {}-{}", v, min, max));
+        }
+        Ok(v)
+    };
+
+    let mut mask = 0u64;
+    for item in spec.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| eyre!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err(eyre!("step must be at least 1"));
+                }
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (value(a)?, value(b)?),
+                // `a/n` runs from `a` to the end of the field.
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if lo > hi {
+            return Err(eyre!("range {}-{} is backwards", lo, hi));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+/// One scheduled order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    /// Job ID, unique within its [`Scheduler`].
+    pub id: u64,
+    /// When the job repeats.
+    pub schedule: Schedule,
+    /// Market ID or `base_network/SYMBOL::quote_network/SYMBOL` shorthand.
+    pub market_id: String,
+    /// Order side (1 = bid, 2 = ask).
+    pub side: i32,
+    /// Quantity as a decimal string.
+    pub quantity: String,
+    /// Limit price as a decimal string.
+    pub price: Option<String>,
+    /// Submit as post-only.
+    #[serde(default)]
+    pub post_only: bool,
+    /// Submit as a hidden order.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Submit as a batch-auction order.
+    #[serde(default)]
+    pub auction: bool,
+    /// Time in force for every submission.
+    #[serde(default)]
+    pub execution: ExecutionType,
+    /// What to do with a quantity or price finer than the market allows.
+    #[serde(default)]
+    pub precision: PrecisionMode,
+    /// Strategy ID attached to every submission.
+    #[serde(default)]
+    pub strategy: Option<String>,
+    /// Tags attached to every submission.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Next run (unix seconds).
+    pub next_run: u64,
+    /// Last run (unix seconds), successful or not.
+    #[serde(default)]
+    pub last_run: Option<u64>,
+    /// Number of runs so far.
+    #[serde(default)]
+    pub runs: u64,
+}
+
+impl ScheduledJob {
+    /// The order this job submits on each run.
+    pub fn order_request(&self) -> OrderRequest {
+        OrderRequest {
+            market_id: self.market_id.clone(),
+            side: self.side,
+            quantity: self.quantity.clone(),
+            price: self.price.clone(),
+            post_only: self.post_only,
+            hidden: self.hidden,
+            auction: self.auction,
+            execution: self.execution,
+            // A fixed expiry can't carry across runs.
+            expires_at: None,
+            precision: self.precision,
+            // Each run is a new order.
+            client_order_id: None,
+            tags: OrderTags {
+                strategy: self.strategy.clone(),
+                tags: self.tags.clone(),
+                referral: None,
+            },
+        }
+    }
+}
+
+/// Outcome of one job run from [`Scheduler::run_due`].
+#[derive(Debug)]
+pub struct JobRun {
+    /// The job that ran.
+    pub job_id: u64,
+    /// The order submission result.
+    pub result: crate::error::Result<SendOrderResponse>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScheduleFile {
+    next_id: u64,
+    jobs: Vec<ScheduledJob>,
+}
+
+/// A persistent list of [`ScheduledJob`]s.
+///
+/// Every mutation is written back to the job file immediately, so the CLI
+/// can add or remove jobs while a `schedule run` process is executing them.
+#[derive(Debug)]
+pub struct Scheduler {
+    storage: Arc<dyn Storage>,
+    name: String,
+    file: ScheduleFile,
+}
+
+impl Scheduler {
+    /// Open the job file at `path`; a missing file is an empty schedule.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let (storage, name) = storage_for_path(&path.into())?;
+        Self::load(storage, name)
+    }
+
+    /// Open [`SCHEDULE_FILE`] in `storage`.
+    pub fn open_in(storage: Arc<dyn Storage>) -> Result<Self> {
+        Self::load(storage, SCHEDULE_FILE.to_string())
+    }
+
+    /// Open [`SCHEDULE_FILE`] in the default storage (see [`crate::storage`]).
+    pub fn open_default() -> Result<Self> {
+        Self::open_in(default_storage()?)
+    }
+
+    fn load(storage: Arc<dyn Storage>, name: String) -> Result<Self> {
+        let mut scheduler = Self {
+            storage,
+            name,
+            file: ScheduleFile::default(),
+        };
+        scheduler.reload()?;
+        Ok(scheduler)
+    }
+
+    /// Path of the job file.
+    pub fn path(&self) -> PathBuf {
+        self.storage.path(&self.name)
+    }
+
+    /// Scheduled jobs, in the order they were added.
+    pub fn jobs(&self) -> &[ScheduledJob] {
+        &self.file.jobs
+    }
+
+    /// Re-read the job file, picking up changes made by other processes.
+    pub fn reload(&mut self) -> Result<()> {
+        self.file = match self.storage.read(&self.name)? {
+            Some(json) => SCHEDULE_SCHEMA
+                .load(&json)
+                .map_err(|e| eyre!("invalid schedule file {}: {}", self.path().display(), e))?,
+            None => ScheduleFile::default(),
+        };
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        let _lock = self.storage.lock(&self.name)?;
+        self.storage
+            .write(&self.name, &SCHEDULE_SCHEMA.to_string(&self.file)?)
+    }
+
+    /// Schedule `order` on `schedule`, first running at the schedule's next
+    /// slot after `now` (unix seconds). Returns the new job's ID.
+    pub fn add(&mut self, schedule: Schedule, order: OrderRequest, now: u64) -> Result<u64> {
+        let next_run = schedule.next_after(now)?;
+        order.tags.validate()?;
+        if !matches!(order.side, 1 | 2) {
+            return Err(eyre!("invalid side {}", order.side));
+        }
+
+        self.reload()?;
+        self.file.next_id += 1;
+        let id = self.file.next_id;
+        self.file.jobs.push(ScheduledJob {
+            id,
+            schedule,
+            market_id: order.market_id,
+            side: order.side,
+            quantity: order.quantity,
+            price: order.price,
+            post_only: order.post_only,
+            hidden: order.hidden,
+            auction: order.auction,
+            execution: order.execution,
+            precision: order.precision,
+            strategy: order.tags.strategy,
+            tags: order.tags.tags,
+            next_run,
+            last_run: None,
+            runs: 0,
+        });
+        self.save()?;
+        Ok(id)
+    }
+
+    /// Remove job `id`. Returns false if there was no such job.
+    pub fn remove(&mut self, id: u64) -> Result<bool> {
+        self.reload()?;
+        let before = self.file.jobs.len();
+        self.file.jobs.retain(|j| j.id != id);
+        if self.file.jobs.len() == before {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Earliest `next_run` across all jobs.
+    pub fn next_due(&self) -> Option<u64> {
+        self.file.jobs.iter().map(|j| j.next_run).min()
+    }
+
+    /// Submit every job due at `now` (unix seconds) and advance it to its
+    /// next slot after `now`.
+    ///
+    /// A failed submission still counts as a run, so one bad job can't fire
+    /// in a tight loop.
+    pub async fn run_due<A: AspensApi>(
+        &mut self,
+        api: &A,
+        wallets: &[&Wallet],
+        now: u64,
+    ) -> Result<Vec<JobRun>> {
+        let mut runs = Vec::new();
+        for job in self.file.jobs.iter_mut().filter(|j| j.next_run <= now) {
+            let result = api.send_order(job.order_request(), wallets).await;
+            job.last_run = Some(now);
+            job.runs += 1;
+            job.next_run = job.schedule.next_after(now)?;
+            runs.push(JobRun {
+                job_id: job.id,
+                result,
+            });
+        }
+        if !runs.is_empty() {
+            self.save()?;
+        }
+        Ok(runs)
+    }
+
+    /// Run due jobs until the future is dropped, re-reading the job file at
+    /// least every [`SCHEDULER_POLL_INTERVAL`].
+    pub async fn run<A: AspensApi>(&mut self, api: &A, wallets: &[&Wallet]) -> Result<()> {
+        loop {
+            self.reload()?;
+            let now = unix_now();
+            for run in self.run_due(api, wallets, now).await? {
+                match run.result {
+                    Ok(response) => info!(
+                        "Scheduled job {} submitted order {}",
+                        run.job_id, response.order_id
+                    ),
+                    Err(e) => warn!("Scheduled job {} failed: {}", run.job_id, e),
+                }
+            }
+            let wait = self
+                .next_due()
+                .map(|due| Duration::from_secs(due.saturating_sub(unix_now())))
+                .unwrap_or(SCHEDULER_POLL_INTERVAL)
+                .min(SCHEDULER_POLL_INTERVAL);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mock::MockAspensApi;
+    use crate::commands::config::config_pb::{Configuration, GetConfigResponse, Market};
+
+    // Tuesday 2023-11-14 22:13:20 UTC.
+    const TUESDAY_NIGHT: u64 = 1_700_000_000;
+
+    #[test]
+    fn weekday_cron_skips_to_next_weekday_morning() {
+        let cron = CronExpr::parse("0 9 * * MON-FRI").unwrap();
+        // Wednesday 2023-11-15 09:00.
+        assert_eq!(cron.next_after(TUESDAY_NIGHT), Some(1_700_038_800));
+        // Friday 2023-11-17 10:00 -> Monday 2023-11-20 09:00.
+        assert_eq!(cron.next_after(1_700_215_200), Some(1_700_470_800));
+    }
+
+    #[test]
+    fn cron_steps_lists_and_names() {
+        let cron = CronExpr::parse("*/15 * * * *").unwrap();
+        // 22:13:20 -> 22:15:00.
+        assert_eq!(cron.next_after(TUESDAY_NIGHT), Some(1_700_000_100));
+        let cron = CronExpr::parse("30 8,20 1 jan-mar 7").unwrap();
+        assert!(cron.next_after(TUESDAY_NIGHT).is_some());
+        // An exact match is not "after".
+        let cron = CronExpr::parse("15 22 * * *").unwrap();
+        assert_eq!(cron.next_after(1_700_000_100), Some(1_700_000_100 + 86_400));
+    }
+
+    #[test]
+    fn rejects_malformed_cron() {
+        for bad in [
+            "* * * *",
+            "60 * * * *",
+            "* * * * FUNDAY",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(CronExpr::parse(bad).is_err(), "accepted {bad:?}");
+        }
+        assert!(Schedule::cron("0 0 31 2 *").next_after(0).is_err());
+        assert!(Schedule::every(Duration::ZERO).next_after(0).is_err());
+    }
+
+    fn api() -> MockAspensApi {
+        MockAspensApi::new(GetConfigResponse {
+            config: Some(Configuration {
+                chains: vec![],
+                markets: vec![Market {
+                    market_id: "m".into(),
+                    ..Default::default()
+                }],
+                maintenance: None,
+                signing_versions: vec![],
+            }),
+        })
+    }
+
+    fn order() -> OrderRequest {
+        OrderRequest {
+            market_id: "m".into(),
+            side: 1,
+            quantity: "10".into(),
+            price: Some("1".into()),
+            tags: OrderTags::strategy("dca"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn jobs_persist_across_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join(SCHEDULE_FILE);
+        let mut scheduler = Scheduler::open(&path).unwrap();
+        let id = scheduler
+            .add(Schedule::every(Duration::from_secs(3_600)), order(), 100)
+            .unwrap();
+
+        let reopened = Scheduler::open(&path).unwrap();
+        assert_eq!(reopened.jobs().len(), 1);
+        assert_eq!(reopened.jobs()[0].next_run, 3_700);
+        assert_eq!(reopened.jobs()[0].order_request(), order());
+
+        assert!(scheduler.remove(id).unwrap());
+        assert!(!scheduler.remove(id).unwrap());
+        assert!(Scheduler::open(&path).unwrap().jobs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn run_due_submits_and_reschedules() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut scheduler = Scheduler::open(dir.path().join(SCHEDULE_FILE)).unwrap();
+        scheduler
+            .add(Schedule::every(Duration::from_secs(60)), order(), 0)
+            .unwrap();
+        let api = api();
+        let wallet = Wallet::from_evm_hex(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+
+        assert!(
+            scheduler
+                .run_due(&api, &[&wallet], 59)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let runs = scheduler.run_due(&api, &[&wallet], 90).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].result.is_ok());
+        assert_eq!(scheduler.jobs()[0].next_run, 150);
+        assert_eq!(scheduler.jobs()[0].runs, 1);
+        assert_eq!(api.sent_orders_tagged("dca").len(), 1);
+    }
+}
@@ -0,0 +1,213 @@
+//! Versioned on-disk formats.
+//!
+//! Every file the SDK writes for itself (presets, the schedule, the deploy
+//! journal, telemetry, the update check) and every saved stack config
+//! carries a top-level [`VERSION_KEY`]. A [`Schema`] names a format and
+//! lists the [`Migration`]s that upgrade it one version at a time; loading
+//! a file through it runs whatever steps the file is missing, so a file
+//! written by an older release opens instead of failing to deserialize.
+//! Upgraded files are written back in the current format the next time
+//! they are saved.
+//!
+//! Files from before versioning have no [`VERSION_KEY`] and count as
+//! version 1. A file with a version newer than the schema knows was written
+//! by a newer release and is refused rather than misread.
+//!
+//! Adding a format change means appending a step:
+//!
+//! ```
+//! use aspens::schema::Schema;
+//!
+//! /// v1 kept a bare list; v2 wraps it so the file can grow fields.
+//! static JOBS: Schema = Schema::new("jobs file", &[|doc| {
+//!     let jobs = doc.take();
+//!     *doc = serde_json::json!({ "jobs": jobs });
+//!     Ok(())
+//! }]);
+//!
+//! let jobs: serde_json::Value = JOBS.load(r#"[1, 2]"#).unwrap();
+//! assert_eq!(jobs["jobs"], serde_json::json!([1, 2]));
+//! assert_eq!(JOBS.current(), 2);
+//! ```
+
+use eyre::{Result, eyre};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Top-level key holding a file's format version.
+pub const VERSION_KEY: &str = "schema_version";
+
+/// Upgrades a document from one version to the next, in place.
+pub type Migration = fn(&mut Value) -> Result<()>;
+
+/// A versioned file format and the steps that bring old files up to date.
+#[derive(Debug)]
+pub struct Schema {
+    name: &'static str,
+    migrations: &'static [Migration],
+}
+
+impl Schema {
+    /// Format `name` (used in errors), where `migrations[i]` upgrades
+    /// version `i + 1` to `i + 2`.
+    pub const fn new(name: &'static str, migrations: &'static [Migration]) -> Self {
+        Self { name, migrations }
+    }
+
+    /// The version files are written in.
+    pub fn current(&self) -> u32 {
+        self.migrations.len() as u32 + 1
+    }
+
+    /// The version `doc` is in: its [`VERSION_KEY`], or 1 without one.
+    pub fn version_of(&self, doc: &Value) -> Result<u32> {
+        match doc.get(VERSION_KEY) {
+            Some(Value::Number(n)) => n
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|&v| v >= 1)
+                .ok_or_else(|| eyre!("{}: invalid {} {}", self.name, VERSION_KEY, n)),
+            _ => Ok(1),
+        }
+    }
+
+    /// Run the migrations `doc` is missing and stamp it with the current
+    /// version. Returns the version it started at.
+    pub fn upgrade(&self, doc: &mut Value) -> Result<u32> {
+        let from = self.version_of(doc)?;
+        if from > self.current() {
+            return Err(eyre!(
+                "{} is schema version {}, but this release reads up to {}\n\n\
+                 Hints:\n\
+                 - Upgrade aspens to read it",
+                self.name,
+                from,
+                self.current()
+            ));
+        }
+        for (i, step) in self.migrations.iter().enumerate().skip(from as usize - 1) {
+            step(doc).map_err(|e| {
+                eyre!(
+                    "{}: upgrading from version {} to {}: {}",
+                    self.name,
+                    i + 1,
+                    i + 2,
+                    e
+                )
+            })?;
+        }
+        if let Value::Object(map) = doc {
+            map.insert(VERSION_KEY.to_string(), self.current().into());
+        }
+        Ok(from)
+    }
+
+    /// Upgrade `doc` and deserialize it.
+    pub fn from_value<T: DeserializeOwned>(&self, mut doc: Value) -> Result<T> {
+        self.upgrade(&mut doc)?;
+        Ok(serde_json::from_value(doc)?)
+    }
+
+    /// Parse JSON `contents`, upgrade and deserialize it.
+    pub fn load<T: DeserializeOwned>(&self, contents: &str) -> Result<T> {
+        self.from_value(serde_json::from_str(contents)?)
+    }
+
+    /// `value` with the current version added, for serializing in any
+    /// format. `value` must serialize as a map (a struct or map type).
+    pub fn versioned<'a, T: Serialize>(&self, value: &'a T) -> Versioned<'a, T> {
+        Versioned {
+            schema_version: self.current(),
+            value,
+        }
+    }
+
+    /// `value` as pretty JSON in the current version.
+    pub fn to_string<T: Serialize>(&self, value: &T) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.versioned(value))?)
+    }
+}
+
+/// A value serialized with a leading [`VERSION_KEY`]; see
+/// [`Schema::versioned`].
+#[derive(Debug, Serialize)]
+pub struct Versioned<'a, T> {
+    schema_version: u32,
+    #[serde(flatten)]
+    value: &'a T,
+}
+
+/// Move a version-1 document under `key` in a new object: the usual step
+/// when a bare map or list grows into a struct.
+pub fn wrap_in(doc: &mut Value, key: &str) {
+    let mut inner = doc.take();
+    if let Value::Object(map) = &mut inner {
+        map.remove(VERSION_KEY);
+    }
+    *doc = Value::Object([(key.to_string(), inner)].into_iter().collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    static V3: Schema = Schema::new(
+        "test file",
+        &[
+            |doc| {
+                wrap_in(doc, "items");
+                Ok(())
+            },
+            |doc| {
+                let items = doc["items"].take();
+                doc["entries"] = items;
+                doc["label"] = json!("default");
+                Ok(())
+            },
+        ],
+    );
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct File {
+        entries: BTreeMap<String, u32>,
+        label: String,
+    }
+
+    #[test]
+    fn upgrades_each_older_version_and_stamps_the_current_one() {
+        let expected = File {
+            entries: [("a".to_string(), 1)].into_iter().collect(),
+            label: "default".into(),
+        };
+        // Unversioned (v1), v2 and v3 files all load as v3.
+        assert_eq!(V3.load::<File>(r#"{"a": 1}"#).unwrap(), expected);
+        assert_eq!(
+            V3.load::<File>(r#"{"schema_version": 2, "items": {"a": 1}}"#)
+                .unwrap(),
+            expected
+        );
+        let current = V3.to_string(&expected).unwrap();
+        assert!(current.contains("\"schema_version\": 3"), "{current}");
+        assert_eq!(V3.load::<File>(&current).unwrap(), expected);
+
+        let mut doc = json!({"a": 1});
+        assert_eq!(V3.upgrade(&mut doc).unwrap(), 1);
+        assert_eq!(doc[VERSION_KEY], 3);
+    }
+
+    #[test]
+    fn refuses_newer_and_invalid_versions() {
+        let err = V3
+            .load::<File>(r#"{"schema_version": 4}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("reads up to 3"), "{err}");
+        assert!(V3.load::<File>(r#"{"schema_version": 0}"#).is_err());
+    }
+}
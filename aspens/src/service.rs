@@ -0,0 +1,276 @@
+//! Embedding the SDK in tower and axum web services.
+//!
+//! An [`AspensService`] owns one client for the life of the server: build it
+//! at startup, [`warm`](AspensService::warm) the config cache, and install
+//! its [`layer`](AspensService::layer). Every request then carries a cheap
+//! handle on that client, so handlers share the cached config and JWT
+//! instead of connecting per request. With the `axum` feature, handlers take
+//! the [`Aspens`] extractor and can return [`AspensError`](crate::AspensError)
+//! directly:
+//!
+//! ```ignore
+//! use aspens::service::{Aspens, AspensService};
+//! use aspens::{AspensApi, AspensClient, AspensError};
+//! use axum::{Json, Router, routing::get};
+//!
+//! async fn chains(aspens: Aspens) -> Result<Json<usize>, AspensError> {
+//!     let config = aspens.get_config().await?;
+//!     Ok(Json(config.config.map_or(0, |c| c.chains.len())))
+//! }
+//!
+//! let service = AspensService::new(AspensClient::builder().build()?);
+//! let app = Router::new()
+//!     .route("/chains", get(chains))
+//!     .layer(service.layer());
+//! ```
+//!
+//! The service is generic over the client so handlers written against
+//! [`AspensApi`](crate::AspensApi) can be tested with
+//! [`MockAspensApi`](crate::api::mock::MockAspensApi).
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::client::AspensClient;
+
+/// One shared client for a web service. Clones share the client.
+#[derive(Debug)]
+pub struct AspensService<A = AspensClient> {
+    client: Arc<A>,
+}
+
+impl<A> Clone for AspensService<A> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+        }
+    }
+}
+
+impl<A> AspensService<A> {
+    /// Share `client` across every request of a service.
+    pub fn new(client: A) -> Self {
+        Self {
+            client: Arc::new(client),
+        }
+    }
+
+    /// The shared client.
+    pub fn client(&self) -> &A {
+        &self.client
+    }
+
+    /// A handle on the shared client for one request or task.
+    pub fn handle(&self) -> Arc<A> {
+        self.client.clone()
+    }
+
+    /// A tower layer that puts this service into every request's
+    /// extensions, where the [`Aspens`] extractor finds it.
+    pub fn layer(&self) -> AspensLayer<A> {
+        AspensLayer {
+            service: self.clone(),
+        }
+    }
+}
+
+impl AspensService<AspensClient> {
+    /// Fetch and cache the stack config, so the first requests don't each
+    /// fetch it. Call once at startup.
+    pub async fn warm(&self) -> eyre::Result<()> {
+        self.client.fetch_config().await
+    }
+}
+
+/// Tower [`Layer`] built by [`AspensService::layer`].
+#[derive(Debug)]
+pub struct AspensLayer<A = AspensClient> {
+    service: AspensService<A>,
+}
+
+impl<A> Clone for AspensLayer<A> {
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+        }
+    }
+}
+
+impl<S, A> Layer<S> for AspensLayer<A> {
+    type Service = AspensMiddleware<S, A>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AspensMiddleware {
+            inner,
+            service: self.service.clone(),
+        }
+    }
+}
+
+/// Middleware installed by [`AspensLayer`]: inserts the [`AspensService`]
+/// into each request's extensions and calls the inner service.
+#[derive(Debug)]
+pub struct AspensMiddleware<S, A = AspensClient> {
+    inner: S,
+    service: AspensService<A>,
+}
+
+impl<S: Clone, A> Clone for AspensMiddleware<S, A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            service: self.service.clone(),
+        }
+    }
+}
+
+impl<S, A, B> Service<http::Request<B>> for AspensMiddleware<S, A>
+where
+    S: Service<http::Request<B>>,
+    A: Send + Sync + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        req.extensions_mut().insert(self.service.clone());
+        self.inner.call(req)
+    }
+}
+
+#[cfg(feature = "axum")]
+pub use extract::Aspens;
+
+#[cfg(feature = "axum")]
+mod extract {
+    use std::ops::Deref;
+    use std::sync::Arc;
+
+    use axum_core::extract::FromRequestParts;
+    use axum_core::response::{IntoResponse, Response};
+    use http::StatusCode;
+    use http::request::Parts;
+
+    use super::AspensService;
+    use crate::client::AspensClient;
+    use crate::error::AspensError;
+
+    /// Axum extractor for the handle an [`AspensLayer`](super::AspensLayer)
+    /// attached to the request. Derefs to the client.
+    ///
+    /// Rejects with `500 Internal Server Error` when the route isn't behind
+    /// the layer.
+    #[derive(Debug)]
+    pub struct Aspens<A = AspensClient>(pub Arc<A>);
+
+    impl<A> Clone for Aspens<A> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+
+    impl<A> Deref for Aspens<A> {
+        type Target = A;
+
+        fn deref(&self) -> &A {
+            &self.0
+        }
+    }
+
+    impl<S, A> FromRequestParts<S> for Aspens<A>
+    where
+        S: Send + Sync,
+        A: Send + Sync + 'static,
+    {
+        type Rejection = (StatusCode, &'static str);
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<Self, Self::Rejection> {
+            parts
+                .extensions
+                .get::<AspensService<A>>()
+                .map(|service| Aspens(service.handle()))
+                .ok_or((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "AspensService layer is not installed on this route",
+                ))
+        }
+    }
+
+    impl AspensError {
+        /// The HTTP status a web service answers with for this error.
+        pub fn http_status(&self) -> StatusCode {
+            match self {
+                AspensError::Connection(_) => StatusCode::SERVICE_UNAVAILABLE,
+                AspensError::Auth(_) => StatusCode::UNAUTHORIZED,
+                AspensError::NotFound(_) => StatusCode::NOT_FOUND,
+                AspensError::InsufficientBalance(_) | AspensError::RiskLimit(_) => {
+                    StatusCode::UNPROCESSABLE_ENTITY
+                }
+                AspensError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+                AspensError::Protocol(_) | AspensError::Chain(_) => StatusCode::BAD_GATEWAY,
+                AspensError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        }
+    }
+
+    /// Responds with [`http_status`](AspensError::http_status) and the
+    /// error message as plain text.
+    impl IntoResponse for AspensError {
+        fn into_response(self) -> Response {
+            (self.http_status(), self.to_string()).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::future::{Ready, ready};
+
+    use super::*;
+
+    /// Inner service that reports whether the layer attached the client.
+    #[derive(Clone)]
+    struct Probe;
+
+    impl Service<http::Request<()>> for Probe {
+        type Response = Option<&'static str>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: http::Request<()>) -> Self::Future {
+            let service = req.extensions().get::<AspensService<&'static str>>();
+            ready(Ok(service.map(|s| *s.client())))
+        }
+    }
+
+    #[tokio::test]
+    async fn layer_attaches_shared_client_to_each_request() {
+        let service = AspensService::new("stack");
+        let mut svc = service.layer().layer(Probe);
+        for _ in 0..2 {
+            let got = svc.call(http::Request::new(())).await.unwrap();
+            assert_eq!(got, Some("stack"));
+        }
+        // The layer's copies and the handle all point at one client.
+        assert!(Arc::ptr_eq(
+            &service.handle(),
+            &service.layer().service.client
+        ));
+    }
+}
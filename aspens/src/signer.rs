@@ -0,0 +1,178 @@
+//! The signer abstraction command functions take.
+//!
+//! [`AspensSigner`] is what placing and canceling orders, admin logins,
+//! deposits and withdrawals need from a key: its curve and address,
+//! message and EIP-712 digest signatures and, for on-chain steps, a
+//! transaction signer. [`Wallet`] implements it for every key source the
+//! SDK loads (hex keys, keystores, mnemonics, Ledger devices, remote
+//! signers), and so do the signers themselves: a `PrivateKeySigner`, a
+//! Solana `Keypair`, a `LedgerWallet` or any `Clone` remote signer can be
+//! passed to a command function as is.
+
+#[cfg(feature = "client")]
+use std::sync::Arc;
+
+use alloy_primitives::B256;
+use alloy_signer::Signer;
+use alloy_signer_local::PrivateKeySigner;
+use async_trait::async_trait;
+use eyre::{Result, eyre};
+
+use crate::wallet::{CurveType, Wallet};
+
+#[cfg(feature = "solana")]
+use solana_sdk::signature::{Keypair, Signer as SolanaSigner};
+
+/// Transaction signer handed to alloy providers for on-chain steps.
+#[cfg(feature = "client")]
+pub type SharedTxSigner =
+    Arc<dyn alloy::network::TxSigner<alloy_primitives::Signature> + Send + Sync>;
+
+/// A key that signs for an Aspens account.
+#[async_trait]
+pub trait AspensSigner: Send + Sync {
+    /// The key's curve.
+    fn curve(&self) -> CurveType;
+
+    /// The account address: checksummed `0x` hex on EVM, base58 on Solana.
+    fn address(&self) -> String;
+
+    /// Sign an arbitrary message: an EIP-191 personal message on EVM
+    /// (65 bytes, r || s || v), the raw bytes on Solana (64 bytes).
+    async fn sign_message(&self, msg: &[u8]) -> Result<Vec<u8>>;
+
+    /// Sign a 32-byte EIP-712 digest. EVM keys only; the default refuses,
+    /// for signers that only sign what they can display.
+    async fn sign_eip712_digest(&self, digest: B256) -> Result<Vec<u8>> {
+        let _ = digest;
+        Err(eyre!("{} can't sign a raw EIP-712 digest", self.address()))
+    }
+
+    /// A transaction signer for deposits, withdrawals and deployments;
+    /// `None` (the default) if the key can't sign EVM transactions.
+    #[cfg(feature = "client")]
+    fn tx_signer(&self) -> Option<SharedTxSigner> {
+        None
+    }
+
+    /// The Solana keypair, for building Solana transactions; `None` by
+    /// default.
+    #[cfg(feature = "solana")]
+    fn as_solana(&self) -> Option<&Keypair> {
+        None
+    }
+
+    /// The Ledger device, which signs admin logins as typed data instead
+    /// of a digest; `None` by default.
+    #[cfg(feature = "ledger")]
+    fn as_ledger(&self) -> Option<&crate::ledger::LedgerWallet> {
+        None
+    }
+}
+
+/// View a wallet list as signers, for passing the high-level API's
+/// `&[&Wallet]` on to command functions.
+pub fn as_signers<'a>(wallets: &[&'a Wallet]) -> Vec<&'a dyn AspensSigner> {
+    wallets.iter().map(|w| *w as &dyn AspensSigner).collect()
+}
+
+#[async_trait]
+impl AspensSigner for Wallet {
+    fn curve(&self) -> CurveType {
+        Wallet::curve(self)
+    }
+
+    fn address(&self) -> String {
+        Wallet::address(self)
+    }
+
+    async fn sign_message(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        Wallet::sign_message(self, msg).await
+    }
+
+    async fn sign_eip712_digest(&self, digest: B256) -> Result<Vec<u8>> {
+        Wallet::sign_eip712_digest(self, digest).await
+    }
+
+    #[cfg(feature = "client")]
+    fn tx_signer(&self) -> Option<SharedTxSigner> {
+        Wallet::tx_signer(self)
+    }
+
+    #[cfg(feature = "solana")]
+    fn as_solana(&self) -> Option<&Keypair> {
+        Wallet::as_solana(self)
+    }
+
+    #[cfg(feature = "ledger")]
+    fn as_ledger(&self) -> Option<&crate::ledger::LedgerWallet> {
+        Wallet::as_ledger(self)
+    }
+}
+
+#[async_trait]
+impl AspensSigner for PrivateKeySigner {
+    fn curve(&self) -> CurveType {
+        CurveType::Secp256k1
+    }
+
+    fn address(&self) -> String {
+        Signer::address(self).to_checksum(None)
+    }
+
+    async fn sign_message(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        Ok(Signer::sign_message(self, msg).await?.as_bytes().to_vec())
+    }
+
+    async fn sign_eip712_digest(&self, digest: B256) -> Result<Vec<u8>> {
+        Ok(self.sign_hash(&digest).await?.as_bytes().to_vec())
+    }
+
+    #[cfg(feature = "client")]
+    fn tx_signer(&self) -> Option<SharedTxSigner> {
+        Some(Arc::new(self.clone()))
+    }
+}
+
+#[cfg(feature = "solana")]
+#[async_trait]
+impl AspensSigner for Keypair {
+    fn curve(&self) -> CurveType {
+        CurveType::Ed25519
+    }
+
+    fn address(&self) -> String {
+        self.pubkey().to_string()
+    }
+
+    async fn sign_message(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        Ok(SolanaSigner::sign_message(self, msg).as_ref().to_vec())
+    }
+
+    fn as_solana(&self) -> Option<&Keypair> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_EVM_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    #[tokio::test]
+    async fn bare_keys_sign_like_their_wallets() {
+        let signer: PrivateKeySigner = TEST_EVM_KEY.parse().unwrap();
+        let wallet = Wallet::Evm(signer.clone());
+        let (a, b): (&dyn AspensSigner, &dyn AspensSigner) = (&signer, &wallet);
+        assert_eq!(a.address(), b.address());
+        assert_eq!(
+            a.sign_message(b"order").await.unwrap(),
+            b.sign_message(b"order").await.unwrap()
+        );
+        assert_eq!(
+            a.sign_eip712_digest(B256::repeat_byte(7)).await.unwrap(),
+            b.sign_eip712_digest(B256::repeat_byte(7)).await.unwrap()
+        );
+    }
+}
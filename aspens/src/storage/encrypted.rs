@@ -0,0 +1,460 @@
+//! At-rest encryption for a [`Storage`].
+//!
+//! [`EncryptedStorage`] wraps another backend and seals every file with
+//! XChaCha20-Poly1305 before it reaches the disk. Encryption is switched on
+//! per profile by [`EncryptedStorage::enable`], which writes
+//! [`ENCRYPTION_FILE`] next to the profile's other files; from then on
+//! [`default_storage`](super::default_storage) unlocks the profile before
+//! handing it out. The key comes from one of two places:
+//!
+//! - [`KeySource::Passphrase`]: derived with Argon2id from a passphrase and
+//!   a random salt kept in [`ENCRYPTION_FILE`]. Non-interactive callers set
+//!   [`PASSPHRASE_ENV`].
+//! - [`KeySource::Keychain`]: a random key kept in the OS keychain (Keychain
+//!   on macOS, Secret Service on Linux, Credential Manager on Windows).
+//!   Needs the `encryption-keychain` feature, which on Linux links the
+//!   system libdbus; without it such profiles can't be enabled or opened.
+//!
+//! Enabling encryption seals the files already there. Each file is sealed
+//! with its name as associated data, so one can't be passed off as
+//! another. A plaintext file that turns up later (say, written by an older
+//! build) is still read as it is and sealed the next time it is written.
+//! Lock files and [`ENCRYPTION_FILE`] itself stay in the clear; they hold
+//! no secrets.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use super::{Storage, StorageLock};
+
+/// File that marks a profile's storage as encrypted and says where its key
+/// comes from.
+pub const ENCRYPTION_FILE: &str = "encryption.json";
+
+/// Environment variable holding the passphrase of a passphrase-encrypted
+/// profile.
+pub const PASSPHRASE_ENV: &str = "ASPENS_STORAGE_PASSPHRASE";
+
+/// Keychain service the keys of keychain-encrypted profiles are stored
+/// under.
+pub const KEYCHAIN_SERVICE: &str = "aspens-storage";
+
+/// Prefix of a sealed file; anything else is read as plaintext.
+const SEALED_PREFIX: &str = "aspens-sealed:v1:";
+
+/// Plaintext sealed into [`ENCRYPTION_FILE`] to tell a wrong key from a
+/// corrupt file.
+const CHECK_PLAINTEXT: &str = "aspens";
+
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+
+/// Where the key of an encrypted profile comes from.
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    /// Derive the key from this passphrase.
+    Passphrase(String),
+    /// Keep a random key in the OS keychain. Needs the
+    /// `encryption-keychain` feature.
+    Keychain,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "key", rename_all = "lowercase")]
+enum KeyMarker {
+    Passphrase { salt: String, check: String },
+    Keychain { account: String, check: String },
+}
+
+impl KeyMarker {
+    fn check(&self) -> &str {
+        match self {
+            KeyMarker::Passphrase { check, .. } | KeyMarker::Keychain { check, .. } => check,
+        }
+    }
+}
+
+/// A [`Storage`] whose files are sealed with XChaCha20-Poly1305.
+#[derive(Clone)]
+pub struct EncryptedStorage {
+    inner: Arc<dyn Storage>,
+    cipher: XChaCha20Poly1305,
+}
+
+impl std::fmt::Debug for EncryptedStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedStorage")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EncryptedStorage {
+    /// Seal `inner`'s files with `key`. Doesn't touch [`ENCRYPTION_FILE`];
+    /// use [`enable`](Self::enable) and [`unlock`](Self::unlock) for
+    /// profiles.
+    pub fn new(inner: Arc<dyn Storage>, key: &[u8; 32]) -> Self {
+        Self {
+            inner,
+            cipher: XChaCha20Poly1305::new(key.into()),
+        }
+    }
+
+    /// True if `storage` has encryption enabled.
+    pub fn is_enabled(storage: &dyn Storage) -> Result<bool> {
+        Ok(storage.read(ENCRYPTION_FILE)?.is_some())
+    }
+
+    /// Turn encryption on for `inner` with a key from `source`, seal the
+    /// files it already holds, and return the unlocked storage. Fails if
+    /// it is already enabled.
+    ///
+    /// A file that can't be read as text (the SQLite journal) is left as
+    /// it is, with a warning.
+    pub fn enable(inner: Arc<dyn Storage>, source: KeySource) -> Result<Self> {
+        let _lock = inner.lock(ENCRYPTION_FILE)?;
+        if Self::is_enabled(inner.as_ref())? {
+            return Err(eyre!(
+                "{} is already encrypted",
+                inner.path(ENCRYPTION_FILE).display()
+            ));
+        }
+        let (storage, marker) = match source {
+            KeySource::Passphrase(passphrase) => {
+                let mut salt = [0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                let storage = Self::new(inner, &derive_key(&passphrase, &salt)?);
+                let check = storage.seal(ENCRYPTION_FILE, CHECK_PLAINTEXT)?;
+                let salt = STANDARD.encode(salt);
+                (storage, KeyMarker::Passphrase { salt, check })
+            }
+            KeySource::Keychain => {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                let account = keychain_account(inner.as_ref());
+                store_keychain_key(&account, &STANDARD.encode(key))?;
+                let storage = Self::new(inner, &key);
+                let check = storage.seal(ENCRYPTION_FILE, CHECK_PLAINTEXT)?;
+                (storage, KeyMarker::Keychain { account, check })
+            }
+        };
+        // Marker first: if sealing stops halfway, the files left in the
+        // clear are still read as they are.
+        storage
+            .inner
+            .write(ENCRYPTION_FILE, &serde_json::to_string_pretty(&marker)?)?;
+        storage.seal_existing()?;
+        Ok(storage)
+    }
+
+    /// Seal every plaintext file in the inner storage, each under its own
+    /// lock so a concurrent writer can't slip plaintext back in.
+    fn seal_existing(&self) -> Result<()> {
+        for name in self.inner.list()? {
+            if name == ENCRYPTION_FILE {
+                continue;
+            }
+            let _lock = self.inner.lock(&name)?;
+            let contents = match self.inner.read(&name) {
+                Ok(Some(contents)) => contents,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::warn!(
+                        "Leaving {} unencrypted: {}",
+                        self.inner.path(&name).display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            if !contents.starts_with(SEALED_PREFIX) {
+                self.inner.write(&name, &self.seal(&name, &contents)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Unlock `inner` if encryption is enabled on it; `Ok(None)` if it
+    /// isn't. `passphrase` is used for passphrase-encrypted storage, falling
+    /// back to [`PASSPHRASE_ENV`].
+    pub fn unlock(inner: Arc<dyn Storage>, passphrase: Option<&str>) -> Result<Option<Self>> {
+        let Some(json) = inner.read(ENCRYPTION_FILE)? else {
+            return Ok(None);
+        };
+        let marker: KeyMarker = serde_json::from_str(&json).map_err(|e| {
+            eyre!(
+                "invalid encryption file {}: {}",
+                inner.path(ENCRYPTION_FILE).display(),
+                e
+            )
+        })?;
+        let key = match &marker {
+            KeyMarker::Passphrase { salt, .. } => {
+                let passphrase = match passphrase {
+                    Some(p) => p.to_string(),
+                    None => std::env::var(PASSPHRASE_ENV).map_err(|_| {
+                        eyre!(
+                            "{} is encrypted with a passphrase\n\n\
+                             Hints:\n\
+                             - Set {} to the passphrase",
+                            inner.path(ENCRYPTION_FILE).display(),
+                            PASSPHRASE_ENV
+                        )
+                    })?,
+                };
+                let salt = STANDARD
+                    .decode(salt)
+                    .map_err(|e| eyre!("invalid salt in {}: {}", ENCRYPTION_FILE, e))?;
+                derive_key(&passphrase, &salt)?
+            }
+            KeyMarker::Keychain { account, .. } => {
+                let encoded = load_keychain_key(account)?;
+                STANDARD
+                    .decode(encoded)
+                    .ok()
+                    .and_then(|k| <[u8; 32]>::try_from(k).ok())
+                    .ok_or_else(|| eyre!("the storage key in the keychain is malformed"))?
+            }
+        };
+        let storage = Self::new(inner, &key);
+        if storage
+            .open(ENCRYPTION_FILE, marker.check())
+            .ok()
+            .as_deref()
+            != Some(CHECK_PLAINTEXT)
+        {
+            return Err(eyre!(
+                "wrong key for {}",
+                storage.inner.path(ENCRYPTION_FILE).display()
+            ));
+        }
+        Ok(Some(storage))
+    }
+
+    /// Seal `plaintext` for the file `name`, which is bound in as
+    /// associated data.
+    fn seal(&self, name: &str, plaintext: &str) -> Result<String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut sealed = nonce.to_vec();
+        let payload = Payload {
+            msg: plaintext.as_bytes(),
+            aad: name.as_bytes(),
+        };
+        sealed.extend(
+            self.cipher
+                .encrypt(&nonce, payload)
+                .map_err(|_| eyre!("encryption failed"))?,
+        );
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(sealed)))
+    }
+
+    /// Open `contents` of the file `name`; plaintext is returned as is.
+    fn open(&self, name: &str, contents: &str) -> Result<String> {
+        let Some(encoded) = contents.trim_end().strip_prefix(SEALED_PREFIX) else {
+            return Ok(contents.to_string());
+        };
+        let sealed = STANDARD
+            .decode(encoded)
+            .map_err(|e| eyre!("malformed sealed file: {}", e))?;
+        let Some((nonce, ciphertext)) = sealed.split_first_chunk::<NONCE_LEN>() else {
+            return Err(eyre!("malformed sealed file: too short"));
+        };
+        let payload = Payload {
+            msg: ciphertext,
+            aad: name.as_bytes(),
+        };
+        let plaintext = self
+            .cipher
+            .decrypt(&XNonce::from(*nonce), payload)
+            .map_err(|_| eyre!("decryption failed: wrong key or modified file"))?;
+        String::from_utf8(plaintext).map_err(|e| eyre!("sealed file is not UTF-8: {}", e))
+    }
+}
+
+impl Storage for EncryptedStorage {
+    fn read(&self, name: &str) -> Result<Option<String>> {
+        self.inner
+            .read(name)?
+            .map(|contents| {
+                self.open(name, &contents)
+                    .map_err(|e| eyre!("{}: {}", self.inner.path(name).display(), e))
+            })
+            .transpose()
+    }
+
+    fn write(&self, name: &str, contents: &str) -> Result<()> {
+        self.inner.write(name, &self.seal(name, contents)?)
+    }
+
+    fn remove(&self, name: &str) -> Result<bool> {
+        self.inner.remove(name)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        self.inner.list()
+    }
+
+    fn lock(&self, name: &str) -> Result<StorageLock> {
+        self.inner.lock(name)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.inner.path(name)
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| eyre!("failed to derive the storage key: {}", e))?;
+    Ok(key)
+}
+
+/// Keychain account for `storage`'s key: one per profile directory.
+fn keychain_account(storage: &dyn Storage) -> String {
+    storage.path(ENCRYPTION_FILE).display().to_string()
+}
+
+#[cfg(feature = "encryption-keychain")]
+fn keychain_entry(account: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, account)
+        .map_err(|e| eyre!("failed to open the OS keychain: {}", e))
+}
+
+/// Keep the base64 `key` in the OS keychain under `account`.
+#[cfg(feature = "encryption-keychain")]
+fn store_keychain_key(account: &str, key: &str) -> Result<()> {
+    keychain_entry(account)?
+        .set_password(key)
+        .map_err(|e| eyre!("failed to store the storage key in the keychain: {}", e))
+}
+
+/// The base64 key kept in the OS keychain under `account`.
+#[cfg(feature = "encryption-keychain")]
+fn load_keychain_key(account: &str) -> Result<String> {
+    keychain_entry(account)?
+        .get_password()
+        .map_err(|e| eyre!("failed to read the storage key from the keychain: {}", e))
+}
+
+#[cfg(not(feature = "encryption-keychain"))]
+fn store_keychain_key(_account: &str, _key: &str) -> Result<()> {
+    Err(no_keychain())
+}
+
+#[cfg(not(feature = "encryption-keychain"))]
+fn load_keychain_key(_account: &str) -> Result<String> {
+    Err(no_keychain())
+}
+
+#[cfg(not(feature = "encryption-keychain"))]
+fn no_keychain() -> eyre::Report {
+    eyre!(
+        "this build has no OS keychain support\n\n\
+         Hints:\n\
+         - Rebuild with the `encryption-keychain` feature (the CLI's `keychain`)\n\
+         - Or encrypt with a passphrase in {}",
+        PASSPHRASE_ENV
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn passphrase_storage_seals_files_and_rejects_wrong_passphrases() {
+        let inner = Arc::new(MemoryStorage::new());
+        inner.write("legacy.json", "{\"old\":true}").unwrap();
+        assert!(!EncryptedStorage::is_enabled(inner.as_ref()).unwrap());
+
+        let storage =
+            EncryptedStorage::enable(inner.clone(), KeySource::Passphrase("hunter2".into()))
+                .unwrap();
+        storage
+            .write("session.json", "{\"jwt\":\"secret\"}")
+            .unwrap();
+        let raw = inner.read("session.json").unwrap().unwrap();
+        assert!(raw.starts_with(SEALED_PREFIX));
+        assert!(!raw.contains("secret"));
+
+        // Files from before encryption are sealed and still readable.
+        assert!(
+            inner
+                .read("legacy.json")
+                .unwrap()
+                .unwrap()
+                .starts_with(SEALED_PREFIX)
+        );
+        assert_eq!(
+            storage.read("legacy.json").unwrap().as_deref(),
+            Some("{\"old\":true}")
+        );
+        assert!(
+            EncryptedStorage::enable(inner.clone(), KeySource::Passphrase("x".into())).is_err()
+        );
+
+        let unlocked = EncryptedStorage::unlock(inner.clone(), Some("hunter2"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            unlocked.read("session.json").unwrap().as_deref(),
+            Some("{\"jwt\":\"secret\"}")
+        );
+        assert!(EncryptedStorage::unlock(inner.clone(), Some("wrong")).is_err());
+
+        // Tampering is detected.
+        let mut tampered = raw.clone();
+        tampered.pop();
+        tampered.push(if raw.ends_with('A') { 'B' } else { 'A' });
+        inner.write("session.json", &tampered).unwrap();
+        assert!(unlocked.read("session.json").is_err());
+
+        let plain = Arc::new(MemoryStorage::new());
+        assert!(EncryptedStorage::unlock(plain, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn enabling_seals_every_existing_file_under_its_own_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner: Arc<dyn Storage> = Arc::new(crate::storage::FsStorage::new(dir.path()));
+        inner.write("presets.json", "{\"key\":\"secret\"}").unwrap();
+        inner
+            .write("telemetry.json", "{\"queued\":\"secret\"}")
+            .unwrap();
+        // Not text, so left alone rather than failing the whole profile.
+        std::fs::write(dir.path().join("journal.sqlite3"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let storage =
+            EncryptedStorage::enable(inner.clone(), KeySource::Passphrase("hunter2".into()))
+                .unwrap();
+        for name in inner.list().unwrap() {
+            let raw = std::fs::read(dir.path().join(&name)).unwrap();
+            let raw = String::from_utf8_lossy(&raw);
+            assert!(!raw.contains("secret"), "{name} is still readable: {raw}");
+            if name.ends_with(".json") && name != ENCRYPTION_FILE {
+                assert!(raw.starts_with(SEALED_PREFIX), "{name}");
+            }
+        }
+        assert_eq!(
+            storage.read("telemetry.json").unwrap().as_deref(),
+            Some("{\"queued\":\"secret\"}")
+        );
+
+        // A sealed file copied over another doesn't open as that file.
+        let presets = inner.read("presets.json").unwrap().unwrap();
+        inner.write("telemetry.json", &presets).unwrap();
+        assert!(storage.read("telemetry.json").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Where SDK state that outlives a process is kept.
+//!
+//! Presets, the scheduler's jobs, the deploy journal, telemetry and the
+//! update check each persist one small file through a [`Storage`]. The
+//! default, [`FsStorage::open_default`], is a directory picked by
+//! [`profile_dir`](crate::util::profile_dir):
+//!
+//! 1. `$ASPENS_HOME`, if set;
+//! 2. `~/.aspens`, if it already exists (installs from before the XDG
+//!    layout keep their files);
+//! 3. `$XDG_DATA_HOME/aspens`, or `~/.local/share/aspens`.
+//!
+//! Setting `ASPENS_PROFILE` scopes all of it to `profiles/<name>` under that
+//! directory, so a testnet and a mainnet setup don't share presets or
+//! journals. Disposable data goes under [`cache_dir`] instead.
+//!
+//! Writes are atomic (temp file and rename), and [`Storage::lock`] takes an
+//! advisory lock for read-modify-write cycles that must not interleave with
+//! another process. Tests use [`MemoryStorage`] and never touch the disk.
+//!
+//! With the `encryption` feature, a profile can be sealed at rest:
+//! `EncryptedStorage` wraps any backend, and once enabled on a profile
+//! [`default_storage`] unlocks it with a passphrase or an OS keychain key.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+
+use eyre::{Result, eyre};
+
+use crate::util::{profile_dir, read_profile_file, write_profile_file};
+
+#[cfg(feature = "encryption")]
+mod encrypted;
+#[cfg(feature = "encryption")]
+pub use encrypted::{
+    ENCRYPTION_FILE, EncryptedStorage, KEYCHAIN_SERVICE, KeySource, PASSPHRASE_ENV,
+};
+
+/// File that marks a profile's storage as encrypted (see `EncryptedStorage`
+/// with the `encryption` feature).
+#[cfg(not(feature = "encryption"))]
+pub const ENCRYPTION_FILE: &str = "encryption.json";
+
+/// Environment variable naming the profile to scope storage to.
+pub const PROFILE_ENV: &str = "ASPENS_PROFILE";
+
+/// Subdirectory holding one directory per named profile.
+pub const PROFILES_DIR: &str = "profiles";
+
+/// A place to keep named files of SDK state.
+///
+/// Names are plain file names (`schedule.json`); each backend decides where
+/// they live.
+pub trait Storage: Send + Sync + fmt::Debug {
+    /// Contents of `name`; `None` if it doesn't exist yet.
+    fn read(&self, name: &str) -> Result<Option<String>>;
+
+    /// Replace `name` with `contents`. A concurrent reader sees either the
+    /// old or the new contents, never a mix.
+    fn write(&self, name: &str, contents: &str) -> Result<()>;
+
+    /// Delete `name`. Returns false if it didn't exist.
+    fn remove(&self, name: &str) -> Result<bool>;
+
+    /// Names of the files kept here, in no particular order. Lock files and
+    /// half-written temp files aren't included.
+    fn list(&self) -> Result<Vec<String>>;
+
+    /// Block until this process holds the lock on `name`. Released when the
+    /// returned guard drops.
+    fn lock(&self, name: &str) -> Result<StorageLock>;
+
+    /// Where `name` lives, for messages.
+    fn path(&self, name: &str) -> PathBuf;
+}
+
+/// Guard returned by [`Storage::lock`]; dropping it releases the lock.
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct StorageLock {
+    _guard: Box<dyn Send>,
+}
+
+impl StorageLock {
+    /// Wrap a backend's guard; the lock is released when `guard` drops.
+    pub fn new(guard: impl Send + 'static) -> Self {
+        Self {
+            _guard: Box::new(guard),
+        }
+    }
+}
+
+impl fmt::Debug for StorageLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StorageLock").finish_non_exhaustive()
+    }
+}
+
+/// Files in a directory on disk.
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    root: PathBuf,
+}
+
+impl FsStorage {
+    /// Storage rooted at `root`. The directory is created on first write.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The profile directory, scoped to `$ASPENS_PROFILE` when set.
+    pub fn open_default() -> Result<Self> {
+        let storage = Self::new(profile_dir()?);
+        match std::env::var(PROFILE_ENV) {
+            Ok(name) if !name.trim().is_empty() => storage.profile(name.trim()),
+            _ => Ok(storage),
+        }
+    }
+
+    /// The storage of profile `name`, under [`PROFILES_DIR`] in this root.
+    pub fn profile(&self, name: &str) -> Result<Self> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(eyre!(
+                "invalid profile name '{}': use letters, digits, '-' and '_'",
+                name
+            ));
+        }
+        Ok(Self::new(self.root.join(PROFILES_DIR).join(name)))
+    }
+
+    /// The directory files are kept in.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Storage for FsStorage {
+    fn read(&self, name: &str) -> Result<Option<String>> {
+        read_profile_file(&self.path(name))
+    }
+
+    fn write(&self, name: &str, contents: &str) -> Result<()> {
+        write_profile_file(&self.path(name), contents)
+    }
+
+    fn remove(&self, name: &str) -> Result<bool> {
+        let path = self.path(name);
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(eyre!("failed to remove {}: {}", path.display(), e)),
+        }
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let entries = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(eyre!("failed to list {}: {}", self.root.display(), e)),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry =
+                entry.map_err(|e| eyre!("failed to list {}: {}", self.root.display(), e))?;
+            if !entry.file_type().is_ok_and(|t| t.is_file()) {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str()
+                && !name.ends_with(".lock")
+                && !name.ends_with(".tmp")
+            {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    fn lock(&self, name: &str) -> Result<StorageLock> {
+        std::fs::create_dir_all(&self.root)
+            .map_err(|e| eyre!("failed to create {}: {}", self.root.display(), e))?;
+        let path = self.path(&format!("{}.lock", name));
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| eyre!("failed to open {}: {}", path.display(), e))?;
+        file.lock()
+            .map_err(|e| eyre!("failed to lock {}: {}", path.display(), e))?;
+        // Closing the file releases the lock.
+        Ok(StorageLock::new(file))
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+}
+
+/// Files held in memory, for tests. Clones share the same files.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    files: Arc<Mutex<HashMap<String, String>>>,
+    locks: Arc<(Mutex<HashSet<String>>, Condvar)>,
+}
+
+impl MemoryStorage {
+    /// Empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Releases a [`MemoryStorage`] lock on drop.
+struct MemoryLock {
+    locks: Arc<(Mutex<HashSet<String>>, Condvar)>,
+    name: String,
+}
+
+impl Drop for MemoryLock {
+    fn drop(&mut self) {
+        let (held, released) = &*self.locks;
+        held.lock().unwrap().remove(&self.name);
+        released.notify_all();
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, name: &str) -> Result<Option<String>> {
+        Ok(self.files.lock().unwrap().get(name).cloned())
+    }
+
+    fn write(&self, name: &str, contents: &str) -> Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), contents.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) -> Result<bool> {
+        Ok(self.files.lock().unwrap().remove(name).is_some())
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        Ok(self.files.lock().unwrap().keys().cloned().collect())
+    }
+
+    fn lock(&self, name: &str) -> Result<StorageLock> {
+        let (held, released) = &*self.locks;
+        let mut held = held.lock().unwrap();
+        while held.contains(name) {
+            held = released.wait(held).unwrap();
+        }
+        held.insert(name.to_string());
+        Ok(StorageLock::new(MemoryLock {
+            locks: self.locks.clone(),
+            name: name.to_string(),
+        }))
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        PathBuf::from("memory").join(name)
+    }
+}
+
+/// The default storage as a shared handle, for the stores' `open_in`.
+///
+/// A profile with encryption enabled is unlocked first (see
+/// `EncryptedStorage::unlock`); without the `encryption` feature such a
+/// profile is an error rather than a pile of unreadable files.
+pub fn default_storage() -> Result<Arc<dyn Storage>> {
+    let storage: Arc<dyn Storage> = Arc::new(FsStorage::open_default()?);
+    #[cfg(feature = "encryption")]
+    if let Some(encrypted) = EncryptedStorage::unlock(storage.clone(), None)? {
+        return Ok(Arc::new(encrypted));
+    }
+    #[cfg(not(feature = "encryption"))]
+    if storage.read(ENCRYPTION_FILE)?.is_some() {
+        return Err(eyre!(
+            "{} is encrypted, but this build has no encryption support\n\n\
+             Hints:\n\
+             - Build with the `encryption` feature",
+            storage.path(ENCRYPTION_FILE).display()
+        ));
+    }
+    Ok(storage)
+}
+
+/// Storage for the file at `path`: an [`FsStorage`] rooted at its parent
+/// directory, and the file name to use in it.
+pub(crate) fn storage_for_path(path: &Path) -> Result<(Arc<dyn Storage>, String)> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| eyre!("not a file path: {}", path.display()))?;
+    let root = path.parent().unwrap_or(Path::new(""));
+    Ok((Arc::new(FsStorage::new(root)), name.to_string()))
+}
+
+/// Directory for data that can be rebuilt if deleted (downloaded configs
+/// and the like): `$XDG_CACHE_HOME/aspens`, or `~/.cache/aspens`. With
+/// `$ASPENS_HOME` set it is `$ASPENS_HOME/cache` instead, so one variable
+/// relocates everything.
+pub fn cache_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(crate::util::PROFILE_DIR_ENV).filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir).join("cache"));
+    }
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir).join("aspens"));
+    }
+    Ok(crate::util::home_dir()?.join(".cache").join("aspens"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn memory_storage_round_trips_and_locks() {
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.read("a.json").unwrap(), None);
+        storage.write("a.json", "{}").unwrap();
+        assert_eq!(
+            storage.clone().read("a.json").unwrap().as_deref(),
+            Some("{}")
+        );
+        assert!(storage.remove("a.json").unwrap());
+        assert!(!storage.remove("a.json").unwrap());
+
+        // A second locker waits until the first guard drops.
+        let guard = storage.lock("a.json").unwrap();
+        let other = storage.clone();
+        let waiter = std::thread::spawn(move || {
+            let _guard = other.lock("a.json").unwrap();
+            other.write("a.json", "second").unwrap();
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        storage.write("a.json", "first").unwrap();
+        drop(guard);
+        waiter.join().unwrap();
+        assert_eq!(storage.read("a.json").unwrap().as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn fs_storage_scopes_profiles_and_locks() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FsStorage::new(dir.path());
+        let testnet = storage.profile("testnet").unwrap();
+        assert!(storage.profile("../up").is_err());
+
+        testnet.write("presets.json", "{}").unwrap();
+        assert_eq!(storage.read("presets.json").unwrap(), None);
+        assert_eq!(
+            testnet.path("presets.json"),
+            dir.path().join("profiles/testnet/presets.json")
+        );
+        assert_eq!(testnet.read("presets.json").unwrap().as_deref(), Some("{}"));
+
+        let guard = testnet.lock("presets.json").unwrap();
+        drop(guard);
+        let _again = testnet.lock("presets.json").unwrap();
+        assert!(testnet.remove("presets.json").unwrap());
+    }
+}
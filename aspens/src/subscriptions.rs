@@ -0,0 +1,357 @@
+//! One upstream stream per market, shared by every component in a process.
+//!
+//! A TUI, an order tracker and a risk engine watching the same market would
+//! otherwise each open their own orderbook and trades streams. A
+//! [`SubscriptionHub`] opens one upstream subscription per market and
+//! stream kind, fans its events out over a `broadcast` channel, and keeps a
+//! reference count: the upstream opens with the first [`Subscription`] and
+//! closes when the last one is dropped.
+//!
+//! Subscriptions see live events from the moment they join; a component
+//! that needs the resting book first should take a snapshot
+//! ([`fetch_book_snapshot`](crate::commands::trading::conflate::fetch_book_snapshot))
+//! and apply the stream on top. A subscriber that falls more than the
+//! channel capacity behind skips the oldest events and counts them in
+//! [`Subscription::missed`]. If the upstream ends, every subscription's
+//! [`recv`](Subscription::recv) returns `None` and the next subscribe opens
+//! a fresh one.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+
+use crate::api::StreamHandle;
+use crate::commands::trading::stream_orderbook::{
+    StreamOrderbookOptions, arborter_pb::OrderbookEntry, stream_orderbook_channel,
+};
+use crate::commands::trading::stream_trades::{
+    StreamTradesOptions, arborter_pb::Trade, stream_trades_channel,
+};
+use crate::error::Result;
+use crate::executor::AbortOnDrop;
+use crate::grpc::GrpcOptions;
+
+/// Events buffered per market before slow subscribers start missing them.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+static NEXT_UPSTREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+/// One open upstream stream and the subscriptions sharing it.
+struct Upstream<T> {
+    /// Tells a stale subscription or task apart from a reopened upstream.
+    id: u64,
+    tx: broadcast::Sender<T>,
+    subscribers: usize,
+    task: JoinHandle<()>,
+}
+
+type Upstreams<T> = Arc<Mutex<HashMap<String, Upstream<T>>>>;
+
+/// Shares per-market orderbook and trades streams across a process.
+///
+/// Cheap to [`Clone`]; clones share the upstreams. Subscribing needs a
+/// tokio runtime, which runs the upstream tasks.
+#[derive(Clone)]
+pub struct SubscriptionHub {
+    url: Arc<str>,
+    grpc: Option<GrpcOptions>,
+    capacity: usize,
+    orderbook: Upstreams<OrderbookEntry>,
+    trades: Upstreams<Trade>,
+}
+
+impl SubscriptionHub {
+    /// A hub for the stack at `url`, opening its streams with `grpc`
+    /// (`None` for the defaults).
+    pub fn new(url: impl Into<String>, grpc: Option<GrpcOptions>) -> Self {
+        Self {
+            url: url.into().into(),
+            grpc,
+            capacity: DEFAULT_CAPACITY,
+            orderbook: Arc::default(),
+            trades: Arc::default(),
+        }
+    }
+
+    /// Buffer `capacity` events per market (default [`DEFAULT_CAPACITY`]).
+    /// Applies to upstreams opened afterwards.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Subscribe to the live orderbook of `market_id` (a resolved market
+    /// ID), opening the upstream if this is its first subscriber.
+    pub fn orderbook(&self, market_id: &str) -> Subscription<OrderbookEntry> {
+        let options = StreamOrderbookOptions {
+            market_id: market_id.to_string(),
+            grpc: self.grpc,
+            ..Default::default()
+        };
+        let url = self.url.to_string();
+        subscribe(&self.orderbook, market_id, self.capacity, move || {
+            stream_orderbook_channel(url, options)
+        })
+    }
+
+    /// Subscribe to the live trades of `market_id`, opening the upstream if
+    /// this is its first subscriber.
+    pub fn trades(&self, market_id: &str) -> Subscription<Trade> {
+        let options = StreamTradesOptions {
+            market_id: market_id.to_string(),
+            grpc: self.grpc,
+            ..Default::default()
+        };
+        let url = self.url.to_string();
+        subscribe(&self.trades, market_id, self.capacity, move || {
+            stream_trades_channel(url, options)
+        })
+    }
+
+    /// Subscriptions sharing the orderbook upstream of `market_id`.
+    pub fn orderbook_subscribers(&self, market_id: &str) -> usize {
+        subscribers(&self.orderbook, market_id)
+    }
+
+    /// Subscriptions sharing the trades upstream of `market_id`.
+    pub fn trades_subscribers(&self, market_id: &str) -> usize {
+        subscribers(&self.trades, market_id)
+    }
+
+    /// Upstream streams currently open, orderbook and trades together.
+    pub fn upstreams(&self) -> usize {
+        self.orderbook.lock().unwrap().len() + self.trades.lock().unwrap().len()
+    }
+}
+
+fn subscribers<T>(upstreams: &Upstreams<T>, market_id: &str) -> usize {
+    upstreams
+        .lock()
+        .unwrap()
+        .get(market_id)
+        .map_or(0, |u| u.subscribers)
+}
+
+/// Join the upstream for `market_id`, opening it with `open` if there is
+/// none.
+fn subscribe<T, F, Fut>(
+    upstreams: &Upstreams<T>,
+    market_id: &str,
+    capacity: usize,
+    open: F,
+) -> Subscription<T>
+where
+    T: Clone + Send + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<StreamHandle<T>>> + Send + 'static,
+{
+    let mut map = upstreams.lock().unwrap();
+    if let Some(upstream) = map.get_mut(market_id) {
+        upstream.subscribers += 1;
+        return Subscription {
+            market_id: market_id.to_string(),
+            id: upstream.id,
+            rx: upstream.tx.subscribe(),
+            upstreams: upstreams.clone(),
+            missed: 0,
+        };
+    }
+
+    let id = NEXT_UPSTREAM_ID.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = broadcast::channel(capacity);
+    let task = tokio::spawn(forward(
+        upstreams.clone(),
+        market_id.to_string(),
+        id,
+        tx.clone(),
+        open,
+    ));
+    map.insert(
+        market_id.to_string(),
+        Upstream {
+            id,
+            tx,
+            subscribers: 1,
+            task,
+        },
+    );
+    Subscription {
+        market_id: market_id.to_string(),
+        id,
+        rx,
+        upstreams: upstreams.clone(),
+        missed: 0,
+    }
+}
+
+/// Pump one upstream into its broadcast channel until it ends, then
+/// unregister it so the next subscriber opens a fresh one.
+async fn forward<T, F, Fut>(
+    upstreams: Upstreams<T>,
+    market_id: String,
+    id: u64,
+    tx: broadcast::Sender<T>,
+    open: F,
+) where
+    T: Clone + Send + 'static,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<StreamHandle<T>>>,
+{
+    let result = async {
+        let (mut rx, stream) = open().await?;
+        // Aborting this task (last subscriber gone) takes the stream down.
+        let mut stream = AbortOnDrop(stream);
+        while let Some(event) = rx.recv().await {
+            // No receivers only means every subscriber is between drops.
+            let _ = tx.send(event);
+        }
+        (&mut stream.0).await?
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("shared stream for market {} ended: {}", market_id, e);
+    }
+
+    let mut map = upstreams.lock().unwrap();
+    if map.get(&market_id).is_some_and(|u| u.id == id) {
+        map.remove(&market_id);
+    }
+}
+
+/// A handle on a shared market stream. Dropping the last one for a market
+/// closes its upstream.
+pub struct Subscription<T: Clone> {
+    market_id: String,
+    id: u64,
+    rx: broadcast::Receiver<T>,
+    upstreams: Upstreams<T>,
+    missed: u64,
+}
+
+impl<T: Clone> Subscription<T> {
+    /// The market this subscription follows.
+    pub fn market_id(&self) -> &str {
+        &self.market_id
+    }
+
+    /// The next event, or `None` once the upstream has ended. Events this
+    /// subscriber fell too far behind to receive are skipped and counted
+    /// in [`missed`](Self::missed).
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(
+                        "subscriber on market {} fell behind; skipped {} events",
+                        self.market_id,
+                        skipped
+                    );
+                    self.missed += skipped;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Events skipped because this subscriber fell behind.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+impl<T: Clone> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        let Ok(mut map) = self.upstreams.lock() else {
+            return;
+        };
+        let Some(upstream) = map.get_mut(&self.market_id) else {
+            return;
+        };
+        // The upstream may have ended and been reopened for others.
+        if upstream.id != self.id {
+            return;
+        }
+        upstream.subscribers -= 1;
+        if upstream.subscribers == 0 {
+            upstream.task.abort();
+            map.remove(&self.market_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use tokio::sync::mpsc;
+
+    /// An opener that counts opens and hands back the sender feeding the
+    /// upstream.
+    fn opener(
+        opens: Arc<AtomicUsize>,
+        feed: Arc<Mutex<Option<mpsc::Sender<u32>>>>,
+    ) -> impl FnOnce() -> std::future::Ready<Result<StreamHandle<u32>>> + Send + 'static {
+        move || {
+            opens.fetch_add(1, Ordering::SeqCst);
+            let (tx, rx) = mpsc::channel(8);
+            *feed.lock().unwrap() = Some(tx);
+            let task = tokio::spawn(async { Ok(()) });
+            std::future::ready(Ok((rx, task)))
+        }
+    }
+
+    #[tokio::test]
+    async fn shares_one_upstream_and_closes_with_the_last_subscriber() {
+        let upstreams: Upstreams<u32> = Arc::default();
+        let opens = Arc::new(AtomicUsize::new(0));
+        let feed = Arc::new(Mutex::new(None));
+
+        let mut a = subscribe(&upstreams, "m", 8, opener(opens.clone(), feed.clone()));
+        let mut b = subscribe(&upstreams, "m", 8, opener(opens.clone(), feed.clone()));
+        assert_eq!(subscribers(&upstreams, "m"), 2);
+
+        // Let the upstream task open the stream.
+        while feed.lock().unwrap().is_none() {
+            tokio::task::yield_now().await;
+        }
+        let tx = feed.lock().unwrap().clone().unwrap();
+        tx.send(7).await.unwrap();
+        assert_eq!(a.recv().await, Some(7));
+        assert_eq!(b.recv().await, Some(7));
+        assert_eq!(opens.load(Ordering::SeqCst), 1);
+
+        drop(a);
+        assert_eq!(subscribers(&upstreams, "m"), 1);
+        drop(b);
+        assert!(upstreams.lock().unwrap().is_empty());
+
+        let _c = subscribe(&upstreams, "m", 8, opener(opens.clone(), feed.clone()));
+        while opens.load(Ordering::SeqCst) < 2 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn ended_upstream_closes_subscriptions() {
+        let upstreams: Upstreams<u32> = Arc::default();
+        let opens = Arc::new(AtomicUsize::new(0));
+        let feed = Arc::new(Mutex::new(None));
+        let mut sub = subscribe(&upstreams, "m", 8, opener(opens, feed.clone()));
+        while feed.lock().unwrap().is_none() {
+            tokio::task::yield_now().await;
+        }
+        // Dropping the feed ends the upstream, which unregisters itself.
+        feed.lock().unwrap().take();
+        assert_eq!(sub.recv().await, None);
+        assert!(upstreams.lock().unwrap().is_empty());
+        assert_eq!(sub.missed(), 0);
+        // Dropping a subscription to an ended upstream is a no-op.
+        drop(sub);
+        assert_eq!(subscribers(&upstreams, "m"), 0);
+    }
+}
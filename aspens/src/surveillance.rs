@@ -0,0 +1,524 @@
+//! Market surveillance for operators of permissioned stacks.
+//!
+//! A [`Surveillance`] feeds a market's trade and orderbook streams through
+//! pluggable [`Detector`]s and hands whatever they flag to the configured
+//! [`Notifier`]s. Three detectors ship with the SDK:
+//!
+//! - [`WashTradeDetector`]: trades where one address is on both sides, and
+//!   the same pair of addresses trading with each other repeatedly within a
+//!   window.
+//! - [`PriceSpikeDetector`]: a trade price move larger than a number of
+//!   standard deviations of the recent moves.
+//! - [`QuoteStuffingDetector`]: one address posting or canceling orders
+//!   faster than a rate limit.
+//!
+//! They are heuristics that point an operator at activity worth a look,
+//! not proof of abuse. Hidden orders' trades print with one side redacted
+//! and are invisible to the book stream, so they escape all three.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::commands::trading::replay::HistoricalReplay;
+use crate::commands::trading::stream_orderbook::{
+    StreamOrderbookOptions, arborter_pb::OrderbookEntry, stream_orderbook_channel,
+};
+use crate::commands::trading::stream_trades::{
+    StreamTradesOptions, arborter_pb::Trade, stream_trades_channel,
+};
+use crate::error::{AspensError, Result};
+use crate::notify::{Notification, Notifier, Severity};
+
+/// Something a [`Detector`] flagged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Name of the detector that raised it.
+    pub detector: &'static str,
+    /// How urgent it is.
+    pub severity: Severity,
+    /// What was seen.
+    pub message: String,
+    /// When it happened, unix milliseconds.
+    pub timestamp_ms: u64,
+}
+
+impl Finding {
+    /// The finding as a notification from `surveillance/<detector>` on
+    /// `market_id`.
+    pub fn to_notification(&self, market_id: &str) -> Notification {
+        Notification {
+            severity: self.severity,
+            source: format!("surveillance/{}", self.detector),
+            message: format!("{}: {}", market_id, self.message),
+            timestamp_ms: self.timestamp_ms,
+        }
+    }
+}
+
+/// Inspects market events and flags suspicious activity.
+///
+/// Both hooks default to flagging nothing, so a detector implements only
+/// the streams it cares about. `now_ms` is the time the event was
+/// received, for rate-based detectors.
+pub trait Detector: Send {
+    /// Short name used in findings, e.g. `wash-trade`.
+    fn name(&self) -> &'static str;
+
+    /// Inspect a trade.
+    fn on_trade(&mut self, _trade: &Trade, _now_ms: u64) -> Vec<Finding> {
+        Vec::new()
+    }
+
+    /// Inspect an orderbook update.
+    fn on_book(&mut self, _entry: &OrderbookEntry, _now_ms: u64) -> Vec<Finding> {
+        Vec::new()
+    }
+}
+
+/// Flags self-trades and address pairs trading with each other repeatedly.
+#[derive(Debug, Clone)]
+pub struct WashTradeDetector {
+    /// Trades between the same two addresses within [`Self::window`] that
+    /// raise a finding.
+    pub pair_threshold: usize,
+    /// How far back pair trades count.
+    pub window: Duration,
+    pairs: HashMap<(String, String), VecDeque<u64>>,
+}
+
+impl WashTradeDetector {
+    /// Flag a pair after `pair_threshold` trades within `window`.
+    pub fn new(pair_threshold: usize, window: Duration) -> Self {
+        Self {
+            pair_threshold,
+            window,
+            pairs: HashMap::new(),
+        }
+    }
+}
+
+impl Default for WashTradeDetector {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(600))
+    }
+}
+
+impl Detector for WashTradeDetector {
+    fn name(&self) -> &'static str {
+        "wash-trade"
+    }
+
+    fn on_trade(&mut self, trade: &Trade, _now_ms: u64) -> Vec<Finding> {
+        let (maker, taker) = (&trade.maker_base_address, &trade.taker_base_address);
+        // A redacted (hidden) side has no address to compare.
+        if maker.is_empty() || taker.is_empty() {
+            return Vec::new();
+        }
+        let detector = self.name();
+        let finding = |severity, message| {
+            vec![Finding {
+                detector,
+                severity,
+                message,
+                timestamp_ms: trade.timestamp,
+            }]
+        };
+        if maker.eq_ignore_ascii_case(taker)
+            || (!trade.maker_quote_address.is_empty()
+                && trade
+                    .maker_quote_address
+                    .eq_ignore_ascii_case(&trade.taker_quote_address))
+        {
+            return finding(
+                Severity::Critical,
+                format!(
+                    "{} traded with itself (order {}, qty {} at {})",
+                    maker, trade.order_hit, trade.qty, trade.price
+                ),
+            );
+        }
+
+        let key = if maker.to_lowercase() < taker.to_lowercase() {
+            (maker.to_lowercase(), taker.to_lowercase())
+        } else {
+            (taker.to_lowercase(), maker.to_lowercase())
+        };
+        let window_ms = self.window.as_millis() as u64;
+        let times = self.pairs.entry(key).or_default();
+        times.push_back(trade.timestamp);
+        while times
+            .front()
+            .is_some_and(|&t| t + window_ms < trade.timestamp)
+        {
+            times.pop_front();
+        }
+        if times.len() >= self.pair_threshold {
+            let count = times.len();
+            // Start a fresh count so one burst raises one finding.
+            times.clear();
+            return finding(
+                Severity::Warning,
+                format!(
+                    "{} and {} traded with each other {} times within {}s",
+                    maker,
+                    taker,
+                    count,
+                    self.window.as_secs()
+                ),
+            );
+        }
+        Vec::new()
+    }
+}
+
+/// Flags trade prices that move more than `sigma` standard deviations of
+/// the recent price moves.
+#[derive(Debug, Clone)]
+pub struct PriceSpikeDetector {
+    /// Threshold, in standard deviations of the recent moves.
+    pub sigma: f64,
+    /// How many recent moves the deviation is measured over.
+    pub window: usize,
+    /// Moves needed before anything is flagged.
+    pub min_samples: usize,
+    last_price: Option<f64>,
+    moves: VecDeque<f64>,
+}
+
+impl PriceSpikeDetector {
+    /// Flag moves beyond `sigma` deviations of the last `window` moves.
+    pub fn new(sigma: f64, window: usize) -> Self {
+        Self {
+            sigma,
+            window,
+            min_samples: window.min(20),
+            last_price: None,
+            moves: VecDeque::new(),
+        }
+    }
+}
+
+impl Default for PriceSpikeDetector {
+    fn default() -> Self {
+        Self::new(4.0, 100)
+    }
+}
+
+impl Detector for PriceSpikeDetector {
+    fn name(&self) -> &'static str {
+        "price-spike"
+    }
+
+    fn on_trade(&mut self, trade: &Trade, _now_ms: u64) -> Vec<Finding> {
+        let Ok(price) = trade.price.parse::<f64>() else {
+            return Vec::new();
+        };
+        if price <= 0.0 {
+            return Vec::new();
+        }
+        let Some(last) = self.last_price.replace(price) else {
+            return Vec::new();
+        };
+        // Log returns, so the threshold means the same at any price level.
+        let change = (price / last).ln();
+
+        let mut findings = Vec::new();
+        if self.moves.len() >= self.min_samples.max(2) {
+            let n = self.moves.len() as f64;
+            let mean = self.moves.iter().sum::<f64>() / n;
+            let variance = self.moves.iter().map(|m| (m - mean).powi(2)).sum::<f64>() / n;
+            let deviation = variance.sqrt();
+            if deviation > 0.0 && (change - mean).abs() > self.sigma * deviation {
+                findings.push(Finding {
+                    detector: self.name(),
+                    severity: Severity::Warning,
+                    message: format!(
+                        "price moved {:+.2}% to {} ({:.1} sigma)",
+                        (change.exp() - 1.0) * 100.0,
+                        trade.price,
+                        (change - mean).abs() / deviation
+                    ),
+                    timestamp_ms: trade.timestamp,
+                });
+            }
+        }
+        self.moves.push_back(change);
+        if self.moves.len() > self.window {
+            self.moves.pop_front();
+        }
+        findings
+    }
+}
+
+/// Flags an address whose orderbook updates (new orders, cancels) exceed
+/// `max_updates` within `window`.
+#[derive(Debug, Clone)]
+pub struct QuoteStuffingDetector {
+    /// Updates per address allowed within [`Self::window`].
+    pub max_updates: usize,
+    /// The rate window.
+    pub window: Duration,
+    updates: HashMap<String, VecDeque<u64>>,
+}
+
+impl QuoteStuffingDetector {
+    /// Flag more than `max_updates` updates per address within `window`.
+    pub fn new(max_updates: usize, window: Duration) -> Self {
+        Self {
+            max_updates,
+            window,
+            updates: HashMap::new(),
+        }
+    }
+}
+
+impl Default for QuoteStuffingDetector {
+    fn default() -> Self {
+        Self::new(50, Duration::from_secs(1))
+    }
+}
+
+impl Detector for QuoteStuffingDetector {
+    fn name(&self) -> &'static str {
+        "quote-stuffing"
+    }
+
+    fn on_book(&mut self, entry: &OrderbookEntry, now_ms: u64) -> Vec<Finding> {
+        if entry.maker_base_address.is_empty() {
+            return Vec::new();
+        }
+        let window_ms = self.window.as_millis() as u64;
+        let times = self
+            .updates
+            .entry(entry.maker_base_address.to_lowercase())
+            .or_default();
+        times.push_back(now_ms);
+        while times.front().is_some_and(|&t| t + window_ms <= now_ms) {
+            times.pop_front();
+        }
+        if times.len() <= self.max_updates {
+            return Vec::new();
+        }
+        let count = times.len();
+        // Start a fresh count so a sustained burst raises one finding per
+        // window rather than one per update.
+        times.clear();
+        vec![Finding {
+            detector: self.name(),
+            severity: Severity::Warning,
+            message: format!(
+                "{} sent {} orderbook updates within {}ms (limit {})",
+                entry.maker_base_address, count, window_ms, self.max_updates
+            ),
+            timestamp_ms: now_ms,
+        }]
+    }
+}
+
+/// Detectors and notifiers watching one market.
+pub struct Surveillance {
+    market_id: String,
+    detectors: Vec<Box<dyn Detector>>,
+    notifiers: Vec<Arc<dyn Notifier>>,
+}
+
+impl Surveillance {
+    /// Surveillance of `market_id` with no detectors or notifiers.
+    pub fn new(market_id: impl Into<String>) -> Self {
+        Self {
+            market_id: market_id.into(),
+            detectors: Vec::new(),
+            notifiers: Vec::new(),
+        }
+    }
+
+    /// Surveillance of `market_id` with every built-in detector at its
+    /// default thresholds.
+    pub fn with_default_detectors(market_id: impl Into<String>) -> Self {
+        Self::new(market_id)
+            .with_detector(WashTradeDetector::default())
+            .with_detector(PriceSpikeDetector::default())
+            .with_detector(QuoteStuffingDetector::default())
+    }
+
+    /// Add a detector.
+    pub fn with_detector(mut self, detector: impl Detector + 'static) -> Self {
+        self.detectors.push(Box::new(detector));
+        self
+    }
+
+    /// Add a notifier; every finding goes to each one.
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Run a trade through every detector and notify the findings.
+    pub fn on_trade(&mut self, trade: &Trade, now_ms: u64) -> Vec<Finding> {
+        let findings: Vec<Finding> = self
+            .detectors
+            .iter_mut()
+            .flat_map(|d| d.on_trade(trade, now_ms))
+            .collect();
+        self.dispatch(&findings);
+        findings
+    }
+
+    /// Run an orderbook update through every detector and notify the
+    /// findings.
+    pub fn on_book(&mut self, entry: &OrderbookEntry, now_ms: u64) -> Vec<Finding> {
+        let findings: Vec<Finding> = self
+            .detectors
+            .iter_mut()
+            .flat_map(|d| d.on_book(entry, now_ms))
+            .collect();
+        self.dispatch(&findings);
+        findings
+    }
+
+    fn dispatch(&self, findings: &[Finding]) {
+        for finding in findings {
+            let notification = finding.to_notification(&self.market_id);
+            for notifier in &self.notifiers {
+                notifier.notify(&notification);
+            }
+        }
+    }
+
+    /// Watch the market's live trade and orderbook streams until either
+    /// closes, returning how that stream ended.
+    pub async fn run(mut self, url: String) -> Result<()> {
+        let (mut trades, trades_task) = stream_trades_channel(
+            url.clone(),
+            StreamTradesOptions {
+                market_id: self.market_id.clone(),
+                historical_closed_trades: false,
+                filter_by_trader: None,
+                grpc: None,
+                replay: HistoricalReplay::default(),
+                idle: None,
+                reconnect: None,
+            },
+        )
+        .await?;
+        let (mut book, book_task) = stream_orderbook_channel(
+            url,
+            StreamOrderbookOptions {
+                market_id: self.market_id.clone(),
+                historical_open_orders: false,
+                filter_by_trader: None,
+                grpc: None,
+                replay: HistoricalReplay::default(),
+                idle: None,
+                reconnect: None,
+            },
+        )
+        .await?;
+
+        // Whichever stream ends first ends the watch; its task holds the
+        // reason.
+        let (finished, other) = loop {
+            tokio::select! {
+                trade = trades.recv() => match trade {
+                    Some(trade) => { self.on_trade(&trade, crate::util::unix_now_ms()); }
+                    None => break (trades_task, book_task),
+                },
+                entry = book.recv() => match entry {
+                    Some(entry) => { self.on_book(&entry, crate::util::unix_now_ms()); }
+                    None => break (book_task, trades_task),
+                },
+            }
+        };
+        other.abort();
+        match finished.await {
+            Ok(result) => result,
+            Err(e) => Err(AspensError::other(format!(
+                "surveillance stream task failed: {}",
+                e
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::ChannelNotifier;
+
+    fn trade(timestamp: u64, price: &str, maker: &str, taker: &str) -> Trade {
+        Trade {
+            timestamp,
+            price: price.to_string(),
+            qty: "1".to_string(),
+            maker_base_address: maker.to_string(),
+            taker_base_address: taker.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flags_self_trades_and_repeated_pairs() {
+        let (notifier, mut rx) = ChannelNotifier::new();
+        let mut surveillance = Surveillance::new("m")
+            .with_detector(WashTradeDetector::new(3, Duration::from_secs(60)))
+            .with_notifier(Arc::new(notifier));
+
+        let found = surveillance.on_trade(&trade(1_000, "100", "0xA", "0xa"), 0);
+        assert_eq!(found[0].severity, Severity::Critical);
+        assert_eq!(rx.try_recv().unwrap().source, "surveillance/wash-trade");
+
+        assert!(
+            surveillance
+                .on_trade(&trade(2_000, "100", "0xa", "0xb"), 0)
+                .is_empty()
+        );
+        assert!(
+            surveillance
+                .on_trade(&trade(3_000, "100", "0xb", "0xa"), 0)
+                .is_empty()
+        );
+        let found = surveillance.on_trade(&trade(4_000, "100", "0xa", "0xb"), 0);
+        assert_eq!(found.len(), 1, "{found:?}");
+        assert!(found[0].message.contains("3 times"));
+        // Outside the window the count starts over.
+        assert!(
+            surveillance
+                .on_trade(&trade(100_000, "100", "0xa", "0xb"), 0)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn flags_price_spikes_beyond_sigma() {
+        let mut detector = PriceSpikeDetector::new(4.0, 50);
+        for i in 0..40 {
+            let price = if i % 2 == 0 { "100" } else { "101" };
+            assert!(detector.on_trade(&trade(i, price, "a", "b"), 0).is_empty());
+        }
+        let found = detector.on_trade(&trade(40, "130", "a", "b"), 0);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("sigma"), "{}", found[0].message);
+    }
+
+    #[test]
+    fn flags_quote_stuffing_once_per_burst() {
+        let mut detector = QuoteStuffingDetector::new(3, Duration::from_secs(1));
+        let entry = OrderbookEntry {
+            maker_base_address: "0xa".to_string(),
+            ..Default::default()
+        };
+        let found: Vec<Finding> = (0..8)
+            .flat_map(|i| detector.on_book(&entry, 1_000 + i * 10))
+            .collect();
+        assert_eq!(found.len(), 2);
+        // Spread out, the same number of updates is fine.
+        let mut detector = QuoteStuffingDetector::new(3, Duration::from_secs(1));
+        assert!(
+            (0..8)
+                .flat_map(|i| detector.on_book(&entry, i * 500))
+                .next()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Opt-in, anonymous usage statistics.
+//!
+//! Telemetry is off until the user turns it on (`aspens-cli telemetry on`).
+//! When on, each command run appends a [`UsageEvent`] to a local queue in
+//! the profile directory (see [`crate::storage`]). An event holds
+//! only the command name, how long it took, whether it succeeded and the SDK
+//! version — never keys, addresses, amounts, markets or arguments. The queue
+//! stays on disk, where `aspens-cli telemetry status` shows it; nothing is
+//! sent anywhere by this module.
+//!
+//! `DO_NOT_TRACK=1` or `ASPENS_TELEMETRY=0` in the environment disables
+//! recording regardless of the saved setting.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::Schema;
+use crate::storage::{Storage, default_storage, storage_for_path};
+use crate::util::unix_now;
+
+/// File name of the telemetry setting and queue inside the profile directory.
+pub const TELEMETRY_FILE: &str = "telemetry.json";
+
+static TELEMETRY_SCHEMA: Schema = Schema::new("telemetry file", &[]);
+
+/// Most events kept in the queue; older ones are dropped first.
+pub const MAX_QUEUED_EVENTS: usize = 1_000;
+
+/// Version of this SDK, as recorded in each event.
+pub const SDK_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// One command run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    /// Command name, e.g. `deposit` (no arguments).
+    pub command: String,
+    /// Wall-clock duration, in milliseconds.
+    pub duration_ms: u64,
+    /// True if the command succeeded.
+    pub success: bool,
+    /// SDK version that ran it.
+    pub sdk_version: String,
+    /// Unix seconds when it finished.
+    pub at: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TelemetryFile {
+    enabled: bool,
+    #[serde(default)]
+    queue: VecDeque<UsageEvent>,
+}
+
+/// The telemetry setting and its local event queue.
+#[derive(Debug)]
+pub struct Telemetry {
+    storage: Arc<dyn Storage>,
+    name: String,
+    file: TelemetryFile,
+}
+
+impl Telemetry {
+    /// Open the telemetry file at `path`; a missing file means telemetry is
+    /// off.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let (storage, name) = storage_for_path(&path.into())?;
+        Self::load(storage, name)
+    }
+
+    /// Open [`TELEMETRY_FILE`] in `storage`.
+    pub fn open_in(storage: Arc<dyn Storage>) -> Result<Self> {
+        Self::load(storage, TELEMETRY_FILE.to_string())
+    }
+
+    /// Open [`TELEMETRY_FILE`] in the default storage (see [`crate::storage`]).
+    pub fn open_default() -> Result<Self> {
+        Self::open_in(default_storage()?)
+    }
+
+    fn load(storage: Arc<dyn Storage>, name: String) -> Result<Self> {
+        let file = match storage.read(&name)? {
+            Some(json) => TELEMETRY_SCHEMA.load(&json).map_err(|e| {
+                eyre!(
+                    "invalid telemetry file {}: {}",
+                    storage.path(&name).display(),
+                    e
+                )
+            })?,
+            None => TelemetryFile::default(),
+        };
+        Ok(Self {
+            storage,
+            name,
+            file,
+        })
+    }
+
+    /// Path of the telemetry file.
+    pub fn path(&self) -> PathBuf {
+        self.storage.path(&self.name)
+    }
+
+    /// True if the user opted in.
+    pub fn enabled(&self) -> bool {
+        self.file.enabled
+    }
+
+    /// True if the environment vetoes recording (`DO_NOT_TRACK=1` or
+    /// `ASPENS_TELEMETRY=0`).
+    pub fn disabled_by_env() -> bool {
+        let set = |name: &str, value: &str| std::env::var(name).is_ok_and(|v| v.trim() == value);
+        set("DO_NOT_TRACK", "1") || set("ASPENS_TELEMETRY", "0")
+    }
+
+    /// Turn telemetry on or off. Turning it off also clears the queue.
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.file.enabled = enabled;
+        if !enabled {
+            self.file.queue.clear();
+        }
+        self.write()
+    }
+
+    /// Queue an event for `command`, if telemetry is on and the environment
+    /// allows it. Returns whether the event was recorded.
+    pub fn record(&mut self, command: &str, duration: Duration, success: bool) -> Result<bool> {
+        if !self.file.enabled || Self::disabled_by_env() || command.is_empty() {
+            return Ok(false);
+        }
+        if self.file.queue.len() >= MAX_QUEUED_EVENTS {
+            self.file.queue.pop_front();
+        }
+        self.file.queue.push_back(UsageEvent {
+            command: command.to_string(),
+            duration_ms: duration.as_millis().try_into().unwrap_or(u64::MAX),
+            success,
+            sdk_version: SDK_VERSION.to_string(),
+            at: unix_now(),
+        });
+        self.write()?;
+        Ok(true)
+    }
+
+    /// Queued events, oldest first.
+    pub fn queued(&self) -> impl Iterator<Item = &UsageEvent> {
+        self.file.queue.iter()
+    }
+
+    /// Remove and return the queued events, oldest first.
+    pub fn drain(&mut self) -> Result<Vec<UsageEvent>> {
+        let events = self.file.queue.drain(..).collect();
+        self.write()?;
+        Ok(events)
+    }
+
+    fn write(&self) -> Result<()> {
+        let _lock = self.storage.lock(&self.name)?;
+        self.storage
+            .write(&self.name, &TELEMETRY_SCHEMA.to_string(&self.file)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_when_opted_in_and_clears_on_opt_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TELEMETRY_FILE);
+
+        let mut telemetry = Telemetry::open(&path).unwrap();
+        assert!(!telemetry.enabled());
+        assert!(
+            !telemetry
+                .record("deposit", Duration::from_millis(5), true)
+                .unwrap()
+        );
+
+        telemetry.set_enabled(true).unwrap();
+        if !Telemetry::disabled_by_env() {
+            assert!(
+                telemetry
+                    .record("deposit", Duration::from_millis(5), false)
+                    .unwrap()
+            );
+            let reopened = Telemetry::open(&path).unwrap();
+            let events: Vec<_> = reopened.queued().collect();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].command, "deposit");
+            assert!(!events[0].success);
+            assert_eq!(events[0].sdk_version, SDK_VERSION);
+        }
+
+        telemetry.set_enabled(false).unwrap();
+        let reopened = Telemetry::open(&path).unwrap();
+        assert!(!reopened.enabled());
+        assert_eq!(reopened.queued().count(), 0);
+    }
+}
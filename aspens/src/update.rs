@@ -0,0 +1,469 @@
+//! Checks for newer SDK releases.
+//!
+//! The check reads the GitHub releases feed of this repository (override it
+//! with `ASPENS_RELEASE_FEED`) and compares the newest stable release with
+//! the installed version. A release can also declare the stack version it
+//! becomes mandatory at, with a line in its notes such as
+//!
+//! ```text
+//! Required from stack: 1.4.0
+//! ```
+//!
+//! meaning stacks at 1.4.0 or later need that release or newer. Given the
+//! connected stack's version, [`evaluate`] works out the minimum SDK version
+//! the stack supports, and [`UpdateStatus::message`] says how to upgrade.
+//!
+//! Nothing here runs on its own: `aspens-cli version --check` runs the check
+//! on demand, and the REPL runs it at most once per [`CHECK_INTERVAL`],
+//! remembered in [`UPDATE_CHECK_FILE`] in the profile directory.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::Schema;
+use crate::storage::{Storage, default_storage, storage_for_path};
+use crate::telemetry::SDK_VERSION;
+use crate::util::unix_now;
+
+/// Release feed queried by default.
+pub const DEFAULT_RELEASE_FEED: &str = "https://api.github.com/repos/aspensprotocol/sdk/releases";
+
+/// Environment variable that overrides [`DEFAULT_RELEASE_FEED`].
+pub const RELEASE_FEED_ENV: &str = "ASPENS_RELEASE_FEED";
+
+/// Prefix of the release-notes line that names the stack version a release
+/// becomes mandatory at.
+pub const REQUIRED_FROM_STACK_MARKER: &str = "Required from stack:";
+
+/// How often the background check runs.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// File name of the last-check timestamp inside the profile directory.
+pub const UPDATE_CHECK_FILE: &str = "update_check.json";
+
+static UPDATE_CHECK_SCHEMA: Schema = Schema::new("update check file", &[]);
+
+/// Timeout for fetching the release feed.
+const FEED_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A `major.minor.patch[-pre]` version. Build metadata is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemVer {
+    /// Major version.
+    pub major: u64,
+    /// Minor version.
+    pub minor: u64,
+    /// Patch version.
+    pub patch: u64,
+    /// Pre-release label, e.g. `rc.1`.
+    pub pre: Option<String>,
+}
+
+impl FromStr for SemVer {
+    type Err = eyre::Report;
+
+    /// Parse `1.2.3`, `v1.2.3`, `1.2` or `1.2.3-rc.1+build`.
+    fn from_str(s: &str) -> Result<Self> {
+        let trimmed = s.trim();
+        let trimmed = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+        let core = trimmed.split('+').next().unwrap_or_default();
+        let (numbers, pre) = match core.split_once('-') {
+            Some((numbers, pre)) => (numbers, Some(pre.to_string())),
+            None => (core, None),
+        };
+        let parts = numbers
+            .split('.')
+            .map(|p| p.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| eyre!("invalid version '{}': {}", s, e))?;
+        let (major, minor, patch) = match parts.as_slice() {
+            [major, minor] => (*major, *minor, 0),
+            [major, minor, patch] => (*major, *minor, *patch),
+            _ => return Err(eyre!("invalid version '{}': expected major.minor.patch", s)),
+        };
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+/// One stable release from the feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// Release version.
+    pub version: SemVer,
+    /// Git tag, e.g. `v0.4.0`.
+    pub tag: String,
+    /// Release page.
+    pub url: String,
+    /// Stack version from which this release is the minimum supported SDK.
+    pub required_from_stack: Option<SemVer>,
+}
+
+#[derive(Deserialize)]
+struct FeedEntry {
+    tag_name: String,
+    #[serde(default)]
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+}
+
+/// The feed to query: `ASPENS_RELEASE_FEED`, or [`DEFAULT_RELEASE_FEED`].
+pub fn release_feed() -> String {
+    std::env::var(RELEASE_FEED_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_RELEASE_FEED.to_string())
+}
+
+/// Parse a GitHub-style releases feed, skipping drafts, pre-releases and
+/// tags that aren't versions.
+pub fn parse_releases(json: &str) -> Result<Vec<Release>> {
+    let entries: Vec<FeedEntry> =
+        serde_json::from_str(json).map_err(|e| eyre!("invalid release feed: {}", e))?;
+    Ok(entries
+        .into_iter()
+        .filter(|entry| !entry.draft && !entry.prerelease)
+        .filter_map(|entry| {
+            let version = release_version(&entry.tag_name)?;
+            let required_from_stack = entry.body.as_deref().and_then(|body| {
+                body.lines().find_map(|line| {
+                    line.trim()
+                        .trim_start_matches(['*', '-', ' '])
+                        .strip_prefix(REQUIRED_FROM_STACK_MARKER)
+                        .and_then(|v| v.trim().trim_matches('`').parse().ok())
+                })
+            });
+            Some(Release {
+                version,
+                tag: entry.tag_name,
+                url: entry.html_url,
+                required_from_stack,
+            })
+        })
+        .collect())
+}
+
+/// The version in a tag such as `v0.4.0` or `aspens-cli-v0.4.0`.
+fn release_version(tag: &str) -> Option<SemVer> {
+    let version = tag.rsplit_once("-v").map_or(tag, |(_, v)| v);
+    version.parse().ok().filter(|v: &SemVer| v.pre.is_none())
+}
+
+/// Fetch the releases in `feed`.
+pub async fn fetch_releases(feed: &str) -> Result<Vec<Release>> {
+    let client = reqwest::Client::builder()
+        .timeout(FEED_TIMEOUT)
+        .user_agent(concat!("aspens-sdk/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| eyre!("building HTTP client: {}", e))?;
+    let response = client
+        .get(feed)
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| eyre!("Failed to fetch release feed {}: {}", feed, e))?;
+    if !response.status().is_success() {
+        return Err(eyre!(
+            "release feed {} returned HTTP {}",
+            feed,
+            response.status()
+        ));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| eyre!("Failed to read release feed {}: {}", feed, e))?;
+    parse_releases(&body)
+}
+
+/// Version of the stack at `url`.
+pub async fn stack_version(url: String) -> Result<SemVer> {
+    use crate::commands::config::config_pb::Empty;
+    use crate::commands::config::config_pb::config_service_client::ConfigServiceClient;
+
+    let channel = crate::grpc::create_channel(&url).await?;
+    let mut client = ConfigServiceClient::new(channel);
+    let version = client
+        .get_version(tonic::Request::new(Empty {}))
+        .await?
+        .into_inner()
+        .version;
+    version.parse()
+}
+
+/// Result of comparing the installed version with the releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateStatus {
+    pub installed: SemVer,
+    /// Newest stable release, if the feed had any.
+    pub latest: Option<Release>,
+    /// Oldest release the connected stack supports, if any release
+    /// declares a requirement at or below the stack's version.
+    pub minimum_for_stack: Option<Release>,
+    /// Version of the connected stack, if it was queried.
+    pub stack_version: Option<SemVer>,
+}
+
+impl UpdateStatus {
+    /// True if a newer release exists.
+    pub fn update_available(&self) -> bool {
+        self.latest
+            .as_ref()
+            .is_some_and(|r| r.version > self.installed)
+    }
+
+    /// True if the installed version is older than the connected stack
+    /// supports.
+    pub fn unsupported(&self) -> bool {
+        self.minimum_for_stack
+            .as_ref()
+            .is_some_and(|r| r.version > self.installed)
+    }
+
+    /// A one-line summary, plus upgrade instructions when a newer release
+    /// exists.
+    pub fn message(&self) -> String {
+        let Some(latest) = self.latest.as_ref().filter(|_| self.update_available()) else {
+            return format!("aspens {} is up to date.", self.installed);
+        };
+        let mut msg = if let Some(minimum) = self
+            .minimum_for_stack
+            .as_ref()
+            .filter(|_| self.unsupported())
+        {
+            format!(
+                "aspens {} is older than {}, the minimum supported by the connected stack{}.",
+                self.installed,
+                minimum.version,
+                self.stack_version
+                    .as_ref()
+                    .map(|v| format!(" (version {})", v))
+                    .unwrap_or_default()
+            )
+        } else {
+            format!(
+                "aspens {} is available (installed: {}).",
+                latest.version, self.installed
+            )
+        };
+        msg.push_str(&format!(
+            "\n\nTo upgrade:\n  curl -fsSL https://raw.githubusercontent.com/aspensprotocol/sdk/main/install.sh | sh\n\
+             or:\n  cargo install --locked --git https://github.com/aspensprotocol/sdk --tag {} aspens-cli aspens-repl",
+            latest.tag
+        ));
+        if !latest.url.is_empty() {
+            msg.push_str(&format!("\n\nRelease notes: {}", latest.url));
+        }
+        msg
+    }
+}
+
+/// Compare `installed` with `releases`, given the connected stack's version
+/// if known.
+pub fn evaluate(
+    installed: SemVer,
+    releases: &[Release],
+    stack_version: Option<SemVer>,
+) -> UpdateStatus {
+    let latest = releases
+        .iter()
+        .max_by(|a, b| a.version.cmp(&b.version))
+        .cloned();
+    let minimum_for_stack = stack_version.as_ref().and_then(|stack| {
+        releases
+            .iter()
+            .filter(|r| {
+                r.required_from_stack
+                    .as_ref()
+                    .is_some_and(|req| req <= stack)
+            })
+            .max_by(|a, b| a.version.cmp(&b.version))
+            .cloned()
+    });
+    UpdateStatus {
+        installed,
+        latest,
+        minimum_for_stack,
+        stack_version,
+    }
+}
+
+/// Run the check against the release feed, and against the stack at
+/// `stack_url` when given. A stack that can't be reached or reports an
+/// unparseable version is skipped rather than failing the check.
+pub async fn check(stack_url: Option<String>) -> Result<UpdateStatus> {
+    let releases = fetch_releases(&release_feed()).await?;
+    let stack_version = match stack_url {
+        Some(url) => match stack_version(url).await {
+            Ok(v) => Some(v),
+            Err(e) => {
+                tracing::debug!("skipping stack version in update check: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    Ok(evaluate(SDK_VERSION.parse()?, &releases, stack_version))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CheckFile {
+    checked_at: u64,
+}
+
+/// When the background check last ran.
+#[derive(Debug)]
+pub struct CheckState {
+    storage: Arc<dyn Storage>,
+    name: String,
+    file: CheckFile,
+}
+
+impl CheckState {
+    /// Open the check file at `path`; a missing file means never checked.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let (storage, name) = storage_for_path(&path.into())?;
+        Self::load(storage, name)
+    }
+
+    /// Open [`UPDATE_CHECK_FILE`] in `storage`.
+    pub fn open_in(storage: Arc<dyn Storage>) -> Result<Self> {
+        Self::load(storage, UPDATE_CHECK_FILE.to_string())
+    }
+
+    /// Open [`UPDATE_CHECK_FILE`] in the default storage (see [`crate::storage`]).
+    pub fn open_default() -> Result<Self> {
+        Self::open_in(default_storage()?)
+    }
+
+    fn load(storage: Arc<dyn Storage>, name: String) -> Result<Self> {
+        let file = match storage.read(&name)? {
+            Some(json) => UPDATE_CHECK_SCHEMA.load(&json).map_err(|e| {
+                eyre!(
+                    "invalid update check file {}: {}",
+                    storage.path(&name).display(),
+                    e
+                )
+            })?,
+            None => CheckFile::default(),
+        };
+        Ok(Self {
+            storage,
+            name,
+            file,
+        })
+    }
+
+    /// Path of the check file.
+    pub fn path(&self) -> PathBuf {
+        self.storage.path(&self.name)
+    }
+
+    /// True if the last check is more than [`CHECK_INTERVAL`] ago.
+    pub fn check_due(&self) -> bool {
+        unix_now().saturating_sub(self.file.checked_at) >= CHECK_INTERVAL.as_secs()
+    }
+
+    /// Record a check made now.
+    pub fn record_check(&mut self) -> Result<()> {
+        self.file.checked_at = unix_now();
+        let _lock = self.storage.lock(&self.name)?;
+        self.storage
+            .write(&self.name, &UPDATE_CHECK_SCHEMA.to_string(&self.file)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> SemVer {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn finds_latest_and_stack_minimum() {
+        assert!(v("v1.2.10") > v("1.2.9"));
+        assert!(v("1.3.0") > v("1.3.0-rc.1"));
+        assert_eq!(v("1.3").to_string(), "1.3.0");
+
+        let feed = r#"[
+            {"tag_name": "v0.5.0-rc.1", "html_url": "", "body": null, "prerelease": true},
+            {"tag_name": "v0.4.0", "html_url": "https://example.com/v0.4.0",
+             "body": "Fixes.\n\n- Required from stack: `2.0.0`"},
+            {"tag_name": "v0.3.0", "html_url": "", "body": "Required from stack: 1.5.0"},
+            {"tag_name": "v0.2.0", "html_url": "", "body": "Initial"}
+        ]"#;
+        let releases = parse_releases(feed).unwrap();
+        assert_eq!(releases.len(), 3);
+        assert_eq!(releases[0].required_from_stack, Some(v("2.0.0")));
+
+        let status = evaluate(v("0.2.0"), &releases, Some(v("1.6.0")));
+        assert_eq!(status.latest.as_ref().unwrap().tag, "v0.4.0");
+        assert_eq!(status.minimum_for_stack.as_ref().unwrap().tag, "v0.3.0");
+        assert!(status.update_available() && status.unsupported());
+        assert!(status.message().contains("--tag v0.4.0"));
+
+        let status = evaluate(v("0.3.0"), &releases, Some(v("1.6.0")));
+        assert!(status.update_available() && !status.unsupported());
+
+        let status = evaluate(v("0.4.0"), &releases, None);
+        assert!(!status.update_available());
+        assert!(status.message().contains("up to date"));
+    }
+
+    #[test]
+    fn check_is_due_once_per_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(UPDATE_CHECK_FILE);
+        let mut state = CheckState::open(&path).unwrap();
+        assert!(state.check_due());
+        state.record_check().unwrap();
+        assert!(!CheckState::open(&path).unwrap().check_due());
+    }
+}
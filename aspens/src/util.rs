@@ -0,0 +1,595 @@
+//! Small parsing helpers for user-typed values: addresses, durations,
+//! amounts and basis points, plus the on-disk profile directory and the
+//! CSV quoting every export shares.
+//!
+//! The CLI binaries wrap these as clap value parsers, so a flag accepts the
+//! same syntax in every binary and a bot reading its own config gets the
+//! same validation.
+
+use alloy_primitives::Address;
+use eyre::{Result, eyre};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::decimals::parse_decimal_amount;
+
+/// Parse an EVM address, enforcing EIP-55 when the input is mixed-case.
+///
+/// All-lowercase and all-uppercase hex carry no checksum and are accepted
+/// as-is; mixed-case input must match its EIP-55 checksum, since a mismatch
+/// almost always means a mistyped character. The `0x` prefix is optional.
+/// The returned [`Address`] displays in checksummed form.
+pub fn parse_address(s: &str) -> Result<Address> {
+    let trimmed = s.trim();
+    let hex = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+
+    if hex.len() != 40 {
+        return Err(eyre!(
+            "invalid EVM address '{}': expected 40 hex characters after 0x, got {}",
+            s,
+            hex.len()
+        ));
+    }
+    if let Some(bad) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(eyre!(
+            "invalid EVM address '{}': '{}' is not a hex character",
+            s,
+            bad
+        ));
+    }
+
+    let address: Address = hex
+        .parse()
+        .map_err(|e| eyre!("invalid EVM address '{}': {}", s, e))?;
+
+    let has_lower = hex.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper && address.to_checksum(None)[2..] != *hex {
+        return Err(eyre!(
+            "invalid EVM address '{}': EIP-55 checksum mismatch, which usually means \
+             a mistyped character\n\n\
+             Hints:\n\
+             - Copy the address again from its source\n\
+             - Pass it all-lowercase to skip checksum validation",
+            s
+        ));
+    }
+
+    Ok(address)
+}
+
+/// Normalize an address whose chain architecture isn't known at the call
+/// site.
+///
+/// `0x`-prefixed input is validated with [`parse_address`] and returned in
+/// EIP-55 form. Anything else must look like a Solana base58 public key
+/// (32–44 base58 characters) and is returned trimmed but otherwise
+/// unchanged.
+pub fn normalize_address(s: &str) -> Result<String> {
+    let trimmed = s.trim();
+    if trimmed.starts_with("0x") || trimmed.starts_with("0X") {
+        return Ok(parse_address(trimmed)?.to_checksum(None));
+    }
+
+    // Base58 alphabet: alphanumerics minus 0, O, I and l.
+    let is_base58 = |c: char| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l');
+    if (32..=44).contains(&trimmed.len()) && trimmed.chars().all(is_base58) {
+        return Ok(trimmed.to_string());
+    }
+
+    Err(eyre!(
+        "invalid address '{}': expected a 0x-prefixed EVM address or a base58 Solana address",
+        s
+    ))
+}
+
+/// Parse a human-readable duration such as `500ms`, `30s`, `5m`, `24h`,
+/// `7d` or a compound like `1h30m`.
+///
+/// A bare integer is read as seconds, so existing `--timeout 30` style flags
+/// keep working. `0` is a valid (zero) duration.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let input = s.trim();
+    if input.is_empty() {
+        return Err(eyre!("duration is empty"));
+    }
+    if let Ok(secs) = input.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total_ms: u64 = 0;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits_end == 0 {
+            return Err(eyre!(
+                "invalid duration '{}': expected a number before '{}'",
+                s,
+                rest
+            ));
+        }
+        let value: u64 = rest[..digits_end]
+            .parse()
+            .map_err(|_| eyre!("invalid duration '{}': number too large", s))?;
+        rest = &rest[digits_end..];
+
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_ms: u64 = match rest[..unit_end].trim() {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            "w" => 604_800_000,
+            "" => {
+                return Err(eyre!(
+                    "invalid duration '{}': missing unit after {} (use ms, s, m, h, d or w)",
+                    s,
+                    value
+                ));
+            }
+            unit => {
+                return Err(eyre!(
+                    "invalid duration '{}': unknown unit '{}' (use ms, s, m, h, d or w)",
+                    s,
+                    unit
+                ));
+            }
+        };
+        rest = &rest[unit_end..];
+
+        total_ms = value
+            .checked_mul(unit_ms)
+            .and_then(|ms| total_ms.checked_add(ms))
+            .ok_or_else(|| eyre!("invalid duration '{}': too large", s))?;
+    }
+
+    Ok(Duration::from_millis(total_ms))
+}
+
+/// Parse a point in time as unix milliseconds: a bare integer is a unix
+/// timestamp in seconds, anything else a [duration](parse_duration) before
+/// `now_ms` (`1h` is an hour ago).
+pub fn parse_since(s: &str, now_ms: u64) -> Result<u64> {
+    let input = s.trim();
+    if let Ok(secs) = input.parse::<u64>() {
+        return secs
+            .checked_mul(1_000)
+            .ok_or_else(|| eyre!("invalid timestamp '{}': too large", s));
+    }
+    let ago = parse_duration(input)?;
+    Ok(now_ms.saturating_sub(ago.as_millis() as u64))
+}
+
+/// Remove `_` digit-group separators (`1_000.5` → `1000.5`).
+///
+/// Each `_` must sit between two digits, so typos like `1__000` or `_1`
+/// are still rejected.
+fn strip_digit_separators(s: &str) -> Result<String> {
+    let trimmed = s.trim();
+    let chars: Vec<char> = trimmed.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '_' {
+            let between_digits = i > 0
+                && chars[i - 1].is_ascii_digit()
+                && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit());
+            if !between_digits {
+                return Err(eyre!(
+                    "invalid amount '{}': '_' must separate two digits",
+                    s
+                ));
+            }
+        }
+    }
+    Ok(trimmed.replace('_', ""))
+}
+
+/// Parse a human-readable amount into base units.
+///
+/// Same as [`parse_decimal_amount`], but also accepts `_` digit-group
+/// separators (`1_000.5`).
+pub fn parse_amount(s: &str, decimals: u32) -> Result<u128> {
+    parse_decimal_amount(&strip_digit_separators(s)?, decimals)
+}
+
+/// The amount argument that stands for "everything": the whole wallet
+/// balance for a deposit, the whole available trade balance for a
+/// withdraw, an unlimited approval.
+pub const MAX_AMOUNT: &str = "max";
+
+/// Validate a human-readable amount whose token decimals aren't known yet
+/// and return it in the plain form [`parse_decimal_amount`] accepts
+/// (separators removed).
+pub fn normalize_amount(s: &str) -> Result<String> {
+    let clean = strip_digit_separators(s)?;
+    let fraction_digits = clean.split_once('.').map_or(0, |(_, f)| f.len());
+    parse_decimal_amount(&clean, fraction_digits as u32)?;
+    Ok(clean)
+}
+
+/// Parse basis points from `50`, `50bps` / `50bp`, or a percentage like
+/// `0.5%`.
+///
+/// A percentage must resolve to a whole number of basis points
+/// (`0.005%` is rejected rather than rounded).
+pub fn parse_bps(s: &str) -> Result<u32> {
+    let input = s.trim();
+    let bps: u128 = if let Some(pct) = input.strip_suffix('%') {
+        let pct = pct.trim();
+        if let Some((_, fraction)) = pct.split_once('.')
+            && fraction.trim_end_matches('0').len() > 2
+        {
+            return Err(eyre!(
+                "invalid basis points '{}': {}% is finer than 1 bp (0.01%)",
+                s,
+                pct
+            ));
+        }
+        parse_decimal_amount(pct, 2).map_err(|e| eyre!("invalid basis points '{}': {}", s, e))?
+    } else {
+        let number = input
+            .strip_suffix("bps")
+            .or_else(|| input.strip_suffix("bp"))
+            .unwrap_or(input)
+            .trim();
+        number.parse().map_err(|_| {
+            eyre!(
+                "invalid basis points '{}': expected a whole number (e.g. 50, 50bps) or a \
+                 percentage (e.g. 0.5%)",
+                s
+            )
+        })?
+    };
+    u32::try_from(bps).map_err(|_| eyre!("invalid basis points '{}': too large", s))
+}
+
+/// Parse a signed basis-point offset: [`parse_bps`] syntax with an optional
+/// leading `-` or `+` (`-0.1%`, `+25bps`).
+pub fn parse_signed_bps(s: &str) -> Result<i32> {
+    let input = s.trim();
+    let (negative, magnitude) = match input.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let bps = i32::try_from(parse_bps(magnitude)?)
+        .map_err(|_| eyre!("invalid basis points '{}': too large", s))?;
+    Ok(if negative { -bps } else { bps })
+}
+
+/// Environment variable overriding [`profile_dir`].
+pub const PROFILE_DIR_ENV: &str = "ASPENS_HOME";
+
+/// Directory for SDK state that outlives a process (scheduled jobs and the
+/// like): `$ASPENS_HOME` if set; otherwise `~/.aspens` if it exists, so
+/// existing installs keep their files; otherwise `$XDG_DATA_HOME/aspens`
+/// (`~/.local/share/aspens` by default).
+///
+/// The directory isn't created here; callers create it when they first
+/// write. [`crate::storage::FsStorage::open_default`] adds profile scoping
+/// on top.
+pub fn profile_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(PROFILE_DIR_ENV).filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    let home = home_dir()?;
+    let legacy = home.join(".aspens");
+    if legacy.is_dir() {
+        return Ok(legacy);
+    }
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return Ok(PathBuf::from(dir).join("aspens"));
+    }
+    Ok(home.join(".local").join("share").join("aspens"))
+}
+
+/// The user's home directory.
+pub(crate) fn home_dir() -> Result<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            eyre!(
+                "can't locate a home directory for the aspens profile\n\n\
+                 Hints:\n\
+                 - Set {} to the directory to use",
+                PROFILE_DIR_ENV
+            )
+        })
+}
+
+/// Read a file under the profile directory; `None` if it doesn't exist yet.
+pub(crate) fn read_profile_file(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(eyre!("failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Replace a file under the profile directory, creating the directory if
+/// needed. Written to a temp file and renamed so a concurrent reader never
+/// sees a torn file.
+pub(crate) fn write_profile_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| eyre!("failed to create {}: {}", dir.display(), e))?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, contents)
+        .map_err(|e| eyre!("failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| eyre!("failed to write {}: {}", path.display(), e))
+}
+
+/// Current time in unix seconds.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Current time in unix milliseconds.
+pub fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Convert days since the unix epoch to a proleptic Gregorian
+/// `(year, month, day)`, after Howard Hinnant's `civil_from_days`.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+/// Format unix seconds as `YYYY-MM-DD HH:MM:SS`.
+pub(crate) fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        (rem / 60) % 60,
+        rem % 60
+    )
+}
+
+/// `value` as a CSV field, quoted when it holds a comma, quote or line
+/// break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// `fields` as one CSV record, each passed through [`csv_field`], without
+/// the trailing newline.
+pub fn csv_row<I>(fields: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    fields
+        .into_iter()
+        .map(|field| csv_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Anvil test account #0.
+    const CHECKSUMMED: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    #[test]
+    fn accepts_checksummed_lowercase_and_uppercase() {
+        let expected = parse_address(CHECKSUMMED).unwrap();
+        assert_eq!(
+            parse_address(&CHECKSUMMED.to_lowercase()).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_address(&format!("0x{}", CHECKSUMMED[2..].to_uppercase())).unwrap(),
+            expected
+        );
+        assert_eq!(parse_address(&CHECKSUMMED[2..]).unwrap(), expected);
+        assert_eq!(expected.to_string(), CHECKSUMMED);
+    }
+
+    #[test]
+    fn rejects_bad_checksum() {
+        // Flip the case of one letter.
+        let typo = CHECKSUMMED.replacen("Fd6", "fd6", 1);
+        let err = parse_address(&typo).unwrap_err().to_string();
+        assert!(err.contains("checksum mismatch"), "{err}");
+    }
+
+    #[test]
+    fn rejects_wrong_length_and_non_hex() {
+        let err = parse_address("0x1234").unwrap_err().to_string();
+        assert!(err.contains("got 4"), "{err}");
+        let err = parse_address(&format!("0x{}", "g".repeat(40)))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'g'"), "{err}");
+    }
+
+    #[test]
+    fn normalize_checksums_evm_and_passes_solana_through() {
+        assert_eq!(
+            normalize_address(&CHECKSUMMED.to_lowercase()).unwrap(),
+            CHECKSUMMED
+        );
+        let sol = "So11111111111111111111111111111111111111112";
+        assert_eq!(normalize_address(sol).unwrap(), sol);
+        assert!(normalize_address("not-an-address").is_err());
+    }
+
+    #[test]
+    fn parses_durations_with_units() {
+        assert_eq!(parse_duration("30").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("0").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("24h").unwrap(), Duration::from_secs(86_400));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5_400));
+        assert_eq!(
+            parse_duration(" 2d ").unwrap(),
+            Duration::from_secs(172_800)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for bad in ["", "m", "5x", "1.5h", "-1s", "5m3"] {
+            assert!(parse_duration(bad).is_err(), "accepted {bad:?}");
+        }
+        let err = parse_duration("10y").unwrap_err().to_string();
+        assert!(err.contains("unknown unit 'y'"), "{err}");
+    }
+
+    #[test]
+    fn parses_since_as_timestamp_or_ago() {
+        let now_ms = 1_700_000_000_000;
+        assert_eq!(
+            parse_since("1699990000", now_ms).unwrap(),
+            1_699_990_000_000
+        );
+        assert_eq!(parse_since("1h", now_ms).unwrap(), now_ms - 3_600_000);
+        assert!(parse_since("yesterday", now_ms).is_err());
+    }
+
+    #[test]
+    fn amounts_accept_digit_separators() {
+        assert_eq!(parse_amount("1_000.5", 6).unwrap(), 1_000_500_000);
+        assert_eq!(parse_amount("1000.5", 6).unwrap(), 1_000_500_000);
+        assert_eq!(normalize_amount(" 1_000_000 ").unwrap(), "1000000");
+        for bad in ["_1", "1_", "1__0", "1_.5", "abc", "1.2.3"] {
+            assert!(normalize_amount(bad).is_err(), "accepted {bad:?}");
+        }
+    }
+
+    #[test]
+    fn parses_bps_in_every_notation() {
+        assert_eq!(parse_bps("50").unwrap(), 50);
+        assert_eq!(parse_bps("50bps").unwrap(), 50);
+        assert_eq!(parse_bps("50 bp").unwrap(), 50);
+        assert_eq!(parse_bps("0.5%").unwrap(), 50);
+        assert_eq!(parse_bps("1.25%").unwrap(), 125);
+        assert_eq!(parse_bps("100%").unwrap(), 10_000);
+        assert_eq!(parse_bps("0.50%").unwrap(), 50);
+    }
+
+    #[test]
+    fn parses_signed_bps() {
+        assert_eq!(parse_signed_bps("-0.1%").unwrap(), -10);
+        assert_eq!(parse_signed_bps("+25bps").unwrap(), 25);
+        assert_eq!(parse_signed_bps("5").unwrap(), 5);
+        assert!(parse_signed_bps("--5").is_err());
+    }
+
+    #[test]
+    fn civil_from_days_handles_leap_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn format_utc_renders_calendar_dates() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13:20");
+    }
+
+    #[test]
+    fn rejects_sub_bp_and_malformed_bps() {
+        let err = parse_bps("0.005%").unwrap_err().to_string();
+        assert!(err.contains("finer than 1 bp"), "{err}");
+        assert!(parse_bps("12.5").is_err());
+        assert!(parse_bps("-5").is_err());
+        assert!(parse_bps("lots").is_err());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn parse_address_accepts_every_unchecksummed_form(bytes: [u8; 20]) {
+            let address = Address::from(bytes);
+            let checksummed = address.to_checksum(None);
+            let hex = &checksummed[2..];
+            for form in [
+                checksummed.clone(),
+                checksummed.to_lowercase(),
+                format!("0x{}", hex.to_uppercase()),
+                hex.to_string(),
+                format!("  {}  ", checksummed),
+            ] {
+                proptest::prop_assert_eq!(parse_address(&form).unwrap(), address, "{}", form);
+            }
+        }
+
+        #[test]
+        fn parse_address_rejects_one_flipped_case(bytes: [u8; 20], pick: usize) {
+            let checksummed = Address::from(bytes).to_checksum(None);
+            let letters: Vec<usize> = checksummed
+                .char_indices()
+                .skip(2)
+                .filter(|(_, c)| c.is_ascii_alphabetic())
+                .map(|(i, _)| i)
+                .collect();
+            proptest::prop_assume!(!letters.is_empty());
+            let mut flipped = checksummed.into_bytes();
+            let i = letters[pick % letters.len()];
+            flipped[i] ^= 0x20;
+            let flipped = String::from_utf8(flipped).unwrap();
+            let hex = &flipped[2..];
+            // Still mixed-case, so the checksum applies and can't match.
+            let mixed = hex.chars().any(|c| c.is_ascii_lowercase())
+                && hex.chars().any(|c| c.is_ascii_uppercase());
+            proptest::prop_assume!(mixed);
+            proptest::prop_assert!(parse_address(&flipped).is_err());
+        }
+
+        #[test]
+        fn parse_address_never_panics(s in "\\PC{0,48}") {
+            let _ = parse_address(&s);
+        }
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+        assert_eq!(csv_field("two\r\nlines"), "\"two\r\nlines\"");
+        assert_eq!(csv_row(["1::0xa", "x,y", ""]), "1::0xa,\"x,y\",");
+    }
+}
@@ -0,0 +1,272 @@
+//! Minisign signature verification for release artifacts and configs.
+//!
+//! Releases publish a `SHA256SUMS` file signed with minisign
+//! (`SHA256SUMS.minisig`); `install.sh` and `aspens-cli verify-signature`
+//! check it before trusting the checksums of the binaries. Configs handed
+//! out out-of-band can be signed the same way: when `ASPENS_CONFIG_PUBKEY`
+//! is set, [`GetConfigResponse::from_file`] refuses a config file without a
+//! valid `<file>.minisig` next to it.
+//!
+//! Both signature algorithms minisign writes are accepted: `Ed` signs the
+//! file itself, and the default `ED` signs its BLAKE2b-512 hash. The trusted
+//! comment is covered by a second, global signature and is returned on
+//! success.
+//!
+//! [`GetConfigResponse::from_file`]: crate::commands::config::config_pb::GetConfigResponse::from_file
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use eyre::{Result, eyre};
+
+/// Environment variable holding the minisign public key that signs release
+/// artifacts.
+pub const RELEASE_PUBKEY_ENV: &str = "ASPENS_RELEASE_PUBKEY";
+
+/// Environment variable holding the minisign public key that signs configs
+/// loaded from file. When set, unsigned config files are rejected.
+pub const CONFIG_PUBKEY_ENV: &str = "ASPENS_CONFIG_PUBKEY";
+
+/// Extension minisign gives signature files.
+pub const SIGNATURE_EXTENSION: &str = "minisig";
+
+const UNTRUSTED_PREFIX: &str = "untrusted comment:";
+const TRUSTED_PREFIX: &str = "trusted comment: ";
+
+/// A minisign public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+
+impl PublicKey {
+    /// Parse a public key: the base64 line alone (as passed to `minisign
+    /// -P`) or the contents of a `.pub` file with its comment line.
+    pub fn parse(text: &str) -> Result<Self> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with(UNTRUSTED_PREFIX))
+            .ok_or_else(|| eyre!("empty minisign public key"))?;
+        let bytes = decode(line, "public key")?;
+        if !bytes.starts_with(b"Ed") {
+            return Err(eyre!("unsupported minisign public key algorithm"));
+        }
+        if bytes.len() != 42 {
+            return Err(eyre!("minisign public key has the wrong length"));
+        }
+        let key: [u8; 32] = bytes[10..].try_into()?;
+        Ok(Self {
+            key_id: bytes[2..10].try_into()?,
+            key: VerifyingKey::from_bytes(&key)
+                .map_err(|e| eyre!("invalid minisign public key: {}", e))?,
+        })
+    }
+
+    /// Parse `value` as a key, or else read it as a path to a `.pub` file.
+    pub fn from_arg(value: &str) -> Result<Self> {
+        match Self::parse(value) {
+            Ok(key) => Ok(key),
+            Err(_) if Path::new(value).is_file() => Self::parse(
+                &std::fs::read_to_string(value)
+                    .map_err(|e| eyre!("Failed to read public key {}: {}", value, e))?,
+            ),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The key in environment variable `name`, if set.
+    pub fn from_env(name: &str) -> Result<Option<Self>> {
+        match std::env::var(name) {
+            Ok(v) if !v.trim().is_empty() => Self::from_arg(v.trim())
+                .map(Some)
+                .map_err(|e| eyre!("{}: {}", name, e)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The key ID, as minisign prints it.
+    pub fn key_id(&self) -> String {
+        self.key_id
+            .iter()
+            .rev()
+            .map(|b| format!("{:02X}", b))
+            .collect()
+    }
+}
+
+/// A parsed `.minisig` file.
+#[derive(Debug, Clone)]
+pub struct MinisignSignature {
+    prehashed: bool,
+    key_id: [u8; 8],
+    signature: Signature,
+    trusted_comment: String,
+    global_signature: Signature,
+}
+
+impl MinisignSignature {
+    /// Parse the contents of a `.minisig` file.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text.lines().map(|l| l.trim_end_matches('\r'));
+        let mut next = |what: &str| {
+            lines
+                .next()
+                .ok_or_else(|| eyre!("minisign signature is missing its {}", what))
+        };
+        if !next("comment")?.starts_with(UNTRUSTED_PREFIX) {
+            return Err(eyre!("not a minisign signature (no untrusted comment)"));
+        }
+        let bytes = decode(next("signature")?, "signature")?;
+        let trusted_comment = next("trusted comment")?
+            .strip_prefix(TRUSTED_PREFIX)
+            .ok_or_else(|| eyre!("minisign signature has no trusted comment"))?
+            .to_string();
+        let global = decode(next("global signature")?, "global signature")?;
+
+        let prehashed = match bytes.get(..2) {
+            Some(b"Ed") => false,
+            Some(b"ED") => true,
+            _ => return Err(eyre!("unsupported minisign signature algorithm")),
+        };
+        if bytes.len() != 74 {
+            return Err(eyre!("minisign signature has the wrong length"));
+        }
+        Ok(Self {
+            prehashed,
+            key_id: bytes[2..10].try_into()?,
+            signature: Signature::from_slice(&bytes[10..])?,
+            trusted_comment,
+            global_signature: Signature::from_slice(&global)
+                .map_err(|_| eyre!("minisign global signature has the wrong length"))?,
+        })
+    }
+
+    /// The trusted comment (signed, unlike the untrusted one).
+    pub fn trusted_comment(&self) -> &str {
+        &self.trusted_comment
+    }
+
+    /// Check the signature over `data` with `key`, and the global signature
+    /// over the trusted comment.
+    pub fn verify(&self, key: &PublicKey, data: &[u8]) -> Result<()> {
+        if self.key_id != key.key_id {
+            return Err(eyre!(
+                "signed by a different key than {} (key ID mismatch)",
+                key.key_id()
+            ));
+        }
+        let checked = if self.prehashed {
+            key.key.verify(&Blake2b512::digest(data), &self.signature)
+        } else {
+            key.key.verify(data, &self.signature)
+        };
+        checked.map_err(|_| eyre!("signature verification failed: the data was modified"))?;
+
+        let mut global = self.signature.to_bytes().to_vec();
+        global.extend_from_slice(self.trusted_comment.as_bytes());
+        key.key
+            .verify(&global, &self.global_signature)
+            .map_err(|_| eyre!("trusted comment verification failed"))
+    }
+}
+
+/// Path of the signature minisign writes for `path`: `<path>.minisig`.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(SIGNATURE_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Verify `data` against the signature file at `signature`. Returns the
+/// trusted comment.
+pub fn verify_bytes(data: &[u8], signature: &Path, key: &PublicKey) -> Result<String> {
+    let text = std::fs::read_to_string(signature)
+        .map_err(|e| eyre!("Failed to read signature {}: {}", signature.display(), e))?;
+    let sig =
+        MinisignSignature::parse(&text).map_err(|e| eyre!("{}: {}", signature.display(), e))?;
+    sig.verify(key, data)?;
+    Ok(sig.trusted_comment)
+}
+
+/// Verify the file at `path` against `signature`, or `<path>.minisig` when
+/// `None`. Returns the trusted comment.
+pub fn verify_file(path: &Path, signature: Option<&Path>, key: &PublicKey) -> Result<String> {
+    let data =
+        std::fs::read(path).map_err(|e| eyre!("Failed to read {}: {}", path.display(), e))?;
+    let signature = signature.map_or_else(|| signature_path(path), Path::to_path_buf);
+    verify_bytes(&data, &signature, key).map_err(|e| eyre!("{}: {}", path.display(), e))
+}
+
+fn decode(line: &str, what: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(line.trim())
+        .map_err(|e| eyre!("invalid minisign {} encoding: {}", what, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Write what `minisign -S [-H]` would for `data`.
+    fn sign(sk: &SigningKey, key_id: [u8; 8], data: &[u8], prehashed: bool) -> String {
+        let signature = if prehashed {
+            sk.sign(&Blake2b512::digest(data))
+        } else {
+            sk.sign(data)
+        };
+        let mut bytes = if prehashed {
+            b"ED".to_vec()
+        } else {
+            b"Ed".to_vec()
+        };
+        bytes.extend_from_slice(&key_id);
+        bytes.extend_from_slice(&signature.to_bytes());
+        let comment = "timestamp:1700000000\tfile:SHA256SUMS";
+        let mut global = signature.to_bytes().to_vec();
+        global.extend_from_slice(comment.as_bytes());
+        format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+            STANDARD.encode(bytes),
+            comment,
+            STANDARD.encode(sk.sign(&global).to_bytes())
+        )
+    }
+
+    #[test]
+    fn verifies_both_algorithms_and_rejects_tampering() {
+        let sk = SigningKey::from_bytes(&[7u8; 32]);
+        let key_id = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut pk = b"Ed".to_vec();
+        pk.extend_from_slice(&key_id);
+        pk.extend_from_slice(sk.verifying_key().as_bytes());
+        let key = PublicKey::parse(&format!(
+            "untrusted comment: minisign public key\n{}\n",
+            STANDARD.encode(pk)
+        ))
+        .unwrap();
+        assert_eq!(key.key_id(), "0807060504030201");
+
+        let data = b"abc123  aspens-cli-linux-x86_64.tar.gz\n";
+        for prehashed in [false, true] {
+            let sig = MinisignSignature::parse(&sign(&sk, key_id, data, prehashed)).unwrap();
+            sig.verify(&key, data).unwrap();
+            assert!(sig.trusted_comment().contains("file:SHA256SUMS"));
+            assert!(sig.verify(&key, b"tampered").is_err());
+        }
+
+        let other = SigningKey::from_bytes(&[9u8; 32]);
+        let sig = MinisignSignature::parse(&sign(&other, key_id, data, true)).unwrap();
+        assert!(sig.verify(&key, data).is_err());
+
+        let forged = sign(&sk, key_id, data, true).replace("file:SHA256SUMS", "file:other");
+        let sig = MinisignSignature::parse(&forged).unwrap();
+        assert!(sig.verify(&key, data).is_err());
+    }
+}
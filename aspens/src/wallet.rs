@@ -3,11 +3,27 @@
 //! Wraps EVM (secp256k1, via Alloy) and Solana (Ed25519, via solana-sdk)
 //! keys behind a single interface so call sites don't need to branch on
 //! curve type. Solana support is gated behind the `solana` feature.
+//!
+//! With the `keystore` feature an EVM key can also come from an encrypted
+//! Web3 Secret Storage file instead of a plaintext environment variable:
+//! the loaders below unlock `TRADER_KEYSTORE` / `ADMIN_KEYSTORE` when
+//! they're set. With the `mnemonic` feature it can be derived from a BIP-39
+//! phrase instead ([`Wallet::from_mnemonic`]): `TRADER_MNEMONIC` plus
+//! `TRADER_ACCOUNT_INDEX` picks one of many trading accounts from one
+//! backup. With the `ledger` feature it can stay on a Ledger device
+//! instead ([`Wallet::Ledger`]), and with `remote-signer` in a signing
+//! service such as AWS KMS ([`Wallet::Remote`]).
 
 use alloy_primitives::B256;
 use alloy_signer::Signer;
 use alloy_signer_local::PrivateKeySigner;
 use eyre::{Result, eyre};
+#[cfg(feature = "keystore")]
+use std::collections::HashMap;
+#[cfg(feature = "keystore")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "keystore")]
+use std::sync::Mutex;
 
 #[cfg(feature = "solana")]
 use solana_sdk::signature::{Keypair, Signer as SolanaSigner};
@@ -22,6 +38,47 @@ pub enum CurveType {
     Ed25519,
 }
 
+/// Environment variable holding the passphrase of encrypted keystore files,
+/// for non-interactive use. When it's unset, [`Wallet::unlock_keystore`]
+/// prompts on the terminal.
+#[cfg(feature = "keystore")]
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "ASPENS_KEYSTORE_PASSPHRASE";
+
+/// Keys [`Wallet::unlock_keystore`] decrypted since [`remember_keystores`],
+/// by canonical path; `None` while keys aren't kept.
+#[cfg(feature = "keystore")]
+static REMEMBERED: Mutex<Option<HashMap<PathBuf, PrivateKeySigner>>> = Mutex::new(None);
+
+#[cfg(feature = "keystore")]
+fn remembered() -> std::sync::MutexGuard<'static, Option<HashMap<PathBuf, PrivateKeySigner>>> {
+    REMEMBERED.lock().expect("keystore cache lock poisoned")
+}
+
+/// Have [`Wallet::unlock_keystore`] keep the keys it decrypts, so each file
+/// is prompted for and derived once per process. Off by default, for
+/// binaries that load their wallet more than once per command; the keys
+/// stay in memory until [`forget_keystores`].
+#[cfg(feature = "keystore")]
+pub fn remember_keystores() {
+    remembered().get_or_insert_with(HashMap::new);
+}
+
+/// Drop the keys kept since [`remember_keystores`] and stop keeping new
+/// ones.
+#[cfg(feature = "keystore")]
+pub fn forget_keystores() {
+    *remembered() = None;
+}
+
+/// Environment variable holding the trader's BIP-39 mnemonic phrase.
+#[cfg(feature = "mnemonic")]
+pub const TRADER_MNEMONIC_ENV: &str = "TRADER_MNEMONIC";
+
+/// Environment variable selecting the account derived from
+/// [`TRADER_MNEMONIC_ENV`]; 0 when unset.
+#[cfg(feature = "mnemonic")]
+pub const TRADER_ACCOUNT_INDEX_ENV: &str = "TRADER_ACCOUNT_INDEX";
+
 /// A wallet that can sign messages on EVM (always) or Solana (with `solana`
 /// feature) chains.
 pub enum Wallet {
@@ -30,6 +87,22 @@ pub enum Wallet {
     /// Solana wallet backed by a `solana-sdk` `Keypair` (Ed25519).
     #[cfg(feature = "solana")]
     Solana(Box<Keypair>),
+    /// EVM wallet on a Ledger device (secp256k1). Signs messages and, for
+    /// admin login, EIP-712 typed data; not raw digests or transactions.
+    /// Shared, since one device connection serves every wallet lookup.
+    #[cfg(feature = "ledger")]
+    Ledger(std::sync::Arc<crate::ledger::LedgerWallet>),
+    /// EVM wallet whose key is held by a signing service (secp256k1), e.g.
+    /// AWS KMS. Signs everything a local key does, one request each.
+    #[cfg(feature = "remote-signer")]
+    Remote(std::sync::Arc<dyn crate::remote_signer::RemoteSigner>),
+}
+
+#[cfg(feature = "ledger")]
+impl From<crate::ledger::LedgerWallet> for Wallet {
+    fn from(ledger: crate::ledger::LedgerWallet) -> Self {
+        Wallet::Ledger(std::sync::Arc::new(ledger))
+    }
 }
 
 impl Wallet {
@@ -41,6 +114,43 @@ impl Wallet {
         Ok(Wallet::Evm(signer))
     }
 
+    /// Decrypt an EVM wallet from a Web3 Secret Storage file (the JSON
+    /// keystore geth, `cast wallet import` and most wallets export).
+    #[cfg(feature = "keystore")]
+    pub fn from_keystore(path: impl AsRef<Path>, passphrase: &str) -> Result<Self> {
+        Ok(Wallet::Evm(decrypt_keystore(path.as_ref(), passphrase)?))
+    }
+
+    /// Decrypt a keystore file with the passphrase from
+    /// [`KEYSTORE_PASSPHRASE_ENV`], prompting for it if that's unset. After
+    /// [`remember_keystores`] a file already unlocked is reused instead of
+    /// prompting and running the key derivation again.
+    #[cfg(feature = "keystore")]
+    pub fn unlock_keystore(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        unlock_keystore_with(path, || match std::env::var(KEYSTORE_PASSPHRASE_ENV) {
+            Ok(passphrase) => Ok(passphrase),
+            Err(_) => rpassword::prompt_password(format!("Passphrase for {}: ", path.display()))
+                .map_err(|e| eyre!("cannot read the keystore passphrase: {}", e)),
+        })
+    }
+
+    /// Derive the EVM wallet of account `index` from a BIP-39 English
+    /// mnemonic, at the BIP-44 path `m/44'/60'/0'/0/{index}` (as MetaMask,
+    /// Foundry and Hardhat do).
+    #[cfg(feature = "mnemonic")]
+    pub fn from_mnemonic(phrase: &str, index: u32) -> Result<Self> {
+        use alloy_signer_local::{MnemonicBuilder, coins_bip39::English};
+
+        let signer = MnemonicBuilder::<English>::default()
+            .phrase(phrase.trim())
+            .index(index)
+            .map_err(|e| eyre!("invalid account index {}: {}", index, e))?
+            .build()
+            .map_err(|e| eyre!("invalid mnemonic: {}", e))?;
+        Ok(Wallet::Evm(signer))
+    }
+
     /// Load a Solana wallet from a base58-encoded keypair string
     /// (the standard `solana-keygen` output format).
     #[cfg(feature = "solana")]
@@ -82,6 +192,10 @@ impl Wallet {
             Wallet::Evm(_) => CurveType::Secp256k1,
             #[cfg(feature = "solana")]
             Wallet::Solana(_) => CurveType::Ed25519,
+            #[cfg(feature = "ledger")]
+            Wallet::Ledger(_) => CurveType::Secp256k1,
+            #[cfg(feature = "remote-signer")]
+            Wallet::Remote(_) => CurveType::Secp256k1,
         }
     }
 
@@ -93,6 +207,10 @@ impl Wallet {
             Wallet::Evm(s) => s.address().to_checksum(None),
             #[cfg(feature = "solana")]
             Wallet::Solana(kp) => kp.pubkey().to_string(),
+            #[cfg(feature = "ledger")]
+            Wallet::Ledger(ledger) => ledger.address().to_checksum(None),
+            #[cfg(feature = "remote-signer")]
+            Wallet::Remote(remote) => remote.address().to_checksum(None),
         }
     }
 
@@ -110,6 +228,17 @@ impl Wallet {
                 let sig = kp.sign_message(msg);
                 Ok(sig.as_ref().to_vec())
             }
+            #[cfg(feature = "ledger")]
+            Wallet::Ledger(ledger) => {
+                let sig = ledger.sign_message(msg).await?;
+                Ok(sig.as_bytes().to_vec())
+            }
+            #[cfg(feature = "remote-signer")]
+            Wallet::Remote(remote) => {
+                let hash = alloy_primitives::eip191_hash_message(msg);
+                let sig = remote.sign_hash(&hash).await?;
+                Ok(sig.as_bytes().to_vec())
+            }
         }
     }
 
@@ -124,15 +253,63 @@ impl Wallet {
             Wallet::Solana(_) => Err(eyre!(
                 "EIP-712 digest signing is not supported for Ed25519 wallets"
             )),
+            #[cfg(feature = "ledger")]
+            Wallet::Ledger(_) => Err(eyre!(
+                "a Ledger can't sign a raw EIP-712 digest; it signs typed data it can display"
+            )),
+            #[cfg(feature = "remote-signer")]
+            Wallet::Remote(remote) => {
+                let sig = remote.sign_hash(&digest).await?;
+                Ok(sig.as_bytes().to_vec())
+            }
         }
     }
 
-    /// Borrow as an EVM signer, if this is an EVM wallet.
+    /// Borrow as an EVM signer, if this is an EVM wallet with its key in
+    /// memory.
     pub fn as_evm(&self) -> Option<&PrivateKeySigner> {
         match self {
             Wallet::Evm(s) => Some(s),
             #[cfg(feature = "solana")]
             Wallet::Solana(_) => None,
+            #[cfg(feature = "ledger")]
+            Wallet::Ledger(_) => None,
+            #[cfg(feature = "remote-signer")]
+            Wallet::Remote(_) => None,
+        }
+    }
+
+    /// Borrow as a Ledger signer, if this is a Ledger wallet.
+    #[cfg(feature = "ledger")]
+    pub fn as_ledger(&self) -> Option<&crate::ledger::LedgerWallet> {
+        match self {
+            Wallet::Ledger(ledger) => Some(ledger),
+            _ => None,
+        }
+    }
+
+    /// Borrow as a remote signer, if this is a remote wallet.
+    #[cfg(feature = "remote-signer")]
+    pub fn as_remote(&self) -> Option<&dyn crate::remote_signer::RemoteSigner> {
+        match self {
+            Wallet::Remote(remote) => Some(remote.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// A transaction signer for deposits, withdrawals and deployments:
+    /// the local key or the remote signer. `None` for Solana and Ledger
+    /// wallets.
+    #[cfg(feature = "client")]
+    pub fn tx_signer(&self) -> Option<crate::signer::SharedTxSigner> {
+        match self {
+            Wallet::Evm(s) => Some(std::sync::Arc::new(s.clone())),
+            #[cfg(feature = "remote-signer")]
+            Wallet::Remote(remote) => Some(std::sync::Arc::new(
+                crate::remote_signer::RemoteTxSigner(remote.clone()),
+            )),
+            #[allow(unreachable_patterns)]
+            _ => None,
         }
     }
 
@@ -140,24 +317,95 @@ impl Wallet {
     #[cfg(feature = "solana")]
     pub fn as_solana(&self) -> Option<&Keypair> {
         match self {
-            Wallet::Evm(_) => None,
             Wallet::Solana(kp) => Some(kp),
+            _ => None,
         }
     }
 }
 
+#[cfg(feature = "keystore")]
+fn decrypt_keystore(path: &Path, passphrase: &str) -> Result<PrivateKeySigner> {
+    PrivateKeySigner::decrypt_keystore(path, passphrase)
+        .map_err(|e| eyre!("cannot unlock keystore {}: {}", path.display(), e))
+}
+
+/// The trader account derived from [`TRADER_MNEMONIC_ENV`] at
+/// [`TRADER_ACCOUNT_INDEX_ENV`], or `None` if no mnemonic is set.
+#[cfg(feature = "mnemonic")]
+fn load_mnemonic_wallet() -> Option<Result<Wallet>> {
+    let phrase = std::env::var(TRADER_MNEMONIC_ENV)
+        .ok()
+        .filter(|p| !p.trim().is_empty())?;
+    let index = match std::env::var(TRADER_ACCOUNT_INDEX_ENV) {
+        Ok(index) => match index.trim().parse::<u32>() {
+            Ok(index) => index,
+            Err(_) => {
+                return Some(Err(eyre!(
+                    "{} must be a non-negative integer, got '{}'",
+                    TRADER_ACCOUNT_INDEX_ENV,
+                    index
+                )));
+            }
+        },
+        Err(_) => 0,
+    };
+    Some(Wallet::from_mnemonic(&phrase, index))
+}
+
+/// [`Wallet::unlock_keystore`] with the passphrase from `passphrase`, asked
+/// only if the key isn't remembered.
+#[cfg(feature = "keystore")]
+fn unlock_keystore_with(
+    path: &Path,
+    passphrase: impl FnOnce() -> Result<String>,
+) -> Result<Wallet> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| eyre!("cannot read keystore {}: {}", path.display(), e))?;
+    if let Some(signer) = remembered().as_ref().and_then(|keys| keys.get(&canonical)) {
+        return Ok(Wallet::Evm(signer.clone()));
+    }
+
+    let signer = decrypt_keystore(&canonical, &passphrase()?)?;
+    if let Some(keys) = remembered().as_mut() {
+        keys.insert(canonical, signer.clone());
+    }
+    Ok(Wallet::Evm(signer))
+}
+
+/// Load the EVM wallet from `keystore_var`'s keystore file if it's set (and
+/// the `keystore` feature is on), then from the trader mnemonic if
+/// `mnemonic` is set (and the `mnemonic` feature is on), otherwise from
+/// `privkey_var`'s hex key.
+fn load_evm_wallet(privkey_var: &str, keystore_var: &str, mnemonic: bool) -> Result<Wallet> {
+    #[cfg(feature = "keystore")]
+    if let Some(path) = std::env::var_os(keystore_var).filter(|p| !p.is_empty()) {
+        return Wallet::unlock_keystore(path);
+    }
+    #[cfg(not(feature = "keystore"))]
+    let _ = keystore_var;
+    #[cfg(feature = "mnemonic")]
+    if mnemonic && let Some(wallet) = load_mnemonic_wallet() {
+        return wallet;
+    }
+    #[cfg(not(feature = "mnemonic"))]
+    let _ = mnemonic;
+    let key =
+        std::env::var(privkey_var).map_err(|_| eyre!("{} not set in environment", privkey_var))?;
+    Wallet::from_evm_hex(&key)
+}
+
 /// Load a trader wallet from environment variables based on the requested curve.
 ///
-/// - `Secp256k1`: reads `TRADER_PRIVKEY` (hex)
+/// - `Secp256k1`: unlocks the keystore file named by `TRADER_KEYSTORE` (with
+///   the `keystore` feature), or derives account `TRADER_ACCOUNT_INDEX` from
+///   `TRADER_MNEMONIC` (with the `mnemonic` feature), or reads
+///   `TRADER_PRIVKEY` (hex)
 /// - `Ed25519`: reads `TRADER_PRIVKEY_SOLANA` (base58 keypair) — requires the
 ///   `solana` feature
 pub fn load_trader_wallet(curve: CurveType) -> Result<Wallet> {
     match curve {
-        CurveType::Secp256k1 => {
-            let key = std::env::var("TRADER_PRIVKEY")
-                .map_err(|_| eyre!("TRADER_PRIVKEY not set in environment"))?;
-            Wallet::from_evm_hex(&key)
-        }
+        CurveType::Secp256k1 => load_evm_wallet("TRADER_PRIVKEY", "TRADER_KEYSTORE", true),
         CurveType::Ed25519 => {
             #[cfg(feature = "solana")]
             {
@@ -225,16 +473,13 @@ pub fn load_trader_wallet_for_network(
 
 /// Load an admin wallet from environment variables based on the requested curve.
 ///
-/// - `Secp256k1`: reads `ADMIN_PRIVKEY` (hex)
+/// - `Secp256k1`: unlocks the keystore file named by `ADMIN_KEYSTORE` (with
+///   the `keystore` feature), or reads `ADMIN_PRIVKEY` (hex)
 /// - `Ed25519`: reads `ADMIN_PRIVKEY_SOLANA` (base58 keypair) — requires the
 ///   `solana` feature
 pub fn load_admin_wallet(curve: CurveType) -> Result<Wallet> {
     match curve {
-        CurveType::Secp256k1 => {
-            let key = std::env::var("ADMIN_PRIVKEY")
-                .map_err(|_| eyre!("ADMIN_PRIVKEY not set in environment"))?;
-            Wallet::from_evm_hex(&key)
-        }
+        CurveType::Secp256k1 => load_evm_wallet("ADMIN_PRIVKEY", "ADMIN_KEYSTORE", false),
         CurveType::Ed25519 => {
             #[cfg(feature = "solana")]
             {
@@ -325,6 +570,65 @@ mod tests {
         assert!(Wallet::from_evm_hex("not-hex").is_err());
     }
 
+    #[cfg(feature = "keystore")]
+    #[test]
+    fn keystore_unlocks_to_the_same_key() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/keystore_anvil0.json"
+        );
+        let w = Wallet::from_keystore(path, "correct horse battery staple").unwrap();
+        assert_eq!(
+            w.address(),
+            Wallet::from_evm_hex(TEST_EVM_KEY).unwrap().address()
+        );
+        let err = Wallet::from_keystore(path, "wrong").err().unwrap();
+        assert!(err.to_string().contains("cannot unlock keystore"), "{err}");
+    }
+
+    #[cfg(feature = "keystore")]
+    #[test]
+    fn keystores_are_only_remembered_until_forgotten() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/keystore_anvil0.json"
+        ));
+        let right = || Ok("correct horse battery staple".to_string());
+        let wrong = || Ok("wrong".to_string());
+
+        // Off by default: every unlock decrypts again.
+        unlock_keystore_with(path, right).unwrap();
+        assert!(unlock_keystore_with(path, wrong).is_err());
+
+        remember_keystores();
+        unlock_keystore_with(path, right).unwrap();
+        let w = unlock_keystore_with(path, || panic!("asked for a remembered key")).unwrap();
+        assert_eq!(
+            w.address(),
+            Wallet::from_evm_hex(TEST_EVM_KEY).unwrap().address()
+        );
+
+        forget_keystores();
+        assert!(remembered().is_none());
+        assert!(unlock_keystore_with(path, wrong).is_err());
+    }
+
+    #[cfg(feature = "mnemonic")]
+    #[test]
+    fn mnemonic_derives_accounts_by_index() {
+        // Anvil's default mnemonic: account 0 is test key #0.
+        let phrase = "test test test test test test test test test test test junk";
+        assert_eq!(
+            Wallet::from_mnemonic(phrase, 0).unwrap().address(),
+            Wallet::from_evm_hex(TEST_EVM_KEY).unwrap().address()
+        );
+        assert_eq!(
+            Wallet::from_mnemonic(phrase, 1).unwrap().address(),
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+        );
+        assert!(Wallet::from_mnemonic("not a mnemonic", 0).is_err());
+    }
+
     #[cfg(not(feature = "solana"))]
     #[test]
     fn ed25519_load_errors_without_feature() {
@@ -10,6 +10,8 @@
 #   INSTALL_DIR=/path       install location
 #                           (default: /usr/local/bin on Linux, $HOME/.local/bin on macOS)
 #   ASPENS_VERSION=v0.6.0   install a specific release tag (default: latest)
+#   ASPENS_RELEASE_PUBKEY=… minisign public key; when set, SHA256SUMS must carry
+#                           a valid signature by it (needs `minisign`)
 
 set -e
 
@@ -80,6 +82,30 @@ verify_checksum() { # <file> <asset-name>
   fi
 }
 
+# When ASPENS_RELEASE_PUBKEY is set, require a valid minisign signature on
+# SHA256SUMS, so the checksums (and through them the binaries) are trusted.
+verify_signature() {
+  [ -n "${ASPENS_RELEASE_PUBKEY:-}" ] || return 0
+  if [ -z "$SHA256SUMS" ]; then
+    echo "Error: ASPENS_RELEASE_PUBKEY is set but the release has no SHA256SUMS"
+    exit 1
+  fi
+  if ! command -v minisign >/dev/null 2>&1; then
+    echo "Error: ASPENS_RELEASE_PUBKEY is set but minisign is not installed"
+    exit 1
+  fi
+  if ! curl -fsSL "https://github.com/${REPO}/releases/download/${VERSION}/SHA256SUMS.minisig" \
+       -o "${SHA256SUMS}.minisig"; then
+    echo "Error: failed to download SHA256SUMS.minisig"
+    exit 1
+  fi
+  if ! minisign -Vqm "$SHA256SUMS" -P "$ASPENS_RELEASE_PUBKEY"; then
+    echo "Error: SHA256SUMS signature verification failed"
+    exit 1
+  fi
+  echo "Verified SHA256SUMS signature"
+}
+
 install() {
   detect_platform
   resolve_version
@@ -95,6 +121,7 @@ install() {
        -o "${TMP_DIR}/SHA256SUMS" 2>/dev/null; then
     SHA256SUMS="${TMP_DIR}/SHA256SUMS"
   fi
+  verify_signature
 
   mkdir -p "$INSTALL_DIR"
 